@@ -1,6 +1,16 @@
+use std::{
+    pin::Pin,
+    thread,
+    time::{Duration, Instant},
+};
+
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use postage::mpsc;
-use postage::{sink::Sink, stream::Stream};
+use postage::{
+    sink::{PollSend, Sink},
+    stream::{Stream, TryRecvError},
+    Context,
+};
 
 #[derive(Clone, Debug)]
 struct Message;
@@ -38,5 +48,74 @@ pub fn recv_empty(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, send_recv, send_full, recv_empty);
+pub fn send_vectored(c: &mut Criterion) {
+    let (mut tx, mut rx) = mpsc::channel::<Message>(8);
+    let mut cx = Context::empty();
+
+    c.bench_function("mpsc::send_vectored", |b| {
+        b.iter(|| {
+            let batch = vec![Message {}, Message {}, Message {}, Message {}];
+            match Pin::new(&mut tx).poll_send_vectored(&mut cx, black_box(batch)) {
+                PollSend::Ready => {}
+                _ => panic!("the buffer has room for the batch"),
+            }
+
+            for _ in 0..4 {
+                rx.try_recv().unwrap();
+            }
+        });
+    });
+}
+
+const CONTENDED_SENDERS: u64 = 4;
+
+// measures the time for each of the senders to send one message, on separate threads
+pub fn send_contended(c: &mut Criterion) {
+    c.bench_function("mpsc::send_contended", |b| {
+        b.iter_custom(|iters| {
+            let (tx, mut rx) = mpsc::channel::<Message>(64);
+            let start = Instant::now();
+
+            let senders: Vec<_> = (0..CONTENDED_SENDERS)
+                .map(|_| {
+                    let mut tx = tx.clone();
+                    thread::spawn(move || {
+                        for _ in 0..iters {
+                            while tx.try_send(Message {}).is_err() {
+                                thread::yield_now();
+                            }
+                        }
+                    })
+                })
+                .collect();
+            drop(tx);
+
+            loop {
+                match rx.try_recv() {
+                    Ok(message) => drop(black_box(message)),
+                    Err(TryRecvError::Pending) => thread::yield_now(),
+                    Err(TryRecvError::Closed) => break,
+                }
+            }
+
+            for sender in senders {
+                sender.join().unwrap();
+            }
+
+            start.elapsed()
+        });
+    });
+}
+
+fn config() -> Criterion {
+    Criterion::default()
+        .warm_up_time(Duration::from_secs(1))
+        .measurement_time(Duration::from_secs(3))
+}
+
+criterion_group!(
+    name = benches;
+    config = config();
+    targets = send_recv, send_full, recv_empty, send_vectored, send_contended
+);
 criterion_main!(benches);
@@ -0,0 +1,308 @@
+//! Typed mailboxes for actors, built on mpsc channels.
+//!
+//! An actor owns a [Mailbox](./struct.Mailbox.html), and other tasks send it messages through a cloneable [Address](./struct.Address.html).
+//! Each mailbox has a priority control lane, for messages such as shutdown or reconfiguration requests.
+//! Control messages are received before any queued messages.
+//!
+//! [spawn_actor](./fn.spawn_actor.html) creates the mailbox, and spawns the actor's message loop.
+//!
+//! ```rust
+//! use postage::{actor, prelude::*, spawn::BoxFuture};
+//!
+//! enum Counter {
+//!     Add(usize),
+//!     Get(postage::oneshot::Sender<usize>),
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let spawn = |task: BoxFuture| {
+//!         tokio::spawn(task);
+//!     };
+//!
+//!     let mut address = actor::spawn_actor(&spawn, 16, |mut mailbox| async move {
+//!         let mut count = 0;
+//!         while let Some(message) = mailbox.recv().await {
+//!             match message {
+//!                 Counter::Add(n) => count += n,
+//!                 Counter::Get(mut reply) => {
+//!                     reply.send(count).await.ok();
+//!                 }
+//!             }
+//!         }
+//!     });
+//!
+//!     address.send(Counter::Add(2)).await.ok();
+//!
+//!     let (reply, mut count) = postage::oneshot::channel();
+//!     address.send(Counter::Get(reply)).await.ok();
+//!     assert_eq!(Some(2), count.recv().await);
+//! }
+//! ```
+
+use std::{fmt, future::Future, pin::Pin};
+
+use static_assertions::assert_impl_all;
+
+use crate::{
+    mpsc,
+    sink::{PollSend, Sink},
+    spawn::Spawn,
+    stream::{PollRecv, Stream},
+    Context,
+};
+
+/// Constructs an address and a mailbox, which buffers up to `capacity` messages, and `capacity` control messages
+pub fn mailbox<M>(capacity: usize) -> (Address<M>, Mailbox<M>) {
+    Builder::new(capacity).build()
+}
+
+/// Creates a mailbox with the given capacity, and spawns the actor with the spawner.
+///
+/// The actor is called with the mailbox, and returns the future which processes its messages.
+/// The future is run with the [spawner](../spawn/trait.Spawn.html).
+pub fn spawn_actor<M, Sp, A, F>(spawner: &Sp, capacity: usize, actor: A) -> Address<M>
+where
+    Sp: Spawn + ?Sized,
+    A: FnOnce(Mailbox<M>) -> F,
+    F: Future<Output = ()> + Send + 'static,
+{
+    let (address, mailbox) = mailbox(capacity);
+    spawner.spawn(Box::pin(actor(mailbox)));
+    address
+}
+
+/// Constructs a mailbox, with additional configuration.
+#[derive(Clone, Debug)]
+pub struct Builder {
+    capacity: usize,
+    control_capacity: usize,
+    name: Option<String>,
+}
+
+impl Builder {
+    /// Creates a builder for a mailbox which buffers up to `capacity` messages
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            control_capacity: capacity,
+            name: None,
+        }
+    }
+
+    /// Sets the capacity of the control lane.  Defaults to the capacity of the mailbox.
+    pub fn control_capacity(mut self, capacity: usize) -> Self {
+        self.control_capacity = capacity;
+        self
+    }
+
+    /// Names the mailbox's channels, which is used in metrics, traces, and the registry.
+    /// The control lane is named with a `.control` suffix.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Constructs the address and the mailbox
+    pub fn build<M>(self) -> (Address<M>, Mailbox<M>) {
+        let mut messages = mpsc::Builder::new(self.capacity);
+        let mut control = mpsc::Builder::new(self.control_capacity);
+
+        if let Some(name) = self.name {
+            control = control.name(format!("{}.control", name));
+            messages = messages.name(name);
+        }
+
+        let (messages_tx, messages_rx) = messages.build();
+        let (control_tx, control_rx) = control.build();
+
+        let address = Address {
+            messages: messages_tx,
+            control: control_tx,
+        };
+
+        let mailbox = Mailbox {
+            messages: messages_rx,
+            control: control_rx,
+        };
+
+        (address, mailbox)
+    }
+}
+
+/// A cloneable handle which sends messages to an actor's mailbox.  Can send messages with the postage::Sink trait.
+///
+/// Messages sent to the address are queued in order.  Messages sent with [control](#method.control) skip the queue.
+pub struct Address<M> {
+    messages: mpsc::Sender<M>,
+    control: mpsc::Sender<M>,
+}
+
+assert_impl_all!(Address<String>: Clone, Send, Sync, fmt::Debug);
+
+impl<M> Address<M> {
+    /// Returns a sink for the control lane.  Control messages are received before any queued messages.
+    pub fn control(&self) -> ControlAddress<M> {
+        ControlAddress {
+            control: self.control.clone(),
+        }
+    }
+}
+
+impl<M> Sink for Address<M> {
+    type Item = M;
+
+    fn poll_send(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        value: Self::Item,
+    ) -> PollSend<Self::Item> {
+        Pin::new(&mut self.get_mut().messages).poll_send(cx, value)
+    }
+}
+
+impl<M> Clone for Address<M> {
+    fn clone(&self) -> Self {
+        Self {
+            messages: self.messages.clone(),
+            control: self.control.clone(),
+        }
+    }
+}
+
+impl<M> fmt::Debug for Address<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Address").finish()
+    }
+}
+
+/// A sink for an actor's control lane, created by [Address::control](./struct.Address.html#method.control).
+pub struct ControlAddress<M> {
+    control: mpsc::Sender<M>,
+}
+
+assert_impl_all!(ControlAddress<String>: Clone, Send, Sync, fmt::Debug);
+
+impl<M> Sink for ControlAddress<M> {
+    type Item = M;
+
+    fn poll_send(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        value: Self::Item,
+    ) -> PollSend<Self::Item> {
+        Pin::new(&mut self.get_mut().control).poll_send(cx, value)
+    }
+}
+
+impl<M> Clone for ControlAddress<M> {
+    fn clone(&self) -> Self {
+        Self {
+            control: self.control.clone(),
+        }
+    }
+}
+
+impl<M> fmt::Debug for ControlAddress<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ControlAddress").finish()
+    }
+}
+
+/// An actor's mailbox.  Receives messages with the postage::Stream trait.
+///
+/// Control messages are received first, and then queued messages in the order they were sent.
+/// The mailbox closes when every address has been dropped, and all messages have been received.
+pub struct Mailbox<M> {
+    messages: mpsc::Receiver<M>,
+    control: mpsc::Receiver<M>,
+}
+
+assert_impl_all!(Mailbox<String>: Send, Sync, fmt::Debug);
+
+impl<M> Stream for Mailbox<M> {
+    type Item = M;
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item> {
+        let this = self.get_mut();
+
+        let control = Pin::new(&mut this.control).poll_recv(cx);
+        if let PollRecv::Ready(message) = control {
+            return PollRecv::Ready(message);
+        }
+
+        match Pin::new(&mut this.messages).poll_recv(cx) {
+            PollRecv::Ready(message) => PollRecv::Ready(message),
+            PollRecv::Closed if matches!(control, PollRecv::Closed) => PollRecv::Closed,
+            PollRecv::Closed | PollRecv::Pending => PollRecv::Pending,
+        }
+    }
+}
+
+impl<M> fmt::Debug for Mailbox<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mailbox").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{mailbox, spawn_actor, Builder, Mailbox};
+    use crate::{
+        oneshot,
+        sink::Sink,
+        spawn::BoxFuture,
+        stream::{Stream, TryRecvError},
+    };
+
+    #[test]
+    fn control_first() {
+        let (mut address, mut mailbox) = mailbox(4);
+        let mut control = address.control();
+
+        address.try_send("queued 1").unwrap();
+        address.try_send("queued 2").unwrap();
+        control.try_send("control").unwrap();
+
+        assert_eq!(Ok("control"), mailbox.try_recv());
+        assert_eq!(Ok("queued 1"), mailbox.try_recv());
+        assert_eq!(Ok("queued 2"), mailbox.try_recv());
+        assert_eq!(Err(TryRecvError::Pending), mailbox.try_recv());
+    }
+
+    #[test]
+    fn closes_after_addresses_drop() {
+        let (address, mut mailbox) = Builder::new(4).control_capacity(1).build::<usize>();
+        let mut control = address.control();
+        drop(address);
+
+        control.try_send(1).unwrap();
+        assert_eq!(Ok(1), mailbox.try_recv());
+        assert_eq!(Err(TryRecvError::Pending), mailbox.try_recv());
+
+        drop(control);
+        assert_eq!(Err(TryRecvError::Closed), mailbox.try_recv());
+    }
+
+    #[tokio::test]
+    async fn spawn() {
+        let spawn = |task: BoxFuture| {
+            tokio::spawn(task);
+        };
+
+        type Message = (usize, oneshot::Sender<usize>);
+        let mut address = spawn_actor(&spawn, 4, |mut mailbox: Mailbox<Message>| async move {
+            let mut total = 0;
+            while let Some((n, mut reply)) = mailbox.recv().await {
+                total += n;
+                reply.send(total).await.ok();
+            }
+        });
+
+        for (n, expected) in [(1usize, 1usize), (2, 3), (3, 6)] {
+            let (reply, mut total) = oneshot::channel();
+            address.send((n, reply)).await.unwrap();
+            assert_eq!(Some(expected), total.recv().await);
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! A trait for constructing channels, so code can be generic over the channel flavor which connects two components.
+//!
+//! Each flavor is a marker type, which implements [Channel](./trait.Channel.html) for the message types it supports.
+//!
+//! ```rust
+//! use postage::{channel::{self, Channel}, prelude::*};
+//!
+//! async fn run<C: Channel<usize>>() -> Option<usize> {
+//!     let (mut tx, mut rx) = C::channel(4);
+//!     tx.send(1).await.ok();
+//!     rx.recv().await
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     assert_eq!(Some(1), run::<channel::Mpsc>().await);
+//!     assert_eq!(Some(1), run::<channel::Broadcast>().await);
+//! }
+//! ```
+
+use crate::{broadcast, dispatch, mpsc, sink::Sink, stream::Stream, watch};
+
+/// Constructs a pair of channel endpoints, which carry messages of type `T`.
+pub trait Channel<T> {
+    /// The sender half of the channel
+    type Sender: Sink<Item = T> + Unpin;
+    /// The receiver half of the channel
+    type Receiver: Stream<Item = T> + Unpin;
+
+    /// Constructs a channel with the given capacity.  Flavors which don't buffer messages ignore the capacity.
+    fn channel(capacity: usize) -> (Self::Sender, Self::Receiver);
+}
+
+/// A [broadcast](../broadcast/index.html) channel
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Broadcast;
+
+impl<T: Clone> Channel<T> for Broadcast {
+    type Sender = broadcast::Sender<T>;
+    type Receiver = broadcast::Receiver<T>;
+
+    fn channel(capacity: usize) -> (Self::Sender, Self::Receiver) {
+        broadcast::channel(capacity)
+    }
+}
+
+/// A [dispatch](../dispatch/index.html) channel
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Dispatch;
+
+impl<T> Channel<T> for Dispatch {
+    type Sender = dispatch::Sender<T>;
+    type Receiver = dispatch::Receiver<T>;
+
+    fn channel(capacity: usize) -> (Self::Sender, Self::Receiver) {
+        dispatch::channel(capacity)
+    }
+}
+
+/// An [mpsc](../mpsc/index.html) channel
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Mpsc;
+
+impl<T> Channel<T> for Mpsc {
+    type Sender = mpsc::Sender<T>;
+    type Receiver = mpsc::Receiver<T>;
+
+    fn channel(capacity: usize) -> (Self::Sender, Self::Receiver) {
+        mpsc::channel(capacity)
+    }
+}
+
+/// A [watch](../watch/index.html) channel, which is created empty.  Receivers observe the latest message.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Watch;
+
+impl<T: Clone> Channel<T> for Watch {
+    type Sender = watch::Sender<T>;
+    type Receiver = watch::Receiver<T>;
+
+    fn channel(_capacity: usize) -> (Self::Sender, Self::Receiver) {
+        watch::channel_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Broadcast, Channel, Dispatch, Mpsc, Watch};
+    use crate::{
+        sink::Sink,
+        stream::{Stream, TryRecvError},
+    };
+
+    fn send_recv<C: Channel<usize>>() -> Vec<usize> {
+        let (mut tx, mut rx) = C::channel(4);
+        tx.try_send(1).unwrap();
+        tx.try_send(2).unwrap();
+        drop(tx);
+
+        let mut received = Vec::new();
+        loop {
+            match rx.try_recv() {
+                Ok(value) => received.push(value),
+                Err(TryRecvError::Closed) => return received,
+                Err(TryRecvError::Pending) => panic!("the channel should be closed"),
+            }
+        }
+    }
+
+    #[test]
+    fn queues() {
+        assert_eq!(vec![1, 2], send_recv::<Mpsc>());
+        assert_eq!(vec![1, 2], send_recv::<Dispatch>());
+        assert_eq!(vec![1, 2], send_recv::<Broadcast>());
+    }
+
+    #[test]
+    fn watch() {
+        assert_eq!(vec![2], send_recv::<Watch>());
+    }
+}
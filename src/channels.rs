@@ -1,8 +1,13 @@
+pub mod ack;
 pub mod barrier;
 pub mod broadcast;
+pub mod credit;
 pub mod dispatch;
+pub mod group;
 pub mod mpsc;
 pub mod oneshot;
+#[cfg(feature = "spill")]
+pub mod spill;
 pub mod watch;
 
 use std::{cell::Cell, marker::Sync};
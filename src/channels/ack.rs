@@ -0,0 +1,603 @@
+//! A multi-producer, multi-consumer queue with at-least-once delivery.
+//!
+//! Receivers produce a [Delivery](./struct.Delivery.html) guard for each message, which must be acknowledged with `ack()`.
+//! If a delivery is not acknowledged within the ack timeout (because the guard was dropped, the consumer panicked, or the consumer is stuck),
+//! the message is redelivered to the next receiver that polls.  A delivery can also be returned immediately with `nack()`.
+//!
+//! Unacknowledged messages count against the capacity of the channel, so a pool of stuck consumers applies backpressure to the senders.
+//! Each delivery holds a clone of the message, and the channel keeps the original until it is acknowledged.
+//!
+//! With the `time` or `time-async-io` feature, a waiting receiver is woken by the [time](../../time/index.html) timer when a delivery expires,
+//! and the `time` feature requires receivers to be polled within a tokio runtime.  Without a timer feature, expired deliveries are
+//! requeued the next time a receiver is polled, or woken by a send, an ack, or a nack.
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use postage::{ack, prelude::*};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let (mut tx, mut rx) = ack::channel(16, Duration::from_secs(30));
+//!     tx.send("job".to_string()).await.ok();
+//!
+//!     let delivery = rx.recv().await.unwrap();
+//!     assert_eq!("job", delivery.as_str());
+//!     assert_eq!(1, delivery.attempt());
+//!     delivery.ack();
+//! }
+//! ```
+
+use std::{
+    collections::{BTreeSet, HashMap, VecDeque},
+    fmt,
+    ops::Deref,
+    pin::Pin,
+    sync::Arc,
+    task::Poll,
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+use static_assertions::{assert_impl_all, assert_not_impl_all};
+
+#[cfg(feature = "timer")]
+use crate::time::timer::Timer;
+
+use super::SendMessage;
+use crate::{
+    sink::{PollSend, Sink},
+    stream::{PollRecv, Stream},
+    sync::notifier::Notifier,
+    Context,
+};
+
+/// Constructs a pair of endpoints, which holds up to `capacity` queued and unacknowledged messages.
+/// Deliveries which are not acknowledged within `ack_timeout` are redelivered.
+pub fn channel<T: Clone>(capacity: usize, ack_timeout: Duration) -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Shared {
+        capacity,
+        ack_timeout,
+        state: Mutex::new(State {
+            queue: VecDeque::new(),
+            in_flight: HashMap::new(),
+            deadlines: BTreeSet::new(),
+            next_id: 0,
+            senders: 1,
+            receivers: 1,
+        }),
+        notify_tx: Notifier::new(),
+        notify_rx: Notifier::new(),
+    });
+
+    let sender = Sender {
+        shared: shared.clone(),
+    };
+
+    let receiver = Receiver {
+        shared,
+        #[cfg(feature = "timer")]
+        timer: None,
+    };
+
+    (sender, receiver)
+}
+
+struct Shared<T> {
+    capacity: usize,
+    ack_timeout: Duration,
+    state: Mutex<State<T>>,
+    notify_tx: Notifier,
+    notify_rx: Notifier,
+}
+
+struct State<T> {
+    queue: VecDeque<Queued<T>>,
+    in_flight: HashMap<u64, Queued<T>>,
+    deadlines: BTreeSet<(Instant, u64)>,
+    next_id: u64,
+    senders: usize,
+    receivers: usize,
+}
+
+struct Queued<T> {
+    value: T,
+    attempts: usize,
+}
+
+impl<T> State<T> {
+    fn len(&self) -> usize {
+        self.queue.len() + self.in_flight.len()
+    }
+
+    /// Moves the deliveries whose deadline has passed to the front of the queue
+    fn requeue_expired(&mut self, now: Instant) {
+        while let Some(&(deadline, id)) = self.deadlines.iter().next() {
+            if deadline > now {
+                break;
+            }
+
+            self.deadlines.remove(&(deadline, id));
+            if let Some(queued) = self.in_flight.remove(&id) {
+                self.queue.push_front(queued);
+            }
+        }
+    }
+}
+
+/// The sender half of an ack channel.  Can send messages with the postage::Sink trait.
+///
+/// Can be cloned.
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+assert_impl_all!(Sender<String>: Clone, Send, Sync, fmt::Debug);
+
+impl<T> Sink for Sender<T> {
+    type Item = T;
+
+    fn poll_send(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        value: Self::Item,
+    ) -> PollSend<Self::Item> {
+        loop {
+            let guard = self.shared.notify_tx.guard();
+
+            {
+                let mut state = self.shared.state.lock();
+                if state.receivers == 0 {
+                    return PollSend::Rejected(value);
+                }
+
+                if state.len() < self.shared.capacity {
+                    state.queue.push_back(Queued { value, attempts: 0 });
+                    drop(state);
+
+                    self.shared.notify_rx.notify();
+                    return PollSend::Ready;
+                }
+            }
+
+            self.shared.notify_tx.subscribe(cx);
+
+            if guard.is_expired() {
+                continue;
+            }
+
+            return PollSend::Pending(value);
+        }
+    }
+}
+
+#[cfg(feature = "futures-traits")]
+mod impl_futures {
+    use std::task::Poll;
+
+    use crate::{
+        sink::{PollSend, SendError, Sink},
+        Context,
+    };
+
+    impl<T> futures_sink::Sink<T> for super::Sender<T> {
+        type Error = SendError<T>;
+
+        fn poll_ready(
+            self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            loop {
+                let guard = self.shared.notify_tx.guard();
+
+                {
+                    // a closed channel is ready, and rejects the message in start_send
+                    let state = self.shared.state.lock();
+                    if state.receivers == 0 || state.len() < self.shared.capacity {
+                        return Poll::Ready(Ok(()));
+                    }
+                }
+
+                self.shared.notify_tx.subscribe(&cx.into());
+
+                if guard.is_expired() {
+                    continue;
+                }
+
+                return Poll::Pending;
+            }
+        }
+
+        fn start_send(self: std::pin::Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+            match self.poll_send(&mut Context::empty(), item) {
+                PollSend::Ready => Ok(()),
+                PollSend::Pending(item) | PollSend::Rejected(item) => Err(SendError(item)),
+            }
+        }
+
+        fn poll_flush(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.shared.state.lock().senders += 1;
+
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock();
+        state.senders -= 1;
+
+        if state.senders == 0 {
+            drop(state);
+            self.shared.notify_rx.notify();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender").finish()
+    }
+}
+
+/// The receiver half of an ack channel.  Can be cloned, and each message is delivered to one receiver at a time.
+///
+/// Receives [Delivery](./struct.Delivery.html) guards with the postage::Stream trait.  The channel closes when all senders
+/// have been dropped, and every message has been acknowledged.
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+    #[cfg(feature = "timer")]
+    timer: Option<Timer>,
+}
+
+assert_impl_all!(Receiver<SendMessage>: Send, Sync, fmt::Debug);
+
+impl<T: Clone> Stream for Receiver<T> {
+    type Item = Delivery<T>;
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item> {
+        let this = self.get_mut();
+
+        loop {
+            let guard = this.shared.notify_rx.guard();
+            let now = Instant::now();
+
+            let next_deadline = {
+                let mut state = this.shared.state.lock();
+                state.requeue_expired(now);
+
+                if let Some(mut queued) = state.queue.pop_front() {
+                    let id = state.next_id;
+                    let deadline = now + this.shared.ack_timeout;
+                    state.next_id += 1;
+
+                    queued.attempts += 1;
+                    let delivery = Delivery {
+                        value: queued.value.clone(),
+                        attempt: queued.attempts,
+                        id,
+                        deadline,
+                        shared: this.shared.clone(),
+                    };
+
+                    state.in_flight.insert(id, queued);
+                    state.deadlines.insert((deadline, id));
+                    return PollRecv::Ready(delivery);
+                }
+
+                if state.senders == 0 && state.in_flight.is_empty() {
+                    return PollRecv::Closed;
+                }
+
+                state.deadlines.iter().next().map(|(deadline, _)| *deadline)
+            };
+
+            this.shared.notify_rx.subscribe(cx);
+
+            if guard.is_expired() {
+                continue;
+            }
+
+            // the earliest deadline may have passed since the in-flight deliveries were checked
+            if let Some(deadline) = next_deadline {
+                if this.poll_deadline(cx, deadline).is_ready() {
+                    continue;
+                }
+            }
+
+            return PollRecv::Pending;
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    /// The number of messages which are queued, or delivered and not yet acknowledged
+    pub fn len(&self) -> usize {
+        self.shared.state.lock().len()
+    }
+
+    /// Returns true if there are no queued or unacknowledged messages
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of messages which have been delivered, and not yet acknowledged
+    pub fn in_flight(&self) -> usize {
+        self.shared.state.lock().in_flight.len()
+    }
+
+    /// Wakes the task when the earliest unacknowledged delivery expires.  Returns Ready if the deadline has passed.
+    #[cfg(feature = "timer")]
+    fn poll_deadline(&mut self, cx: &mut Context<'_>, deadline: Instant) -> Poll<()> {
+        let timer = match &mut self.timer {
+            Some(timer) => timer,
+            None if cx.waker().is_some() => self.timer.insert(Timer::new(deadline)),
+            None => return Poll::Pending,
+        };
+
+        if timer.deadline() != deadline {
+            timer.reset(deadline);
+        }
+
+        timer.poll_elapsed(cx)
+    }
+
+    /// Without a timer, expired deliveries are requeued when a receiver is next polled
+    #[cfg(not(feature = "timer"))]
+    fn poll_deadline(&mut self, _cx: &mut Context<'_>, _deadline: Instant) -> Poll<()> {
+        Poll::Pending
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        self.shared.state.lock().receivers += 1;
+
+        Self {
+            shared: self.shared.clone(),
+            #[cfg(feature = "timer")]
+            timer: None,
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock();
+        state.receivers -= 1;
+
+        if state.receivers == 0 {
+            drop(state);
+            self.shared.notify_tx.notify();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver").finish()
+    }
+}
+
+/// A message which has been delivered to a receiver.  Dereferences to the message.
+///
+/// The delivery should be acknowledged with `ack()` once the message has been processed.  If the delivery is dropped,
+/// or is not acknowledged within the ack timeout, the message is redelivered.
+#[must_use = "deliveries which are not acknowledged are redelivered"]
+pub struct Delivery<T> {
+    value: T,
+    attempt: usize,
+    id: u64,
+    deadline: Instant,
+    shared: Arc<Shared<T>>,
+}
+
+assert_not_impl_all!(Delivery<String>: Clone);
+
+impl<T> Delivery<T> {
+    /// The number of times the message has been delivered, starting at 1
+    pub fn attempt(&self) -> usize {
+        self.attempt
+    }
+
+    /// Acknowledges the message, so it is not redelivered.
+    ///
+    /// Returns false if the ack timeout had already expired, and the message was returned to the queue.
+    pub fn ack(self) -> bool {
+        let mut state = self.shared.state.lock();
+        if state.in_flight.remove(&self.id).is_none() {
+            return false;
+        }
+
+        state.deadlines.remove(&(self.deadline, self.id));
+        drop(state);
+
+        self.shared.notify_tx.notify();
+        self.shared.notify_rx.notify();
+        true
+    }
+
+    /// Returns the message to the front of the queue, so it is redelivered immediately.
+    pub fn nack(self) {
+        let mut state = self.shared.state.lock();
+        if let Some(queued) = state.in_flight.remove(&self.id) {
+            state.deadlines.remove(&(self.deadline, self.id));
+            state.queue.push_front(queued);
+            drop(state);
+
+            self.shared.notify_rx.notify();
+        }
+    }
+
+    /// Takes the message.  The delivery is not acknowledged, and will be redelivered after the ack timeout.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Deref for Delivery<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Delivery<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Delivery")
+            .field("value", &self.value)
+            .field("attempt", &self.attempt)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{pin::Pin, time::Duration};
+
+    use futures_test::task::new_count_waker;
+
+    use super::channel;
+    use crate::{
+        sink::{PollSend, Sink},
+        stream::{PollRecv, Stream, TryRecvError},
+        test::noop_context,
+        Context,
+    };
+
+    #[test]
+    fn ack() {
+        let (mut tx, mut rx) = channel(4, Duration::from_secs(60));
+        tx.try_send(1usize).unwrap();
+
+        let delivery = rx.try_recv().unwrap();
+        assert_eq!(1, *delivery);
+        assert_eq!(1, rx.in_flight());
+        assert!(delivery.ack());
+
+        assert!(rx.is_empty());
+        drop(tx);
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Closed)));
+    }
+
+    #[test]
+    fn nack_redelivers() {
+        let (mut tx, mut rx) = channel(4, Duration::from_secs(60));
+        let mut rx2 = rx.clone();
+        tx.try_send(1usize).unwrap();
+        tx.try_send(2usize).unwrap();
+
+        let delivery = rx.try_recv().unwrap();
+        delivery.nack();
+
+        let delivery = rx2.try_recv().unwrap();
+        assert_eq!(1, *delivery);
+        assert_eq!(2, delivery.attempt());
+        assert!(delivery.ack());
+    }
+
+    #[test]
+    fn timeout_redelivers() {
+        let (mut tx, mut rx) = channel(4, Duration::from_millis(10));
+        let mut rx2 = rx.clone();
+        tx.try_send(1usize).unwrap();
+
+        let delivery = rx.try_recv().unwrap();
+        assert!(matches!(rx2.try_recv(), Err(TryRecvError::Pending)));
+        drop(delivery);
+
+        std::thread::sleep(Duration::from_millis(20));
+        let redelivered = rx2.try_recv().unwrap();
+        assert_eq!(1, *redelivered);
+        assert_eq!(2, redelivered.attempt());
+    }
+
+    #[test]
+    fn late_ack() {
+        let (mut tx, mut rx) = channel(4, Duration::from_millis(10));
+        tx.try_send(1usize).unwrap();
+
+        let delivery = rx.try_recv().unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        let redelivered = rx.try_recv().unwrap();
+
+        assert!(!delivery.ack());
+        assert!(redelivered.ack());
+    }
+
+    #[test]
+    fn in_flight_capacity() {
+        let (mut tx, mut rx) = channel(1, Duration::from_secs(60));
+        tx.try_send(1usize).unwrap();
+        let delivery = rx.try_recv().unwrap();
+
+        let (waker, count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker);
+        assert_eq!(
+            PollSend::Pending(2),
+            Pin::new(&mut tx).poll_send(&mut cx, 2)
+        );
+
+        delivery.ack();
+        assert_eq!(1, count.get());
+        assert_eq!(PollSend::Ready, Pin::new(&mut tx).poll_send(&mut cx, 2));
+    }
+
+    #[test]
+    fn open_while_in_flight() {
+        let (mut tx, mut rx) = channel(4, Duration::from_secs(60));
+        tx.try_send(1usize).unwrap();
+        let delivery = rx.try_recv().unwrap();
+        drop(tx);
+
+        assert!(matches!(
+            Pin::new(&mut rx).poll_recv(&mut noop_context()),
+            PollRecv::Pending
+        ));
+        delivery.ack();
+        assert!(matches!(
+            Pin::new(&mut rx).poll_recv(&mut noop_context()),
+            PollRecv::Closed
+        ));
+    }
+
+    #[cfg(feature = "timer")]
+    #[tokio::test]
+    async fn panicked_consumer() {
+        let (mut tx, rx) = channel(4, Duration::from_millis(10));
+        tx.send(1usize).await.ok();
+
+        let mut worker = rx.clone();
+        let result = tokio::spawn(async move {
+            let _delivery = worker.recv().await.unwrap();
+            panic!("consumer failed");
+        })
+        .await;
+        assert!(result.is_err());
+
+        let mut rx = rx;
+        let delivery = rx.recv().await.unwrap();
+        assert_eq!(1, *delivery);
+        assert_eq!(2, delivery.attempt());
+        assert!(delivery.ack());
+    }
+}
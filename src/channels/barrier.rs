@@ -1,17 +1,42 @@
 //! Barriers transmit when the sender half is dropped, and can synchronize events in async tasks.
 //!
 //! The barrier can also be triggered with `tx.send(())`.
+//!
+//! For repeated rounds between a fixed number of tasks, [Barrier](./struct.Barrier.html) releases once all participants are waiting.
+//! Like `std::sync::Barrier`, each waiter learns the generation of the round, and one waiter per round is the leader.
+//!
+//! ```rust
+//! use postage::barrier::Barrier;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let barrier = Barrier::new(2);
+//!     let worker = barrier.clone();
+//!
+//!     let handle = tokio::spawn(async move { worker.wait().await });
+//!     let result = barrier.wait().await;
+//!     let other = handle.await.unwrap();
+//!
+//!     assert_eq!(0, result.generation());
+//!     assert!(result.is_leader() != other.is_leader());
+//! }
+//! ```
 
 use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::Poll;
 
 use atomic::{Atomic, Ordering};
+use parking_lot::Mutex;
 use static_assertions::{assert_impl_all, assert_not_impl_all};
 
 use crate::{
     sink::{PollSend, Sink},
     stream::{PollRecv, Stream},
     sync::notifier::Notifier,
+    trace::Tracer,
 };
 
 /// Constructs a pair of barrier endpoints, which transmits when the sender is dropped.
@@ -21,6 +46,7 @@ pub fn channel() -> (Sender, Receiver) {
     let shared = Arc::new(Shared {
         state: Atomic::new(State::Pending),
         notify_rx: Notifier::new(),
+        tracer: Tracer::new("barrier", None, None),
     });
 
     let sender = Sender {
@@ -52,10 +78,14 @@ impl Sink for Sender {
     ) -> PollSend<Self::Item> {
         match self.shared.state.load(Ordering::Acquire) {
             State::Pending => {
+                self.shared.tracer.send();
                 self.shared.close();
                 PollSend::Ready
             }
-            State::Sent => PollSend::Rejected(()),
+            State::Sent => {
+                self.shared.tracer.reject();
+                PollSend::Rejected(())
+            }
         }
     }
 }
@@ -73,7 +103,7 @@ mod impl_futures {
     use atomic::Ordering;
     use std::task::{Context, Poll};
 
-    impl futures::sink::Sink<()> for super::Sender {
+    impl futures_sink::Sink<()> for super::Sender {
         type Error = SendError<()>;
 
         fn poll_ready(
@@ -135,11 +165,15 @@ enum State {
 struct Shared {
     state: Atomic<State>,
     notify_rx: Notifier,
+    tracer: Tracer,
 }
 
 impl Shared {
     pub fn close(&self) {
-        self.state.store(State::Sent, Ordering::Release);
+        if let State::Pending = self.state.swap(State::Sent, Ordering::AcqRel) {
+            self.tracer.senders_closed();
+        }
+
         self.notify_rx.notify();
     }
 }
@@ -172,6 +206,179 @@ impl fmt::Debug for Receiver {
     }
 }
 
+/// A reusable barrier, which releases each time `n` tasks are waiting.
+///
+/// Clones of the barrier share the same rounds.
+#[derive(Clone)]
+pub struct Barrier {
+    shared: Arc<BarrierShared>,
+}
+
+assert_impl_all!(Barrier: Clone, Send, Sync, fmt::Debug);
+
+struct BarrierShared {
+    n: usize,
+    round: Mutex<Round>,
+    notify: Notifier,
+    tracer: Tracer,
+}
+
+struct Round {
+    waiting: usize,
+    generation: usize,
+}
+
+impl Barrier {
+    /// Creates a barrier which releases when `n` tasks are waiting.  A barrier with `n` of 0 or 1 releases every waiter immediately.
+    pub fn new(n: usize) -> Self {
+        Self {
+            shared: Arc::new(BarrierShared {
+                n,
+                round: Mutex::new(Round {
+                    waiting: 0,
+                    generation: 0,
+                }),
+                notify: Notifier::new(),
+                tracer: Tracer::new("barrier", None, Some(n)),
+            }),
+        }
+    }
+
+    /// Waits until `n` tasks are waiting on the barrier.  The last task to arrive is the leader of the round.
+    ///
+    /// If the future is dropped before the barrier releases, the task is no longer counted as waiting.
+    pub fn wait(&self) -> BarrierWait<'_> {
+        BarrierWait {
+            barrier: self,
+            generation: None,
+            complete: false,
+        }
+    }
+
+    /// The generation of the current round, which is incremented each time the barrier releases
+    pub fn generation(&self) -> usize {
+        self.shared.round.lock().generation
+    }
+}
+
+impl fmt::Debug for Barrier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let round = self.shared.round.lock();
+        f.debug_struct("Barrier")
+            .field("n", &self.shared.n)
+            .field("waiting", &round.waiting)
+            .field("generation", &round.generation)
+            .finish()
+    }
+}
+
+/// The result of waiting on a [Barrier](./struct.Barrier.html).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BarrierWaitResult {
+    generation: usize,
+    leader: bool,
+}
+
+impl BarrierWaitResult {
+    /// The generation of the round which released, starting at 0
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Returns true for exactly one waiter in each round, which can perform one-time work for the round
+    pub fn is_leader(&self) -> bool {
+        self.leader
+    }
+}
+
+/// A future returned by `Barrier::wait`.
+#[must_use = "futures do nothing unless polled"]
+pub struct BarrierWait<'b> {
+    barrier: &'b Barrier,
+    generation: Option<usize>,
+    complete: bool,
+}
+
+impl<'b> Future for BarrierWait<'b> {
+    type Output = BarrierWaitResult;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let shared = &this.barrier.shared;
+        let cx: crate::Context<'_> = cx.into();
+
+        let generation = match this.generation {
+            Some(generation) => generation,
+            None => {
+                let mut round = shared.round.lock();
+                let generation = round.generation;
+                round.waiting += 1;
+
+                if round.waiting >= shared.n {
+                    round.waiting = 0;
+                    round.generation += 1;
+                    drop(round);
+
+                    this.complete = true;
+                    shared.tracer.send();
+                    shared.notify.notify();
+                    return Poll::Ready(BarrierWaitResult {
+                        generation,
+                        leader: true,
+                    });
+                }
+
+                this.generation = Some(generation);
+                generation
+            }
+        };
+
+        loop {
+            let guard = shared.notify.guard();
+
+            if shared.round.lock().generation != generation {
+                this.complete = true;
+                shared.tracer.recv();
+                return Poll::Ready(BarrierWaitResult {
+                    generation,
+                    leader: false,
+                });
+            }
+
+            shared.notify.subscribe(&cx);
+
+            if guard.is_expired() {
+                continue;
+            }
+
+            return Poll::Pending;
+        }
+    }
+}
+
+impl<'b> Drop for BarrierWait<'b> {
+    fn drop(&mut self) {
+        if self.complete {
+            return;
+        }
+
+        if let Some(generation) = self.generation {
+            let mut round = self.barrier.shared.round.lock();
+            if round.generation == generation {
+                round.waiting -= 1;
+            }
+        }
+    }
+}
+
+impl<'b> fmt::Debug for BarrierWait<'b> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BarrierWait")
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{pin::Pin, task::Context};
@@ -181,9 +388,10 @@ mod tests {
         stream::{PollRecv, Stream},
         test::{noop_context, panic_context},
     };
-    use futures_test::task::new_count_waker;
+    use futures_test::task::{new_count_waker, noop_waker};
+    use std::{future::Future, task::Poll};
 
-    use super::channel;
+    use super::{channel, Barrier};
 
     #[test]
     fn send_accepted() {
@@ -293,6 +501,69 @@ mod tests {
         assert_eq!(1, w_count.get());
     }
 
+    #[test]
+    fn barrier_rounds() {
+        let barrier = Barrier::new(2);
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        for round in 0..2 {
+            let mut first = barrier.wait();
+            assert_eq!(Poll::Pending, Pin::new(&mut first).poll(&mut cx));
+
+            let mut second = barrier.wait();
+            let leader = match Pin::new(&mut second).poll(&mut cx) {
+                Poll::Ready(result) => result,
+                Poll::Pending => panic!("barrier should release"),
+            };
+            assert!(leader.is_leader());
+            assert_eq!(round, leader.generation());
+
+            let follower = match Pin::new(&mut first).poll(&mut cx) {
+                Poll::Ready(result) => result,
+                Poll::Pending => panic!("barrier should release"),
+            };
+            assert!(!follower.is_leader());
+            assert_eq!(round, follower.generation());
+        }
+
+        assert_eq!(2, barrier.generation());
+    }
+
+    #[test]
+    fn barrier_wakes_waiters() {
+        let barrier = Barrier::new(2);
+        let (w, w_count) = new_count_waker();
+        let mut cx = Context::from_waker(&w);
+
+        let mut first = barrier.wait();
+        assert!(Pin::new(&mut first).poll(&mut cx).is_pending());
+        assert_eq!(0, w_count.get());
+
+        let mut second = barrier.wait();
+        assert!(Pin::new(&mut second).poll(&mut cx).is_ready());
+        assert_eq!(1, w_count.get());
+    }
+
+    #[test]
+    fn barrier_cancelled_wait() {
+        let barrier = Barrier::new(2);
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        let mut cancelled = barrier.wait();
+        assert!(Pin::new(&mut cancelled).poll(&mut cx).is_pending());
+        drop(cancelled);
+
+        let mut first = barrier.wait();
+        assert!(Pin::new(&mut first).poll(&mut cx).is_pending());
+        assert_eq!(0, barrier.generation());
+
+        let mut second = barrier.wait();
+        assert!(Pin::new(&mut second).poll(&mut cx).is_ready());
+        assert_eq!(1, barrier.generation());
+    }
+
     #[test]
     fn wake_receiver_on_disconnect() {
         let (tx, mut rx) = channel();
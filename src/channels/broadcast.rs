@@ -1,44 +1,209 @@
 //! Provides a lossless, MPMC channel.  All receivers are guaranteed to recieve each message.
 //!
 //! When a receiver is cloned, the new receive will observe the same series of messages as the original.
-//! When a receiver is created with `Sender::subscribe`, it will observe new messages.
+//! When a receiver is created with `Sender::subscribe` or `Receiver::resubscribe`, it will observe new messages.
+//! This allows subscribers to join the channel at runtime, such as when a client connects.
+//!
+//! By default, senders wait when the slowest receiver falls a full buffer behind.  The [SlowSubscriber](./enum.SlowSubscriber.html) policy
+//! can instead skip the lagging receiver ahead, or disconnect it, so one stalled subscriber cannot block the channel.
+//! Low-priority observers can be created with [Sender::subscribe_with_capacity](./struct.Sender.html#method.subscribe_with_capacity),
+//! which gives the receiver a personal buffer, and never blocks the senders.
+//!
+//! A receiver's position can be exported with [Receiver::cursor](./struct.Receiver.html#method.cursor), and a new receiver can resume
+//! from it with [Sender::subscribe_at](./struct.Sender.html#method.subscribe_at), while the messages are still in the buffer.
+//!
+//! [Receiver::into_mpsc](./struct.Receiver.html#method.into_mpsc) forwards the messages to a bounded mpsc channel, with an optional transform.
+//!
+//! Messages must be `Clone`, as each receiver observes its own copy.  [arc_channel](./fn.arc_channel.html) broadcasts messages which
+//! are not `Clone`, by wrapping them in an `Arc`.
 
-use std::fmt;
+use std::{fmt, marker::PhantomData, sync::Arc};
 
 use super::SendMessage;
 use static_assertions::assert_impl_all;
 
 use crate::{
-    sink::{PollSend, Sink},
+    dead_letter::{DeadLetter, Undelivered},
+    metrics::{ChannelMetrics, MetricsHook},
+    registry::Registration,
+    sink::{PollSend, Sink, TrySendError},
+    spawn::Spawn,
+    stop::StopToken,
     stream::{PollRecv, Stream},
     sync::{
         mpmc_circular_buffer::{BufferReader, MpmcCircularBuffer, TryRead, TryWrite},
         shared, ReceiverShared, SenderShared,
     },
+    trace::Tracer,
 };
 
+mod arc;
+
+pub use arc::{arc_channel, ArcSender};
+
 /// Constructs a pair of broadcast endpoints, with a fixed-size buffer of the given capacity
 pub fn channel<T: Clone>(capacity: usize) -> (Sender<T>, Receiver<T>) {
-    #[cfg(feature = "debug")]
-    log::error!("Creating broadcast channel with capacity {}", capacity);
-    // we add one spare capacity so that receivers have an empty slot to wait on
-    let (buffer, reader) = MpmcCircularBuffer::new(capacity);
+    Builder::new(capacity).build()
+}
+
+/// Constructs a broadcast channel, with additional configuration.
+///
+/// ```rust
+/// use postage::broadcast;
+///
+/// let (tx, rx) = broadcast::Builder::<usize>::new(16).name("events").build();
+/// ```
+pub struct Builder<T> {
+    capacity: usize,
+    slow_subscriber: SlowSubscriber,
+    name: Option<String>,
+    metrics: Option<Arc<dyn ChannelMetrics>>,
+    stop: Option<StopToken>,
+    undelivered: Undelivered<T>,
+    // set by arc_channel
+    release: bool,
+    _t: PhantomData<fn() -> T>,
+}
+
+impl<T: Clone> Builder<T> {
+    /// Creates a builder for a channel with a fixed-size buffer of the given capacity
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            slow_subscriber: SlowSubscriber::Block,
+            name: None,
+            metrics: None,
+            stop: None,
+            undelivered: Undelivered::new(),
+            release: false,
+            _t: PhantomData,
+        }
+    }
+
+    /// Names the channel.  The name is attached to the channel's tracing span.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Installs a metrics hook on the channel, which takes precedence over the global hook.
+    pub fn metrics(mut self, metrics: Arc<dyn ChannelMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Closes the channel when the token's source is stopped.  Senders reject new messages,
+    /// and receivers drain the buffered messages before observing the channel as closed.
+    /// Senders blocked by a full buffer observe the stop when a receiver reads a message.
+    pub fn stop_token(mut self, token: StopToken) -> Self {
+        self.stop = Some(token);
+        self
+    }
+
+    /// Attaches a dead-letter sink to the channel.  Messages sent after every receiver has been dropped,
+    /// or the channel has been stopped, are returned to the sender, and a copy is forwarded to the sink without blocking,
+    /// tagged with `DeadLetterReason::Rejected`.
+    pub fn dead_letter<S>(mut self, sink: S) -> Self
+    where
+        S: Sink<Item = DeadLetter<T>> + Send + 'static,
+    {
+        self.undelivered.set_dead_letter(sink);
+        self.undelivered.set_copy_rejected(Some(T::clone));
+        self
+    }
+
+    /// Chooses what happens when a receiver falls a full buffer behind the senders.  Defaults to `SlowSubscriber::Block`.
+    ///
+    /// ```rust
+    /// use postage::broadcast::{self, SlowSubscriber};
+    ///
+    /// let (tx, rx) = broadcast::Builder::<usize>::new(16)
+    ///     .slow_subscriber(SlowSubscriber::Skip)
+    ///     .build();
+    /// ```
+    pub fn slow_subscriber(mut self, policy: SlowSubscriber) -> Self {
+        self.slow_subscriber = policy;
+        self
+    }
+
+    /// Constructs the pair of channel endpoints
+    pub fn build(self) -> (Sender<T>, Receiver<T>) {
+        #[cfg(feature = "debug")]
+        log::error!("Creating broadcast channel with capacity {}", self.capacity);
+        // we add one spare capacity so that receivers have an empty slot to wait on
+        let overwrite = self.slow_subscriber != SlowSubscriber::Block;
+        let (mut buffer, reader) = MpmcCircularBuffer::new(self.capacity, overwrite);
+        if self.release {
+            buffer = buffer.release_on_read();
+        }
+
+        let tracer = Tracer::new("broadcast", self.name.as_deref(), Some(self.capacity));
+        let metrics = MetricsHook::resolve(self.metrics, "broadcast", self.name.as_deref());
+        let registration = Registration::new(
+            "broadcast",
+            self.name.as_deref(),
+            Some(self.capacity),
+            false,
+        );
+        let (tx_shared, rx_shared) = shared(buffer, tracer, metrics, registration, self.stop);
+        let sender = Sender {
+            shared: tx_shared,
+            slow_subscriber: self.slow_subscriber,
+            undelivered: Arc::new(self.undelivered),
+        };
+
+        let receiver = Receiver::new(rx_shared, reader, self.slow_subscriber);
 
-    let (tx_shared, rx_shared) = shared(buffer);
-    let sender = Sender { shared: tx_shared };
+        (sender, receiver)
+    }
+}
 
-    let receiver = Receiver::new(rx_shared, reader);
+impl<T> fmt::Debug for Builder<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Builder")
+            .field("capacity", &self.capacity)
+            .field("slow_subscriber", &self.slow_subscriber)
+            .field("name", &self.name)
+            .field("metrics", &self.metrics.is_some())
+            .field("stop", &self.stop)
+            .field("undelivered", &self.undelivered)
+            .finish()
+    }
+}
 
-    (sender, receiver)
+/// The behavior of the channel when a receiver falls a full buffer behind the senders.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SlowSubscriber {
+    /// Senders wait until the slowest receiver reads a message
+    #[default]
+    Block,
+    /// Senders overwrite the oldest message, and the lagging receiver skips ahead to the oldest message in the buffer.
+    /// The number of skipped messages is reported by `Receiver::take_lagged`.
+    Skip,
+    /// Senders overwrite the oldest message, and the lagging receiver is disconnected.
+    /// The receiver observes the channel as closed, and no longer counts towards `slowest_lag`.
+    Disconnect,
+}
+
+/// The behavior of a [Receiver::into_mpsc](./struct.Receiver.html#method.into_mpsc) bridge when the mpsc channel is full.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Overflow {
+    /// The bridge waits for capacity.  The bridge subscription lags, and is handled by the channel's [SlowSubscriber](./enum.SlowSubscriber.html) policy.
+    #[default]
+    Block,
+    /// The bridge drops the message which did not fit
+    DropNewest,
+    /// The bridge stops, and the mpsc channel closes after the buffered messages are received
+    Close,
 }
 
 /// A broadcast sender that can be used with the postage::Sink trait.  Can be cloned.
 ///
 /// The sender task is suspended when the internal buffer is filled.
-///
-/// Note: no implementation of the `futures::Sink` trait is provided for the broadcast Sender.
 pub struct Sender<T> {
     pub(in crate::channels::broadcast) shared: SenderShared<MpmcCircularBuffer<T>>,
+    slow_subscriber: SlowSubscriber,
+    undelivered: Arc<Undelivered<T>>,
 }
 
 unsafe impl<T: Send> Send for Sender<T> {}
@@ -48,6 +213,8 @@ impl<T> Clone for Sender<T> {
     fn clone(&self) -> Self {
         Self {
             shared: self.shared.clone(),
+            slow_subscriber: self.slow_subscriber,
+            undelivered: self.undelivered.clone(),
         }
     }
 }
@@ -70,7 +237,11 @@ where
         // however, it would not receive this item, as it would need to be called
         //   before the message is sent.
         if self.shared.is_closed() {
-            return PollSend::Rejected(value);
+            self.shared.tracer().reject();
+            if let Some(metrics) = self.shared.metrics() {
+                metrics.on_reject();
+            }
+            return PollSend::Rejected(self.undelivered.reject(value));
         }
 
         // start at the head
@@ -80,13 +251,97 @@ where
         //   overwrite the element
         let buffer = self.shared.extension();
         match buffer.try_write(value, cx) {
-            TryWrite::Pending(value) => PollSend::Pending(value),
-            TryWrite::Ready => PollSend::Ready,
+            TryWrite::Pending(value) => {
+                self.shared.tracer().lag();
+                if let Some(metrics) = self.shared.metrics() {
+                    metrics.set_blocked_senders(self.shared.blocked_senders());
+                }
+                PollSend::Pending(value)
+            }
+            TryWrite::Ready => {
+                self.shared.tracer().send();
+                if let Some(metrics) = self.shared.metrics() {
+                    metrics.on_send(None);
+                }
+                PollSend::Ready
+            }
+        }
+    }
+}
+
+#[cfg(feature = "futures-traits")]
+mod impl_futures {
+    use std::task::Poll;
+
+    use crate::{sink::SendError, sync::mpmc_circular_buffer::TryWrite, Context};
+
+    impl<T> futures_sink::Sink<T> for super::Sender<T>
+    where
+        T: Clone,
+    {
+        type Error = SendError<T>;
+
+        fn poll_ready(
+            self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            if self.shared.is_closed() {
+                return Poll::Ready(Ok(()));
+            }
+
+            let cx = cx.into();
+            if self.shared.extension().poll_writable(&cx) {
+                Poll::Ready(Ok(()))
+            } else {
+                self.shared.tracer().lag();
+                Poll::Pending
+            }
+        }
+
+        fn start_send(self: std::pin::Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+            if self.shared.is_closed() {
+                self.shared.tracer().reject();
+                if let Some(metrics) = self.shared.metrics() {
+                    metrics.on_reject();
+                }
+                return Err(SendError(self.undelivered.reject(item)));
+            }
+
+            // another sender may have taken the slot since poll_ready
+            match self.shared.extension().try_write(item, &Context::empty()) {
+                TryWrite::Pending(item) => Err(SendError(item)),
+                TryWrite::Ready => {
+                    self.shared.tracer().send();
+                    if let Some(metrics) = self.shared.metrics() {
+                        metrics.on_send(None);
+                    }
+                    Ok(())
+                }
+            }
+        }
+
+        fn poll_flush(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
         }
     }
 }
 
 impl<T> Sender<T> {
+    /// Returns true if every receiver has been dropped, or the channel has been stopped.  Sends to a closed channel are rejected.
+    pub fn is_closed(&self) -> bool {
+        self.shared.is_closed()
+    }
+
     /// Subscribes to the channel, creating a new receiver.  The receiver
     /// will observe all messages sent after the call to subscribe.
     ///
@@ -96,7 +351,74 @@ impl<T> Sender<T> {
         let reader = shared.extension().new_reader();
         self.shared.notify_self();
 
-        Receiver::new(shared, reader)
+        Receiver::new(shared, reader, self.slow_subscriber)
+    }
+
+    /// Subscribes to the channel with a personal buffer of `capacity` messages, creating a new receiver.
+    /// The receiver will observe messages sent after the call to subscribe.
+    ///
+    /// Senders never wait for the receiver.  If it falls more than `capacity` messages behind, it skips ahead to the most recent messages,
+    /// and the number of skipped messages is reported by `Receiver::take_lagged`.
+    /// This suits low-priority observers, which should not hold back the critical consumers of the channel.
+    ///
+    /// The capacity is limited to the capacity of the channel.  Clones and resubscriptions of the receiver have the same capacity.
+    ///
+    /// ```rust
+    /// use postage::{broadcast, prelude::*};
+    ///
+    /// let (mut tx, mut rx) = broadcast::channel(16);
+    /// let mut observer = tx.subscribe_with_capacity(2);
+    ///
+    /// for i in 0..4usize {
+    ///     tx.try_send(i).ok();
+    /// }
+    ///
+    /// assert_eq!(Ok(0), rx.try_recv());
+    /// assert_eq!(Ok(2), observer.try_recv());
+    /// assert_eq!(2, observer.take_lagged());
+    /// ```
+    pub fn subscribe_with_capacity(&self, capacity: usize) -> Receiver<T> {
+        let shared = self.shared.clone_receiver();
+        let reader = shared.extension().new_lossy_reader(capacity);
+        self.shared.notify_self();
+
+        Receiver::new(shared, reader, SlowSubscriber::Skip)
+    }
+
+    /// Subscribes to the channel at the cursor, creating a new receiver.  The receiver observes the message at the cursor,
+    /// and every message sent after it.
+    ///
+    /// Returns `None` if the message at the cursor has been overwritten, or the cursor is ahead of the channel.
+    /// The cursor must have been taken from a receiver of this channel.
+    ///
+    /// ```rust
+    /// use postage::{broadcast, prelude::*};
+    ///
+    /// let (mut tx, mut rx) = broadcast::channel(4);
+    /// tx.try_send(1usize).ok();
+    /// tx.try_send(2).ok();
+    ///
+    /// assert_eq!(Ok(1), rx.try_recv());
+    /// let cursor = rx.cursor().unwrap();
+    /// drop(rx);
+    ///
+    /// let mut rx = tx.subscribe_at(cursor).unwrap();
+    /// assert_eq!(Ok(2), rx.try_recv());
+    /// ```
+    pub fn subscribe_at(&self, cursor: Cursor) -> Option<Receiver<T>> {
+        let shared = self.shared.clone_receiver();
+        let reader = shared.extension().new_reader_at(cursor.0)?;
+        self.shared.notify_self();
+
+        Some(Receiver::new(shared, reader, self.slow_subscriber))
+    }
+
+    /// The number of messages the slowest receiver has yet to read.
+    ///
+    /// Senders are blocked when this reaches the capacity of the channel, so this can be used to
+    /// identify a consumer which is holding back the channel.
+    pub fn slowest_lag(&self) -> usize {
+        self.shared.extension().slowest_lag()
     }
 }
 
@@ -111,7 +433,10 @@ impl<T> fmt::Debug for Sender<T> {
 /// When cloned, the new receiver will begin processing messages at the same location as the original.
 pub struct Receiver<T> {
     shared: ReceiverShared<MpmcCircularBuffer<T>>,
-    reader: BufferReader,
+    // released when the receiver is disconnected, so it no longer holds slots in the buffer
+    reader: Option<BufferReader>,
+    slow_subscriber: SlowSubscriber,
+    lagged: usize,
 }
 
 unsafe impl<T: Send> Send for Receiver<T> {}
@@ -120,8 +445,102 @@ unsafe impl<T: Send> Sync for Receiver<T> {}
 assert_impl_all!(Receiver<SendMessage>: Send, Sync, Clone, fmt::Debug);
 
 impl<T> Receiver<T> {
-    fn new(shared: ReceiverShared<MpmcCircularBuffer<T>>, reader: BufferReader) -> Self {
-        Self { shared, reader }
+    fn new(
+        shared: ReceiverShared<MpmcCircularBuffer<T>>,
+        reader: BufferReader,
+        slow_subscriber: SlowSubscriber,
+    ) -> Self {
+        Self {
+            shared,
+            reader: Some(reader),
+            slow_subscriber,
+            lagged: 0,
+        }
+    }
+
+    /// Releases the receiver's cursor, so the senders no longer wait for it to read
+    fn release(&mut self) {
+        if let Some(mut reader) = self.reader.take() {
+            reader.drop_with(self.shared.extension());
+        }
+    }
+
+    /// Creates a new receiver with a fresh cursor at the head of the channel.  The receiver
+    /// will observe all messages sent after the call to resubscribe.
+    ///
+    /// Unlike `clone`, messages which this receiver has not yet read are not received.
+    pub fn resubscribe(&self) -> Receiver<T> {
+        let shared = self.shared.clone();
+        let lossy = self.reader.as_ref().and_then(BufferReader::lossy_capacity);
+        let reader = match lossy {
+            Some(capacity) => shared.extension().new_lossy_reader(capacity),
+            None => shared.extension().new_reader(),
+        };
+
+        Receiver::new(shared, reader, self.slow_subscriber)
+    }
+
+    /// Returns the position of the next message the receiver will read, which can be passed to
+    /// [Sender::subscribe_at](./struct.Sender.html#method.subscribe_at).
+    ///
+    /// Returns `None` if the receiver has been disconnected.
+    pub fn cursor(&self) -> Option<Cursor> {
+        self.reader.as_ref().map(|reader| Cursor(reader.index()))
+    }
+
+    /// Returns the number of messages this receiver has skipped since the last call, and resets the count.
+    ///
+    /// Messages are only skipped with the `SlowSubscriber::Skip` policy.
+    pub fn take_lagged(&mut self) -> usize {
+        std::mem::take(&mut self.lagged)
+    }
+
+    /// Returns true if every sender has been dropped, the channel has been stopped, or the receiver has been disconnected.
+    ///
+    /// Messages which are still queued for the receiver can be received.
+    pub fn is_closed(&self) -> bool {
+        self.is_disconnected() || self.shared.is_closed()
+    }
+
+    /// Returns true if the receiver was disconnected by the `SlowSubscriber::Disconnect` policy
+    pub fn is_disconnected(&self) -> bool {
+        self.reader.is_none()
+    }
+
+    /// The number of messages which are queued for this receiver
+    pub fn len(&self) -> usize {
+        self.reader
+            .as_ref()
+            .map_or(0, |reader| reader.queued(self.shared.extension()))
+    }
+
+    /// Returns true if no messages are queued for this receiver
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of messages the slowest receiver of the channel has yet to read
+    pub fn slowest_lag(&self) -> usize {
+        self.shared.extension().slowest_lag()
+    }
+}
+
+/// The position of a receiver in a broadcast channel, returned by [Receiver::cursor](./struct.Receiver.html#method.cursor).
+///
+/// Cursors of the same channel are ordered by position.  The position can be sent to a client, and restored with `Cursor::from`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Cursor(usize);
+
+impl Cursor {
+    /// The position as a number, which increases by one with each message sent on the channel
+    pub fn position(&self) -> usize {
+        self.0
+    }
+}
+
+impl From<usize> for Cursor {
+    fn from(position: usize) -> Self {
+        Self(position)
     }
 }
 
@@ -137,37 +556,189 @@ where
     ) -> PollRecv<Self::Item> {
         // unpin self, so Rust can infer that the borrows of reader and buffer are disjoint
         let this = self.get_mut();
-        let reader = &mut this.reader;
         let buffer = this.shared.extension();
+        let reader = match this.reader {
+            Some(ref mut reader) => reader,
+            None => return PollRecv::Closed,
+        };
+
+        loop {
+            // if the channel is closed before the read, the read observes every value that was sent.
+            // otherwise, the guard expires when the last sender drops.
+            let guard = this.shared.send_guard();
+            let closed = this.shared.is_closed();
+
+            let skipped = reader.skip_lagged(buffer);
+            if skipped > 0 {
+                this.lagged += skipped;
+                this.shared.tracer().skipped(skipped);
+            }
 
-        match reader.try_read(buffer, cx) {
-            TryRead::Pending => {
-                this.shared.subscribe_send(cx);
+            match reader.try_read(buffer, cx) {
+                TryRead::Pending => {
+                    if closed {
+                        return PollRecv::Closed;
+                    }
 
-                if this.shared.is_closed() {
-                    return PollRecv::Closed;
-                }
+                    this.shared.subscribe_send(cx);
+
+                    if guard.is_expired() {
+                        continue;
+                    }
 
-                PollRecv::Pending
+                    return PollRecv::Pending;
+                }
+                TryRead::Overwritten => match this.slow_subscriber {
+                    SlowSubscriber::Block => return PollRecv::Pending,
+                    SlowSubscriber::Skip => {
+                        let skipped = reader.skip_overwritten(buffer);
+                        this.lagged += skipped;
+                        this.shared.tracer().skipped(skipped);
+                        continue;
+                    }
+                    SlowSubscriber::Disconnect => {
+                        this.release();
+                        this.shared.tracer().disconnected();
+                        return PollRecv::Closed;
+                    }
+                },
+                TryRead::Ready(value) => {
+                    this.shared.tracer().recv();
+                    if let Some(metrics) = this.shared.metrics() {
+                        metrics.on_recv(None, None);
+                    }
+                    return PollRecv::Ready(value);
+                }
             }
-            TryRead::Ready(value) => PollRecv::Ready(value),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let reader = match self.reader {
+            Some(ref reader) => reader,
+            None => return (0, Some(0)),
+        };
+
+        let len = self.len();
+
+        // queued messages are only guaranteed to be received if the senders wait for this receiver
+        let lower =
+            if self.slow_subscriber == SlowSubscriber::Block && reader.lossy_capacity().is_none() {
+                len
+            } else {
+                0
+            };
+
+        if self.shared.is_closed() {
+            (lower, Some(len))
+        } else {
+            (lower, None)
         }
     }
 }
 
+impl<T> Receiver<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    /// Forwards the messages to a bounded mpsc channel, and returns the mpsc receiver.
+    ///
+    /// A forwarding task is spawned with the [spawner](../spawn/trait.Spawn.html).  If the mpsc channel is full, the
+    /// [Overflow](./enum.Overflow.html) policy decides whether the forwarder waits, drops the message, or stops.
+    /// The forwarder stops when the broadcast channel closes, or when it forwards a message after the mpsc receiver is dropped.
+    pub fn into_mpsc<Sp>(
+        self,
+        capacity: usize,
+        overflow: Overflow,
+        spawner: &Sp,
+    ) -> crate::mpsc::Receiver<T>
+    where
+        Sp: Spawn + ?Sized,
+    {
+        self.into_mpsc_with(capacity, overflow, Some, spawner)
+    }
+
+    /// Forwards the messages to a bounded mpsc channel, transforming each message with `transform`.
+    ///
+    /// Messages for which `transform` returns `None` are not forwarded, so the closure can map and filter the messages.
+    /// Otherwise, behaves like [into_mpsc](./struct.Receiver.html#method.into_mpsc).
+    ///
+    /// ```rust
+    /// use postage::{broadcast::{self, Overflow}, prelude::*};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let spawner = |task| {
+    ///         tokio::spawn(task);
+    ///     };
+    ///
+    ///     let (mut tx, rx) = broadcast::channel(4);
+    ///     let mut rx = rx.into_mpsc_with(4, Overflow::Block, |n: usize| (n % 2 == 0).then(|| n * 10), &spawner);
+    ///
+    ///     for n in 1..=4 {
+    ///         tx.send(n).await.ok();
+    ///     }
+    ///
+    ///     assert_eq!(Some(20), rx.recv().await);
+    ///     assert_eq!(Some(40), rx.recv().await);
+    /// }
+    /// ```
+    pub fn into_mpsc_with<U, F, Sp>(
+        mut self,
+        capacity: usize,
+        overflow: Overflow,
+        mut transform: F,
+        spawner: &Sp,
+    ) -> crate::mpsc::Receiver<U>
+    where
+        U: Send + 'static,
+        F: FnMut(T) -> Option<U> + Send + 'static,
+        Sp: Spawn + ?Sized,
+    {
+        let (mut tx, rx) = crate::mpsc::channel(capacity);
+
+        spawner.spawn(Box::pin(async move {
+            while let Some(message) = self.recv().await {
+                let message = match transform(message) {
+                    Some(message) => message,
+                    None => continue,
+                };
+
+                let sent = match overflow {
+                    Overflow::Block => tx.send(message).await.is_ok(),
+                    Overflow::DropNewest => {
+                        !matches!(tx.try_send(message), Err(TrySendError::Rejected(_)))
+                    }
+                    Overflow::Close => tx.try_send(message).is_ok(),
+                };
+
+                if !sent {
+                    break;
+                }
+            }
+        }));
+
+        rx
+    }
+}
+
 impl<T> Clone for Receiver<T> {
     fn clone(&self) -> Self {
         let buffer = self.shared.extension();
-        let reader = self.reader.clone_with(buffer);
+        let reader = self.reader.as_ref().map(|reader| reader.clone_with(buffer));
 
-        Self::new(self.shared.clone(), reader)
+        Self {
+            shared: self.shared.clone(),
+            reader,
+            slow_subscriber: self.slow_subscriber,
+            lagged: 0,
+        }
     }
 }
 
 impl<T> Drop for Receiver<T> {
     fn drop(&mut self) {
-        let buffer = self.shared.extension();
-        self.reader.drop_with(buffer);
+        self.release();
     }
 }
 
@@ -182,6 +753,7 @@ mod tests {
     use std::pin::Pin;
 
     use crate::{
+        dead_letter::DeadLetterReason,
         sink::{PollSend, Sink},
         stream::{PollRecv, Stream},
         test::{noop_context, panic_context},
@@ -189,10 +761,10 @@ mod tests {
     };
     use futures_test::task::new_count_waker;
 
-    use super::{channel, Receiver, Sender};
+    use super::{channel, Builder, Receiver, Sender, SlowSubscriber};
 
     //TODO: add test covering rx location when cloned on an in-progress channel (exercising tail)
-    fn pin<'a, 'b>(
+    fn pin(
         chan: &mut (Sender<Message>, Receiver<Message>),
     ) -> (Pin<&mut Sender<Message>>, Pin<&mut Receiver<Message>>) {
         let tx = Pin::new(&mut chan.0);
@@ -252,6 +824,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn builder_send_recv() {
+        let mut cx = noop_context();
+        let (mut tx, mut rx) = Builder::new(2).name("builder").build();
+
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, Message(1))
+        );
+        assert_eq!(
+            PollRecv::Ready(Message(1)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+    }
+
     #[test]
     fn send_recv() {
         let mut cx = noop_context();
@@ -313,6 +900,287 @@ mod tests {
         );
     }
 
+    #[test]
+    fn receiver_resubscribe() {
+        let mut cx = noop_context();
+        let (mut tx, mut rx) = channel(2);
+
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, Message(1))
+        );
+
+        let mut rx2 = rx.resubscribe();
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx2).poll_recv(&mut cx));
+
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, Message(2))
+        );
+        assert_eq!(
+            PollRecv::Ready(Message(1)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        assert_eq!(
+            PollRecv::Ready(Message(2)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        assert_eq!(
+            PollRecv::Ready(Message(2)),
+            Pin::new(&mut rx2).poll_recv(&mut cx)
+        );
+
+        drop(rx);
+        drop(tx);
+        assert_eq!(PollRecv::Closed, Pin::new(&mut rx2).poll_recv(&mut cx));
+    }
+
+    #[test]
+    fn receiver_len() {
+        let mut cx = noop_context();
+        let (mut tx, mut rx) = channel(4);
+        let mut rx2 = rx.clone();
+
+        assert_eq!(0, rx.len());
+        assert!(rx.is_empty());
+        assert_eq!(0, tx.slowest_lag());
+
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, Message(1))
+        );
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, Message(2))
+        );
+        assert_eq!(2, rx.len());
+        assert_eq!(2, tx.slowest_lag());
+
+        assert_eq!(
+            PollRecv::Ready(Message(1)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        assert_eq!(1, rx.len());
+        assert_eq!(2, rx2.len());
+        assert_eq!(2, tx.slowest_lag());
+
+        assert_eq!(
+            PollRecv::Ready(Message(1)),
+            Pin::new(&mut rx2).poll_recv(&mut cx)
+        );
+        assert_eq!(1, tx.slowest_lag());
+
+        assert_eq!(
+            PollRecv::Ready(Message(2)),
+            Pin::new(&mut rx2).poll_recv(&mut cx)
+        );
+        assert_eq!(1, tx.slowest_lag());
+        assert_eq!(1, rx2.slowest_lag());
+
+        drop(rx);
+        assert_eq!(0, tx.slowest_lag());
+        assert_eq!(0, rx2.len());
+    }
+
+    #[test]
+    fn size_hint() {
+        let mut cx = noop_context();
+        let (mut tx, mut rx) = channel(4);
+        let observer = tx.subscribe_with_capacity(2);
+        assert_eq!((0, None), rx.size_hint());
+
+        for i in 1..=3 {
+            assert_eq!(
+                PollSend::Ready,
+                Pin::new(&mut tx).poll_send(&mut cx, Message(i))
+            );
+        }
+        assert_eq!((3, None), rx.size_hint());
+        // the observer may skip messages, so only the upper bound is known
+        assert_eq!((0, None), observer.size_hint());
+
+        assert_eq!(
+            PollRecv::Ready(Message(1)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        drop(tx);
+        assert_eq!((2, Some(2)), rx.size_hint());
+        assert_eq!((0, Some(2)), observer.size_hint());
+    }
+
+    #[test]
+    fn size_hint_disconnected() {
+        let mut cx = noop_context();
+        let (mut tx, mut fast) = Builder::new(2)
+            .slow_subscriber(SlowSubscriber::Disconnect)
+            .build();
+        let mut slow = fast.clone();
+
+        for i in 1..=3 {
+            assert_eq!(
+                PollSend::Ready,
+                Pin::new(&mut tx).poll_send(&mut cx, Message(i))
+            );
+            assert_eq!(
+                PollRecv::Ready(Message(i)),
+                Pin::new(&mut fast).poll_recv(&mut cx)
+            );
+        }
+
+        assert_eq!((0, None), slow.size_hint());
+        assert_eq!(PollRecv::Closed, Pin::new(&mut slow).poll_recv(&mut cx));
+        assert_eq!((0, Some(0)), slow.size_hint());
+    }
+
+    #[test]
+    fn slow_subscriber_skip() {
+        let mut cx = noop_context();
+        let (mut tx, mut rx) = Builder::new(2)
+            .slow_subscriber(SlowSubscriber::Skip)
+            .build();
+
+        for i in 1..=4 {
+            assert_eq!(
+                PollSend::Ready,
+                Pin::new(&mut tx).poll_send(&mut cx, Message(i))
+            );
+        }
+
+        assert_eq!(
+            PollRecv::Ready(Message(3)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        assert_eq!(2, rx.take_lagged());
+        assert_eq!(0, rx.take_lagged());
+        assert_eq!(
+            PollRecv::Ready(Message(4)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx).poll_recv(&mut cx));
+    }
+
+    #[test]
+    fn slow_subscriber_disconnect() {
+        let mut cx = noop_context();
+        let (mut tx, mut fast) = Builder::new(2)
+            .slow_subscriber(SlowSubscriber::Disconnect)
+            .build();
+        let mut slow = fast.clone();
+
+        for i in 1..=3 {
+            assert_eq!(
+                PollSend::Ready,
+                Pin::new(&mut tx).poll_send(&mut cx, Message(i))
+            );
+            assert_eq!(
+                PollRecv::Ready(Message(i)),
+                Pin::new(&mut fast).poll_recv(&mut cx)
+            );
+        }
+
+        assert!(!slow.is_disconnected());
+        assert_eq!(PollRecv::Closed, Pin::new(&mut slow).poll_recv(&mut cx));
+        assert!(slow.is_disconnected());
+
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, Message(4))
+        );
+        assert_eq!(PollRecv::Closed, Pin::new(&mut slow).poll_recv(&mut cx));
+        assert_eq!(
+            PollRecv::Ready(Message(4)),
+            Pin::new(&mut fast).poll_recv(&mut cx)
+        );
+    }
+
+    #[test]
+    fn subscribe_with_capacity_does_not_block() {
+        let mut cx = noop_context();
+        let (mut tx, mut rx) = channel(2);
+        let mut observer = tx.subscribe_with_capacity(1);
+
+        for i in 1..=4 {
+            assert_eq!(
+                PollSend::Ready,
+                Pin::new(&mut tx).poll_send(&mut cx, Message(i))
+            );
+            assert_eq!(
+                PollRecv::Ready(Message(i)),
+                Pin::new(&mut rx).poll_recv(&mut cx)
+            );
+        }
+
+        assert_eq!(1, observer.len());
+        assert_eq!(0, tx.slowest_lag());
+        assert_eq!(
+            PollRecv::Ready(Message(4)),
+            Pin::new(&mut observer).poll_recv(&mut cx)
+        );
+        assert_eq!(3, observer.take_lagged());
+        assert_eq!(
+            PollRecv::Pending,
+            Pin::new(&mut observer).poll_recv(&mut cx)
+        );
+    }
+
+    #[test]
+    fn subscribe_with_capacity_in_order() {
+        let mut cx = noop_context();
+        let (mut tx, _rx) = Builder::new(4)
+            .slow_subscriber(SlowSubscriber::Skip)
+            .build();
+        let mut observer = tx.subscribe_with_capacity(8);
+        let mut clone = observer.clone();
+
+        for i in 1..=3 {
+            assert_eq!(
+                PollSend::Ready,
+                Pin::new(&mut tx).poll_send(&mut cx, Message(i))
+            );
+        }
+
+        for i in 1..=3 {
+            assert_eq!(
+                PollRecv::Ready(Message(i)),
+                Pin::new(&mut observer).poll_recv(&mut cx)
+            );
+            assert_eq!(
+                PollRecv::Ready(Message(i)),
+                Pin::new(&mut clone).poll_recv(&mut cx)
+            );
+        }
+
+        assert_eq!(0, observer.take_lagged());
+        drop(observer);
+        drop(clone);
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, Message(4))
+        );
+    }
+
+    #[test]
+    fn subscribe_after_receivers_dropped() {
+        let mut cx = noop_context();
+        let (mut tx, rx) = channel(2);
+        drop(rx);
+
+        assert_eq!(
+            PollSend::Rejected(Message(1)),
+            Pin::new(&mut tx).poll_send(&mut cx, Message(1))
+        );
+
+        let mut rx = tx.subscribe();
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, Message(2))
+        );
+        assert_eq!(
+            PollRecv::Ready(Message(2)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+    }
+
     #[test]
     fn two_senders_recv() {
         // SimpleLogger::new().init().unwrap();
@@ -447,6 +1315,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn dead_letter_rejected() {
+        let mut cx = panic_context();
+        let (dead_tx, mut dead_rx) = crate::mpsc::channel(1);
+        let (mut tx, rx) = Builder::new(4).dead_letter(dead_tx).build();
+        let mut tx2 = tx.clone();
+
+        // the message is returned to the sender, and a copy is forwarded to the dead-letter sink
+        drop(rx);
+        assert_eq!(
+            PollSend::Rejected(Message(1)),
+            Pin::new(&mut tx).poll_send(&mut cx, Message(1))
+        );
+
+        // clones share the sink, which is now full
+        assert_eq!(
+            PollSend::Rejected(Message(2)),
+            Pin::new(&mut tx2).poll_send(&mut cx, Message(2))
+        );
+
+        let letter = dead_rx.try_recv().unwrap();
+        assert_eq!(Message(1), letter.value);
+        assert_eq!(DeadLetterReason::Rejected, letter.reason);
+        assert!(dead_rx.try_recv().is_err());
+    }
+
     #[test]
     fn receiver_reconnect() {
         let mut cx = panic_context();
@@ -482,14 +1376,14 @@ mod tests {
         );
 
         let (w2, w2_count) = new_count_waker();
-        let w2_context = Context::from_waker(&w2);
+        let mut w2_context = Context::from_waker(&w2);
         assert_eq!(
             PollSend::Ready,
             Pin::new(&mut tx).poll_send(&mut cx, Message(2))
         );
         assert_eq!(
             PollSend::Pending(Message(3)),
-            Pin::new(&mut tx).poll_send(&mut w2_context.into(), Message(3))
+            Pin::new(&mut tx).poll_send(&mut w2_context, Message(3))
         );
 
         assert_eq!(0, w2_count.get());
@@ -519,11 +1413,11 @@ mod tests {
         let (mut tx, mut rx) = channel(100);
 
         let (w1, w1_count) = new_count_waker();
-        let w1_context = Context::from_waker(&w1);
+        let mut w1_context = Context::from_waker(&w1);
 
         assert_eq!(
             PollRecv::Pending,
-            Pin::new(&mut rx).poll_recv(&mut w1_context.into())
+            Pin::new(&mut rx).poll_recv(&mut w1_context)
         );
 
         assert_eq!(0, w1_count.get());
@@ -606,13 +1500,94 @@ mod tests {
         );
     }
 
+    #[test]
+    fn drop_lagging_receiver_wakes_sender() {
+        let (waker, count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker);
+        let (mut tx, mut rx) = channel(2);
+        let mut lagging = rx.clone();
+
+        for i in 1..=2 {
+            assert_eq!(
+                PollSend::Ready,
+                Pin::new(&mut tx).poll_send(&mut cx, Message(i))
+            );
+            assert_eq!(
+                PollRecv::Ready(Message(i)),
+                Pin::new(&mut rx).poll_recv(&mut cx)
+            );
+        }
+
+        assert_eq!(
+            PollRecv::Ready(Message(1)),
+            Pin::new(&mut lagging).poll_recv(&mut cx)
+        );
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, Message(3))
+        );
+        assert_eq!(
+            PollSend::Pending(Message(4)),
+            Pin::new(&mut tx).poll_send(&mut cx, Message(4))
+        );
+        assert_eq!(0, count.get());
+
+        drop(lagging);
+
+        assert_eq!(1, count.get());
+        assert_eq!(1, tx.slowest_lag());
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, Message(4))
+        );
+    }
+
+    #[test]
+    fn disconnect_releases_cursor() {
+        let mut cx = noop_context();
+        let (mut tx, mut fast) = Builder::new(2)
+            .slow_subscriber(SlowSubscriber::Disconnect)
+            .build();
+        let mut slow = fast.clone();
+
+        for i in 1..=3 {
+            assert_eq!(
+                PollSend::Ready,
+                Pin::new(&mut tx).poll_send(&mut cx, Message(i))
+            );
+            assert_eq!(
+                PollRecv::Ready(Message(i)),
+                Pin::new(&mut fast).poll_recv(&mut cx)
+            );
+        }
+
+        assert_eq!(PollRecv::Closed, Pin::new(&mut slow).poll_recv(&mut cx));
+        assert_eq!(0, slow.len());
+        assert_eq!(0, tx.slowest_lag());
+
+        let mut clone = slow.clone();
+        assert!(clone.is_disconnected());
+        assert_eq!(PollRecv::Closed, Pin::new(&mut clone).poll_recv(&mut cx));
+        drop(clone);
+        drop(slow);
+
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, Message(4))
+        );
+        assert_eq!(
+            PollRecv::Ready(Message(4)),
+            Pin::new(&mut fast).poll_recv(&mut cx)
+        );
+    }
+
     #[test]
     fn wake_sender_on_disconnect() {
         let (mut tx, rx) = channel(2);
 
         let (w1, w1_count) = new_count_waker();
         let w1_context = Context::from_waker(&w1);
-        let mut w1_context: crate::Context<'_> = w1_context.into();
+        let mut w1_context: crate::Context<'_> = w1_context;
 
         assert_eq!(
             PollSend::Ready,
@@ -641,11 +1616,11 @@ mod tests {
         let (tx, mut rx) = channel::<()>(100);
 
         let (w1, w1_count) = new_count_waker();
-        let w1_context = Context::from_waker(&w1);
+        let mut w1_context = Context::from_waker(&w1);
 
         assert_eq!(
             PollRecv::Pending,
-            Pin::new(&mut rx).poll_recv(&mut w1_context.into())
+            Pin::new(&mut rx).poll_recv(&mut w1_context)
         );
 
         assert_eq!(0, w1_count.get());
@@ -774,6 +1749,115 @@ mod tests {
             Pin::new(&mut tx).poll_send(&mut cx, Message(3))
         );
     }
+
+    #[test]
+    fn is_closed() {
+        let (tx, rx) = channel::<Message>(4);
+        let rx2 = rx.clone();
+        assert!(!tx.is_closed());
+        assert!(!rx.is_closed());
+
+        drop(rx);
+        assert!(!tx.is_closed());
+
+        drop(rx2);
+        assert!(tx.is_closed());
+
+        let (tx, rx) = channel::<Message>(4);
+        drop(tx);
+        assert!(rx.is_closed());
+    }
+
+    #[test]
+    fn subscribe_at_cursor() {
+        let mut cx = noop_context();
+        let (mut tx, mut rx) = channel(4);
+        let head = rx.cursor().unwrap();
+
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, Message(1))
+        );
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, Message(2))
+        );
+        assert_eq!(
+            PollRecv::Ready(Message(1)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+
+        let cursor = rx.cursor().unwrap();
+        assert!(head < cursor);
+
+        let mut resumed = tx.subscribe_at(cursor).unwrap();
+        assert_eq!(cursor, resumed.cursor().unwrap());
+        assert_eq!(
+            PollRecv::Ready(Message(2)),
+            Pin::new(&mut resumed).poll_recv(&mut cx)
+        );
+
+        let mut replay = tx.subscribe_at(head).unwrap();
+        assert_eq!(
+            PollRecv::Ready(Message(1)),
+            Pin::new(&mut replay).poll_recv(&mut cx)
+        );
+        assert_eq!(
+            PollRecv::Ready(Message(2)),
+            Pin::new(&mut replay).poll_recv(&mut cx)
+        );
+        assert_eq!(PollRecv::Pending, Pin::new(&mut replay).poll_recv(&mut cx));
+    }
+
+    #[test]
+    fn subscribe_at_holds_slots() {
+        let mut cx = noop_context();
+        let (mut tx, rx) = channel(2);
+        let cursor = rx.cursor().unwrap();
+        let _rx = tx.subscribe();
+        drop(rx);
+
+        let mut resumed = tx.subscribe_at(cursor).unwrap();
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, Message(1))
+        );
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, Message(2))
+        );
+        assert_eq!(
+            PollSend::Pending(Message(3)),
+            Pin::new(&mut tx).poll_send(&mut cx, Message(3))
+        );
+
+        assert_eq!(
+            PollRecv::Ready(Message(1)),
+            Pin::new(&mut resumed).poll_recv(&mut cx)
+        );
+    }
+
+    #[test]
+    fn subscribe_at_expired_cursor() {
+        let mut cx = noop_context();
+        let (mut tx, mut rx) = channel(2);
+        let cursor = rx.cursor().unwrap();
+
+        for i in 1..=3 {
+            assert_eq!(
+                PollSend::Ready,
+                Pin::new(&mut tx).poll_send(&mut cx, Message(i))
+            );
+            assert_eq!(
+                PollRecv::Ready(Message(i)),
+                Pin::new(&mut rx).poll_recv(&mut cx)
+            );
+        }
+
+        assert!(tx.subscribe_at(cursor).is_none());
+        assert!(tx.subscribe_at(super::Cursor::from(100)).is_none());
+        assert!(tx.subscribe_at(rx.cursor().unwrap()).is_some());
+    }
 }
 
 #[cfg(test)]
@@ -958,7 +2042,7 @@ mod tokio_tests {
                 loop {
                     let next = rx2.try_recv();
 
-                    if let Ok(_) = next {
+                    if next.is_ok() {
                         continue;
                     }
 
@@ -966,7 +2050,7 @@ mod tokio_tests {
                         break;
                     }
 
-                    if let Ok(_) = sender_quit.try_recv() {
+                    if sender_quit.try_recv().is_ok() {
                         break;
                     }
 
@@ -996,6 +2080,65 @@ mod tokio_tests {
                 .expect("join failure");
         }
     }
+
+    #[tokio::test]
+    async fn into_mpsc_transform() {
+        let spawn = |task: crate::spawn::BoxFuture| {
+            tokio::spawn(task);
+        };
+
+        let (mut tx, rx) = super::channel(4);
+        let mut rx = rx.into_mpsc_with(
+            4,
+            super::Overflow::Block,
+            |n: usize| (n % 2 == 1).then(|| n.to_string()),
+            &spawn,
+        );
+
+        for n in 0..4 {
+            tx.send(n).await.unwrap();
+        }
+        drop(tx);
+
+        assert_eq!(Some("1".to_string()), rx.recv().await);
+        assert_eq!(Some("3".to_string()), rx.recv().await);
+        assert_eq!(None, rx.recv().await);
+    }
+
+    #[tokio::test]
+    async fn into_mpsc_drop_newest() {
+        let spawn = |task: crate::spawn::BoxFuture| {
+            tokio::spawn(task);
+        };
+
+        let (mut tx, rx) = super::channel(8);
+        let mut rx = rx.into_mpsc(2, super::Overflow::DropNewest, &spawn);
+
+        for n in 0..4usize {
+            tx.send(n).await.unwrap();
+        }
+        drop(tx);
+
+        assert_eq!(Some(0), rx.recv().await);
+        assert_eq!(Some(1), rx.recv().await);
+        assert_eq!(None, rx.recv().await);
+    }
+
+    #[tokio::test]
+    async fn into_mpsc_close() {
+        let spawn = |task: crate::spawn::BoxFuture| {
+            tokio::spawn(task);
+        };
+
+        let (mut tx, rx) = super::channel(8);
+        let mut rx = rx.into_mpsc(1, super::Overflow::Close, &spawn);
+
+        tx.send(1usize).await.unwrap();
+        tx.send(2).await.unwrap();
+
+        assert_eq!(Some(1), rx.recv().await);
+        assert_eq!(None, rx.recv().await);
+    }
 }
 
 #[cfg(test)]
@@ -1178,7 +2321,7 @@ mod async_std_tests {
                 loop {
                     let next = rx2.try_recv();
 
-                    if let Ok(_) = next {
+                    if next.is_ok() {
                         continue;
                     }
 
@@ -1186,7 +2329,7 @@ mod async_std_tests {
                         break;
                     }
 
-                    if let Ok(_) = sender_quit.try_recv() {
+                    if sender_quit.try_recv().is_ok() {
                         break;
                     }
 
@@ -0,0 +1,292 @@
+use std::{fmt, pin::Pin, sync::Arc};
+
+use static_assertions::assert_impl_all;
+
+use super::{Builder, Cursor, Receiver, Sender};
+use crate::{
+    channels::SendSyncMessage,
+    sink::{PollSend, Sink},
+};
+
+/// Constructs a pair of broadcast endpoints for messages which are not `Clone`, with a fixed-size buffer of the given capacity.
+///
+/// The sender wraps each message in an `Arc`, and the receivers yield `Arc<T>`.  The channel drops its reference
+/// once every receiver has read the message, so when a single receiver remains, it holds the only reference,
+/// and `Arc::try_unwrap` recovers the message without a copy.
+///
+/// Receivers created with `subscribe_with_capacity` may read a message after the other receivers,
+/// so while one exists, the channel keeps its reference until the message is overwritten.
+///
+/// Messages sent after the channel closes are returned to the sender, and are not forwarded to a dead-letter sink.
+///
+/// ```rust
+/// use postage::{broadcast, prelude::*};
+/// use std::sync::Arc;
+///
+/// struct Frame(Vec<u8>);
+///
+/// let (mut tx, mut rx) = broadcast::arc_channel(4);
+/// tx.try_send(Frame(vec![1, 2, 3])).ok();
+///
+/// let frame: Arc<Frame> = rx.try_recv().unwrap();
+/// let frame = Arc::try_unwrap(frame).ok().unwrap();
+/// assert_eq!(vec![1, 2, 3], frame.0);
+/// ```
+pub fn arc_channel<T>(capacity: usize) -> (ArcSender<T>, Receiver<Arc<T>>) {
+    Builder::new(capacity).build_arc()
+}
+
+impl<T> Builder<Arc<T>> {
+    /// Constructs a pair of endpoints for messages which are not `Clone`, with the builder's configuration.
+    /// See [arc_channel](./fn.arc_channel.html).
+    pub fn build_arc(mut self) -> (ArcSender<T>, Receiver<Arc<T>>) {
+        self.release = true;
+        // a rejected message is returned to the sender, which can't share it with the dead-letter sink
+        self.undelivered.set_copy_rejected(None);
+        let (sender, receiver) = self.build();
+
+        (ArcSender { sender }, receiver)
+    }
+}
+
+/// A broadcast sender which wraps each message in an `Arc`, constructed by [arc_channel](./fn.arc_channel.html).  Can be cloned.
+///
+/// The sender task is suspended when the internal buffer is filled.
+pub struct ArcSender<T> {
+    sender: Sender<Arc<T>>,
+}
+
+assert_impl_all!(ArcSender<SendSyncMessage>: Send, Sync, Clone, fmt::Debug);
+
+impl<T> Clone for ArcSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<T> Sink for ArcSender<T> {
+    type Item = T;
+
+    fn poll_send(
+        mut self: Pin<&mut Self>,
+        cx: &mut crate::Context<'_>,
+        value: Self::Item,
+    ) -> PollSend<Self::Item> {
+        match Pin::new(&mut self.sender).poll_send(cx, Arc::new(value)) {
+            PollSend::Ready => PollSend::Ready,
+            PollSend::Pending(value) => PollSend::Pending(unwrap_unsent(value)),
+            PollSend::Rejected(value) => PollSend::Rejected(unwrap_unsent(value)),
+        }
+    }
+}
+
+#[cfg(feature = "futures-traits")]
+mod impl_futures {
+    use std::{pin::Pin, sync::Arc, task::Poll};
+
+    use super::unwrap_unsent;
+    use crate::sink::SendError;
+
+    impl<T> futures_sink::Sink<T> for super::ArcSender<T> {
+        type Error = SendError<T>;
+
+        fn poll_ready(
+            mut self: Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            futures_sink::Sink::poll_ready(Pin::new(&mut self.sender), cx)
+                .map_err(|SendError(value)| SendError(unwrap_unsent(value)))
+        }
+
+        fn start_send(mut self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+            Pin::new(&mut self.sender)
+                .start_send(Arc::new(item))
+                .map_err(|SendError(value)| SendError(unwrap_unsent(value)))
+        }
+
+        fn poll_flush(
+            self: Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(
+            self: Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+}
+
+// a message which was not written to the buffer has not been shared with a receiver
+fn unwrap_unsent<T>(value: Arc<T>) -> T {
+    Arc::try_unwrap(value).unwrap_or_else(|_| unreachable!("an unsent message was shared"))
+}
+
+impl<T> ArcSender<T> {
+    /// Returns true if every receiver has been dropped, or the channel has been stopped.  Sends to a closed channel are rejected.
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
+
+    /// Subscribes to the channel, creating a new receiver.  The receiver
+    /// will observe all messages sent after the call to subscribe.
+    pub fn subscribe(&self) -> Receiver<Arc<T>> {
+        self.sender.subscribe()
+    }
+
+    /// Subscribes to the channel with a personal buffer of `capacity` messages, creating a new receiver.
+    /// See [Sender::subscribe_with_capacity](./struct.Sender.html#method.subscribe_with_capacity).
+    ///
+    /// While the receiver exists, the channel keeps its reference to each message until it is overwritten.
+    pub fn subscribe_with_capacity(&self, capacity: usize) -> Receiver<Arc<T>> {
+        self.sender.subscribe_with_capacity(capacity)
+    }
+
+    /// Subscribes to the channel at the cursor, creating a new receiver.
+    /// See [Sender::subscribe_at](./struct.Sender.html#method.subscribe_at).
+    ///
+    /// Returns `None` if a message from the cursor onwards has been read by every receiver, and dropped by the channel.
+    pub fn subscribe_at(&self, cursor: Cursor) -> Option<Receiver<Arc<T>>> {
+        self.sender.subscribe_at(cursor)
+    }
+
+    /// The number of messages the slowest receiver has yet to read.
+    pub fn slowest_lag(&self) -> usize {
+        self.sender.slowest_lag()
+    }
+}
+
+impl<T> fmt::Debug for ArcSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArcSender").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::arc_channel;
+    use crate::{
+        broadcast::Builder,
+        sink::{Sink, TrySendError},
+        stop::StopSource,
+        stream::{Stream, TryRecvError},
+    };
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Message(usize);
+
+    #[test]
+    fn single_receiver_unwraps() {
+        let (mut tx, mut rx) = arc_channel(4);
+        tx.try_send(Message(1)).unwrap();
+        tx.try_send(Message(2)).unwrap();
+
+        let message = rx.try_recv().unwrap();
+        assert_eq!(Ok(Message(1)), Arc::try_unwrap(message));
+        let message = rx.try_recv().unwrap();
+        assert_eq!(Ok(Message(2)), Arc::try_unwrap(message));
+    }
+
+    #[test]
+    fn shared_until_every_receiver_reads() {
+        let (mut tx, mut rx) = arc_channel(4);
+        let mut rx2 = tx.subscribe();
+        tx.try_send(Message(1)).unwrap();
+
+        let first = rx.try_recv().unwrap();
+        let first = Arc::try_unwrap(first).unwrap_err();
+
+        let second = rx2.try_recv().unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        drop(first);
+        assert_eq!(Ok(Message(1)), Arc::try_unwrap(second));
+
+        // once the other receiver is dropped, the remaining receiver holds the only reference
+        drop(rx2);
+        tx.try_send(Message(2)).unwrap();
+        let message = rx.try_recv().unwrap();
+        assert_eq!(Ok(Message(2)), Arc::try_unwrap(message));
+    }
+
+    #[test]
+    fn unsent_messages_are_returned() {
+        let (mut tx, rx) = arc_channel(2);
+        tx.try_send(Message(1)).unwrap();
+        tx.try_send(Message(2)).unwrap();
+        assert_eq!(
+            Err(TrySendError::Pending(Message(3))),
+            tx.try_send(Message(3))
+        );
+
+        drop(rx);
+        assert_eq!(
+            Err(TrySendError::Rejected(Message(3))),
+            tx.try_send(Message(3))
+        );
+    }
+
+    #[test]
+    fn lossy_receiver_keeps_messages() {
+        let (mut tx, mut rx) = arc_channel(4);
+        let mut observer = tx.subscribe_with_capacity(4);
+        tx.try_send(Message(1)).unwrap();
+
+        let message = rx.try_recv().unwrap();
+        assert!(Arc::try_unwrap(message).is_err());
+        assert_eq!(Ok(Arc::new(Message(1))), observer.try_recv());
+    }
+
+    #[test]
+    fn subscribe_at_released() {
+        let (mut tx, mut rx) = arc_channel(4);
+        tx.try_send(Message(1)).unwrap();
+        tx.try_send(Message(2)).unwrap();
+
+        let cursor = rx.cursor().unwrap();
+        let message = rx.try_recv().unwrap();
+        assert_eq!(Ok(Message(1)), Arc::try_unwrap(message));
+
+        // the first message has been read by every receiver, and dropped
+        assert!(tx.subscribe_at(cursor).is_none());
+
+        let cursor = rx.cursor().unwrap();
+        let mut resumed = tx.subscribe_at(cursor).unwrap();
+        assert_eq!(Ok(Arc::new(Message(2))), resumed.try_recv());
+    }
+
+    #[test]
+    fn stop_token() {
+        let stop = StopSource::new();
+        let (mut tx, mut rx) = Builder::new(4).stop_token(stop.token()).build_arc();
+        tx.try_send(Message(1)).unwrap();
+
+        stop.stop();
+        assert_eq!(
+            Err(TrySendError::Rejected(Message(2))),
+            tx.try_send(Message(2))
+        );
+        assert_eq!(Ok(Arc::new(Message(1))), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Closed), rx.try_recv());
+    }
+
+    #[test]
+    fn dead_letter_rejected() {
+        let (dead_tx, mut dead_rx) = crate::mpsc::channel(1);
+        let (mut tx, rx) = Builder::new(4).dead_letter(dead_tx).build_arc();
+        drop(rx);
+
+        // the sender holds the only reference, so the message is returned rather than forwarded
+        assert_eq!(
+            Err(TrySendError::Rejected(Message(1))),
+            tx.try_send(Message(1))
+        );
+        assert!(dead_rx.try_recv().is_err());
+    }
+}
@@ -0,0 +1,602 @@
+//! A multi-producer, single-consumer channel with credit-based flow control.
+//!
+//! Each message consumes a credit, and senders wait while no credits are available.  The receiver grants credits with
+//! [Receiver::grant](./struct.Receiver.html#method.grant), so it decides how many messages may be in flight, rather than a fixed capacity.
+//! This models window-based protocols, such as HTTP/2 flow control, where the consumer opens the window as it processes data.
+//!
+//! ```rust
+//! use postage::{credit, prelude::*};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let (mut tx, mut rx) = credit::channel(1);
+//!
+//!     tx.send("first").await.ok();
+//!     assert_eq!(0, tx.credits());
+//!
+//!     rx.grant(2);
+//!     tx.send("second").await.ok();
+//!     assert_eq!(1, rx.credits());
+//!
+//!     assert_eq!(Some("first"), rx.recv().await);
+//!     assert_eq!(Some("second"), rx.recv().await);
+//! }
+//! ```
+
+use std::{
+    fmt,
+    marker::PhantomData,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use static_assertions::{assert_impl_all, assert_not_impl_all};
+
+use super::SendMessage;
+use crate::{
+    dead_letter::{DeadLetter, DeadLetterReason, Undelivered},
+    metrics::MetricsHook,
+    registry::Registration,
+    sink::{PollSend, Sink},
+    stop::StopToken,
+    stream::{PollRecv, Stream},
+    sync::{primitives::SegQueue, shared, ReceiverShared, SenderShared},
+    trace::Tracer,
+};
+
+/// Constructs a pair of credit channel endpoints, with the given number of initial credits
+pub fn channel<T>(credits: usize) -> (Sender<T>, Receiver<T>) {
+    Builder::new(credits).build()
+}
+
+/// Constructs a credit channel, with additional configuration.
+pub struct Builder<T> {
+    credits: usize,
+    name: Option<String>,
+    stop: Option<StopToken>,
+    undelivered: Undelivered<T>,
+    _t: PhantomData<fn() -> T>,
+}
+
+impl<T> Builder<T> {
+    /// Creates a builder for a channel with the given number of initial credits
+    pub fn new(credits: usize) -> Self {
+        Self {
+            credits,
+            name: None,
+            stop: None,
+            undelivered: Undelivered::new(),
+            _t: PhantomData,
+        }
+    }
+
+    /// Names the channel.  The name is attached to the channel's tracing span.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Closes the channel when the token's source is stopped.  Senders reject new messages,
+    /// and the receiver drains the buffered messages before observing the channel as closed.
+    pub fn stop_token(mut self, token: StopToken) -> Self {
+        self.stop = Some(token);
+        self
+    }
+
+    /// Attaches a dead-letter sink to the channel.  Messages which are still buffered when the channel is torn down
+    /// are forwarded to the sink without blocking.  If the sink is full or closed, the messages are passed to the `on_drop` hook.
+    ///
+    /// Messages sent after the channel closes are returned to the sender.  To also forward a copy to the sink,
+    /// use [dead_letter_rejected](#method.dead_letter_rejected).
+    pub fn dead_letter<S>(mut self, sink: S) -> Self
+    where
+        S: Sink<Item = DeadLetter<T>> + Send + 'static,
+    {
+        self.undelivered.set_dead_letter(sink);
+        self
+    }
+
+    /// Installs a hook which is called with each message still buffered when the channel is torn down,
+    /// so resources held by the messages can be released deterministically.
+    pub fn on_drop<F>(mut self, on_drop: F) -> Self
+    where
+        F: FnMut(T) + Send + 'static,
+    {
+        self.undelivered.set_on_drop(on_drop);
+        self
+    }
+
+    /// Constructs the pair of channel endpoints
+    pub fn build(self) -> (Sender<T>, Receiver<T>) {
+        #[cfg(feature = "debug")]
+        log::error!("Creating credit channel with {} credits", self.credits);
+        let tracer = Tracer::new("credit", self.name.as_deref(), None);
+        let metrics = MetricsHook::resolve(None, "credit", self.name.as_deref());
+        let registration = Registration::new("credit", self.name.as_deref(), None, false);
+        let (tx_shared, rx_shared) = shared(
+            StateExtension {
+                queue: SegQueue::new(),
+                credits: AtomicUsize::new(self.credits),
+                undelivered: self.undelivered,
+            },
+            tracer,
+            metrics,
+            registration,
+            self.stop,
+        );
+
+        let sender = Sender { shared: tx_shared };
+        let receiver = Receiver { shared: rx_shared };
+
+        (sender, receiver)
+    }
+}
+
+impl<T: Clone> Builder<T> {
+    /// Forwards a copy of each message sent after the channel closes to the dead-letter sink, tagged with `DeadLetterReason::Rejected`.
+    /// The send still returns the message, so the sender observes the closure.
+    pub fn dead_letter_rejected(mut self) -> Self {
+        self.undelivered.set_copy_rejected(Some(T::clone));
+        self
+    }
+}
+
+impl<T> fmt::Debug for Builder<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Builder")
+            .field("credits", &self.credits)
+            .field("name", &self.name)
+            .field("stop", &self.stop)
+            .field("undelivered", &self.undelivered)
+            .finish()
+    }
+}
+
+/// The sender half of a credit channel.  Can send messages with the `postage::Sink` trait.
+///
+/// Can be cloned.  Clones draw from the same credits.
+pub struct Sender<T> {
+    shared: SenderShared<StateExtension<T>>,
+}
+
+assert_impl_all!(Sender<String>: Clone, Send, Sync, fmt::Debug);
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Sink for Sender<T> {
+    type Item = T;
+
+    fn poll_send(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut crate::Context<'_>,
+        mut value: Self::Item,
+    ) -> PollSend<Self::Item> {
+        loop {
+            // take the guard before checking for closure, so a receiver drop or a grant between the checks
+            // and the subscription below expires the guard
+            let guard = self.shared.recv_guard();
+
+            if self.shared.is_closed() {
+                self.shared.tracer().reject();
+                if let Some(metrics) = self.shared.metrics() {
+                    metrics.on_reject();
+                }
+                return PollSend::Rejected(self.shared.extension().undelivered.reject(value));
+            }
+
+            value = match self.try_push(value) {
+                Ok(()) => return PollSend::Ready,
+                Err(value) => value,
+            };
+
+            self.shared.subscribe_recv(cx);
+
+            if guard.is_expired() {
+                continue;
+            }
+
+            self.shared.tracer().full();
+            if let Some(metrics) = self.shared.metrics() {
+                metrics.set_blocked_senders(self.shared.blocked_senders());
+            }
+            return PollSend::Pending(value);
+        }
+    }
+}
+
+#[cfg(feature = "futures-traits")]
+mod impl_futures {
+    use std::task::Poll;
+
+    use crate::sink::SendError;
+
+    impl<T> futures_sink::Sink<T> for super::Sender<T> {
+        type Error = SendError<T>;
+
+        fn poll_ready(
+            self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            loop {
+                let guard = self.shared.recv_guard();
+
+                if self.shared.is_closed() || self.credits() > 0 {
+                    return Poll::Ready(Ok(()));
+                }
+
+                let cx = cx.into();
+                self.shared.subscribe_recv(&cx);
+
+                if guard.is_expired() {
+                    continue;
+                }
+
+                return Poll::Pending;
+            }
+        }
+
+        fn start_send(self: std::pin::Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+            if self.shared.is_closed() {
+                self.shared.tracer().reject();
+                if let Some(metrics) = self.shared.metrics() {
+                    metrics.on_reject();
+                }
+                return Err(SendError(self.shared.extension().undelivered.reject(item)));
+            }
+
+            // another clone may have taken the credit since poll_ready
+            self.try_push(item).map_err(SendError)
+        }
+
+        fn poll_flush(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+}
+
+impl<T> Sender<T> {
+    /// Queues the message if a credit is available, and wakes the receiver.  Returns the message if no credit is available.
+    fn try_push(&self, value: T) -> Result<(), T> {
+        if !self.shared.extension().take_credit() {
+            return Err(value);
+        }
+
+        self.shared.extension().queue.push(value);
+        self.shared.tracer().send();
+        if let Some(metrics) = self.shared.metrics() {
+            metrics.on_send(None);
+        }
+        self.shared.notify_receivers();
+        Ok(())
+    }
+
+    /// The number of messages which can be sent before the receiver grants more credits
+    pub fn credits(&self) -> usize {
+        self.shared.extension().credits.load(Ordering::Acquire)
+    }
+
+    /// The number of sender tasks which are parked, waiting for credit.
+    ///
+    /// The count is approximate, as a task which is polled again before it is woken may be counted twice.
+    pub fn blocked_senders(&self) -> usize {
+        self.shared.blocked_senders()
+    }
+
+    /// Returns true if the receiver has been dropped.  Sends to a closed channel are rejected.
+    pub fn is_closed(&self) -> bool {
+        self.shared.is_closed()
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("credits", &self.credits())
+            .finish()
+    }
+}
+
+/// The receiver half of a credit channel.  Cannot be cloned.
+///
+/// Can receive messages with the `postage::Stream` trait.  Receiving a message does not grant a credit.
+pub struct Receiver<T> {
+    shared: ReceiverShared<StateExtension<T>>,
+}
+
+assert_impl_all!(Receiver<SendMessage>: Send, Sync, fmt::Debug);
+assert_not_impl_all!(Receiver<SendMessage>: Clone);
+
+impl<T> Stream for Receiver<T> {
+    type Item = T;
+
+    fn poll_recv(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut crate::Context<'_>,
+    ) -> PollRecv<Self::Item> {
+        loop {
+            let guard = self.shared.send_guard();
+
+            if let Some(value) = self.shared.extension().queue.pop() {
+                self.shared.tracer().recv();
+                if let Some(metrics) = self.shared.metrics() {
+                    metrics.on_recv(None, None);
+                }
+                return PollRecv::Ready(value);
+            }
+
+            if self.shared.is_closed() {
+                // a sender may have pushed a message, and then dropped, since the pop
+                if guard.is_expired() {
+                    continue;
+                }
+
+                return PollRecv::Closed;
+            }
+
+            self.shared.subscribe_send(cx);
+
+            if guard.is_expired() {
+                continue;
+            }
+
+            return PollRecv::Pending;
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Grants `n` credits to the senders, and wakes the senders which are waiting for credit
+    pub fn grant(&self, n: usize) {
+        if n == 0 {
+            return;
+        }
+
+        self.shared
+            .extension()
+            .credits
+            .fetch_add(n, Ordering::AcqRel);
+        self.shared.notify_senders();
+    }
+
+    /// The number of credits which the senders have not used
+    pub fn credits(&self) -> usize {
+        self.shared.extension().credits.load(Ordering::Acquire)
+    }
+
+    /// The number of receiver tasks which are parked, waiting for messages.
+    ///
+    /// The count is approximate, as a task which is polled again before it is woken may be counted twice.
+    pub fn waiting_receivers(&self) -> usize {
+        self.shared.waiting_receivers()
+    }
+
+    /// The number of messages which have been sent, and not yet received
+    pub fn len(&self) -> usize {
+        self.shared.extension().queue.len()
+    }
+
+    /// Returns true if no messages are waiting to be received
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if every sender has been dropped.  Messages which are still queued can be received.
+    pub fn is_closed(&self) -> bool {
+        self.shared.is_closed()
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("credits", &self.credits())
+            .finish()
+    }
+}
+
+struct StateExtension<T> {
+    queue: SegQueue<T>,
+    credits: AtomicUsize,
+    undelivered: Undelivered<T>,
+}
+
+impl<T> StateExtension<T> {
+    /// Consumes a credit for a message.  Returns false if no credit is available.
+    fn take_credit(&self) -> bool {
+        self.credits
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |credits| {
+                credits.checked_sub(1)
+            })
+            .is_ok()
+    }
+}
+
+impl<T> Drop for StateExtension<T> {
+    fn drop(&mut self) {
+        if !self.undelivered.is_some() {
+            return;
+        }
+
+        while let Some(value) = self.queue.pop() {
+            self.undelivered
+                .release(value, DeadLetterReason::Undelivered);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+
+    use futures_test::task::new_count_waker;
+
+    use super::{channel, Builder};
+    use crate::{
+        dead_letter::{DeadLetter, DeadLetterReason},
+        sink::{PollSend, Sink, TrySendError},
+        stop::StopSource,
+        stream::{Stream, TryRecvError},
+    };
+
+    #[test]
+    fn sends_against_credits() {
+        let (mut tx, mut rx) = channel(2);
+        assert_eq!(2, tx.credits());
+
+        tx.try_send(1usize).unwrap();
+        tx.try_send(2).unwrap();
+        assert_eq!(Err(TrySendError::Pending(3)), tx.try_send(3));
+        assert_eq!(0, rx.credits());
+        assert_eq!(2, rx.len());
+
+        // receiving does not return the credit
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(Err(TrySendError::Pending(3)), tx.try_send(3));
+
+        rx.grant(1);
+        assert_eq!(1, tx.credits());
+        tx.try_send(3).unwrap();
+        assert_eq!(Ok(2), rx.try_recv());
+        assert_eq!(Ok(3), rx.try_recv());
+    }
+
+    #[test]
+    fn grant_wakes_sender() {
+        let (mut tx, rx) = channel(0);
+
+        let (waker, count) = new_count_waker();
+        let mut cx = std::task::Context::from_waker(&waker).into();
+        assert_eq!(
+            PollSend::Pending(1usize),
+            Pin::new(&mut tx).poll_send(&mut cx, 1)
+        );
+
+        rx.grant(1);
+        assert_eq!(1, count.get());
+        assert_eq!(PollSend::Ready, Pin::new(&mut tx).poll_send(&mut cx, 1));
+    }
+
+    #[test]
+    fn clones_share_credits() {
+        let (mut tx, _rx) = channel(1);
+        let mut tx2 = tx.clone();
+
+        tx.try_send(1usize).unwrap();
+        assert_eq!(Err(TrySendError::Pending(2)), tx2.try_send(2));
+    }
+
+    #[test]
+    fn closure() {
+        let (mut tx, mut rx) = channel(4);
+        tx.try_send(1usize).unwrap();
+        drop(tx);
+
+        assert!(rx.is_closed());
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Closed), rx.try_recv());
+
+        let (mut tx, rx) = channel(4);
+        drop(rx);
+        assert!(tx.is_closed());
+        assert_eq!(Err(TrySendError::Rejected(1usize)), tx.try_send(1));
+    }
+
+    #[test]
+    fn stop_token() {
+        let stop = StopSource::new();
+        let (mut tx, mut rx) = Builder::new(1).stop_token(stop.token()).build();
+        tx.try_send(1usize).unwrap();
+
+        let (waker, count) = new_count_waker();
+        let mut cx = std::task::Context::from_waker(&waker).into();
+        assert_eq!(
+            PollSend::Pending(2),
+            Pin::new(&mut tx).poll_send(&mut cx, 2)
+        );
+
+        stop.stop();
+        assert_eq!(1, count.get());
+        assert_eq!(
+            PollSend::Rejected(2),
+            Pin::new(&mut tx).poll_send(&mut cx, 2)
+        );
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Closed), rx.try_recv());
+    }
+
+    #[test]
+    fn dead_letter_undelivered() {
+        let (dead_tx, mut dead_rx) = crate::mpsc::channel(4);
+        let (mut tx, mut rx) = Builder::new(4).dead_letter(dead_tx).build();
+
+        tx.try_send(1usize).unwrap();
+        tx.try_send(2).unwrap();
+        assert_eq!(Ok(1), rx.try_recv());
+
+        drop(tx);
+        drop(rx);
+
+        assert_eq!(
+            Ok(DeadLetter {
+                value: 2,
+                reason: DeadLetterReason::Undelivered
+            }),
+            dead_rx.try_recv()
+        );
+        assert!(dead_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn dead_letter_rejected() {
+        let (dead_tx, mut dead_rx) = crate::mpsc::channel(4);
+        let (mut tx, rx) = Builder::new(4)
+            .dead_letter(dead_tx)
+            .dead_letter_rejected()
+            .build();
+        drop(rx);
+
+        // the message is returned to the sender, and a copy is forwarded to the dead-letter sink
+        assert_eq!(
+            Err(crate::sink::TrySendError::Rejected(1)),
+            tx.try_send(1usize)
+        );
+        assert_eq!(
+            Ok(DeadLetter {
+                value: 1,
+                reason: DeadLetterReason::Rejected
+            }),
+            dead_rx.try_recv()
+        );
+    }
+
+    #[test]
+    fn on_drop_undelivered() {
+        let dropped = std::sync::Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let hook = dropped.clone();
+        let (mut tx, rx) = Builder::new(4)
+            .on_drop(move |message| hook.lock().push(message))
+            .build();
+
+        tx.try_send(1usize).unwrap();
+        tx.try_send(2).unwrap();
+        drop(tx);
+        drop(rx);
+
+        assert_eq!(vec![1, 2], *dropped.lock());
+    }
+}
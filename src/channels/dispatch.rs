@@ -2,29 +2,224 @@
 //!
 //! Senders and recievers can be cloned, and additional recievers can be created with `tx.subscribe()`
 //!
+//! By default, receivers share a single queue, and each message is taken by whichever receiver polls first.  Receivers can be added
+//! or dropped at runtime, and an idle receiver immediately picks up queued work, so a consumer pool can scale
+//! without rebalancing.  `Sender::len` and `Sender::receiver_count` report the backlog and pool size for autoscaling decisions.
+//!
+//! The [Distribution](./enum.Distribution.html) strategy can instead assign each message to a receiver as it is sent,
+//! in turn or to the least-loaded receiver.  A receiver which runs out of assigned work steals messages from busy peers,
+//! and the messages assigned to a dropped receiver are handed to the remaining receivers.
+//!
 //! The producer can be cloned, and the sender task is suspended if the channel becomes full.
 
-use std::fmt;
+use std::{
+    fmt, future::Future, marker::PhantomData, pin::Pin, sync::Arc, task::Poll, time::Duration,
+};
 
+use self::queue::{Lane, Queue};
 use super::SendMessage;
 use crate::{
-    sink::{PollSend, Sink},
+    dead_letter::{DeadLetter, DeadLetterReason, Undelivered},
+    metrics::{ChannelMetrics, MetricsHook},
+    registry::Registration,
+    sink::{BufferedSink, PollReady, PollSend, Sink},
+    stop::StopToken,
     stream::{PollRecv, Stream},
-    sync::{shared, ReceiverShared, SenderShared},
+    sync::{envelope::Envelope, primitives, shared, ReceiverShared, SenderShared},
+    trace::Tracer,
+    watermark::{Watermark, Watermarks},
 };
-use crossbeam_queue::ArrayQueue;
 use static_assertions::assert_impl_all;
 
+mod queue;
+
 /// Constructs a pair of dispatch endpoints, with a fixed-size buffer of the given capacity
 pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
-    #[cfg(feature = "debug")]
-    log::error!("Creating dispatch channel with capacity {}", capacity);
-    let (tx_shared, rx_shared) = shared(StateExtension::new(capacity));
-    let sender = Sender { shared: tx_shared };
+    Builder::new(capacity).build()
+}
+
+/// Constructs a dispatch channel, with additional configuration.
+///
+/// ```rust
+/// use postage::dispatch;
+///
+/// let (tx, rx) = dispatch::Builder::<usize>::new(16).name("jobs").build();
+/// ```
+pub struct Builder<T> {
+    capacity: usize,
+    distribution: Distribution,
+    name: Option<String>,
+    metrics: Option<Arc<dyn ChannelMetrics>>,
+    stop: Option<StopToken>,
+    undelivered: Undelivered<T>,
+    watermarks: Option<Watermarks>,
+    ttl: Option<Duration>,
+    track_age: bool,
+    _t: PhantomData<fn() -> T>,
+}
+
+impl<T> Builder<T> {
+    /// Creates a builder for a channel with a fixed-size buffer of the given capacity
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            distribution: Distribution::Shared,
+            name: None,
+            metrics: None,
+            stop: None,
+            undelivered: Undelivered::new(),
+            watermarks: None,
+            ttl: None,
+            track_age: false,
+            _t: PhantomData,
+        }
+    }
+
+    /// Chooses how messages are distributed among the receivers.  Defaults to `Distribution::Shared`.
+    ///
+    /// ```rust
+    /// use postage::dispatch::{self, Distribution};
+    ///
+    /// let (tx, rx) = dispatch::Builder::<usize>::new(16)
+    ///     .distribution(Distribution::LeastLoaded)
+    ///     .build();
+    /// ```
+    pub fn distribution(mut self, distribution: Distribution) -> Self {
+        self.distribution = distribution;
+        self
+    }
 
-    let receiver = Receiver { shared: rx_shared };
+    /// Names the channel.  The name is attached to the channel's tracing span.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Installs a metrics hook on the channel, which takes precedence over the global hook.
+    pub fn metrics(mut self, metrics: Arc<dyn ChannelMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Closes the channel when the token's source is stopped.  Senders reject new messages,
+    /// and receivers drain the buffered messages before observing the channel as closed.
+    pub fn stop_token(mut self, token: StopToken) -> Self {
+        self.stop = Some(token);
+        self
+    }
+
+    /// Discards messages which have been in the buffer for longer than `ttl`, instead of delivering them.
+    /// Expired messages are passed to the dead-letter sink or the `on_drop` hook.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Timestamps messages as they are sent, so the receiver can observe their age with `recv_with_age`.
+    pub fn track_age(mut self) -> Self {
+        self.track_age = true;
+        self
+    }
+
+    /// Configures high and low watermarks on the buffer depth.  The callback is invoked with `Watermark::High`
+    /// when the depth reaches `high`, and with `Watermark::Low` when the depth then falls to `low`.
+    ///
+    /// Panics if `low` is not less than `high`.
+    pub fn watermarks<F>(mut self, high: usize, low: usize, callback: F) -> Self
+    where
+        F: Fn(Watermark) + Send + Sync + 'static,
+    {
+        self.watermarks = Some(Watermarks::new(high, low, callback));
+        self
+    }
+
+    /// Attaches a dead-letter sink to the channel.  Messages which are still buffered when the channel is torn down
+    /// are forwarded to the sink without blocking.  If the sink is full or closed, the messages are passed to the `on_drop` hook.
+    ///
+    /// Messages sent after the channel closes are returned to the sender.  To also forward a copy to the sink,
+    /// use [dead_letter_rejected](#method.dead_letter_rejected).
+    pub fn dead_letter<S>(mut self, sink: S) -> Self
+    where
+        S: Sink<Item = DeadLetter<T>> + Send + 'static,
+    {
+        self.undelivered.set_dead_letter(sink);
+        self
+    }
+
+    /// Installs a hook which is called with each message still buffered when the channel is torn down,
+    /// so resources held by the messages can be released deterministically.
+    pub fn on_drop<F>(mut self, on_drop: F) -> Self
+    where
+        F: FnMut(T) + Send + 'static,
+    {
+        self.undelivered.set_on_drop(on_drop);
+        self
+    }
 
-    (sender, receiver)
+    /// Constructs the pair of channel endpoints
+    pub fn build(self) -> (Sender<T>, Receiver<T>) {
+        #[cfg(feature = "debug")]
+        log::error!("Creating dispatch channel with capacity {}", self.capacity);
+        let tracer = Tracer::new("dispatch", self.name.as_deref(), Some(self.capacity));
+        let metrics = MetricsHook::resolve(self.metrics, "dispatch", self.name.as_deref());
+        let registration =
+            Registration::new("dispatch", self.name.as_deref(), Some(self.capacity), true);
+        let (tx_shared, rx_shared) = shared(
+            StateExtension {
+                queue: Queue::new(self.capacity, self.distribution),
+                undelivered: self.undelivered,
+                watermarks: self.watermarks,
+                ttl: self.ttl,
+                timestamps: self.track_age || self.ttl.is_some() || metrics.is_some(),
+            },
+            tracer,
+            metrics,
+            registration,
+            self.stop,
+        );
+        let sender = Sender { shared: tx_shared };
+
+        let receiver = Receiver::new(rx_shared);
+
+        (sender, receiver)
+    }
+}
+
+impl<T: Clone> Builder<T> {
+    /// Forwards a copy of each message sent after the channel closes to the dead-letter sink, tagged with `DeadLetterReason::Rejected`.
+    /// The send still returns the message, so the sender observes the closure.
+    pub fn dead_letter_rejected(mut self) -> Self {
+        self.undelivered.set_copy_rejected(Some(T::clone));
+        self
+    }
+}
+
+impl<T> fmt::Debug for Builder<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Builder")
+            .field("capacity", &self.capacity)
+            .field("distribution", &self.distribution)
+            .field("name", &self.name)
+            .field("metrics", &self.metrics.is_some())
+            .field("stop", &self.stop)
+            .field("undelivered", &self.undelivered)
+            .field("watermarks", &self.watermarks)
+            .field("ttl", &self.ttl)
+            .field("track_age", &self.track_age)
+            .finish()
+    }
+}
+
+/// How a dispatch channel distributes messages among its receivers.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Distribution {
+    /// Receivers share a single queue, and each message is taken by whichever receiver polls first
+    #[default]
+    Shared,
+    /// Each message is assigned to the next receiver in turn
+    RoundRobin,
+    /// Each message is assigned to the receiver with the fewest assigned messages
+    LeastLoaded,
 }
 
 /// The sender half of a dispatch channel.  Can send messages with the `postage::Sink` trait.
@@ -53,25 +248,39 @@ impl<T> Sink for Sender<T> {
         mut value: Self::Item,
     ) -> PollSend<Self::Item> {
         loop {
+            // take the guard before checking for closure, so a receiver drop between the check
+            // and the subscription below expires the guard
+            let guard = self.shared.recv_guard();
+
             if self.shared.is_closed() {
-                return PollSend::Rejected(value);
+                self.shared.tracer().reject();
+                if let Some(metrics) = self.shared.metrics() {
+                    metrics.on_reject();
+                }
+                return PollSend::Rejected(self.shared.extension().undelivered.reject(value));
             }
 
             let queue = &self.shared.extension().queue;
-            let guard = self.shared.recv_guard();
 
-            match queue.push(value) {
+            let envelope = Envelope::new(value, self.shared.extension().timestamps);
+            match queue.push(envelope) {
                 Ok(_) => {
+                    self.record_send();
                     self.shared.notify_receivers();
                     return PollSend::Ready;
                 }
                 Err(v) => {
+                    let v = v.into_inner();
                     self.shared.subscribe_recv(cx);
                     if guard.is_expired() {
                         value = v;
                         continue;
                     }
 
+                    self.shared.tracer().full();
+                    if let Some(metrics) = self.shared.metrics() {
+                        metrics.set_blocked_senders(self.shared.blocked_senders());
+                    }
                     return PollSend::Pending(v);
                 }
             }
@@ -79,6 +288,60 @@ impl<T> Sink for Sender<T> {
     }
 }
 
+impl<T> BufferedSink for Sender<T> {
+    fn poll_ready(self: std::pin::Pin<&mut Self>, cx: &mut crate::Context<'_>) -> PollReady {
+        loop {
+            let guard = self.shared.recv_guard();
+
+            if self.shared.is_closed() {
+                return PollReady::Closed;
+            }
+
+            if !self.shared.extension().queue.is_full() {
+                return PollReady::Ready;
+            }
+
+            self.shared.subscribe_recv(cx);
+
+            if guard.is_expired() {
+                continue;
+            }
+
+            return PollReady::Pending;
+        }
+    }
+
+    /// Messages are delivered as they are sent, so the flush completes immediately
+    fn poll_flush(self: std::pin::Pin<&mut Self>, _cx: &mut crate::Context<'_>) -> PollReady {
+        if self.shared.is_closed() {
+            PollReady::Closed
+        } else {
+            PollReady::Ready
+        }
+    }
+
+    /// The channel closes when all senders are dropped, so the close is equivalent to a flush
+    fn poll_close(self: std::pin::Pin<&mut Self>, cx: &mut crate::Context<'_>) -> PollReady {
+        self.poll_flush(cx)
+    }
+}
+
+impl<T> Sender<T> {
+    fn record_send(&self) {
+        let extension = self.shared.extension();
+        let depth = extension.queue.len();
+
+        self.shared.tracer().send();
+        if let Some(metrics) = self.shared.metrics() {
+            metrics.on_send(Some(depth));
+        }
+        self.shared.registration().set_depth(depth);
+        if let Some(ref watermarks) = extension.watermarks {
+            watermarks.on_send(depth);
+        }
+    }
+}
+
 impl<T> fmt::Debug for Sender<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Sender").finish()
@@ -87,10 +350,10 @@ impl<T> fmt::Debug for Sender<T> {
 
 #[cfg(feature = "futures-traits")]
 mod impl_futures {
-    use crate::sink::SendError;
+    use crate::{sink::SendError, sync::envelope::Envelope};
     use std::task::Poll;
 
-    impl<T> futures::sink::Sink<T> for super::Sender<T> {
+    impl<T> futures_sink::Sink<T> for super::Sender<T> {
         type Error = SendError<T>;
 
         fn poll_ready(
@@ -98,16 +361,17 @@ mod impl_futures {
             cx: &mut std::task::Context<'_>,
         ) -> Poll<Result<(), Self::Error>> {
             loop {
+                let guard = self.shared.recv_guard();
+
                 if self.shared.is_closed() {
                     return Poll::Ready(Ok(()));
                 }
 
                 let queue = &self.shared.extension().queue;
-                let guard = self.shared.recv_guard();
 
                 if queue.is_full() {
-                    let mut cx = cx.into();
-                    self.shared.subscribe_recv(&mut cx);
+                    let cx = cx.into();
+                    self.shared.subscribe_recv(&cx);
 
                     if guard.is_expired() {
                         continue;
@@ -122,17 +386,20 @@ mod impl_futures {
 
         fn start_send(self: std::pin::Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
             if self.shared.is_closed() {
-                return Err(SendError(item));
+                if let Some(metrics) = self.shared.metrics() {
+                    metrics.on_reject();
+                }
+                return Err(SendError(self.shared.extension().undelivered.reject(item)));
             }
 
-            let result = self
-                .shared
-                .extension()
-                .queue
-                .push(item)
-                .map_err(|item| SendError(item));
+            let queue = &self.shared.extension().queue;
+            let envelope = Envelope::new(item, self.shared.extension().timestamps);
+            let result = queue
+                .push(envelope)
+                .map_err(|envelope| SendError(envelope.into_inner()));
 
             if result.is_ok() {
+                self.record_send();
                 self.shared.notify_receivers();
             }
 
@@ -158,17 +425,47 @@ mod impl_futures {
 impl<T> Sender<T> {
     /// Creates a new Receiver that listens to this channel.
     pub fn subscribe(&self) -> Receiver<T> {
-        Receiver {
-            shared: self.shared.clone_receiver(),
-        }
+        Receiver::new(self.shared.clone_receiver())
+    }
+
+    /// Returns true if every receiver has been dropped, or the channel has been stopped.  Sends to a closed channel are rejected.
+    pub fn is_closed(&self) -> bool {
+        self.shared.is_closed()
+    }
+
+    /// The number of messages waiting in the buffer
+    pub fn len(&self) -> usize {
+        self.shared.extension().queue.len()
+    }
+
+    /// Returns true if no messages are waiting in the buffer
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of receivers which are consuming from the channel
+    pub fn receiver_count(&self) -> usize {
+        self.shared.receiver_count()
+    }
+
+    /// The number of sender tasks which are parked, waiting for capacity.
+    ///
+    /// The count is approximate, as a task which is polled again before it is woken may be counted twice.
+    /// A count which stays above zero shows that producers are queuing up, and more receivers may be needed.
+    pub fn blocked_senders(&self) -> usize {
+        self.shared.blocked_senders()
     }
 }
 
 /// The receiver half of a dispatch channel.
 ///
 /// Can receive messages with the `postage::Stream` trait.
+///
+/// With the `RoundRobin` and `LeastLoaded` distributions, messages are assigned to the receiver as they are sent.
+/// While the receiver's task is waiting for messages, its assigned messages are left for it.  Otherwise, idle peers can steal them.
 pub struct Receiver<T> {
     shared: ReceiverShared<StateExtension<T>>,
+    lane: Option<primitives::Arc<Lane<T>>>,
 }
 
 assert_impl_all!(Receiver<SendMessage>: Clone, Send, Sync, fmt::Debug);
@@ -180,38 +477,251 @@ impl<T> Stream for Receiver<T> {
         self: std::pin::Pin<&mut Self>,
         cx: &mut crate::Context<'_>,
     ) -> PollRecv<Self::Item> {
+        match self.poll_envelope(cx) {
+            PollRecv::Ready(envelope) => PollRecv::Ready(envelope.into_inner()),
+            PollRecv::Pending => PollRecv::Pending,
+            PollRecv::Closed => PollRecv::Closed,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.shared.extension().queue.len();
+
+        // other receivers may take the buffered messages, so only the upper bound is known
+        if self.shared.is_closed() {
+            (0, Some(len))
+        } else {
+            (0, None)
+        }
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.shared.clone())
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        if let Some(ref lane) = self.lane {
+            self.shared.extension().queue.leave(lane);
+            // the remaining receivers may be parked, waiting for the messages which were handed over
+            self.shared.notify_receivers();
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    fn new(shared: ReceiverShared<StateExtension<T>>) -> Self {
+        let lane = shared.extension().queue.join();
+        Self { shared, lane }
+    }
+
+    /// Returns true if every sender has been dropped, or the channel has been stopped.  Messages which are still queued can be received.
+    pub fn is_closed(&self) -> bool {
+        self.shared.is_closed()
+    }
+
+    /// The number of receivers which are consuming from the channel, including this one
+    pub fn receiver_count(&self) -> usize {
+        self.shared.receiver_count()
+    }
+
+    /// The number of receiver tasks which are parked, waiting for messages.
+    ///
+    /// The count is approximate, as a task which is polled again before it is woken may be counted twice.
+    /// Idle receivers show that the pool has spare consumers.
+    pub fn waiting_receivers(&self) -> usize {
+        self.shared.waiting_receivers()
+    }
+
+    /// Discards the queued messages, and wakes any senders which were waiting for capacity.
+    /// Returns the number of messages which were discarded.
+    ///
+    /// Discarded messages are passed to the dead-letter sink, tagged with `DeadLetterReason::Cleared`, or to the `on_drop` hook.
+    /// Messages which are sent while the channel is being cleared may be kept.
+    pub fn clear(&self) -> usize {
+        let extension = self.shared.extension();
+
+        let mut cleared = 0;
+        for _ in 0..extension.queue.len() {
+            let envelope = match extension.queue.pop_any() {
+                Some(envelope) => envelope,
+                None => break,
+            };
+
+            extension
+                .undelivered
+                .release(envelope.into_inner(), DeadLetterReason::Cleared);
+            cleared += 1;
+        }
+
+        if cleared > 0 {
+            let depth = extension.queue.len();
+            self.shared.registration().set_depth(depth);
+            if let Some(ref watermarks) = extension.watermarks {
+                watermarks.on_recv(depth);
+            }
+
+            self.shared.notify_senders();
+        }
+
+        cleared
+    }
+
+    /// Receives a message, or returns None if the channel is closed.
+    ///
+    /// This shadows `Stream::recv`.  If the future is dropped while it is waiting, for example by a `select!` which
+    /// completes another branch, the messages assigned to this receiver can be taken by its peers.
+    pub fn recv(&mut self) -> RecvFuture<'_, T> {
+        RecvFuture {
+            receiver: Unpark(self),
+        }
+    }
+
+    /// Receives a message, along with the time it spent in the channel buffer.
+    ///
+    /// The age is only available if the channel was constructed with `Builder::track_age`, `Builder::ttl`, or a metrics hook.
+    pub async fn recv_with_age(&mut self) -> Option<(T, Option<Duration>)> {
+        let receiver = Unpark(self);
+        std::future::poll_fn(|cx| {
+            let mut cx = cx.into();
+            match Pin::new(&mut *receiver.0).poll_recv_with_age(&mut cx) {
+                PollRecv::Ready(value) => Poll::Ready(Some(value)),
+                PollRecv::Pending => Poll::Pending,
+                PollRecv::Closed => Poll::Ready(None),
+            }
+        })
+        .await
+    }
+
+    /// Attempts to receive a message, along with the time it spent in the channel buffer.
+    ///
+    /// The age is only available if the channel was constructed with `Builder::track_age`, `Builder::ttl`, or a metrics hook.
+    pub fn poll_recv_with_age(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut crate::Context<'_>,
+    ) -> PollRecv<(T, Option<Duration>)> {
+        match self.poll_envelope(cx) {
+            PollRecv::Ready(envelope) => {
+                let age = envelope.age();
+                PollRecv::Ready((envelope.into_inner(), age))
+            }
+            PollRecv::Pending => PollRecv::Pending,
+            PollRecv::Closed => PollRecv::Closed,
+        }
+    }
+
+    /// Leaves the assigned messages to peers, as the receiver's task is no longer waiting for them
+    fn unpark(&self) {
+        if let Some(ref lane) = self.lane {
+            if lane.unpark() {
+                self.shared.notify_receivers();
+            }
+        }
+    }
+
+    fn poll_envelope(&self, cx: &mut crate::Context<'_>) -> PollRecv<Envelope<T>> {
+        if let Some(ref lane) = self.lane {
+            lane.set_parked(false);
+        }
+
         loop {
             let guard = self.shared.send_guard();
-            match self.shared.extension().queue.pop() {
-                Some(v) => {
+            let extension = self.shared.extension();
+            match extension.queue.pop(self.lane.as_deref()) {
+                Some(envelope) if extension.is_expired(&envelope) => {
+                    self.shared.tracer().expired();
                     self.shared.notify_senders();
-                    return PollRecv::Ready(v);
+                    extension
+                        .undelivered
+                        .release(envelope.into_inner(), DeadLetterReason::Expired);
+                }
+                Some(envelope) => {
+                    self.record_recv(&envelope);
+                    self.shared.notify_senders();
+                    if let Some(metrics) = self.shared.metrics() {
+                        metrics.set_blocked_senders(self.shared.blocked_senders());
+                    }
+                    return PollRecv::Ready(envelope);
                 }
                 None => {
                     if self.shared.is_closed() {
+                        // a sender may have pushed a message, and then dropped, since the pop
+                        if guard.is_expired() {
+                            continue;
+                        }
+
                         return PollRecv::Closed;
                     }
 
                     self.shared.subscribe_send(cx);
+
                     if guard.is_expired() {
                         continue;
                     }
 
+                    // a task which will be woken keeps its assigned messages, rather than losing them to peers
+                    if let (Some(lane), Some(_)) = (&self.lane, cx.waker()) {
+                        lane.set_parked(true);
+                    }
+
                     return PollRecv::Pending;
                 }
             }
         }
     }
+
+    fn record_recv(&self, envelope: &Envelope<T>) {
+        let extension = self.shared.extension();
+        let depth = extension.queue.len();
+
+        self.shared.tracer().recv();
+        if let Some(metrics) = self.shared.metrics() {
+            metrics.on_recv(Some(depth), envelope.age());
+        }
+        self.shared.registration().set_depth(depth);
+        if let Some(ref watermarks) = extension.watermarks {
+            watermarks.on_recv(depth);
+        }
+    }
 }
 
-impl<T> Clone for Receiver<T> {
-    fn clone(&self) -> Self {
-        Self {
-            shared: self.shared.clone(),
+/// A future returned by `Receiver::recv`.
+#[must_use = "futures do nothing unless polled"]
+pub struct RecvFuture<'r, T> {
+    receiver: Unpark<'r, T>,
+}
+
+impl<T> Future for RecvFuture<'_, T> {
+    type Output = Option<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        let mut cx = cx.into();
+        match Pin::new(&mut *self.receiver.0).poll_recv(&mut cx) {
+            PollRecv::Ready(value) => Poll::Ready(Some(value)),
+            PollRecv::Pending => Poll::Pending,
+            PollRecv::Closed => Poll::Ready(None),
         }
     }
 }
 
+impl<T> fmt::Debug for RecvFuture<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecvFuture").finish()
+    }
+}
+
+/// Unparks the receiver when a receive future is dropped, so a cancelled receive doesn't strand its assigned messages
+struct Unpark<'r, T>(&'r mut Receiver<T>);
+
+impl<T> Drop for Unpark<'_, T> {
+    fn drop(&mut self) {
+        self.0.unpark();
+    }
+}
+
 impl<T> fmt::Debug for Receiver<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Receiver").finish()
@@ -219,31 +729,52 @@ impl<T> fmt::Debug for Receiver<T> {
 }
 
 struct StateExtension<T> {
-    queue: ArrayQueue<T>,
+    queue: Queue<T>,
+    undelivered: Undelivered<T>,
+    watermarks: Option<Watermarks>,
+    ttl: Option<Duration>,
+    timestamps: bool,
 }
 
 impl<T> StateExtension<T> {
-    pub fn new(capacity: usize) -> Self {
-        Self {
-            queue: ArrayQueue::new(capacity),
+    fn is_expired(&self, envelope: &Envelope<T>) -> bool {
+        match (self.ttl, envelope.age()) {
+            (Some(ttl), Some(age)) => age > ttl,
+            _ => false,
+        }
+    }
+}
+
+impl<T> Drop for StateExtension<T> {
+    fn drop(&mut self) {
+        if !self.undelivered.is_some() {
+            return;
+        }
+
+        while let Some(envelope) = self.queue.pop_any() {
+            self.undelivered
+                .release(envelope.into_inner(), DeadLetterReason::Undelivered);
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{pin::Pin, task::Context};
+    use std::{pin::Pin, sync::Arc, task::Context, time::Duration};
+
+    use parking_lot::Mutex;
 
     use crate::{
+        dead_letter::{DeadLetter, DeadLetterReason},
         sink::{PollSend, Sink},
         stream::{PollRecv, Stream},
         test::{noop_context, panic_context},
     };
     use futures_test::task::new_count_waker;
 
-    use super::{channel, Receiver, Sender};
+    use super::{channel, Builder, Distribution, Receiver, Sender};
 
-    fn pin<'a, 'b>(
+    fn pin(
         chan: &mut (Sender<Message>, Receiver<Message>),
     ) -> (Pin<&mut Sender<Message>>, Pin<&mut Receiver<Message>>) {
         let tx = Pin::new(&mut chan.0);
@@ -252,7 +783,7 @@ mod tests {
         (tx, rx)
     }
 
-    #[derive(Debug, PartialEq, Eq)]
+    #[derive(Clone, Debug, PartialEq, Eq)]
     struct Message(usize);
 
     #[test]
@@ -264,6 +795,80 @@ mod tests {
         assert_eq!(PollSend::Ready, tx.poll_send(&mut cx, Message(1)));
     }
 
+    #[test]
+    fn scaling_introspection() {
+        let mut cx = noop_context();
+        let (mut tx, mut rx) = channel(4);
+
+        assert_eq!(1, tx.receiver_count());
+        assert!(tx.is_empty());
+
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, Message(1))
+        );
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, Message(2))
+        );
+        assert_eq!(2, tx.len());
+
+        let mut rx2 = tx.subscribe();
+        assert_eq!(2, tx.receiver_count());
+        assert_eq!(2, rx.receiver_count());
+
+        assert_eq!(
+            PollRecv::Ready(Message(1)),
+            Pin::new(&mut rx2).poll_recv(&mut cx)
+        );
+        drop(rx2);
+        assert_eq!(1, tx.receiver_count());
+
+        assert_eq!(
+            PollRecv::Ready(Message(2)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        assert!(tx.is_empty());
+    }
+
+    #[test]
+    fn waiting_task_counts() {
+        let (waker, _count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker).into();
+        let (mut tx, mut rx) = channel(1);
+        let mut rx2 = rx.clone();
+        assert_eq!(0, rx.waiting_receivers());
+
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx).poll_recv(&mut cx));
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx2).poll_recv(&mut cx));
+        assert_eq!(2, rx.waiting_receivers());
+
+        // the send wakes both receivers
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, Message(1))
+        );
+        assert_eq!(0, rx.waiting_receivers());
+        assert_eq!(0, tx.blocked_senders());
+
+        let mut tx2 = tx.clone();
+        assert_eq!(
+            PollSend::Pending(Message(2)),
+            Pin::new(&mut tx).poll_send(&mut cx, Message(2))
+        );
+        assert_eq!(
+            PollSend::Pending(Message(3)),
+            Pin::new(&mut tx2).poll_send(&mut cx, Message(3))
+        );
+        assert_eq!(2, tx.blocked_senders());
+
+        assert_eq!(
+            PollRecv::Ready(Message(1)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        assert_eq!(0, tx.blocked_senders());
+    }
+
     #[test]
     fn send_blocks() {
         let mut cx = panic_context();
@@ -313,6 +918,167 @@ mod tests {
         );
     }
 
+    #[test]
+    fn builder_send_recv() {
+        let mut cx = panic_context();
+        let (mut tx, mut rx) = Builder::new(1).name("builder").build();
+
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, Message(1))
+        );
+        assert_eq!(
+            PollSend::Pending(Message(2)),
+            Pin::new(&mut tx).poll_send(&mut noop_context(), Message(2))
+        );
+        assert_eq!(
+            PollRecv::Ready(Message(1)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+    }
+
+    #[test]
+    fn dead_letter_undelivered() {
+        let (dead_tx, mut dead_rx) = crate::mpsc::channel(4);
+        let (mut tx, mut rx) = Builder::new(4).dead_letter(dead_tx).build();
+
+        tx.try_send(Message(1)).unwrap();
+        tx.try_send(Message(2)).unwrap();
+        assert_eq!(Ok(Message(1)), rx.try_recv());
+
+        drop(tx);
+        drop(rx);
+
+        assert_eq!(
+            Ok(DeadLetter {
+                value: Message(2),
+                reason: DeadLetterReason::Undelivered
+            }),
+            dead_rx.try_recv()
+        );
+        assert!(dead_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn dead_letter_rejected() {
+        let (dead_tx, mut dead_rx) = crate::mpsc::channel(1);
+        let (mut tx, rx) = Builder::new(4)
+            .dead_letter(dead_tx)
+            .dead_letter_rejected()
+            .build();
+        drop(rx);
+
+        // the message is returned to the sender, and a copy is forwarded to the dead-letter sink
+        assert_eq!(
+            Err(crate::sink::TrySendError::Rejected(Message(1))),
+            tx.try_send(Message(1))
+        );
+        assert_eq!(
+            Ok(DeadLetter {
+                value: Message(1),
+                reason: DeadLetterReason::Rejected
+            }),
+            dead_rx.try_recv()
+        );
+
+        // once the dead-letter channel is full, the copy is discarded
+        assert_eq!(
+            Err(crate::sink::TrySendError::Rejected(Message(2))),
+            tx.try_send(Message(2))
+        );
+        assert_eq!(
+            Err(crate::sink::TrySendError::Rejected(Message(3))),
+            tx.try_send(Message(3))
+        );
+        assert_eq!(Message(2), dead_rx.try_recv().unwrap().value);
+        assert!(dead_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn on_drop_undelivered() {
+        let dropped = Arc::new(Mutex::new(Vec::new()));
+        let hook = dropped.clone();
+        let (dead_tx, dead_rx) = crate::mpsc::channel(1);
+        let (mut tx, rx) = Builder::new(4)
+            .dead_letter(dead_tx)
+            .on_drop(move |message| hook.lock().push(message))
+            .build();
+
+        tx.try_send(Message(1)).unwrap();
+        tx.try_send(Message(2)).unwrap();
+        drop(tx);
+        drop(rx);
+
+        // the dead-letter channel accepts the first message, and is then full
+        assert_eq!(vec![Message(2)], *dropped.lock());
+        drop(dead_rx);
+    }
+
+    #[test]
+    fn clear() {
+        let dropped = Arc::new(Mutex::new(Vec::new()));
+        let hook = dropped.clone();
+        let (mut tx, mut rx) = Builder::new(2)
+            .on_drop(move |message| hook.lock().push(message))
+            .build();
+
+        tx.try_send(Message(1)).unwrap();
+        tx.try_send(Message(2)).unwrap();
+
+        let (waker, count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker).into();
+        assert_eq!(
+            PollSend::Pending(Message(3)),
+            Pin::new(&mut tx).poll_send(&mut cx, Message(3))
+        );
+
+        assert_eq!(2, rx.clear());
+        assert_eq!(vec![Message(1), Message(2)], *dropped.lock());
+        assert_eq!(1, count.get());
+
+        tx.try_send(Message(3)).unwrap();
+        assert_eq!(Ok(Message(3)), rx.try_recv());
+        assert_eq!(0, rx.clear());
+    }
+
+    #[test]
+    fn clear_dead_letter() {
+        let (dead_tx, mut dead_rx) = crate::mpsc::channel(4);
+        let (mut tx, rx) = Builder::new(4).dead_letter(dead_tx).build();
+
+        tx.try_send(Message(1)).unwrap();
+        assert_eq!(1, rx.clear());
+        assert_eq!(
+            Ok(DeadLetter {
+                value: Message(1),
+                reason: DeadLetterReason::Cleared
+            }),
+            dead_rx.try_recv()
+        );
+    }
+
+    #[test]
+    fn ttl_expires() {
+        let (dead_tx, mut dead_rx) = crate::mpsc::channel(4);
+        let (mut tx, mut rx) = Builder::new(4)
+            .ttl(Duration::from_millis(10))
+            .dead_letter(dead_tx)
+            .build();
+
+        tx.try_send(Message(1)).unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        tx.try_send(Message(2)).unwrap();
+
+        assert_eq!(Ok(Message(2)), rx.try_recv());
+        assert_eq!(
+            Ok(DeadLetter {
+                value: Message(1),
+                reason: DeadLetterReason::Expired
+            }),
+            dead_rx.try_recv()
+        );
+    }
+
     #[test]
     fn sender_disconnect() {
         let mut cx = panic_context();
@@ -519,6 +1285,307 @@ mod tests {
         assert_eq!(PollRecv::Pending, Pin::new(&mut rx).poll_recv(&mut cx));
         assert_eq!(PollRecv::Pending, Pin::new(&mut rx2).poll_recv(&mut cx));
     }
+
+    fn send_all(tx: &mut Sender<Message>, values: impl IntoIterator<Item = usize>) {
+        let mut cx = noop_context();
+        for value in values {
+            assert_eq!(
+                PollSend::Ready,
+                Pin::new(&mut *tx).poll_send(&mut cx, Message(value))
+            );
+        }
+    }
+
+    #[test]
+    fn distribution_shared() {
+        let (waker, _count) = new_count_waker();
+        let mut parked = Context::from_waker(&waker).into();
+        let mut cx = noop_context();
+        let (mut tx, mut rx) = channel(4);
+        let mut rx2 = rx.clone();
+
+        // receivers take messages from the front of the shared queue, even if a peer is waiting
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx2).poll_recv(&mut parked));
+        send_all(&mut tx, 1..=2);
+
+        assert_eq!(
+            PollRecv::Ready(Message(1)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        assert_eq!(
+            PollRecv::Ready(Message(2)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx2).poll_recv(&mut cx));
+    }
+
+    #[test]
+    fn distribution_round_robin() {
+        let (waker, _count) = new_count_waker();
+        let mut parked = Context::from_waker(&waker).into();
+        let (mut tx, mut rx) = Builder::new(4)
+            .distribution(Distribution::RoundRobin)
+            .build();
+        let mut rx2 = rx.clone();
+
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx).poll_recv(&mut parked));
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx2).poll_recv(&mut parked));
+        send_all(&mut tx, 1..=4);
+        assert_eq!(4, tx.len());
+
+        let (waker, _count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker).into();
+        assert_eq!(
+            PollRecv::Ready(Message(1)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        assert_eq!(
+            PollRecv::Ready(Message(3)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        assert_eq!(
+            PollRecv::Ready(Message(2)),
+            Pin::new(&mut rx2).poll_recv(&mut cx)
+        );
+        assert_eq!(
+            PollRecv::Ready(Message(4)),
+            Pin::new(&mut rx2).poll_recv(&mut cx)
+        );
+
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx).poll_recv(&mut cx));
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx2).poll_recv(&mut cx));
+        assert_eq!(0, tx.len());
+    }
+
+    #[test]
+    fn distribution_least_loaded() {
+        let (waker, _count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker).into();
+        let (mut tx, mut rx) = Builder::new(4)
+            .distribution(Distribution::LeastLoaded)
+            .build();
+        let mut rx2 = rx.clone();
+
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx).poll_recv(&mut cx));
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx2).poll_recv(&mut cx));
+        send_all(&mut tx, 1..=3);
+
+        assert_eq!(
+            PollRecv::Ready(Message(1)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        assert_eq!(
+            PollRecv::Ready(Message(3)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx).poll_recv(&mut cx));
+
+        // rx has drained its messages, and rx2 has not, so new messages go to rx
+        send_all(&mut tx, 4..=4);
+        assert_eq!(
+            PollRecv::Ready(Message(4)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx).poll_recv(&mut cx));
+        assert_eq!(
+            PollRecv::Ready(Message(2)),
+            Pin::new(&mut rx2).poll_recv(&mut cx)
+        );
+    }
+
+    #[test]
+    fn distribution_capacity() {
+        let mut cx = noop_context();
+        let (mut tx, mut rx) = Builder::new(2)
+            .distribution(Distribution::RoundRobin)
+            .build();
+        let _rx2 = rx.clone();
+
+        send_all(&mut tx, 1..=2);
+        assert_eq!(
+            PollSend::Pending(Message(3)),
+            Pin::new(&mut tx).poll_send(&mut cx, Message(3))
+        );
+
+        assert_eq!(
+            PollRecv::Ready(Message(1)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        send_all(&mut tx, 3..=3);
+    }
+
+    #[test]
+    fn distribution_steals_from_busy() {
+        let mut cx = noop_context();
+        let (mut tx, mut rx) = Builder::new(4)
+            .distribution(Distribution::RoundRobin)
+            .build();
+        let _rx2 = rx.clone();
+
+        // rx2 is not waiting for messages, so rx takes its work
+        send_all(&mut tx, 1..=2);
+        assert_eq!(
+            PollRecv::Ready(Message(1)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        assert_eq!(
+            PollRecv::Ready(Message(2)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx).poll_recv(&mut cx));
+    }
+
+    #[test]
+    fn distribution_leaves_parked() {
+        let mut cx = noop_context();
+        let (waker, count) = new_count_waker();
+        let mut parked = Context::from_waker(&waker).into();
+        let (mut tx, mut rx) = Builder::new(4)
+            .distribution(Distribution::RoundRobin)
+            .build();
+        let mut rx2 = rx.clone();
+
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx2).poll_recv(&mut parked));
+        send_all(&mut tx, 1..=2);
+        assert!(count.get() > 0);
+
+        assert_eq!(
+            PollRecv::Ready(Message(1)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx).poll_recv(&mut cx));
+        assert_eq!(
+            PollRecv::Ready(Message(2)),
+            Pin::new(&mut rx2).poll_recv(&mut cx)
+        );
+    }
+
+    #[test]
+    fn distribution_cancelled_recv() {
+        use std::future::Future;
+
+        let mut cx = noop_context();
+        let (waker, count) = new_count_waker();
+        let mut task = Context::from_waker(&waker);
+        let (mut tx, mut rx) = Builder::new(4)
+            .distribution(Distribution::RoundRobin)
+            .build();
+        let mut rx2 = rx.clone();
+
+        // rx2 waits in a receive which is then cancelled, so its assigned message can be taken by rx
+        let mut recv = rx2.recv();
+        assert!(Pin::new(&mut recv).poll(&mut task).is_pending());
+        send_all(&mut tx, 1..=2);
+
+        assert_eq!(
+            PollRecv::Ready(Message(1)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        assert_eq!(
+            PollRecv::Pending,
+            Pin::new(&mut rx).poll_recv(&mut task.into())
+        );
+
+        let woken = count.get();
+        drop(recv);
+        assert!(count.get() > woken);
+        assert_eq!(
+            PollRecv::Ready(Message(2)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+    }
+
+    #[test]
+    fn distribution_receiver_drop() {
+        let (waker, count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker).into();
+        let (mut tx, mut rx) = Builder::new(4)
+            .distribution(Distribution::RoundRobin)
+            .build();
+        let mut rx2 = rx.clone();
+
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx).poll_recv(&mut cx));
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx2).poll_recv(&mut cx));
+        send_all(&mut tx, 1..=2);
+
+        assert_eq!(
+            PollRecv::Ready(Message(1)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx).poll_recv(&mut cx));
+
+        // the dropped receiver's message is handed over, and the parked receiver is woken
+        let woken = count.get();
+        drop(rx2);
+        assert!(count.get() > woken);
+        assert_eq!(
+            PollRecv::Ready(Message(2)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+
+        // a receiver added at runtime shares the new messages
+        let mut rx3 = tx.subscribe();
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx).poll_recv(&mut cx));
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx3).poll_recv(&mut cx));
+        send_all(&mut tx, 3..=4);
+
+        let mut received = Vec::new();
+        for rx in [&mut rx, &mut rx3] {
+            match Pin::new(&mut *rx).poll_recv(&mut cx) {
+                PollRecv::Ready(Message(value)) => received.push(value),
+                poll => panic!("expected a message, got {:?}", poll),
+            }
+            assert_eq!(PollRecv::Pending, Pin::new(rx).poll_recv(&mut cx));
+        }
+        received.sort_unstable();
+        assert_eq!(vec![3, 4], received);
+    }
+
+    #[test]
+    fn distribution_dead_letter() {
+        let (dead_tx, mut dead_rx) = crate::mpsc::channel(4);
+        let (mut tx, rx) = Builder::new(4)
+            .distribution(Distribution::LeastLoaded)
+            .dead_letter(dead_tx)
+            .build();
+        let rx2 = rx.clone();
+
+        tx.try_send(Message(1)).unwrap();
+        tx.try_send(Message(2)).unwrap();
+        tx.try_send(Message(3)).unwrap();
+        assert_eq!(3, rx.clear());
+
+        // messages assigned to the last receiver are still released when the channel is torn down
+        tx.try_send(Message(4)).unwrap();
+        drop(rx);
+        drop(rx2);
+        drop(tx);
+
+        // clear takes the messages lane by lane
+        for (value, reason) in [
+            (1, DeadLetterReason::Cleared),
+            (3, DeadLetterReason::Cleared),
+            (2, DeadLetterReason::Cleared),
+            (4, DeadLetterReason::Undelivered),
+        ] {
+            let letter = dead_rx.try_recv().unwrap();
+            assert_eq!((Message(value), reason), (letter.value, letter.reason));
+        }
+    }
+
+    #[test]
+    fn is_closed() {
+        let (tx, rx) = channel::<Message>(4);
+        assert!(!tx.is_closed());
+        assert!(!rx.is_closed());
+
+        drop(tx);
+        assert!(rx.is_closed());
+
+        let (tx, rx) = channel::<Message>(4);
+        drop(rx);
+        assert!(tx.is_closed());
+    }
 }
 
 #[cfg(test)]
@@ -530,6 +1597,7 @@ mod tokio_tests {
         time::{sleep, timeout},
     };
 
+    use super::{Builder, Distribution};
     use crate::{
         sink::Sink,
         stream::Stream,
@@ -637,6 +1705,52 @@ mod tokio_tests {
         }
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn multi_receiver_distribution() {
+        for distribution in [Distribution::RoundRobin, Distribution::LeastLoaded] {
+            for cap in capacity_iter() {
+                let (mut tx, rx) = Builder::new(cap).distribution(distribution).build();
+
+                spawn(async move {
+                    for message in 0..1000usize {
+                        tx.send(message).await.expect("send failed");
+                    }
+                });
+
+                // a receiver may steal messages out of order, so only the total is checked
+                let handles: Vec<JoinHandle<usize>> = (0..CHANNEL_TEST_RECEIVERS)
+                    .map(|_| {
+                        let mut rx2 = rx.clone();
+
+                        spawn(async move {
+                            let mut sum = 0;
+                            while let Some(message) = rx2.recv().await {
+                                sum += message;
+                            }
+                            sum
+                        })
+                    })
+                    .collect();
+
+                drop(rx);
+
+                let rx_handle = spawn(async move {
+                    let mut sum = 0;
+                    for handle in handles {
+                        sum += handle.await.expect("Assertion failure");
+                    }
+                    sum
+                });
+
+                let sum = timeout(TEST_TIMEOUT, rx_handle)
+                    .await
+                    .expect("test timeout")
+                    .expect("join failure");
+                assert_eq!((0..1000).sum::<usize>(), sum);
+            }
+        }
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn multi_sender_multi_receiver() {
         // crate::logging::enable_log();
@@ -704,7 +1818,7 @@ mod tokio_tests {
 
             spawn(async move {
                 loop {
-                    if let Ok(_) = sender_quit.try_recv() {
+                    if sender_quit.try_recv().is_ok() {
                         break;
                     }
 
@@ -910,7 +2024,7 @@ mod async_std_tests {
 
             spawn(async move {
                 loop {
-                    if let Ok(_) = sender_quit.try_recv() {
+                    if sender_quit.try_recv().is_ok() {
                         break;
                     }
 
@@ -0,0 +1,196 @@
+use super::Distribution;
+use crate::sync::{
+    envelope::Envelope,
+    primitives::{Arc, ArrayQueue, Atomic, AtomicUsize, Ordering, RwLock, SegQueue},
+};
+
+/// The buffer of a dispatch channel.
+///
+/// With `Distribution::Shared`, receivers pop from one bounded queue.  Otherwise, each receiver has a lane,
+/// and senders assign each message to a lane.  A receiver takes messages from its own lane, and when it is empty,
+/// steals from the lanes of peers which are busy.  A peer is busy unless it is parked, waiting for messages.
+pub(super) enum Queue<T> {
+    Shared(ArrayQueue<Envelope<T>>),
+    Lanes(Lanes<T>),
+}
+
+pub(super) struct Lanes<T> {
+    distribution: Distribution,
+    capacity: usize,
+    len: AtomicUsize,
+    // senders assign messages while holding the read lock, so a lane which has been removed receives no more messages
+    lanes: RwLock<Vec<Arc<Lane<T>>>>,
+    // messages which were assigned when no receiver had a lane, or were handed back by a dropped receiver
+    orphans: SegQueue<Envelope<T>>,
+    next: AtomicUsize,
+}
+
+/// The messages assigned to a receiver
+pub(super) struct Lane<T> {
+    queue: SegQueue<Envelope<T>>,
+    parked: Atomic<bool>,
+}
+
+impl<T> Lane<T> {
+    /// Marks the receiver as parked, so peers leave its messages for it, or as busy, so peers can steal them
+    pub fn set_parked(&self, parked: bool) {
+        self.parked.store(parked, Ordering::Release);
+    }
+
+    /// Marks a parked receiver as busy.  Returns true if it was parked with messages, which peers can now steal.
+    pub fn unpark(&self) -> bool {
+        let parked = self.parked.load(Ordering::Acquire);
+        self.set_parked(false);
+        parked && !self.queue.is_empty()
+    }
+}
+
+impl<T> Queue<T> {
+    pub fn new(capacity: usize, distribution: Distribution) -> Self {
+        match distribution {
+            Distribution::Shared => Self::Shared(ArrayQueue::new(capacity)),
+            _ => Self::Lanes(Lanes {
+                distribution,
+                capacity,
+                len: AtomicUsize::new(0),
+                lanes: RwLock::new(Vec::new()),
+                orphans: SegQueue::new(),
+                next: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// Creates a lane for a new receiver.  Returns None if receivers share the queue.
+    pub fn join(&self) -> Option<Arc<Lane<T>>> {
+        match self {
+            Self::Shared(_) => None,
+            Self::Lanes(lanes) => {
+                let lane = Arc::new(Lane {
+                    queue: SegQueue::new(),
+                    parked: Atomic::new(false),
+                });
+
+                lanes.lanes.write().push(lane.clone());
+                Some(lane)
+            }
+        }
+    }
+
+    /// Removes the lane of a dropped receiver, and assigns its messages to the remaining receivers
+    pub fn leave(&self, lane: &Arc<Lane<T>>) {
+        if let Self::Lanes(lanes) = self {
+            let mut receivers = lanes.lanes.write();
+            receivers.retain(|receiver| !Arc::ptr_eq(receiver, lane));
+
+            while let Some(envelope) = lane.queue.pop() {
+                lanes.assign(&receivers, envelope);
+            }
+        }
+    }
+
+    /// Buffers the message, or returns it if the buffer is full
+    pub fn push(&self, envelope: Envelope<T>) -> Result<(), Envelope<T>> {
+        match self {
+            Self::Shared(queue) => queue.push(envelope),
+            Self::Lanes(lanes) => {
+                let claimed = lanes
+                    .len
+                    .fetch_update(Ordering::AcqRel, Ordering::Acquire, |len| {
+                        (len < lanes.capacity).then_some(len + 1)
+                    })
+                    .is_ok();
+
+                if !claimed {
+                    return Err(envelope);
+                }
+
+                lanes.assign(&lanes.lanes.read(), envelope);
+                Ok(())
+            }
+        }
+    }
+
+    /// Takes a message for the receiver with the lane
+    pub fn pop(&self, lane: Option<&Lane<T>>) -> Option<Envelope<T>> {
+        match (self, lane) {
+            (Self::Shared(queue), _) => queue.pop(),
+            (Self::Lanes(lanes), Some(lane)) => {
+                let envelope = lane
+                    .queue
+                    .pop()
+                    .or_else(|| lanes.orphans.pop())
+                    .or_else(|| lanes.steal(lane))?;
+
+                lanes.len.fetch_sub(1, Ordering::AcqRel);
+                Some(envelope)
+            }
+            (Self::Lanes(lanes), None) => lanes.pop_any(),
+        }
+    }
+
+    /// Takes any message, regardless of the lane it was assigned to
+    pub fn pop_any(&self) -> Option<Envelope<T>> {
+        match self {
+            Self::Shared(queue) => queue.pop(),
+            Self::Lanes(lanes) => lanes.pop_any(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Shared(queue) => queue.len(),
+            Self::Lanes(lanes) => lanes.len.load(Ordering::Acquire),
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        match self {
+            Self::Shared(queue) => queue.is_full(),
+            Self::Lanes(lanes) => lanes.len.load(Ordering::Acquire) >= lanes.capacity,
+        }
+    }
+}
+
+impl<T> Lanes<T> {
+    fn assign(&self, receivers: &[Arc<Lane<T>>], envelope: Envelope<T>) {
+        if receivers.is_empty() {
+            self.orphans.push(envelope);
+            return;
+        }
+
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let lane = match self.distribution {
+            Distribution::LeastLoaded => {
+                // ties are broken in turn, so idle receivers share the load
+                (0..receivers.len())
+                    .map(|i| &receivers[(start + i) % receivers.len()])
+                    .min_by_key(|lane| lane.queue.len())
+                    .unwrap()
+            }
+            _ => &receivers[start % receivers.len()],
+        };
+
+        lane.queue.push(envelope);
+    }
+
+    /// Takes a message from the busiest peer which is not parked
+    fn steal(&self, thief: &Lane<T>) -> Option<Envelope<T>> {
+        let receivers = self.lanes.read();
+        receivers
+            .iter()
+            .filter(|lane| !std::ptr::eq(&***lane, thief) && !lane.parked.load(Ordering::Acquire))
+            .max_by_key(|lane| lane.queue.len())?
+            .queue
+            .pop()
+    }
+
+    fn pop_any(&self) -> Option<Envelope<T>> {
+        let envelope = self.orphans.pop().or_else(|| {
+            let receivers = self.lanes.read();
+            receivers.iter().find_map(|lane| lane.queue.pop())
+        })?;
+
+        self.len.fetch_sub(1, Ordering::AcqRel);
+        Some(envelope)
+    }
+}
@@ -0,0 +1,530 @@
+//! A multi-producer channel with consumer groups.  Each group receives every message,
+//! and the receivers within a group share the group's messages.
+//!
+//! Receivers join a group by name with `Sender::subscribe`, or `Receiver::join`.  A receiver which is cloned stays in its group.
+//! A group observes the messages sent after it was created, and is removed when its last receiver is dropped.
+//!
+//! Each group buffers up to `capacity` messages, and senders wait for the slowest group.
+//!
+//! ```rust
+//! use postage::{group, prelude::*};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let (mut tx, mut billing) = group::channel(4, "billing");
+//!     let mut billing2 = billing.clone();
+//!     let mut audit = tx.subscribe("audit");
+//!
+//!     tx.send(1usize).await.ok();
+//!     tx.send(2usize).await.ok();
+//!
+//!     // the billing receivers share the stream
+//!     assert_eq!(Some(1), billing.recv().await);
+//!     assert_eq!(Some(2), billing2.recv().await);
+//!
+//!     // and the audit group gets all of it
+//!     assert_eq!(Some(1), audit.recv().await);
+//!     assert_eq!(Some(2), audit.recv().await);
+//! }
+//! ```
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    pin::Pin,
+    sync::Arc,
+};
+
+use parking_lot::Mutex;
+use static_assertions::assert_impl_all;
+
+use crate::{
+    sink::{PollSend, Sink},
+    stream::{PollRecv, Stream},
+    sync::notifier::Notifier,
+    Context,
+};
+
+/// Constructs a pair of endpoints.  Each group buffers up to `capacity` messages,
+/// and the receiver is the first member of `group`.
+pub fn channel<T: Clone>(capacity: usize, group: &str) -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Shared {
+        capacity,
+        state: Mutex::new(State {
+            groups: HashMap::new(),
+            senders: 1,
+        }),
+        notify_tx: Notifier::new(),
+    });
+
+    let receiver = Receiver::join_group(shared.clone(), group);
+    let sender = Sender { shared };
+
+    (sender, receiver)
+}
+
+struct Shared<T> {
+    capacity: usize,
+    state: Mutex<State<T>>,
+    notify_tx: Notifier,
+}
+
+struct State<T> {
+    groups: HashMap<String, Group<T>>,
+    senders: usize,
+}
+
+struct Group<T> {
+    queue: VecDeque<T>,
+    receivers: usize,
+    notify_rx: Arc<Notifier>,
+}
+
+/// The sender half of a consumer group channel.  Can send messages with the postage::Sink trait.
+///
+/// Can be cloned.
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+assert_impl_all!(Sender<String>: Clone, Send, Sync, fmt::Debug);
+
+impl<T> Sender<T> {
+    /// Creates a receiver in the named group.  If the group does not exist, it is created,
+    /// and will observe all messages sent after the call to subscribe.
+    pub fn subscribe(&self, group: &str) -> Receiver<T> {
+        Receiver::join_group(self.shared.clone(), group)
+    }
+
+    /// The names of the groups which currently have receivers, in no particular order
+    pub fn groups(&self) -> Vec<String> {
+        self.shared.state.lock().groups.keys().cloned().collect()
+    }
+}
+
+impl<T: Clone> Sink for Sender<T> {
+    type Item = T;
+
+    fn poll_send(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        value: Self::Item,
+    ) -> PollSend<Self::Item> {
+        loop {
+            let guard = self.shared.notify_tx.guard();
+
+            {
+                let mut state = self.shared.state.lock();
+
+                // if all receivers have disconnected, we return Rejected like other channels.
+                // tx.subscribe() can be used to create a new group.
+                if state.groups.is_empty() {
+                    return PollSend::Rejected(value);
+                }
+
+                let full = state
+                    .groups
+                    .values()
+                    .any(|group| group.queue.len() >= self.shared.capacity);
+
+                if !full {
+                    let mut notify = Vec::with_capacity(state.groups.len());
+                    let mut groups = state.groups.values_mut().peekable();
+
+                    while let Some(group) = groups.next() {
+                        notify.push(group.notify_rx.clone());
+
+                        // the last group takes the value, and the others take clones
+                        if groups.peek().is_none() {
+                            group.queue.push_back(value);
+                            break;
+                        }
+
+                        group.queue.push_back(value.clone());
+                    }
+
+                    drop(state);
+                    for notifier in notify {
+                        notifier.notify();
+                    }
+
+                    return PollSend::Ready;
+                }
+            }
+
+            self.shared.notify_tx.subscribe(cx);
+
+            if guard.is_expired() {
+                continue;
+            }
+
+            return PollSend::Pending(value);
+        }
+    }
+}
+
+#[cfg(feature = "futures-traits")]
+mod impl_futures {
+    use std::task::Poll;
+
+    use crate::{
+        sink::{PollSend, SendError, Sink},
+        Context,
+    };
+
+    impl<T: Clone> futures_sink::Sink<T> for super::Sender<T> {
+        type Error = SendError<T>;
+
+        fn poll_ready(
+            self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            loop {
+                let guard = self.shared.notify_tx.guard();
+
+                {
+                    // a closed channel is ready, and rejects the message in start_send
+                    let state = self.shared.state.lock();
+                    let full = !state.groups.is_empty()
+                        && state
+                            .groups
+                            .values()
+                            .any(|group| group.queue.len() >= self.shared.capacity);
+
+                    if !full {
+                        return Poll::Ready(Ok(()));
+                    }
+                }
+
+                self.shared.notify_tx.subscribe(&cx.into());
+
+                if guard.is_expired() {
+                    continue;
+                }
+
+                return Poll::Pending;
+            }
+        }
+
+        fn start_send(self: std::pin::Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+            match self.poll_send(&mut Context::empty(), item) {
+                PollSend::Ready => Ok(()),
+                PollSend::Pending(item) | PollSend::Rejected(item) => Err(SendError(item)),
+            }
+        }
+
+        fn poll_flush(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.shared.state.lock().senders += 1;
+
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock();
+        state.senders -= 1;
+
+        if state.senders == 0 {
+            let notify: Vec<_> = state
+                .groups
+                .values()
+                .map(|group| group.notify_rx.clone())
+                .collect();
+
+            drop(state);
+            for notifier in notify {
+                notifier.notify();
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender").finish()
+    }
+}
+
+/// The receiver half of a consumer group channel.  Each message sent to the group is received by one member.
+///
+/// Receives messages with the postage::Stream trait.  Can be cloned, and the clone joins the same group.
+/// The receiver closes when all senders have been dropped, and the group's buffer is empty.
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+    group: Arc<str>,
+    notify_rx: Arc<Notifier>,
+}
+
+assert_impl_all!(Receiver<String>: Clone, Send, Sync, fmt::Debug);
+
+impl<T> Receiver<T> {
+    fn join_group(shared: Arc<Shared<T>>, group: &str) -> Self {
+        let notify_rx = {
+            let mut state = shared.state.lock();
+            let group = state
+                .groups
+                .entry(group.to_string())
+                .or_insert_with(|| Group {
+                    queue: VecDeque::new(),
+                    receivers: 0,
+                    notify_rx: Arc::new(Notifier::new()),
+                });
+
+            group.receivers += 1;
+            group.notify_rx.clone()
+        };
+
+        Self {
+            shared,
+            group: group.into(),
+            notify_rx,
+        }
+    }
+
+    /// Creates a receiver in another group, which is created if it does not exist
+    pub fn join(&self, group: &str) -> Receiver<T> {
+        Self::join_group(self.shared.clone(), group)
+    }
+
+    /// The name of the receiver's group
+    pub fn group(&self) -> &str {
+        &self.group
+    }
+
+    /// The number of messages buffered for the receiver's group
+    pub fn len(&self) -> usize {
+        self.shared
+            .state
+            .lock()
+            .groups
+            .get(&*self.group)
+            .map(|group| group.queue.len())
+            .unwrap_or(0)
+    }
+
+    /// Returns true if there are no messages buffered for the receiver's group
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Stream for Receiver<T> {
+    type Item = T;
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item> {
+        loop {
+            let guard = self.notify_rx.guard();
+
+            {
+                let mut state = self.shared.state.lock();
+                let group = state
+                    .groups
+                    .get_mut(&*self.group)
+                    .expect("a group with receivers is not removed");
+
+                if let Some(value) = group.queue.pop_front() {
+                    drop(state);
+                    self.shared.notify_tx.notify();
+                    return PollRecv::Ready(value);
+                }
+
+                if state.senders == 0 {
+                    return PollRecv::Closed;
+                }
+            }
+
+            self.notify_rx.subscribe(cx);
+
+            if guard.is_expired() {
+                continue;
+            }
+
+            return PollRecv::Pending;
+        }
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        Self::join_group(self.shared.clone(), &self.group)
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock();
+        let group = state
+            .groups
+            .get_mut(&*self.group)
+            .expect("a group with receivers is not removed");
+
+        group.receivers -= 1;
+        if group.receivers == 0 {
+            // the group's buffered messages are discarded, which frees capacity for the senders
+            state.groups.remove(&*self.group);
+            drop(state);
+            self.shared.notify_tx.notify();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("group", &self.group)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{pin::Pin, task::Context};
+
+    use futures_test::task::{new_count_waker, noop_context};
+
+    use super::channel;
+    use crate::{
+        sink::{PollSend, Sink, TrySendError},
+        stream::{PollRecv, Stream, TryRecvError},
+    };
+
+    #[test]
+    fn shares_within_group() {
+        let (mut tx, mut rx) = channel(4, "workers");
+        let mut rx2 = rx.clone();
+
+        tx.try_send(1usize).unwrap();
+        tx.try_send(2).unwrap();
+
+        assert_eq!(Ok(1), rx2.try_recv());
+        assert_eq!(Ok(2), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Pending), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Pending), rx2.try_recv());
+    }
+
+    #[test]
+    fn groups_get_every_message() {
+        let (mut tx, mut a) = channel(4, "a");
+        let mut b = a.join("b");
+        assert_eq!("b", b.group());
+
+        tx.try_send(1usize).unwrap();
+        tx.try_send(2).unwrap();
+
+        assert_eq!(Ok(1), a.try_recv());
+        assert_eq!(Ok(2), a.try_recv());
+        assert_eq!(Ok(1), b.try_recv());
+        assert_eq!(Ok(2), b.try_recv());
+    }
+
+    #[test]
+    fn new_group_observes_later_messages() {
+        let (mut tx, _a) = channel(4, "a");
+        tx.try_send(1usize).unwrap();
+
+        let mut b = tx.subscribe("b");
+        assert_eq!(Err(TryRecvError::Pending), b.try_recv());
+        tx.try_send(2).unwrap();
+        assert_eq!(Ok(2), b.try_recv());
+    }
+
+    #[test]
+    fn slowest_group_applies_backpressure() {
+        let (mut tx, mut fast) = channel(1, "fast");
+        let slow = tx.subscribe("slow");
+
+        tx.try_send(1usize).unwrap();
+        assert_eq!(Ok(1), fast.try_recv());
+
+        let (waker, count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker);
+        assert_eq!(
+            PollSend::Pending(2),
+            Pin::new(&mut tx).poll_send(&mut (&mut cx).into(), 2)
+        );
+
+        drop(slow);
+        assert_eq!(1, count.get());
+        tx.try_send(2).unwrap();
+        assert_eq!(Ok(2), fast.try_recv());
+    }
+
+    #[test]
+    fn group_removed_with_last_receiver() {
+        let (mut tx, rx) = channel(4, "a");
+        let rx2 = rx.clone();
+
+        drop(rx);
+        assert_eq!(vec!["a".to_string()], tx.groups());
+        drop(rx2);
+        assert!(tx.groups().is_empty());
+        assert_eq!(Err(TrySendError::Rejected(1usize)), tx.try_send(1));
+    }
+
+    #[test]
+    fn closes_after_buffer() {
+        let (mut tx, mut rx) = channel(4, "a");
+        tx.try_send(1usize).unwrap();
+        drop(tx);
+
+        let mut cx = noop_context();
+        assert_eq!(
+            PollRecv::Ready(1),
+            Pin::new(&mut rx).poll_recv(&mut (&mut cx).into())
+        );
+        assert_eq!(
+            PollRecv::Closed,
+            Pin::new(&mut rx).poll_recv(&mut (&mut cx).into())
+        );
+    }
+
+    #[tokio::test]
+    async fn workers() {
+        let (mut tx, rx) = channel(2, "workers");
+
+        let mut handles = Vec::new();
+        for _ in 0..3 {
+            let mut rx = rx.clone();
+            handles.push(tokio::spawn(async move {
+                let mut received = Vec::new();
+                while let Some(value) = rx.recv().await {
+                    received.push(value);
+                }
+                received
+            }));
+        }
+        drop(rx);
+
+        for i in 0..30usize {
+            tx.send(i).await.unwrap();
+        }
+        drop(tx);
+
+        let mut received = Vec::new();
+        for handle in handles {
+            received.extend(handle.await.unwrap());
+        }
+        received.sort_unstable();
+        assert_eq!((0..30).collect::<Vec<_>>(), received);
+    }
+}
@@ -1,27 +1,301 @@
 //! A fixed-capacity multi-producer, single-consumer channel.  
 //!
 //! The producer can be cloned, and the sender task is suspended if the channel becomes full.
+//!
+//! For very large numbers of producers, [sharded](./fn.sharded.html) constructs a channel with one queue per shard,
+//! which reduces contention between producer threads.
+//!
+//! [linked](./fn.linked.html) constructs the channels on either side of a pipeline stage, so upstream senders wait while the downstream channel is full.
+//!
+//! [with_control](./fn.with_control.html) constructs a channel with a small control lane, which the receiver checks before the data lane.
+//! Shutdown or flush commands sent on the control lane reach the consumer even when the data lane is full.
 
-use std::fmt;
+use std::{collections::VecDeque, fmt, marker::PhantomData, sync::Arc, task::Poll, time::Duration};
 
+use self::{
+    linked::Credits,
+    quota::{Queued, Quota},
+    slots::Slots,
+};
 use super::SendMessage;
 use crate::{
-    sink::{PollSend, Sink},
+    broadcast,
+    dead_letter::{DeadLetter, DeadLetterReason, Undelivered},
+    metrics::{ChannelMetrics, MetricsHook},
+    registry::Registration,
+    sink::{BufferedSink, PollReady, PollSend, SendError, Sink, TrySendError},
+    spawn::Spawn,
+    stop::StopToken,
     stream::{PollRecv, Stream},
-    sync::{shared, ReceiverShared, SenderShared},
+    sync::{
+        envelope::Envelope,
+        notifier::Notifier,
+        primitives::{ArrayQueue, AtomicBool, AtomicU64, AtomicUsize, Mutex, Ordering},
+        shared, ReceiverShared, SenderShared,
+    },
+    trace::Tracer,
+    watermark::{Watermark, Watermarks},
 };
-use crossbeam_queue::ArrayQueue;
 use static_assertions::{assert_impl_all, assert_not_impl_all};
 
+mod control;
+#[cfg(feature = "serde")]
+mod frozen;
+mod linked;
+mod permit;
+mod quota;
+mod sharded;
+mod slots;
+
+pub use control::{with_control, ControlledReceiver, ControlledSender, CONTROL_CAPACITY};
+#[cfg(feature = "serde")]
+pub use frozen::FrozenChannel;
+pub use linked::{linked, linked_with};
+pub use permit::SendPermit;
+pub use sharded::{sharded, sharded_with, ShardedReceiver, ShardedSender};
+
+/// Constructs a pair of mpsc endpoints, with a fixed-size buffer of the given capacity
 pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
-    #[cfg(feature = "debug")]
-    log::error!("Creating mpsc channel with capacity {}", capacity);
-    let (tx_shared, rx_shared) = shared(StateExtension::new(capacity));
-    let sender = Sender { shared: tx_shared };
+    Builder::new(capacity).build()
+}
+
+/// Constructs an mpsc channel, with additional configuration.
+///
+/// ```rust
+/// use postage::mpsc;
+///
+/// let (tx, rx) = mpsc::Builder::<usize>::new(16).name("requests").build();
+/// ```
+pub struct Builder<T> {
+    capacity: usize,
+    name: Option<String>,
+    metrics: Option<Arc<dyn ChannelMetrics>>,
+    stop: Option<StopToken>,
+    undelivered: Undelivered<T>,
+    watermarks: Option<Watermarks>,
+    ttl: Option<Duration>,
+    track_age: bool,
+    sender_quota: Option<usize>,
+    overflow: Overflow,
+    sequenced: bool,
+    credits: Option<Arc<Credits>>,
+    gate: Option<Arc<Credits>>,
+    _t: PhantomData<fn() -> T>,
+}
+
+impl<T> Builder<T> {
+    /// Creates a builder for a channel with a fixed-size buffer of the given capacity
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            name: None,
+            metrics: None,
+            stop: None,
+            undelivered: Undelivered::new(),
+            watermarks: None,
+            ttl: None,
+            track_age: false,
+            sender_quota: None,
+            overflow: Overflow::Block,
+            sequenced: false,
+            credits: None,
+            gate: None,
+            _t: PhantomData,
+        }
+    }
+
+    /// Names the channel.  The name is attached to the channel's tracing span.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Installs a metrics hook on the channel, which takes precedence over the global hook.
+    pub fn metrics(mut self, metrics: Arc<dyn ChannelMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Closes the channel when the token's source is stopped.  Senders reject new messages,
+    /// and receivers drain the buffered messages before observing the channel as closed.
+    pub fn stop_token(mut self, token: StopToken) -> Self {
+        self.stop = Some(token);
+        self
+    }
+
+    /// Discards messages which have been in the buffer for longer than `ttl`, instead of delivering them.
+    /// Expired messages are passed to the dead-letter sink or the `on_drop` hook.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Limits each sender to `quota` messages in the buffer.  A sender which reaches its quota waits
+    /// until the receiver takes one of its messages, even if the buffer has room for other senders.
+    ///
+    /// Each clone of a sender has its own quota.
+    pub fn sender_quota(mut self, quota: usize) -> Self {
+        self.sender_quota = Some(quota);
+        self
+    }
+
+    /// Configures the behavior of senders when the buffer is full.  By default, senders wait for capacity.
+    ///
+    /// Messages discarded by the policy are passed to the dead-letter sink, tagged with `DeadLetterReason::Overflowed`, or to the `on_drop` hook.
+    /// Senders still wait while the receiver is paused, or when they reach their quota.
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Assigns each message a sequence number as it is buffered, starting at 1.  The number is returned by
+    /// [Sender::send_seq](./struct.Sender.html#method.send_seq), and the number of the last message received
+    /// is available from [last_delivered_seq](./struct.Receiver.html#method.last_delivered_seq).
+    ///
+    /// Numbers are assigned in the order messages are buffered, so they are received in increasing order.
+    /// Vectored sends and send permits also consume numbers, and so do messages discarded by the overflow policy.
+    pub fn sequence_numbers(mut self) -> Self {
+        self.sequenced = true;
+        self
+    }
+
+    /// Timestamps messages as they are sent, so the receiver can observe their age with `recv_with_age`.
+    pub fn track_age(mut self) -> Self {
+        self.track_age = true;
+        self
+    }
+
+    /// Configures high and low watermarks on the buffer depth.  The callback is invoked with `Watermark::High`
+    /// when the depth reaches `high`, and with `Watermark::Low` when the depth then falls to `low`.
+    ///
+    /// Panics if `low` is not less than `high`.
+    pub fn watermarks<F>(mut self, high: usize, low: usize, callback: F) -> Self
+    where
+        F: Fn(Watermark) + Send + Sync + 'static,
+    {
+        self.watermarks = Some(Watermarks::new(high, low, callback));
+        self
+    }
 
-    let receiver = Receiver { shared: rx_shared };
+    /// Attaches a dead-letter sink to the channel.  Messages which are still buffered when the channel is torn down
+    /// are forwarded to the sink without blocking.  If the sink is full or closed, the messages are passed to the `on_drop` hook.
+    ///
+    /// Messages sent after the channel closes are returned to the sender.  To also forward a copy to the sink,
+    /// use [dead_letter_rejected](#method.dead_letter_rejected).
+    pub fn dead_letter<S>(mut self, sink: S) -> Self
+    where
+        S: Sink<Item = DeadLetter<T>> + Send + 'static,
+    {
+        self.undelivered.set_dead_letter(sink);
+        self
+    }
+
+    /// Installs a hook which is called with each message still buffered when the channel is torn down,
+    /// so resources held by the messages can be released deterministically.
+    pub fn on_drop<F>(mut self, on_drop: F) -> Self
+    where
+        F: FnMut(T) + Send + 'static,
+    {
+        self.undelivered.set_on_drop(on_drop);
+        self
+    }
+
+    /// Constructs the pair of channel endpoints
+    pub fn build(self) -> (Sender<T>, Receiver<T>) {
+        #[cfg(feature = "debug")]
+        log::error!("Creating mpsc channel with capacity {}", self.capacity);
+        let tracer = Tracer::new("mpsc", self.name.as_deref(), Some(self.capacity));
+        let metrics = MetricsHook::resolve(self.metrics, "mpsc", self.name.as_deref());
+        let registration =
+            Registration::new("mpsc", self.name.as_deref(), Some(self.capacity), true);
+        let (tx_shared, rx_shared) = shared(
+            StateExtension {
+                queue: ArrayQueue::new(self.capacity),
+                capacity: self.capacity,
+                #[cfg(feature = "serde")]
+                name: self.name.clone(),
+                stash: Mutex::new(VecDeque::new()),
+                stashed: AtomicUsize::new(0),
+                undelivered: self.undelivered,
+                watermarks: self.watermarks,
+                ttl: self.ttl,
+                #[cfg(feature = "serde")]
+                track_age: self.track_age,
+                timestamps: self.track_age || self.ttl.is_some() || metrics.is_some(),
+                paused: AtomicBool::new(false),
+                generation: AtomicUsize::new(0),
+                handover: Mutex::new(()),
+                sender_quota: self.sender_quota,
+                slots: Slots::new(self.capacity),
+                overflow: self.overflow,
+                sequence: self.sequenced.then(|| Mutex::new(0)),
+                delivered: AtomicU64::new(0),
+                pushed: AtomicU64::new(0),
+                popped: AtomicU64::new(0),
+                on_pop: Notifier::new(),
+                credits: self.credits,
+                gate: self.gate,
+            },
+            tracer,
+            metrics,
+            registration,
+            self.stop,
+        );
+        let sender = Sender {
+            quota: self.sender_quota.map(Quota::new),
+            shared: tx_shared,
+            last_seq: None,
+            flush_target: 0,
+        };
+
+        let receiver = Receiver {
+            shared: rx_shared,
+            generation: 0,
+        };
+
+        (sender, receiver)
+    }
+}
+
+impl<T: Clone> Builder<T> {
+    /// Forwards a copy of each message sent after the channel closes to the dead-letter sink, tagged with `DeadLetterReason::Rejected`.
+    /// The send still returns the message, so the sender observes the closure.
+    pub fn dead_letter_rejected(mut self) -> Self {
+        self.undelivered.set_copy_rejected(Some(T::clone));
+        self
+    }
+}
+
+impl<T> fmt::Debug for Builder<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Builder")
+            .field("capacity", &self.capacity)
+            .field("name", &self.name)
+            .field("metrics", &self.metrics.is_some())
+            .field("stop", &self.stop)
+            .field("undelivered", &self.undelivered)
+            .field("watermarks", &self.watermarks)
+            .field("ttl", &self.ttl)
+            .field("track_age", &self.track_age)
+            .field("sender_quota", &self.sender_quota)
+            .field("overflow", &self.overflow)
+            .field("sequenced", &self.sequenced)
+            .finish()
+    }
+}
 
-    (sender, receiver)
+/// The behavior of an mpsc channel when a message is sent to a full buffer.
+///
+/// Vectored sends and reservations always wait for capacity.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Overflow {
+    /// Senders wait until the receiver makes room
+    #[default]
+    Block,
+    /// The message being sent is discarded, and the send completes
+    DropNewest,
+    /// The oldest buffered message is discarded to make room, and the send completes
+    DropOldest,
 }
 
 /// The sender half of an mpsc channel.  Can send messages with the postage::Sink trait.
@@ -29,6 +303,12 @@ pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
 /// Can be cloned.
 pub struct Sender<T> {
     pub(in crate::channels::mpsc) shared: SenderShared<StateExtension<T>>,
+    quota: Option<Arc<Quota>>,
+    /// The sequence number of the last message sent by `poll_send`
+    last_seq: Option<u64>,
+    /// The number of messages which had been counted as buffered once this sender's last message was buffered.
+    /// A flush completes when the receiver has taken as many messages.
+    flush_target: u64,
 }
 
 assert_impl_all!(Sender<String>: Clone, Send, Sync, fmt::Debug);
@@ -37,6 +317,9 @@ impl<T> Clone for Sender<T> {
     fn clone(&self) -> Self {
         Self {
             shared: self.shared.clone(),
+            quota: self.shared.extension().sender_quota.map(Quota::new),
+            last_seq: None,
+            flush_target: 0,
         }
     }
 }
@@ -49,15 +332,236 @@ impl<T> Sink for Sender<T> {
         cx: &mut crate::Context<'_>,
         mut value: Self::Item,
     ) -> PollSend<Self::Item> {
+        let this = self.get_mut();
+
         loop {
+            // take the guard before checking for closure, so a receiver drop between the check
+            // and the subscription below expires the guard
+            let guard = this.shared.recv_guard();
+
+            if this.shared.is_closed() {
+                this.shared.tracer().reject();
+                if let Some(metrics) = this.shared.metrics() {
+                    metrics.on_reject();
+                }
+                return PollSend::Rejected(this.shared.extension().undelivered.reject(value));
+            }
+
+            match this.push(value) {
+                Ok(seq) => {
+                    this.last_seq = seq;
+                    this.flush_target = this.shared.extension().pushed_count();
+                    this.record_send();
+                    this.shared.notify_receivers();
+                    return PollSend::Ready;
+                }
+                Err(v) => {
+                    this.shared.subscribe_recv(cx);
+
+                    if guard.is_expired() {
+                        value = v;
+                        continue;
+                    }
+
+                    this.shared.tracer().full();
+                    if let Some(metrics) = this.shared.metrics() {
+                        metrics.set_blocked_senders(this.shared.blocked_senders());
+                    }
+                    return PollSend::Pending(v);
+                }
+            }
+        }
+    }
+}
+
+impl<T> BufferedSink for Sender<T> {
+    fn poll_ready(self: std::pin::Pin<&mut Self>, cx: &mut crate::Context<'_>) -> PollReady {
+        loop {
+            let guard = self.shared.recv_guard();
+
             if self.shared.is_closed() {
-                return PollSend::Rejected(value);
+                return PollReady::Closed;
+            }
+
+            if !self.is_full() {
+                return PollReady::Ready;
+            }
+
+            self.shared.subscribe_recv(cx);
+
+            if guard.is_expired() {
+                continue;
+            }
+
+            return PollReady::Pending;
+        }
+    }
+
+    /// Waits until the receiver has taken every message sent by this sender, and the messages buffered ahead of them.
+    /// Messages discarded by the channel, such as expired messages, count as taken.
+    ///
+    /// The target is fixed by the sender's last send, so a flush which is cancelled and retried waits for the same messages.
+    /// Returns `Closed` if the receiver has been dropped.
+    fn poll_flush(self: std::pin::Pin<&mut Self>, cx: &mut crate::Context<'_>) -> PollReady {
+        let this = self.get_mut();
+        let extension = this.shared.extension();
+        let target = this.flush_target;
+
+        loop {
+            let pop_guard = extension.on_pop.guard();
+            let guard = this.shared.recv_guard();
+
+            if !this.shared.is_alive() {
+                return PollReady::Closed;
+            }
+
+            if extension.popped.load(Ordering::Acquire) >= target {
+                return PollReady::Ready;
+            }
+
+            extension.on_pop.subscribe(cx);
+            this.shared.subscribe_recv(cx);
+
+            if pop_guard.is_expired() || guard.is_expired() {
+                continue;
             }
 
+            return PollReady::Pending;
+        }
+    }
+
+    /// The channel closes when all senders are dropped, so the close is equivalent to a flush
+    fn poll_close(self: std::pin::Pin<&mut Self>, cx: &mut crate::Context<'_>) -> PollReady {
+        self.poll_flush(cx)
+    }
+}
+
+impl<T> Sender<T> {
+    /// Returns true if the receiver has been dropped, or the channel has been stopped.  Sends to a closed channel are rejected.
+    pub fn is_closed(&self) -> bool {
+        self.shared.is_closed()
+    }
+
+    /// The number of sender tasks which are parked, waiting for capacity.
+    ///
+    /// The count is approximate, as a task which is polled again before it is woken may be counted twice.
+    /// A count which stays above zero shows that producers are queuing up, and the consumer is not keeping up.
+    pub fn blocked_senders(&self) -> usize {
+        self.shared.blocked_senders()
+    }
+
+    /// Sends a message, waiting for capacity as needed, and returns its sequence number.
+    ///
+    /// Panics if the channel was not constructed with `Builder::sequence_numbers`.
+    ///
+    /// ```rust
+    /// use postage::{mpsc, prelude::*};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let (mut tx, mut rx) = mpsc::Builder::new(4).sequence_numbers().build();
+    ///
+    ///     assert_eq!(Ok(1), tx.send_seq("first").await);
+    ///     assert_eq!(Ok(2), tx.send_seq("second").await);
+    ///
+    ///     assert_eq!(Some("first"), rx.recv().await);
+    ///     assert_eq!(Some(1), tx.last_delivered_seq());
+    /// }
+    /// ```
+    pub async fn send_seq(&mut self, value: T) -> Result<u64, SendError<T>> {
+        self.send(value).await?;
+        Ok(self.take_seq())
+    }
+
+    /// Attempts to send a message without waiting, and returns its sequence number.
+    ///
+    /// Panics if the channel was not constructed with `Builder::sequence_numbers`.
+    pub fn try_send_seq(&mut self, value: T) -> Result<u64, TrySendError<T>> {
+        self.try_send(value)?;
+        Ok(self.take_seq())
+    }
+
+    /// The sequence number of the last message taken by the receiver, or `None` if no message has been received.
+    ///
+    /// Always `None` unless the channel was constructed with `Builder::sequence_numbers`.
+    pub fn last_delivered_seq(&self) -> Option<u64> {
+        self.shared.extension().last_delivered_seq()
+    }
+
+    fn take_seq(&mut self) -> u64 {
+        self.last_seq
+            .take()
+            .expect("the channel was not constructed with Builder::sequence_numbers")
+    }
+
+    /// Creates a new receiver for the channel, which takes over from the current receiver.
+    ///
+    /// Messages which are still buffered are received by the new receiver, so a consumer can be restarted without losing messages.
+    /// The previous receiver observes the channel as closed, and receives no further messages.
+    /// It no longer keeps the channel open, so once the replacement is dropped, sends are rejected.
+    pub fn replace_receiver(&self) -> Receiver<T> {
+        let extension = self.shared.extension();
+        let _handover = extension.handover.lock();
+
+        // the replacement takes over the count of the previous receiver, unless it has been dropped
+        let shared = if self.shared.is_alive() {
+            self.shared.adopt_receiver()
+        } else {
+            self.shared.clone_receiver()
+        };
+        let generation = extension.generation.fetch_add(1, Ordering::AcqRel) + 1;
+
+        // wake the previous receiver, so it observes the closure
+        self.shared.notify_receivers();
+
+        Receiver { shared, generation }
+    }
+
+    /// Attempts to send every value in the batch, with no messages from other senders in between.
+    ///
+    /// The batch is buffered all-or-none.  If the buffer does not have room for the whole batch,
+    /// returns `Pending` with the batch, and the task is woken when the receiver makes progress.
+    /// If the channel is closed, or the batch is larger than the capacity or the sender quota, returns `Rejected` with the batch.
+    pub fn poll_send_vectored(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut crate::Context<'_>,
+        mut values: Vec<T>,
+    ) -> PollSend<Vec<T>> {
+        let extension = self.shared.extension();
+        let limit = match self.quota {
+            Some(ref quota) => quota.limit().min(extension.capacity),
+            None => extension.capacity,
+        };
+
+        loop {
             let guard = self.shared.recv_guard();
-            let queue = &self.shared.extension().queue;
-            match queue.push(value) {
-                Ok(_) => {
+
+            let closed = self.shared.is_closed();
+            if closed || values.len() > limit {
+                self.shared.tracer().reject();
+                if let Some(metrics) = self.shared.metrics() {
+                    metrics.on_reject();
+                }
+
+                if !closed {
+                    return PollSend::Rejected(values);
+                }
+
+                let rejected = values
+                    .into_iter()
+                    .map(|value| extension.undelivered.reject(value))
+                    .collect();
+
+                return PollSend::Rejected(rejected);
+            }
+
+            match self.push_vectored(values) {
+                Ok(sent) => {
+                    self.flush_target = self.shared.extension().pushed_count();
+                    for _ in 0..sent {
+                        self.record_send();
+                    }
+
                     self.shared.notify_receivers();
                     return PollSend::Ready;
                 }
@@ -65,15 +569,223 @@ impl<T> Sink for Sender<T> {
                     self.shared.subscribe_recv(cx);
 
                     if guard.is_expired() {
-                        value = v;
+                        values = v;
                         continue;
                     }
 
+                    self.shared.tracer().full();
+                    if let Some(metrics) = self.shared.metrics() {
+                        metrics.set_blocked_senders(self.shared.blocked_senders());
+                    }
                     return PollSend::Pending(v);
                 }
             }
         }
     }
+
+    /// Sends every value in the batch, with no messages from other senders in between.
+    ///
+    /// Waits until the buffer has room for the whole batch.  If the channel is closed, or the batch can never fit
+    /// in the buffer, returns the batch in the error.  Messages sent to a closed channel are first offered to the dead-letter sink,
+    /// and only the messages it refuses are returned.
+    ///
+    /// ```rust
+    /// use postage::{mpsc, prelude::*};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let (mut tx, mut rx) = mpsc::channel(4);
+    ///
+    ///     tx.send_vectored(vec!["header", "body"]).await.ok();
+    ///     assert_eq!(Some("header"), rx.recv().await);
+    ///     assert_eq!(Some("body"), rx.recv().await);
+    /// }
+    /// ```
+    pub async fn send_vectored(&mut self, values: Vec<T>) -> Result<(), SendError<Vec<T>>> {
+        let mut values = Some(values);
+        std::future::poll_fn(|cx| {
+            let mut cx = cx.into();
+            let batch = values.take().expect("polled after completion");
+            match std::pin::Pin::new(&mut *self).poll_send_vectored(&mut cx, batch) {
+                PollSend::Ready => Poll::Ready(Ok(())),
+                PollSend::Pending(batch) => {
+                    values = Some(batch);
+                    Poll::Pending
+                }
+                PollSend::Rejected(batch) => Poll::Ready(Err(SendError(batch))),
+            }
+        })
+        .await
+    }
+
+    /// Buffers the whole batch, unless the buffer or the quota lacks room for it.  Returns the number of messages sent.
+    fn push_vectored(&self, values: Vec<T>) -> Result<usize, Vec<T>> {
+        let extension = self.shared.extension();
+        if extension.is_paused() || extension.is_gated() {
+            return Err(values);
+        }
+
+        let mut permits = Vec::with_capacity(values.len());
+        if let Some(ref quota) = self.quota {
+            for _ in 0..values.len() {
+                match quota.acquire() {
+                    Some(permit) => permits.push(Some(permit)),
+                    None => return Err(values),
+                }
+            }
+        } else {
+            permits.resize_with(values.len(), || None);
+        }
+
+        // other sends wait until the batch is buffered, so batches are not interleaved
+        if !extension.slots.begin_batch(values.len()) {
+            return Err(values);
+        }
+
+        let sent = values.len();
+        for (value, permit) in values.into_iter().zip(permits) {
+            let envelope = Envelope::new(value, extension.timestamps);
+            extension.push(Queued::new(envelope, permit));
+        }
+
+        extension.slots.end_batch();
+        Ok(sent)
+    }
+
+    /// Buffers the message, unless the buffer is full, the receiver is paused, or the sender has reached its quota.
+    /// Returns the sequence number of the message, if the channel is sequenced.
+    fn push(&self, value: T) -> Result<Option<u64>, T> {
+        let extension = self.shared.extension();
+        if extension.is_paused() || extension.is_gated() {
+            return Err(value);
+        }
+
+        let permit = match self.quota {
+            Some(ref quota) => match quota.acquire() {
+                Some(permit) => Some(permit),
+                None => return Err(value),
+            },
+            None => None,
+        };
+
+        while !extension.slots.begin_push() {
+            match extension.overflow {
+                Overflow::Block => return Err(value),
+                Overflow::DropNewest => {
+                    extension
+                        .undelivered
+                        .release(value, DeadLetterReason::Overflowed);
+                    return Ok(extension.skip_seq());
+                }
+                // if another sender takes the slot which was freed, the next oldest message is discarded
+                Overflow::DropOldest => {
+                    if extension.is_paused() || !extension.drop_oldest() {
+                        return Err(value);
+                    }
+                }
+            }
+        }
+
+        let envelope = Envelope::new(value, extension.timestamps);
+        let seq = extension.push(Queued::new(envelope, permit));
+        extension.slots.end_push();
+        Ok(seq)
+    }
+
+    /// Whether a send would wait, because the buffer is full, the receiver is paused, or the sender has reached its quota
+    fn is_full(&self) -> bool {
+        self.shared.extension().must_wait() || self.quota.as_ref().is_some_and(|q| q.is_exhausted())
+    }
+
+    fn record_send(&self) {
+        let extension = self.shared.extension();
+        let depth = extension.len();
+
+        self.shared.tracer().send();
+        if let Some(metrics) = self.shared.metrics() {
+            metrics.on_send(Some(depth));
+        }
+        self.shared.registration().set_depth(depth);
+        if let Some(ref watermarks) = extension.watermarks {
+            watermarks.on_send(depth);
+        }
+    }
+}
+
+impl<T: Copy> Sender<T> {
+    /// Sends every value in the slice, waiting for capacity as needed.
+    ///
+    /// If the channel closes, returns the number of values which were sent.
+    pub async fn send_slice(&mut self, mut values: &[T]) -> Result<(), SendError<usize>> {
+        let mut sent = 0;
+        while !values.is_empty() {
+            let poll = std::future::poll_fn(|cx| {
+                let mut cx = cx.into();
+                std::pin::Pin::new(&mut *self).poll_send_slice(&mut cx, values)
+            });
+
+            match poll.await {
+                Ok(n) => {
+                    sent += n;
+                    values = &values[n..];
+                }
+                Err(_) => return Err(SendError(sent)),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Attempts to send as many values from the slice as the buffer can hold, in one poll.
+    ///
+    /// Returns the number of values which were sent, which is at least one unless the slice is empty.
+    /// Returns `Pending` if the buffer is full, and an error if the channel is closed.
+    /// Receivers are notified once per poll, rather than once per value.
+    pub fn poll_send_slice(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut crate::Context<'_>,
+        values: &[T],
+    ) -> Poll<Result<usize, SendError<()>>> {
+        loop {
+            let guard = self.shared.recv_guard();
+
+            if self.shared.is_closed() {
+                self.shared.tracer().reject();
+                if let Some(metrics) = self.shared.metrics() {
+                    metrics.on_reject();
+                }
+                return Poll::Ready(Err(SendError(())));
+            }
+
+            let mut sent = 0;
+            for value in values {
+                if self.push(*value).is_err() {
+                    break;
+                }
+
+                self.record_send();
+                sent += 1;
+            }
+
+            if sent > 0 || values.is_empty() {
+                self.flush_target = self.shared.extension().pushed_count();
+                self.shared.notify_receivers();
+                return Poll::Ready(Ok(sent));
+            }
+
+            self.shared.subscribe_recv(cx);
+
+            if guard.is_expired() {
+                continue;
+            }
+
+            self.shared.tracer().full();
+            if let Some(metrics) = self.shared.metrics() {
+                metrics.set_blocked_senders(self.shared.blocked_senders());
+            }
+            return Poll::Pending;
+        }
+    }
 }
 
 impl<T> fmt::Debug for Sender<T> {
@@ -87,7 +799,7 @@ mod impl_futures {
     use crate::sink::SendError;
     use std::task::Poll;
 
-    impl<T> futures::sink::Sink<T> for super::Sender<T> {
+    impl<T> futures_sink::Sink<T> for super::Sender<T> {
         type Error = SendError<T>;
 
         fn poll_ready(
@@ -95,16 +807,15 @@ mod impl_futures {
             cx: &mut std::task::Context<'_>,
         ) -> Poll<Result<(), Self::Error>> {
             loop {
+                let guard = self.shared.recv_guard();
+
                 if self.shared.is_closed() {
                     return Poll::Ready(Ok(()));
                 }
 
-                let queue = &self.shared.extension().queue;
-                let guard = self.shared.recv_guard();
-
-                if queue.is_full() {
-                    let mut cx = cx.into();
-                    self.shared.subscribe_recv(&mut cx);
+                if self.is_full() {
+                    let cx = cx.into();
+                    self.shared.subscribe_recv(&cx);
 
                     if guard.is_expired() {
                         continue;
@@ -118,22 +829,21 @@ mod impl_futures {
         }
 
         fn start_send(self: std::pin::Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
-            if self.shared.is_closed() {
-                return Err(SendError(item));
+            let this = self.get_mut();
+            if this.shared.is_closed() {
+                if let Some(metrics) = this.shared.metrics() {
+                    metrics.on_reject();
+                }
+                return Err(SendError(this.shared.extension().undelivered.reject(item)));
             }
 
-            let result = self
-                .shared
-                .extension()
-                .queue
-                .push(item)
-                .map_err(|item| SendError(item));
+            let seq = this.push(item).map_err(SendError)?;
+            this.last_seq = seq;
+            this.flush_target = this.shared.extension().pushed_count();
+            this.record_send();
+            this.shared.notify_receivers();
 
-            if result.is_ok() {
-                self.shared.notify_receivers();
-            }
-
-            result
+            Ok(())
         }
 
         fn poll_flush(
@@ -157,6 +867,7 @@ mod impl_futures {
 /// Can receive messages with the postage::Stream trait.
 pub struct Receiver<T> {
     pub(in crate::channels::mpsc) shared: ReceiverShared<StateExtension<T>>,
+    generation: usize,
 }
 
 assert_impl_all!(Receiver<SendMessage>: Send, Sync, fmt::Debug);
@@ -169,15 +880,226 @@ impl<T> Stream for Receiver<T> {
         self: std::pin::Pin<&mut Self>,
         cx: &mut crate::Context<'_>,
     ) -> PollRecv<Self::Item> {
+        match self.poll_envelope(cx) {
+            PollRecv::Ready(envelope) => PollRecv::Ready(envelope.into_inner()),
+            PollRecv::Pending => PollRecv::Pending,
+            PollRecv::Closed => PollRecv::Closed,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let extension = self.shared.extension();
+        let len = extension.len();
+
+        // buffered messages may expire before they are received
+        let lower = if extension.ttl.is_some() { 0 } else { len };
+
+        // messages may still be sent while a sender is alive
+        if self.shared.is_closed() {
+            (lower, Some(len))
+        } else {
+            (lower, None)
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let inner = self.shared.inner.clone();
+        let _handover = inner.extension.handover.lock();
+
+        // a retired receiver's count was handed over to its replacement
+        if self.is_replaced() {
+            self.shared.disown();
+        } else {
+            self.shared.release();
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Pauses the channel.  While paused, the channel is full to senders, and they wait until it is resumed.
+    ///
+    /// Messages which are already queued are kept, and can still be received.
+    pub fn pause(&self) {
+        self.shared
+            .extension()
+            .paused
+            .store(true, Ordering::Release);
+    }
+
+    /// Resumes the channel, and wakes any senders which were waiting while it was paused
+    pub fn resume(&self) {
+        self.shared
+            .extension()
+            .paused
+            .store(false, Ordering::Release);
+        self.shared.notify_senders();
+    }
+
+    /// Whether the channel has been paused with [pause](#method.pause)
+    pub fn is_paused(&self) -> bool {
+        self.shared.extension().is_paused()
+    }
+
+    /// Returns true if every sender has been dropped, the channel has been stopped, or the receiver has been replaced.
+    ///
+    /// Messages which are still queued can be received, unless the receiver has been replaced.
+    pub fn is_closed(&self) -> bool {
+        self.is_replaced() || self.shared.is_closed()
+    }
+
+    /// The number of receiver tasks which are parked, waiting for messages.
+    ///
+    /// The count is approximate, as a task which is polled again before it is woken may be counted twice.
+    pub fn waiting_receivers(&self) -> usize {
+        self.shared.waiting_receivers()
+    }
+
+    /// Discards the queued messages, and wakes any senders which were waiting for capacity.
+    /// Returns the number of messages which were discarded.
+    ///
+    /// Discarded messages are passed to the dead-letter sink, tagged with `DeadLetterReason::Cleared`, or to the `on_drop` hook.
+    /// Messages which are sent while the channel is being cleared may be kept.
+    pub fn clear(&self) -> usize {
+        if self.is_replaced() {
+            return 0;
+        }
+
+        let extension = self.shared.extension();
+
+        let mut cleared = 0;
+        for _ in 0..extension.len() {
+            let envelope = match extension.pop() {
+                Some(envelope) => envelope,
+                None => break,
+            };
+
+            extension
+                .undelivered
+                .release(envelope.into_inner(), DeadLetterReason::Cleared);
+            cleared += 1;
+        }
+
+        if cleared > 0 {
+            let depth = extension.len();
+            self.shared.registration().set_depth(depth);
+            if let Some(ref watermarks) = extension.watermarks {
+                watermarks.on_recv(depth);
+            }
+
+            self.shared.notify_senders();
+        }
+
+        cleared
+    }
+
+    /// Receives a message, along with the time it spent in the channel buffer.
+    ///
+    /// The age is only available if the channel was constructed with `Builder::track_age`, `Builder::ttl`, or a metrics hook.
+    pub async fn recv_with_age(&mut self) -> Option<(T, Option<Duration>)> {
+        std::future::poll_fn(|cx| {
+            let mut cx = cx.into();
+            match std::pin::Pin::new(&mut *self).poll_recv_with_age(&mut cx) {
+                PollRecv::Ready(value) => std::task::Poll::Ready(Some(value)),
+                PollRecv::Pending => std::task::Poll::Pending,
+                PollRecv::Closed => std::task::Poll::Ready(None),
+            }
+        })
+        .await
+    }
+
+    /// Attempts to receive a message, along with the time it spent in the channel buffer.
+    ///
+    /// The age is only available if the channel was constructed with `Builder::track_age`, `Builder::ttl`, or a metrics hook.
+    pub fn poll_recv_with_age(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut crate::Context<'_>,
+    ) -> PollRecv<(T, Option<Duration>)> {
+        match self.poll_envelope(cx) {
+            PollRecv::Ready(envelope) => {
+                let age = envelope.age();
+                PollRecv::Ready((envelope.into_inner(), age))
+            }
+            PollRecv::Pending => PollRecv::Pending,
+            PollRecv::Closed => PollRecv::Closed,
+        }
+    }
+
+    /// The sequence number of the last message received, or `None` if no message has been received.
+    ///
+    /// Always `None` unless the channel was constructed with `Builder::sequence_numbers`.
+    /// The number is kept when the receiver is replaced, so a new receiver can tell producers where to resume.
+    pub fn last_delivered_seq(&self) -> Option<u64> {
+        self.shared.extension().last_delivered_seq()
+    }
+
+    /// Waits until every message which is buffered when the call is made has been taken from the channel.
+    /// Messages discarded by the channel, such as expired messages, count as taken.
+    ///
+    /// The messages must be received by another task, such as the receiver which
+    /// [replaced](./struct.Sender.html#method.replace_receiver) this one.
+    /// Senders can wait for their messages to be received with [flush](../sink/trait.BufferedSink.html#method.flush).
+    pub async fn emptied(&self) {
+        let extension = self.shared.extension();
+        let target = extension.pushed_count();
+
+        std::future::poll_fn(|cx| loop {
+            let guard = extension.on_pop.guard();
+
+            if extension.popped.load(Ordering::Acquire) >= target {
+                return Poll::Ready(());
+            }
+
+            extension.on_pop.subscribe(&cx.into());
+
+            if guard.is_expired() {
+                continue;
+            }
+
+            return Poll::Pending;
+        })
+        .await
+    }
+
+    /// Whether the receiver has been retired by [Sender::replace_receiver](./struct.Sender.html#method.replace_receiver)
+    pub fn is_replaced(&self) -> bool {
+        self.shared.extension().generation.load(Ordering::Acquire) != self.generation
+    }
+
+    fn poll_envelope(&self, cx: &mut crate::Context<'_>) -> PollRecv<Envelope<T>> {
         loop {
+            if self.is_replaced() {
+                return PollRecv::Closed;
+            }
+
             let guard = self.shared.send_guard();
-            match self.shared.extension().queue.pop() {
-                Some(v) => {
+            let extension = self.shared.extension();
+            match extension.pop_queued() {
+                Some(queued) if extension.is_expired(queued.envelope()) => {
+                    self.shared.tracer().expired();
+                    self.shared.notify_senders();
+                    extension.undelivered.release(
+                        queued.into_envelope().into_inner(),
+                        DeadLetterReason::Expired,
+                    );
+                }
+                Some(queued) => {
+                    let envelope = extension.deliver(queued);
+                    self.record_recv(&envelope);
                     self.shared.notify_senders();
-                    return PollRecv::Ready(v);
+                    if let Some(metrics) = self.shared.metrics() {
+                        metrics.set_blocked_senders(self.shared.blocked_senders());
+                    }
+                    return PollRecv::Ready(envelope);
                 }
                 None => {
                     if self.shared.is_closed() {
+                        // a sender may have pushed a message, and then dropped, since the pop
+                        if guard.is_expired() {
+                            continue;
+                        }
+
                         return PollRecv::Closed;
                     }
 
@@ -192,6 +1114,172 @@ impl<T> Stream for Receiver<T> {
             }
         }
     }
+
+    fn record_recv(&self, envelope: &Envelope<T>) {
+        let extension = self.shared.extension();
+        let depth = extension.len();
+
+        self.shared.tracer().recv();
+        if let Some(metrics) = self.shared.metrics() {
+            metrics.on_recv(Some(depth), envelope.age());
+        }
+        self.shared.registration().set_depth(depth);
+        if let Some(ref watermarks) = extension.watermarks {
+            watermarks.on_recv(depth);
+        }
+    }
+}
+
+impl<T> Receiver<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    /// Converts the receiver into a broadcast channel, so the messages can be observed by multiple receivers.
+    ///
+    /// A forwarding task is spawned with the [spawner](../spawn/trait.Spawn.html), which sends each message to a broadcast channel of the given capacity.
+    /// Additional receivers can be created with [resubscribe](../broadcast/struct.Receiver.html#method.resubscribe), or by cloning the returned receiver.
+    ///
+    /// The forwarder stops, and the mpsc channel is closed, when every broadcast receiver has been dropped.
+    pub fn into_broadcast<Sp>(mut self, capacity: usize, spawner: &Sp) -> broadcast::Receiver<T>
+    where
+        Sp: Spawn + ?Sized,
+    {
+        let (mut tx, rx) = broadcast::channel(capacity);
+
+        spawner.spawn(Box::pin(async move {
+            while let Some(message) = self.recv().await {
+                if tx.send(message).await.is_err() {
+                    break;
+                }
+            }
+        }));
+
+        rx
+    }
+}
+
+impl<T: Clone> Receiver<T> {
+    /// Returns a copy of the buffered messages, in the order they will be received.  The messages are not consumed.
+    ///
+    /// Messages which are sent while the snapshot is taken may not be included.
+    pub fn snapshot(&self) -> Vec<T> {
+        let extension = self.shared.extension();
+
+        // the queue can't be iterated, so the messages are moved to the stash, where they are delivered first
+        let mut stash = extension.stash.lock();
+        for _ in 0..extension.queue.len() {
+            // count the message before it leaves the queue, so senders never observe its slot as free
+            extension.stashed.fetch_add(1, Ordering::AcqRel);
+            match extension.queue.pop() {
+                Some(queued) => stash.push_back(queued),
+                None => {
+                    extension.stashed.fetch_sub(1, Ordering::AcqRel);
+                    break;
+                }
+            }
+        }
+
+        stash
+            .iter()
+            .map(|queued| queued.envelope().get_ref().clone())
+            .collect()
+    }
+}
+
+impl<T: Copy> Receiver<T> {
+    /// Receives at least one message into the buffer, and returns the number of messages received.
+    ///
+    /// Returns `None` if the channel is closed.
+    pub async fn recv_slice(&mut self, buf: &mut [T]) -> Option<usize> {
+        std::future::poll_fn(|cx| {
+            let mut cx = cx.into();
+            match std::pin::Pin::new(&mut *self).poll_recv_slice(&mut cx, buf) {
+                PollRecv::Ready(n) => Poll::Ready(Some(n)),
+                PollRecv::Pending => Poll::Pending,
+                PollRecv::Closed => Poll::Ready(None),
+            }
+        })
+        .await
+    }
+
+    /// Attempts to fill the buffer with queued messages, in one poll.
+    ///
+    /// Returns the number of messages received, which is at least one unless the buffer is empty.
+    /// Senders are notified once per poll, rather than once per message.
+    pub fn poll_recv_slice(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut crate::Context<'_>,
+        buf: &mut [T],
+    ) -> PollRecv<usize> {
+        if buf.is_empty() {
+            return PollRecv::Ready(0);
+        }
+
+        loop {
+            if self.is_replaced() {
+                return PollRecv::Closed;
+            }
+
+            let guard = self.shared.send_guard();
+            let extension = self.shared.extension();
+
+            let mut received = 0;
+            let mut popped = false;
+            while received < buf.len() {
+                let queued = match extension.pop_queued() {
+                    Some(queued) => queued,
+                    None => break,
+                };
+
+                popped = true;
+                if extension.is_expired(queued.envelope()) {
+                    self.shared.tracer().expired();
+                    extension.undelivered.release(
+                        queued.into_envelope().into_inner(),
+                        DeadLetterReason::Expired,
+                    );
+                    continue;
+                }
+
+                let envelope = extension.deliver(queued);
+                self.record_recv(&envelope);
+                buf[received] = envelope.into_inner();
+                received += 1;
+            }
+
+            if popped {
+                self.shared.notify_senders();
+                if let Some(metrics) = self.shared.metrics() {
+                    metrics.set_blocked_senders(self.shared.blocked_senders());
+                }
+            }
+
+            if received > 0 {
+                return PollRecv::Ready(received);
+            }
+
+            if popped {
+                continue;
+            }
+
+            if self.shared.is_closed() {
+                // a sender may have pushed a message, and then dropped, since the pop
+                if guard.is_expired() {
+                    continue;
+                }
+
+                return PollRecv::Closed;
+            }
+
+            self.shared.subscribe_send(cx);
+
+            if guard.is_expired() {
+                continue;
+            }
+
+            return PollRecv::Pending;
+        }
+    }
 }
 
 impl<T> fmt::Debug for Receiver<T> {
@@ -201,31 +1289,217 @@ impl<T> fmt::Debug for Receiver<T> {
 }
 
 struct StateExtension<T> {
-    queue: ArrayQueue<T>,
+    queue: ArrayQueue<Queued<T>>,
+    capacity: usize,
+    #[cfg(feature = "serde")]
+    name: Option<String>,
+    /// Messages moved out of the queue by `Receiver::snapshot`, which are delivered before the queue
+    stash: Mutex<VecDeque<Queued<T>>>,
+    stashed: AtomicUsize,
+    undelivered: Undelivered<T>,
+    watermarks: Option<Watermarks>,
+    ttl: Option<Duration>,
+    #[cfg(feature = "serde")]
+    track_age: bool,
+    timestamps: bool,
+    paused: AtomicBool,
+    /// Incremented by `Sender::replace_receiver`, which retires the previous receiver
+    generation: AtomicUsize,
+    /// Held while a receiver is replaced or dropped, so the receiver count is handed over or released exactly once
+    handover: Mutex<()>,
+    sender_quota: Option<usize>,
+    /// The slots claimed by buffered messages, send permits, and pushes in progress
+    slots: Slots,
+    overflow: Overflow,
+    /// The last sequence number assigned, if the channel is sequenced.  Held while a numbered message is pushed,
+    /// so the numbers are buffered in order.
+    sequence: Option<Mutex<u64>>,
+    /// The sequence number of the last message received, or zero
+    delivered: AtomicU64,
+    /// The number of messages buffered, and taken from the buffer, since the channel was constructed
+    pushed: AtomicU64,
+    popped: AtomicU64,
+    /// Notified when a message is taken from the buffer, for flushes which wait for the receiver
+    on_pop: Notifier,
+    /// Consumed by messages in this buffer, if it is the downstream channel of a linked pair
+    credits: Option<Arc<Credits>>,
+    /// The downstream channel's credits, if this is the upstream channel of a linked pair
+    gate: Option<Arc<Credits>>,
 }
 
 impl<T> StateExtension<T> {
-    pub fn new(capacity: usize) -> Self {
-        Self {
-            queue: ArrayQueue::new(capacity),
+    fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Acquire)
+    }
+
+    /// Whether the downstream channel of a linked pair is full
+    fn is_gated(&self) -> bool {
+        self.gate.as_ref().is_some_and(|gate| gate.is_exhausted())
+    }
+
+    /// Whether senders must wait, because the buffer is full, the receiver is paused, or the downstream channel is full
+    fn is_full(&self) -> bool {
+        if self.is_paused() || self.is_gated() {
+            return true;
+        }
+
+        // stashed messages and reserved slots still count towards the capacity
+        self.slots.is_full()
+    }
+
+    /// Whether senders must wait, because the buffer is full and the overflow policy does not discard messages
+    fn must_wait(&self) -> bool {
+        self.is_full() && (self.overflow == Overflow::Block || self.is_paused() || self.is_gated())
+    }
+
+    /// Discards the oldest buffered message, to make room for a new message.  Returns false if no message is buffered.
+    fn drop_oldest(&self) -> bool {
+        match self.pop() {
+            Some(envelope) => {
+                self.undelivered
+                    .release(envelope.into_inner(), DeadLetterReason::Overflowed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The number of buffered messages
+    fn len(&self) -> usize {
+        self.queue.len() + self.stashed.load(Ordering::Acquire)
+    }
+
+    /// Buffers the message into a claimed slot, and assigns it the next sequence number if the channel is sequenced
+    fn push(&self, queued: Queued<T>) -> Option<u64> {
+        // the message is counted before it is buffered, so a flush which observes it in the buffer also counts it
+        self.pushed.fetch_add(1, Ordering::AcqRel);
+
+        let seq = match self.sequence {
+            Some(ref sequence) => {
+                let mut last = sequence.lock();
+                *last += 1;
+                self.push_queue(queued.with_seq(*last));
+                Some(*last)
+            }
+            None => {
+                self.push_queue(queued);
+                None
+            }
+        };
+
+        if let Some(ref credits) = self.credits {
+            credits.consume();
+        }
+
+        seq
+    }
+
+    fn push_queue(&self, queued: Queued<T>) {
+        if self.queue.push(queued).is_err() {
+            unreachable!("the message has a claimed slot");
+        }
+    }
+
+    /// The number of messages buffered since the channel was constructed, including pushes in progress
+    fn pushed_count(&self) -> u64 {
+        self.pushed.load(Ordering::Acquire)
+    }
+
+    /// Consumes a sequence number for a message which was discarded instead of buffered
+    fn skip_seq(&self) -> Option<u64> {
+        let mut last = self.sequence.as_ref()?.lock();
+        *last += 1;
+        Some(*last)
+    }
+
+    /// Records the sequence number of a message which is being delivered to the receiver
+    fn deliver(&self, queued: Queued<T>) -> Envelope<T> {
+        if let Some(seq) = queued.seq() {
+            self.delivered.store(seq, Ordering::Release);
+        }
+
+        queued.into_envelope()
+    }
+
+    fn last_delivered_seq(&self) -> Option<u64> {
+        match self.delivered.load(Ordering::Acquire) {
+            0 => None,
+            seq => Some(seq),
+        }
+    }
+
+    /// Takes the next buffered message, from the stash and then the queue
+    fn pop(&self) -> Option<Envelope<T>> {
+        self.pop_queued().map(Queued::into_envelope)
+    }
+
+    fn pop_queued(&self) -> Option<Queued<T>> {
+        let queued = self.take_queued()?;
+        self.slots.release();
+        self.popped.fetch_add(1, Ordering::AcqRel);
+        self.on_pop.notify();
+        if let Some(ref credits) = self.credits {
+            credits.release();
+        }
+
+        Some(queued)
+    }
+
+    fn take_queued(&self) -> Option<Queued<T>> {
+        if self.stashed.load(Ordering::Acquire) > 0 {
+            let mut stash = self.stash.lock();
+            if let Some(queued) = stash.pop_front() {
+                self.stashed.store(stash.len(), Ordering::Release);
+                return Some(queued);
+            }
+        }
+
+        self.queue.pop()
+    }
+
+    fn is_expired(&self, envelope: &Envelope<T>) -> bool {
+        match (self.ttl, envelope.age()) {
+            (Some(ttl), Some(age)) => age > ttl,
+            _ => false,
+        }
+    }
+}
+
+impl<T> Drop for StateExtension<T> {
+    fn drop(&mut self) {
+        if !self.undelivered.is_some() {
+            return;
+        }
+
+        while let Some(envelope) = self.pop() {
+            self.undelivered
+                .release(envelope.into_inner(), DeadLetterReason::Undelivered);
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{pin::Pin, task::Context};
+    use std::{
+        pin::Pin,
+        sync::Arc,
+        task::{Context, Poll},
+        time::Duration,
+    };
+
+    use parking_lot::Mutex;
 
     use crate::{
-        sink::{PollSend, Sink},
-        stream::{PollRecv, Stream},
+        dead_letter::{DeadLetter, DeadLetterReason},
+        sink::{BufferedSink, PollReady, PollSend, SendError, Sink, TrySendError},
+        stream::{PollRecv, Stream, TryRecvError},
         test::{noop_context, panic_context},
     };
     use futures_test::task::new_count_waker;
 
-    use super::{channel, Receiver, Sender};
+    use super::{channel, Builder, Overflow, Receiver, Sender};
 
-    fn pin<'a, 'b>(
+    fn pin(
         chan: &mut (Sender<Message>, Receiver<Message>),
     ) -> (Pin<&mut Sender<Message>>, Pin<&mut Receiver<Message>>) {
         let tx = Pin::new(&mut chan.0);
@@ -234,7 +1508,7 @@ mod tests {
         (tx, rx)
     }
 
-    #[derive(Debug, PartialEq, Eq)]
+    #[derive(Clone, Debug, PartialEq, Eq)]
     struct Message(usize);
 
     #[test]
@@ -295,6 +1569,357 @@ mod tests {
         );
     }
 
+    #[test]
+    fn builder_send_recv() {
+        let mut cx = panic_context();
+        let (mut tx, mut rx) = Builder::new(1).name("builder").build();
+
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, Message(1))
+        );
+        assert_eq!(
+            PollSend::Pending(Message(2)),
+            Pin::new(&mut tx).poll_send(&mut noop_context(), Message(2))
+        );
+        assert_eq!(
+            PollRecv::Ready(Message(1)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+    }
+
+    #[test]
+    fn dead_letter_undelivered() {
+        let (dead_tx, mut dead_rx) = crate::mpsc::channel(4);
+        let (mut tx, mut rx) = Builder::new(4).dead_letter(dead_tx).build();
+
+        tx.try_send(Message(1)).unwrap();
+        tx.try_send(Message(2)).unwrap();
+        assert_eq!(Ok(Message(1)), rx.try_recv());
+
+        drop(tx);
+        drop(rx);
+
+        assert_eq!(
+            Ok(DeadLetter {
+                value: Message(2),
+                reason: DeadLetterReason::Undelivered
+            }),
+            dead_rx.try_recv()
+        );
+        assert!(dead_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn on_drop_undelivered() {
+        let dropped = Arc::new(Mutex::new(Vec::new()));
+        let hook = dropped.clone();
+        let (dead_tx, dead_rx) = crate::mpsc::channel(1);
+        let (mut tx, rx) = Builder::new(4)
+            .dead_letter(dead_tx)
+            .on_drop(move |message| hook.lock().push(message))
+            .build();
+
+        tx.try_send(Message(1)).unwrap();
+        tx.try_send(Message(2)).unwrap();
+        drop(tx);
+        drop(rx);
+
+        // the dead-letter channel accepts the first message, and is then full
+        assert_eq!(vec![Message(2)], *dropped.lock());
+        drop(dead_rx);
+    }
+
+    #[test]
+    fn dead_letter_rejected() {
+        let (dead_tx, mut dead_rx) = crate::mpsc::channel(1);
+        let (mut tx, rx) = Builder::new(4)
+            .dead_letter(dead_tx)
+            .dead_letter_rejected()
+            .build();
+        drop(rx);
+
+        // the message is returned to the sender, and a copy is forwarded to the dead-letter sink
+        assert_eq!(
+            Err(TrySendError::Rejected(Message(1))),
+            tx.try_send(Message(1))
+        );
+        assert_eq!(
+            Ok(DeadLetter {
+                value: Message(1),
+                reason: DeadLetterReason::Rejected
+            }),
+            dead_rx.try_recv()
+        );
+
+        // once the dead-letter channel is full, the copy is discarded
+        assert_eq!(
+            Err(TrySendError::Rejected(Message(2))),
+            tx.try_send(Message(2))
+        );
+        assert_eq!(
+            Err(TrySendError::Rejected(Message(3))),
+            tx.try_send(Message(3))
+        );
+        assert_eq!(Message(2), dead_rx.try_recv().unwrap().value);
+        assert!(dead_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn dead_letter_rejected_without_copies() {
+        let (dead_tx, mut dead_rx) = crate::mpsc::channel(1);
+        let (mut tx, rx) = Builder::new(4).dead_letter(dead_tx).build();
+        drop(rx);
+
+        assert_eq!(
+            Err(TrySendError::Rejected(Message(1))),
+            tx.try_send(Message(1))
+        );
+        assert!(dead_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn dead_letter_rejected_vectored() {
+        let mut cx = noop_context();
+        let (dead_tx, mut dead_rx) = crate::mpsc::channel(2);
+        let (mut tx, rx) = Builder::new(4)
+            .dead_letter(dead_tx)
+            .dead_letter_rejected()
+            .build();
+        drop(rx);
+
+        assert_eq!(
+            PollSend::Rejected(vec![Message(1), Message(2), Message(3)]),
+            Pin::new(&mut tx).poll_send_vectored(&mut cx, vec![Message(1), Message(2), Message(3)])
+        );
+        assert_eq!(Message(1), dead_rx.try_recv().unwrap().value);
+        assert_eq!(Message(2), dead_rx.try_recv().unwrap().value);
+    }
+
+    #[test]
+    fn hooks_reentrant() {
+        let stop = crate::stop::StopSource::new();
+        let slot: Arc<Mutex<Option<Sender<Message>>>> = Arc::new(Mutex::new(None));
+        let rejected = Arc::new(Mutex::new(Vec::new()));
+
+        // the hook sends to the channel which released the message, while the hooks are in use
+        let (hook_slot, hook_rejected) = (slot.clone(), rejected.clone());
+        let (mut tx, rx) = Builder::new(4)
+            .stop_token(stop.token())
+            .on_drop(move |message: Message| {
+                if let Some(tx) = hook_slot.lock().as_mut() {
+                    if let Err(TrySendError::Rejected(message)) = tx.try_send(message) {
+                        hook_rejected.lock().push(message);
+                    }
+                }
+            })
+            .build();
+
+        tx.try_send(Message(1)).unwrap();
+        tx.try_send(Message(2)).unwrap();
+        *slot.lock() = Some(tx.clone());
+        stop.stop();
+
+        assert_eq!(2, rx.clear());
+        assert_eq!(vec![Message(1), Message(2)], *rejected.lock());
+
+        // breaks the cycle between the hook and the channel
+        slot.lock().take();
+    }
+
+    #[test]
+    fn clear() {
+        let dropped = Arc::new(Mutex::new(Vec::new()));
+        let hook = dropped.clone();
+        let (mut tx, mut rx) = Builder::new(2)
+            .on_drop(move |message| hook.lock().push(message))
+            .build();
+
+        tx.try_send(Message(1)).unwrap();
+        tx.try_send(Message(2)).unwrap();
+
+        let (waker, count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker).into();
+        assert_eq!(
+            PollSend::Pending(Message(3)),
+            Pin::new(&mut tx).poll_send(&mut cx, Message(3))
+        );
+
+        assert_eq!(2, rx.clear());
+        assert_eq!(vec![Message(1), Message(2)], *dropped.lock());
+        assert_eq!(1, count.get());
+
+        tx.try_send(Message(3)).unwrap();
+        assert_eq!(Ok(Message(3)), rx.try_recv());
+        assert_eq!(0, rx.clear());
+    }
+
+    #[test]
+    fn clear_dead_letter() {
+        let (dead_tx, mut dead_rx) = crate::mpsc::channel(4);
+        let (mut tx, rx) = Builder::new(4).dead_letter(dead_tx).build();
+
+        tx.try_send(Message(1)).unwrap();
+        assert_eq!(1, rx.clear());
+        assert_eq!(
+            Ok(DeadLetter {
+                value: Message(1),
+                reason: DeadLetterReason::Cleared
+            }),
+            dead_rx.try_recv()
+        );
+    }
+
+    #[test]
+    fn overflow_drop_newest() {
+        let (dead_tx, mut dead_rx) = crate::mpsc::channel(4);
+        let (mut tx, mut rx) = Builder::new(1)
+            .overflow(Overflow::DropNewest)
+            .dead_letter(dead_tx)
+            .build();
+
+        tx.try_send(Message(1)).unwrap();
+        tx.try_send(Message(2)).unwrap();
+
+        assert_eq!(Ok(Message(1)), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Pending), rx.try_recv());
+        assert_eq!(
+            Ok(DeadLetter {
+                value: Message(2),
+                reason: DeadLetterReason::Overflowed
+            }),
+            dead_rx.try_recv()
+        );
+    }
+
+    #[test]
+    fn overflow_drop_oldest() {
+        let dropped = Arc::new(Mutex::new(Vec::new()));
+        let hook = dropped.clone();
+        let (mut tx, mut rx) = Builder::new(2)
+            .overflow(Overflow::DropOldest)
+            .on_drop(move |message| hook.lock().push(message))
+            .build();
+
+        for i in 1..=4 {
+            tx.try_send(Message(i)).unwrap();
+        }
+
+        assert_eq!(Ok(Message(3)), rx.try_recv());
+        assert_eq!(Ok(Message(4)), rx.try_recv());
+        assert_eq!(vec![Message(1), Message(2)], *dropped.lock());
+    }
+
+    #[test]
+    fn overflow_waits_while_paused() {
+        let (mut tx, mut rx) = Builder::new(1).overflow(Overflow::DropOldest).build();
+
+        tx.try_send(Message(1)).unwrap();
+        rx.pause();
+        assert_eq!(
+            Err(TrySendError::Pending(Message(2))),
+            tx.try_send(Message(2))
+        );
+
+        rx.resume();
+        tx.try_send(Message(2)).unwrap();
+        assert_eq!(Ok(Message(2)), rx.try_recv());
+    }
+
+    #[test]
+    fn ttl_expires() {
+        let (dead_tx, mut dead_rx) = crate::mpsc::channel(4);
+        let (mut tx, mut rx) = Builder::new(4)
+            .ttl(Duration::from_millis(10))
+            .dead_letter(dead_tx)
+            .build();
+
+        tx.try_send(Message(1)).unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        tx.try_send(Message(2)).unwrap();
+
+        assert_eq!(Ok(Message(2)), rx.try_recv());
+        assert_eq!(
+            Ok(DeadLetter {
+                value: Message(1),
+                reason: DeadLetterReason::Expired
+            }),
+            dead_rx.try_recv()
+        );
+    }
+
+    #[test]
+    fn recv_with_age() {
+        let mut cx = noop_context();
+        let (mut tx, mut rx) = Builder::new(4).track_age().build();
+
+        tx.try_send(Message(1)).unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+
+        match Pin::new(&mut rx).poll_recv_with_age(&mut cx) {
+            PollRecv::Ready((message, age)) => {
+                assert_eq!(Message(1), message);
+                assert!(age.unwrap() >= Duration::from_millis(5));
+            }
+            other => panic!("expected Ready, got {:?}", other),
+        }
+
+        let (mut tx, mut rx) = channel(4);
+        tx.try_send(Message(2)).unwrap();
+        assert_eq!(
+            PollRecv::Ready((Message(2), None)),
+            Pin::new(&mut rx).poll_recv_with_age(&mut cx)
+        );
+    }
+
+    #[test]
+    fn poll_ready() {
+        let mut cx = noop_context();
+        let (mut tx, mut rx) = channel(1);
+
+        assert_eq!(PollReady::Ready, Pin::new(&mut tx).poll_ready(&mut cx));
+        tx.try_send(Message(1)).unwrap();
+
+        let (waker, count) = new_count_waker();
+        let mut wake_cx = Context::from_waker(&waker);
+        assert_eq!(
+            PollReady::Pending,
+            Pin::new(&mut tx).poll_ready(&mut (&mut wake_cx).into())
+        );
+
+        assert_eq!(Ok(Message(1)), rx.try_recv());
+        assert_eq!(1, count.get());
+        assert_eq!(PollReady::Ready, Pin::new(&mut tx).poll_ready(&mut cx));
+        assert_eq!(PollReady::Ready, Pin::new(&mut tx).poll_flush(&mut cx));
+
+        drop(rx);
+        assert_eq!(PollReady::Closed, Pin::new(&mut tx).poll_ready(&mut cx));
+        assert_eq!(PollReady::Closed, Pin::new(&mut tx).poll_close(&mut cx));
+    }
+
+    #[test]
+    fn size_hint() {
+        let (mut tx, rx) = channel(4);
+        assert_eq!((0, None), rx.size_hint());
+
+        tx.try_send(Message(1)).unwrap();
+        tx.try_send(Message(2)).unwrap();
+        assert_eq!((2, None), rx.size_hint());
+
+        drop(tx);
+        assert_eq!((2, Some(2)), rx.size_hint());
+    }
+
+    #[test]
+    fn size_hint_ttl() {
+        let (mut tx, rx) = Builder::new(4).ttl(Duration::from_secs(60)).build();
+        tx.try_send(Message(1)).unwrap();
+        assert_eq!((0, None), rx.size_hint());
+
+        drop(tx);
+        assert_eq!((0, Some(1)), rx.size_hint());
+    }
+
     #[test]
     fn sender_disconnect() {
         let mut cx = panic_context();
@@ -462,6 +2087,577 @@ mod tests {
 
         assert_eq!(1, w1_count.get());
     }
+
+    #[test]
+    fn pause_resume() {
+        let (mut tx, mut rx) = channel(4);
+        tx.try_send(Message(1)).unwrap();
+
+        rx.pause();
+        assert!(rx.is_paused());
+        assert_eq!(
+            Err(TrySendError::Pending(Message(2))),
+            tx.try_send(Message(2))
+        );
+        assert_eq!(Ok(Message(1)), rx.try_recv());
+
+        rx.resume();
+        assert!(!rx.is_paused());
+        tx.try_send(Message(2)).unwrap();
+        assert_eq!(Ok(Message(2)), rx.try_recv());
+    }
+
+    #[test]
+    fn resume_wakes_sender() {
+        let (mut tx, rx) = channel(4);
+        let (waker, count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker).into();
+
+        rx.pause();
+        assert_eq!(
+            PollSend::Pending(Message(1)),
+            Pin::new(&mut tx).poll_send(&mut cx, Message(1))
+        );
+        assert_eq!(PollReady::Pending, Pin::new(&mut tx).poll_ready(&mut cx));
+
+        assert_eq!(0, count.get());
+
+        rx.resume();
+        assert!(count.get() > 0);
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, Message(1))
+        );
+    }
+
+    #[test]
+    fn sender_quota() {
+        let (mut tx, mut rx) = Builder::new(4).sender_quota(2).build();
+        let mut other = tx.clone();
+
+        tx.try_send(Message(1)).unwrap();
+        tx.try_send(Message(2)).unwrap();
+        assert_eq!(
+            Err(TrySendError::Pending(Message(3))),
+            tx.try_send(Message(3))
+        );
+
+        // the buffer has room, and the clone has its own quota
+        other.try_send(Message(10)).unwrap();
+
+        assert_eq!(Ok(Message(1)), rx.try_recv());
+        tx.try_send(Message(3)).unwrap();
+    }
+
+    #[test]
+    fn sender_quota_wakes_sender() {
+        let (mut tx, mut rx) = Builder::new(4).sender_quota(1).build();
+        let (waker, count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker).into();
+
+        tx.try_send(Message(1)).unwrap();
+        assert_eq!(PollReady::Pending, Pin::new(&mut tx).poll_ready(&mut cx));
+        assert_eq!(0, count.get());
+
+        assert_eq!(Ok(Message(1)), rx.try_recv());
+        assert!(count.get() > 0);
+        assert_eq!(PollReady::Ready, Pin::new(&mut tx).poll_ready(&mut cx));
+    }
+
+    #[test]
+    fn snapshot() {
+        let (mut tx, mut rx) = channel(3);
+        tx.try_send(Message(1)).unwrap();
+        tx.try_send(Message(2)).unwrap();
+
+        assert_eq!(vec![Message(1), Message(2)], rx.snapshot());
+
+        // the snapshot messages still count towards the capacity, and are received first
+        tx.try_send(Message(3)).unwrap();
+        assert_eq!(
+            Err(TrySendError::Pending(Message(4))),
+            tx.try_send(Message(4))
+        );
+        assert_eq!(vec![Message(1), Message(2), Message(3)], rx.snapshot());
+        assert_eq!((3, None), rx.size_hint());
+
+        assert_eq!(Ok(Message(1)), rx.try_recv());
+        tx.try_send(Message(4)).unwrap();
+        assert_eq!(Ok(Message(2)), rx.try_recv());
+        assert_eq!(Ok(Message(3)), rx.try_recv());
+        assert_eq!(Ok(Message(4)), rx.try_recv());
+        assert!(rx.snapshot().is_empty());
+    }
+
+    #[test]
+    fn snapshot_undelivered() {
+        let dropped = Arc::new(Mutex::new(Vec::new()));
+        let hook = dropped.clone();
+        let (mut tx, rx) = Builder::new(2)
+            .on_drop(move |message| hook.lock().push(message))
+            .build();
+
+        tx.try_send(Message(1)).unwrap();
+        assert_eq!(vec![Message(1)], rx.snapshot());
+        assert_eq!(1, rx.clear());
+        assert_eq!(vec![Message(1)], *dropped.lock());
+
+        tx.try_send(Message(2)).unwrap();
+        rx.snapshot();
+        drop(rx);
+        drop(tx);
+        assert_eq!(vec![Message(1), Message(2)], *dropped.lock());
+    }
+
+    #[test]
+    fn replace_receiver() {
+        let (mut tx, mut rx) = channel(4);
+        tx.try_send(Message(1)).unwrap();
+        tx.try_send(Message(2)).unwrap();
+        assert_eq!(Ok(Message(1)), rx.try_recv());
+
+        let mut replacement = tx.replace_receiver();
+        assert!(rx.is_replaced());
+        assert!(!replacement.is_replaced());
+        assert_eq!(Err(TryRecvError::Closed), rx.try_recv());
+
+        tx.try_send(Message(3)).unwrap();
+        assert_eq!(Ok(Message(2)), replacement.try_recv());
+        assert_eq!(Ok(Message(3)), replacement.try_recv());
+
+        drop(replacement);
+        drop(rx);
+        assert!(tx.try_send(Message(4)).is_err());
+    }
+
+    #[test]
+    fn replace_receiver_drop_replacement() {
+        let (mut tx, rx) = channel(4);
+        let replacement = tx.replace_receiver();
+
+        // the retired receiver doesn't keep the channel open
+        drop(replacement);
+        assert!(tx.is_closed());
+        assert_eq!(
+            Err(TrySendError::Rejected(Message(1))),
+            tx.try_send(Message(1))
+        );
+
+        drop(rx);
+        assert!(tx.is_closed());
+    }
+
+    #[test]
+    fn replace_dropped_receiver() {
+        let (mut tx, rx) = channel(4);
+        drop(rx);
+        assert!(tx.is_closed());
+
+        let mut replacement = tx.replace_receiver();
+        assert!(!tx.is_closed());
+        tx.try_send(Message(1)).unwrap();
+        assert_eq!(Ok(Message(1)), replacement.try_recv());
+    }
+
+    #[test]
+    fn replace_receiver_wakes_receiver() {
+        let (tx, mut rx) = channel::<Message>(4);
+        let (waker, count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker).into();
+
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx).poll_recv(&mut cx));
+        let _replacement = tx.replace_receiver();
+
+        assert_eq!(1, count.get());
+        assert_eq!(PollRecv::Closed, Pin::new(&mut rx).poll_recv(&mut cx));
+    }
+
+    #[test]
+    fn send_recv_slice() {
+        let mut cx = panic_context();
+        let (mut tx, mut rx) = channel::<u16>(4);
+
+        assert_eq!(
+            Poll::Ready(Ok(4)),
+            Pin::new(&mut tx).poll_send_slice(&mut cx, &[1, 2, 3, 4, 5])
+        );
+
+        let mut buf = [0; 3];
+        assert_eq!(
+            PollRecv::Ready(3),
+            Pin::new(&mut rx).poll_recv_slice(&mut cx, &mut buf)
+        );
+        assert_eq!([1, 2, 3], buf);
+
+        assert_eq!(
+            PollRecv::Ready(1),
+            Pin::new(&mut rx).poll_recv_slice(&mut cx, &mut buf)
+        );
+        assert_eq!(4, buf[0]);
+    }
+
+    #[test]
+    fn send_slice_full() {
+        let mut cx = noop_context();
+        let (mut tx, rx) = channel::<u16>(2);
+
+        assert_eq!(
+            Poll::Ready(Ok(2)),
+            Pin::new(&mut tx).poll_send_slice(&mut cx, &[1, 2, 3])
+        );
+        assert_eq!(
+            Poll::Pending,
+            Pin::new(&mut tx).poll_send_slice(&mut cx, &[3])
+        );
+
+        drop(rx);
+        assert_eq!(
+            Poll::Ready(Err(SendError(()))),
+            Pin::new(&mut tx).poll_send_slice(&mut cx, &[3])
+        );
+    }
+
+    #[test]
+    fn send_vectored_all_or_none() {
+        let mut cx = noop_context();
+        let (mut tx, mut rx) = channel(3);
+
+        tx.try_send(1usize).unwrap();
+        assert_eq!(
+            PollSend::Pending(vec![2, 3, 4]),
+            Pin::new(&mut tx).poll_send_vectored(&mut cx, vec![2, 3, 4])
+        );
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send_vectored(&mut cx, vec![2, 3])
+        );
+
+        for expected in 1..=3 {
+            assert_eq!(Ok(expected), rx.try_recv());
+        }
+        assert_eq!(Err(TryRecvError::Pending), rx.try_recv());
+    }
+
+    #[test]
+    fn send_vectored_rejected() {
+        let mut cx = noop_context();
+        let (mut tx, rx) = Builder::new(4).sender_quota(2).build();
+
+        assert_eq!(
+            PollSend::Rejected(vec![1usize, 2, 3]),
+            Pin::new(&mut tx).poll_send_vectored(&mut cx, vec![1, 2, 3])
+        );
+
+        drop(rx);
+        assert_eq!(
+            PollSend::Rejected(vec![1]),
+            Pin::new(&mut tx).poll_send_vectored(&mut cx, vec![1])
+        );
+    }
+
+    #[test]
+    fn send_vectored_wakes() {
+        let (mut tx, mut rx) = channel(2);
+
+        tx.try_send(1usize).unwrap();
+        tx.try_send(2).unwrap();
+
+        let (waker, count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker).into();
+        assert_eq!(
+            PollSend::Pending(vec![3, 4]),
+            Pin::new(&mut tx).poll_send_vectored(&mut cx, vec![3, 4])
+        );
+
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(1, count.get());
+        assert_eq!(
+            PollSend::Pending(vec![3, 4]),
+            Pin::new(&mut tx).poll_send_vectored(&mut cx, vec![3, 4])
+        );
+
+        assert_eq!(Ok(2), rx.try_recv());
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send_vectored(&mut cx, vec![3, 4])
+        );
+    }
+
+    #[test]
+    fn send_vectored_not_interleaved() {
+        let (tx, mut rx) = channel(8);
+
+        let senders: Vec<_> = (0..2usize)
+            .map(|id| {
+                let mut tx = tx.clone();
+                std::thread::spawn(move || {
+                    let mut cx = noop_context();
+                    for _ in 0..500 {
+                        let mut batch = vec![id; 3];
+                        loop {
+                            match Pin::new(&mut tx).poll_send_vectored(&mut cx, batch) {
+                                PollSend::Ready => break,
+                                PollSend::Pending(unsent) => batch = unsent,
+                                PollSend::Rejected(_) => panic!("the channel is open"),
+                            }
+
+                            std::thread::yield_now();
+                        }
+
+                        while tx.try_send(id + 10).is_err() {
+                            std::thread::yield_now();
+                        }
+                    }
+                })
+            })
+            .collect();
+        drop(tx);
+
+        let mut received = Vec::new();
+        loop {
+            match rx.try_recv() {
+                Ok(value) => received.push(value),
+                Err(TryRecvError::Pending) => std::thread::yield_now(),
+                Err(TryRecvError::Closed) => break,
+            }
+        }
+
+        for sender in senders {
+            sender.join().unwrap();
+        }
+
+        // single sends are numbered from 10, and batch messages are numbered by sender
+        let mut messages = received.into_iter();
+        while let Some(id) = messages.next() {
+            if id < 10 {
+                assert_eq!(Some(id), messages.next());
+                assert_eq!(Some(id), messages.next());
+            }
+        }
+    }
+
+    #[test]
+    fn recv_slice_closed() {
+        let mut cx = noop_context();
+        let (mut tx, mut rx) = channel::<u16>(2);
+        let mut buf = [0; 4];
+
+        assert_eq!(
+            PollRecv::Pending,
+            Pin::new(&mut rx).poll_recv_slice(&mut cx, &mut buf)
+        );
+
+        tx.try_send(1).unwrap();
+        drop(tx);
+        assert_eq!(
+            PollRecv::Ready(1),
+            Pin::new(&mut rx).poll_recv_slice(&mut cx, &mut buf)
+        );
+        assert_eq!(
+            PollRecv::Closed,
+            Pin::new(&mut rx).poll_recv_slice(&mut cx, &mut buf)
+        );
+    }
+
+    #[test]
+    fn is_closed() {
+        let (tx, rx) = channel::<Message>(4);
+        assert!(!tx.is_closed());
+        assert!(!rx.is_closed());
+
+        let replacement = tx.replace_receiver();
+        assert!(rx.is_closed());
+        assert!(!replacement.is_closed());
+
+        drop(tx);
+        assert!(replacement.is_closed());
+
+        let (tx, rx) = channel::<Message>(4);
+        drop(rx);
+        assert!(tx.is_closed());
+    }
+
+    #[test]
+    fn is_closed_stopped() {
+        let stop = crate::stop::StopSource::new();
+        let (tx, rx) = Builder::<Message>::new(4).stop_token(stop.token()).build();
+
+        stop.stop();
+        assert!(tx.is_closed());
+        assert!(rx.is_closed());
+    }
+
+    #[test]
+    fn sequence_numbers() {
+        let (mut tx, mut rx) = Builder::new(4).sequence_numbers().build();
+        let mut tx2 = tx.clone();
+        assert_eq!(None, rx.last_delivered_seq());
+
+        assert_eq!(Ok(1), tx.try_send_seq(Message(1)));
+        assert_eq!(Ok(2), tx2.try_send_seq(Message(2)));
+        assert_eq!(Ok(()), tx.try_send(Message(3)));
+        assert_eq!(Ok(4), tx.try_send_seq(Message(4)));
+        assert_eq!(
+            Err(TrySendError::Pending(Message(5))),
+            tx.try_send_seq(Message(5))
+        );
+
+        assert_eq!(Ok(Message(1)), rx.try_recv());
+        assert_eq!(Some(1), rx.last_delivered_seq());
+        assert_eq!(Ok(Message(2)), rx.try_recv());
+        assert_eq!(Some(2), tx.last_delivered_seq());
+
+        // the replacement continues from the last delivered message
+        let mut replacement = tx.replace_receiver();
+        assert_eq!(Some(2), replacement.last_delivered_seq());
+        assert_eq!(Ok(Message(3)), replacement.try_recv());
+        assert_eq!(Some(3), replacement.last_delivered_seq());
+    }
+
+    #[test]
+    fn sequence_numbers_overflow_and_batches() {
+        let (mut tx, mut rx) = Builder::new(2)
+            .overflow(Overflow::DropNewest)
+            .sequence_numbers()
+            .build();
+
+        let mut cx = noop_context();
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send_vectored(&mut cx, vec![Message(1), Message(2)])
+        );
+        assert_eq!(Ok(3), tx.try_send_seq(Message(3)));
+
+        assert_eq!(Ok(Message(1)), rx.try_recv());
+        assert_eq!(Ok(Message(2)), rx.try_recv());
+        assert_eq!(Some(2), rx.last_delivered_seq());
+
+        assert_eq!(Ok(4), tx.try_send_seq(Message(4)));
+        assert_eq!(Ok(Message(4)), rx.try_recv());
+        assert_eq!(Some(4), rx.last_delivered_seq());
+    }
+
+    #[test]
+    fn flush_waits_for_receiver() {
+        let (mut tx, mut rx) = channel(4);
+        tx.try_send(Message(1)).unwrap();
+        tx.try_send(Message(2)).unwrap();
+
+        let (waker, count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker).into();
+        assert_eq!(PollReady::Pending, Pin::new(&mut tx).poll_flush(&mut cx));
+
+        assert_eq!(Ok(Message(1)), rx.try_recv());
+        assert!(count.get() > 0);
+        assert_eq!(PollReady::Pending, Pin::new(&mut tx).poll_flush(&mut cx));
+
+        // messages sent by other senders during the flush are not waited for
+        let mut tx2 = tx.clone();
+        tx2.try_send(Message(3)).unwrap();
+        assert_eq!(Ok(Message(2)), rx.try_recv());
+        assert_eq!(PollReady::Ready, Pin::new(&mut tx).poll_flush(&mut cx));
+
+        assert_eq!(PollReady::Pending, Pin::new(&mut tx2).poll_flush(&mut cx));
+        drop(rx);
+        assert_eq!(PollReady::Closed, Pin::new(&mut tx2).poll_flush(&mut cx));
+    }
+
+    #[test]
+    fn flush_cancelled() {
+        use std::future::Future;
+
+        let mut cx = futures_test::task::noop_context();
+        let (mut tx, mut rx) = channel(4);
+        tx.try_send(Message(1)).unwrap();
+
+        let mut flush = tx.flush();
+        assert!(Pin::new(&mut flush).poll(&mut cx).is_pending());
+        drop(flush);
+
+        // the next flush also waits for the messages sent after the cancelled flush
+        tx.try_send(Message(2)).unwrap();
+        let mut flush = tx.flush();
+        assert!(Pin::new(&mut flush).poll(&mut cx).is_pending());
+
+        assert_eq!(Ok(Message(1)), rx.try_recv());
+        assert!(Pin::new(&mut flush).poll(&mut cx).is_pending());
+
+        assert_eq!(Ok(Message(2)), rx.try_recv());
+        assert!(Pin::new(&mut flush).poll(&mut cx).is_ready());
+    }
+
+    #[test]
+    fn flush_counts_discarded() {
+        let (mut tx, rx) = channel(4);
+        tx.try_send(Message(1)).unwrap();
+
+        let mut cx = noop_context();
+        assert_eq!(PollReady::Pending, Pin::new(&mut tx).poll_flush(&mut cx));
+
+        assert_eq!(1, rx.clear());
+        assert_eq!(PollReady::Ready, Pin::new(&mut tx).poll_flush(&mut cx));
+    }
+
+    #[test]
+    fn emptied() {
+        use std::future::Future;
+
+        let (mut tx, rx) = channel(4);
+        tx.try_send(Message(1)).unwrap();
+        tx.try_send(Message(2)).unwrap();
+
+        let mut replacement = tx.replace_receiver();
+        let mut emptied = Box::pin(rx.emptied());
+        let mut cx = futures_test::task::noop_context();
+        assert_eq!(Poll::Pending, emptied.as_mut().poll(&mut cx));
+
+        tx.try_send(Message(3)).unwrap();
+        assert_eq!(Ok(Message(1)), replacement.try_recv());
+        assert_eq!(Poll::Pending, emptied.as_mut().poll(&mut cx));
+        assert_eq!(Ok(Message(2)), replacement.try_recv());
+        assert_eq!(Poll::Ready(()), emptied.as_mut().poll(&mut cx));
+    }
+
+    #[test]
+    #[should_panic]
+    fn send_seq_unsequenced() {
+        let (mut tx, _rx) = channel(4);
+        tx.try_send_seq(Message(1)).ok();
+    }
+
+    #[test]
+    fn waiting_task_counts() {
+        let (waker, _count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker).into();
+        let (mut tx, mut rx) = channel(1);
+        assert_eq!(0, rx.waiting_receivers());
+
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx).poll_recv(&mut cx));
+        assert_eq!(1, rx.waiting_receivers());
+
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, Message(1))
+        );
+        assert_eq!(0, rx.waiting_receivers());
+
+        let mut tx2 = tx.clone();
+        assert_eq!(
+            PollSend::Pending(Message(2)),
+            Pin::new(&mut tx).poll_send(&mut cx, Message(2))
+        );
+        assert_eq!(
+            PollSend::Pending(Message(3)),
+            Pin::new(&mut tx2).poll_send(&mut cx, Message(3))
+        );
+        assert_eq!(2, tx.blocked_senders());
+
+        assert_eq!(
+            PollRecv::Ready(Message(1)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        assert_eq!(0, tx.blocked_senders());
+    }
 }
 
 #[cfg(test)]
@@ -476,6 +2672,63 @@ mod tokio_tests {
         test::{capacity_iter, Channel, Channels, Message, CHANNEL_TEST_SENDERS, TEST_TIMEOUT},
     };
 
+    #[tokio::test]
+    async fn into_broadcast() {
+        let spawn = |task: crate::spawn::BoxFuture| {
+            tokio::spawn(task);
+        };
+
+        let (mut tx, rx) = super::channel(4);
+        let mut rx1 = rx.into_broadcast(4, &spawn);
+        let mut rx2 = rx1.resubscribe();
+
+        tx.send(1usize).await.unwrap();
+        tx.send(2).await.unwrap();
+        drop(tx);
+
+        assert_eq!(Some(1), rx1.recv().await);
+        assert_eq!(Some(2), rx1.recv().await);
+        assert_eq!(None, rx1.recv().await);
+
+        assert_eq!(Some(1), rx2.recv().await);
+        assert_eq!(Some(2), rx2.recv().await);
+        assert_eq!(None, rx2.recv().await);
+    }
+
+    #[tokio::test]
+    async fn into_broadcast_closes_producer() {
+        let spawn = |task: crate::spawn::BoxFuture| {
+            tokio::spawn(task);
+        };
+
+        let (mut tx, rx) = super::channel(4);
+        let rx = rx.into_broadcast(4, &spawn);
+        drop(rx);
+
+        let result = timeout(TEST_TIMEOUT, async {
+            while tx.send(1usize).await.is_ok() {}
+        })
+        .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn slices() {
+        let (mut tx, mut rx) = super::channel::<u32>(8);
+        let samples: Vec<u32> = (0..100).collect();
+
+        let join = spawn(async move { tx.send_slice(&samples).await });
+
+        let mut received = Vec::new();
+        let mut buf = [0; 16];
+        while let Some(n) = rx.recv_slice(&mut buf).await {
+            received.extend_from_slice(&buf[..n]);
+        }
+
+        assert_eq!(Ok(()), join.await.unwrap());
+        assert_eq!((0..100).collect::<Vec<_>>(), received);
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn simple() {
         // crate::logging::enable_log();
@@ -556,7 +2809,7 @@ mod tokio_tests {
 
             spawn(async move {
                 loop {
-                    if let Ok(_) = sender_quit.try_recv() {
+                    if sender_quit.try_recv().is_ok() {
                         break;
                     }
 
@@ -671,7 +2924,7 @@ mod async_std_tests {
 
             spawn(async move {
                 loop {
-                    if let Ok(_) = sender_quit.try_recv() {
+                    if sender_quit.try_recv().is_ok() {
                         break;
                     }
 
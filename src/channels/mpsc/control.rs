@@ -0,0 +1,294 @@
+use std::{fmt, pin::Pin};
+
+use super::{Builder, Receiver, Sender};
+use crate::{
+    sink::{PollSend, Sink},
+    stream::{PollRecv, Stream},
+    Context,
+};
+
+/// The capacity of the control lane of a [with_control](./fn.with_control.html) channel
+pub const CONTROL_CAPACITY: usize = 4;
+
+/// Constructs an mpsc channel with a data lane of the given capacity, and a small high-priority control lane.
+///
+/// The receiver checks the control lane before the data lane on every poll, so commands such as shutdown or flush
+/// reach the consumer even when the data lane is saturated.  The control lane holds [CONTROL_CAPACITY](./constant.CONTROL_CAPACITY.html) messages.
+///
+/// ```rust
+/// use postage::{mpsc, prelude::*};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Message {
+///     Data(usize),
+///     Shutdown,
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let (mut tx, mut rx) = mpsc::with_control(1);
+///     let mut control = tx.control();
+///
+///     tx.send(Message::Data(1)).await.ok();
+///     control.send(Message::Shutdown).await.ok();
+///
+///     assert_eq!(Some(Message::Shutdown), rx.recv().await);
+///     assert_eq!(Some(Message::Data(1)), rx.recv().await);
+/// }
+/// ```
+pub fn with_control<T>(capacity: usize) -> (ControlledSender<T>, ControlledReceiver<T>) {
+    Builder::new(capacity).build_with_control()
+}
+
+impl<T> Builder<T> {
+    /// Constructs a channel with a control lane, with the builder's configuration for the data lane.
+    /// See [with_control](./fn.with_control.html).
+    ///
+    /// The control lane shares the builder's stop token, so both lanes close together.
+    pub fn build_with_control(self) -> (ControlledSender<T>, ControlledReceiver<T>) {
+        let mut control = Builder::new(CONTROL_CAPACITY);
+        control.stop = self.stop.clone();
+
+        let (data_tx, data_rx) = self.build();
+        let (control_tx, control_rx) = control.build();
+
+        let sender = ControlledSender {
+            data: data_tx,
+            control: control_tx,
+        };
+
+        let receiver = ControlledReceiver {
+            data: data_rx,
+            control: control_rx,
+        };
+
+        (sender, receiver)
+    }
+}
+
+/// The sender half of a [with_control](./fn.with_control.html) channel.  Messages are sent to the data lane.
+///
+/// Can be cloned.
+pub struct ControlledSender<T> {
+    data: Sender<T>,
+    control: Sender<T>,
+}
+
+impl<T> ControlledSender<T> {
+    /// Returns a sender for the control lane.  Messages sent to the control lane are received before the buffered data.
+    pub fn control(&self) -> Sender<T> {
+        self.control.clone()
+    }
+
+    /// Returns a sender for the data lane
+    pub fn data(&self) -> Sender<T> {
+        self.data.clone()
+    }
+}
+
+impl<T> Clone for ControlledSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            data: self.data.clone(),
+            control: self.control.clone(),
+        }
+    }
+}
+
+impl<T> Sink for ControlledSender<T> {
+    type Item = T;
+
+    fn poll_send(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        value: Self::Item,
+    ) -> PollSend<Self::Item> {
+        Pin::new(&mut self.get_mut().data).poll_send(cx, value)
+    }
+}
+
+#[cfg(feature = "futures-traits")]
+mod impl_futures {
+    use std::{pin::Pin, task::Poll};
+
+    use crate::sink::SendError;
+
+    impl<T> futures_sink::Sink<T> for super::ControlledSender<T> {
+        type Error = SendError<T>;
+
+        fn poll_ready(
+            self: Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            let this = self.get_mut();
+            Pin::new(&mut this.data).poll_ready(cx)
+        }
+
+        fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+            let this = self.get_mut();
+            Pin::new(&mut this.data).start_send(item)
+        }
+
+        fn poll_flush(
+            self: Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            let this = self.get_mut();
+            Pin::new(&mut this.data).poll_flush(cx)
+        }
+
+        fn poll_close(
+            self: Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            let this = self.get_mut();
+            Pin::new(&mut this.data).poll_close(cx)
+        }
+    }
+}
+
+impl<T> fmt::Debug for ControlledSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ControlledSender")
+            .field("data", &self.data)
+            .field("control", &self.control)
+            .finish()
+    }
+}
+
+/// The receiver half of a [with_control](./fn.with_control.html) channel.  Cannot be cloned.
+///
+/// Receives from the control lane before the data lane, and closes when both lanes are closed.
+pub struct ControlledReceiver<T> {
+    data: Receiver<T>,
+    control: Receiver<T>,
+}
+
+impl<T> ControlledReceiver<T> {
+    /// The number of messages waiting in the control lane
+    pub fn control_len(&self) -> usize {
+        self.control.shared.extension().len()
+    }
+
+    /// The number of messages waiting in the data lane
+    pub fn data_len(&self) -> usize {
+        self.data.shared.extension().len()
+    }
+}
+
+impl<T> Stream for ControlledReceiver<T> {
+    type Item = T;
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item> {
+        let this = self.get_mut();
+
+        let control = Pin::new(&mut this.control).poll_recv(cx);
+        if let PollRecv::Ready(value) = control {
+            return PollRecv::Ready(value);
+        }
+
+        match Pin::new(&mut this.data).poll_recv(cx) {
+            PollRecv::Ready(value) => PollRecv::Ready(value),
+            PollRecv::Pending => PollRecv::Pending,
+            PollRecv::Closed => control,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (data_low, data_high) = self.data.size_hint();
+        let (control_low, control_high) = self.control.size_hint();
+
+        let high = match (data_high, control_high) {
+            (Some(data_high), Some(control_high)) => Some(data_high + control_high),
+            _ => None,
+        };
+
+        (data_low + control_low, high)
+    }
+}
+
+impl<T> fmt::Debug for ControlledReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ControlledReceiver")
+            .field("data", &self.data)
+            .field("control", &self.control)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+
+    use super::{with_control, CONTROL_CAPACITY};
+    use crate::{
+        sink::{PollSend, Sink},
+        stream::{PollRecv, Stream, TryRecvError},
+        test::noop_context,
+    };
+
+    #[test]
+    fn control_first() {
+        let (mut tx, mut rx) = with_control(4);
+        let mut control = tx.control();
+
+        tx.try_send(1usize).unwrap();
+        tx.try_send(2).unwrap();
+        control.try_send(100).unwrap();
+
+        assert_eq!(1, rx.control_len());
+        assert_eq!(2, rx.data_len());
+
+        assert_eq!(Ok(100), rx.try_recv());
+        assert_eq!(Ok(1), rx.try_recv());
+        control.try_send(101).unwrap();
+        assert_eq!(Ok(101), rx.try_recv());
+        assert_eq!(Ok(2), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Pending), rx.try_recv());
+    }
+
+    #[test]
+    fn control_when_data_saturated() {
+        let mut cx = noop_context();
+        let (mut tx, mut rx) = with_control(1);
+        let mut control = tx.control();
+
+        tx.try_send(1usize).unwrap();
+        assert_eq!(
+            PollSend::Pending(2),
+            Pin::new(&mut tx).poll_send(&mut cx, 2)
+        );
+
+        for i in 0..CONTROL_CAPACITY {
+            assert_eq!(
+                PollSend::Ready,
+                Pin::new(&mut control).poll_send(&mut cx, 100 + i)
+            );
+        }
+
+        for i in 0..CONTROL_CAPACITY {
+            assert_eq!(
+                PollRecv::Ready(100 + i),
+                Pin::new(&mut rx).poll_recv(&mut cx)
+            );
+        }
+
+        assert_eq!(PollRecv::Ready(1), Pin::new(&mut rx).poll_recv(&mut cx));
+    }
+
+    #[test]
+    fn closes_when_both_lanes_close() {
+        let mut cx = noop_context();
+        let (tx, mut rx) = with_control(4);
+        let mut control = tx.control();
+        drop(tx);
+
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx).poll_recv(&mut cx));
+
+        control.try_send(1usize).unwrap();
+        drop(control);
+
+        assert_eq!(PollRecv::Ready(1), Pin::new(&mut rx).poll_recv(&mut cx));
+        assert_eq!(PollRecv::Closed, Pin::new(&mut rx).poll_recv(&mut cx));
+    }
+}
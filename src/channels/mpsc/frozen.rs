@@ -0,0 +1,162 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::{quota::Queued, Builder, Receiver, Sender};
+use crate::sync::envelope::Envelope;
+
+/// The serializable state of an mpsc channel, captured by [Receiver::freeze](./struct.Receiver.html#method.freeze).
+///
+/// Includes the buffered messages, and the channel's capacity, name, time-to-live, age tracking, and sender quota.
+/// Hooks such as metrics, watermarks, dead-letter sinks, and stop tokens can't be serialized, and can be reattached with [thaw_with](#method.thaw_with).
+///
+/// ```rust
+/// use postage::{mpsc, sink::Sink, stream::Stream};
+///
+/// let (mut tx, rx) = mpsc::channel(4);
+/// tx.try_send(1usize).unwrap();
+///
+/// let frozen = rx.freeze();
+/// let json = serde_json::to_string(&frozen).unwrap();
+///
+/// let frozen: mpsc::FrozenChannel<usize> = serde_json::from_str(&json).unwrap();
+/// let (_tx, mut rx) = frozen.thaw();
+/// assert_eq!(Ok(1), rx.try_recv());
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FrozenChannel<T> {
+    capacity: usize,
+    name: Option<String>,
+    ttl: Option<Duration>,
+    track_age: bool,
+    sender_quota: Option<usize>,
+    messages: Vec<T>,
+}
+
+impl<T> FrozenChannel<T> {
+    /// The capacity of the channel
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The buffered messages, in the order they will be received
+    pub fn messages(&self) -> &[T] {
+        &self.messages
+    }
+
+    /// Reconstructs the channel, with the frozen configuration and buffered messages
+    pub fn thaw(self) -> (Sender<T>, Receiver<T>) {
+        let mut builder = Builder::new(self.capacity);
+        builder.name = self.name.clone();
+        builder.ttl = self.ttl;
+        builder.track_age = self.track_age;
+        builder.sender_quota = self.sender_quota;
+
+        self.thaw_with(builder)
+    }
+
+    /// Reconstructs the channel from the builder, and buffers the frozen messages.
+    ///
+    /// The builder's capacity is raised, if needed, to hold the messages.
+    /// The messages don't count towards the sender quota, and their age is measured from the thaw.
+    pub fn thaw_with(self, mut builder: Builder<T>) -> (Sender<T>, Receiver<T>) {
+        builder.capacity = builder.capacity.max(self.messages.len());
+        let (sender, receiver) = builder.build();
+
+        let extension = sender.shared.extension();
+        if !extension.slots.reserve(self.messages.len()) {
+            unreachable!("the capacity holds the frozen messages");
+        }
+
+        for message in self.messages {
+            let envelope = Envelope::new(message, extension.timestamps);
+            extension.push(Queued::new(envelope, None));
+        }
+
+        sender.shared.registration().set_depth(extension.len());
+        (sender, receiver)
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Closes the channel, and captures its configuration and buffered messages, so it can be serialized and [thawed](./struct.FrozenChannel.html#method.thaw).
+    ///
+    /// Requires the `serde` feature.
+    pub fn freeze(self) -> FrozenChannel<T> {
+        let extension = self.shared.extension();
+
+        let mut messages = Vec::with_capacity(extension.len());
+        if !self.is_replaced() {
+            while let Some(envelope) = extension.pop() {
+                messages.push(envelope.into_inner());
+            }
+        }
+
+        self.shared.notify_senders();
+
+        FrozenChannel {
+            capacity: extension.capacity,
+            name: extension.name.clone(),
+            ttl: extension.ttl,
+            track_age: extension.track_age,
+            sender_quota: extension.sender_quota,
+            messages,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::FrozenChannel;
+    use crate::{
+        mpsc::{channel, Builder},
+        sink::{Sink, TrySendError},
+        stream::{Stream, TryRecvError},
+    };
+
+    #[test]
+    fn freeze_thaw() {
+        let (mut tx, mut rx) = Builder::new(3)
+            .name("jobs")
+            .ttl(Duration::from_secs(60))
+            .sender_quota(2)
+            .build();
+
+        tx.try_send(1usize).unwrap();
+        tx.try_send(2).unwrap();
+        assert_eq!(Ok(1), rx.try_recv());
+        tx.try_send(3).unwrap();
+
+        let frozen = rx.freeze();
+        assert_eq!(&[2, 3], frozen.messages());
+        assert_eq!(Err(TrySendError::Rejected(4)), tx.try_send(4));
+
+        let json = serde_json::to_string(&frozen).unwrap();
+        let thawed: FrozenChannel<usize> = serde_json::from_str(&json).unwrap();
+        assert_eq!(frozen, thawed);
+
+        let (mut tx, mut rx) = thawed.thaw();
+        tx.try_send(4).unwrap();
+        assert_eq!(Err(TrySendError::Pending(5)), tx.try_send(5));
+
+        assert_eq!(Ok(2), rx.try_recv());
+        assert_eq!(Ok(3), rx.try_recv());
+        assert_eq!(Ok(4), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Pending), rx.try_recv());
+    }
+
+    #[test]
+    fn thaw_with_raises_capacity() {
+        let (mut tx, rx) = channel(4);
+        for i in 0..4usize {
+            tx.try_send(i).unwrap();
+        }
+
+        let (_tx, mut rx) = rx.freeze().thaw_with(Builder::new(1));
+        for i in 0..4 {
+            assert_eq!(Ok(i), rx.try_recv());
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use std::sync::{
+    atomic::{AtomicIsize, Ordering},
+    Arc, OnceLock, Weak,
+};
+
+use super::{Builder, Receiver, Sender};
+use crate::sync::notifier::Notifier;
+
+/// Constructs a pair of mpsc channels for adjacent pipeline stages, with backpressure from the downstream channel.
+///
+/// The first pair carries messages into a stage, and the second carries its output.  Senders on the upstream channel
+/// wait while the downstream buffer is full, even if the upstream buffer has room, so overload at the end of the
+/// pipeline reaches the producers at its start, instead of being hidden by the buffer in between.
+///
+/// ```rust
+/// use postage::{mpsc, prelude::*};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let ((mut input, mut stage_rx), (mut stage_tx, mut output)) = mpsc::linked(4, 1);
+///     tokio::spawn(async move {
+///         while let Some(value) = stage_rx.recv().await {
+///             stage_tx.send(value * 10).await.ok();
+///         }
+///     });
+///
+///     input.send(1usize).await.ok();
+///     assert_eq!(Some(10), output.recv().await);
+/// }
+/// ```
+#[allow(clippy::type_complexity)]
+pub fn linked<A, B>(
+    upstream: usize,
+    downstream: usize,
+) -> ((Sender<A>, Receiver<A>), (Sender<B>, Receiver<B>)) {
+    linked_with(Builder::new(upstream), Builder::new(downstream))
+}
+
+/// Constructs a pair of linked mpsc channels from builders, so each channel can be configured, for example with a stop token.
+/// See [linked](./fn.linked.html).
+#[allow(clippy::type_complexity)]
+pub fn linked_with<A, B>(
+    mut upstream: Builder<A>,
+    mut downstream: Builder<B>,
+) -> ((Sender<A>, Receiver<A>), (Sender<B>, Receiver<B>)) {
+    let credits = Credits::new(downstream.capacity);
+
+    downstream.credits = Some(credits.clone());
+    let downstream = downstream.build();
+
+    upstream.gate = Some(credits.clone());
+    let upstream = upstream.build();
+
+    credits
+        .upstream
+        .set(Arc::downgrade(&upstream.0.shared.notifier()))
+        .ok();
+
+    (upstream, downstream)
+}
+
+/// The free slots in the downstream channel of a linked pair.  Upstream senders wait while no credit is available.
+pub(super) struct Credits {
+    // may be briefly negative, if a pop is counted after the push which took its slot
+    available: AtomicIsize,
+    upstream: OnceLock<Weak<Notifier>>,
+}
+
+impl Credits {
+    fn new(capacity: usize) -> Arc<Self> {
+        Arc::new(Self {
+            available: AtomicIsize::new(capacity as isize),
+            upstream: OnceLock::new(),
+        })
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.available.load(Ordering::Acquire) <= 0
+    }
+
+    /// Takes a credit, when a message is buffered in the downstream channel
+    pub fn consume(&self) {
+        self.available.fetch_sub(1, Ordering::AcqRel);
+    }
+
+    /// Returns a credit, when a message is taken from the downstream channel, and wakes the upstream senders
+    pub fn release(&self) {
+        if self.available.fetch_add(1, Ordering::AcqRel) > 0 {
+            return;
+        }
+
+        if let Some(notifier) = self.upstream.get().and_then(Weak::upgrade) {
+            notifier.notify();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+
+    use futures_test::task::new_count_waker;
+
+    use super::linked;
+    use crate::{
+        sink::{PollSend, Sink, TrySendError},
+        stream::Stream,
+    };
+
+    #[test]
+    fn downstream_gates_upstream() {
+        let ((mut input, mut stage_rx), (mut stage_tx, mut output)) = linked(4, 1);
+
+        input.try_send(1usize).unwrap();
+        assert_eq!(Ok(1), stage_rx.try_recv());
+        stage_tx.try_send(10usize).unwrap();
+
+        // the upstream buffer is empty, but the downstream buffer is full
+        assert_eq!(Err(TrySendError::Pending(2)), input.try_send(2));
+
+        let (waker, count) = new_count_waker();
+        let mut cx = std::task::Context::from_waker(&waker).into();
+        assert_eq!(
+            PollSend::Pending(2),
+            Pin::new(&mut input).poll_send(&mut cx, 2)
+        );
+
+        assert_eq!(Ok(10), output.try_recv());
+        assert_eq!(1, count.get());
+        input.try_send(2).unwrap();
+        assert_eq!(Ok(2), stage_rx.try_recv());
+    }
+
+    #[test]
+    fn upstream_capacity() {
+        let ((mut input, _stage_rx), (_stage_tx, _output)) = linked::<usize, usize>(2, 4);
+
+        input.try_send(1usize).unwrap();
+        input.try_send(2).unwrap();
+        assert_eq!(Err(TrySendError::Pending(3)), input.try_send(3));
+    }
+
+    #[test]
+    fn downstream_closure() {
+        let ((mut input, stage_rx), (stage_tx, output)) = linked::<usize, usize>(2, 1);
+        drop(output);
+        drop(stage_tx);
+        drop(stage_rx);
+
+        assert_eq!(Err(TrySendError::Rejected(1)), input.try_send(1));
+    }
+}
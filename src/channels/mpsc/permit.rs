@@ -0,0 +1,221 @@
+use std::{fmt, future::poll_fn, task::Poll};
+
+use super::{
+    quota::{Permit, Queued},
+    Sender,
+};
+use crate::{
+    sink::{SendError, TrySendError},
+    sync::envelope::Envelope,
+    Context,
+};
+
+impl<T> Sender<T> {
+    /// Reserves a slot in the buffer, waiting for capacity as needed.
+    ///
+    /// The permit sends one message without waiting.  If the permit is dropped without sending, the slot is released.
+    /// Returns an error if the channel is closed.
+    ///
+    /// ```rust
+    /// use postage::{mpsc, prelude::*};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let (tx, mut rx) = mpsc::channel(1);
+    ///
+    ///     let permit = tx.reserve().await.unwrap();
+    ///     permit.send("hello");
+    ///     assert_eq!(Some("hello"), rx.recv().await);
+    /// }
+    /// ```
+    pub async fn reserve(&self) -> Result<SendPermit<'_, T>, SendError<()>> {
+        poll_fn(|cx| self.poll_reserve(&mut cx.into())).await
+    }
+
+    /// Reserves a slot in the buffer, if one is free
+    pub fn try_reserve(&self) -> Result<SendPermit<'_, T>, TrySendError<()>> {
+        match self.poll_reserve(&mut Context::empty()) {
+            Poll::Ready(Ok(permit)) => Ok(permit),
+            Poll::Ready(Err(_)) => Err(TrySendError::Rejected(())),
+            Poll::Pending => Err(TrySendError::Pending(())),
+        }
+    }
+
+    /// Attempts to reserve a slot in the buffer.
+    ///
+    /// Returns `Pending` if the buffer is full, the receiver is paused, or the sender has reached its quota,
+    /// and an error if the channel is closed.
+    pub fn poll_reserve(
+        &self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<SendPermit<'_, T>, SendError<()>>> {
+        loop {
+            let guard = self.shared.recv_guard();
+
+            if self.shared.is_closed() {
+                self.shared.tracer().reject();
+                if let Some(metrics) = self.shared.metrics() {
+                    metrics.on_reject();
+                }
+                return Poll::Ready(Err(SendError(())));
+            }
+
+            if let Some(permit) = self.reserve_slot() {
+                return Poll::Ready(Ok(permit));
+            }
+
+            self.shared.subscribe_recv(cx);
+
+            if guard.is_expired() {
+                continue;
+            }
+
+            self.shared.tracer().full();
+            if let Some(metrics) = self.shared.metrics() {
+                metrics.set_blocked_senders(self.shared.blocked_senders());
+            }
+            return Poll::Pending;
+        }
+    }
+
+    fn reserve_slot(&self) -> Option<SendPermit<'_, T>> {
+        let extension = self.shared.extension();
+        if extension.is_paused() || extension.is_gated() {
+            return None;
+        }
+
+        let quota = match self.quota {
+            Some(ref quota) => Some(quota.acquire()?),
+            None => None,
+        };
+
+        if !extension.slots.reserve(1) {
+            return None;
+        }
+
+        Some(SendPermit {
+            sender: self,
+            quota,
+            sent: false,
+        })
+    }
+}
+
+/// A reserved slot in an mpsc channel, created by [Sender::reserve](./struct.Sender.html#method.reserve).
+///
+/// Sends one message without waiting.  If dropped without sending, the slot is released.
+pub struct SendPermit<'s, T> {
+    sender: &'s Sender<T>,
+    quota: Option<Permit>,
+    sent: bool,
+}
+
+impl<'s, T> SendPermit<'s, T> {
+    /// Sends the message into the reserved slot.
+    ///
+    /// If the receiver has been dropped since the slot was reserved, the message is dropped with the buffer.
+    pub fn send(mut self, value: T) {
+        let sender = self.sender;
+        let extension = sender.shared.extension();
+
+        // the slot is held by the message once it is buffered
+        extension.slots.begin_reserved_push();
+        let envelope = Envelope::new(value, extension.timestamps);
+        extension.push(Queued::new(envelope, self.quota.take()));
+        extension.slots.end_push();
+
+        self.sent = true;
+        sender.record_send();
+        sender.shared.notify_receivers();
+    }
+}
+
+impl<'s, T> Drop for SendPermit<'s, T> {
+    fn drop(&mut self) {
+        if self.sent {
+            return;
+        }
+
+        let shared = &self.sender.shared;
+        shared.extension().slots.release();
+        shared.notify_self();
+    }
+}
+
+impl<'s, T> fmt::Debug for SendPermit<'s, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SendPermit").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{pin::Pin, task::Context};
+
+    use futures_test::task::new_count_waker;
+
+    use crate::{
+        mpsc::{channel, Builder},
+        sink::{PollSend, Sink, TrySendError},
+        stream::Stream,
+    };
+
+    #[test]
+    fn reserve_holds_capacity() {
+        let (tx, mut rx) = channel(2);
+        let mut other = tx.clone();
+        let permit = tx.try_reserve().unwrap();
+
+        other.try_send(1usize).unwrap();
+        assert_eq!(Err(TrySendError::Pending(2)), other.try_send(2));
+
+        drop(permit);
+        other.try_send(2).unwrap();
+
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(Ok(2), rx.try_recv());
+    }
+
+    #[test]
+    fn permit_sends() {
+        let (tx, mut rx) = channel(1);
+        let permit = tx.try_reserve().unwrap();
+        assert!(matches!(tx.try_reserve(), Err(TrySendError::Pending(()))));
+
+        permit.send(1usize);
+        assert_eq!(Ok(1), rx.try_recv());
+        assert!(tx.try_reserve().is_ok());
+    }
+
+    #[test]
+    fn dropped_permit_wakes() {
+        let (mut tx, _rx) = channel(1);
+        let permit = tx.try_reserve().unwrap();
+
+        let (waker, count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker).into();
+        let mut other = tx.clone();
+        assert_eq!(
+            PollSend::Pending(1usize),
+            Pin::new(&mut other).poll_send(&mut cx, 1)
+        );
+
+        drop(permit);
+        assert_eq!(1, count.get());
+        tx.try_send(1).unwrap();
+    }
+
+    #[test]
+    fn reserve_quota() {
+        let (tx, _rx) = Builder::<usize>::new(4).sender_quota(1).build();
+        let _permit = tx.try_reserve().unwrap();
+        assert!(matches!(tx.try_reserve(), Err(TrySendError::Pending(()))));
+    }
+
+    #[test]
+    fn reserve_closed() {
+        let (tx, rx) = channel::<usize>(1);
+        drop(rx);
+        assert!(matches!(tx.try_reserve(), Err(TrySendError::Rejected(()))));
+    }
+}
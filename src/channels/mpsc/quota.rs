@@ -0,0 +1,85 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use crate::sync::envelope::Envelope;
+
+/// Limits the number of messages a single sender can have in the buffer
+#[derive(Debug)]
+pub(super) struct Quota {
+    limit: usize,
+    in_flight: AtomicUsize,
+}
+
+impl Quota {
+    pub fn new(limit: usize) -> Arc<Self> {
+        Arc::new(Self {
+            limit,
+            in_flight: AtomicUsize::new(0),
+        })
+    }
+
+    /// Reserves a slot in the quota, which is released when the permit is dropped
+    pub fn acquire(self: &Arc<Self>) -> Option<Permit> {
+        let in_flight = self.in_flight.fetch_add(1, Ordering::AcqRel);
+        if in_flight >= self.limit {
+            self.in_flight.fetch_sub(1, Ordering::AcqRel);
+            return None;
+        }
+
+        Some(Permit(self.clone()))
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.in_flight.load(Ordering::Acquire) >= self.limit
+    }
+}
+
+/// A slot in a sender's quota, which is held by a buffered message
+pub(super) struct Permit(Arc<Quota>);
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// A buffered message, the quota slot it holds, and its sequence number
+pub(super) struct Queued<T> {
+    envelope: Envelope<T>,
+    seq: Option<u64>,
+    _permit: Option<Permit>,
+}
+
+impl<T> Queued<T> {
+    pub fn new(envelope: Envelope<T>, permit: Option<Permit>) -> Self {
+        Self {
+            envelope,
+            seq: None,
+            _permit: permit,
+        }
+    }
+
+    pub fn with_seq(mut self, seq: u64) -> Self {
+        self.seq = Some(seq);
+        self
+    }
+
+    pub fn envelope(&self) -> &Envelope<T> {
+        &self.envelope
+    }
+
+    pub fn seq(&self) -> Option<u64> {
+        self.seq
+    }
+
+    /// Releases the quota slot, and returns the message
+    pub fn into_envelope(self) -> Envelope<T> {
+        self.envelope
+    }
+}
@@ -0,0 +1,343 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    fmt,
+    hash::{Hash, Hasher},
+    pin::Pin,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use super::{Builder, Receiver, Sender};
+use crate::{
+    sink::{PollSend, Sink},
+    stream::{PollRecv, Stream},
+    Context,
+};
+
+/// Constructs a sharded mpsc channel, with `shards` internal queues of the given capacity.
+///
+/// Each producer thread is assigned a shard, so producers on different threads rarely contend on the same queue.
+/// The receiver drains the shards round-robin.  Messages sent from one thread, or with one key, are received in order,
+/// but messages from different shards may be interleaved.
+///
+/// Panics if `shards` is zero.
+///
+/// ```rust
+/// use postage::{mpsc, prelude::*};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let (mut tx, mut rx) = mpsc::sharded(4, 16);
+///
+///     tx.send(1usize).await.ok();
+///     tx.shard_for(&"user-42").send(2usize).await.ok();
+///     drop(tx);
+///
+///     let mut received = Vec::new();
+///     while let Some(value) = rx.recv().await {
+///         received.push(value);
+///     }
+///
+///     received.sort();
+///     assert_eq!(vec![1, 2], received);
+/// }
+/// ```
+pub fn sharded<T>(shards: usize, capacity: usize) -> (ShardedSender<T>, ShardedReceiver<T>) {
+    sharded_with(shards, || Builder::new(capacity))
+}
+
+/// Constructs a sharded mpsc channel, with one internal queue for each builder returned by `builder`.
+/// The shards can share configuration, such as a stop token.  See [sharded](./fn.sharded.html).
+///
+/// Panics if `shards` is zero.
+pub fn sharded_with<T>(
+    shards: usize,
+    mut builder: impl FnMut() -> Builder<T>,
+) -> (ShardedSender<T>, ShardedReceiver<T>) {
+    assert!(shards > 0, "a sharded channel requires at least one shard");
+
+    let (senders, receivers) = (0..shards).map(|_| builder().build()).unzip();
+
+    let sender = ShardedSender { shards: senders };
+    let receiver = ShardedReceiver {
+        shards: receivers,
+        next: 0,
+    };
+
+    (sender, receiver)
+}
+
+/// The sender half of a sharded mpsc channel.  Messages are sent to the shard assigned to the current thread.
+///
+/// Can be cloned.
+pub struct ShardedSender<T> {
+    shards: Vec<Sender<T>>,
+}
+
+impl<T> ShardedSender<T> {
+    /// The number of shards in the channel
+    pub fn shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Returns the sender for the shard which the key hashes to.
+    /// Messages sent with the same key are received in the order they were sent.
+    pub fn shard_for<K: Hash + ?Sized>(&self, key: &K) -> Sender<T> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        let index = (hasher.finish() % self.shards.len() as u64) as usize;
+
+        self.shards[index].clone()
+    }
+
+    fn thread_shard(&self) -> usize {
+        static NEXT_THREAD: AtomicUsize = AtomicUsize::new(0);
+
+        thread_local! {
+            static THREAD_INDEX: usize = NEXT_THREAD.fetch_add(1, Ordering::Relaxed);
+        }
+
+        THREAD_INDEX.with(|index| *index % self.shards.len())
+    }
+}
+
+impl<T> Clone for ShardedSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shards: self.shards.clone(),
+        }
+    }
+}
+
+impl<T> Sink for ShardedSender<T> {
+    type Item = T;
+
+    fn poll_send(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        value: Self::Item,
+    ) -> PollSend<Self::Item> {
+        let this = self.get_mut();
+        let index = this.thread_shard();
+
+        Pin::new(&mut this.shards[index]).poll_send(cx, value)
+    }
+}
+
+#[cfg(feature = "futures-traits")]
+mod impl_futures {
+    use std::{pin::Pin, task::Poll};
+
+    use crate::sink::SendError;
+
+    // the shard is chosen by the current thread, so poll_ready and start_send use the same shard
+    impl<T> futures_sink::Sink<T> for super::ShardedSender<T> {
+        type Error = SendError<T>;
+
+        fn poll_ready(
+            self: Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            let this = self.get_mut();
+            let index = this.thread_shard();
+            Pin::new(&mut this.shards[index]).poll_ready(cx)
+        }
+
+        fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+            let this = self.get_mut();
+            let index = this.thread_shard();
+            Pin::new(&mut this.shards[index]).start_send(item)
+        }
+
+        fn poll_flush(
+            self: Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            let this = self.get_mut();
+            let index = this.thread_shard();
+            Pin::new(&mut this.shards[index]).poll_flush(cx)
+        }
+
+        fn poll_close(
+            self: Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            let this = self.get_mut();
+            let index = this.thread_shard();
+            Pin::new(&mut this.shards[index]).poll_close(cx)
+        }
+    }
+}
+
+impl<T> fmt::Debug for ShardedSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShardedSender")
+            .field("shards", &self.shards.len())
+            .finish()
+    }
+}
+
+/// The receiver half of a sharded mpsc channel.  Cannot be cloned.
+///
+/// Receives from the shards round-robin, and closes when every shard is closed.
+pub struct ShardedReceiver<T> {
+    shards: Vec<Receiver<T>>,
+    next: usize,
+}
+
+impl<T> ShardedReceiver<T> {
+    /// The number of shards in the channel
+    pub fn shards(&self) -> usize {
+        self.shards.len()
+    }
+}
+
+impl<T> Stream for ShardedReceiver<T> {
+    type Item = T;
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item> {
+        let this = self.get_mut();
+        let len = this.shards.len();
+        let start = this.next;
+        let mut closed = 0;
+
+        for i in 0..len {
+            let index = (start + i) % len;
+            match Pin::new(&mut this.shards[index]).poll_recv(cx) {
+                PollRecv::Ready(value) => {
+                    this.next = (index + 1) % len;
+                    return PollRecv::Ready(value);
+                }
+                PollRecv::Pending => {}
+                PollRecv::Closed => closed += 1,
+            }
+        }
+
+        if closed == len {
+            PollRecv::Closed
+        } else {
+            PollRecv::Pending
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.shards.iter().map(Stream::size_hint).fold(
+            (0, Some(0)),
+            |(low, high), (shard_low, shard_high)| {
+                let high = match (high, shard_high) {
+                    (Some(high), Some(shard_high)) => Some(high + shard_high),
+                    _ => None,
+                };
+
+                (low + shard_low, high)
+            },
+        )
+    }
+}
+
+impl<T> fmt::Debug for ShardedReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShardedReceiver")
+            .field("shards", &self.shards.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+
+    use futures_test::task::new_count_waker;
+
+    use super::sharded;
+    use crate::{
+        sink::{PollSend, Sink},
+        stream::{PollRecv, Stream, TryRecvError},
+        test::noop_context,
+        Context,
+    };
+
+    #[test]
+    fn drains_round_robin() {
+        let (tx, mut rx) = sharded(2, 4);
+        let mut a = tx.shards[0].clone();
+        let mut b = tx.shards[1].clone();
+
+        a.try_send(1usize).unwrap();
+        a.try_send(2).unwrap();
+        b.try_send(3).unwrap();
+        b.try_send(4).unwrap();
+
+        let mut received = vec![];
+        while let Ok(value) = rx.try_recv() {
+            received.push(value);
+        }
+
+        assert_eq!(vec![1, 3, 2, 4], received);
+    }
+
+    #[test]
+    fn thread_shard() {
+        let (mut tx, mut rx) = sharded(4, 4);
+        for i in 0..4usize {
+            assert_eq!(
+                PollSend::Ready,
+                Pin::new(&mut tx).poll_send(&mut noop_context(), i)
+            );
+        }
+
+        for i in 0..4 {
+            assert_eq!(Ok(i), rx.try_recv());
+        }
+        assert_eq!(Err(TryRecvError::Pending), rx.try_recv());
+    }
+
+    #[test]
+    fn wakes_receiver() {
+        let (tx, mut rx) = sharded(3, 4);
+        let (waker, count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker);
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx).poll_recv(&mut cx));
+
+        tx.shard_for("key").try_send(1usize).unwrap();
+        assert_eq!(1, count.get());
+        assert_eq!(PollRecv::Ready(1), Pin::new(&mut rx).poll_recv(&mut cx));
+    }
+
+    #[test]
+    fn closes_when_senders_dropped() {
+        let (tx, mut rx) = sharded(3, 4);
+        let mut keyed = tx.shard_for(&1usize);
+        drop(tx);
+
+        keyed.try_send(1usize).unwrap();
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Pending), rx.try_recv());
+
+        drop(keyed);
+        assert_eq!(Err(TryRecvError::Closed), rx.try_recv());
+        assert_eq!((0, Some(0)), rx.size_hint());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn many_producers() {
+        let (tx, mut rx) = sharded(8, 16);
+
+        for producer in 0..64usize {
+            let mut tx = tx.clone();
+            tokio::spawn(async move {
+                for i in 0..100usize {
+                    tx.send(producer * 100 + i).await.ok();
+                }
+            });
+        }
+        drop(tx);
+
+        let mut received = Vec::new();
+        while let Some(value) = rx.recv().await {
+            received.push(value);
+        }
+
+        received.sort_unstable();
+        assert_eq!((0..6400).collect::<Vec<_>>(), received);
+    }
+}
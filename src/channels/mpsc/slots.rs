@@ -0,0 +1,197 @@
+use crate::sync::primitives::{spin_loop, yield_now, AtomicU64, Ordering};
+
+// the low bits count the occupied slots, the middle bits count the pushes in progress,
+// and the high bit is set while a vectored send is pushing its batch
+const OCCUPIED: u64 = (1 << 32) - 1;
+const PUSHING: u64 = 1 << 32;
+const PUSHING_MASK: u64 = ((1 << 31) - 1) << 32;
+const BATCH: u64 = 1 << 63;
+
+/// The occupied slots in an mpsc buffer, packed into one word, so senders claim a slot with a single CAS.
+///
+/// A slot is claimed before the message is pushed, and released when the message is taken from the buffer.
+/// Slots are counted for buffered and stashed messages, for outstanding send permits, and for pushes in progress.
+///
+/// Vectored sends set the batch flag while they push, so no other message is buffered between the messages of the batch.
+/// A batch waits for the pushes in progress, and pushes wait for the batch, which takes only as long as its pushes.
+#[derive(Debug)]
+pub(super) struct Slots {
+    state: AtomicU64,
+    capacity: u64,
+}
+
+impl Slots {
+    pub fn new(capacity: usize) -> Self {
+        assert!(
+            capacity as u64 <= OCCUPIED,
+            "mpsc capacity {} is too large",
+            capacity
+        );
+
+        Self {
+            state: AtomicU64::new(0),
+            capacity: capacity as u64,
+        }
+    }
+
+    /// Whether every slot is held by a buffered message, a reservation, or a push in progress
+    pub fn is_full(&self) -> bool {
+        self.state.load(Ordering::Acquire) & OCCUPIED >= self.capacity
+    }
+
+    /// Claims `count` slots, for messages which will be pushed later.  Returns false if the buffer lacks room.
+    pub fn reserve(&self, count: usize) -> bool {
+        self.state
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |state| {
+                if (state & OCCUPIED) + count as u64 > self.capacity {
+                    return None;
+                }
+
+                Some(state + count as u64)
+            })
+            .is_ok()
+    }
+
+    /// Releases a slot, when a message is taken from the buffer, or a reservation is dropped
+    pub fn release(&self) {
+        self.state.fetch_sub(1, Ordering::AcqRel);
+    }
+
+    /// Claims a slot for a message, and begins the push.  Returns false if the buffer is full.
+    ///
+    /// The caller must call `end_push` once the message is buffered.
+    pub fn begin_push(&self) -> bool {
+        self.begin(|state| {
+            if state & OCCUPIED >= self.capacity {
+                return None;
+            }
+
+            Some(state + 1 + PUSHING)
+        })
+    }
+
+    /// Begins the push of a message into a slot which was claimed by `reserve`
+    pub fn begin_reserved_push(&self) {
+        self.begin(|state| Some(state + PUSHING));
+    }
+
+    pub fn end_push(&self) {
+        self.state.fetch_sub(PUSHING, Ordering::AcqRel);
+    }
+
+    /// Claims `count` slots for a batch, and waits until the pushes in progress have finished.
+    /// Returns false if the buffer lacks room.
+    ///
+    /// The caller must call `end_batch` once the batch is buffered.
+    pub fn begin_batch(&self, count: usize) -> bool {
+        let claimed = self.begin(|state| {
+            if (state & OCCUPIED) + count as u64 > self.capacity {
+                return None;
+            }
+
+            Some((state + count as u64) | BATCH)
+        });
+
+        if claimed {
+            Self::wait_while(|| self.state.load(Ordering::Acquire) & PUSHING_MASK != 0);
+        }
+
+        claimed
+    }
+
+    pub fn end_batch(&self) {
+        self.state.fetch_and(!BATCH, Ordering::AcqRel);
+    }
+
+    // waits for a batch in progress, and applies the update once no batch is being pushed
+    fn begin(&self, update: impl Fn(u64) -> Option<u64>) -> bool {
+        let mut state = self.state.load(Ordering::Acquire);
+        loop {
+            if state & BATCH != 0 {
+                Self::wait_while(|| self.state.load(Ordering::Acquire) & BATCH != 0);
+                state = self.state.load(Ordering::Acquire);
+                continue;
+            }
+
+            let next = match update(state) {
+                Some(next) => next,
+                None => return false,
+            };
+
+            match self
+                .state
+                .compare_exchange_weak(state, next, Ordering::AcqRel, Ordering::Acquire)
+            {
+                Ok(_) => return true,
+                Err(actual) => state = actual,
+            }
+        }
+    }
+
+    // the wait is bounded by the pushes of another sender, which never block
+    fn wait_while(condition: impl Fn() -> bool) {
+        let mut spins = 0u32;
+        while condition() {
+            if spins < 64 {
+                spin_loop();
+                spins += 1;
+            } else {
+                yield_now();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Slots;
+
+    #[test]
+    fn reserve_counts_towards_capacity() {
+        let slots = Slots::new(2);
+        assert!(slots.reserve(1));
+        assert!(slots.begin_push());
+        slots.end_push();
+
+        assert!(slots.is_full());
+        assert!(!slots.begin_push());
+        assert!(!slots.reserve(1));
+
+        slots.release();
+        assert!(slots.begin_push());
+        assert!(slots.is_full());
+    }
+
+    #[test]
+    fn batch_claims_room_for_every_message() {
+        let slots = Slots::new(3);
+        assert!(slots.begin_push());
+        slots.end_push();
+
+        assert!(!slots.begin_batch(3));
+        assert!(slots.begin_batch(2));
+        slots.end_batch();
+        assert!(slots.is_full());
+    }
+
+    #[test]
+    fn push_waits_for_batch() {
+        let slots = std::sync::Arc::new(Slots::new(3));
+        assert!(slots.begin_batch(2));
+
+        let pusher = {
+            let slots = slots.clone();
+            std::thread::spawn(move || {
+                assert!(slots.begin_push());
+                slots.end_push();
+            })
+        };
+
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        assert!(!slots.is_full());
+        slots.end_batch();
+
+        pusher.join().unwrap();
+        assert!(slots.is_full());
+    }
+}
@@ -1,23 +1,43 @@
 //! Oneshot channels transmit a single value between a sender and a reciever.  
 //!
 //! Neither can be cloned.  If the sender drops, the receiver recieves a `None` value.
+//!
+//! Completion can be checked from synchronous code with [Stream::try_recv](../stream/trait.Stream.html#method.try_recv),
+//! which distinguishes a pending value from a closed channel, or awaited with [Stream::blocking_recv](../stream/trait.Stream.html#method.blocking_recv).
+//!
+//! ```rust
+//! use postage::{oneshot, prelude::*, stream::TryRecvError};
+//!
+//! let (mut tx, mut rx) = oneshot::channel();
+//! assert_eq!(Err(TryRecvError::Pending), rx.try_recv());
+//!
+//! tx.try_send(1usize).unwrap();
+//! assert_eq!(Ok(1), rx.try_recv());
+//! assert_eq!(Err(TryRecvError::Closed), rx.try_recv());
+//! ```
+//!
+//! A collection of receivers can be awaited together with [join_all](./fn.join_all.html), or raced with [race](./fn.race.html).
 use std::fmt;
-use std::sync::Arc;
 
 use super::SendMessage;
 use crate::{
     sink::{PollSend, Sink},
     stream::{PollRecv, Stream},
-    sync::transfer::Transfer,
+    sync::{primitives::Arc, transfer::Transfer},
+    trace::Tracer,
 };
 use static_assertions::{assert_impl_all, assert_not_impl_all};
 
+mod join;
+
+pub use join::{join_all, race, JoinAll, Race};
+
 /// Constructs a pair of oneshot endpoints
 pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
     #[cfg(feature = "debug")]
     log::error!("Creating oneshot channel");
 
-    let shared = Arc::new(Transfer::new());
+    let shared = Arc::new(Transfer::new(Tracer::new("oneshot", None, None)));
     let sender = Sender {
         shared: shared.clone(),
     };
@@ -50,6 +70,13 @@ impl<T> Sink for Sender<T> {
     }
 }
 
+impl<T> Sender<T> {
+    /// Returns true if the receiver has been dropped.  Sends to a closed channel are rejected.
+    pub fn is_closed(&self) -> bool {
+        self.shared.is_receiver_closed()
+    }
+}
+
 impl<T> fmt::Debug for Sender<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Sender").finish()
@@ -61,7 +88,7 @@ mod impl_futures {
     use crate::sink::SendError;
     use std::task::Poll;
 
-    impl<T> futures::sink::Sink<T> for super::Sender<T> {
+    impl<T> futures_sink::Sink<T> for super::Sender<T> {
         type Error = crate::sink::SendError<T>;
 
         fn poll_ready(
@@ -114,6 +141,17 @@ impl<T> Stream for Receiver<T> {
     ) -> PollRecv<Self::Item> {
         self.shared.recv(cx)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.shared.size_hint()
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Returns true if the sender has been dropped.  A value which was sent before the drop can still be received.
+    pub fn is_closed(&self) -> bool {
+        self.shared.is_sender_closed()
+    }
 }
 
 impl<T> Drop for Receiver<T> {
@@ -134,7 +172,7 @@ mod tests {
 
     use crate::{
         sink::{PollSend, Sink},
-        stream::{PollRecv, Stream},
+        stream::{PollRecv, Stream, TryRecvError},
         test::{noop_context, panic_context},
         Context,
     };
@@ -177,6 +215,33 @@ mod tests {
         assert_eq!(PollRecv::Closed, Pin::new(&mut rx).poll_recv(&mut cx));
     }
 
+    #[test]
+    fn size_hint() {
+        let mut cx = noop_context();
+        let (mut tx, mut rx) = channel();
+        assert_eq!((0, Some(1)), rx.size_hint());
+
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, Message(1))
+        );
+        assert_eq!((1, Some(1)), rx.size_hint());
+
+        assert_eq!(
+            PollRecv::Ready(Message(1)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        assert_eq!((0, Some(0)), rx.size_hint());
+    }
+
+    #[test]
+    fn size_hint_disconnect() {
+        let (tx, rx) = channel::<Message>();
+
+        drop(tx);
+        assert_eq!((0, Some(0)), rx.size_hint());
+    }
+
     #[test]
     fn sender_disconnect() {
         let mut cx = noop_context();
@@ -261,6 +326,31 @@ mod tests {
         assert_eq!(1, w1_count.get());
     }
 
+    #[test]
+    fn try_recv() {
+        let (mut tx, mut rx) = channel();
+
+        assert_eq!(Err(TryRecvError::Pending), rx.try_recv());
+        assert_eq!(Ok(()), tx.try_send(Message(1)));
+        assert_eq!(Ok(Message(1)), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Closed), rx.try_recv());
+
+        let (tx, mut rx) = channel::<Message>();
+        drop(tx);
+        assert_eq!(Err(TryRecvError::Closed), rx.try_recv());
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn blocking_recv() {
+        let (mut tx, mut rx) = channel();
+
+        let thread = std::thread::spawn(move || tx.blocking_send(Message(1)));
+        assert_eq!(Some(Message(1)), rx.blocking_recv());
+        assert_eq!(None, rx.blocking_recv());
+        assert!(thread.join().unwrap().is_ok());
+    }
+
     #[test]
     fn sender_disconnect_wakes_receiver() {
         let (tx, mut rx) = channel::<usize>();
@@ -284,6 +374,20 @@ mod tests {
             Pin::new(&mut rx).poll_recv(&mut w1_context)
         );
     }
+
+    #[test]
+    fn is_closed() {
+        let (tx, rx) = channel::<Message>();
+        assert!(!tx.is_closed());
+        assert!(!rx.is_closed());
+
+        drop(tx);
+        assert!(rx.is_closed());
+
+        let (tx, rx) = channel::<Message>();
+        drop(rx);
+        assert!(tx.is_closed());
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,236 @@
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{self, Poll},
+};
+
+use super::Receiver;
+use crate::stream::{PollRecv, Stream};
+
+/// Waits for every receiver to complete.
+///
+/// Resolves with the value of each receiver, in the order the receivers were provided.
+/// The value is `None` if the sender was dropped without sending.
+///
+/// ```rust
+/// use postage::{oneshot, prelude::*};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let (mut a_tx, a_rx) = oneshot::channel();
+///     let (b_tx, b_rx) = oneshot::channel();
+///
+///     a_tx.send(1usize).await.ok();
+///     drop(b_tx);
+///
+///     assert_eq!(vec![Some(1), None], oneshot::join_all(vec![a_rx, b_rx]).await);
+/// }
+/// ```
+pub fn join_all<T, I>(receivers: I) -> JoinAll<T>
+where
+    I: IntoIterator<Item = Receiver<T>>,
+{
+    let receivers: Vec<_> = receivers.into_iter().map(Some).collect();
+    let values = receivers.iter().map(|_| None).collect();
+    let remaining = receivers.len();
+
+    JoinAll {
+        receivers,
+        values,
+        remaining,
+    }
+}
+
+/// Waits for the first receiver to complete with a value.
+///
+/// Resolves with the index and value of the first completion, and drops the remaining receivers,
+/// so their senders observe the channel as closed.  Resolves with `None` if every sender was dropped without sending.
+///
+/// ```rust
+/// use postage::{oneshot, prelude::*};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let (_slow_tx, slow_rx) = oneshot::channel();
+///     let (mut fast_tx, fast_rx) = oneshot::channel();
+///
+///     fast_tx.send("fast").await.ok();
+///     assert_eq!(Some((1, "fast")), oneshot::race(vec![slow_rx, fast_rx]).await);
+/// }
+/// ```
+pub fn race<T, I>(receivers: I) -> Race<T>
+where
+    I: IntoIterator<Item = Receiver<T>>,
+{
+    Race {
+        receivers: receivers.into_iter().map(Some).collect(),
+    }
+}
+
+/// A future which waits for every receiver, created by [join_all](./fn.join_all.html)
+#[must_use = "futures do nothing unless polled"]
+pub struct JoinAll<T> {
+    receivers: Vec<Option<Receiver<T>>>,
+    values: Vec<Option<T>>,
+    remaining: usize,
+}
+
+// the receivers and values are never pinned
+impl<T> Unpin for JoinAll<T> {}
+
+impl<T> Future for JoinAll<T> {
+    type Output = Vec<Option<T>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut cx = cx.into();
+
+        for (slot, value) in this.receivers.iter_mut().zip(this.values.iter_mut()) {
+            let receiver = match slot {
+                Some(receiver) => receiver,
+                None => continue,
+            };
+
+            match Pin::new(receiver).poll_recv(&mut cx) {
+                PollRecv::Ready(v) => *value = Some(v),
+                PollRecv::Closed => {}
+                PollRecv::Pending => continue,
+            }
+
+            *slot = None;
+            this.remaining -= 1;
+        }
+
+        if this.remaining > 0 {
+            return Poll::Pending;
+        }
+
+        Poll::Ready(std::mem::take(&mut this.values))
+    }
+}
+
+impl<T> fmt::Debug for JoinAll<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JoinAll")
+            .field("receivers", &self.receivers.len())
+            .field("remaining", &self.remaining)
+            .finish()
+    }
+}
+
+/// A future which waits for the first receiver to complete, created by [race](./fn.race.html)
+#[must_use = "futures do nothing unless polled"]
+pub struct Race<T> {
+    receivers: Vec<Option<Receiver<T>>>,
+}
+
+impl<T> Future for Race<T> {
+    type Output = Option<(usize, T)>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut cx = cx.into();
+
+        for (index, slot) in this.receivers.iter_mut().enumerate() {
+            let receiver = match slot {
+                Some(receiver) => receiver,
+                None => continue,
+            };
+
+            match Pin::new(receiver).poll_recv(&mut cx) {
+                PollRecv::Ready(value) => {
+                    // cancel the losers
+                    this.receivers.clear();
+                    return Poll::Ready(Some((index, value)));
+                }
+                PollRecv::Closed => *slot = None,
+                PollRecv::Pending => {}
+            }
+        }
+
+        if this.receivers.iter().all(Option::is_none) {
+            return Poll::Ready(None);
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<T> fmt::Debug for Race<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Race")
+            .field("receivers", &self.receivers.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::Future, pin::Pin, task::Poll};
+
+    use futures_test::task::{new_count_waker, noop_context};
+
+    use super::{join_all, race};
+    use crate::{
+        oneshot,
+        sink::{Sink, TrySendError},
+    };
+
+    #[test]
+    fn join_all_waits_for_every_receiver() {
+        let mut cx = noop_context();
+        let (mut a_tx, a_rx) = oneshot::channel();
+        let (mut b_tx, b_rx) = oneshot::channel();
+        let (c_tx, c_rx) = oneshot::channel();
+
+        let mut join = join_all(vec![a_rx, b_rx, c_rx]);
+        b_tx.try_send(2usize).unwrap();
+        drop(c_tx);
+        assert_eq!(Poll::Pending, Pin::new(&mut join).poll(&mut cx));
+
+        a_tx.try_send(1).unwrap();
+        assert_eq!(
+            Poll::Ready(vec![Some(1), Some(2), None]),
+            Pin::new(&mut join).poll(&mut cx)
+        );
+    }
+
+    #[test]
+    fn join_all_empty() {
+        let mut cx = noop_context();
+        let mut join = join_all(Vec::<oneshot::Receiver<usize>>::new());
+        assert_eq!(Poll::Ready(vec![]), Pin::new(&mut join).poll(&mut cx));
+    }
+
+    #[test]
+    fn race_cancels_losers() {
+        let (waker, count) = new_count_waker();
+        let mut cx = std::task::Context::from_waker(&waker);
+        let (mut a_tx, a_rx) = oneshot::channel();
+        let (mut b_tx, b_rx) = oneshot::channel();
+
+        let mut race = race(vec![a_rx, b_rx]);
+        assert_eq!(Poll::Pending, Pin::new(&mut race).poll(&mut cx));
+
+        b_tx.try_send(2usize).unwrap();
+        assert_eq!(1, count.get());
+        assert_eq!(Poll::Ready(Some((1, 2))), Pin::new(&mut race).poll(&mut cx));
+
+        assert_eq!(Err(TrySendError::Rejected(1)), a_tx.try_send(1));
+    }
+
+    #[test]
+    fn race_all_dropped() {
+        let mut cx = noop_context();
+        let (a_tx, a_rx) = oneshot::channel::<usize>();
+        let (b_tx, b_rx) = oneshot::channel();
+
+        let mut race = race(vec![a_rx, b_rx]);
+        drop(a_tx);
+        assert_eq!(Poll::Pending, Pin::new(&mut race).poll(&mut cx));
+
+        drop(b_tx);
+        assert_eq!(Poll::Ready(None), Pin::new(&mut race).poll(&mut cx));
+    }
+}
@@ -0,0 +1,647 @@
+//! A multi-producer, single-consumer channel which spills to disk when the in-memory buffer is full.
+//!
+//! Messages are buffered in memory, up to the capacity of the channel.  When the buffer is full, senders append messages
+//! to a temporary file instead of waiting, and the receiver reads them back as the buffer drains.  This absorbs bursts
+//! without growing memory, and messages are always received in the order they were sent.
+//!
+//! Spilled messages are serialized with bincode, and the spill file is deleted when the channel is dropped.
+//!
+//! Requires the `spill` feature.
+//!
+//! ```rust
+//! use postage::{prelude::*, spill};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let (mut tx, mut rx) = spill::channel(2).unwrap();
+//!     for i in 0..4usize {
+//!         tx.send(i).await.ok();
+//!     }
+//!
+//!     assert_eq!(2, rx.spilled());
+//!     assert_eq!(Some(0), rx.recv().await);
+//! }
+//! ```
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use bytes::BytesMut;
+use parking_lot::Mutex;
+use serde::{de::DeserializeOwned, Serialize};
+use static_assertions::assert_impl_all;
+
+use crate::{
+    codec::{Bincode, Decoder, Encoder},
+    sink::{PollSend, Sink},
+    stop::StopToken,
+    stream::{PollRecv, Stream},
+    sync::notifier::Notifier,
+    Context,
+};
+
+/// Constructs a pair of endpoints, which buffers up to `capacity` messages in memory,
+/// and spills to a file in the system temporary directory.
+pub fn channel<T>(capacity: usize) -> io::Result<(Sender<T>, Receiver<T>)>
+where
+    T: Serialize + DeserializeOwned,
+{
+    Builder::new(capacity).build()
+}
+
+/// Constructs a spill channel, with additional configuration.
+#[derive(Clone, Debug)]
+pub struct Builder {
+    capacity: usize,
+    dir: PathBuf,
+    stop: Option<StopToken>,
+}
+
+impl Builder {
+    /// Creates a builder for a channel which buffers up to `capacity` messages in memory
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            dir: std::env::temp_dir(),
+            stop: None,
+        }
+    }
+
+    /// Sets the directory where the spill file is created.  Defaults to the system temporary directory.
+    pub fn dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.dir = dir.as_ref().to_path_buf();
+        self
+    }
+
+    /// Closes the channel when the token's source is stopped.  Senders reject new messages,
+    /// and the receiver drains the buffered and spilled messages before observing the channel as closed.
+    pub fn stop_token(mut self, token: StopToken) -> Self {
+        self.stop = Some(token);
+        self
+    }
+
+    /// Constructs the channel.  The spill file is created when the first message spills.
+    pub fn build<T>(self) -> io::Result<(Sender<T>, Receiver<T>)>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        fs::create_dir_all(&self.dir)?;
+
+        // senders never wait, so only the receiver is woken when the token's source stops
+        let notify_rx = Arc::new(Notifier::new());
+        if let Some(ref stop) = self.stop {
+            stop.register(&notify_rx, &notify_rx);
+        }
+
+        let shared = Arc::new(Shared {
+            capacity: self.capacity.max(1),
+            state: Mutex::new(State {
+                memory: VecDeque::new(),
+                spill: Spill::new(self.dir),
+                senders: 1,
+                receiver: true,
+            }),
+            notify_rx,
+            stop: self.stop,
+        });
+
+        let sender = Sender {
+            shared: shared.clone(),
+            error: None,
+        };
+
+        let receiver = Receiver {
+            shared,
+            error: None,
+        };
+
+        Ok((sender, receiver))
+    }
+}
+
+struct Shared<T> {
+    capacity: usize,
+    state: Mutex<State<T>>,
+    notify_rx: Arc<Notifier>,
+    stop: Option<StopToken>,
+}
+
+impl<T> Shared<T> {
+    fn is_stopped(&self) -> bool {
+        self.stop.as_ref().is_some_and(StopToken::is_stopped)
+    }
+}
+
+struct State<T> {
+    memory: VecDeque<T>,
+    spill: Spill<T>,
+    senders: usize,
+    receiver: bool,
+}
+
+impl<T> State<T>
+where
+    T: DeserializeOwned,
+{
+    /// Reads spilled messages back into memory, until the buffer is full
+    fn refill(&mut self, capacity: usize) -> io::Result<()> {
+        while self.memory.len() < capacity && self.spill.len > 0 {
+            let value = self.spill.read()?;
+            self.memory.push_back(value);
+        }
+
+        Ok(())
+    }
+}
+
+/// An append-only file of length-prefixed bincode records, which is truncated when it has been read to the end
+struct Spill<T> {
+    dir: PathBuf,
+    file: Option<(PathBuf, File)>,
+    codec: Bincode<T>,
+    buffer: BytesMut,
+    read_pos: u64,
+    write_pos: u64,
+    len: usize,
+}
+
+impl<T> Spill<T> {
+    fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            file: None,
+            codec: Bincode::with_max_length(u32::MAX as usize),
+            buffer: BytesMut::new(),
+            read_pos: 0,
+            write_pos: 0,
+            len: 0,
+        }
+    }
+
+    fn file(&mut self) -> io::Result<&mut File> {
+        if self.file.is_none() {
+            static NEXT: AtomicUsize = AtomicUsize::new(0);
+            let id = NEXT.fetch_add(1, Ordering::Relaxed);
+            let path = self
+                .dir
+                .join(format!("postage-spill-{}-{}.bin", std::process::id(), id));
+
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(&path)?;
+
+            self.file = Some((path, file));
+        }
+
+        Ok(&mut self.file.as_mut().unwrap().1)
+    }
+
+    fn write(&mut self, value: &T) -> io::Result<()>
+    where
+        T: Serialize,
+    {
+        self.buffer.clear();
+        self.codec
+            .encode(value, &mut self.buffer)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let position = self.write_pos;
+        let buffer = std::mem::take(&mut self.buffer);
+        let length = buffer.len();
+        let result = self.file().and_then(|file| {
+            file.seek(SeekFrom::Start(position))?;
+            file.write_all(&buffer)
+        });
+        self.buffer = buffer;
+
+        if let Err(e) = result {
+            // remove the partial record, so the next record is written in its place
+            if let Some((_, file)) = &self.file {
+                file.set_len(position).ok();
+            }
+
+            return Err(e);
+        }
+
+        self.write_pos += length as u64;
+        self.len += 1;
+        Ok(())
+    }
+
+    fn read(&mut self) -> io::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let position = self.read_pos;
+        let mut buffer = std::mem::take(&mut self.buffer);
+        let result = self.file().and_then(|file| {
+            file.seek(SeekFrom::Start(position))?;
+
+            let mut prefix = [0u8; 4];
+            file.read_exact(&mut prefix)?;
+            let length = u32::from_le_bytes(prefix) as usize;
+
+            buffer.clear();
+            buffer.extend_from_slice(&prefix);
+            buffer.resize(prefix.len() + length, 0);
+            file.read_exact(&mut buffer[prefix.len()..])?;
+            Ok(prefix.len() + length)
+        });
+
+        let record = match result {
+            Ok(record) => record,
+            Err(e) => {
+                self.buffer = buffer;
+                return Err(e);
+            }
+        };
+
+        let value = self
+            .codec
+            .decode(&mut buffer)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            .and_then(|value| value.ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof)));
+        self.buffer = buffer;
+
+        self.read_pos += record as u64;
+        self.len -= 1;
+
+        if self.len == 0 {
+            // the file has been read to the end, so the space can be reclaimed
+            self.read_pos = 0;
+            self.write_pos = 0;
+            if let Some((_, file)) = &self.file {
+                file.set_len(0)?;
+            }
+        }
+
+        value
+    }
+}
+
+impl<T> Drop for Spill<T> {
+    fn drop(&mut self) {
+        if let Some((path, file)) = self.file.take() {
+            drop(file);
+            fs::remove_file(path).ok();
+        }
+    }
+}
+
+/// The sender half of a spill channel.  Can send messages with the postage::Sink trait.
+///
+/// Sends complete immediately, either into the memory buffer or the spill file.  If a message cannot be spilled,
+/// it is rejected, and the error can be retrieved with `take_error`.
+///
+/// Can be cloned.
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+    error: Option<io::Error>,
+}
+
+assert_impl_all!(Sender<String>: Clone, Send, Sync, fmt::Debug);
+
+impl<T> Sender<T> {
+    /// Takes the error which caused the most recent message to be rejected
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+}
+
+impl<T> Sink for Sender<T>
+where
+    T: Serialize,
+{
+    type Item = T;
+
+    fn poll_send(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        value: Self::Item,
+    ) -> PollSend<Self::Item> {
+        let this = self.get_mut();
+        let mut state = this.shared.state.lock();
+
+        if !state.receiver || this.shared.is_stopped() {
+            return PollSend::Rejected(value);
+        }
+
+        // once a message has spilled, later messages spill behind it, so the order is preserved
+        if state.spill.len == 0 && state.memory.len() < this.shared.capacity {
+            state.memory.push_back(value);
+        } else if let Err(e) = state.spill.write(&value) {
+            drop(state);
+            this.error = Some(e);
+            return PollSend::Rejected(value);
+        }
+
+        drop(state);
+        this.shared.notify_rx.notify();
+        PollSend::Ready
+    }
+}
+
+#[cfg(feature = "futures-traits")]
+mod impl_futures {
+    use std::task::Poll;
+
+    use serde::Serialize;
+
+    use crate::{
+        sink::{PollSend, SendError, Sink},
+        Context,
+    };
+
+    impl<T: Serialize> futures_sink::Sink<T> for super::Sender<T> {
+        type Error = SendError<T>;
+
+        /// Messages are never refused for lack of capacity, so the sender is always ready
+        fn poll_ready(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn start_send(self: std::pin::Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+            match self.poll_send(&mut Context::empty(), item) {
+                PollSend::Ready => Ok(()),
+                PollSend::Pending(item) | PollSend::Rejected(item) => Err(SendError(item)),
+            }
+        }
+
+        fn poll_flush(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.shared.state.lock().senders += 1;
+
+        Self {
+            shared: self.shared.clone(),
+            error: None,
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock();
+        state.senders -= 1;
+
+        if state.senders == 0 {
+            drop(state);
+            self.shared.notify_rx.notify();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender").finish()
+    }
+}
+
+/// The receiver half of a spill channel.  Cannot be cloned.
+///
+/// Receives messages with the postage::Stream trait.  The channel closes when all senders have been dropped,
+/// and every message has been received.  If a spilled message cannot be read back, the receiver closes,
+/// and the error can be retrieved with `take_error`.
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+    error: Option<io::Error>,
+}
+
+assert_impl_all!(Receiver<String>: Send, Sync, fmt::Debug);
+
+impl<T> Receiver<T> {
+    /// The number of messages waiting to be received, in memory and on disk
+    pub fn len(&self) -> usize {
+        let state = self.shared.state.lock();
+        state.memory.len() + state.spill.len
+    }
+
+    /// Returns true if there are no messages waiting to be received
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of messages which are waiting in the spill file
+    pub fn spilled(&self) -> usize {
+        self.shared.state.lock().spill.len
+    }
+
+    /// Takes the error which caused the receiver to close
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+}
+
+impl<T> Stream for Receiver<T>
+where
+    T: DeserializeOwned,
+{
+    type Item = T;
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item> {
+        let this = self.get_mut();
+
+        loop {
+            let guard = this.shared.notify_rx.guard();
+
+            {
+                let mut state = this.shared.state.lock();
+                if state.memory.is_empty() && this.error.is_none() {
+                    if let Err(e) = state.refill(this.shared.capacity) {
+                        this.error = Some(e);
+                    }
+                }
+
+                if let Some(value) = state.memory.pop_front() {
+                    if let Err(e) = state.refill(this.shared.capacity) {
+                        this.error = Some(e);
+                    }
+
+                    return PollRecv::Ready(value);
+                }
+
+                // if the spill file could not be read, the remaining messages are lost
+                if this.error.is_some() || state.senders == 0 || this.shared.is_stopped() {
+                    return PollRecv::Closed;
+                }
+            }
+
+            this.shared.notify_rx.subscribe(cx);
+
+            if guard.is_expired() {
+                continue;
+            }
+
+            return PollRecv::Pending;
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock();
+        state.receiver = false;
+        state.memory.clear();
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::{channel, Builder};
+    use crate::{
+        sink::{Sink, TrySendError},
+        stop::StopSource,
+        stream::{Stream, TryRecvError},
+    };
+
+    fn test_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("postage-spill-{}-{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn memory() {
+        let (mut tx, mut rx) = channel::<usize>(2).unwrap();
+        tx.try_send(1).unwrap();
+        tx.try_send(2).unwrap();
+
+        assert_eq!(0, rx.spilled());
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(Ok(2), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Pending), rx.try_recv());
+    }
+
+    #[test]
+    fn spills_in_order() {
+        let (mut tx, mut rx) = channel::<String>(2).unwrap();
+        for i in 0..6 {
+            tx.try_send(i.to_string()).unwrap();
+        }
+
+        assert_eq!(6, rx.len());
+        assert_eq!(4, rx.spilled());
+
+        assert_eq!(Ok("0".to_string()), rx.try_recv());
+        assert_eq!(3, rx.spilled());
+
+        // the buffer has room, but the message queues behind the spilled messages
+        tx.try_send("6".to_string()).unwrap();
+        assert_eq!(4, rx.spilled());
+
+        for i in 1..7 {
+            assert_eq!(Ok(i.to_string()), rx.try_recv());
+        }
+        assert!(rx.is_empty());
+
+        // the channel buffers in memory again, once the spill file has drained
+        tx.try_send("7".to_string()).unwrap();
+        assert_eq!(0, rx.spilled());
+    }
+
+    #[test]
+    fn close() {
+        let (mut tx, mut rx) = channel::<usize>(1).unwrap();
+        tx.try_send(1).unwrap();
+        tx.try_send(2).unwrap();
+        drop(tx);
+
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(Ok(2), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Closed), rx.try_recv());
+    }
+
+    #[test]
+    fn receiver_dropped() {
+        let (mut tx, rx) = channel::<usize>(1).unwrap();
+        drop(rx);
+        assert!(tx.try_send(1).is_err());
+    }
+
+    #[test]
+    fn removes_file() {
+        let dir = test_dir("removes-file");
+        let (mut tx, rx) = Builder::new(1).dir(&dir).build::<usize>().unwrap();
+        tx.try_send(1).unwrap();
+        tx.try_send(2).unwrap();
+        assert_eq!(1, fs::read_dir(&dir).unwrap().count());
+
+        drop((tx, rx));
+        assert_eq!(0, fs::read_dir(&dir).unwrap().count());
+        fs::remove_dir(&dir).ok();
+    }
+
+    #[test]
+    fn stop_token() {
+        let stop = StopSource::new();
+        let (mut tx, mut rx) = Builder::new(1)
+            .stop_token(stop.token())
+            .build::<usize>()
+            .unwrap();
+        tx.try_send(1).unwrap();
+        tx.try_send(2).unwrap();
+
+        stop.stop();
+        assert_eq!(Err(TrySendError::Rejected(3)), tx.try_send(3));
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(Ok(2), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Closed), rx.try_recv());
+    }
+
+    #[tokio::test]
+    async fn wakes_receiver() {
+        let (mut tx, mut rx) = channel::<usize>(1).unwrap();
+
+        let recv = tokio::spawn(async move {
+            let mut received = Vec::new();
+            while let Some(value) = rx.recv().await {
+                received.push(value);
+            }
+            received
+        });
+
+        for i in 0..4 {
+            tx.send(i).await.unwrap();
+        }
+        drop(tx);
+
+        assert_eq!(vec![0, 1, 2, 3], recv.await.unwrap());
+    }
+}
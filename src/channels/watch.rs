@@ -3,47 +3,222 @@
 //! When the channel is created, the receiver will immediately observe `T::default()`.  Cloned receivers will immediately observe the latest stored value.
 //!
 //! Senders can mutably borrow the contained value (which notifies receivers on release).  Receivers can immutably borrow the contained value.
+//!
+//! [combine_latest](./fn.combine_latest.html) and the [combine_latest!](../macro.combine_latest.html) macro derive state from several channels.
+//!
+//! If there is no meaningful initial value, [channel_empty](./fn.channel_empty.html) creates a channel where receivers wait for the first value to be sent.
+//!
+//! [Builder::history](./struct.Builder.html#method.history) retains the last N values, so a receiver can process each transition it missed with [Receiver::take_changes](./struct.Receiver.html#method.take_changes).
 
 use super::SendSyncMessage;
 use std::{
+    collections::VecDeque,
     fmt,
+    future::Future,
+    marker::PhantomData,
     ops::{Deref, DerefMut},
-    sync::atomic::{AtomicUsize, Ordering},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::Poll,
 };
 
-use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use parking_lot::{
+    MappedRwLockReadGuard, MappedRwLockWriteGuard, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard,
+};
+use pin_project::pin_project;
 use static_assertions::{assert_impl_all, assert_not_impl_all};
 
 use crate::{
+    dead_letter::{DeadLetter, Undelivered},
+    metrics::{ChannelMetrics, MetricsHook},
+    registry::Registration,
     sink::{PollSend, Sink},
+    stop::StopToken,
     stream::{PollRecv, Stream},
     sync::{shared, ReceiverShared, SenderShared},
+    trace::Tracer,
 };
 
 /// Constructs a new watch channel pair, filled with `T::default()`.
 pub fn channel<T: Clone + Default>() -> (Sender<T>, Receiver<T>) {
-    channel_with(T::default())
+    Builder::new().build()
 }
 
 /// Constructs a new watch channel pair, filled with the provided value
 pub fn channel_with<T: Clone>(value: T) -> (Sender<T>, Receiver<T>) {
-    #[cfg(feature = "debug")]
-    log::error!("Creating watch channel");
+    Builder::new().build_with(value)
+}
 
-    let (tx_shared, rx_shared) = shared(StateExtension::new(value));
-    let sender = Sender { shared: tx_shared };
+/// Constructs a new watch channel pair, with no stored value.
+///
+/// Receivers wait for the first value to be sent, rather than observing a placeholder.
+///
+/// ```rust
+/// use postage::{prelude::*, watch};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let (mut tx, mut rx) = watch::channel_empty::<String>();
+///     assert!(rx.try_borrow().is_none());
+///
+///     tx.send("config".to_string()).await.ok();
+///     assert_eq!(Some("config".to_string()), rx.recv().await);
+/// }
+/// ```
+pub fn channel_empty<T: Clone>() -> (Sender<T>, Receiver<T>) {
+    Builder::new().build_empty()
+}
 
-    let receiver = Receiver {
-        shared: rx_shared,
-        generation: AtomicUsize::new(0),
-    };
+/// Constructs a watch channel, with additional configuration.
+///
+/// ```rust
+/// use postage::watch;
+///
+/// let (tx, rx) = watch::Builder::new().name("config").build_with(1usize);
+/// ```
+pub struct Builder<T> {
+    name: Option<String>,
+    metrics: Option<Arc<dyn ChannelMetrics>>,
+    stop: Option<StopToken>,
+    history: Option<usize>,
+    undelivered: Undelivered<T>,
+    _t: PhantomData<fn() -> T>,
+}
+
+impl<T: Clone> Builder<T> {
+    /// Creates a builder for a watch channel
+    pub fn new() -> Self {
+        Self {
+            name: None,
+            metrics: None,
+            stop: None,
+            history: None,
+            undelivered: Undelivered::new(),
+            _t: PhantomData,
+        }
+    }
+
+    /// Names the channel.  The name is attached to the channel's tracing span.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Installs a metrics hook on the channel, which takes precedence over the global hook.
+    pub fn metrics(mut self, metrics: Arc<dyn ChannelMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
 
-    (sender, receiver)
+    /// Closes the channel when the token's source is stopped.  Senders reject new messages,
+    /// and receivers drain the buffered messages before observing the channel as closed.
+    pub fn stop_token(mut self, token: StopToken) -> Self {
+        self.stop = Some(token);
+        self
+    }
+
+    /// Attaches a dead-letter sink to the channel.  Values sent after every receiver has been dropped,
+    /// or the channel has been stopped, are returned to the sender, and a copy is forwarded to the sink without blocking,
+    /// tagged with `DeadLetterReason::Rejected`.
+    pub fn dead_letter<S>(mut self, sink: S) -> Self
+    where
+        S: Sink<Item = DeadLetter<T>> + Send + 'static,
+    {
+        self.undelivered.set_dead_letter(sink);
+        self.undelivered.set_copy_rejected(Some(T::clone));
+        self
+    }
+
+    /// Retains the last `capacity` values stored in the channel, so receivers can observe the changes they missed
+    /// with [Receiver::take_changes](./struct.Receiver.html#method.take_changes).
+    ///
+    /// Panics if `capacity` is zero.
+    ///
+    /// ```rust
+    /// use postage::{prelude::*, watch};
+    ///
+    /// let (mut tx, mut rx) = watch::Builder::new().history(4).build_with(0usize);
+    ///
+    /// tx.try_send(1).ok();
+    /// tx.try_send(2).ok();
+    ///
+    /// assert_eq!(vec![0, 1, 2], rx.take_changes());
+    /// assert!(rx.take_changes().is_empty());
+    /// ```
+    pub fn history(mut self, capacity: usize) -> Self {
+        assert!(capacity > 0, "the watch history capacity must be non-zero");
+        self.history = Some(capacity);
+        self
+    }
+
+    /// Constructs the pair of channel endpoints, filled with `T::default()`
+    pub fn build(self) -> (Sender<T>, Receiver<T>)
+    where
+        T: Default,
+    {
+        self.build_with(T::default())
+    }
+
+    /// Constructs the pair of channel endpoints, filled with the provided value
+    pub fn build_with(self, value: T) -> (Sender<T>, Receiver<T>) {
+        self.build_state(Some(value))
+    }
+
+    /// Constructs the pair of channel endpoints, with no stored value.  Receivers wait for the first value to be sent.
+    pub fn build_empty(self) -> (Sender<T>, Receiver<T>) {
+        self.build_state(None)
+    }
+
+    fn build_state(self, value: Option<T>) -> (Sender<T>, Receiver<T>) {
+        #[cfg(feature = "debug")]
+        log::error!("Creating watch channel");
+
+        let tracer = Tracer::new("watch", self.name.as_deref(), None);
+        let metrics = MetricsHook::resolve(self.metrics, "watch", self.name.as_deref());
+        let registration = Registration::new("watch", self.name.as_deref(), None, false);
+        let (tx_shared, rx_shared) = shared(
+            StateExtension::new(value, self.history.map(History::new), self.undelivered),
+            tracer,
+            metrics,
+            registration,
+            self.stop,
+        );
+        let sender = Sender { shared: tx_shared };
+
+        let receiver = Receiver {
+            shared: rx_shared,
+            generation: AtomicUsize::new(0),
+        };
+
+        (sender, receiver)
+    }
+}
+
+impl<T: Clone> Default for Builder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for Builder<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Builder")
+            .field("name", &self.name)
+            .field("metrics", &self.metrics.is_some())
+            .field("stop", &self.stop)
+            .field("history", &self.history)
+            .field("undelivered", &self.undelivered)
+            .finish()
+    }
 }
 
 /// Constructs a pair of channel endpoints that store Option<T>
 ///
 /// This is helpful if T does not implement Default, and you don't have an initial value.
+/// [channel_empty](./fn.channel_empty.html) avoids the `Option` in the item type.
 pub fn channel_with_option<T: Clone>() -> (Sender<Option<T>>, Receiver<Option<T>>) {
     channel::<Option<T>>()
 }
@@ -65,10 +240,18 @@ impl<T> Sink for Sender<T> {
         value: Self::Item,
     ) -> PollSend<Self::Item> {
         if self.shared.is_closed() {
-            return PollSend::Rejected(value);
+            self.shared.tracer().reject();
+            if let Some(metrics) = self.shared.metrics() {
+                metrics.on_reject();
+            }
+            return PollSend::Rejected(self.shared.extension().undelivered.reject(value));
         }
 
         self.shared.extension().push(value);
+        self.shared.tracer().send();
+        if let Some(metrics) = self.shared.metrics() {
+            metrics.on_send(None);
+        }
         self.shared.notify_receivers();
 
         PollSend::Ready
@@ -77,12 +260,19 @@ impl<T> Sink for Sender<T> {
 
 #[allow(clippy::needless_lifetimes)]
 impl<T> Sender<T> {
+    /// Returns true if every receiver has been dropped, or the channel has been stopped.  Sends to a closed channel are rejected.
+    pub fn is_closed(&self) -> bool {
+        self.shared.is_closed()
+    }
+
     /// Mutably borrows the contained value, blocking the channel while the borrow is held.
     ///
     /// After the borrow is released, receivers will be notified of a new value.
+    ///
+    /// Panics if the channel was created empty, and no value has been sent.
     pub fn borrow_mut<'s>(&'s mut self) -> RefMut<'s, T> {
         let extension = self.shared.extension();
-        let lock = extension.value.write();
+        let lock = extension.write().expect(EMPTY_BORROW);
 
         RefMut {
             lock,
@@ -91,7 +281,10 @@ impl<T> Sender<T> {
     }
 
     /// Creates a new Receiver that listens to this channel.
-    pub fn subscribe(&mut self) -> Receiver<T> {
+    ///
+    /// The receiver immediately observes the stored value (if any), and then each later update.
+    /// This allows components which only own the sender to hand out views of the state.
+    pub fn subscribe(&self) -> Receiver<T> {
         Receiver {
             shared: self.shared.clone_receiver(),
             generation: AtomicUsize::new(0),
@@ -99,9 +292,11 @@ impl<T> Sender<T> {
     }
 
     /// Immutably borrows the contained value, blocking the channel while the borrow is held.
+    ///
+    /// Panics if the channel was created empty, and no value has been sent.
     pub fn borrow<'s>(&'s mut self) -> Ref<'s, T> {
         let extension = self.shared.extension();
-        let lock = extension.value.read();
+        let lock = extension.read().expect(EMPTY_BORROW);
 
         Ref { lock }
     }
@@ -119,7 +314,7 @@ mod impl_futures {
 
     use crate::sink::SendError;
 
-    impl<T> futures::sink::Sink<T> for super::Sender<T> {
+    impl<T> futures_sink::Sink<T> for super::Sender<T> {
         type Error = SendError<T>;
 
         fn poll_ready(
@@ -131,10 +326,17 @@ mod impl_futures {
 
         fn start_send(self: std::pin::Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
             if self.shared.is_closed() {
-                return Err(SendError(item));
+                if let Some(metrics) = self.shared.metrics() {
+                    metrics.on_reject();
+                }
+                return Err(SendError(self.shared.extension().undelivered.reject(item)));
             }
 
             self.shared.extension().push(item);
+            self.shared.tracer().send();
+            if let Some(metrics) = self.shared.metrics() {
+                metrics.on_send(None);
+            }
             self.shared.notify_receivers();
 
             Ok(())
@@ -193,10 +395,31 @@ where
 
                     return PollRecv::Pending;
                 }
-                TryRecv::Ready(v) => return PollRecv::Ready(v),
+                TryRecv::Ready(v) => {
+                    self.shared.tracer().recv();
+                    if let Some(metrics) = self.shared.metrics() {
+                        metrics.on_recv(None, None);
+                    }
+                    return PollRecv::Ready(v);
+                }
             }
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let state = self.shared.extension();
+
+        // updates are coalesced, so at most the stored value is ready
+        let unseen = self.generation.load(Ordering::Acquire) <= state.generation(Ordering::SeqCst)
+            && state.read().is_some();
+        let ready = usize::from(unseen);
+
+        if self.shared.is_closed() {
+            (ready, Some(ready))
+        } else {
+            (ready, None)
+        }
+    }
 }
 
 impl<T> Receiver<T>
@@ -211,7 +434,10 @@ where
             return TryRecv::Pending;
         }
 
-        let borrow = self.shared.extension().value.read();
+        let borrow = match state.read() {
+            Some(borrow) => borrow,
+            None => return TryRecv::Pending,
+        };
         let stored_generation = self.shared.extension().generation(Ordering::SeqCst);
         self.generation
             .store(stored_generation + 1, Ordering::Release);
@@ -242,13 +468,13 @@ impl<T> fmt::Debug for Receiver<T> {
 /// A mutable reference to the value contained in the channel.
 /// Receivers are notified when the borrow is released.
 pub struct RefMut<'t, T> {
-    lock: RwLockWriteGuard<'t, T>,
+    lock: MappedRwLockWriteGuard<'t, T>,
     shared: SenderShared<StateExtension<T>>,
 }
 
 impl<'t, T> DerefMut for RefMut<'t, T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut *self.lock
+        &mut self.lock
     }
 }
 
@@ -256,59 +482,402 @@ impl<'t, T> Deref for RefMut<'t, T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        &*self.lock
+        &self.lock
     }
 }
 
 impl<'t, T> Drop for RefMut<'t, T> {
     fn drop(&mut self) {
-        self.shared.extension().increment();
+        let extension = self.shared.extension();
+        extension.increment();
+        extension.record(&self.lock);
+        self.shared.tracer().send();
+        if let Some(metrics) = self.shared.metrics() {
+            metrics.on_send(None);
+        }
         self.shared.notify_receivers();
     }
 }
 
 /// An immutable reference to the value contained in the channel.
 pub struct Ref<'t, T> {
-    lock: RwLockReadGuard<'t, T>,
+    lock: MappedRwLockReadGuard<'t, T>,
 }
 
 impl<'t, T> Deref for Ref<'t, T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        &*self.lock
+        &self.lock
     }
 }
 
 impl<T> Receiver<T> {
+    /// Returns true if the sender has been dropped, or the channel has been stopped.  The last value can still be borrowed.
+    pub fn is_closed(&self) -> bool {
+        self.shared.is_closed()
+    }
+
     /// Borrows the value in the channel, blocking the channel while the value is held.
+    ///
+    /// Panics if the channel was created empty, and no value has been sent.
     pub fn borrow(&self) -> Ref<'_, T> {
-        let lock = self.shared.extension().value.read();
-        Ref { lock }
+        self.try_borrow().expect(EMPTY_BORROW)
+    }
+
+    /// Borrows the value in the channel, or returns `None` if the channel was created empty and no value has been sent.
+    pub fn try_borrow(&self) -> Option<Ref<'_, T>> {
+        let lock = self.shared.extension().read()?;
+        Some(Ref { lock })
+    }
+
+    /// Marks the stored value as unseen, so the next receive produces the current value again.
+    ///
+    /// This is useful when the consumer needs to re-process the current state, such as after an internal reset.
+    pub fn mark_changed(&mut self) {
+        self.generation.store(0, Ordering::Release);
+    }
+
+    /// Returns the values which this receiver has not yet observed, oldest first, and marks the stored value as seen.
+    ///
+    /// If the channel was built with [Builder::history](./struct.Builder.html#method.history), every retained value
+    /// that was stored after the receiver's last receive is returned.  Older values are lost, if the receiver fell further behind than the history.
+    /// Otherwise, only the stored value is returned, if it is unseen.
+    pub fn take_changes(&mut self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let extension = self.shared.extension();
+        let changes = extension.changes(self.generation.load(Ordering::Acquire));
+
+        self.generation
+            .store(changes.generation + 1, Ordering::Release);
+        changes.values
+    }
+
+    /// Marks the stored value as seen, so the next receive waits for a new value.
+    pub fn mark_unchanged(&mut self) {
+        let generation = self.shared.extension().generation(Ordering::SeqCst);
+        self.generation.store(generation + 1, Ordering::Release);
+    }
+
+    /// Waits until the stored value satisfies the predicate, and borrows it.
+    ///
+    /// The current value is checked first, so this completes immediately if the predicate already holds.
+    /// The borrowed value is marked as seen by the receiver.  Resolves to `None` if the sender is dropped,
+    /// and the last stored value does not satisfy the predicate.
+    ///
+    /// ```rust
+    /// use postage::{prelude::*, watch};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let (mut tx, mut rx) = watch::channel_with(0usize);
+    ///
+    ///     tokio::spawn(async move {
+    ///         for i in 1..=3 {
+    ///             tx.send(i).await.ok();
+    ///         }
+    ///     });
+    ///
+    ///     let value = rx.wait_for(|v| *v == 3).await;
+    ///     assert_eq!(Some(3), value.as_deref().copied());
+    /// }
+    /// ```
+    pub fn wait_for<F>(&mut self, predicate: F) -> WaitForFuture<'_, T, F>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        WaitForFuture {
+            receiver: self,
+            predicate,
+        }
+    }
+}
+
+/// A future returned by `Receiver::wait_for`.
+#[must_use = "futures do nothing unless polled"]
+pub struct WaitForFuture<'r, T, F> {
+    receiver: &'r Receiver<T>,
+    predicate: F,
+}
+
+impl<'r, T, F> Future for WaitForFuture<'r, T, F>
+where
+    F: FnMut(&T) -> bool + Unpin,
+{
+    type Output = Option<Ref<'r, T>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let receiver = this.receiver;
+        let cx: crate::Context<'_> = cx.into();
+
+        loop {
+            let guard = receiver.shared.send_guard();
+            // checked before the read, so a final value written just before the sender drops is observed
+            let closed = receiver.shared.is_closed();
+
+            let extension = receiver.shared.extension();
+            if let Some(lock) = extension.read() {
+                if (this.predicate)(&lock) {
+                    let generation = extension.generation(Ordering::SeqCst);
+                    receiver.generation.store(generation + 1, Ordering::Release);
+                    return Poll::Ready(Some(Ref { lock }));
+                }
+            }
+
+            if closed {
+                return Poll::Ready(None);
+            }
+
+            receiver.shared.subscribe_send(&cx);
+
+            if guard.is_expired() {
+                continue;
+            }
+
+            return Poll::Pending;
+        }
+    }
+}
+
+impl<'r, T, F> fmt::Debug for WaitForFuture<'r, T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WaitForFuture").finish()
     }
 }
 
+/// Combines the latest values of two receivers.  Produces an `(A, B)` snapshot whenever either input changes,
+/// once both inputs have produced a value.
+///
+/// Works with any stream, but is designed for watch receivers, which always yield the latest value.
+/// The stream closes when both inputs close, or when an input closes before producing a value.
+/// Use the [combine_latest!](../macro.combine_latest.html) macro to combine more than two receivers.
+///
+/// ```rust
+/// use postage::{prelude::*, watch};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let (mut width_tx, width) = watch::channel_with(1usize);
+///     let (mut height_tx, height) = watch::channel_empty::<usize>();
+///
+///     let mut area = watch::combine_latest(width, height).map(|(w, h)| w * h);
+///
+///     height_tx.send(2).await.ok();
+///     assert_eq!(Some(2), area.recv().await);
+///
+///     width_tx.send(3).await.ok();
+///     assert_eq!(Some(6), area.recv().await);
+/// }
+/// ```
+pub fn combine_latest<A, B>(a: A, b: B) -> CombineLatest<A, B>
+where
+    A: Stream,
+    B: Stream,
+    A::Item: Clone,
+    B::Item: Clone,
+{
+    CombineLatest {
+        a,
+        b,
+        latest_a: None,
+        latest_b: None,
+        a_closed: false,
+        b_closed: false,
+    }
+}
+
+/// Combines the latest values of several receivers, producing a flat tuple of snapshots.
+///
+/// ```rust
+/// use postage::{combine_latest, prelude::*, watch};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let (_a_tx, a) = watch::channel_with(1usize);
+///     let (_b_tx, b) = watch::channel_with('b');
+///     let (_c_tx, c) = watch::channel_with("c");
+///
+///     let mut combined = combine_latest!(a, b, c);
+///     assert_eq!(Some((1, 'b', "c")), combined.recv().await);
+/// }
+/// ```
+#[macro_export]
+macro_rules! combine_latest {
+    ($a:expr, $b:expr $(,)?) => {
+        $crate::watch::combine_latest($a, $b)
+    };
+    ($a:expr, $b:expr, $($rest:expr),+ $(,)?) => {
+        $crate::__combine_latest!($crate::watch::combine_latest($a, $b); (a, b); $($rest),+)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __combine_latest {
+    ($stream:expr; ($($v:ident),*);) => {
+        $stream
+    };
+    ($stream:expr; ($($v:ident),*); $next:expr $(, $rest:expr)*) => {
+        $crate::__combine_latest!(
+            $crate::stream::Stream::map(
+                $crate::watch::combine_latest($stream, $next),
+                |(($($v),*), next)| ($($v,)* next)
+            );
+            ($($v,)* next);
+            $($rest),*
+        )
+    };
+}
+
+/// A stream returned by `combine_latest`.
+#[pin_project]
+pub struct CombineLatest<A: Stream, B: Stream> {
+    #[pin]
+    a: A,
+    #[pin]
+    b: B,
+    latest_a: Option<A::Item>,
+    latest_b: Option<B::Item>,
+    a_closed: bool,
+    b_closed: bool,
+}
+
+impl<A, B> Stream for CombineLatest<A, B>
+where
+    A: Stream,
+    B: Stream,
+    A::Item: Clone,
+    B::Item: Clone,
+{
+    type Item = (A::Item, B::Item);
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut crate::Context<'_>) -> PollRecv<Self::Item> {
+        let this = self.project();
+        let mut changed = false;
+
+        if !*this.a_closed {
+            match this.a.poll_recv(cx) {
+                PollRecv::Ready(value) => {
+                    *this.latest_a = Some(value);
+                    changed = true;
+                }
+                PollRecv::Pending => {}
+                PollRecv::Closed => *this.a_closed = true,
+            }
+        }
+
+        if !*this.b_closed {
+            match this.b.poll_recv(cx) {
+                PollRecv::Ready(value) => {
+                    *this.latest_b = Some(value);
+                    changed = true;
+                }
+                PollRecv::Pending => {}
+                PollRecv::Closed => *this.b_closed = true,
+            }
+        }
+
+        match (this.latest_a.as_ref(), this.latest_b.as_ref()) {
+            (Some(a), Some(b)) if changed => return PollRecv::Ready((a.clone(), b.clone())),
+            (None, _) if *this.a_closed => return PollRecv::Closed,
+            (_, None) if *this.b_closed => return PollRecv::Closed,
+            _ => {}
+        }
+
+        if *this.a_closed && *this.b_closed {
+            return PollRecv::Closed;
+        }
+
+        if changed {
+            // an input produced a value without registering for the next, so poll again
+            if let Some(waker) = cx.waker() {
+                waker.wake_by_ref();
+            }
+        }
+
+        PollRecv::Pending
+    }
+}
+
+impl<A: Stream, B: Stream> fmt::Debug for CombineLatest<A, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CombineLatest")
+            .field("a_closed", &self.a_closed)
+            .field("b_closed", &self.b_closed)
+            .finish()
+    }
+}
+
+const EMPTY_BORROW: &str = "the watch channel has no value";
+
 struct StateExtension<T> {
     generation: AtomicUsize,
-    value: RwLock<T>,
+    value: RwLock<Option<T>>,
+    history: Option<History<T>>,
+    undelivered: Undelivered<T>,
 }
 
 impl<T> StateExtension<T> {
-    pub fn new(value: T) -> Self {
+    pub fn new(value: Option<T>, history: Option<History<T>>, undelivered: Undelivered<T>) -> Self {
+        if let (Some(history), Some(value)) = (&history, &value) {
+            history.record(0, value);
+        }
+
         Self {
             generation: AtomicUsize::new(0),
             value: RwLock::new(value),
+            history,
+            undelivered,
         }
     }
 
     pub fn push(&self, value: T) {
         let mut lock = self.value.write();
-        *lock = value;
+        *lock = Some(value);
 
         self.generation.fetch_add(1, Ordering::SeqCst);
+        if let Some(value) = lock.as_ref() {
+            self.record(value);
+        }
         drop(lock);
     }
 
+    /// Records the stored value in the history.  Must be called while the value is locked for writing.
+    pub fn record(&self, value: &T) {
+        if let Some(ref history) = self.history {
+            history.record(self.generation(Ordering::SeqCst), value);
+        }
+    }
+
+    /// The values stored at or after the given generation
+    pub fn changes(&self, since: usize) -> Changes<T>
+    where
+        T: Clone,
+    {
+        // the read lock prevents new values from being stored
+        let lock = self.value.read();
+        let generation = self.generation(Ordering::SeqCst);
+
+        let values = match (&self.history, lock.as_ref()) {
+            (Some(history), _) => history.since(since),
+            (None, Some(value)) if since <= generation => vec![value.clone()],
+            (None, _) => Vec::new(),
+        };
+
+        Changes { generation, values }
+    }
+
+    pub fn read(&self) -> Option<MappedRwLockReadGuard<'_, T>> {
+        RwLockReadGuard::try_map(self.value.read(), Option::as_ref).ok()
+    }
+
+    pub fn write(&self) -> Option<MappedRwLockWriteGuard<'_, T>> {
+        RwLockWriteGuard::try_map(self.value.write(), Option::as_mut).ok()
+    }
+
     pub fn increment(&self) {
         self.generation.fetch_add(1, Ordering::SeqCst);
     }
@@ -318,27 +887,70 @@ impl<T> StateExtension<T> {
     }
 }
 
+struct Changes<T> {
+    generation: usize,
+    values: Vec<T>,
+}
+
+/// The most recent values stored in the channel, tagged with their generation
+struct History<T> {
+    capacity: usize,
+    values: Mutex<VecDeque<(usize, T)>>,
+    // captured by the builder, so values can be recorded when a RefMut is released without a Clone bound
+    clone: fn(&T) -> T,
+}
+
+impl<T> History<T> {
+    pub fn new(capacity: usize) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            capacity,
+            values: Mutex::new(VecDeque::with_capacity(capacity)),
+            clone: T::clone,
+        }
+    }
+
+    pub fn record(&self, generation: usize, value: &T) {
+        let mut values = self.values.lock();
+        if values.len() == self.capacity {
+            values.pop_front();
+        }
+
+        values.push_back((generation, (self.clone)(value)));
+    }
+
+    pub fn since(&self, generation: usize) -> Vec<T> {
+        self.values
+            .lock()
+            .iter()
+            .filter(|(stored, _)| *stored >= generation)
+            .map(|(_, value)| (self.clone)(value))
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::{pin::Pin, task::Context};
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
 
-    use super::channel;
+    use super::{channel, channel_empty, channel_with, combine_latest, Builder};
     use crate::{
+        dead_letter::DeadLetterReason,
         sink::{PollSend, Sink},
         stream::{PollRecv, Stream},
         test::{noop_context, panic_context},
     };
-    use futures_test::task::new_count_waker;
+    use futures_test::task::{new_count_waker, noop_waker};
 
-    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[derive(Clone, Debug, Default, PartialEq, Eq)]
     struct State(usize);
 
-    impl Default for State {
-        fn default() -> Self {
-            State(0)
-        }
-    }
-
     #[test]
     fn send_accepted() {
         let mut cx = noop_context();
@@ -371,6 +983,67 @@ mod tests {
         assert_eq!(PollRecv::Pending, Pin::new(&mut rx).poll_recv(&mut cx));
     }
 
+    #[test]
+    fn builder_send_recv() {
+        let mut cx = noop_context();
+        let (mut tx, mut rx) = Builder::new().name("builder").build_with(State(1));
+
+        assert_eq!(
+            PollRecv::Ready(State(1)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, State(2))
+        );
+        assert_eq!(
+            PollRecv::Ready(State(2)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+    }
+
+    #[test]
+    fn size_hint() {
+        let mut cx = noop_context();
+        let (mut tx, mut rx) = channel();
+        assert_eq!((1, None), rx.size_hint());
+
+        assert_eq!(
+            PollRecv::Ready(State(0)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        assert_eq!((0, None), rx.size_hint());
+
+        // updates are coalesced into one value
+        for i in 1..=2 {
+            assert_eq!(
+                PollSend::Ready,
+                Pin::new(&mut tx).poll_send(&mut cx, State(i))
+            );
+        }
+        assert_eq!((1, None), rx.size_hint());
+
+        drop(tx);
+        assert_eq!((1, Some(1)), rx.size_hint());
+        assert_eq!(
+            PollRecv::Ready(State(2)),
+            Pin::new(&mut rx).poll_recv(&mut cx)
+        );
+        assert_eq!((0, Some(0)), rx.size_hint());
+    }
+
+    #[test]
+    fn size_hint_empty() {
+        let (mut tx, rx) = channel_empty::<State>();
+        assert_eq!((0, None), rx.size_hint());
+
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut noop_context(), State(1))
+        );
+        assert_eq!((1, None), rx.size_hint());
+    }
+
     #[test]
     fn recv_default() {
         let mut cx = panic_context();
@@ -474,6 +1147,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn dead_letter_rejected() {
+        let mut cx = noop_context();
+        let (dead_tx, mut dead_rx) = crate::mpsc::channel(1);
+        let (mut tx, rx) = Builder::new().dead_letter(dead_tx).build();
+
+        drop(rx);
+
+        // the value is returned to the sender, and a copy is forwarded to the dead-letter sink
+        assert_eq!(
+            PollSend::Rejected(State(1)),
+            Pin::new(&mut tx).poll_send(&mut cx, State(1))
+        );
+        assert_eq!(
+            PollSend::Rejected(State(2)),
+            Pin::new(&mut tx).poll_send(&mut cx, State(2))
+        );
+
+        let letter = dead_rx.try_recv().unwrap();
+        assert_eq!(State(1), letter.value);
+        assert_eq!(DeadLetterReason::Rejected, letter.reason);
+        assert!(dead_rx.try_recv().is_err());
+    }
+
     #[test]
     fn send_then_receiver_disconnect() {
         let mut cx = noop_context();
@@ -552,7 +1249,7 @@ mod tests {
     #[async_std::test]
     async fn subscribe_default() {
         let mut cx = panic_context();
-        let (mut tx, _rx) = channel();
+        let (tx, _rx) = channel();
         let mut rx2 = tx.subscribe();
 
         assert_eq!(
@@ -568,7 +1265,7 @@ mod tests {
     #[async_std::test]
     async fn subscribe_both_receive_value() {
         let mut cx = panic_context();
-        let (mut tx, mut rx) = channel();
+        let (tx, mut rx) = channel();
         let mut rx2 = tx.subscribe();
 
         assert_eq!(
@@ -589,6 +1286,344 @@ mod tests {
             Pin::new(&mut rx2).poll_recv(&mut noop_context())
         );
     }
+
+    #[test]
+    fn subscribe_shared_sender() {
+        let (tx, rx) = channel();
+        drop(rx);
+
+        let tx = std::sync::Arc::new(tx);
+        let mut rx = tx.subscribe();
+
+        assert_eq!(
+            PollRecv::Ready(State(0)),
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+
+        drop(tx);
+        assert_eq!(
+            PollRecv::Closed,
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+    }
+
+    #[test]
+    fn mark_changed() {
+        let (mut tx, mut rx) = channel();
+
+        assert_eq!(
+            PollRecv::Ready(State(0)),
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+        assert_eq!(
+            PollRecv::Pending,
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+
+        rx.mark_changed();
+        assert_eq!(
+            PollRecv::Ready(State(0)),
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut noop_context(), State(1))
+        );
+        rx.mark_changed();
+        assert_eq!(
+            PollRecv::Ready(State(1)),
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+        assert_eq!(
+            PollRecv::Pending,
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+    }
+
+    #[test]
+    fn history_take_changes() {
+        let (mut tx, mut rx) = Builder::new().history(8).build_with(State(0));
+
+        assert_eq!(
+            PollRecv::Ready(State(0)),
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut noop_context(), State(1))
+        );
+        tx.borrow_mut().0 = 2;
+
+        assert_eq!(vec![State(1), State(2)], rx.take_changes());
+        assert!(rx.take_changes().is_empty());
+        assert_eq!(
+            PollRecv::Pending,
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+
+        let mut clone = rx.clone();
+        assert_eq!(vec![State(0), State(1), State(2)], clone.take_changes());
+    }
+
+    #[test]
+    fn history_drops_oldest() {
+        let (mut tx, mut rx) = Builder::new().history(2).build_empty();
+
+        for i in 1..=4 {
+            assert_eq!(
+                PollSend::Ready,
+                Pin::new(&mut tx).poll_send(&mut noop_context(), State(i))
+            );
+        }
+
+        assert_eq!(vec![State(3), State(4)], rx.take_changes());
+    }
+
+    #[test]
+    fn take_changes_without_history() {
+        let (mut tx, mut rx) = channel();
+
+        assert_eq!(vec![State(0)], rx.take_changes());
+        assert!(rx.take_changes().is_empty());
+
+        for i in 1..=2 {
+            assert_eq!(
+                PollSend::Ready,
+                Pin::new(&mut tx).poll_send(&mut noop_context(), State(i))
+            );
+        }
+
+        assert_eq!(vec![State(2)], rx.take_changes());
+    }
+
+    #[test]
+    fn mark_unchanged() {
+        let (mut tx, mut rx) = channel();
+
+        rx.mark_unchanged();
+        assert_eq!(
+            PollRecv::Pending,
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut noop_context(), State(1))
+        );
+        rx.mark_unchanged();
+        assert_eq!(
+            PollRecv::Pending,
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut noop_context(), State(2))
+        );
+        assert_eq!(
+            PollRecv::Ready(State(2)),
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+    }
+
+    #[test]
+    fn combine_latest_seeds() {
+        let (mut a_tx, a) = channel_empty::<usize>();
+        let (mut b_tx, b) = channel_empty::<char>();
+        let mut combined = combine_latest(a, b);
+
+        assert_eq!(
+            PollRecv::Pending,
+            Pin::new(&mut combined).poll_recv(&mut noop_context())
+        );
+
+        Pin::new(&mut a_tx).poll_send(&mut noop_context(), 1);
+        assert_eq!(
+            PollRecv::Pending,
+            Pin::new(&mut combined).poll_recv(&mut noop_context())
+        );
+
+        Pin::new(&mut b_tx).poll_send(&mut noop_context(), 'b');
+        assert_eq!(
+            PollRecv::Ready((1, 'b')),
+            Pin::new(&mut combined).poll_recv(&mut noop_context())
+        );
+
+        Pin::new(&mut a_tx).poll_send(&mut noop_context(), 2);
+        assert_eq!(
+            PollRecv::Ready((2, 'b')),
+            Pin::new(&mut combined).poll_recv(&mut noop_context())
+        );
+        assert_eq!(
+            PollRecv::Pending,
+            Pin::new(&mut combined).poll_recv(&mut noop_context())
+        );
+    }
+
+    #[test]
+    fn combine_latest_closes() {
+        let (a_tx, a) = channel_with(1usize);
+        let (b_tx, b) = channel_empty::<usize>();
+        let mut combined = combine_latest(a, b);
+
+        drop(a_tx);
+        assert_eq!(
+            PollRecv::Pending,
+            Pin::new(&mut combined).poll_recv(&mut noop_context())
+        );
+
+        drop(b_tx);
+        assert_eq!(
+            PollRecv::Closed,
+            Pin::new(&mut combined).poll_recv(&mut noop_context())
+        );
+    }
+
+    #[test]
+    fn combine_latest_macro() {
+        let (_a_tx, a) = channel_with(1usize);
+        let (_b_tx, b) = channel_with('b');
+        let (_c_tx, c) = channel_with("c");
+        let (mut d_tx, d) = channel_with(4u8);
+        let mut combined = crate::combine_latest!(a, b, c, d);
+
+        assert_eq!(
+            PollRecv::Ready((1, 'b', "c", 4)),
+            Pin::new(&mut combined).poll_recv(&mut noop_context())
+        );
+
+        Pin::new(&mut d_tx).poll_send(&mut noop_context(), 5);
+        assert_eq!(
+            PollRecv::Ready((1, 'b', "c", 5)),
+            Pin::new(&mut combined).poll_recv(&mut noop_context())
+        );
+    }
+
+    #[test]
+    fn empty_waits_for_value() {
+        let (mut tx, mut rx) = channel_empty();
+
+        assert!(rx.try_borrow().is_none());
+        assert_eq!(
+            PollRecv::Pending,
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut noop_context(), State(1))
+        );
+        assert_eq!(State(1), *rx.borrow());
+        assert_eq!(
+            PollRecv::Ready(State(1)),
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+        assert_eq!(
+            PollRecv::Pending,
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+    }
+
+    #[test]
+    fn empty_closed() {
+        let (tx, mut rx) = channel_empty::<State>();
+        drop(tx);
+
+        assert_eq!(
+            PollRecv::Closed,
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn empty_borrow_panics() {
+        let (_tx, rx) = channel_empty::<State>();
+        rx.borrow();
+    }
+
+    #[test]
+    fn wait_for_current() {
+        let (_tx, mut rx) = channel_with(State(1));
+
+        let mut wait = rx.wait_for(|state| state.0 == 1);
+        match Pin::new(&mut wait).poll(&mut Context::from_waker(&noop_waker())) {
+            Poll::Ready(Some(state)) => assert_eq!(State(1), *state),
+            _ => panic!("wait_for should observe the current value"),
+        }
+        drop(wait);
+
+        assert_eq!(
+            PollRecv::Pending,
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+    }
+
+    #[test]
+    fn wait_for_update() {
+        let (mut tx, mut rx) = channel();
+        let (waker, count) = new_count_waker();
+        let mut std_cx = Context::from_waker(&waker);
+
+        let mut wait = rx.wait_for(|state: &State| state.0 == 2);
+        assert!(Pin::new(&mut wait).poll(&mut std_cx).is_pending());
+
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut noop_context(), State(1))
+        );
+        assert_eq!(1, count.get());
+        assert!(Pin::new(&mut wait).poll(&mut std_cx).is_pending());
+
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut noop_context(), State(2))
+        );
+        match Pin::new(&mut wait).poll(&mut std_cx) {
+            Poll::Ready(Some(state)) => assert_eq!(State(2), *state),
+            _ => panic!("wait_for should observe the update"),
+        };
+    }
+
+    #[test]
+    fn wait_for_closed() {
+        let (mut tx, mut rx) = channel();
+
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut noop_context(), State(1))
+        );
+        drop(tx);
+
+        let mut wait = rx.wait_for(|state: &State| state.0 == 1);
+        assert!(matches!(
+            Pin::new(&mut wait).poll(&mut Context::from_waker(&noop_waker())),
+            Poll::Ready(Some(_))
+        ));
+        drop(wait);
+
+        let mut wait = rx.wait_for(|state: &State| state.0 == 2);
+        assert!(matches!(
+            Pin::new(&mut wait).poll(&mut Context::from_waker(&noop_waker())),
+            Poll::Ready(None)
+        ));
+    }
+
+    #[test]
+    fn is_closed() {
+        let (tx, rx) = channel::<State>();
+        assert!(!tx.is_closed());
+        assert!(!rx.is_closed());
+
+        drop(tx);
+        assert!(rx.is_closed());
+
+        let (tx, rx) = channel::<State>();
+        drop(rx);
+        assert!(tx.is_closed());
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,443 @@
+//! Typed messages over byte-oriented sinks and streams.
+//!
+//! An [Encoder](./trait.Encoder.html) writes messages into a byte buffer, and a [Decoder](./trait.Decoder.html) reads
+//! messages from the bytes received so far.  [encode](./fn.encode.html) adapts a `Sink<Item = Bytes>` into a sink of typed messages,
+//! and [decode](./fn.decode.html) adapts a `Stream<Item = Bytes>` into a stream of decoded messages.  The byte stream does not need to
+//! preserve frame boundaries, so it can be fed with arbitrary chunks read from a socket.
+//!
+//! Built-in codecs:
+//! - [JsonLines](./struct.JsonLines.html), newline-delimited JSON.  Requires the `json` feature.
+//! - [Bincode](./struct.Bincode.html), length-prefixed bincode.  Requires the `bincode` feature.
+//!
+//! Requires the `codec` feature.
+//!
+//! ```rust
+//! use bytes::{Bytes, BytesMut};
+//! use postage::{codec::{self, Decoder, Encoder}, mpsc, prelude::*};
+//!
+//! /// Transmits each `u32` as four little-endian bytes
+//! struct U32Codec;
+//!
+//! impl Encoder<u32> for U32Codec {
+//!     type Error = std::convert::Infallible;
+//!
+//!     fn encode(&mut self, item: &u32, dst: &mut BytesMut) -> Result<(), Self::Error> {
+//!         dst.extend_from_slice(&item.to_le_bytes());
+//!         Ok(())
+//!     }
+//! }
+//!
+//! impl Decoder for U32Codec {
+//!     type Item = u32;
+//!     type Error = std::convert::Infallible;
+//!
+//!     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<u32>, Self::Error> {
+//!         if src.len() < 4 {
+//!             return Ok(None);
+//!         }
+//!
+//!         let bytes = src.split_to(4);
+//!         Ok(Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])))
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let (tx, rx) = mpsc::channel::<Bytes>(4);
+//!     let mut tx = codec::encode(tx, U32Codec);
+//!     let mut rx = codec::decode(rx, U32Codec);
+//!
+//!     tx.send(7).await.ok();
+//!     assert_eq!(Some(Ok(7)), rx.recv().await);
+//! }
+//! ```
+
+use std::{fmt, pin::Pin};
+
+use bytes::{Bytes, BytesMut};
+use pin_project::pin_project;
+
+use crate::{
+    sink::{BufferedSink, PollReady, PollSend, Sink},
+    stream::{PollRecv, Stream},
+    Context,
+};
+
+#[cfg(feature = "bincode")]
+mod bincode;
+#[cfg(feature = "json")]
+mod json;
+
+#[cfg(feature = "bincode")]
+pub use self::bincode::Bincode;
+#[cfg(feature = "json")]
+pub use self::json::JsonLines;
+
+/// Writes messages of type `T` into a byte buffer.
+pub trait Encoder<T> {
+    /// The error produced when a message cannot be encoded
+    type Error;
+
+    /// Appends the encoded message to the buffer
+    fn encode(&mut self, item: &T, dst: &mut BytesMut) -> Result<(), Self::Error>;
+}
+
+/// Reads messages from a buffer of received bytes.
+pub trait Decoder {
+    /// The decoded message
+    type Item;
+
+    /// The error produced when the bytes cannot be decoded
+    type Error;
+
+    /// Decodes a message from the front of the buffer, removing its bytes.
+    /// Returns `Ok(None)` if the buffer does not yet contain a complete message.
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error>;
+
+    /// Called when the byte stream has closed, with the bytes that remain.  By default, calls `decode`.
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        self.decode(src)
+    }
+}
+
+/// Wraps a byte sink, encoding each message with the encoder
+pub fn encode<S, E, T>(sink: S, encoder: E) -> EncodeSink<S, E, T>
+where
+    S: Sink<Item = Bytes>,
+    E: Encoder<T>,
+{
+    EncodeSink {
+        sink,
+        encoder,
+        buffer: BytesMut::new(),
+        error: None,
+    }
+}
+
+/// Wraps a byte stream, decoding messages with the decoder
+pub fn decode<S, D>(stream: S, decoder: D) -> DecodeStream<S, D>
+where
+    S: Stream<Item = Bytes>,
+    D: Decoder,
+{
+    DecodeStream {
+        stream,
+        decoder,
+        buffer: BytesMut::new(),
+        closed: false,
+    }
+}
+
+/// A sink returned by `codec::encode`.
+///
+/// If a message cannot be encoded, it is rejected, and the error can be retrieved with `take_error`.
+#[pin_project]
+pub struct EncodeSink<S, E, T>
+where
+    E: Encoder<T>,
+{
+    #[pin]
+    sink: S,
+    encoder: E,
+    buffer: BytesMut,
+    error: Option<E::Error>,
+}
+
+impl<S, E, T> EncodeSink<S, E, T>
+where
+    E: Encoder<T>,
+{
+    /// Takes the error which caused the most recent message to be rejected, if encoding failed
+    pub fn take_error(&mut self) -> Option<E::Error> {
+        self.error.take()
+    }
+
+    /// Returns a reference to the encoder
+    pub fn encoder(&self) -> &E {
+        &self.encoder
+    }
+
+    /// Returns a reference to the byte sink
+    pub fn get_ref(&self) -> &S {
+        &self.sink
+    }
+
+    /// Returns a mutable reference to the byte sink
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    /// Consumes the adapter, returning the byte sink
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+}
+
+impl<S, E, T> Sink for EncodeSink<S, E, T>
+where
+    S: Sink<Item = Bytes>,
+    E: Encoder<T>,
+{
+    type Item = T;
+
+    fn poll_send(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        value: Self::Item,
+    ) -> PollSend<Self::Item> {
+        let this = self.project();
+
+        // the message is encoded on each attempt, so it can be returned if the byte sink is full
+        this.buffer.clear();
+        if let Err(e) = this.encoder.encode(&value, this.buffer) {
+            *this.error = Some(e);
+            return PollSend::Rejected(value);
+        }
+
+        let frame = this.buffer.split().freeze();
+        match this.sink.poll_send(cx, frame) {
+            PollSend::Ready => PollSend::Ready,
+            PollSend::Pending(_) => PollSend::Pending(value),
+            PollSend::Rejected(_) => PollSend::Rejected(value),
+        }
+    }
+}
+
+impl<S, E, T> BufferedSink for EncodeSink<S, E, T>
+where
+    S: BufferedSink<Item = Bytes>,
+    E: Encoder<T>,
+{
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        self.project().sink.poll_ready(cx)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        self.project().sink.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        self.project().sink.poll_close(cx)
+    }
+}
+
+impl<S, E, T> fmt::Debug for EncodeSink<S, E, T>
+where
+    S: fmt::Debug,
+    E: Encoder<T>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncodeSink")
+            .field("sink", &self.sink)
+            .finish()
+    }
+}
+
+/// A stream returned by `codec::decode`.
+///
+/// Produces `Ok` for each decoded message, and `Err` if the bytes cannot be decoded.
+/// Decoding continues after an error, so the decoder can decide whether to skip the invalid bytes.
+#[pin_project]
+pub struct DecodeStream<S, D> {
+    #[pin]
+    stream: S,
+    decoder: D,
+    buffer: BytesMut,
+    closed: bool,
+}
+
+impl<S, D> DecodeStream<S, D> {
+    /// Returns a reference to the decoder
+    pub fn decoder(&self) -> &D {
+        &self.decoder
+    }
+
+    /// The received bytes which have not yet been decoded
+    pub fn buffered(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Returns a reference to the byte stream
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Returns a mutable reference to the byte stream
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Consumes the adapter, returning the byte stream.  Bytes which have not been decoded are discarded.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S, D> Stream for DecodeStream<S, D>
+where
+    S: Stream<Item = Bytes>,
+    D: Decoder,
+{
+    type Item = Result<D::Item, D::Error>;
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item> {
+        let mut this = self.project();
+
+        loop {
+            if *this.closed {
+                if this.buffer.is_empty() {
+                    return PollRecv::Closed;
+                }
+
+                return match this.decoder.decode_eof(this.buffer) {
+                    Ok(Some(item)) => PollRecv::Ready(Ok(item)),
+                    Ok(None) => {
+                        // the remaining bytes can't form a message
+                        this.buffer.clear();
+                        PollRecv::Closed
+                    }
+                    Err(e) => {
+                        this.buffer.clear();
+                        PollRecv::Ready(Err(e))
+                    }
+                };
+            }
+
+            match this.decoder.decode(this.buffer) {
+                Ok(Some(item)) => return PollRecv::Ready(Ok(item)),
+                Ok(None) => {}
+                Err(e) => return PollRecv::Ready(Err(e)),
+            }
+
+            match this.stream.as_mut().poll_recv(cx) {
+                PollRecv::Ready(bytes) => this.buffer.extend_from_slice(&bytes),
+                PollRecv::Pending => return PollRecv::Pending,
+                PollRecv::Closed => *this.closed = true,
+            }
+        }
+    }
+}
+
+impl<S, D> fmt::Debug for DecodeStream<S, D>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DecodeStream")
+            .field("stream", &self.stream)
+            .field("buffered", &self.buffer.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{convert::Infallible, pin::Pin};
+
+    use bytes::{Bytes, BytesMut};
+
+    use super::{decode, encode, Decoder, Encoder};
+    use crate::{
+        mpsc,
+        sink::{PollSend, Sink},
+        stream::{PollRecv, Stream, TryRecvError},
+        test::noop_context,
+    };
+
+    /// Encodes strings as a length byte, followed by the utf-8 bytes
+    struct ShortString;
+
+    impl Encoder<String> for ShortString {
+        type Error = &'static str;
+
+        fn encode(&mut self, item: &String, dst: &mut BytesMut) -> Result<(), Self::Error> {
+            if item.len() > u8::MAX as usize {
+                return Err("too long");
+            }
+
+            dst.extend_from_slice(&[item.len() as u8]);
+            dst.extend_from_slice(item.as_bytes());
+            Ok(())
+        }
+    }
+
+    impl Decoder for ShortString {
+        type Item = String;
+        type Error = Infallible;
+
+        fn decode(&mut self, src: &mut BytesMut) -> Result<Option<String>, Self::Error> {
+            let len = match src.first() {
+                Some(len) => *len as usize,
+                None => return Ok(None),
+            };
+
+            if src.len() <= len {
+                return Ok(None);
+            }
+
+            let frame = src.split_to(len + 1);
+            Ok(Some(String::from_utf8_lossy(&frame[1..]).into_owned()))
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let (tx, rx) = mpsc::channel::<Bytes>(4);
+        let mut tx = encode(tx, ShortString);
+        let mut rx = decode(rx, ShortString);
+
+        assert_eq!(Ok(()), tx.try_send("hello".to_string()));
+        assert_eq!(Ok(()), tx.try_send("world".to_string()));
+        assert_eq!(Ok(Ok("hello".to_string())), rx.try_recv());
+        assert_eq!(Ok(Ok("world".to_string())), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Pending), rx.try_recv());
+    }
+
+    #[test]
+    fn split_chunks() {
+        let (mut tx, rx) = mpsc::channel::<Bytes>(4);
+        let mut rx = decode(rx, ShortString);
+
+        tx.try_send(Bytes::from_static(b"\x02a")).unwrap();
+        assert_eq!(Err(TryRecvError::Pending), rx.try_recv());
+        assert_eq!(b"\x02a", rx.buffered());
+
+        tx.try_send(Bytes::from_static(b"b\x01c")).unwrap();
+        assert_eq!(Ok(Ok("ab".to_string())), rx.try_recv());
+        assert_eq!(Ok(Ok("c".to_string())), rx.try_recv());
+
+        tx.try_send(Bytes::from_static(b"\x05")).unwrap();
+        drop(tx);
+        assert_eq!(
+            PollRecv::Closed,
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+    }
+
+    #[test]
+    fn encode_error() {
+        let (tx, _rx) = mpsc::channel::<Bytes>(4);
+        let mut tx = encode(tx, ShortString);
+
+        let long = "x".repeat(300);
+        assert_eq!(
+            PollSend::Rejected(long.clone()),
+            Pin::new(&mut tx).poll_send(&mut noop_context(), long)
+        );
+        assert_eq!(Some("too long"), tx.take_error());
+    }
+
+    #[test]
+    fn full_sink() {
+        let (tx, mut rx) = mpsc::channel::<Bytes>(1);
+        let mut tx = encode(tx, ShortString);
+
+        assert_eq!(Ok(()), tx.try_send("a".to_string()));
+        assert_eq!(
+            PollSend::Pending("b".to_string()),
+            Pin::new(&mut tx).poll_send(&mut noop_context(), "b".to_string())
+        );
+
+        assert_eq!(Ok(Bytes::from_static(b"\x01a")), rx.try_recv());
+    }
+}
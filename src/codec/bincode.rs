@@ -0,0 +1,170 @@
+use std::{fmt, marker::PhantomData};
+
+use bytes::{Buf, BufMut, BytesMut};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{Decoder, Encoder};
+
+const LENGTH_PREFIX: usize = 4;
+
+/// A codec for bincode messages, each prefixed with its length as a little-endian `u32`.
+///
+/// Requires the `bincode` feature.
+pub struct Bincode<T> {
+    max_length: usize,
+    _t: PhantomData<fn(T) -> T>,
+}
+
+impl<T> Bincode<T> {
+    /// Creates a bincode codec, which accepts frames of up to 8 MiB
+    pub fn new() -> Self {
+        Self::with_max_length(8 * 1024 * 1024)
+    }
+
+    /// Creates a bincode codec, which rejects frames longer than `max_length` bytes
+    pub fn with_max_length(max_length: usize) -> Self {
+        Self {
+            max_length: max_length.min(u32::MAX as usize),
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for Bincode<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for Bincode<T> {
+    fn clone(&self) -> Self {
+        Self::with_max_length(self.max_length)
+    }
+}
+
+impl<T> fmt::Debug for Bincode<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Bincode")
+            .field("max_length", &self.max_length)
+            .finish()
+    }
+}
+
+fn frame_too_long(length: usize, max_length: usize) -> ::bincode::Error {
+    Box::new(::bincode::ErrorKind::Custom(format!(
+        "the frame length ({}) exceeds the maximum length ({})",
+        length, max_length
+    )))
+}
+
+impl<T> Encoder<T> for Bincode<T>
+where
+    T: Serialize,
+{
+    type Error = ::bincode::Error;
+
+    fn encode(&mut self, item: &T, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let length = ::bincode::serialized_size(item)? as usize;
+        if length > self.max_length {
+            return Err(frame_too_long(length, self.max_length));
+        }
+
+        dst.reserve(LENGTH_PREFIX + length);
+        dst.put_u32_le(length as u32);
+        ::bincode::serialize_into(dst.writer(), item)
+    }
+}
+
+impl<T> Decoder for Bincode<T>
+where
+    T: DeserializeOwned,
+{
+    type Item = T;
+    type Error = ::bincode::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if src.len() < LENGTH_PREFIX {
+            return Ok(None);
+        }
+
+        let length = u32::from_le_bytes([src[0], src[1], src[2], src[3]]) as usize;
+        if length > self.max_length {
+            // the stream can't be resynchronized, so the buffered bytes are discarded
+            src.clear();
+            return Err(frame_too_long(length, self.max_length));
+        }
+
+        if src.len() < LENGTH_PREFIX + length {
+            src.reserve(LENGTH_PREFIX + length - src.len());
+            return Ok(None);
+        }
+
+        src.advance(LENGTH_PREFIX);
+        let frame = src.split_to(length);
+        ::bincode::deserialize(&frame).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::{Bytes, BytesMut};
+    use serde::{Deserialize, Serialize};
+
+    use super::Bincode;
+    use crate::{
+        codec::{decode, encode, Decoder, Encoder},
+        mpsc,
+        sink::Sink,
+        stream::{Stream, TryRecvError},
+    };
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Event {
+        id: usize,
+        name: String,
+    }
+
+    #[test]
+    fn channel() {
+        let (tx, rx) = mpsc::channel::<Bytes>(4);
+        let mut tx = encode(tx, Bincode::new());
+        let mut rx = decode(rx, Bincode::<Event>::new());
+
+        let event = Event {
+            id: 1,
+            name: "created".into(),
+        };
+        tx.try_send(event).unwrap();
+        assert_eq!(
+            Event {
+                id: 1,
+                name: "created".into()
+            },
+            rx.try_recv().unwrap().unwrap()
+        );
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Pending)));
+    }
+
+    #[test]
+    fn partial_frame() {
+        let mut codec = Bincode::<u64>::new();
+        let mut buffer = BytesMut::new();
+        codec.encode(&7, &mut buffer).unwrap();
+
+        let mut partial = buffer.split_to(6);
+        assert!(codec.decode(&mut partial).unwrap().is_none());
+        partial.extend_from_slice(&buffer);
+        assert_eq!(Some(7), codec.decode(&mut partial).unwrap());
+    }
+
+    #[test]
+    fn max_length() {
+        let mut codec = Bincode::<String>::with_max_length(4);
+        let mut buffer = BytesMut::new();
+        assert!(codec.encode(&"too long".to_string(), &mut buffer).is_err());
+
+        buffer.extend_from_slice(&100u32.to_le_bytes());
+        assert!(codec.decode(&mut buffer).is_err());
+        assert!(buffer.is_empty());
+    }
+}
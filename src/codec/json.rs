@@ -0,0 +1,147 @@
+use std::{fmt, marker::PhantomData};
+
+use bytes::{Buf, BytesMut};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{Decoder, Encoder};
+
+/// A codec for newline-delimited JSON.  Each message is serialized on a single line, and blank lines are ignored.
+///
+/// Requires the `json` feature.
+pub struct JsonLines<T> {
+    _t: PhantomData<fn(T) -> T>,
+}
+
+impl<T> JsonLines<T> {
+    /// Creates a JSON-lines codec
+    pub fn new() -> Self {
+        Self { _t: PhantomData }
+    }
+}
+
+impl<T> Default for JsonLines<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for JsonLines<T> {
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for JsonLines<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonLines").finish()
+    }
+}
+
+impl<T> Encoder<T> for JsonLines<T>
+where
+    T: Serialize,
+{
+    type Error = serde_json::Error;
+
+    fn encode(&mut self, item: &T, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let line = serde_json::to_vec(item)?;
+        dst.reserve(line.len() + 1);
+        dst.extend_from_slice(&line);
+        dst.extend_from_slice(b"\n");
+        Ok(())
+    }
+}
+
+impl<T> Decoder for JsonLines<T>
+where
+    T: DeserializeOwned,
+{
+    type Item = T;
+    type Error = serde_json::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        while let Some(end) = src.iter().position(|b| *b == b'\n') {
+            let line = src.split_to(end + 1);
+            let line = &line[..end];
+
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+
+            return serde_json::from_slice(line).map(Some);
+        }
+
+        Ok(None)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if let Some(item) = self.decode(src)? {
+            return Ok(Some(item));
+        }
+
+        // the final line may not have a trailing newline
+        let line = src.split();
+        if line.iter().all(u8::is_ascii_whitespace) {
+            return Ok(None);
+        }
+
+        serde_json::from_slice(line.chunk()).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::{Bytes, BytesMut};
+    use serde::{Deserialize, Serialize};
+
+    use super::JsonLines;
+    use crate::{
+        codec::{decode, encode, Decoder, Encoder},
+        mpsc,
+        sink::Sink,
+        stream::{Stream, TryRecvError},
+    };
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Event {
+        id: usize,
+    }
+
+    #[test]
+    fn lines() {
+        let mut codec = JsonLines::<Event>::new();
+        let mut buffer = BytesMut::new();
+        codec.encode(&Event { id: 1 }, &mut buffer).unwrap();
+        assert_eq!(&b"{\"id\":1}\n"[..], &buffer[..]);
+
+        buffer.extend_from_slice(b"\n{\"id\":2}");
+        assert_eq!(Event { id: 1 }, codec.decode(&mut buffer).unwrap().unwrap());
+        assert!(codec.decode(&mut buffer).unwrap().is_none());
+        assert_eq!(
+            Event { id: 2 },
+            codec.decode_eof(&mut buffer).unwrap().unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid_line() {
+        let (mut tx, rx) = mpsc::channel::<Bytes>(4);
+        let mut rx = decode(rx, JsonLines::<Event>::new());
+
+        tx.try_send(Bytes::from_static(b"nope\n{\"id\":3}\n"))
+            .unwrap();
+        assert!(rx.try_recv().unwrap().is_err());
+        assert_eq!(Event { id: 3 }, rx.try_recv().unwrap().unwrap());
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Pending)));
+    }
+
+    #[test]
+    fn channel() {
+        let (tx, rx) = mpsc::channel::<Bytes>(4);
+        let mut tx = encode(tx, JsonLines::new());
+        let mut rx = decode(rx, JsonLines::<Event>::new());
+
+        tx.try_send(Event { id: 4 }).unwrap();
+        assert_eq!(Event { id: 4 }, rx.try_recv().unwrap().unwrap());
+    }
+}
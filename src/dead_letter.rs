@@ -0,0 +1,292 @@
+//! Dead-letter sinks and drop hooks, which receive messages that a channel accepted but never delivered.
+//!
+//! A dead-letter sink can be attached to `mpsc`, `dispatch`, and `credit` channels with their `Builder`.
+//! Messages which are still buffered when the channel is torn down are forwarded to the sink,
+//! tagged with [DeadLetterReason::Undelivered](./enum.DeadLetterReason.html#variant.Undelivered).
+//! Messages dropped because they outlived the channel's time-to-live are tagged with `DeadLetterReason::Expired`,
+//! messages discarded by `Receiver::clear` are tagged with `DeadLetterReason::Cleared`,
+//! and messages discarded by an mpsc [Overflow](../mpsc/enum.Overflow.html) policy are tagged with `DeadLetterReason::Overflowed`.
+//! A [key router](../router/fn.by_key.html) forwards messages which have no open route, tagged with `DeadLetterReason::Unrouted`.
+//!
+//! If the sink is full or closed, or no sink is attached, the message is passed to the `on_drop` hook configured with the `Builder`,
+//! which can release resources held by the message.
+//!
+//! Messages sent to a closed channel are always returned to the sender, in `PollSend::Rejected` or `SendError`, so producers observe the closure.
+//! `broadcast` and `watch` channels accept a sink, and forward a copy of each rejected message, tagged with `DeadLetterReason::Rejected`.
+//! For `mpsc`, `dispatch`, and `credit` channels, copies are forwarded if the channel is built with `dead_letter_rejected`.
+//! Copies are only forwarded to the sink, and are not passed to the `on_drop` hook.
+//!
+//! The hooks are called without holding a lock, so they may send to the channel.  Messages released while a hook is running
+//! are forwarded once it returns.
+//!
+//! ```rust
+//! use postage::{dead_letter::{DeadLetter, DeadLetterReason}, mpsc, sink::Sink, stream::Stream};
+//!
+//! let (dead_tx, mut dead_rx) = mpsc::channel(16);
+//! let (mut tx, rx) = mpsc::Builder::new(4).dead_letter(dead_tx).build();
+//!
+//! tx.try_send(1usize).ok();
+//! drop(rx);
+//! drop(tx);
+//!
+//! let letter = dead_rx.try_recv().unwrap();
+//! assert_eq!(1, letter.value);
+//! assert_eq!(DeadLetterReason::Undelivered, letter.reason);
+//! ```
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use parking_lot::Mutex;
+
+use crate::{
+    sink::{PollSend, Sink},
+    Context,
+};
+
+/// A message which was not delivered by a channel, and the reason it was not delivered.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeadLetter<T> {
+    /// The message
+    pub value: T,
+    /// Why the message was not delivered
+    pub reason: DeadLetterReason,
+}
+
+/// The reason a message was forwarded to a dead-letter sink.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DeadLetterReason {
+    /// The channel was closed when the message was sent
+    Rejected,
+    /// The message was still in the channel buffer when the channel was torn down
+    Undelivered,
+    /// The message was older than the channel's time-to-live when it reached the front of the buffer
+    Expired,
+    /// The message was rejected, or timed out, on every attempt allowed by a [RetryPolicy](../sink/struct.RetryPolicy.html)
+    Exhausted,
+    /// The message was discarded by `Receiver::clear`
+    Cleared,
+    /// The message was discarded by the channel's overflow policy, because the buffer was full
+    Overflowed,
+    /// The message had no open route in a [key router](../router/fn.by_key.html)
+    Unrouted,
+}
+
+/// A type-erased dead-letter sink.
+pub(crate) struct DeadLetterSink<T> {
+    pub forward: Box<dyn FnMut(DeadLetter<T>) -> Result<(), T> + Send>,
+}
+
+impl<T> DeadLetterSink<T> {
+    pub fn new<S>(sink: S) -> Self
+    where
+        S: Sink<Item = DeadLetter<T>> + Send + 'static,
+    {
+        let mut sink = Box::pin(sink);
+        let forward = move |letter| match sink.as_mut().poll_send(&mut Context::empty(), letter) {
+            PollSend::Ready => Ok(()),
+            PollSend::Pending(letter) | PollSend::Rejected(letter) => Err(letter.value),
+        };
+
+        Self {
+            forward: Box::new(forward),
+        }
+    }
+}
+
+/// Handles messages which remain in a channel buffer when the channel is torn down.
+///
+/// Messages are forwarded to the dead-letter sink without blocking.  If there is no sink,
+/// or the sink is full or closed, the message is passed to the `on_drop` hook.
+///
+/// The hooks are called without holding the lock, so a hook can send to the channel which released the message.
+/// While one caller holds the hooks, messages released by other callers are queued, and forwarded by the holder.
+pub(crate) struct Undelivered<T> {
+    inner: Mutex<UndeliveredState<T>>,
+}
+
+struct UndeliveredState<T> {
+    // taken by the caller which is running the hooks
+    hooks: Option<UndeliveredHooks<T>>,
+    pending: VecDeque<(T, DeadLetterReason)>,
+    installed: bool,
+    // copies messages which are rejected by a closed channel, as the original is returned to the sender
+    copy_rejected: Option<fn(&T) -> T>,
+}
+
+struct UndeliveredHooks<T> {
+    dead_letter: Option<DeadLetterSink<T>>,
+    on_drop: Option<Box<dyn FnMut(T) + Send>>,
+}
+
+impl<T> UndeliveredHooks<T> {
+    fn forward(&mut self, value: T, reason: DeadLetterReason) -> Result<(), T> {
+        match self.dead_letter {
+            Some(ref mut dead_letter) => (dead_letter.forward)(DeadLetter { value, reason }),
+            None => Err(value),
+        }
+    }
+
+    fn release(&mut self, value: T, reason: DeadLetterReason) {
+        if let Err(value) = self.forward(value, reason) {
+            // a rejected message is a copy, and the sender holds the original
+            if reason == DeadLetterReason::Rejected {
+                return;
+            }
+
+            if let Some(ref mut on_drop) = self.on_drop {
+                on_drop(value);
+            }
+        }
+    }
+}
+
+impl<T> Undelivered<T> {
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(UndeliveredState {
+                hooks: Some(UndeliveredHooks {
+                    dead_letter: None,
+                    on_drop: None,
+                }),
+                pending: VecDeque::new(),
+                installed: false,
+                copy_rejected: None,
+            }),
+        }
+    }
+
+    pub fn set_dead_letter<S>(&mut self, sink: S)
+    where
+        S: Sink<Item = DeadLetter<T>> + Send + 'static,
+    {
+        let state = self.inner.get_mut();
+        state.installed = true;
+        if let Some(ref mut hooks) = state.hooks {
+            hooks.dead_letter = Some(DeadLetterSink::new(sink));
+        }
+    }
+
+    /// Sets the function which copies each message rejected by a closed channel, so the copy can be forwarded to the dead-letter sink
+    pub fn set_copy_rejected(&mut self, copy: Option<fn(&T) -> T>) {
+        self.inner.get_mut().copy_rejected = copy;
+    }
+
+    pub fn set_on_drop<F>(&mut self, on_drop: F)
+    where
+        F: FnMut(T) + Send + 'static,
+    {
+        let state = self.inner.get_mut();
+        state.installed = true;
+        if let Some(ref mut hooks) = state.hooks {
+            hooks.on_drop = Some(Box::new(on_drop));
+        }
+    }
+
+    /// Returns true if a hook has been installed
+    pub fn is_some(&self) -> bool {
+        self.inner.lock().installed
+    }
+
+    pub fn release(&self, value: T, reason: DeadLetterReason) {
+        let hooks = {
+            let mut state = self.inner.lock();
+            if !state.installed {
+                return;
+            }
+
+            match state.hooks.take() {
+                Some(hooks) => hooks,
+                None => {
+                    state.pending.push_back((value, reason));
+                    return;
+                }
+            }
+        };
+
+        let mut hooks = RestoreHooks::new(self, hooks);
+        hooks.release(value, reason);
+        hooks.finish();
+    }
+
+    /// Forwards a copy of a message which was rejected because the channel is closed to the dead-letter sink,
+    /// tagged with `DeadLetterReason::Rejected`, if copies are enabled.
+    ///
+    /// Returns the message, so it can be returned to the sender.  If another caller holds the hooks,
+    /// the copy is queued and forwarded by that caller.
+    pub fn reject(&self, value: T) -> T {
+        let copy = match self.inner.lock().copy_rejected {
+            Some(copy) => copy,
+            None => return value,
+        };
+
+        self.release(copy(&value), DeadLetterReason::Rejected);
+        value
+    }
+}
+
+/// Returns the hooks to the channel when the caller has finished with them, even if a hook panics
+struct RestoreHooks<'u, T> {
+    undelivered: &'u Undelivered<T>,
+    hooks: Option<UndeliveredHooks<T>>,
+}
+
+impl<'u, T> RestoreHooks<'u, T> {
+    fn new(undelivered: &'u Undelivered<T>, hooks: UndeliveredHooks<T>) -> Self {
+        Self {
+            undelivered,
+            hooks: Some(hooks),
+        }
+    }
+
+    /// Forwards the messages which were queued by other callers while the hooks were held, and returns the hooks
+    fn finish(mut self) {
+        loop {
+            let mut state = self.undelivered.inner.lock();
+            match state.pending.pop_front() {
+                Some((value, reason)) => {
+                    drop(state);
+                    self.release(value, reason);
+                }
+                None => {
+                    state.hooks = self.hooks.take();
+                    return;
+                }
+            }
+        }
+    }
+}
+
+impl<'u, T> Deref for RestoreHooks<'u, T> {
+    type Target = UndeliveredHooks<T>;
+
+    fn deref(&self) -> &Self::Target {
+        self.hooks.as_ref().unwrap()
+    }
+}
+
+impl<'u, T> DerefMut for RestoreHooks<'u, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.hooks.as_mut().unwrap()
+    }
+}
+
+impl<'u, T> Drop for RestoreHooks<'u, T> {
+    fn drop(&mut self) {
+        if let Some(hooks) = self.hooks.take() {
+            self.undelivered.inner.lock().hooks = Some(hooks);
+        }
+    }
+}
+
+impl<T> fmt::Debug for Undelivered<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Undelivered")
+            .field("installed", &self.is_some())
+            .finish()
+    }
+}
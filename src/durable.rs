@@ -0,0 +1,770 @@
+//! A persistent, file-backed multi-producer, single-consumer channel.
+//!
+//! Messages are encoded with a [codec](../codec/index.html), and appended to a segmented log in a directory as they are sent.
+//! The receiver delivers messages from a cursor, which is persisted in the same directory as each message is received.
+//! When the channel is reopened after a restart, undelivered messages are delivered from the persisted cursor,
+//! and segments which have been fully consumed are deleted.
+//!
+//! The cursor is advanced when a message is received, so a message which was received but not processed before a crash is not redelivered.
+//! A directory should be opened by one channel at a time.
+//!
+//! Requires the `durable` feature.
+//!
+//! ```rust
+//! # #[cfg(feature = "json")]
+//! # {
+//! use postage::{codec::JsonLines, durable, prelude::*};
+//!
+//! let dir = std::env::temp_dir().join(format!("postage-durable-doc-{}", std::process::id()));
+//!
+//! {
+//!     let (mut tx, _rx) = durable::open::<String, _>(&dir, JsonLines::new()).unwrap();
+//!     tx.try_send("written before the restart".to_string()).ok();
+//! }
+//!
+//! let (_tx, mut rx) = durable::open::<String, _>(&dir, JsonLines::new()).unwrap();
+//! assert_eq!("written before the restart", rx.try_recv().unwrap().unwrap());
+//! # std::fs::remove_dir_all(&dir).ok();
+//! # }
+//! ```
+
+use std::{
+    error::Error,
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, Read, Seek, SeekFrom, Write},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+};
+
+use bytes::{BufMut, BytesMut};
+use parking_lot::Mutex;
+
+use crate::{
+    codec::{Decoder, Encoder},
+    sink::{PollSend, Sink},
+    stream::{PollRecv, Stream},
+    sync::notifier::Notifier,
+    Context,
+};
+
+const LENGTH_PREFIX: u64 = 4;
+const SEGMENT_EXTENSION: &str = "log";
+const CURSOR_FILE: &str = "cursor";
+const CURSOR_TEMP_FILE: &str = "cursor.tmp";
+
+/// Opens the channel in the directory, with 64 MiB segments.  The directory is created if it does not exist.
+pub fn open<T, C>(dir: impl AsRef<Path>, codec: C) -> io::Result<(Sender<T, C>, Receiver<T, C>)>
+where
+    C: Encoder<T> + Decoder<Item = T> + Clone,
+{
+    Builder::new(dir).open(codec)
+}
+
+/// Opens a durable channel, with additional configuration.
+#[derive(Clone, Debug)]
+pub struct Builder {
+    dir: PathBuf,
+    segment_size: u64,
+    sync: bool,
+}
+
+impl Builder {
+    /// Creates a builder for a channel stored in the directory
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            segment_size: 64 * 1024 * 1024,
+            sync: false,
+        }
+    }
+
+    /// Sets the size in bytes at which the log rolls over to a new segment
+    pub fn segment_size(mut self, bytes: u64) -> Self {
+        self.segment_size = bytes;
+        self
+    }
+
+    /// Syncs each message to disk before the send completes.  By default, messages are written to the OS,
+    /// which preserves them if the process crashes, but not if the machine loses power.
+    pub fn sync(mut self, sync: bool) -> Self {
+        self.sync = sync;
+        self
+    }
+
+    /// Opens the channel, recovering the messages and cursor which were persisted in the directory
+    pub fn open<T, C>(self, codec: C) -> io::Result<(Sender<T, C>, Receiver<T, C>)>
+    where
+        C: Encoder<T> + Decoder<Item = T> + Clone,
+    {
+        fs::create_dir_all(&self.dir)?;
+
+        let mut segments = list_segments(&self.dir)?;
+        let mut cursor = read_cursor(&self.dir)?.unwrap_or(0);
+
+        // segments which end at or before the cursor have been consumed
+        while segments.len() > 1 && segments[1] <= cursor {
+            fs::remove_file(segment_path(&self.dir, segments[0]))?;
+            segments.remove(0);
+        }
+
+        let (active_start, file, active_len, count) = match segments.last() {
+            Some(start) => {
+                let path = segment_path(&self.dir, *start);
+                let (len, count) = scan_segment(&path)?;
+                let file = OpenOptions::new().read(true).write(true).open(&path)?;
+
+                // discard a record which was partially written when the process stopped
+                file.set_len(len)?;
+                (*start, file, len, count)
+            }
+            None => {
+                let file = create_segment(&self.dir, cursor)?;
+                segments.push(cursor);
+                (cursor, file, 0, 0)
+            }
+        };
+
+        let head = active_start + count;
+        cursor = cursor.clamp(segments[0], head);
+
+        let shared = Arc::new(Shared {
+            log: Mutex::new(Log {
+                dir: self.dir.clone(),
+                segment_size: self.segment_size,
+                sync: self.sync,
+                encoder: codec.clone(),
+                buffer: BytesMut::new(),
+                file,
+                active_start,
+                active_len,
+                head,
+                senders: 1,
+            }),
+            notify_rx: Notifier::new(),
+        });
+
+        let sender = Sender {
+            shared: shared.clone(),
+            error: None,
+            _t: PhantomData,
+        };
+
+        let receiver = Receiver {
+            shared,
+            dir: self.dir,
+            decoder: codec,
+            cursor,
+            reader: None,
+            _t: PhantomData,
+        };
+
+        Ok((sender, receiver))
+    }
+}
+
+struct Shared<C> {
+    log: Mutex<Log<C>>,
+    notify_rx: Notifier,
+}
+
+struct Log<C> {
+    dir: PathBuf,
+    segment_size: u64,
+    sync: bool,
+    encoder: C,
+    buffer: BytesMut,
+    file: File,
+    active_start: u64,
+    active_len: u64,
+    head: u64,
+    senders: usize,
+}
+
+impl<C> Log<C> {
+    fn append<T>(&mut self, value: &T) -> io::Result<()>
+    where
+        C: Encoder<T>,
+        <C as Encoder<T>>::Error: Into<Box<dyn Error + Send + Sync>>,
+    {
+        self.buffer.clear();
+        self.buffer.put_u32_le(0);
+        self.encoder
+            .encode(value, &mut self.buffer)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let length = self.buffer.len() as u64 - LENGTH_PREFIX;
+        if length > u32::MAX as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the encoded message is longer than u32::MAX bytes",
+            ));
+        }
+        self.buffer[..4].copy_from_slice(&(length as u32).to_le_bytes());
+
+        if self.active_len > 0 && self.active_len + self.buffer.len() as u64 > self.segment_size {
+            self.file = create_segment(&self.dir, self.head)?;
+            self.active_start = self.head;
+            self.active_len = 0;
+        }
+
+        self.file.seek(SeekFrom::Start(self.active_len))?;
+        if let Err(e) = self.write_record() {
+            // remove the partial record, so later records are readable
+            self.file.set_len(self.active_len).ok();
+            return Err(e);
+        }
+
+        self.active_len += self.buffer.len() as u64;
+        self.head += 1;
+        Ok(())
+    }
+
+    fn write_record(&mut self) -> io::Result<()> {
+        self.file.write_all(&self.buffer)?;
+
+        if self.sync {
+            self.file.sync_data()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The sender half of a durable channel.  Can send messages with the postage::Sink trait.
+///
+/// Sends complete when the message has been written to the log.  If the message cannot be encoded or written,
+/// it is rejected, and the error can be retrieved with `take_error`.
+///
+/// Can be cloned.
+pub struct Sender<T, C> {
+    shared: Arc<Shared<C>>,
+    error: Option<io::Error>,
+    _t: PhantomData<fn(T)>,
+}
+
+impl<T, C> Sender<T, C> {
+    /// Takes the error which caused the most recent message to be rejected
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+}
+
+impl<T, C> Sink for Sender<T, C>
+where
+    C: Encoder<T>,
+    <C as Encoder<T>>::Error: Into<Box<dyn Error + Send + Sync>>,
+{
+    type Item = T;
+
+    fn poll_send(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        value: Self::Item,
+    ) -> PollSend<Self::Item> {
+        let this = self.get_mut();
+        let result = this.shared.log.lock().append(&value);
+
+        match result {
+            Ok(()) => {
+                this.shared.notify_rx.notify();
+                PollSend::Ready
+            }
+            Err(e) => {
+                this.error = Some(e);
+                PollSend::Rejected(value)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "futures-traits")]
+mod impl_futures {
+    use std::{error::Error, task::Poll};
+
+    use crate::{
+        codec::Encoder,
+        sink::{PollSend, SendError, Sink},
+        Context,
+    };
+
+    impl<T, C> futures_sink::Sink<T> for super::Sender<T, C>
+    where
+        C: Encoder<T>,
+        <C as Encoder<T>>::Error: Into<Box<dyn Error + Send + Sync>>,
+    {
+        type Error = SendError<T>;
+
+        /// Messages are never refused for lack of capacity, so the sender is always ready
+        fn poll_ready(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn start_send(self: std::pin::Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+            match self.poll_send(&mut Context::empty(), item) {
+                PollSend::Ready => Ok(()),
+                PollSend::Pending(item) | PollSend::Rejected(item) => Err(SendError(item)),
+            }
+        }
+
+        fn poll_flush(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+}
+
+impl<T, C> Clone for Sender<T, C> {
+    fn clone(&self) -> Self {
+        self.shared.log.lock().senders += 1;
+
+        Self {
+            shared: self.shared.clone(),
+            error: None,
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<T, C> Drop for Sender<T, C> {
+    fn drop(&mut self) {
+        let mut log = self.shared.log.lock();
+        log.senders -= 1;
+
+        if log.senders == 0 {
+            drop(log);
+            self.shared.notify_rx.notify();
+        }
+    }
+}
+
+impl<T, C> fmt::Debug for Sender<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender").finish()
+    }
+}
+
+/// The receiver half of a durable channel.  Cannot be cloned.
+///
+/// Receives messages with the postage::Stream trait.  Produces an error if a message cannot be read or decoded,
+/// and closes when all senders have been dropped and every message has been received.
+pub struct Receiver<T, C> {
+    shared: Arc<Shared<C>>,
+    dir: PathBuf,
+    decoder: C,
+    cursor: u64,
+    reader: Option<SegmentReader>,
+    _t: PhantomData<fn() -> T>,
+}
+
+struct SegmentReader {
+    start: u64,
+    offset: u64,
+    file: BufReader<File>,
+}
+
+impl<T, C> Receiver<T, C> {
+    /// The offset of the next message to be received
+    pub fn cursor(&self) -> u64 {
+        self.cursor
+    }
+
+    /// The number of messages which have been written, and not yet received
+    pub fn len(&self) -> usize {
+        (self.shared.log.lock().head - self.cursor) as usize
+    }
+
+    /// Returns true if every message has been received
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Opens a reader positioned at the cursor
+    fn open_reader(&mut self) -> io::Result<()> {
+        let segments = list_segments(&self.dir)?;
+        let start = segments
+            .iter()
+            .rev()
+            .find(|start| **start <= self.cursor)
+            .copied()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "the segment was deleted"))?;
+
+        if let Some(previous) = self.reader.take() {
+            if previous.start < start {
+                fs::remove_file(segment_path(&self.dir, previous.start))?;
+            }
+        }
+
+        let mut reader = SegmentReader {
+            start,
+            offset: start,
+            file: BufReader::new(File::open(segment_path(&self.dir, start))?),
+        };
+
+        while reader.offset < self.cursor {
+            let length = read_length(&mut reader.file)?.ok_or_else(truncated)?;
+            reader.file.seek_relative(length as i64)?;
+            reader.offset += 1;
+        }
+
+        self.reader = Some(reader);
+        Ok(())
+    }
+
+    /// Reads the record at the cursor, which has been fully written
+    fn read_record(&mut self) -> io::Result<BytesMut> {
+        loop {
+            if self.reader.as_ref().map(|reader| reader.offset) != Some(self.cursor) {
+                self.open_reader()?;
+            }
+
+            let reader = self.reader.as_mut().unwrap();
+            match read_length(&mut reader.file)? {
+                Some(length) => {
+                    let mut record = BytesMut::zeroed(length as usize);
+                    reader.file.read_exact(&mut record)?;
+                    reader.offset += 1;
+                    return Ok(record);
+                }
+                // the segment has been read to the end, and the record is in the next segment
+                None => reader.offset = u64::MAX,
+            }
+        }
+    }
+
+    fn recv_record(&mut self) -> io::Result<T>
+    where
+        C: Decoder<Item = T>,
+        C::Error: Into<Box<dyn Error + Send + Sync>>,
+    {
+        let result = self.read_record().and_then(|mut record| {
+            self.decoder
+                .decode_eof(&mut record)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+                .ok_or_else(truncated)
+        });
+
+        // a corrupt record is skipped, so the receiver can make progress
+        if let Err(ref e) = result {
+            if e.kind() != io::ErrorKind::InvalidData {
+                self.reader = None;
+                return result;
+            }
+        }
+
+        write_cursor(&self.dir, self.cursor + 1)?;
+        self.cursor += 1;
+        result
+    }
+}
+
+impl<T, C> Stream for Receiver<T, C>
+where
+    C: Decoder<Item = T>,
+    C::Error: Into<Box<dyn Error + Send + Sync>>,
+{
+    type Item = io::Result<T>;
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item> {
+        let this = self.get_mut();
+
+        loop {
+            let guard = this.shared.notify_rx.guard();
+            let (head, closed) = {
+                let log = this.shared.log.lock();
+                (log.head, log.senders == 0)
+            };
+
+            if this.cursor < head {
+                return PollRecv::Ready(this.recv_record());
+            }
+
+            if closed {
+                return PollRecv::Closed;
+            }
+
+            this.shared.notify_rx.subscribe(cx);
+
+            if guard.is_expired() {
+                continue;
+            }
+
+            return PollRecv::Pending;
+        }
+    }
+}
+
+impl<T, C> Unpin for Receiver<T, C> {}
+
+impl<T, C> fmt::Debug for Receiver<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("dir", &self.dir)
+            .field("cursor", &self.cursor)
+            .finish()
+    }
+}
+
+fn truncated() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "the record is truncated")
+}
+
+fn segment_path(dir: &Path, start: u64) -> PathBuf {
+    dir.join(format!("{:020}.{}", start, SEGMENT_EXTENSION))
+}
+
+fn create_segment(dir: &Path, start: u64) -> io::Result<File> {
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(segment_path(dir, start))
+}
+
+/// Returns the starting offsets of the segments in the directory, in order
+fn list_segments(dir: &Path) -> io::Result<Vec<u64>> {
+    let mut segments = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(SEGMENT_EXTENSION) {
+            continue;
+        }
+
+        if let Some(start) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.parse().ok())
+        {
+            segments.push(start);
+        }
+    }
+
+    segments.sort_unstable();
+    Ok(segments)
+}
+
+/// Returns the length of the complete records in the segment, and the number of records
+fn scan_segment(path: &Path) -> io::Result<(u64, u64)> {
+    let mut file = BufReader::new(File::open(path)?);
+    let file_len = file.get_ref().metadata()?.len();
+    let mut len = 0;
+    let mut count = 0;
+
+    while let Some(length) = read_length(&mut file)? {
+        let end = len + LENGTH_PREFIX + length as u64;
+        if end > file_len {
+            break;
+        }
+
+        file.seek_relative(length as i64)?;
+        len = end;
+        count += 1;
+    }
+
+    Ok((len, count))
+}
+
+/// Reads a length prefix, returning `None` at the end of the file or if the prefix is incomplete
+fn read_length(file: &mut impl Read) -> io::Result<Option<u32>> {
+    let mut prefix = [0u8; LENGTH_PREFIX as usize];
+    let mut read = 0;
+
+    while read < prefix.len() {
+        match file.read(&mut prefix[read..]) {
+            Ok(0) => return Ok(None),
+            Ok(n) => read += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(Some(u32::from_le_bytes(prefix)))
+}
+
+fn read_cursor(dir: &Path) -> io::Result<Option<u64>> {
+    match fs::read_to_string(dir.join(CURSOR_FILE)) {
+        Ok(cursor) => cursor
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Replaces the cursor file, so a crash leaves either the old or the new cursor
+fn write_cursor(dir: &Path, cursor: u64) -> io::Result<()> {
+    let temp = dir.join(CURSOR_TEMP_FILE);
+    fs::write(&temp, cursor.to_string())?;
+    fs::rename(temp, dir.join(CURSOR_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        convert::Infallible,
+        fs,
+        io::Write,
+        path::PathBuf,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use bytes::{Buf, BytesMut};
+
+    use super::{list_segments, open, segment_path, Builder};
+    use crate::{
+        codec::{Decoder, Encoder},
+        sink::Sink,
+        stream::{Stream, TryRecvError},
+    };
+
+    /// Encodes each u64 as 8 little-endian bytes
+    #[derive(Clone)]
+    struct U64Codec;
+
+    impl Encoder<u64> for U64Codec {
+        type Error = Infallible;
+
+        fn encode(&mut self, item: &u64, dst: &mut BytesMut) -> Result<(), Self::Error> {
+            dst.extend_from_slice(&item.to_le_bytes());
+            Ok(())
+        }
+    }
+
+    impl Decoder for U64Codec {
+        type Item = u64;
+        type Error = Infallible;
+
+        fn decode(&mut self, src: &mut BytesMut) -> Result<Option<u64>, Self::Error> {
+            if src.len() < 8 {
+                return Ok(None);
+            }
+
+            Ok(Some(src.get_u64_le()))
+        }
+    }
+
+    /// A directory which is removed when the test completes
+    struct TestDir(PathBuf);
+
+    impl TestDir {
+        fn new() -> Self {
+            static NEXT: AtomicUsize = AtomicUsize::new(0);
+            let id = NEXT.fetch_add(1, Ordering::Relaxed);
+            let path =
+                std::env::temp_dir().join(format!("postage-durable-{}-{}", std::process::id(), id));
+
+            fs::remove_dir_all(&path).ok();
+            Self(path)
+        }
+    }
+
+    impl Drop for TestDir {
+        fn drop(&mut self) {
+            fs::remove_dir_all(&self.0).ok();
+        }
+    }
+
+    #[test]
+    fn send_recv() {
+        let dir = TestDir::new();
+        let (mut tx, mut rx) = open(&dir.0, U64Codec).unwrap();
+
+        tx.try_send(1u64).unwrap();
+        tx.try_send(2).unwrap();
+        assert_eq!(2, rx.len());
+        assert_eq!(1, rx.try_recv().unwrap().unwrap());
+        assert_eq!(2, rx.try_recv().unwrap().unwrap());
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Pending)));
+
+        drop(tx);
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Closed)));
+    }
+
+    #[test]
+    fn resume() {
+        let dir = TestDir::new();
+
+        {
+            let (mut tx, mut rx) = open(&dir.0, U64Codec).unwrap();
+            for i in 0..4u64 {
+                tx.try_send(i).unwrap();
+            }
+
+            assert_eq!(0, rx.try_recv().unwrap().unwrap());
+        }
+
+        let (mut tx, mut rx) = open(&dir.0, U64Codec).unwrap();
+        assert_eq!(1, rx.cursor());
+        tx.try_send(4).unwrap();
+
+        for i in 1..5u64 {
+            assert_eq!(i, rx.try_recv().unwrap().unwrap());
+        }
+        assert!(rx.is_empty());
+    }
+
+    #[test]
+    fn segments() {
+        let dir = TestDir::new();
+        let (mut tx, mut rx) = Builder::new(&dir.0)
+            .segment_size(24)
+            .open(U64Codec)
+            .unwrap();
+
+        for i in 0..6u64 {
+            tx.try_send(i).unwrap();
+        }
+        assert_eq!(vec![0, 2, 4], list_segments(&dir.0).unwrap());
+
+        for i in 0..5u64 {
+            assert_eq!(i, rx.try_recv().unwrap().unwrap());
+        }
+        assert_eq!(vec![4], list_segments(&dir.0).unwrap());
+
+        drop((tx, rx));
+        let (_tx, mut rx) = Builder::new(&dir.0)
+            .segment_size(24)
+            .open(U64Codec)
+            .unwrap();
+        assert_eq!(5, rx.try_recv().unwrap().unwrap());
+    }
+
+    #[test]
+    fn torn_write() {
+        let dir = TestDir::new();
+
+        {
+            let (mut tx, _rx) = open(&dir.0, U64Codec).unwrap();
+            tx.try_send(1u64).unwrap();
+        }
+
+        let mut segment = fs::OpenOptions::new()
+            .append(true)
+            .open(segment_path(&dir.0, 0))
+            .unwrap();
+        segment.write_all(&[8, 0, 0, 0, 1, 2]).unwrap();
+        drop(segment);
+
+        let (mut tx, mut rx) = open(&dir.0, U64Codec).unwrap();
+        tx.try_send(2).unwrap();
+        assert_eq!(1, rx.try_recv().unwrap().unwrap());
+        assert_eq!(2, rx.try_recv().unwrap().unwrap());
+    }
+}
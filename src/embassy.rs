@@ -0,0 +1,605 @@
+//! Channels for embedded executors such as [embassy](https://embassy.dev), which can be placed in a `static`.
+//!
+//! The channels are constructed with a `const fn`, and store their messages inline, so sending and receiving never allocates.
+//! The endpoints borrow the channel, so a channel in a `static` produces `'static` endpoints, which can be passed to spawned tasks.
+//! Endpoints are `Copy`, and implement the postage `Sink` and `Stream` traits.
+//!
+//! The channel is locked with an `embassy_sync` [RawMutex](./trait.RawMutex.html), chosen by the caller.  `CriticalSectionRawMutex`
+//! shares the channel with interrupt handlers, and `NoopRawMutex` is for channels used within a single executor.
+//! Other `embassy_sync` mutexes, such as `ThreadModeRawMutex`, can also be used.  Parked tasks are tracked with `embassy_sync` waker registrations, so each side of the channel
+//! holds one waker.  If several tasks wait on the same side, a newly registered task wakes the previous one, so it can register again.
+//!
+//! Static channels never close, as the channel outlives its endpoints.
+//!
+//! The module only uses `core` types, but postage itself links `std`.  The channels can be used with embassy's `std` and `wasm` executors,
+//! or to share code between firmware and a host-side simulation.
+//!
+//! ```rust
+//! use postage::{
+//!     embassy::{Channel, CriticalSectionRawMutex},
+//!     prelude::*,
+//! };
+//!
+//! static EVENTS: Channel<CriticalSectionRawMutex, u32, 4> = Channel::new();
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut tx = EVENTS.sender();
+//!     let mut rx = EVENTS.receiver();
+//!
+//!     tx.send(1).await.ok();
+//!     assert_eq!(Some(1), rx.recv().await);
+//! }
+//! ```
+
+use core::{cell::RefCell, fmt, pin::Pin};
+
+use embassy_sync::{blocking_mutex::Mutex, waitqueue::WakerRegistration};
+use heapless::Deque;
+
+pub use embassy_sync::blocking_mutex::raw::{CriticalSectionRawMutex, NoopRawMutex, RawMutex};
+
+use crate::{
+    sink::{PollSend, Sink},
+    stream::{PollRecv, Stream},
+    Context,
+};
+
+/// A bounded multi-producer, multi-consumer queue with capacity `N`, which can be placed in a `static`.
+pub struct Channel<M: RawMutex, T, const N: usize> {
+    state: Mutex<M, RefCell<ChannelState<T, N>>>,
+}
+
+struct ChannelState<T, const N: usize> {
+    queue: Deque<T, N>,
+    senders: WakerRegistration,
+    receivers: WakerRegistration,
+}
+
+impl<M: RawMutex, T, const N: usize> Channel<M, T, N> {
+    /// Creates an empty channel.  Can be used to initialize a `static`.
+    pub const fn new() -> Self {
+        Self {
+            state: Mutex::new(RefCell::new(ChannelState {
+                queue: Deque::new(),
+                senders: WakerRegistration::new(),
+                receivers: WakerRegistration::new(),
+            })),
+        }
+    }
+
+    /// Returns a sender for the channel.  Senders can be copied.
+    pub fn sender(&self) -> Sender<'_, M, T, N> {
+        Sender { channel: self }
+    }
+
+    /// Returns a receiver for the channel.  Receivers can be copied, and each message is taken by one receiver.
+    pub fn receiver(&self) -> Receiver<'_, M, T, N> {
+        Receiver { channel: self }
+    }
+
+    /// Attempts to buffer the message, or returns it if the channel is full
+    pub fn try_send(&self, value: T) -> Result<(), T> {
+        self.poll_send(&Context::empty(), value)
+    }
+
+    /// Attempts to take a message from the buffer
+    pub fn try_recv(&self) -> Option<T> {
+        self.poll_recv(&Context::empty())
+    }
+
+    /// The number of messages waiting in the buffer
+    pub fn len(&self) -> usize {
+        self.lock(|state| state.queue.len())
+    }
+
+    /// Returns true if no messages are waiting in the buffer
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if the buffer is full, and senders must wait
+    pub fn is_full(&self) -> bool {
+        self.len() == N
+    }
+
+    /// The number of messages the buffer can hold
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    fn lock<R>(&self, f: impl FnOnce(&mut ChannelState<T, N>) -> R) -> R {
+        self.state.lock(|state| f(&mut state.borrow_mut()))
+    }
+
+    fn poll_send(&self, cx: &Context<'_>, value: T) -> Result<(), T> {
+        self.lock(|state| match state.queue.push_back(value) {
+            Ok(()) => {
+                state.receivers.wake();
+                Ok(())
+            }
+            Err(value) => {
+                if let Some(waker) = cx.waker() {
+                    state.senders.register(waker);
+                }
+
+                Err(value)
+            }
+        })
+    }
+
+    fn poll_recv(&self, cx: &Context<'_>) -> Option<T> {
+        self.lock(|state| match state.queue.pop_front() {
+            Some(value) => {
+                state.senders.wake();
+                Some(value)
+            }
+            None => {
+                if let Some(waker) = cx.waker() {
+                    state.receivers.register(waker);
+                }
+
+                None
+            }
+        })
+    }
+}
+
+impl<M: RawMutex, T, const N: usize> Default for Channel<M, T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: RawMutex, T, const N: usize> fmt::Debug for Channel<M, T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Channel")
+            .field("len", &self.len())
+            .field("capacity", &N)
+            .finish()
+    }
+}
+
+/// The sender half of a static channel.  Can send messages with the `postage::Sink` trait.
+///
+/// Can be copied.
+pub struct Sender<'ch, M: RawMutex, T, const N: usize> {
+    channel: &'ch Channel<M, T, N>,
+}
+
+impl<M: RawMutex, T, const N: usize> Clone for Sender<'_, M, T, N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M: RawMutex, T, const N: usize> Copy for Sender<'_, M, T, N> {}
+
+impl<M: RawMutex, T, const N: usize> Sink for Sender<'_, M, T, N> {
+    type Item = T;
+
+    fn poll_send(self: Pin<&mut Self>, cx: &mut Context<'_>, value: T) -> PollSend<T> {
+        match self.channel.poll_send(cx, value) {
+            Ok(()) => PollSend::Ready,
+            Err(value) => PollSend::Pending(value),
+        }
+    }
+}
+
+impl<M: RawMutex, T, const N: usize> fmt::Debug for Sender<'_, M, T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender").finish()
+    }
+}
+
+/// The receiver half of a static channel.  Can receive messages with the `postage::Stream` trait.
+///
+/// Can be copied.
+pub struct Receiver<'ch, M: RawMutex, T, const N: usize> {
+    channel: &'ch Channel<M, T, N>,
+}
+
+impl<M: RawMutex, T, const N: usize> Clone for Receiver<'_, M, T, N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M: RawMutex, T, const N: usize> Copy for Receiver<'_, M, T, N> {}
+
+impl<M: RawMutex, T, const N: usize> Stream for Receiver<'_, M, T, N> {
+    type Item = T;
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<T> {
+        match self.channel.poll_recv(cx) {
+            Some(value) => PollRecv::Ready(value),
+            None => PollRecv::Pending,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // other receivers may take the buffered messages, and the channel never closes
+        (0, None)
+    }
+}
+
+impl<M: RawMutex, T, const N: usize> fmt::Debug for Receiver<'_, M, T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver").finish()
+    }
+}
+
+/// A state distribution channel which holds the latest value, and can be placed in a `static`.
+///
+/// Sending overwrites the value, and never waits.  Receiving takes the value, so each value is observed by one receiver,
+/// and a slow receiver observes the latest value.
+pub struct Signal<M: RawMutex, T> {
+    state: Mutex<M, RefCell<SignalState<T>>>,
+}
+
+struct SignalState<T> {
+    value: Option<T>,
+    receivers: WakerRegistration,
+}
+
+impl<M: RawMutex, T> Signal<M, T> {
+    /// Creates a signal with no value.  Can be used to initialize a `static`.
+    pub const fn new() -> Self {
+        Self {
+            state: Mutex::new(RefCell::new(SignalState {
+                value: None,
+                receivers: WakerRegistration::new(),
+            })),
+        }
+    }
+
+    /// Returns a sender for the signal.  Senders can be copied.
+    pub fn sender(&self) -> SignalSender<'_, M, T> {
+        SignalSender { signal: self }
+    }
+
+    /// Returns a receiver for the signal.  Receivers can be copied.
+    pub fn receiver(&self) -> SignalReceiver<'_, M, T> {
+        SignalReceiver { signal: self }
+    }
+
+    /// Stores the value, replacing a value which has not been received, and wakes the receiver
+    pub fn signal(&self, value: T) {
+        self.lock(|state| {
+            state.value = Some(value);
+            state.receivers.wake();
+        })
+    }
+
+    /// Takes the value, if one has been stored
+    pub fn try_take(&self) -> Option<T> {
+        self.lock(|state| state.value.take())
+    }
+
+    /// Discards the stored value
+    pub fn reset(&self) {
+        self.lock(|state| state.value = None)
+    }
+
+    /// Returns true if a value is stored, and has not been received
+    pub fn is_signaled(&self) -> bool {
+        self.lock(|state| state.value.is_some())
+    }
+
+    fn lock<R>(&self, f: impl FnOnce(&mut SignalState<T>) -> R) -> R {
+        self.state.lock(|state| f(&mut state.borrow_mut()))
+    }
+}
+
+impl<M: RawMutex, T> Default for Signal<M, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: RawMutex, T> fmt::Debug for Signal<M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Signal")
+            .field("signaled", &self.is_signaled())
+            .finish()
+    }
+}
+
+/// The sender half of a signal.  Can send values with the `postage::Sink` trait.
+///
+/// Can be copied.
+pub struct SignalSender<'s, M: RawMutex, T> {
+    signal: &'s Signal<M, T>,
+}
+
+impl<M: RawMutex, T> Clone for SignalSender<'_, M, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M: RawMutex, T> Copy for SignalSender<'_, M, T> {}
+
+impl<M: RawMutex, T> Sink for SignalSender<'_, M, T> {
+    type Item = T;
+
+    fn poll_send(self: Pin<&mut Self>, _cx: &mut Context<'_>, value: T) -> PollSend<T> {
+        self.signal.signal(value);
+        PollSend::Ready
+    }
+}
+
+impl<M: RawMutex, T> fmt::Debug for SignalSender<'_, M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignalSender").finish()
+    }
+}
+
+/// The receiver half of a signal.  Can receive values with the `postage::Stream` trait.
+///
+/// Can be copied.
+pub struct SignalReceiver<'s, M: RawMutex, T> {
+    signal: &'s Signal<M, T>,
+}
+
+impl<M: RawMutex, T> Clone for SignalReceiver<'_, M, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M: RawMutex, T> Copy for SignalReceiver<'_, M, T> {}
+
+impl<M: RawMutex, T> Stream for SignalReceiver<'_, M, T> {
+    type Item = T;
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<T> {
+        self.signal.lock(|state| match state.value.take() {
+            Some(value) => PollRecv::Ready(value),
+            None => {
+                if let Some(waker) = cx.waker() {
+                    state.receivers.register(waker);
+                }
+
+                PollRecv::Pending
+            }
+        })
+    }
+}
+
+impl<M: RawMutex, T> fmt::Debug for SignalReceiver<'_, M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignalReceiver").finish()
+    }
+}
+
+#[cfg(feature = "futures-traits")]
+mod impl_futures {
+    use core::{pin::Pin, task::Poll};
+
+    use super::{RawMutex, Sender, SignalSender};
+    use crate::sink::SendError;
+
+    impl<M: RawMutex, T, const N: usize> futures_sink::Sink<T> for Sender<'_, M, T, N> {
+        type Error = SendError<T>;
+
+        fn poll_ready(
+            self: Pin<&mut Self>,
+            cx: &mut core::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            self.channel.lock(|state| {
+                if state.queue.is_full() {
+                    state.senders.register(cx.waker());
+                    Poll::Pending
+                } else {
+                    Poll::Ready(Ok(()))
+                }
+            })
+        }
+
+        fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+            self.channel.try_send(item).map_err(SendError)
+        }
+
+        fn poll_flush(
+            self: Pin<&mut Self>,
+            _cx: &mut core::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(
+            self: Pin<&mut Self>,
+            _cx: &mut core::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    impl<M: RawMutex, T> futures_sink::Sink<T> for SignalSender<'_, M, T> {
+        type Error = SendError<T>;
+
+        fn poll_ready(
+            self: Pin<&mut Self>,
+            _cx: &mut core::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+            self.signal.signal(item);
+            Ok(())
+        }
+
+        fn poll_flush(
+            self: Pin<&mut Self>,
+            _cx: &mut core::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(
+            self: Pin<&mut Self>,
+            _cx: &mut core::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+
+    use futures_test::task::new_count_waker;
+
+    use super::{Channel, CriticalSectionRawMutex, NoopRawMutex, Receiver, Sender, Signal};
+    use crate::{
+        sink::{PollSend, Sink},
+        stream::{PollRecv, Stream, TryRecvError},
+        test::noop_context,
+        Context,
+    };
+
+    static CHANNEL: Channel<CriticalSectionRawMutex, usize, 2> = Channel::new();
+    static SIGNAL: Signal<CriticalSectionRawMutex, usize> = Signal::new();
+
+    fn is_static<T: Send + Sync + 'static>(_value: T) {}
+
+    #[test]
+    fn static_endpoints() {
+        is_static(CHANNEL.sender());
+        is_static(CHANNEL.receiver());
+        is_static(SIGNAL.sender());
+        is_static(SIGNAL.receiver());
+    }
+
+    #[test]
+    fn send_recv() {
+        let mut cx = noop_context();
+        let channel: Channel<NoopRawMutex, usize, 2> = Channel::new();
+        let mut tx = channel.sender();
+        let mut rx = channel.receiver();
+
+        assert_eq!(PollSend::Ready, Pin::new(&mut tx).poll_send(&mut cx, 1));
+        assert_eq!(PollSend::Ready, Pin::new(&mut tx).poll_send(&mut cx, 2));
+        assert_eq!(
+            PollSend::Pending(3),
+            Pin::new(&mut tx).poll_send(&mut cx, 3)
+        );
+        assert!(channel.is_full());
+
+        assert_eq!(PollRecv::Ready(1), Pin::new(&mut rx).poll_recv(&mut cx));
+        assert_eq!(PollRecv::Ready(2), Pin::new(&mut rx).poll_recv(&mut cx));
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx).poll_recv(&mut cx));
+        assert!(channel.is_empty());
+    }
+
+    #[test]
+    fn copied_endpoints() {
+        let channel: Channel<NoopRawMutex, usize, 4> = Channel::new();
+        let mut tx: Sender<'_, _, _, 4> = channel.sender();
+        let mut rx: Receiver<'_, _, _, 4> = channel.receiver();
+        let (mut tx2, mut rx2) = (tx, rx);
+
+        tx.try_send(1).unwrap();
+        tx2.try_send(2).unwrap();
+        assert_eq!(2, channel.len());
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(Ok(2), rx2.try_recv());
+        assert_eq!(Err(TryRecvError::Pending), rx.try_recv());
+    }
+
+    #[test]
+    fn wake_receiver() {
+        let (waker, count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker);
+        let channel: Channel<NoopRawMutex, usize, 2> = Channel::new();
+        let mut rx = channel.receiver();
+
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx).poll_recv(&mut cx));
+        assert_eq!(0, count.get());
+
+        channel.try_send(1).unwrap();
+        assert_eq!(1, count.get());
+        assert_eq!(PollRecv::Ready(1), Pin::new(&mut rx).poll_recv(&mut cx));
+    }
+
+    #[test]
+    fn wake_sender() {
+        let (waker, count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker);
+        let channel: Channel<NoopRawMutex, usize, 1> = Channel::new();
+        let mut tx = channel.sender();
+
+        assert_eq!(PollSend::Ready, Pin::new(&mut tx).poll_send(&mut cx, 1));
+        assert_eq!(
+            PollSend::Pending(2),
+            Pin::new(&mut tx).poll_send(&mut cx, 2)
+        );
+        assert_eq!(0, count.get());
+
+        assert_eq!(Some(1), channel.try_recv());
+        assert_eq!(1, count.get());
+        assert_eq!(PollSend::Ready, Pin::new(&mut tx).poll_send(&mut cx, 2));
+    }
+
+    #[test]
+    fn try_does_not_register() {
+        let (waker, count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker);
+        let channel: Channel<NoopRawMutex, usize, 1> = Channel::new();
+        let mut rx = channel.receiver();
+
+        // try_recv leaves the waiting task registered
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx).poll_recv(&mut cx));
+        assert_eq!(None, channel.try_recv());
+        assert_eq!(0, count.get());
+
+        channel.try_send(1).unwrap();
+        assert_eq!(1, count.get());
+    }
+
+    #[test]
+    fn signal_overwrites() {
+        let (waker, count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker);
+        let signal: Signal<NoopRawMutex, usize> = Signal::new();
+        let mut tx = signal.sender();
+        let mut rx = signal.receiver();
+
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx).poll_recv(&mut cx));
+        assert_eq!(PollSend::Ready, Pin::new(&mut tx).poll_send(&mut cx, 1));
+        assert_eq!(PollSend::Ready, Pin::new(&mut tx).poll_send(&mut cx, 2));
+        assert_eq!(1, count.get());
+        assert!(signal.is_signaled());
+
+        assert_eq!(PollRecv::Ready(2), Pin::new(&mut rx).poll_recv(&mut cx));
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx).poll_recv(&mut cx));
+
+        signal.signal(3);
+        signal.reset();
+        assert_eq!(None, signal.try_take());
+    }
+
+    #[tokio::test]
+    async fn static_tasks() {
+        static EVENTS: Channel<CriticalSectionRawMutex, usize, 2> = Channel::new();
+
+        let mut tx = EVENTS.sender();
+        let producer = tokio::spawn(async move {
+            for i in 0..16 {
+                tx.send(i).await.expect("send failed");
+            }
+        });
+
+        let mut rx = EVENTS.receiver();
+        let mut received = Vec::new();
+        while received.len() < 16 {
+            received.push(rx.recv().await.expect("channel closed"));
+        }
+
+        producer.await.expect("join failure");
+        assert_eq!((0..16).collect::<Vec<_>>(), received);
+    }
+}
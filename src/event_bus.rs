@@ -0,0 +1,181 @@
+//! A typed application event bus, with a broadcast channel for each event type.
+//!
+//! Publishers send any `Clone + Send + Sync + 'static` value with [publish](./struct.EventBus.html#method.publish),
+//! and subscribers receive a stream of a single event type with [subscribe](./struct.EventBus.html#method.subscribe).
+//! Like the broadcast channel, each subscriber receives every event published after it subscribed,
+//! and publishers wait if a subscriber falls a full buffer behind.
+//!
+//! ```rust
+//! use postage::{event_bus::EventBus, prelude::*};
+//!
+//! #[derive(Clone, Debug, PartialEq)]
+//! struct UserCreated(String);
+//!
+//! #[derive(Clone, Debug, PartialEq)]
+//! struct UserDeleted(String);
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let bus = EventBus::new(16);
+//!     let mut created = bus.subscribe::<UserCreated>();
+//!     let mut deleted = bus.subscribe::<UserDeleted>();
+//!
+//!     bus.publish(UserCreated("alice".into())).await.ok();
+//!     bus.publish(UserDeleted("bob".into())).await.ok();
+//!
+//!     assert_eq!(Some(UserCreated("alice".into())), created.recv().await);
+//!     assert_eq!(Some(UserDeleted("bob".into())), deleted.recv().await);
+//! }
+//! ```
+
+use std::{
+    any::{type_name, Any, TypeId},
+    collections::HashMap,
+    fmt,
+    sync::Arc,
+};
+
+use parking_lot::Mutex;
+
+use crate::{
+    broadcast,
+    sink::{SendError, Sink, TrySendError},
+};
+
+/// A typed event bus.  Can be cloned, and clones publish to the same subscribers.
+#[derive(Clone)]
+pub struct EventBus {
+    capacity: usize,
+    channels: Arc<Mutex<HashMap<TypeId, Box<dyn Any + Send + Sync>>>>,
+}
+
+impl EventBus {
+    /// Creates an event bus, where each event type is buffered with the given capacity
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            channels: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Subscribes to events of type `E`.  The receiver observes each event published after the call to subscribe.
+    pub fn subscribe<E>(&self) -> broadcast::Receiver<E>
+    where
+        E: Clone + Send + Sync + 'static,
+    {
+        self.sender::<E>().subscribe()
+    }
+
+    /// Publishes an event to the subscribers of type `E`, waiting if a subscriber's buffer is full.
+    ///
+    /// Returns an error containing the event if there are no subscribers of type `E`.
+    pub async fn publish<E>(&self, event: E) -> Result<(), SendError<E>>
+    where
+        E: Clone + Send + Sync + 'static,
+    {
+        let mut sender = self.sender::<E>();
+        sender.send(event).await
+    }
+
+    /// Attempts to publish an event to the subscribers of type `E`, without waiting.
+    ///
+    /// Returns an error containing the event if a subscriber's buffer is full, or if there are no subscribers of type `E`.
+    pub fn try_publish<E>(&self, event: E) -> Result<(), TrySendError<E>>
+    where
+        E: Clone + Send + Sync + 'static,
+    {
+        let mut sender = self.sender::<E>();
+        sender.try_send(event)
+    }
+
+    /// The number of event types which have been published or subscribed
+    pub fn event_types(&self) -> usize {
+        self.channels.lock().len()
+    }
+
+    /// Returns a sender for the channel of type `E`, creating the channel if it does not exist
+    fn sender<E>(&self) -> broadcast::Sender<E>
+    where
+        E: Clone + Send + Sync + 'static,
+    {
+        let mut channels = self.channels.lock();
+        let sender = channels.entry(TypeId::of::<E>()).or_insert_with(|| {
+            let (tx, _rx) = broadcast::Builder::<E>::new(self.capacity)
+                .name(type_name::<E>())
+                .build();
+
+            Box::new(tx)
+        });
+
+        sender
+            .downcast_ref::<broadcast::Sender<E>>()
+            .expect("event bus channels are keyed by their type id")
+            .clone()
+    }
+}
+
+impl fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventBus")
+            .field("capacity", &self.capacity)
+            .field("event_types", &self.event_types())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EventBus;
+    use crate::stream::{Stream, TryRecvError};
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    struct Started(usize);
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    struct Stopped(usize);
+
+    #[test]
+    fn routes_by_type() {
+        let bus = EventBus::new(4);
+        let mut started = bus.subscribe::<Started>();
+        let mut stopped = bus.subscribe::<Stopped>();
+
+        assert!(bus.try_publish(Started(1)).is_ok());
+        assert!(bus.try_publish(Stopped(2)).is_ok());
+
+        assert_eq!(Ok(Started(1)), started.try_recv());
+        assert_eq!(Err(TryRecvError::Pending), started.try_recv());
+        assert_eq!(Ok(Stopped(2)), stopped.try_recv());
+        assert_eq!(2, bus.event_types());
+    }
+
+    #[test]
+    fn multiple_subscribers() {
+        let bus = EventBus::new(4);
+        let mut a = bus.subscribe::<Started>();
+        let mut b = bus.clone().subscribe::<Started>();
+
+        assert!(bus.try_publish(Started(1)).is_ok());
+        assert_eq!(Ok(Started(1)), a.try_recv());
+        assert_eq!(Ok(Started(1)), b.try_recv());
+    }
+
+    #[test]
+    fn no_subscribers() {
+        let bus = EventBus::new(4);
+        assert!(bus.try_publish(Started(1)).is_err());
+
+        let sub = bus.subscribe::<Started>();
+        drop(sub);
+        assert!(bus.try_publish(Started(2)).is_err());
+    }
+
+    #[tokio::test]
+    async fn publish() {
+        let bus = EventBus::new(4);
+        let mut started = bus.subscribe::<Started>();
+
+        assert!(bus.publish(Started(1)).await.is_ok());
+        assert_eq!(Some(Started(1)), started.recv().await);
+    }
+}
@@ -0,0 +1,217 @@
+use std::{fmt, future::Future, pin::Pin, task::Poll};
+
+use pin_project::pin_project;
+
+use crate::{
+    sink::{PollSend, SendError, Sink},
+    stream::{map::MapStream, PollRecv, Stream},
+};
+
+/// Forwards messages from the stream to the sink, waiting when the sink is full.
+///
+/// The future completes with `Ok` when the stream closes, and with the rejected message when the sink closes.
+/// When the future completes, the stream and sink are dropped, so the closure reaches the channels on both sides:
+/// the stream's senders observe the receiver closing, and the sink's receivers observe the sender closing.
+///
+/// ```rust
+/// use postage::{mpsc, prelude::*};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let (mut input, rx) = mpsc::channel(4);
+///     let (tx, mut output) = mpsc::channel(4);
+///     tokio::spawn(postage::forward(rx, tx));
+///
+///     input.send("message").await.ok();
+///     drop(input);
+///
+///     assert_eq!(Some("message"), output.recv().await);
+///     assert_eq!(None, output.recv().await);
+/// }
+/// ```
+pub fn forward<S, K>(stream: S, sink: K) -> Forward<S, K>
+where
+    S: Stream,
+    K: Sink<Item = S::Item>,
+{
+    Forward {
+        stream: Some(stream),
+        sink: Some(sink),
+        pending: None,
+    }
+}
+
+/// Forwards messages from the stream to the sink, transforming each message with the map function.
+///
+/// Otherwise, behaves like [forward](./fn.forward.html).
+pub fn forward_map<S, K, Map, Into>(
+    stream: S,
+    sink: K,
+    map: Map,
+) -> Forward<MapStream<S, Map, Into>, K>
+where
+    S: Stream,
+    K: Sink<Item = Into>,
+    Map: Fn(S::Item) -> Into,
+{
+    forward(stream.map(map), sink)
+}
+
+/// The future returned by [forward](./fn.forward.html) and [forward_map](./fn.forward_map.html)
+#[must_use = "futures do nothing unless polled"]
+#[pin_project]
+pub struct Forward<S, K>
+where
+    S: Stream,
+{
+    // the stream and sink are dropped when the future completes
+    #[pin]
+    stream: Option<S>,
+    #[pin]
+    sink: Option<K>,
+    pending: Option<S::Item>,
+}
+
+impl<S, K> Future for Forward<S, K>
+where
+    S: Stream,
+    K: Sink<Item = S::Item>,
+{
+    type Output = Result<(), SendError<S::Item>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+        let mut cx: crate::Context<'_> = cx.into();
+
+        loop {
+            if let Some(value) = this.pending.take() {
+                let sink = this
+                    .sink
+                    .as_mut()
+                    .as_pin_mut()
+                    .expect("polled after completion");
+
+                match sink.poll_send(&mut cx, value) {
+                    PollSend::Ready => {}
+                    PollSend::Pending(value) => {
+                        *this.pending = Some(value);
+                        return Poll::Pending;
+                    }
+                    PollSend::Rejected(value) => {
+                        this.stream.set(None);
+                        this.sink.set(None);
+                        return Poll::Ready(Err(SendError(value)));
+                    }
+                }
+            }
+
+            let stream = this
+                .stream
+                .as_mut()
+                .as_pin_mut()
+                .expect("polled after completion");
+
+            match stream.poll_recv(&mut cx) {
+                PollRecv::Ready(value) => *this.pending = Some(value),
+                PollRecv::Pending => return Poll::Pending,
+                PollRecv::Closed => {
+                    this.stream.set(None);
+                    this.sink.set(None);
+                    return Poll::Ready(Ok(()));
+                }
+            }
+        }
+    }
+}
+
+impl<S, K> fmt::Debug for Forward<S, K>
+where
+    S: Stream,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Forward")
+            .field("pending", &self.pending.is_some())
+            .field("complete", &self.stream.is_none())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::Future, pin::Pin, task::Poll};
+
+    use futures_test::task::noop_context;
+
+    use super::{forward, forward_map};
+    use crate::{
+        mpsc,
+        sink::{SendError, Sink},
+        stream::{Stream, TryRecvError},
+    };
+
+    #[test]
+    fn forwards_until_stream_closes() {
+        let mut cx = noop_context();
+        let (mut input, rx) = mpsc::channel(4);
+        let (tx, mut output) = mpsc::channel(4);
+        let mut forward = forward(rx, tx);
+
+        input.try_send(1usize).unwrap();
+        input.try_send(2).unwrap();
+        assert_eq!(Poll::Pending, Pin::new(&mut forward).poll(&mut cx));
+        assert_eq!(Ok(1), output.try_recv());
+        assert_eq!(Ok(2), output.try_recv());
+
+        // the sink is dropped on completion, even though the future is still alive
+        drop(input);
+        assert_eq!(Poll::Ready(Ok(())), Pin::new(&mut forward).poll(&mut cx));
+        assert_eq!(Err(TryRecvError::Closed), output.try_recv());
+    }
+
+    #[test]
+    fn waits_for_sink() {
+        let mut cx = noop_context();
+        let (mut input, rx) = mpsc::channel(4);
+        let (tx, mut output) = mpsc::channel(1);
+        let mut forward = forward(rx, tx);
+
+        input.try_send(1usize).unwrap();
+        input.try_send(2).unwrap();
+        input.try_send(3).unwrap();
+        assert_eq!(Poll::Pending, Pin::new(&mut forward).poll(&mut cx));
+        assert_eq!(Ok(1), output.try_recv());
+        assert_eq!(Err(TryRecvError::Pending), output.try_recv());
+
+        assert_eq!(Poll::Pending, Pin::new(&mut forward).poll(&mut cx));
+        assert_eq!(Ok(2), output.try_recv());
+        assert_eq!(Poll::Pending, Pin::new(&mut forward).poll(&mut cx));
+        assert_eq!(Ok(3), output.try_recv());
+    }
+
+    #[test]
+    fn sink_closure_closes_stream() {
+        let mut cx = noop_context();
+        let (mut input, rx) = mpsc::channel(4);
+        let (tx, output) = mpsc::channel(4);
+        let mut forward = forward(rx, tx);
+        drop(output);
+
+        input.try_send(1usize).unwrap();
+        assert_eq!(
+            Poll::Ready(Err(SendError(1))),
+            Pin::new(&mut forward).poll(&mut cx)
+        );
+        assert!(input.is_closed());
+    }
+
+    #[tokio::test]
+    async fn map() {
+        let (mut input, rx) = mpsc::channel(4);
+        let (tx, mut output) = mpsc::channel(4);
+
+        input.send(1usize).await.unwrap();
+        drop(input);
+        assert_eq!(Ok(()), forward_map(rx, tx, |i| i * 10).await);
+        assert_eq!(Some(10), output.recv().await);
+    }
+}
@@ -14,7 +14,7 @@ macro_rules! poll {
     }};
 }
 
-impl futures::stream::Stream for crate::barrier::Receiver {
+impl futures_core::Stream for crate::barrier::Receiver {
     type Item = ();
 
     fn poll_next(
@@ -25,7 +25,7 @@ impl futures::stream::Stream for crate::barrier::Receiver {
     }
 }
 
-impl<T: Clone> futures::stream::Stream for crate::broadcast::Receiver<T> {
+impl<T: Clone> futures_core::Stream for crate::broadcast::Receiver<T> {
     type Item = T;
 
     fn poll_next(
@@ -36,7 +36,7 @@ impl<T: Clone> futures::stream::Stream for crate::broadcast::Receiver<T> {
     }
 }
 
-impl<T> futures::stream::Stream for crate::dispatch::Receiver<T> {
+impl<T> futures_core::Stream for crate::credit::Receiver<T> {
     type Item = T;
 
     fn poll_next(
@@ -47,7 +47,161 @@ impl<T> futures::stream::Stream for crate::dispatch::Receiver<T> {
     }
 }
 
-impl<T> futures::stream::Stream for crate::mpsc::Receiver<T> {
+impl<T> futures_core::Stream for crate::dispatch::Receiver<T> {
+    type Item = T;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        poll!(self, cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        crate::stream::Stream::size_hint(self)
+    }
+}
+
+impl<T> futures_core::Stream for crate::mpsc::Receiver<T> {
+    type Item = T;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        poll!(self, cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        crate::stream::Stream::size_hint(self)
+    }
+}
+
+impl<T> futures_core::Stream for crate::oneshot::Receiver<T> {
+    type Item = T;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        poll!(self, cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        crate::stream::Stream::size_hint(self)
+    }
+}
+
+impl<T: Clone> futures_core::Stream for crate::watch::Receiver<T> {
+    type Item = T;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        poll!(self, cx)
+    }
+}
+
+impl<M> futures_core::Stream for crate::actor::Mailbox<M> {
+    type Item = M;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        poll!(self, cx)
+    }
+}
+
+impl<T> futures_core::Stream for crate::mpsc::ControlledReceiver<T> {
+    type Item = T;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        poll!(self, cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        crate::stream::Stream::size_hint(self)
+    }
+}
+
+impl<T> futures_core::Stream for crate::mpsc::ShardedReceiver<T> {
+    type Item = T;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        poll!(self, cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        crate::stream::Stream::size_hint(self)
+    }
+}
+
+impl<T: Clone> futures_core::Stream for crate::ack::Receiver<T> {
+    type Item = crate::ack::Delivery<T>;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        poll!(self, cx)
+    }
+}
+
+impl<T> futures_core::Stream for crate::group::Receiver<T> {
+    type Item = T;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        poll!(self, cx)
+    }
+}
+
+#[cfg(feature = "durable")]
+impl<T, C> futures_core::Stream for crate::durable::Receiver<T, C>
+where
+    C: crate::codec::Decoder<Item = T>,
+    C::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    type Item = std::io::Result<T>;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        poll!(self, cx)
+    }
+}
+
+#[cfg(feature = "embassy")]
+impl<M, T, const N: usize> futures_core::Stream for crate::embassy::Receiver<'_, M, T, N>
+where
+    M: crate::embassy::RawMutex,
+{
+    type Item = T;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        poll!(self, cx)
+    }
+}
+
+#[cfg(feature = "embassy")]
+impl<M, T> futures_core::Stream for crate::embassy::SignalReceiver<'_, M, T>
+where
+    M: crate::embassy::RawMutex,
+{
     type Item = T;
 
     fn poll_next(
@@ -58,7 +212,11 @@ impl<T> futures::stream::Stream for crate::mpsc::Receiver<T> {
     }
 }
 
-impl<T> futures::stream::Stream for crate::oneshot::Receiver<T> {
+#[cfg(feature = "spill")]
+impl<T> futures_core::Stream for crate::spill::Receiver<T>
+where
+    T: serde::de::DeserializeOwned,
+{
     type Item = T;
 
     fn poll_next(
@@ -69,7 +227,27 @@ impl<T> futures::stream::Stream for crate::oneshot::Receiver<T> {
     }
 }
 
-impl<T: Clone> futures::stream::Stream for crate::watch::Receiver<T> {
+#[cfg(feature = "timer")]
+impl<S> futures_core::Stream for crate::time::DeadlineReceiver<S>
+where
+    S: crate::stream::Stream + Unpin,
+{
+    type Item = S::Item;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        poll!(self, cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        crate::stream::Stream::size_hint(self)
+    }
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl<T> futures_core::Stream for crate::test::pump::Receiver<T> {
     type Item = T;
 
     fn poll_next(
@@ -82,10 +260,12 @@ impl<T: Clone> futures::stream::Stream for crate::watch::Receiver<T> {
 
 #[cfg(test)]
 mod sink_tests {
-    use std::{pin::Pin, task::Poll};
+    use std::{pin::Pin, task::Poll, time::Duration};
 
-    use crate::{barrier, dispatch, mpsc, oneshot, sink::SendError, watch};
-    use futures::Sink;
+    use crate::{
+        ack, barrier, broadcast, credit, dispatch, group, mpsc, oneshot, sink::SendError, watch,
+    };
+    use futures_sink::Sink;
 
     macro_rules! test_sink {
         ($chan:expr, $val:expr) => {
@@ -130,6 +310,32 @@ mod sink_tests {
         };
     }
 
+    macro_rules! test_sink_broadcast {
+        ($chan:expr) => {
+            let mut std_cx = futures_test::task::noop_context();
+
+            let (mut tx, rx) = $chan;
+
+            for i in 0..2usize {
+                assert_eq!(
+                    Poll::Ready(Ok(())),
+                    Pin::new(&mut tx).poll_ready(&mut std_cx)
+                );
+                assert_eq!(Ok(()), Pin::new(&mut tx).start_send(i));
+            }
+
+            assert_eq!(Poll::Pending, Pin::new(&mut tx).poll_ready(&mut std_cx));
+            assert_eq!(Err(SendError(2)), Pin::new(&mut tx).start_send(2));
+
+            drop(rx);
+            assert_eq!(
+                Poll::Ready(Ok(())),
+                Pin::new(&mut tx).poll_ready(&mut std_cx)
+            );
+            assert_eq!(Err(SendError(2)), Pin::new(&mut tx).start_send(2));
+        };
+    }
+
     #[test]
     fn barrier() {
         let mut std_cx = futures_test::task::noop_context();
@@ -149,10 +355,56 @@ mod sink_tests {
         assert_eq!(Err(SendError(())), Pin::new(&mut tx).start_send(()));
     }
 
-    // I couldn't implement the trait for the broadcast channel.
-    // Values and Contexts are intricately linked to the internal structure of the broadcast channel.
-    // Unfortunately, futures::Sink provides the Context and Item separately,
-    // so there was no reasonable way to implement the futures trait.
+    #[test]
+    fn broadcast() {
+        test_sink_broadcast!(broadcast::channel(2));
+    }
+
+    #[test]
+    fn broadcast_arc() {
+        test_sink_broadcast!(broadcast::arc_channel(2));
+    }
+
+    #[test]
+    fn broadcast_wakes() {
+        let (waker, count) = futures_test::task::new_count_waker();
+        let mut std_cx = std::task::Context::from_waker(&waker);
+
+        let (mut tx, mut rx) = broadcast::channel(2);
+        assert_eq!(Ok(()), Pin::new(&mut tx).start_send(1usize));
+        assert_eq!(Ok(()), Pin::new(&mut tx).start_send(2));
+        assert_eq!(Poll::Pending, Pin::new(&mut tx).poll_ready(&mut std_cx));
+
+        assert_eq!(Ok(1), crate::stream::Stream::try_recv(&mut rx));
+        assert_eq!(1, count.get());
+        assert_eq!(
+            Poll::Ready(Ok(())),
+            Pin::new(&mut tx).poll_ready(&mut std_cx)
+        );
+    }
+
+    #[test]
+    fn credit() {
+        test_sink!(credit::channel(1), 1usize);
+    }
+
+    #[test]
+    fn credit_wakes() {
+        let (waker, count) = futures_test::task::new_count_waker();
+        let mut std_cx = std::task::Context::from_waker(&waker);
+
+        let (mut tx, rx) = credit::channel(0);
+        assert_eq!(Poll::Pending, Pin::new(&mut tx).poll_ready(&mut std_cx));
+        assert_eq!(Err(SendError(1usize)), Pin::new(&mut tx).start_send(1));
+
+        rx.grant(1);
+        assert_eq!(1, count.get());
+        assert_eq!(
+            Poll::Ready(Ok(())),
+            Pin::new(&mut tx).poll_ready(&mut std_cx)
+        );
+        assert_eq!(Ok(()), Pin::new(&mut tx).start_send(1));
+    }
 
     #[test]
     fn dispatch() {
@@ -164,6 +416,52 @@ mod sink_tests {
         test_sink!(mpsc::channel(1), 1usize);
     }
 
+    #[cfg(feature = "embassy")]
+    #[test]
+    fn embassy() {
+        use crate::embassy::{Channel, NoopRawMutex};
+
+        let mut std_cx = futures_test::task::noop_context();
+        let channel: Channel<NoopRawMutex, usize, 1> = Channel::new();
+        let mut tx = channel.sender();
+
+        assert_eq!(
+            Poll::Ready(Ok(())),
+            Pin::new(&mut tx).poll_ready(&mut std_cx)
+        );
+        assert_eq!(Ok(()), Pin::new(&mut tx).start_send(1));
+
+        assert_eq!(Poll::Pending, Pin::new(&mut tx).poll_ready(&mut std_cx));
+        assert_eq!(Err(SendError(2)), Pin::new(&mut tx).start_send(2));
+    }
+
+    #[test]
+    fn mpsc_sequenced() {
+        let (mut tx, _rx) = mpsc::Builder::new(2).sequence_numbers().build();
+        assert_eq!(Ok(()), Pin::new(&mut tx).start_send(1usize));
+        assert_eq!(Ok(2), tx.try_send_seq(2));
+    }
+
+    #[test]
+    fn mpsc_controlled() {
+        test_sink!(mpsc::with_control(1), 1usize);
+    }
+
+    #[test]
+    fn mpsc_sharded() {
+        test_sink!(mpsc::sharded(2, 1), 1usize);
+    }
+
+    #[test]
+    fn ack() {
+        test_sink!(ack::channel(1, Duration::from_secs(1)), 1usize);
+    }
+
+    #[test]
+    fn group() {
+        test_sink!(group::channel(1, "group"), 1usize);
+    }
+
     #[test]
     fn oneshot() {
         let mut std_cx = futures_test::task::noop_context();
@@ -202,11 +500,11 @@ mod stream_tests {
     use std::{pin::Pin, task::Poll};
 
     use crate::{
-        barrier, broadcast, dispatch, mpsc, oneshot,
+        barrier, broadcast, dispatch, group, mpsc, oneshot,
         sink::{PollSend, Sink},
         watch,
     };
-    use futures::Stream;
+    use futures_core::Stream;
 
     macro_rules! test_stream {
         ($chan:expr, $val:expr) => {
@@ -272,6 +570,48 @@ mod stream_tests {
         test_stream!(oneshot::channel(), 1usize);
     }
 
+    #[cfg(feature = "embassy")]
+    #[test]
+    fn embassy() {
+        use crate::embassy::{Channel, NoopRawMutex, Signal};
+
+        let mut std_cx = futures_test::task::noop_context();
+        let mut cx = crate::test::noop_context();
+
+        let channel: Channel<NoopRawMutex, usize, 4> = Channel::new();
+        let (mut tx, mut rx) = (channel.sender(), channel.receiver());
+        assert_eq!(Poll::Pending, Pin::new(&mut rx).poll_next(&mut std_cx));
+        assert_eq!(PollSend::Ready, Pin::new(&mut tx).poll_send(&mut cx, 1));
+        assert_eq!(
+            Poll::Ready(Some(1)),
+            Pin::new(&mut rx).poll_next(&mut std_cx)
+        );
+
+        let signal: Signal<NoopRawMutex, usize> = Signal::new();
+        let (mut tx, mut rx) = (signal.sender(), signal.receiver());
+        assert_eq!(Poll::Pending, Pin::new(&mut rx).poll_next(&mut std_cx));
+        assert_eq!(PollSend::Ready, Pin::new(&mut tx).poll_send(&mut cx, 1));
+        assert_eq!(
+            Poll::Ready(Some(1)),
+            Pin::new(&mut rx).poll_next(&mut std_cx)
+        );
+    }
+
+    #[test]
+    fn mpsc_controlled() {
+        test_stream!(mpsc::with_control(4), 1usize);
+    }
+
+    #[test]
+    fn mpsc_sharded() {
+        test_stream!(mpsc::sharded(2, 4), 1usize);
+    }
+
+    #[test]
+    fn group() {
+        test_stream!(group::channel(4, "group"), 1usize);
+    }
+
     #[test]
     fn watch() {
         let mut std_cx = futures_test::task::noop_context();
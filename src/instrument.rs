@@ -0,0 +1,581 @@
+//! Throughput and latency measurements for any stream or sink.
+//!
+//! [Measured](./struct.Measured.html) wraps a `Stream` or `Sink`, and records each message it passes through.
+//! Over a rolling window, it reports:
+//! - throughput, in messages per second
+//! - wait time, from the first poll of an operation until the message is received or accepted
+//! - processing time, from the completion of an operation until the next poll.  For a stream, this is the time
+//!   the consumer spent handling the previous message.
+//!
+//! The numbers are available with [Measured::stats](./struct.Measured.html#method.stats), or from another task with a
+//! [StatsHandle](./struct.StatsHandle.html).  They are also reported to the [metrics](../metrics/index.html) hook, if one is installed.
+//!
+//! ```rust
+//! use postage::{instrument::Measured, mpsc, prelude::*};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let (mut tx, rx) = mpsc::channel(4);
+//!     let mut rx = Measured::new(rx);
+//!
+//!     tx.send(1usize).await.ok();
+//!     tx.send(2usize).await.ok();
+//!     rx.recv().await;
+//!     rx.recv().await;
+//!
+//!     let stats = rx.stats();
+//!     assert_eq!(2, stats.total);
+//!     assert!(stats.throughput > 0.0);
+//! }
+//! ```
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+use pin_project::pin_project;
+
+use crate::{
+    metrics::{ChannelMetrics, MetricsHook},
+    sink::{BufferedSink, PollReady, PollSend, Sink},
+    stream::{PollRecv, Stream},
+    Context,
+};
+
+const DEFAULT_WINDOW: Duration = Duration::from_secs(10);
+
+/// Measurements taken over the rolling window of a [Measured](./struct.Measured.html) stream or sink.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    /// The number of messages since the wrapper was created
+    pub total: u64,
+    /// The number of messages in the window
+    pub count: usize,
+    /// Messages per second over the window
+    pub throughput: f64,
+    /// The mean wait time of the messages in the window
+    pub mean_wait: Duration,
+    /// The longest wait time of the messages in the window
+    pub max_wait: Duration,
+    /// The mean processing time in the window
+    pub mean_processing: Duration,
+    /// The longest processing time in the window
+    pub max_processing: Duration,
+}
+
+/// Configures a [Measured](./struct.Measured.html) wrapper.
+pub struct Builder {
+    window: Duration,
+    name: Option<String>,
+    metrics: Option<Arc<dyn ChannelMetrics>>,
+}
+
+impl Builder {
+    /// Creates a builder with a ten second window
+    pub fn new() -> Self {
+        Self {
+            window: DEFAULT_WINDOW,
+            name: None,
+            metrics: None,
+        }
+    }
+
+    /// Sets the duration of the rolling window.
+    ///
+    /// Panics if the window is zero.
+    pub fn window(mut self, window: Duration) -> Self {
+        assert!(window > Duration::ZERO, "the window must be non-zero");
+        self.window = window;
+        self
+    }
+
+    /// Names the wrapper.  The name is attached to the measurements reported to the metrics hook.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Installs a metrics hook on the wrapper, which takes precedence over the global hook.
+    pub fn metrics(mut self, metrics: Arc<dyn ChannelMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Wraps the stream or sink
+    pub fn wrap<S>(self, inner: S) -> Measured<S> {
+        let metrics = MetricsHook::resolve(self.metrics, "measured", self.name.as_deref());
+
+        Measured {
+            inner,
+            waiting_since: None,
+            completed_at: None,
+            metrics,
+            handle: StatsHandle {
+                window: Arc::new(Mutex::new(Window::new(self.window))),
+            },
+        }
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Builder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Builder")
+            .field("window", &self.window)
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+/// A stream or sink which measures the messages passing through it.
+#[pin_project]
+pub struct Measured<S> {
+    #[pin]
+    inner: S,
+    waiting_since: Option<Instant>,
+    completed_at: Option<Instant>,
+    metrics: Option<MetricsHook>,
+    handle: StatsHandle,
+}
+
+impl<S> Measured<S> {
+    /// Wraps the stream or sink, with a ten second window
+    pub fn new(inner: S) -> Self {
+        Builder::new().wrap(inner)
+    }
+
+    /// Returns the measurements for the current window
+    pub fn stats(&self) -> Stats {
+        self.handle.stats()
+    }
+
+    /// Returns a handle which can read the measurements from another task
+    pub fn stats_handle(&self) -> StatsHandle {
+        self.handle.clone()
+    }
+
+    /// Returns a reference to the wrapped stream or sink
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped stream or sink
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Consumes the wrapper, returning the stream or sink
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+/// Called at the start of each poll.  Records the processing time of the previous message,
+/// and returns the time the current operation started waiting.
+fn begin(
+    waiting_since: &mut Option<Instant>,
+    completed_at: &mut Option<Instant>,
+    metrics: &Option<MetricsHook>,
+    handle: &StatsHandle,
+) -> Instant {
+    let now = Instant::now();
+
+    if let Some(completed_at) = completed_at.take() {
+        let processing = now - completed_at;
+        handle.window.lock().record_processing(now, processing);
+
+        if let Some(ref metrics) = metrics {
+            metrics.record_processing(processing);
+        }
+    }
+
+    *waiting_since.get_or_insert(now)
+}
+
+/// Called when an operation completes.  Records the wait time of the message.
+fn complete(
+    started: Instant,
+    completed_at: &mut Option<Instant>,
+    metrics: &Option<MetricsHook>,
+    handle: &StatsHandle,
+) {
+    let now = Instant::now();
+    let wait = now - started;
+    handle.window.lock().record_message(now, wait);
+    *completed_at = Some(now);
+
+    if let Some(ref metrics) = metrics {
+        metrics.record_wait(wait);
+    }
+}
+
+impl<S> Stream for Measured<S>
+where
+    S: Stream,
+{
+    type Item = S::Item;
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item> {
+        let this = self.project();
+        let started = begin(
+            this.waiting_since,
+            this.completed_at,
+            this.metrics,
+            this.handle,
+        );
+
+        match this.inner.poll_recv(cx) {
+            PollRecv::Ready(value) => {
+                *this.waiting_since = None;
+                complete(started, this.completed_at, this.metrics, this.handle);
+
+                if let Some(ref metrics) = this.metrics {
+                    metrics.on_recv(None, None);
+                }
+
+                PollRecv::Ready(value)
+            }
+            PollRecv::Pending => PollRecv::Pending,
+            PollRecv::Closed => {
+                *this.waiting_since = None;
+                PollRecv::Closed
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<S> Sink for Measured<S>
+where
+    S: Sink,
+{
+    type Item = S::Item;
+
+    fn poll_send(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        value: Self::Item,
+    ) -> PollSend<Self::Item> {
+        let this = self.project();
+        let started = begin(
+            this.waiting_since,
+            this.completed_at,
+            this.metrics,
+            this.handle,
+        );
+
+        match this.inner.poll_send(cx, value) {
+            PollSend::Ready => {
+                *this.waiting_since = None;
+                complete(started, this.completed_at, this.metrics, this.handle);
+
+                if let Some(ref metrics) = this.metrics {
+                    metrics.on_send(None);
+                }
+
+                PollSend::Ready
+            }
+            PollSend::Pending(value) => PollSend::Pending(value),
+            PollSend::Rejected(value) => {
+                *this.waiting_since = None;
+
+                if let Some(ref metrics) = this.metrics {
+                    metrics.on_reject();
+                }
+
+                PollSend::Rejected(value)
+            }
+        }
+    }
+}
+
+impl<S> BufferedSink for Measured<S>
+where
+    S: BufferedSink,
+{
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        self.project().inner.poll_ready(cx)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        self.project().inner.poll_close(cx)
+    }
+}
+
+impl<S> fmt::Debug for Measured<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Measured")
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+/// A handle to the measurements of a [Measured](./struct.Measured.html) stream or sink.  Can be cloned, and sent to other tasks.
+#[derive(Clone)]
+pub struct StatsHandle {
+    window: Arc<Mutex<Window>>,
+}
+
+impl StatsHandle {
+    /// Returns the measurements for the current window
+    pub fn stats(&self) -> Stats {
+        self.window.lock().stats(Instant::now())
+    }
+}
+
+impl fmt::Debug for StatsHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StatsHandle")
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+/// The samples recorded within the rolling window
+struct Window {
+    duration: Duration,
+    created: Instant,
+    total: u64,
+    waits: VecDeque<(Instant, Duration)>,
+    processing: VecDeque<(Instant, Duration)>,
+}
+
+impl Window {
+    fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            created: Instant::now(),
+            total: 0,
+            waits: VecDeque::new(),
+            processing: VecDeque::new(),
+        }
+    }
+
+    fn record_message(&mut self, now: Instant, wait: Duration) {
+        self.total += 1;
+        self.waits.push_back((now, wait));
+        self.prune(now);
+    }
+
+    fn record_processing(&mut self, now: Instant, processing: Duration) {
+        self.processing.push_back((now, processing));
+        self.prune(now);
+    }
+
+    fn prune(&mut self, now: Instant) {
+        let duration = self.duration;
+        let expired = |sample: &&(Instant, Duration)| now.duration_since(sample.0) > duration;
+
+        while self.waits.front().filter(expired).is_some() {
+            self.waits.pop_front();
+        }
+
+        while self.processing.front().filter(expired).is_some() {
+            self.processing.pop_front();
+        }
+    }
+
+    fn stats(&mut self, now: Instant) -> Stats {
+        self.prune(now);
+
+        let span = now.duration_since(self.created).min(self.duration);
+        let throughput = if span > Duration::ZERO {
+            self.waits.len() as f64 / span.as_secs_f64()
+        } else {
+            0.0
+        };
+
+        let (mean_wait, max_wait) = summarize(&self.waits);
+        let (mean_processing, max_processing) = summarize(&self.processing);
+
+        Stats {
+            total: self.total,
+            count: self.waits.len(),
+            throughput,
+            mean_wait,
+            max_wait,
+            mean_processing,
+            max_processing,
+        }
+    }
+}
+
+/// Returns the mean and maximum of the samples
+fn summarize(samples: &VecDeque<(Instant, Duration)>) -> (Duration, Duration) {
+    if samples.is_empty() {
+        return (Duration::ZERO, Duration::ZERO);
+    }
+
+    let sum: Duration = samples.iter().map(|(_, duration)| *duration).sum();
+    let max = samples
+        .iter()
+        .map(|(_, duration)| *duration)
+        .max()
+        .unwrap_or_default();
+
+    (sum / samples.len() as u32, max)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        pin::Pin,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread,
+        time::Duration,
+    };
+
+    use super::{Builder, Measured};
+    use crate::{
+        metrics::{ChannelLabels, ChannelMetrics},
+        mpsc,
+        sink::{PollSend, Sink},
+        stream::{PollRecv, Stream},
+        test::noop_context,
+    };
+
+    #[test]
+    fn stream_counts() {
+        let (mut tx, rx) = mpsc::channel(4);
+        let mut rx = Measured::new(rx);
+
+        tx.try_send(1usize).unwrap();
+        tx.try_send(2usize).unwrap();
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(Ok(2), rx.try_recv());
+
+        let stats = rx.stats();
+        assert_eq!(2, stats.total);
+        assert_eq!(2, stats.count);
+        assert!(stats.throughput > 0.0);
+    }
+
+    #[test]
+    fn stream_wait() {
+        let (mut tx, rx) = mpsc::channel(4);
+        let mut rx = Measured::new(rx);
+
+        assert_eq!(
+            PollRecv::Pending,
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+        thread::sleep(Duration::from_millis(10));
+        tx.try_send(1usize).unwrap();
+        assert_eq!(
+            PollRecv::Ready(1),
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+
+        assert!(rx.stats().max_wait >= Duration::from_millis(10));
+    }
+
+    #[test]
+    fn stream_processing() {
+        let (mut tx, rx) = mpsc::channel(4);
+        let mut rx = Measured::new(rx);
+
+        tx.try_send(1usize).unwrap();
+        tx.try_send(2usize).unwrap();
+        assert_eq!(Ok(1), rx.try_recv());
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(Ok(2), rx.try_recv());
+
+        let stats = rx.stats();
+        assert!(stats.max_processing >= Duration::from_millis(10));
+        assert!(stats.max_wait < Duration::from_millis(10));
+    }
+
+    #[test]
+    fn sink_counts() {
+        let (tx, rx) = mpsc::channel(1);
+        let mut tx = Measured::new(tx);
+        let handle = tx.stats_handle();
+
+        assert_eq!(Ok(()), tx.try_send(1usize));
+        drop(rx);
+        assert!(tx.try_send(2usize).is_err());
+
+        assert_eq!(1, handle.stats().total);
+        assert_eq!(
+            PollSend::Rejected(3),
+            Pin::new(&mut tx).poll_send(&mut noop_context(), 3)
+        );
+    }
+
+    #[test]
+    fn window_expires() {
+        let (mut tx, rx) = mpsc::channel(4);
+        let mut rx = Builder::new().window(Duration::from_millis(10)).wrap(rx);
+
+        tx.try_send(1usize).unwrap();
+        assert_eq!(Ok(1), rx.try_recv());
+        thread::sleep(Duration::from_millis(20));
+
+        let stats = rx.stats();
+        assert_eq!(1, stats.total);
+        assert_eq!(0, stats.count);
+        assert_eq!(0.0, stats.throughput);
+    }
+
+    #[test]
+    fn metrics_hook() {
+        #[derive(Default)]
+        struct Recorder {
+            recvs: AtomicUsize,
+            waits: AtomicUsize,
+            processing: AtomicUsize,
+        }
+
+        impl ChannelMetrics for Recorder {
+            fn on_recv(&self, channel: &ChannelLabels) {
+                assert_eq!("measured", channel.kind());
+                assert_eq!(Some("consumer"), channel.name());
+                self.recvs.fetch_add(1, Ordering::SeqCst);
+            }
+
+            fn record_wait(&self, _channel: &ChannelLabels, _duration: Duration) {
+                self.waits.fetch_add(1, Ordering::SeqCst);
+            }
+
+            fn record_processing(&self, _channel: &ChannelLabels, _duration: Duration) {
+                self.processing.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let recorder = Arc::new(Recorder::default());
+        let (mut tx, rx) = mpsc::channel(4);
+        let mut rx = Builder::new()
+            .name("consumer")
+            .metrics(recorder.clone())
+            .wrap(rx);
+
+        tx.try_send(1usize).unwrap();
+        tx.try_send(2usize).unwrap();
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(Ok(2), rx.try_recv());
+
+        assert_eq!(2, recorder.recvs.load(Ordering::SeqCst));
+        assert_eq!(2, recorder.waits.load(Ordering::SeqCst));
+        assert_eq!(1, recorder.processing.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,320 @@
+//! An in-memory byte pipe, for testing network code without sockets, and for in-process transports.
+//!
+//! [duplex](./fn.duplex.html) returns two connected [DuplexStream](./struct.DuplexStream.html) handles.  Bytes written to one handle are read from the other.
+//! The handles implement `AsyncRead` and `AsyncWrite` from both tokio and `futures-io`.
+//!
+//! Requires the `io` feature.
+//!
+//! ```rust
+//! use tokio::io::{AsyncReadExt, AsyncWriteExt};
+//!
+//! #[tokio::main]
+//! async fn main() -> std::io::Result<()> {
+//!     let (mut client, mut server) = postage::io::duplex(64);
+//!
+//!     client.write_all(b"ping").await?;
+//!
+//!     let mut buf = [0; 4];
+//!     server.read_exact(&mut buf).await?;
+//!     assert_eq!(b"ping", &buf);
+//!     Ok(())
+//! }
+//! ```
+
+use std::{
+    cmp, fmt, io,
+    pin::Pin,
+    task::{self, Poll},
+};
+
+use bytes::{Buf, Bytes};
+use static_assertions::assert_impl_all;
+
+use crate::{
+    mpsc,
+    sink::{BufferedSink, PollReady, PollSend, Sink},
+    stream::{PollRecv, Stream},
+    Context,
+};
+
+/// Constructs a pair of connected byte streams.
+///
+/// Each direction buffers up to `buffer` bytes, and writes wait until the other handle reads.
+/// When a handle is dropped or shut down, the other handle reads EOF once it has read the buffered bytes.
+///
+/// Panics if `buffer` is zero.
+pub fn duplex(buffer: usize) -> (DuplexStream, DuplexStream) {
+    assert!(buffer > 0, "the duplex buffer must be non-zero");
+
+    let (a_tx, a_rx) = mpsc::channel(1);
+    let (b_tx, b_rx) = mpsc::channel(1);
+
+    let a = DuplexStream::new(buffer, a_tx, b_rx);
+    let b = DuplexStream::new(buffer, b_tx, a_rx);
+
+    (a, b)
+}
+
+/// One end of an in-memory byte pipe, created by [duplex](./fn.duplex.html)
+pub struct DuplexStream {
+    buffer: usize,
+    tx: Option<mpsc::Sender<Bytes>>,
+    rx: mpsc::Receiver<Bytes>,
+    read: Bytes,
+}
+
+assert_impl_all!(DuplexStream: Send, Sync, Unpin, fmt::Debug);
+
+impl DuplexStream {
+    fn new(buffer: usize, tx: mpsc::Sender<Bytes>, rx: mpsc::Receiver<Bytes>) -> Self {
+        Self {
+            buffer,
+            tx: Some(tx),
+            rx,
+            read: Bytes::new(),
+        }
+    }
+
+    fn poll_read_bytes(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        if self.read.is_empty() {
+            match Pin::new(&mut self.rx).poll_recv(cx) {
+                PollRecv::Ready(bytes) => self.read = bytes,
+                PollRecv::Pending => return Poll::Pending,
+                PollRecv::Closed => return Poll::Ready(Ok(0)),
+            }
+        }
+
+        let len = cmp::min(buf.len(), self.read.len());
+        buf[..len].copy_from_slice(&self.read[..len]);
+        self.read.advance(len);
+
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_write_bytes(&mut self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let tx = match self.tx {
+            Some(ref mut tx) => tx,
+            None => return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into())),
+        };
+
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        // wait for capacity before copying the bytes
+        match Pin::new(&mut *tx).poll_ready(cx) {
+            PollReady::Ready => {}
+            PollReady::Pending => return Poll::Pending,
+            PollReady::Closed => return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into())),
+        }
+
+        let len = cmp::min(buf.len(), self.buffer);
+        match Pin::new(tx).poll_send(cx, Bytes::copy_from_slice(&buf[..len])) {
+            PollSend::Ready => Poll::Ready(Ok(len)),
+            PollSend::Pending(_) => Poll::Pending,
+            PollSend::Rejected(_) => Poll::Ready(Err(io::ErrorKind::BrokenPipe.into())),
+        }
+    }
+
+    fn shutdown(&mut self) {
+        self.tx = None;
+    }
+}
+
+impl tokio::io::AsyncRead for DuplexStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let mut cx = cx.into();
+        let unfilled = buf.initialize_unfilled();
+
+        match self.get_mut().poll_read_bytes(&mut cx, unfilled) {
+            Poll::Ready(Ok(len)) => {
+                buf.advance(len);
+                Poll::Ready(Ok(()))
+            }
+            Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl tokio::io::AsyncWrite for DuplexStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().poll_write_bytes(&mut cx.into(), buf)
+    }
+
+    /// Written bytes are delivered to the channel immediately, so the flush completes immediately
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut task::Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut task::Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().shutdown();
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl futures_io::AsyncRead for DuplexStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().poll_read_bytes(&mut cx.into(), buf)
+    }
+}
+
+impl futures_io::AsyncWrite for DuplexStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().poll_write_bytes(&mut cx.into(), buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut task::Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut task::Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().shutdown();
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl fmt::Debug for DuplexStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DuplexStream")
+            .field("buffer", &self.buffer)
+            .field("closed", &self.tx.is_none())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::poll_fn, io, pin::Pin};
+
+    use futures_test::task::noop_context;
+    use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+    use super::{duplex, DuplexStream};
+
+    async fn write(stream: &mut DuplexStream, buf: &[u8]) -> io::Result<usize> {
+        poll_fn(|cx| Pin::new(&mut *stream).poll_write(cx, buf)).await
+    }
+
+    async fn read(stream: &mut DuplexStream, buf: &mut [u8]) -> io::Result<usize> {
+        poll_fn(|cx| {
+            let mut buf = ReadBuf::new(buf);
+            Pin::new(&mut *stream)
+                .poll_read(cx, &mut buf)
+                .map_ok(|_| buf.filled().len())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn both_directions() {
+        let (mut a, mut b) = duplex(16);
+        let mut buf = [0; 16];
+
+        assert_eq!(5, write(&mut a, b"hello").await.unwrap());
+        assert_eq!(5, read(&mut b, &mut buf).await.unwrap());
+        assert_eq!(b"hello", &buf[..5]);
+
+        assert_eq!(5, write(&mut b, b"world").await.unwrap());
+        assert_eq!(5, read(&mut a, &mut buf).await.unwrap());
+        assert_eq!(b"world", &buf[..5]);
+    }
+
+    #[tokio::test]
+    async fn partial_reads() {
+        let (mut a, mut b) = duplex(16);
+        let mut buf = [0; 2];
+
+        write(&mut a, b"abc").await.unwrap();
+        assert_eq!(2, read(&mut b, &mut buf).await.unwrap());
+        assert_eq!(b"ab", &buf);
+        assert_eq!(1, read(&mut b, &mut buf).await.unwrap());
+        assert_eq!(b'c', buf[0]);
+    }
+
+    #[test]
+    fn write_waits_for_reader() {
+        let mut cx = noop_context();
+        let (mut a, _b) = duplex(4);
+
+        assert!(matches!(
+            Pin::new(&mut a).poll_write(&mut cx, b"abcdef"),
+            std::task::Poll::Ready(Ok(4))
+        ));
+        assert!(Pin::new(&mut a).poll_write(&mut cx, b"ef").is_pending());
+    }
+
+    #[tokio::test]
+    async fn shutdown_is_eof() {
+        let (mut a, mut b) = duplex(16);
+        let mut buf = [0; 16];
+
+        write(&mut a, b"bye").await.unwrap();
+        poll_fn(|cx| Pin::new(&mut a).poll_shutdown(cx))
+            .await
+            .unwrap();
+
+        assert_eq!(3, read(&mut b, &mut buf).await.unwrap());
+        assert_eq!(0, read(&mut b, &mut buf).await.unwrap());
+        assert_eq!(
+            io::ErrorKind::BrokenPipe,
+            write(&mut a, b"more").await.unwrap_err().kind()
+        );
+    }
+
+    #[tokio::test]
+    async fn dropped_peer() {
+        let (mut a, b) = duplex(16);
+        drop(b);
+
+        assert_eq!(
+            io::ErrorKind::BrokenPipe,
+            write(&mut a, b"hello").await.unwrap_err().kind()
+        );
+        assert_eq!(0, read(&mut a, &mut [0; 4]).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn futures_io() {
+        let (mut a, mut b) = duplex(16);
+        let mut buf = [0; 16];
+
+        poll_fn(|cx| futures_io::AsyncWrite::poll_write(Pin::new(&mut a), cx, b"hi"))
+            .await
+            .unwrap();
+        poll_fn(|cx| futures_io::AsyncWrite::poll_close(Pin::new(&mut a), cx))
+            .await
+            .unwrap();
+
+        let len = poll_fn(|cx| futures_io::AsyncRead::poll_read(Pin::new(&mut b), cx, &mut buf))
+            .await
+            .unwrap();
+        assert_eq!(b"hi", &buf[..len]);
+        assert_eq!(
+            0,
+            poll_fn(|cx| futures_io::AsyncRead::poll_read(Pin::new(&mut b), cx, &mut buf))
+                .await
+                .unwrap()
+        );
+    }
+}
@@ -2,9 +2,12 @@
 //!
 //! # Why use Postage?
 //! - Includes a **rich set of channels.**
+//!   - [ack](./ack/index.html), a multi-producer, multi-consumer queue with at-least-once delivery.
 //!   - [barrier](./barrier/index.html), a oneshot channel that transmits when the sender half is dropped.
 //!   - [broadcast](./broadcast/index.html), a lossless multi-producer, multi-consumer broadcast channel with backpressure (no lagging!).
+//!   - [credit](./credit/index.html), a multi-producer, single-consumer channel with credit-based flow control, granted by the receiver.
 //!   - [dispatch](./dispatch/index.html), a multi-producer, multi-consumer queue.
+//!   - [group](./group/index.html), a multi-producer channel with consumer groups, which share a stream within each group.
 //!   - [mpsc](./mpsc/index.html), a multi-producer, single-consumer channel.
 //!   - [oneshot](./oneshot/index.html), a oneshot transfer channel.
 //!   - [watch](./watch/index.html), a state distribution channel with a value that can be borrowed.
@@ -16,35 +19,107 @@
 //! - Comes with **built-in [Sink](./sink/trait.Sink.html) and [Stream](./stream/trait.Stream.html) combinators.**
 //!   - Sinks can be chained, and filtered.
 //!   - Streams can be chained, filtered, mapped, and merged.
+//!   - Streams can be [forwarded](./fn.forward.html) into sinks, with backpressure and closure propagated in both directions.
 //!   - With the `logging` feature, Sinks and streams can log their values.  This is really helpful when debugging applications.
 //!
 //! See [the readme](https://github.com/austinjones/postage-rs#benchmarks) for benchmarks.
 //!
 //! ## Cargo features:
+//! - `async-std` - enables [AsyncStdSpawner](./spawn/struct.AsyncStdSpawner.html).
+//! - `bincode` - enables the [Bincode](./codec/struct.Bincode.html) codec.
 //! - `blocking (default)` - enables [Sink::blocking_send](./sink/trait.Sink.html#method.blocking_send) and [Stream::blocking_recv](./stream/trait.Stream.html#method.blocking_recv)
+//! - `codec` - enables the [codec](./codec/index.html) module, which sends typed messages over byte-oriented sinks and streams.
 //! - `debug` - enables _extremely verbose_ internal log statements.
-//! - `futures-traits` - enables `futures::Sink` and `futures::Stream` implementations for the postage channels.  Compatible with `v0.3`.
+//! - `diagnostics` - enables [registry::diagnostics](./registry/fn.diagnostics.html), which reports the tasks parked on each channel, and how long its receivers have been stalled.
+//! - `durable` - enables the [durable](./durable/index.html) channel, which persists messages to a segmented log, and resumes from a persisted cursor after a restart.
+//! - `embassy` - enables the [embassy](./embassy/index.html) module, with channels for embedded executors which are constructed in a `static`, and don't allocate.  Locking and task wakeups use `embassy_sync`.
+//! - `futures-traits` - enables `futures::Sink` and `futures::Stream` implementations for the postage channels.  Every receiver type implements `futures::Stream` directly, so receivers can be passed to `StreamExt` or `SelectAll` without a wrapper.  Compatible with `v0.3`.  Depends only on `futures-core` and `futures-sink`, rather than the full `futures` crate.
+//! - `io` - enables [io::duplex](./io/fn.duplex.html), an in-memory byte pipe with handles that implement tokio and `futures-io` `AsyncRead` and `AsyncWrite`.
+//! - `json` - enables the [JsonLines](./codec/struct.JsonLines.html) codec.
 //! - `logging (default)` - enables the enables [Sink::log(Level)](./sink/trait.Sink.html#method.log) and [Stream::log(Level)](./stream/trait.Stream.html#method.log) combinators.
+//! - `metrics` - enables [MetricsRs](./metrics/struct.MetricsRs.html), which records channel [metrics](./metrics/index.html) to the `metrics` crate.
+//! - `nightly` - implements the nightly `AsyncIterator` trait for the postage receivers and stream combinators.  Requires a nightly compiler.
+//! - `rayon` - enables [Stream::par_bridge](./stream/trait.Stream.html#method.par_bridge), which fans messages out across a rayon pool.
+//! - `registry` - enables [registry::dump](./registry/fn.dump.html), which lists every live channel with its capacity, depth, and handle counts.
+//! - `serde` - enables serialization of [replay::Recording](./replay/struct.Recording.html), and [mpsc::FrozenChannel](./mpsc/struct.FrozenChannel.html), which captures an mpsc channel so it can be restored after a restart.
+//! - `smol` - enables [SmolSpawner](./spawn/struct.SmolSpawner.html).
+//! - `spill` - enables the [spill](./spill/index.html) channel, which spills messages to a temporary file when its memory buffer is full.
+//! - `test-util` - enables the [test](./test/index.html) module, with a deterministic channel for unit tests.
+//! - `time` - enables the [time](./time/index.html) module, with an [interval](./time/fn.interval.html) stream, and [DeadlineReceiver](./time/struct.DeadlineReceiver.html) for receiving with a deadline.  Also enables the [retry](./sink/trait.Sink.html#method.retry) and [circuit_breaker](./sink/trait.Sink.html#method.circuit_breaker) sink combinators, [replay_timed](./replay/fn.replay_timed.html), and ack redelivery wakeups.  Timers are driven by tokio.
+//! - `time-async-io` - enables the same timer features as `time`, with timers driven by `async-io`, so they work in async-std, smol, or any executor.
+//! - `tokio` - enables [TokioSpawner](./spawn/struct.TokioSpawner.html).
+//! - `tracing` - emits `tracing` spans and events for channel creation, send, recv, rejection, closure, and lag.  Channels can be named with their `Builder`.
+//!
+//! ## Model checking:
+//! The sync layer can be compiled against [loom](https://docs.rs/loom) with `RUSTFLAGS="--cfg postage_loom"`.
+//! The scenarios in `tests/loom.rs` can be run with `RUSTFLAGS="--cfg postage_loom" cargo test --test loom --release`.
+
+#![cfg_attr(feature = "nightly", feature(async_iterator))]
 
+pub mod actor;
+pub mod channel;
 mod channels;
+#[cfg(feature = "codec")]
+pub mod codec;
 mod context;
+pub mod dead_letter;
+#[cfg(feature = "durable")]
+pub mod durable;
+#[cfg(feature = "embassy")]
+pub mod embassy;
+pub mod event_bus;
+mod forward;
+pub mod instrument;
+#[cfg(feature = "io")]
+pub mod io;
 mod logging;
+pub mod metrics;
+pub mod pipeline;
 pub mod prelude;
+#[cfg(feature = "registry")]
+pub mod registry;
+#[cfg(not(feature = "registry"))]
+mod registry;
+pub mod replay;
+pub mod router;
+pub mod scatter_gather;
+pub mod select;
+pub mod singleflight;
 pub mod sink;
+pub mod spawn;
+pub mod stop;
 pub mod stream;
 mod sync;
+#[cfg(feature = "timer")]
+pub mod time;
+#[cfg(all(
+    feature = "timer",
+    not(any(feature = "time", feature = "time-async-io"))
+))]
+compile_error!(
+    "the timer feature requires a backend.  enable the time or time-async-io feature instead"
+);
+mod trace;
+pub mod txn;
+pub mod watermark;
 
 #[cfg(feature = "futures-traits")]
 mod futures;
 
+pub use channels::ack;
 pub use channels::barrier;
 pub use channels::broadcast;
+pub use channels::credit;
 pub use channels::dispatch;
+pub use channels::group;
 pub use channels::mpsc;
 pub use channels::oneshot;
+#[cfg(feature = "spill")]
+pub use channels::spill;
 pub use channels::watch;
 
 pub use context::Context;
+pub use forward::{forward, forward_map, Forward};
 
-#[cfg(test)]
-mod test;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test;
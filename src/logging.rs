@@ -1,6 +1,7 @@
 #![allow(dead_code)]
 
 #[cfg(feature = "debug")]
+#[allow(unused_imports)]
 pub use debug_impl::*;
 
 #[cfg(feature = "debug")]
@@ -0,0 +1,393 @@
+//! Hooks for observing channel health.
+//!
+//! Implement [ChannelMetrics](./trait.ChannelMetrics.html), and install it on a channel with the channel `Builder`,
+//! or for all channels with [set_global](./fn.set_global.html).
+//!
+//! ```rust
+//! use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+//! use postage::{mpsc, metrics::{ChannelLabels, ChannelMetrics}, sink::Sink};
+//!
+//! #[derive(Default)]
+//! struct SendCounter(AtomicUsize);
+//!
+//! impl ChannelMetrics for SendCounter {
+//!     fn on_send(&self, _channel: &ChannelLabels) {
+//!         self.0.fetch_add(1, Ordering::Relaxed);
+//!     }
+//! }
+//!
+//! let counter = Arc::new(SendCounter::default());
+//! let (mut tx, _rx) = mpsc::Builder::new(4).metrics(counter.clone()).build();
+//! tx.try_send(1usize).ok();
+//!
+//! assert_eq!(1, counter.0.load(Ordering::Relaxed));
+//! ```
+use std::{fmt, sync::Arc, time::Duration};
+
+use parking_lot::RwLock;
+
+static GLOBAL: GlobalHook = GlobalHook::new();
+
+/// Installs a metrics hook for all channels which are constructed after the call,
+/// and do not have a hook configured with their `Builder`.
+pub fn set_global(metrics: Arc<dyn ChannelMetrics>) {
+    GLOBAL.set(Some(metrics));
+}
+
+/// Removes the global metrics hook.  Existing channels keep the hook they were constructed with.
+pub fn clear_global() {
+    GLOBAL.set(None);
+}
+
+// the slot for the global hook.  tests construct their own, so they don't install hooks on other tests' channels
+struct GlobalHook(RwLock<Option<Arc<dyn ChannelMetrics>>>);
+
+impl GlobalHook {
+    const fn new() -> Self {
+        Self(parking_lot::const_rwlock(None))
+    }
+
+    fn set(&self, metrics: Option<Arc<dyn ChannelMetrics>>) {
+        *self.0.write() = metrics;
+    }
+
+    fn get(&self) -> Option<Arc<dyn ChannelMetrics>> {
+        self.0.read().clone()
+    }
+}
+
+/// Receives measurements from channels.  All methods have empty default implementations.
+///
+/// Hooks are called inline during send and receive operations, so implementations should be cheap.
+pub trait ChannelMetrics: Send + Sync {
+    /// Called when the channel accepts a message.
+    fn on_send(&self, _channel: &ChannelLabels) {}
+
+    /// Called when a receiver takes a message from the channel.
+    fn on_recv(&self, _channel: &ChannelLabels) {}
+
+    /// Called when the channel rejects a message because it is closed.
+    fn on_reject(&self, _channel: &ChannelLabels) {}
+
+    /// Reports the number of messages in the channel buffer.
+    fn set_depth(&self, _channel: &ChannelLabels, _depth: usize) {}
+
+    /// Reports the number of sender tasks which are waiting for capacity.
+    fn set_blocked_senders(&self, _channel: &ChannelLabels, _blocked: usize) {}
+
+    /// Reports the time a message spent in the channel buffer, from send to receive.
+    fn record_time_in_queue(&self, _channel: &ChannelLabels, _duration: Duration) {}
+
+    /// Reports the time a [Measured](../instrument/struct.Measured.html) stream or sink waited for a message to be received or accepted.
+    fn record_wait(&self, _channel: &ChannelLabels, _duration: Duration) {}
+
+    /// Reports the time between a [Measured](../instrument/struct.Measured.html) operation completing and the next poll.
+    fn record_processing(&self, _channel: &ChannelLabels, _duration: Duration) {}
+}
+
+/// Identifies the channel which produced a measurement.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChannelLabels {
+    kind: &'static str,
+    name: Option<Arc<str>>,
+}
+
+impl ChannelLabels {
+    pub(crate) fn new(kind: &'static str, name: Option<&str>) -> Self {
+        Self {
+            kind,
+            name: name.map(Arc::from),
+        }
+    }
+
+    /// The type of channel, such as `mpsc` or `broadcast`.
+    pub fn kind(&self) -> &'static str {
+        self.kind
+    }
+
+    /// The name given to the channel `Builder`, if any.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+/// A channel's installed hook, along with the labels that identify the channel.
+#[derive(Clone)]
+pub(crate) struct MetricsHook {
+    metrics: Arc<dyn ChannelMetrics>,
+    labels: ChannelLabels,
+}
+
+impl MetricsHook {
+    /// Uses the hook configured on the builder, falling back to the global hook.
+    pub fn resolve(
+        metrics: Option<Arc<dyn ChannelMetrics>>,
+        kind: &'static str,
+        name: Option<&str>,
+    ) -> Option<Self> {
+        Self::resolve_from(&GLOBAL, metrics, kind, name)
+    }
+
+    fn resolve_from(
+        global: &GlobalHook,
+        metrics: Option<Arc<dyn ChannelMetrics>>,
+        kind: &'static str,
+        name: Option<&str>,
+    ) -> Option<Self> {
+        let metrics = metrics.or_else(|| global.get())?;
+
+        Some(Self {
+            metrics,
+            labels: ChannelLabels::new(kind, name),
+        })
+    }
+
+    pub fn on_send(&self, depth: Option<usize>) {
+        self.metrics.on_send(&self.labels);
+
+        if let Some(depth) = depth {
+            self.metrics.set_depth(&self.labels, depth);
+        }
+    }
+
+    pub fn on_recv(&self, depth: Option<usize>, time_in_queue: Option<Duration>) {
+        self.metrics.on_recv(&self.labels);
+
+        if let Some(depth) = depth {
+            self.metrics.set_depth(&self.labels, depth);
+        }
+
+        if let Some(duration) = time_in_queue {
+            self.metrics.record_time_in_queue(&self.labels, duration);
+        }
+    }
+
+    pub fn on_reject(&self) {
+        self.metrics.on_reject(&self.labels);
+    }
+
+    pub fn set_blocked_senders(&self, blocked: usize) {
+        self.metrics.set_blocked_senders(&self.labels, blocked);
+    }
+
+    pub fn record_wait(&self, duration: Duration) {
+        self.metrics.record_wait(&self.labels, duration);
+    }
+
+    pub fn record_processing(&self, duration: Duration) {
+        self.metrics.record_processing(&self.labels, duration);
+    }
+}
+
+impl fmt::Debug for MetricsHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MetricsHook")
+            .field("labels", &self.labels)
+            .finish()
+    }
+}
+
+/// A [ChannelMetrics](./trait.ChannelMetrics.html) implementation which records to the `metrics` crate facade.
+///
+/// Measurements are labeled with `kind` and `name`:
+/// - `postage_sends_total`, `postage_receives_total`, and `postage_rejections_total` counters
+/// - `postage_depth` and `postage_blocked_senders` gauges
+/// - `postage_time_in_queue_seconds`, `postage_wait_seconds`, and `postage_processing_seconds` histograms
+///
+/// Requires the `metrics` feature.
+#[cfg(feature = "metrics")]
+#[derive(Clone, Debug, Default)]
+pub struct MetricsRs;
+
+#[cfg(feature = "metrics")]
+impl MetricsRs {
+    fn labels(channel: &ChannelLabels) -> [::metrics::Label; 2] {
+        let name: ::metrics::SharedString = match &channel.name {
+            Some(name) => name.clone().into(),
+            None => "".into(),
+        };
+
+        [
+            ::metrics::Label::new("kind", channel.kind),
+            ::metrics::Label::new("name", name),
+        ]
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl ChannelMetrics for MetricsRs {
+    fn on_send(&self, channel: &ChannelLabels) {
+        ::metrics::counter!("postage_sends_total", Self::labels(channel).iter()).increment(1);
+    }
+
+    fn on_recv(&self, channel: &ChannelLabels) {
+        ::metrics::counter!("postage_receives_total", Self::labels(channel).iter()).increment(1);
+    }
+
+    fn on_reject(&self, channel: &ChannelLabels) {
+        ::metrics::counter!("postage_rejections_total", Self::labels(channel).iter()).increment(1);
+    }
+
+    fn set_depth(&self, channel: &ChannelLabels, depth: usize) {
+        ::metrics::gauge!("postage_depth", Self::labels(channel).iter()).set(depth as f64);
+    }
+
+    fn set_blocked_senders(&self, channel: &ChannelLabels, blocked: usize) {
+        ::metrics::gauge!("postage_blocked_senders", Self::labels(channel).iter())
+            .set(blocked as f64);
+    }
+
+    fn record_time_in_queue(&self, channel: &ChannelLabels, duration: Duration) {
+        ::metrics::histogram!(
+            "postage_time_in_queue_seconds",
+            Self::labels(channel).iter()
+        )
+        .record(duration.as_secs_f64());
+    }
+
+    fn record_wait(&self, channel: &ChannelLabels, duration: Duration) {
+        ::metrics::histogram!("postage_wait_seconds", Self::labels(channel).iter())
+            .record(duration.as_secs_f64());
+    }
+
+    fn record_processing(&self, channel: &ChannelLabels, duration: Duration) {
+        ::metrics::histogram!("postage_processing_seconds", Self::labels(channel).iter())
+            .record(duration.as_secs_f64());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        pin::Pin,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use parking_lot::Mutex;
+
+    use super::{ChannelLabels, ChannelMetrics, GlobalHook, MetricsHook};
+    use crate::{
+        mpsc,
+        sink::{PollSend, Sink},
+        stream::Stream,
+    };
+
+    #[derive(Default)]
+    struct Recorder {
+        sends: AtomicUsize,
+        recvs: AtomicUsize,
+        rejects: AtomicUsize,
+        depth: AtomicUsize,
+        blocked: AtomicUsize,
+        time_in_queue: Mutex<Vec<Duration>>,
+    }
+
+    impl ChannelMetrics for Recorder {
+        fn on_send(&self, _channel: &ChannelLabels) {
+            self.sends.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn on_recv(&self, _channel: &ChannelLabels) {
+            self.recvs.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn on_reject(&self, _channel: &ChannelLabels) {
+            self.rejects.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn set_depth(&self, _channel: &ChannelLabels, depth: usize) {
+            self.depth.store(depth, Ordering::SeqCst);
+        }
+
+        fn set_blocked_senders(&self, _channel: &ChannelLabels, blocked: usize) {
+            self.blocked.store(blocked, Ordering::SeqCst);
+        }
+
+        fn record_time_in_queue(&self, _channel: &ChannelLabels, duration: Duration) {
+            self.time_in_queue.lock().push(duration);
+        }
+    }
+
+    #[test]
+    fn mpsc_counters() {
+        let recorder = Arc::new(Recorder::default());
+        let (mut tx, mut rx) = mpsc::Builder::new(2).metrics(recorder.clone()).build();
+
+        tx.try_send(1usize).unwrap();
+        tx.try_send(2usize).unwrap();
+        assert_eq!(2, recorder.sends.load(Ordering::SeqCst));
+        assert_eq!(2, recorder.depth.load(Ordering::SeqCst));
+
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(1, recorder.recvs.load(Ordering::SeqCst));
+        assert_eq!(1, recorder.depth.load(Ordering::SeqCst));
+        assert_eq!(1, recorder.time_in_queue.lock().len());
+
+        drop(rx);
+        assert!(tx.try_send(3usize).is_err());
+        assert_eq!(1, recorder.rejects.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn blocked_senders() {
+        let recorder = Arc::new(Recorder::default());
+        let (mut tx, mut rx) = mpsc::Builder::new(1).metrics(recorder.clone()).build();
+
+        let mut cx = crate::test::noop_context();
+        let waker = futures_test::task::noop_waker();
+        let mut waker_cx = crate::Context::from_waker(&waker);
+
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, 1usize)
+        );
+        assert_eq!(
+            PollSend::Pending(2),
+            Pin::new(&mut tx).poll_send(&mut waker_cx, 2usize)
+        );
+        assert_eq!(1, recorder.blocked.load(Ordering::SeqCst));
+
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(0, recorder.blocked.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn global_hook() {
+        struct NamedCounter(AtomicUsize);
+
+        impl ChannelMetrics for NamedCounter {
+            fn on_send(&self, channel: &ChannelLabels) {
+                if channel.name() == Some("global_hook") {
+                    self.0.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        }
+
+        // a local slot, as the process-wide hook would be installed on channels built by concurrent tests
+        let global = GlobalHook::new();
+        let counter = Arc::new(NamedCounter(AtomicUsize::new(0)));
+        global.set(Some(counter.clone()));
+
+        let hook = MetricsHook::resolve_from(&global, None, "mpsc", Some("global_hook")).unwrap();
+        let configured = Arc::new(Recorder::default());
+        let overridden = MetricsHook::resolve_from(
+            &global,
+            Some(configured.clone()),
+            "mpsc",
+            Some("global_hook"),
+        )
+        .unwrap();
+
+        global.set(None);
+        assert!(MetricsHook::resolve_from(&global, None, "mpsc", Some("global_hook")).is_none());
+
+        hook.on_send(None);
+        overridden.on_send(None);
+        assert_eq!(1, counter.0.load(Ordering::SeqCst));
+        assert_eq!(1, configured.sends.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,275 @@
+//! A fluent builder for multi-stage pipelines.
+//!
+//! A pipeline starts with [from](./fn.from.html), transforms messages with `map` and `filter`, and ends when it is connected
+//! to a sink with [into](./struct.Stage.html#method.into).  Each call to [buffer](./struct.Stage.html#method.buffer) inserts an
+//! mpsc channel between the stages, so the stages before the buffer can run ahead of the stages after it.
+//!
+//! `into` returns a single [Pipeline](./struct.Pipeline.html) future, which drives every stage.  It can be awaited,
+//! or spawned onto any executor.  The pipeline completes when the source closes and every message has been forwarded,
+//! or when the sink rejects a message.
+//!
+//! ```rust
+//! use postage::{mpsc, pipeline, prelude::*};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let (mut input, rx) = mpsc::channel(4);
+//!     let (tx, mut output) = mpsc::channel(4);
+//!
+//!     let driver = pipeline::from(rx)
+//!         .map(|i: usize| i * 10)
+//!         .filter(|i| *i > 10)
+//!         .buffer(64)
+//!         .into(tx);
+//!
+//!     tokio::spawn(driver);
+//!
+//!     input.send(1).await.ok();
+//!     input.send(2).await.ok();
+//!     input.send(3).await.ok();
+//!     drop(input);
+//!
+//!     assert_eq!(Some(20), output.recv().await);
+//!     assert_eq!(Some(30), output.recv().await);
+//!     assert_eq!(None, output.recv().await);
+//! }
+//! ```
+
+use std::{fmt, future::Future, pin::Pin, task::Poll};
+
+use pin_project::pin_project;
+
+use crate::{
+    forward::{forward, Forward},
+    mpsc,
+    sink::Sink,
+    stream::{filter::FilterStream, map::MapStream, Stream},
+};
+
+type Driver = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Starts a pipeline, which reads messages from the stream
+pub fn from<S>(stream: S) -> Stage<S>
+where
+    S: Stream,
+{
+    Stage {
+        stream,
+        drivers: Vec::new(),
+    }
+}
+
+/// A pipeline which has not yet been connected to a sink.
+pub struct Stage<S> {
+    stream: S,
+    drivers: Vec<Driver>,
+}
+
+impl<S> Stage<S>
+where
+    S: Stream,
+{
+    /// Transforms each message with the map function
+    pub fn map<Map, Into>(self, map: Map) -> Stage<MapStream<S, Map, Into>>
+    where
+        Map: Fn(S::Item) -> Into,
+    {
+        Stage {
+            stream: self.stream.map(map),
+            drivers: self.drivers,
+        }
+    }
+
+    /// Drops the messages where `filter` returns false
+    pub fn filter<Filter>(self, filter: Filter) -> Stage<FilterStream<S, Filter>>
+    where
+        S: Unpin,
+        Filter: FnMut(&S::Item) -> bool + Unpin,
+    {
+        Stage {
+            stream: self.stream.filter(filter),
+            drivers: self.drivers,
+        }
+    }
+
+    /// Inserts an mpsc channel with the given capacity.  The stages before the buffer forward messages
+    /// into the channel, and the stages after the buffer receive from it.
+    pub fn buffer(self, capacity: usize) -> Stage<mpsc::Receiver<S::Item>>
+    where
+        S: Send + 'static,
+        S::Item: Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel(capacity);
+        let mut drivers = self.drivers;
+        let stream = self.stream;
+        drivers.push(Box::pin(async move {
+            forward(stream, tx).await.ok();
+        }));
+
+        Stage {
+            stream: rx,
+            drivers,
+        }
+    }
+
+    /// Connects the pipeline to the sink, returning a future which drives every stage
+    pub fn into<K>(self, sink: K) -> Pipeline<S, K>
+    where
+        K: Sink<Item = S::Item>,
+    {
+        Pipeline {
+            forward: forward(self.stream, sink),
+            drivers: self.drivers.into_iter().map(Some).collect(),
+        }
+    }
+}
+
+impl<S> fmt::Debug for Stage<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stage")
+            .field("buffers", &self.drivers.len())
+            .finish()
+    }
+}
+
+/// A future which drives the stages of a pipeline.  Completes when the last stage completes.
+#[must_use = "futures do nothing unless polled"]
+#[pin_project]
+pub struct Pipeline<S, K>
+where
+    S: Stream,
+{
+    #[pin]
+    forward: Forward<S, K>,
+    drivers: Vec<Option<Driver>>,
+}
+
+impl<S, K> Future for Pipeline<S, K>
+where
+    S: Stream,
+    K: Sink<Item = S::Item>,
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        for slot in this.drivers.iter_mut() {
+            if let Some(driver) = slot {
+                if driver.as_mut().poll(cx).is_ready() {
+                    // completed stages are dropped, so the stage after the buffer observes the closure
+                    *slot = None;
+                }
+            }
+        }
+
+        if this.forward.poll(cx).is_ready() {
+            // the source closed and was drained, or the sink was closed.  dropping the remaining stages
+            // closes the channels between them, and the source.
+            this.drivers.clear();
+            return Poll::Ready(());
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<S, K> fmt::Debug for Pipeline<S, K>
+where
+    S: Stream,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pipeline")
+            .field("buffers", &self.drivers.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::Future, pin::Pin, task::Poll};
+
+    use futures_test::task::noop_context;
+
+    use super::from;
+    use crate::{mpsc, sink::Sink, stream::Stream};
+
+    #[test]
+    fn map_filter() {
+        let (mut input, rx) = mpsc::channel(4);
+        let (tx, mut output) = mpsc::channel(4);
+        let mut pipeline = from(rx).map(|i: usize| i + 1).filter(|i| *i != 2).into(tx);
+
+        input.try_send(1).unwrap();
+        input.try_send(2).unwrap();
+        assert_eq!(
+            Poll::Pending,
+            Pin::new(&mut pipeline).poll(&mut noop_context())
+        );
+        assert_eq!(Ok(3), output.try_recv());
+
+        drop(input);
+        assert_eq!(
+            Poll::Ready(()),
+            Pin::new(&mut pipeline).poll(&mut noop_context())
+        );
+        drop(pipeline);
+        assert!(output.try_recv().is_err());
+    }
+
+    #[test]
+    fn buffers() {
+        let (mut input, rx) = mpsc::channel(8);
+        let (tx, mut output) = mpsc::channel(1);
+        let mut pipeline = from(rx).buffer(4).map(|i: usize| i * 2).buffer(4).into(tx);
+
+        for i in 0..4 {
+            input.try_send(i).unwrap();
+        }
+        drop(input);
+
+        let mut received = Vec::new();
+        loop {
+            let poll = Pin::new(&mut pipeline).poll(&mut noop_context());
+            while let Ok(value) = output.try_recv() {
+                received.push(value);
+            }
+
+            if poll.is_ready() {
+                break;
+            }
+        }
+
+        assert_eq!(vec![0, 2, 4, 6], received);
+    }
+
+    #[test]
+    fn sink_rejects() {
+        let (mut input, rx) = mpsc::channel(4);
+        let (tx, output) = mpsc::channel(4);
+        let mut pipeline = from(rx).buffer(2).into(tx);
+
+        input.try_send(1usize).unwrap();
+        drop(output);
+
+        assert_eq!(
+            Poll::Ready(()),
+            Pin::new(&mut pipeline).poll(&mut noop_context())
+        );
+        assert!(input.try_send(2).is_err());
+    }
+
+    #[tokio::test]
+    async fn spawned() {
+        let (mut input, rx) = mpsc::channel(4);
+        let (tx, mut output) = mpsc::channel(4);
+        let handle = tokio::spawn(from(rx).buffer(4).map(|i: usize| i + 1).into(tx));
+
+        input.send(1).await.ok();
+        assert_eq!(Some(2), output.recv().await);
+
+        drop(input);
+        assert_eq!(None, output.recv().await);
+        handle.await.unwrap();
+    }
+}
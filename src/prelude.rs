@@ -1,3 +1,16 @@
-//! Imports the Sink and Stream traits.
-pub use crate::sink::Sink;
-pub use crate::stream::Stream;
+//! Imports the Sink and Stream traits, their extension traits, and the channel modules.
+//!
+//! ```rust
+//! use postage::prelude::*;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let (mut tx, mut rx) = mpsc::channel(4);
+//!     tx.send(1usize).await.ok();
+//!     assert_eq!(Some(1), rx.recv().await);
+//! }
+//! ```
+pub use crate::sink::{BufferedSink, Sink};
+pub use crate::stream::{Stream, TryStream};
+
+pub use crate::{ack, barrier, broadcast, dispatch, group, mpsc, oneshot, watch};
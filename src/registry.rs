@@ -0,0 +1,464 @@
+//! A global registry of live channels, for debugging stalled or backed-up queues.
+//!
+//! With the `registry` feature, every mpsc, dispatch, broadcast, and watch channel is registered when it is constructed,
+//! and removed when the last endpoint is dropped.  Channels can be named with their `Builder`.
+//!
+//! With the `diagnostics` feature, [diagnostics](./fn.diagnostics.html) also reports the tasks parked on each channel,
+//! and how long the receivers have been stalled while messages are buffered.  With the `serde` feature, the reports can be
+//! serialized for an external tool.
+
+#[cfg(feature = "registry")]
+pub use enabled::{dump, ChannelInfo};
+
+#[cfg(feature = "diagnostics")]
+pub use enabled::{diagnostics, ChannelDiagnostics};
+
+#[cfg(feature = "registry")]
+pub(crate) use enabled::Registration;
+
+#[cfg(not(feature = "registry"))]
+pub(crate) use disabled::Registration;
+
+#[cfg(feature = "registry")]
+mod enabled {
+    use std::{
+        fmt,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Weak,
+        },
+    };
+
+    use parking_lot::Mutex;
+
+    #[cfg(feature = "diagnostics")]
+    use std::time::{Duration, Instant};
+
+    use crate::sync::notifier::Notifier;
+
+    static REGISTRY: Mutex<Vec<Weak<Entry>>> = parking_lot::const_mutex(Vec::new());
+
+    /// Returns a snapshot of every live channel, in the order the channels were constructed.
+    ///
+    /// ```rust
+    /// use postage::{mpsc, registry, sink::Sink};
+    ///
+    /// let (mut tx, _rx) = mpsc::Builder::new(4).name("requests").build();
+    /// tx.try_send(1usize).ok();
+    ///
+    /// for channel in registry::dump() {
+    ///     println!("{}", channel);
+    /// }
+    /// ```
+    pub fn dump() -> Vec<ChannelInfo> {
+        let mut registry = REGISTRY.lock();
+        registry.retain(|entry| entry.strong_count() > 0);
+
+        registry
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|entry| entry.info())
+            .collect()
+    }
+
+    /// Returns diagnostics for every live channel, in the order the channels were constructed.
+    ///
+    /// Requires the `diagnostics` feature.
+    ///
+    /// ```rust
+    /// use postage::{mpsc, registry, sink::Sink};
+    ///
+    /// let (mut tx, _rx) = mpsc::Builder::new(1).name("stalled").build();
+    /// tx.try_send(1usize).ok();
+    ///
+    /// for channel in registry::diagnostics() {
+    ///     println!("{}", channel);
+    /// }
+    /// ```
+    #[cfg(feature = "diagnostics")]
+    pub fn diagnostics() -> Vec<ChannelDiagnostics> {
+        let mut registry = REGISTRY.lock();
+        registry.retain(|entry| entry.strong_count() > 0);
+
+        registry
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|entry| entry.diagnostics())
+            .collect()
+    }
+
+    /// A point-in-time description of a live channel.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+    pub struct ChannelInfo {
+        /// The type of channel, such as `mpsc` or `broadcast`.
+        pub kind: &'static str,
+        /// The name given to the channel `Builder`, if any.
+        pub name: Option<String>,
+        /// The buffer capacity, for channels with a fixed-size buffer.
+        pub capacity: Option<usize>,
+        /// The number of messages in the buffer, for channels which track it.
+        pub depth: Option<usize>,
+        /// The number of live sender handles.
+        pub senders: usize,
+        /// The number of live receiver handles.
+        pub receivers: usize,
+    }
+
+    impl fmt::Display for ChannelInfo {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "{} {}",
+                self.kind,
+                self.name.as_deref().unwrap_or("<unnamed>")
+            )?;
+
+            match (self.depth, self.capacity) {
+                (Some(depth), Some(capacity)) => write!(f, " depth {}/{}", depth, capacity)?,
+                (None, Some(capacity)) => write!(f, " capacity {}", capacity)?,
+                _ => {}
+            }
+
+            write!(f, " senders {} receivers {}", self.senders, self.receivers)
+        }
+    }
+
+    /// A point-in-time description of a live channel, and the tasks which are waiting on it.
+    ///
+    /// Requires the `diagnostics` feature.
+    #[cfg(feature = "diagnostics")]
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+    pub struct ChannelDiagnostics {
+        /// The channel's kind, name, capacity, depth, and handle counts.
+        pub info: ChannelInfo,
+        /// The number of wakers registered by senders which are waiting for capacity.
+        pub parked_senders: usize,
+        /// The number of wakers registered by receivers which are waiting for messages.
+        pub parked_receivers: usize,
+        /// For channels which track their depth, the time since a receiver last took a message while messages were buffered.
+        /// This is how long the message at the front of the buffer has been waiting, at most.
+        pub head_wait: Option<Duration>,
+    }
+
+    #[cfg(feature = "diagnostics")]
+    impl fmt::Display for ChannelDiagnostics {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "{} parked senders {} receivers {}",
+                self.info, self.parked_senders, self.parked_receivers
+            )?;
+
+            if let Some(head_wait) = self.head_wait {
+                write!(f, " head wait {:?}", head_wait)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    struct Entry {
+        kind: &'static str,
+        name: Option<String>,
+        capacity: Option<usize>,
+        depth: Option<AtomicUsize>,
+        senders: AtomicUsize,
+        receivers: AtomicUsize,
+        #[cfg(feature = "diagnostics")]
+        notifiers: Mutex<Option<(Weak<Notifier>, Weak<Notifier>)>>,
+        #[cfg(feature = "diagnostics")]
+        head_since: Mutex<Option<Instant>>,
+    }
+
+    impl Entry {
+        fn info(&self) -> ChannelInfo {
+            ChannelInfo {
+                kind: self.kind,
+                name: self.name.clone(),
+                capacity: self.capacity,
+                depth: self
+                    .depth
+                    .as_ref()
+                    .map(|depth| depth.load(Ordering::Relaxed)),
+                senders: self.senders.load(Ordering::Relaxed),
+                receivers: self.receivers.load(Ordering::Relaxed),
+            }
+        }
+
+        #[cfg(feature = "diagnostics")]
+        fn diagnostics(&self) -> ChannelDiagnostics {
+            let waiting = |notifier: &Weak<Notifier>| {
+                notifier
+                    .upgrade()
+                    .map(|notifier| notifier.waiting())
+                    .unwrap_or(0)
+            };
+
+            let (parked_senders, parked_receivers) = match *self.notifiers.lock() {
+                Some((ref senders, ref receivers)) => (waiting(senders), waiting(receivers)),
+                None => (0, 0),
+            };
+
+            ChannelDiagnostics {
+                info: self.info(),
+                parked_senders,
+                parked_receivers,
+                head_wait: self.head_since.lock().map(|since| since.elapsed()),
+            }
+        }
+    }
+
+    /// A channel's entry in the global registry.  The entry is removed when the registration is dropped.
+    pub(crate) struct Registration {
+        entry: Arc<Entry>,
+    }
+
+    impl Registration {
+        /// Registers a channel with one sender and one receiver.
+        /// If `tracks_depth` is set, the channel reports its buffer depth with `set_depth`.
+        pub fn new(
+            kind: &'static str,
+            name: Option<&str>,
+            capacity: Option<usize>,
+            tracks_depth: bool,
+        ) -> Self {
+            let entry = Arc::new(Entry {
+                kind,
+                name: name.map(String::from),
+                capacity,
+                depth: if tracks_depth {
+                    Some(AtomicUsize::new(0))
+                } else {
+                    None
+                },
+                senders: AtomicUsize::new(1),
+                receivers: AtomicUsize::new(1),
+                #[cfg(feature = "diagnostics")]
+                notifiers: Mutex::new(None),
+                #[cfg(feature = "diagnostics")]
+                head_since: Mutex::new(None),
+            });
+
+            let mut registry = REGISTRY.lock();
+            registry.retain(|entry| entry.strong_count() > 0);
+            registry.push(Arc::downgrade(&entry));
+
+            Self { entry }
+        }
+
+        pub fn set_depth(&self, depth: usize) {
+            if let Some(ref stored) = self.entry.depth {
+                let _previous = stored.swap(depth, Ordering::Relaxed);
+
+                #[cfg(feature = "diagnostics")]
+                {
+                    // the wait restarts when the buffer fills from empty, or a receiver takes a message
+                    let mut head_since = self.entry.head_since.lock();
+                    if depth == 0 {
+                        *head_since = None;
+                    } else if depth < _previous || head_since.is_none() {
+                        *head_since = Some(Instant::now());
+                    }
+                }
+            }
+        }
+
+        /// Records the channel's notifiers, so diagnostics can report the parked tasks
+        pub fn attach(&self, _sender_notify: &Arc<Notifier>, _receiver_notify: &Arc<Notifier>) {
+            #[cfg(feature = "diagnostics")]
+            {
+                *self.entry.notifiers.lock() = Some((
+                    Arc::downgrade(_sender_notify),
+                    Arc::downgrade(_receiver_notify),
+                ));
+            }
+        }
+
+        pub fn sender_added(&self) {
+            self.entry.senders.fetch_add(1, Ordering::Relaxed);
+        }
+
+        pub fn sender_dropped(&self) {
+            self.entry.senders.fetch_sub(1, Ordering::Relaxed);
+        }
+
+        pub fn receiver_added(&self) {
+            self.entry.receivers.fetch_add(1, Ordering::Relaxed);
+        }
+
+        pub fn receiver_dropped(&self) {
+            self.entry.receivers.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+
+    impl fmt::Debug for Registration {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_tuple("Registration")
+                .field(&self.entry.info())
+                .finish()
+        }
+    }
+}
+
+#[cfg(not(feature = "registry"))]
+mod disabled {
+    use crate::sync::notifier::Notifier;
+
+    #[derive(Debug)]
+    pub(crate) struct Registration;
+
+    #[allow(dead_code)]
+    impl Registration {
+        #[inline]
+        pub fn new(
+            _kind: &'static str,
+            _name: Option<&str>,
+            _capacity: Option<usize>,
+            _tracks_depth: bool,
+        ) -> Self {
+            Self
+        }
+
+        #[inline]
+        pub fn set_depth(&self, _depth: usize) {}
+
+        #[inline]
+        pub fn attach(
+            &self,
+            _sender_notify: &std::sync::Arc<Notifier>,
+            _receiver_notify: &std::sync::Arc<Notifier>,
+        ) {
+        }
+
+        #[inline]
+        pub fn sender_added(&self) {}
+
+        #[inline]
+        pub fn sender_dropped(&self) {}
+
+        #[inline]
+        pub fn receiver_added(&self) {}
+
+        #[inline]
+        pub fn receiver_dropped(&self) {}
+    }
+}
+
+#[cfg(all(test, feature = "registry"))]
+mod tests {
+    use super::{dump, ChannelInfo};
+    use crate::{broadcast, mpsc, sink::Sink, stream::Stream, watch};
+
+    fn find(name: &str) -> Option<ChannelInfo> {
+        dump()
+            .into_iter()
+            .find(|info| info.name.as_deref() == Some(name))
+    }
+
+    #[test]
+    fn mpsc_depth_and_handles() {
+        let (mut tx, mut rx) = mpsc::Builder::new(4).name("registry_mpsc").build();
+        let tx2 = tx.clone();
+
+        tx.try_send(1usize).unwrap();
+        tx.try_send(2usize).unwrap();
+        assert_eq!(
+            Some(ChannelInfo {
+                kind: "mpsc",
+                name: Some("registry_mpsc".to_string()),
+                capacity: Some(4),
+                depth: Some(2),
+                senders: 2,
+                receivers: 1,
+            }),
+            find("registry_mpsc")
+        );
+
+        rx.try_recv().unwrap();
+        drop(tx2);
+        let info = find("registry_mpsc").unwrap();
+        assert_eq!(Some(1), info.depth);
+        assert_eq!(1, info.senders);
+    }
+
+    #[test]
+    fn broadcast_subscribe() {
+        let (tx, _rx) = broadcast::Builder::<usize>::new(4)
+            .name("registry_broadcast")
+            .build();
+        let _rx2 = tx.subscribe();
+
+        let info = find("registry_broadcast").unwrap();
+        assert_eq!(None, info.depth);
+        assert_eq!(2, info.receivers);
+    }
+
+    #[test]
+    fn removed_on_drop() {
+        let (tx, rx) = watch::Builder::<usize>::new()
+            .name("registry_watch")
+            .build();
+        assert!(find("registry_watch").is_some());
+
+        drop(tx);
+        assert!(find("registry_watch").is_some());
+
+        drop(rx);
+        assert!(find("registry_watch").is_none());
+    }
+
+    #[cfg(feature = "diagnostics")]
+    #[test]
+    fn diagnostics() {
+        use std::pin::Pin;
+
+        use futures_test::task::noop_context;
+
+        use crate::{sink::PollSend, Context};
+
+        fn find(name: &str) -> super::ChannelDiagnostics {
+            super::diagnostics()
+                .into_iter()
+                .find(|diagnostics| diagnostics.info.name.as_deref() == Some(name))
+                .unwrap()
+        }
+
+        let (mut tx, mut rx) = mpsc::Builder::new(1).name("diagnostics").build();
+        assert_eq!(None, find("diagnostics").head_wait);
+
+        tx.try_send(1usize).unwrap();
+        assert_eq!(
+            PollSend::Pending(2),
+            Pin::new(&mut tx).poll_send(&mut Context::from(&mut noop_context()), 2)
+        );
+
+        let diagnostics = find("diagnostics");
+        assert_eq!(1, diagnostics.parked_senders);
+        assert_eq!(0, diagnostics.parked_receivers);
+        assert!(diagnostics.head_wait.is_some());
+
+        assert_eq!(Ok(1), rx.try_recv());
+        let diagnostics = find("diagnostics");
+        assert_eq!(0, diagnostics.parked_senders);
+        assert_eq!(None, diagnostics.head_wait);
+    }
+
+    #[test]
+    fn display() {
+        let info = ChannelInfo {
+            kind: "mpsc",
+            name: Some("jobs".to_string()),
+            capacity: Some(8),
+            depth: Some(3),
+            senders: 2,
+            receivers: 1,
+        };
+
+        assert_eq!(
+            "mpsc jobs depth 3/8 senders 2 receivers 1",
+            info.to_string()
+        );
+    }
+}
@@ -0,0 +1,433 @@
+//! Records the items flowing through a stream, and replays them as a new stream.
+//!
+//! [record](./fn.record.html) wraps a stream, and captures each item with the time it was received,
+//! relative to the creation of the recorder.  The [Recording](./struct.Recording.html) can be serialized with the `serde` feature,
+//! and later reproduced with [replay](./fn.replay.html) or [replay_timed](./fn.replay_timed.html).
+//!
+//! ```rust
+//! use postage::{mpsc, prelude::*, replay};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let (mut tx, rx) = mpsc::channel(4);
+//!     let (mut rx, log) = replay::record(rx);
+//!
+//!     tx.send(1usize).await.ok();
+//!     tx.send(2usize).await.ok();
+//!     drop(tx);
+//!     while let Some(_) = rx.recv().await {}
+//!
+//!     let mut replayed = replay::replay(log.recording());
+//!     assert_eq!(Some(1), replayed.recv().await);
+//!     assert_eq!(Some(2), replayed.recv().await);
+//!     assert_eq!(None, replayed.recv().await);
+//! }
+//! ```
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+use pin_project::pin_project;
+
+use crate::{
+    stream::{PollRecv, Stream},
+    Context,
+};
+
+#[cfg(feature = "timer")]
+use crate::time::timer::Timer;
+
+/// A log of the items received from a stream, with their timing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Recording<T> {
+    /// The recorded items, in the order they were received
+    pub items: Vec<Recorded<T>>,
+    /// The time at which the stream closed, if it closed while recording
+    pub closed: Option<Duration>,
+}
+
+impl<T> Default for Recording<T> {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            closed: None,
+        }
+    }
+}
+
+/// An item which was received from the recorded stream.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Recorded<T> {
+    /// The time the item was received, relative to the creation of the recorder
+    pub offset: Duration,
+    /// The item
+    pub item: T,
+}
+
+/// Wraps the stream, recording each item it produces.  Items are cloned into the log.
+pub fn record<S>(stream: S) -> (RecordStream<S>, RecordLog<S::Item>)
+where
+    S: Stream,
+    S::Item: Clone,
+{
+    let log = RecordLog {
+        recording: Arc::new(Mutex::new(Recording::default())),
+    };
+
+    let stream = RecordStream {
+        stream,
+        started: Instant::now(),
+        log: log.clone(),
+    };
+
+    (stream, log)
+}
+
+/// A handle to the log of a [RecordStream](./struct.RecordStream.html).
+pub struct RecordLog<T> {
+    recording: Arc<Mutex<Recording<T>>>,
+}
+
+impl<T> RecordLog<T>
+where
+    T: Clone,
+{
+    /// Returns a copy of the items recorded so far
+    pub fn recording(&self) -> Recording<T> {
+        self.recording.lock().clone()
+    }
+}
+
+impl<T> RecordLog<T> {
+    /// Takes the items recorded so far, leaving the log empty
+    pub fn take(&self) -> Recording<T> {
+        std::mem::take(&mut *self.recording.lock())
+    }
+
+    /// The number of items recorded so far
+    pub fn len(&self) -> usize {
+        self.recording.lock().items.len()
+    }
+
+    /// Returns true if no items have been recorded
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Clone for RecordLog<T> {
+    fn clone(&self) -> Self {
+        Self {
+            recording: self.recording.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for RecordLog<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordLog")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// A stream which records the items produced by the wrapped stream.
+#[pin_project]
+pub struct RecordStream<S: Stream> {
+    #[pin]
+    stream: S,
+    started: Instant,
+    log: RecordLog<S::Item>,
+}
+
+impl<S: Stream> RecordStream<S> {
+    /// Returns a reference to the wrapped stream
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Returns a mutable reference to the wrapped stream
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Returns the wrapped stream.  Items which are received later are not recorded.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S> Stream for RecordStream<S>
+where
+    S: Stream,
+    S::Item: Clone,
+{
+    type Item = S::Item;
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item> {
+        let this = self.project();
+
+        match this.stream.poll_recv(cx) {
+            PollRecv::Ready(item) => {
+                this.log.recording.lock().items.push(Recorded {
+                    offset: this.started.elapsed(),
+                    item: item.clone(),
+                });
+
+                PollRecv::Ready(item)
+            }
+            PollRecv::Pending => PollRecv::Pending,
+            PollRecv::Closed => {
+                let mut recording = this.log.recording.lock();
+                if recording.closed.is_none() {
+                    recording.closed = Some(this.started.elapsed());
+                }
+
+                PollRecv::Closed
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
+
+impl<S: Stream> fmt::Debug for RecordStream<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordStream")
+            .field("log", &self.log)
+            .finish()
+    }
+}
+
+/// Replays the recorded items immediately, and then closes.
+pub fn replay<T>(recording: Recording<T>) -> ReplayStream<T> {
+    ReplayStream::new(recording)
+}
+
+/// Replays the recorded items, with the same relative timing as the original stream.
+///
+/// Timing starts when the replay is first polled.  Requires the `time` or `time-async-io` feature,
+/// and with the `time` feature the replay must be polled within a tokio runtime.
+#[cfg(feature = "timer")]
+pub fn replay_timed<T>(recording: Recording<T>) -> ReplayStream<T> {
+    let mut stream = ReplayStream::new(recording);
+    stream.timing = Some(Timing {
+        started: None,
+        timer: None,
+    });
+
+    stream
+}
+
+/// A stream which reproduces a [Recording](./struct.Recording.html).
+pub struct ReplayStream<T> {
+    items: VecDeque<Recorded<T>>,
+    closed: Option<Duration>,
+    #[cfg(feature = "timer")]
+    timing: Option<Timing>,
+}
+
+#[cfg(feature = "timer")]
+struct Timing {
+    started: Option<Instant>,
+    timer: Option<Timer>,
+}
+
+impl<T> ReplayStream<T> {
+    fn new(recording: Recording<T>) -> Self {
+        Self {
+            items: recording.items.into(),
+            closed: recording.closed,
+            #[cfg(feature = "timer")]
+            timing: None,
+        }
+    }
+
+    #[cfg(feature = "timer")]
+    fn is_timed(&self) -> bool {
+        self.timing.is_some()
+    }
+
+    #[cfg(not(feature = "timer"))]
+    fn is_timed(&self) -> bool {
+        false
+    }
+
+    /// Returns false until the offset has elapsed, and wakes the task at the deadline
+    #[cfg(feature = "timer")]
+    fn poll_offset(&mut self, cx: &mut Context<'_>, offset: Duration) -> bool {
+        let timing = match &mut self.timing {
+            Some(timing) => timing,
+            None => return true,
+        };
+
+        let deadline = *timing.started.get_or_insert_with(Instant::now) + offset;
+        if Instant::now() >= deadline {
+            return true;
+        }
+
+        let timer = match &mut timing.timer {
+            Some(timer) => timer,
+            None if cx.waker().is_some() => timing.timer.insert(Timer::new(deadline)),
+            None => return false,
+        };
+
+        if timer.deadline() != deadline {
+            timer.reset(deadline);
+        }
+
+        timer.poll_elapsed(cx).is_ready()
+    }
+
+    #[cfg(not(feature = "timer"))]
+    fn poll_offset(&mut self, _cx: &mut Context<'_>, _offset: Duration) -> bool {
+        true
+    }
+}
+
+impl<T> Stream for ReplayStream<T> {
+    type Item = T;
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item> {
+        let this = self.get_mut();
+
+        match this.items.front().map(|recorded| recorded.offset) {
+            Some(offset) => {
+                if !this.poll_offset(cx, offset) {
+                    return PollRecv::Pending;
+                }
+
+                let recorded = this.items.pop_front().unwrap();
+                PollRecv::Ready(recorded.item)
+            }
+            None => {
+                if let Some(closed) = this.closed {
+                    if !this.poll_offset(cx, closed) {
+                        return PollRecv::Pending;
+                    }
+                }
+
+                PollRecv::Closed
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.items.len(), Some(self.items.len()))
+    }
+}
+
+impl<T> Unpin for ReplayStream<T> {}
+
+impl<T> fmt::Debug for ReplayStream<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReplayStream")
+            .field("remaining", &self.items.len())
+            .field("timed", &self.is_timed())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{record, replay, Recorded, Recording};
+    use crate::{
+        stream::{Stream, TryRecvError},
+        test::stream::from_iter,
+    };
+
+    #[test]
+    fn records_items() {
+        let (mut stream, log) = record(from_iter(vec![1usize, 2]));
+
+        assert_eq!(Ok(1), stream.try_recv());
+        assert_eq!(Ok(2), stream.try_recv());
+        assert_eq!(Err(TryRecvError::Closed), stream.try_recv());
+
+        let recording = log.recording();
+        let items: Vec<usize> = recording.items.iter().map(|r| r.item).collect();
+        assert_eq!(vec![1, 2], items);
+        assert!(recording.closed.is_some());
+        assert!(recording.items[0].offset <= recording.items[1].offset);
+    }
+
+    #[test]
+    fn take_empties_log() {
+        let (mut stream, log) = record(from_iter(vec![1usize]));
+
+        assert_eq!(Ok(1), stream.try_recv());
+        assert_eq!(1, log.take().items.len());
+        assert!(log.is_empty());
+    }
+
+    #[test]
+    fn replays_items() {
+        let recording = Recording {
+            items: vec![
+                Recorded {
+                    offset: Duration::from_secs(10),
+                    item: 1usize,
+                },
+                Recorded {
+                    offset: Duration::from_secs(20),
+                    item: 2usize,
+                },
+            ],
+            closed: Some(Duration::from_secs(30)),
+        };
+
+        let mut stream = replay(recording);
+        assert_eq!(Ok(1), stream.try_recv());
+        assert_eq!(Ok(2), stream.try_recv());
+        assert_eq!(Err(TryRecvError::Closed), stream.try_recv());
+    }
+
+    #[cfg(feature = "timer")]
+    #[tokio::test]
+    async fn replays_timing() {
+        use super::replay_timed;
+
+        let recording = Recording {
+            items: vec![Recorded {
+                offset: Duration::from_millis(50),
+                item: 1usize,
+            }],
+            closed: None,
+        };
+
+        let mut stream = replay_timed(recording);
+        assert_eq!(Err(TryRecvError::Pending), stream.try_recv());
+
+        let started = std::time::Instant::now();
+        assert_eq!(Some(1), stream.recv().await);
+        assert!(started.elapsed() >= Duration::from_millis(40));
+        assert_eq!(None, stream.recv().await);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serializes() {
+        let recording = Recording {
+            items: vec![Recorded {
+                offset: Duration::from_millis(5),
+                item: 1usize,
+            }],
+            closed: Some(Duration::from_millis(10)),
+        };
+
+        let json = serde_json::to_string(&recording).unwrap();
+        assert_eq!(recording, serde_json::from_str(&json).unwrap());
+    }
+}
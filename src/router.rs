@@ -0,0 +1,30 @@
+//! Composite sinks, which route each message to one of several downstream sinks.
+//!
+//! [weighted_round_robin](./fn.weighted_round_robin.html) spreads messages across the sinks in proportion to their weights,
+//! skipping sinks which are full or closed.  [by_key](./fn.by_key.html) demultiplexes messages to the sink registered for each key,
+//! with a default route for unknown keys.
+//!
+//! ```rust
+//! use postage::{mpsc, prelude::*, router};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let (large, mut large_rx) = mpsc::channel(8);
+//!     let (small, mut small_rx) = mpsc::channel(8);
+//!     let mut workers = router::weighted_round_robin(vec![(large, 2), (small, 1)]);
+//!
+//!     for job in 0..3usize {
+//!         workers.send(job).await.ok();
+//!     }
+//!
+//!     assert_eq!(Some(0), large_rx.recv().await);
+//!     assert_eq!(Some(1), small_rx.recv().await);
+//!     assert_eq!(Some(2), large_rx.recv().await);
+//! }
+//! ```
+
+mod by_key;
+mod weighted;
+
+pub use by_key::{by_key, KeyRouter};
+pub use weighted::{weighted_round_robin, WeightedRoundRobin};
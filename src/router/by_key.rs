@@ -0,0 +1,276 @@
+use std::{collections::HashMap, fmt, hash::Hash, pin::Pin};
+
+use crate::{
+    dead_letter::{DeadLetter, DeadLetterReason, DeadLetterSink},
+    sink::{PollSend, Sink},
+    Context,
+};
+
+/// Creates a sink which routes each message to the sink registered for its key.
+///
+/// If the key has no route, or its route is closed, the message is sent to the [default route](./struct.KeyRouter.html#method.default_route).
+/// Without a default route, the message is forwarded to the [dead-letter sink](./struct.KeyRouter.html#method.dead_letter),
+/// or rejected.  The router waits if the selected route is full.
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use postage::{mpsc, prelude::*, router};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let (orders, mut orders_rx) = mpsc::channel(4);
+///     let (refunds, mut refunds_rx) = mpsc::channel(4);
+///     let (other, mut other_rx) = mpsc::channel(4);
+///
+///     let routes = HashMap::from([("order", orders), ("refund", refunds)]);
+///     let mut router = router::by_key(|event: &(&str, usize)| event.0, routes).default_route(other);
+///
+///     router.send(("order", 1)).await.ok();
+///     router.send(("refund", 2)).await.ok();
+///     router.send(("audit", 3)).await.ok();
+///
+///     assert_eq!(Some(("order", 1)), orders_rx.recv().await);
+///     assert_eq!(Some(("refund", 2)), refunds_rx.recv().await);
+///     assert_eq!(Some(("audit", 3)), other_rx.recv().await);
+/// }
+/// ```
+pub fn by_key<K, S, F>(key: F, routes: HashMap<K, S>) -> KeyRouter<K, S, F>
+where
+    K: Eq + Hash,
+    S: Sink + Unpin,
+    F: FnMut(&S::Item) -> K,
+{
+    KeyRouter {
+        key,
+        routes,
+        default_route: None,
+        dead_letter: None,
+    }
+}
+
+/// The sink returned by [by_key](./fn.by_key.html)
+pub struct KeyRouter<K, S: Sink, F> {
+    key: F,
+    routes: HashMap<K, S>,
+    default_route: Option<S>,
+    dead_letter: Option<DeadLetterSink<S::Item>>,
+}
+
+impl<K, S, F> KeyRouter<K, S, F>
+where
+    K: Eq + Hash,
+    S: Sink,
+{
+    /// Sends messages which have no open route to the sink, with backpressure.
+    ///
+    /// Sinks of different types can be used as routes by boxing them as [DynSink](../sink/trait.DynSink.html) trait objects.
+    pub fn default_route(mut self, sink: S) -> Self {
+        self.default_route = Some(sink);
+        self
+    }
+
+    /// Forwards messages which have no open route, and no open default route, to the dead-letter sink,
+    /// tagged with `DeadLetterReason::Unrouted`.
+    ///
+    /// Messages are forwarded without blocking.  If the dead-letter sink is full or closed, the message is rejected.
+    pub fn dead_letter<D>(mut self, sink: D) -> Self
+    where
+        D: Sink<Item = DeadLetter<S::Item>> + Send + 'static,
+    {
+        self.dead_letter = Some(DeadLetterSink::new(sink));
+        self
+    }
+
+    /// Adds a route for the key, returning the previous route
+    pub fn insert(&mut self, key: K, sink: S) -> Option<S> {
+        self.routes.insert(key, sink)
+    }
+
+    /// Removes the route for the key.  Later messages with the key are sent to the default route.
+    pub fn remove(&mut self, key: &K) -> Option<S> {
+        self.routes.remove(key)
+    }
+
+    /// Returns a reference to the routes
+    pub fn routes(&self) -> &HashMap<K, S> {
+        &self.routes
+    }
+
+    /// Returns a mutable reference to the routes
+    pub fn routes_mut(&mut self) -> &mut HashMap<K, S> {
+        &mut self.routes
+    }
+}
+
+// the routes are Unpin, and the other fields are never pinned
+impl<K, S: Sink + Unpin, F> Unpin for KeyRouter<K, S, F> {}
+
+impl<K, S, F> Sink for KeyRouter<K, S, F>
+where
+    K: Eq + Hash,
+    S: Sink + Unpin,
+    F: FnMut(&S::Item) -> K,
+{
+    type Item = S::Item;
+
+    fn poll_send(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        value: Self::Item,
+    ) -> PollSend<Self::Item> {
+        let this = self.get_mut();
+
+        let key = (this.key)(&value);
+        let value = match this.routes.get_mut(&key) {
+            Some(route) => match Pin::new(route).poll_send(cx, value) {
+                PollSend::Rejected(value) => value,
+                poll => return poll,
+            },
+            None => value,
+        };
+
+        let value = match this.default_route {
+            Some(ref mut route) => match Pin::new(route).poll_send(cx, value) {
+                PollSend::Rejected(value) => value,
+                poll => return poll,
+            },
+            None => value,
+        };
+
+        let value = match this.dead_letter {
+            Some(ref mut dead_letter) => {
+                let letter = DeadLetter {
+                    value,
+                    reason: DeadLetterReason::Unrouted,
+                };
+
+                match (dead_letter.forward)(letter) {
+                    Ok(()) => return PollSend::Ready,
+                    Err(value) => value,
+                }
+            }
+            None => value,
+        };
+
+        PollSend::Rejected(value)
+    }
+}
+
+impl<K, S, F> fmt::Debug for KeyRouter<K, S, F>
+where
+    K: fmt::Debug,
+    S: Sink,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyRouter")
+            .field("routes", &self.routes.keys().collect::<Vec<_>>())
+            .field("default_route", &self.default_route.is_some())
+            .field("dead_letter", &self.dead_letter.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, pin::Pin};
+
+    use futures_test::task::new_count_waker;
+
+    use super::by_key;
+    use crate::{
+        dead_letter::DeadLetterReason,
+        mpsc,
+        sink::{PollSend, Sink, TrySendError},
+        stream::{Stream, TryRecvError},
+    };
+
+    fn key(value: &usize) -> usize {
+        value % 10
+    }
+
+    #[test]
+    fn routes_by_key() {
+        let (a, mut a_rx) = mpsc::channel(4);
+        let (b, mut b_rx) = mpsc::channel(4);
+        let mut router = by_key(key, HashMap::from([(1, a), (2, b)]));
+
+        router.try_send(11usize).unwrap();
+        router.try_send(22).unwrap();
+        router.try_send(21).unwrap();
+
+        assert_eq!(Ok(11), a_rx.try_recv());
+        assert_eq!(Ok(21), a_rx.try_recv());
+        assert_eq!(Ok(22), b_rx.try_recv());
+    }
+
+    #[test]
+    fn unrouted_rejected() {
+        let (a, _a_rx) = mpsc::channel(4);
+        let mut router = by_key(key, HashMap::from([(1, a)]));
+
+        assert_eq!(Err(TrySendError::Rejected(3usize)), router.try_send(3));
+    }
+
+    #[test]
+    fn default_route() {
+        let (a, a_rx) = mpsc::channel(4);
+        let (other, mut other_rx) = mpsc::channel(4);
+        let mut router = by_key(key, HashMap::from([(1, a)])).default_route(other);
+
+        router.try_send(2usize).unwrap();
+        assert_eq!(Ok(2), other_rx.try_recv());
+
+        // a closed route falls back to the default route
+        drop(a_rx);
+        router.try_send(1).unwrap();
+        assert_eq!(Ok(1), other_rx.try_recv());
+    }
+
+    #[test]
+    fn dead_letter() {
+        let (a, _a_rx) = mpsc::channel(4);
+        let (dead_tx, mut dead_rx) = mpsc::channel(4);
+        let mut router = by_key(key, HashMap::from([(1, a)])).dead_letter(dead_tx);
+
+        router.try_send(2usize).unwrap();
+        let letter = dead_rx.try_recv().unwrap();
+        assert_eq!(2, letter.value);
+        assert_eq!(DeadLetterReason::Unrouted, letter.reason);
+    }
+
+    #[test]
+    fn waits_for_full_route() {
+        let (a, mut a_rx) = mpsc::channel(1);
+        let (other, mut other_rx) = mpsc::channel(4);
+        let mut router = by_key(key, HashMap::from([(1, a)])).default_route(other);
+        router.try_send(1usize).unwrap();
+
+        let (waker, count) = new_count_waker();
+        let mut cx = std::task::Context::from_waker(&waker).into();
+        assert_eq!(
+            PollSend::Pending(11),
+            Pin::new(&mut router).poll_send(&mut cx, 11)
+        );
+        assert_eq!(Err(TryRecvError::Pending), other_rx.try_recv());
+
+        assert_eq!(Ok(1), a_rx.try_recv());
+        assert_eq!(1, count.get());
+        router.try_send(11).unwrap();
+        assert_eq!(Ok(11), a_rx.try_recv());
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let (a, mut a_rx) = mpsc::channel(4);
+        let (other, mut other_rx) = mpsc::channel(4);
+        let mut router = by_key(key, HashMap::new()).default_route(other);
+
+        router.insert(1, a);
+        router.try_send(1usize).unwrap();
+        assert_eq!(Ok(1), a_rx.try_recv());
+
+        assert!(router.remove(&1).is_some());
+        router.try_send(1).unwrap();
+        assert_eq!(Ok(1), other_rx.try_recv());
+    }
+}
@@ -0,0 +1,232 @@
+use std::{fmt, pin::Pin};
+
+use crate::{
+    sink::{PollSend, Sink},
+    Context,
+};
+
+/// Creates a sink which distributes messages across the sinks, in proportion to their weights.
+///
+/// Messages are spread evenly, so sinks with weights of 2 and 1 receive messages in the order `a, b, a`.
+/// If a sink is full, the message is sent to the next sink in the rotation, and if a sink is closed, it is removed from the rotation.
+/// A sink with a weight of zero only receives messages when every weighted sink is full.
+///
+/// The router waits if every sink is full, and rejects messages when every sink is closed.
+pub fn weighted_round_robin<S, I>(routes: I) -> WeightedRoundRobin<S>
+where
+    S: Sink + Unpin,
+    I: IntoIterator<Item = (S, usize)>,
+{
+    let routes = routes
+        .into_iter()
+        .map(|(sink, weight)| Route {
+            sink,
+            weight: weight as i64,
+            current: 0,
+            closed: false,
+        })
+        .collect();
+
+    WeightedRoundRobin { routes }
+}
+
+/// The sink returned by [weighted_round_robin](./fn.weighted_round_robin.html)
+pub struct WeightedRoundRobin<S> {
+    routes: Vec<Route<S>>,
+}
+
+struct Route<S> {
+    sink: S,
+    weight: i64,
+    current: i64,
+    closed: bool,
+}
+
+impl<S> WeightedRoundRobin<S> {
+    /// Returns a reference to the sink at the index, in the order the routes were provided
+    pub fn get_ref(&self, index: usize) -> Option<&S> {
+        self.routes.get(index).map(|route| &route.sink)
+    }
+
+    /// Returns a mutable reference to the sink at the index, in the order the routes were provided
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut S> {
+        self.routes.get_mut(index).map(|route| &mut route.sink)
+    }
+
+    /// Returns the sinks, in the order the routes were provided
+    pub fn into_inner(self) -> Vec<S> {
+        self.routes.into_iter().map(|route| route.sink).collect()
+    }
+
+    /// The order the sinks are tried for the next message.  Closed sinks are skipped.
+    fn candidates(&self) -> Vec<usize> {
+        let mut candidates: Vec<usize> = (0..self.routes.len())
+            .filter(|index| !self.routes[*index].closed)
+            .collect();
+
+        // a stable sort, so ties go to the earlier route
+        candidates.sort_by_key(|index| {
+            let route = &self.routes[*index];
+            std::cmp::Reverse(route.current + route.weight)
+        });
+
+        candidates
+    }
+
+    /// Advances the smooth weighted round-robin state, after the message was accepted by the winner
+    fn commit(&mut self, winner: usize) {
+        let mut total = 0;
+        for route in self.routes.iter_mut().filter(|route| !route.closed) {
+            route.current += route.weight;
+            total += route.weight;
+        }
+
+        self.routes[winner].current -= total;
+    }
+}
+
+impl<S> Sink for WeightedRoundRobin<S>
+where
+    S: Sink + Unpin,
+{
+    type Item = S::Item;
+
+    fn poll_send(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        mut value: Self::Item,
+    ) -> PollSend<Self::Item> {
+        let this = self.get_mut();
+
+        for index in this.candidates() {
+            let route = &mut this.routes[index];
+            value = match Pin::new(&mut route.sink).poll_send(cx, value) {
+                PollSend::Ready => {
+                    this.commit(index);
+                    return PollSend::Ready;
+                }
+                PollSend::Pending(value) => value,
+                PollSend::Rejected(value) => {
+                    route.closed = true;
+                    value
+                }
+            };
+        }
+
+        if this.routes.iter().all(|route| route.closed) {
+            return PollSend::Rejected(value);
+        }
+
+        PollSend::Pending(value)
+    }
+}
+
+impl<S> fmt::Debug for WeightedRoundRobin<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let weights: Vec<_> = self.routes.iter().map(|route| route.weight).collect();
+
+        f.debug_struct("WeightedRoundRobin")
+            .field("weights", &weights)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+
+    use futures_test::task::new_count_waker;
+
+    use super::weighted_round_robin;
+    use crate::{
+        mpsc,
+        sink::{PollSend, Sink, TrySendError},
+        stream::{Stream, TryRecvError},
+    };
+
+    #[test]
+    fn proportional() {
+        let (a, mut a_rx) = mpsc::channel(8);
+        let (b, mut b_rx) = mpsc::channel(8);
+        let (c, mut c_rx) = mpsc::channel(8);
+        let mut router = weighted_round_robin(vec![(a, 3), (b, 2), (c, 1)]);
+
+        for i in 0..12usize {
+            router.try_send(i).unwrap();
+        }
+
+        let count =
+            |rx: &mut mpsc::Receiver<usize>| std::iter::from_fn(|| rx.try_recv().ok()).count();
+        assert_eq!(6, count(&mut a_rx));
+        assert_eq!(4, count(&mut b_rx));
+        assert_eq!(2, count(&mut c_rx));
+    }
+
+    #[test]
+    fn interleaves() {
+        let (a, mut a_rx) = mpsc::channel(8);
+        let (b, mut b_rx) = mpsc::channel(8);
+        let mut router = weighted_round_robin(vec![(a, 2), (b, 1)]);
+
+        for i in 0..3usize {
+            router.try_send(i).unwrap();
+        }
+
+        assert_eq!(Ok(0), a_rx.try_recv());
+        assert_eq!(Ok(2), a_rx.try_recv());
+        assert_eq!(Ok(1), b_rx.try_recv());
+    }
+
+    #[test]
+    fn skips_full() {
+        let (a, mut a_rx) = mpsc::channel(1);
+        let (b, mut b_rx) = mpsc::channel(8);
+        let mut router = weighted_round_robin(vec![(a, 10), (b, 1)]);
+
+        router.try_send(1usize).unwrap();
+        router.try_send(2).unwrap();
+        router.try_send(3).unwrap();
+
+        assert_eq!(Ok(1), a_rx.try_recv());
+        assert_eq!(Ok(2), b_rx.try_recv());
+        assert_eq!(Ok(3), b_rx.try_recv());
+    }
+
+    #[test]
+    fn skips_closed() {
+        let (a, a_rx) = mpsc::channel(8);
+        let (b, mut b_rx) = mpsc::channel(8);
+        let mut router = weighted_round_robin(vec![(a, 10), (b, 1)]);
+        drop(a_rx);
+
+        router.try_send(1usize).unwrap();
+        router.try_send(2).unwrap();
+        assert_eq!(Ok(1), b_rx.try_recv());
+        assert_eq!(Ok(2), b_rx.try_recv());
+
+        drop(b_rx);
+        assert_eq!(Err(TrySendError::Rejected(3)), router.try_send(3));
+    }
+
+    #[test]
+    fn waits_when_all_full() {
+        let (a, mut a_rx) = mpsc::channel(1);
+        let (b, _b_rx) = mpsc::channel(1);
+        let mut router = weighted_round_robin(vec![(a, 1), (b, 1)]);
+        router.try_send(1usize).unwrap();
+        router.try_send(2).unwrap();
+
+        let (waker, count) = new_count_waker();
+        let mut cx = std::task::Context::from_waker(&waker).into();
+        assert_eq!(
+            PollSend::Pending(3),
+            Pin::new(&mut router).poll_send(&mut cx, 3)
+        );
+
+        assert_eq!(Ok(1), a_rx.try_recv());
+        assert_eq!(1, count.get());
+        router.try_send(3).unwrap();
+        assert_eq!(Ok(3), a_rx.try_recv());
+        assert_eq!(Err(TryRecvError::Pending), a_rx.try_recv());
+    }
+}
@@ -0,0 +1,302 @@
+//! Fans a request out to a set of workers, and gathers their responses.
+//!
+//! [scatter_gather](./fn.scatter_gather.html) sends a clone of the request to each worker's sink, and waits for one
+//! response from each worker's paired stream.  It resolves when every worker has responded, or when the timeout elapses,
+//! with the responses that arrived in time.
+//!
+//! The timeout is measured from the call to `scatter_gather`, and is scheduled on a background thread,
+//! so it does not depend on a particular async runtime.
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use postage::{mpsc, prelude::*, scatter_gather::scatter_gather};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut workers = Vec::new();
+//!     for id in 0..3usize {
+//!         let (request_tx, mut request_rx) = mpsc::channel::<usize>(1);
+//!         let (mut response_tx, response_rx) = mpsc::channel(1);
+//!
+//!         tokio::spawn(async move {
+//!             while let Some(request) = request_rx.recv().await {
+//!                 // the last worker never responds
+//!                 if id < 2 {
+//!                     response_tx.send(request + id).await.ok();
+//!                 }
+//!             }
+//!         });
+//!
+//!         workers.push((request_tx, response_rx));
+//!     }
+//!
+//!     let workers = workers.iter_mut().map(|(tx, rx)| (tx, rx));
+//!     let responses = scatter_gather(10, workers, Duration::from_millis(50)).await;
+//!     assert_eq!(vec![Some(10), Some(11), None], responses);
+//! }
+//! ```
+
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Poll, Waker},
+    thread,
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use crate::{
+    sink::{PollSend, Sink},
+    stream::{PollRecv, Stream},
+};
+
+/// Sends a clone of the request to each worker, and gathers one response from each worker.
+///
+/// Each worker is a pair of a sink which accepts requests, and a stream which produces responses.
+/// Long-lived workers can be borrowed with `workers.iter_mut().map(|(tx, rx)| (tx, rx))`.
+/// The future resolves with a response for each worker, in the order the workers were provided.
+/// The response is `None` if the worker did not respond before the timeout, or if its sink or stream was closed.
+pub fn scatter_gather<Req, S, R, I>(
+    request: Req,
+    workers: I,
+    timeout: Duration,
+) -> ScatterGather<Req, S, R>
+where
+    Req: Clone,
+    I: IntoIterator<Item = (S, R)>,
+    S: Sink<Item = Req> + Unpin,
+    R: Stream + Unpin,
+{
+    let workers = workers
+        .into_iter()
+        .map(|(sink, stream)| Worker {
+            sink,
+            stream,
+            request: Some(request.clone()),
+            response: None,
+            done: false,
+        })
+        .collect();
+
+    ScatterGather {
+        workers,
+        deadline: Instant::now() + timeout,
+        timer: None,
+    }
+}
+
+/// A future returned by `scatter_gather`.
+#[must_use = "futures do nothing unless polled"]
+pub struct ScatterGather<Req, S, R>
+where
+    R: Stream,
+{
+    workers: Vec<Worker<Req, S, R>>,
+    deadline: Instant,
+    timer: Option<Arc<Mutex<Option<Waker>>>>,
+}
+
+// the workers are never pinned, so the future can be moved after it is polled
+impl<Req, S, R> Unpin for ScatterGather<Req, S, R> where R: Stream {}
+
+struct Worker<Req, S, R>
+where
+    R: Stream,
+{
+    sink: S,
+    stream: R,
+    request: Option<Req>,
+    response: Option<R::Item>,
+    done: bool,
+}
+
+impl<Req, S, R> Worker<Req, S, R>
+where
+    S: Sink<Item = Req> + Unpin,
+    R: Stream + Unpin,
+{
+    /// Sends the request, and then polls for the response.  Marks the worker done if it responds or closes.
+    fn poll(&mut self, cx: &mut crate::Context<'_>) {
+        if let Some(request) = self.request.take() {
+            match Pin::new(&mut self.sink).poll_send(cx, request) {
+                PollSend::Ready => {}
+                PollSend::Pending(request) => {
+                    self.request = Some(request);
+                    return;
+                }
+                PollSend::Rejected(_) => {
+                    self.done = true;
+                    return;
+                }
+            }
+        }
+
+        match Pin::new(&mut self.stream).poll_recv(cx) {
+            PollRecv::Ready(response) => {
+                self.response = Some(response);
+                self.done = true;
+            }
+            PollRecv::Pending => {}
+            PollRecv::Closed => self.done = true,
+        }
+    }
+}
+
+impl<Req, S, R> ScatterGather<Req, S, R>
+where
+    R: Stream,
+{
+    fn take_responses(&mut self) -> Vec<Option<R::Item>> {
+        self.workers
+            .iter_mut()
+            .map(|worker| worker.response.take())
+            .collect()
+    }
+
+    /// Schedules a wakeup at the deadline, or updates the waker of the scheduled wakeup
+    fn schedule_timer(&mut self, waker: &Waker) {
+        if let Some(ref timer) = self.timer {
+            *timer.lock() = Some(waker.clone());
+            return;
+        }
+
+        let timer = Arc::new(Mutex::new(Some(waker.clone())));
+        let deadline = self.deadline;
+        let thread_timer = timer.clone();
+        thread::spawn(move || {
+            thread::sleep(deadline.saturating_duration_since(Instant::now()));
+            if let Some(waker) = thread_timer.lock().take() {
+                waker.wake();
+            }
+        });
+
+        self.timer = Some(timer);
+    }
+}
+
+impl<Req, S, R> Future for ScatterGather<Req, S, R>
+where
+    S: Sink<Item = Req> + Unpin,
+    R: Stream + Unpin,
+{
+    type Output = Vec<Option<R::Item>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut context: crate::Context<'_> = cx.into();
+
+        for worker in this.workers.iter_mut().filter(|worker| !worker.done) {
+            worker.poll(&mut context);
+        }
+
+        if this.workers.iter().all(|worker| worker.done) || Instant::now() >= this.deadline {
+            return Poll::Ready(this.take_responses());
+        }
+
+        this.schedule_timer(cx.waker());
+        Poll::Pending
+    }
+}
+
+impl<Req, S, R> fmt::Debug for ScatterGather<Req, S, R>
+where
+    R: Stream,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let responded = self
+            .workers
+            .iter()
+            .filter(|worker| worker.response.is_some())
+            .count();
+
+        f.debug_struct("ScatterGather")
+            .field("workers", &self.workers.len())
+            .field("responded", &responded)
+            .field("deadline", &self.deadline)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::Future, pin::Pin, task::Poll, time::Duration};
+
+    use futures_test::task::noop_context;
+
+    use super::scatter_gather;
+    use crate::{mpsc, sink::Sink, stream::Stream};
+
+    #[test]
+    fn all_respond() {
+        let (req_a, mut worker_a) = mpsc::channel::<usize>(1);
+        let (mut resp_a, gather_a) = mpsc::channel(1);
+        let (req_b, mut worker_b) = mpsc::channel::<usize>(1);
+        let (mut resp_b, gather_b) = mpsc::channel(1);
+
+        let mut future = scatter_gather(
+            5,
+            vec![(req_a, gather_a), (req_b, gather_b)],
+            Duration::from_secs(60),
+        );
+        assert_eq!(
+            Poll::Pending,
+            Pin::new(&mut future).poll(&mut noop_context())
+        );
+
+        assert_eq!(Ok(5), worker_a.try_recv());
+        assert_eq!(Ok(5), worker_b.try_recv());
+        resp_b.try_send(2).unwrap();
+        assert_eq!(
+            Poll::Pending,
+            Pin::new(&mut future).poll(&mut noop_context())
+        );
+
+        resp_a.try_send(1).unwrap();
+        assert_eq!(
+            Poll::Ready(vec![Some(1), Some(2)]),
+            Pin::new(&mut future).poll(&mut noop_context())
+        );
+    }
+
+    #[test]
+    fn closed_workers() {
+        let (req_a, worker_a) = mpsc::channel::<usize>(1);
+        let (_resp_a, gather_a) = mpsc::channel::<usize>(1);
+        let (req_b, _worker_b) = mpsc::channel::<usize>(1);
+        let (resp_b, gather_b) = mpsc::channel::<usize>(1);
+        drop(worker_a);
+        drop(resp_b);
+
+        let mut future = scatter_gather(
+            5,
+            vec![(req_a, gather_a), (req_b, gather_b)],
+            Duration::from_secs(60),
+        );
+        assert_eq!(
+            Poll::Ready(vec![None, None]),
+            Pin::new(&mut future).poll(&mut noop_context())
+        );
+    }
+
+    #[tokio::test]
+    async fn timeout() {
+        let (req_a, mut worker_a) = mpsc::channel::<usize>(1);
+        let (mut resp_a, gather_a) = mpsc::channel(1);
+        let (req_b, _worker_b) = mpsc::channel::<usize>(1);
+        let (_resp_b, gather_b) = mpsc::channel(1);
+
+        tokio::spawn(async move {
+            if let Some(request) = worker_a.recv().await {
+                resp_a.send(request * 2).await.ok();
+            }
+        });
+
+        let mut workers = [(req_a, gather_a), (req_b, gather_b)];
+        let borrowed = workers.iter_mut().map(|(tx, rx)| (tx, rx));
+        let responses = scatter_gather(4, borrowed, Duration::from_millis(20)).await;
+        assert_eq!(vec![Some(8), None], responses);
+    }
+}
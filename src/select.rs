@@ -0,0 +1,505 @@
+//! Waits for the first of several channel operations to complete.
+//!
+//! [select2](./fn.select2.html) and [select3](./fn.select3.html) race operations of different types, such as a `recv` and a `send`.
+//! [select_all](./fn.select_all.html) races any number of operations of the same type, such as `recv` calls on a list of receivers.
+//!
+//! Branches are polled in a rotating order, so a busy branch cannot starve the others.
+//! The order can be chosen per call site with a [Fairness](./enum.Fairness.html) strategy, trading strict fairness for the lowest latency on a preferred branch.
+//! `recv` is cancel-safe, and the value of an incomplete `send` can be recovered by passing the
+//! future by reference, and calling [SendFuture::into_inner](../sink/struct.SendFuture.html#method.into_inner) after the select completes.
+//!
+//! ```rust
+//! use postage::{prelude::*, select::{select2, Either}};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let (mut requests_tx, mut requests) = mpsc::channel::<usize>(4);
+//!     let (mut shutdown_tx, mut shutdown) = oneshot::channel::<()>();
+//!
+//!     requests_tx.send(1).await.ok();
+//!
+//!     match select2(requests.recv(), shutdown.recv()).await {
+//!         Either::Left(request) => assert_eq!(Some(1), request),
+//!         Either::Right(_shutdown) => unreachable!(),
+//!     }
+//!
+//!     shutdown_tx.send(()).await.ok();
+//!     match select2(requests.recv(), shutdown.recv()).await {
+//!         Either::Left(_request) => unreachable!(),
+//!         Either::Right(shutdown) => assert_eq!(Some(()), shutdown),
+//!     }
+//! }
+//! ```
+
+use std::{
+    cell::Cell,
+    collections::hash_map::RandomState,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    pin::Pin,
+    task::Poll,
+};
+
+/// The order in which the branches of a select, or the streams of a merge, are polled.
+///
+/// ```rust
+/// use postage::{prelude::*, select::{select_all, Fairness}};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let (mut tx, mut rx) = mpsc::channel::<usize>(4);
+///     let (mut tx2, mut rx2) = mpsc::channel::<usize>(4);
+///     tx.send(1).await.ok();
+///     tx2.send(2).await.ok();
+///
+///     let (index, value) = select_all(vec![rx2.recv(), rx.recv()])
+///         .fairness(Fairness::Prioritized)
+///         .await;
+///
+///     assert_eq!((0, Some(2)), (index, value));
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Fairness {
+    /// The first branch to be polled rotates on each poll.  This is the default.
+    #[default]
+    Alternate,
+    /// The first branch to be polled is chosen at random on each poll
+    RandomStart,
+    /// The branches are always polled in order, so the first branch has the lowest latency, and can starve the others
+    Prioritized,
+    /// The first branch to be polled rotates, and each branch goes first for the number of polls given by its weight.
+    /// Missing or zero weights are treated as one.
+    WeightedRoundRobin(Vec<usize>),
+}
+
+/// Chooses the first branch to poll, according to a fairness strategy
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Scheduler {
+    fairness: Fairness,
+    next: usize,
+    credits: usize,
+}
+
+impl Scheduler {
+    pub fn new(fairness: Fairness) -> Self {
+        Self {
+            fairness,
+            next: 0,
+            credits: 0,
+        }
+    }
+
+    pub fn fairness(&self) -> &Fairness {
+        &self.fairness
+    }
+
+    /// Returns the index of the branch to poll first, out of `len` branches
+    pub fn start(&mut self, len: usize) -> usize {
+        match self.fairness {
+            Fairness::Alternate => {
+                let start = self.next % len;
+                self.next = (start + 1) % len;
+                start
+            }
+            Fairness::RandomStart => random() % len,
+            Fairness::Prioritized => 0,
+            Fairness::WeightedRoundRobin(ref weights) => {
+                let weight = |index: usize| weights.get(index).copied().unwrap_or(1).max(1);
+
+                let start = self.next % len;
+                if self.credits == 0 {
+                    self.credits = weight(start);
+                }
+
+                self.credits -= 1;
+                if self.credits == 0 {
+                    self.next = (start + 1) % len;
+                }
+
+                start
+            }
+        }
+    }
+}
+
+/// A fast, thread-local pseudo-random number, seeded from the std hasher keys
+fn random() -> usize {
+    thread_local! {
+        static STATE: Cell<u64> = Cell::new(RandomState::new().build_hasher().finish() | 1);
+    }
+
+    STATE.with(|state| {
+        // xorshift64
+        let mut x = state.get();
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        state.set(x);
+        x as usize
+    })
+}
+
+/// The output of a two-branch select.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Either<L, R> {
+    /// The first branch completed
+    Left(L),
+    /// The second branch completed
+    Right(R),
+}
+
+/// The output of a three-branch select.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Either3<A, B, C> {
+    /// The first branch completed
+    A(A),
+    /// The second branch completed
+    B(B),
+    /// The third branch completed
+    C(C),
+}
+
+/// Waits for the first of two operations to complete.
+pub fn select2<A, B>(a: A, b: B) -> Select2<A, B>
+where
+    A: Future + Unpin,
+    B: Future + Unpin,
+{
+    Select2 {
+        a,
+        b,
+        scheduler: Scheduler::default(),
+    }
+}
+
+/// Waits for the first of three operations to complete.
+pub fn select3<A, B, C>(a: A, b: B, c: C) -> Select3<A, B, C>
+where
+    A: Future + Unpin,
+    B: Future + Unpin,
+    C: Future + Unpin,
+{
+    Select3 {
+        a,
+        b,
+        c,
+        scheduler: Scheduler::default(),
+    }
+}
+
+/// Waits for the first of the operations to complete, returning its index and output.
+///
+/// Panics if the iterator is empty.
+pub fn select_all<I>(futures: I) -> SelectAll<I::Item>
+where
+    I: IntoIterator,
+    I::Item: Future + Unpin,
+{
+    let futures: Vec<_> = futures.into_iter().collect();
+    assert!(
+        !futures.is_empty(),
+        "select_all requires at least one future"
+    );
+
+    SelectAll {
+        futures,
+        scheduler: Scheduler::default(),
+    }
+}
+
+/// A future returned by `select2`.
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct Select2<A, B> {
+    a: A,
+    b: B,
+    scheduler: Scheduler,
+}
+
+impl<A, B> Select2<A, B> {
+    /// Sets the order in which the branches are polled.  Defaults to `Fairness::Alternate`.
+    pub fn fairness(mut self, fairness: Fairness) -> Self {
+        self.scheduler = Scheduler::new(fairness);
+        self
+    }
+
+    /// Consumes the select, returning the branch futures
+    pub fn into_inner(self) -> (A, B) {
+        (self.a, self.b)
+    }
+}
+
+impl<A, B> Future for Select2<A, B>
+where
+    A: Future + Unpin,
+    B: Future + Unpin,
+{
+    type Output = Either<A::Output, B::Output>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let start = this.scheduler.start(2);
+
+        for i in 0..2 {
+            let ready = match (start + i) % 2 {
+                0 => Pin::new(&mut this.a).poll(cx).map(Either::Left),
+                _ => Pin::new(&mut this.b).poll(cx).map(Either::Right),
+            };
+
+            if ready.is_ready() {
+                return ready;
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+/// A future returned by `select3`.
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct Select3<A, B, C> {
+    a: A,
+    b: B,
+    c: C,
+    scheduler: Scheduler,
+}
+
+impl<A, B, C> Select3<A, B, C> {
+    /// Sets the order in which the branches are polled.  Defaults to `Fairness::Alternate`.
+    pub fn fairness(mut self, fairness: Fairness) -> Self {
+        self.scheduler = Scheduler::new(fairness);
+        self
+    }
+
+    /// Consumes the select, returning the branch futures
+    pub fn into_inner(self) -> (A, B, C) {
+        (self.a, self.b, self.c)
+    }
+}
+
+impl<A, B, C> Future for Select3<A, B, C>
+where
+    A: Future + Unpin,
+    B: Future + Unpin,
+    C: Future + Unpin,
+{
+    type Output = Either3<A::Output, B::Output, C::Output>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let start = this.scheduler.start(3);
+
+        for i in 0..3 {
+            let ready = match (start + i) % 3 {
+                0 => Pin::new(&mut this.a).poll(cx).map(Either3::A),
+                1 => Pin::new(&mut this.b).poll(cx).map(Either3::B),
+                _ => Pin::new(&mut this.c).poll(cx).map(Either3::C),
+            };
+
+            if ready.is_ready() {
+                return ready;
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+/// A future returned by `select_all`.
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct SelectAll<F> {
+    futures: Vec<F>,
+    scheduler: Scheduler,
+}
+
+impl<F> SelectAll<F> {
+    /// Sets the order in which the branches are polled.  Defaults to `Fairness::Alternate`.
+    pub fn fairness(mut self, fairness: Fairness) -> Self {
+        self.scheduler = Scheduler::new(fairness);
+        self
+    }
+
+    /// Consumes the select, returning the branch futures
+    pub fn into_inner(self) -> Vec<F> {
+        self.futures
+    }
+}
+
+impl<F> Future for SelectAll<F>
+where
+    F: Future + Unpin,
+{
+    type Output = (usize, F::Output);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let len = this.futures.len();
+        let start = this.scheduler.start(len);
+
+        for i in 0..len {
+            let index = (start + i) % len;
+            if let Poll::Ready(output) = Pin::new(&mut this.futures[index]).poll(cx) {
+                return Poll::Ready((index, output));
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::Future, pin::Pin, task::Poll};
+
+    use futures_test::task::noop_context;
+
+    use super::{select2, select3, select_all, Either, Either3, Fairness, Scheduler};
+    use crate::{mpsc, sink::Sink, stream::Stream};
+
+    #[test]
+    fn select2_ready() {
+        let (mut tx, mut rx) = mpsc::channel::<usize>(1);
+        let (_tx2, mut rx2) = mpsc::channel::<usize>(1);
+        tx.try_send(1).unwrap();
+
+        let mut select = select2(rx2.recv(), rx.recv());
+        assert_eq!(
+            Poll::Ready(Either::Right(Some(1))),
+            Pin::new(&mut select).poll(&mut noop_context())
+        );
+    }
+
+    #[test]
+    fn select2_rotates() {
+        let (mut tx, mut rx) = mpsc::channel::<usize>(4);
+        let (mut tx2, mut rx2) = mpsc::channel::<usize>(4);
+        tx.try_send(1).unwrap();
+        tx.try_send(2).unwrap();
+        tx2.try_send(3).unwrap();
+
+        let mut select = select2(rx.recv(), rx2.recv());
+        let mut cx = noop_context();
+        assert_eq!(
+            Poll::Ready(Either::Left(Some(1))),
+            Pin::new(&mut select).poll(&mut cx)
+        );
+        assert_eq!(
+            Poll::Ready(Either::Right(Some(3))),
+            Pin::new(&mut select).poll(&mut cx)
+        );
+    }
+
+    #[test]
+    fn select2_recovers_send() {
+        let (mut tx, _rx) = mpsc::channel::<usize>(1);
+        let (mut tx2, mut rx2) = mpsc::channel::<usize>(1);
+        tx.try_send(1).unwrap();
+        tx2.try_send(2).unwrap();
+
+        let mut send = tx.send(3);
+        let mut select = select2(&mut send, rx2.recv());
+        assert_eq!(
+            Poll::Ready(Either::Right(Some(2))),
+            Pin::new(&mut select).poll(&mut noop_context())
+        );
+        drop(select);
+        assert_eq!(Some(3), send.into_inner());
+    }
+
+    #[test]
+    fn select2_prioritized() {
+        let (mut tx, mut rx) = mpsc::channel::<usize>(4);
+        let (mut tx2, mut rx2) = mpsc::channel::<usize>(4);
+        tx.try_send(1).unwrap();
+        tx.try_send(2).unwrap();
+        tx2.try_send(3).unwrap();
+
+        let mut select = select2(rx.recv(), rx2.recv()).fairness(Fairness::Prioritized);
+        let mut cx = noop_context();
+        assert_eq!(
+            Poll::Ready(Either::Left(Some(1))),
+            Pin::new(&mut select).poll(&mut cx)
+        );
+        assert_eq!(
+            Poll::Ready(Either::Left(Some(2))),
+            Pin::new(&mut select).poll(&mut cx)
+        );
+        assert_eq!(
+            Poll::Ready(Either::Right(Some(3))),
+            Pin::new(&mut select).poll(&mut cx)
+        );
+    }
+
+    #[test]
+    fn scheduler_weighted() {
+        let mut scheduler = Scheduler::new(Fairness::WeightedRoundRobin(vec![3, 0]));
+        let starts: Vec<_> = (0..7).map(|_| scheduler.start(3)).collect();
+        assert_eq!(vec![0, 0, 0, 1, 2, 0, 0], starts);
+    }
+
+    #[test]
+    fn scheduler_random_start() {
+        let mut scheduler = Scheduler::new(Fairness::RandomStart);
+        let mut seen = [false; 3];
+        for _ in 0..1000 {
+            seen[scheduler.start(3)] = true;
+        }
+
+        assert_eq!([true; 3], seen);
+    }
+
+    #[test]
+    fn select3_ready() {
+        let (mut tx, mut rx) = mpsc::channel::<usize>(1);
+        let (_tx2, mut rx2) = mpsc::channel::<usize>(1);
+        let (tx3, mut rx3) = mpsc::channel::<usize>(1);
+
+        let mut select = select3(rx.recv(), rx2.recv(), tx.send(1));
+        assert_eq!(
+            Poll::Ready(Either3::C(Ok(()))),
+            Pin::new(&mut select).poll(&mut noop_context())
+        );
+
+        drop(tx3);
+        let mut select = select3(rx.recv(), rx2.recv(), rx3.recv());
+        assert_eq!(
+            Poll::Ready(Either3::A(Some(1))),
+            Pin::new(&mut select).poll(&mut noop_context())
+        );
+    }
+
+    #[test]
+    fn select_all_ready() {
+        let (mut tx, mut rx) = mpsc::channel::<usize>(1);
+        let (_tx2, mut rx2) = mpsc::channel::<usize>(1);
+        tx.try_send(1).unwrap();
+
+        let mut select = select_all(vec![rx2.recv(), rx.recv()]);
+        assert_eq!(
+            Poll::Ready((1, Some(1))),
+            Pin::new(&mut select).poll(&mut noop_context())
+        );
+    }
+
+    #[test]
+    fn select_all_pending() {
+        let (_tx, mut rx) = mpsc::channel::<usize>(1);
+        let (mut tx2, _rx2) = mpsc::channel::<usize>(1);
+        tx2.try_send(1).unwrap();
+
+        let mut select = select_all(vec![rx.recv()]);
+        assert_eq!(
+            Poll::Pending,
+            Pin::new(&mut select).poll(&mut noop_context())
+        );
+
+        let mut select = select_all(vec![tx2.send(2)]);
+        assert_eq!(
+            Poll::Pending,
+            Pin::new(&mut select).poll(&mut noop_context())
+        );
+    }
+}
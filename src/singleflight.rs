@@ -0,0 +1,227 @@
+//! Coalesces concurrent requests for the same key, so they share one in-flight response.
+//!
+//! A service receives requests from an mpsc channel, paired with a oneshot sender for the response.
+//! [SingleFlight](./struct.SingleFlight.html) sends the first request for a key to the service,
+//! and requests for the same key which arrive while it is in flight wait for a clone of its response.
+//!
+//! ```rust
+//! use postage::{mpsc, oneshot, prelude::*, singleflight::SingleFlight};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let (requests, mut service) = mpsc::channel::<(String, oneshot::Sender<usize>)>(4);
+//!
+//!     tokio::spawn(async move {
+//!         while let Some((key, mut reply)) = service.recv().await {
+//!             reply.send(key.len()).await.ok();
+//!         }
+//!     });
+//!
+//!     let flights = SingleFlight::new(requests);
+//!     let (a, b) = tokio::join!(flights.request("hello".to_string()), flights.request("hello".to_string()));
+//!     assert_eq!((Some(5), Some(5)), (a, b));
+//! }
+//! ```
+
+use std::{collections::HashMap, fmt, hash::Hash, sync::Arc};
+
+use parking_lot::Mutex;
+
+use crate::{mpsc, oneshot, sink::Sink, stream::Stream};
+
+type Waiters<K, V> = Arc<Mutex<HashMap<K, Vec<oneshot::Sender<V>>>>>;
+
+/// A handle which sends requests to a service, coalescing concurrent requests for the same key.
+///
+/// The handle can be cloned, and the clones share the in-flight requests.
+pub struct SingleFlight<K, V> {
+    requests: mpsc::Sender<(K, oneshot::Sender<V>)>,
+    inflight: Waiters<K, V>,
+}
+
+impl<K, V> SingleFlight<K, V>
+where
+    K: Clone + Eq + Hash,
+    V: Clone,
+{
+    /// Creates a handle which sends requests to the service's request channel
+    pub fn new(requests: mpsc::Sender<(K, oneshot::Sender<V>)>) -> Self {
+        Self {
+            requests,
+            inflight: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Requests the value for the key.  If a request for the key is in flight, waits for its response.
+    ///
+    /// Returns `None` if the request channel is closed, or the service drops the response sender without responding.
+    /// If the request which reached the service is cancelled, the requests waiting for it also resolve with `None`.
+    pub async fn request(&self, key: K) -> Option<V> {
+        let flight = {
+            let mut inflight = self.inflight.lock();
+            match inflight.get_mut(&key) {
+                Some(waiters) => {
+                    let (tx, rx) = oneshot::channel();
+                    waiters.push(tx);
+                    Err(rx)
+                }
+                None => {
+                    inflight.insert(key.clone(), Vec::new());
+                    Ok(Flight {
+                        inflight: &self.inflight,
+                        key: Some(key),
+                    })
+                }
+            }
+        };
+
+        let mut flight = match flight {
+            Ok(flight) => flight,
+            Err(mut rx) => return rx.recv().await,
+        };
+
+        let (reply, mut response) = oneshot::channel();
+        let request = (flight.key().clone(), reply);
+        self.requests.clone().send(request).await.ok()?;
+
+        let value = response.recv().await?;
+        flight.complete(&value);
+        Some(value)
+    }
+
+    /// The number of keys with a request in flight
+    pub fn in_flight(&self) -> usize {
+        self.inflight.lock().len()
+    }
+}
+
+impl<K, V> Clone for SingleFlight<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            requests: self.requests.clone(),
+            inflight: self.inflight.clone(),
+        }
+    }
+}
+
+impl<K, V> fmt::Debug for SingleFlight<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SingleFlight")
+            .field("in_flight", &self.inflight.lock().len())
+            .finish()
+    }
+}
+
+/// Removes the in-flight entry when the request which reached the service completes, or is cancelled.
+///
+/// If the request is cancelled, the waiters' senders are dropped, and they resolve with `None`.
+struct Flight<'a, K: Eq + Hash, V> {
+    inflight: &'a Waiters<K, V>,
+    key: Option<K>,
+}
+
+impl<'a, K: Eq + Hash, V: Clone> Flight<'a, K, V> {
+    fn key(&self) -> &K {
+        self.key.as_ref().expect("flight completed")
+    }
+
+    fn complete(&mut self, value: &V) {
+        let key = self.key.take().expect("flight completed");
+        let waiters = self.inflight.lock().remove(&key).unwrap_or_default();
+
+        for mut waiter in waiters {
+            waiter.try_send(value.clone()).ok();
+        }
+    }
+}
+
+impl<'a, K: Eq + Hash, V> Drop for Flight<'a, K, V> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.inflight.lock().remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::Future, task::Poll};
+
+    use futures_test::task::noop_context;
+
+    use super::SingleFlight;
+    use crate::{
+        mpsc, oneshot,
+        sink::Sink,
+        stream::{Stream, TryRecvError},
+    };
+
+    #[test]
+    fn coalesces_concurrent_requests() {
+        let mut cx = noop_context();
+        let (requests, mut service) = mpsc::channel::<(usize, oneshot::Sender<usize>)>(4);
+        let flights = SingleFlight::new(requests);
+
+        let mut first = Box::pin(flights.request(1));
+        let mut second = Box::pin(flights.request(1));
+        let mut other = Box::pin(flights.request(2));
+        assert_eq!(Poll::Pending, first.as_mut().poll(&mut cx));
+        assert_eq!(Poll::Pending, second.as_mut().poll(&mut cx));
+        assert_eq!(Poll::Pending, other.as_mut().poll(&mut cx));
+        assert_eq!(2, flights.in_flight());
+
+        let (key, mut reply) = service.try_recv().unwrap();
+        assert_eq!(1, key);
+        let (key, _other_reply) = service.try_recv().unwrap();
+        assert_eq!(2, key);
+        assert!(matches!(service.try_recv(), Err(TryRecvError::Pending)));
+
+        reply.try_send(10).unwrap();
+        assert_eq!(Poll::Ready(Some(10)), first.as_mut().poll(&mut cx));
+        assert_eq!(Poll::Ready(Some(10)), second.as_mut().poll(&mut cx));
+        assert_eq!(1, flights.in_flight());
+    }
+
+    #[test]
+    fn later_request_is_sent() {
+        let mut cx = noop_context();
+        let (requests, mut service) = mpsc::channel::<(usize, oneshot::Sender<usize>)>(4);
+        let flights = SingleFlight::new(requests);
+
+        let mut first = Box::pin(flights.request(1));
+        assert_eq!(Poll::Pending, first.as_mut().poll(&mut cx));
+        let (_, mut reply) = service.try_recv().unwrap();
+        reply.try_send(10).unwrap();
+        assert_eq!(Poll::Ready(Some(10)), first.as_mut().poll(&mut cx));
+
+        let mut second = Box::pin(flights.request(1));
+        assert_eq!(Poll::Pending, second.as_mut().poll(&mut cx));
+        assert!(service.try_recv().is_ok());
+    }
+
+    #[test]
+    fn cancelled_request_releases_waiters() {
+        let mut cx = noop_context();
+        let (requests, _service) = mpsc::channel::<(usize, oneshot::Sender<usize>)>(4);
+        let flights = SingleFlight::new(requests);
+
+        let mut first = Box::pin(flights.request(1));
+        let mut second = Box::pin(flights.request(1));
+        assert_eq!(Poll::Pending, first.as_mut().poll(&mut cx));
+        assert_eq!(Poll::Pending, second.as_mut().poll(&mut cx));
+
+        drop(first);
+        assert_eq!(0, flights.in_flight());
+        assert_eq!(Poll::Ready(None), second.as_mut().poll(&mut cx));
+    }
+
+    #[tokio::test]
+    async fn closed_service() {
+        let (requests, service) = mpsc::channel::<(usize, oneshot::Sender<usize>)>(4);
+        let flights = SingleFlight::new(requests);
+        drop(service);
+
+        assert_eq!(None, flights.request(1).await);
+        assert_eq!(0, flights.in_flight());
+    }
+}
@@ -50,20 +50,50 @@
 //!     tx.send(true).await.ok();
 //! }
 //! ```
-use std::marker::PhantomPinned;
-use std::{future::Future, ops::DerefMut, pin::Pin, task::Poll};
+use std::{
+    future::Future,
+    ops::DerefMut,
+    pin::Pin,
+    sync::Arc,
+    task::{Poll, Waker},
+};
 
 use crate::Context;
 use pin_project::pin_project;
 
+use self::abort::AbortWaker;
+
+mod abort;
+#[cfg(feature = "timer")]
+mod batched;
+mod buffered;
 mod chain;
+#[cfg(feature = "timer")]
+mod circuit_breaker;
+mod dyn_sink;
 mod errors;
 mod filter;
+mod layer;
+#[cfg(feature = "timer")]
+mod retry;
+mod select;
+mod shed;
 
 #[cfg(feature = "logging")]
 mod sink_log;
 
+#[cfg(feature = "timer")]
+pub use batched::BatchedSink;
+pub use buffered::{BufferedSink, FlushFuture, PollReady};
+#[cfg(feature = "timer")]
+pub use circuit_breaker::{CircuitBreaker, CircuitBreakerSink, CircuitState};
+pub use dyn_sink::DynSink;
 pub use errors::*;
+pub use layer::{layer_fn, Identity, Layer, LayerFn, Stack};
+#[cfg(feature = "timer")]
+pub use retry::{RetryPolicy, RetrySink};
+pub use select::{send_to_first_ready, SendToFirstReady};
+pub use shed::{ShedPolicy, ShedSink};
 
 /// A sink which can asynchronously accept messages, and at some point may refuse to accept any further messages.
 ///
@@ -110,7 +140,7 @@ pub use errors::*;
 ///
 ///     combo.send(1usize).await.ok();
 ///     combo.send(2usize).await.ok();
-
+///
 ///     assert_eq!(Some(2usize), rx.recv().await);
 ///     drop(rx);
 ///
@@ -143,10 +173,22 @@ pub trait Sink {
     /// Returns:
     /// - `Ok(())` if the value was accepted.
     /// - `Err(SendError(value))` if the sink rejected the message.
-    fn send(&mut self, value: Self::Item) -> SendFuture<Self> {
+    ///
+    /// The future is cancel-safe.  Until it resolves, the value has not been accepted by the sink,
+    /// and it can be recovered with [SendFuture::into_inner](./struct.SendFuture.html#method.into_inner)
+    /// or [SendFuture::take_value](./struct.SendFuture.html#method.take_value).
+    fn send(&mut self, value: Self::Item) -> SendFuture<'_, Self> {
         SendFuture::new(self, value)
     }
 
+    /// Attempts to send a message into the sink, with a future which can be cancelled by
+    /// [AbortableSendFuture::abort_pending](./struct.AbortableSendFuture.html#method.abort_pending).
+    ///
+    /// The sink is given a waker which can be disarmed, so a send that is combined with a timeout doesn't wake the task after it is aborted.
+    fn send_abortable(&mut self, value: Self::Item) -> AbortableSendFuture<'_, Self> {
+        AbortableSendFuture::new(self, value)
+    }
+
     /// Attempts to send a message over the sink, without blocking.
     ///
     /// Returns:
@@ -196,6 +238,71 @@ pub trait Sink {
         filter::FilterSink::new(filter, self)
     }
 
+    /// Accumulates messages, and sends them to the sink in batches.
+    ///
+    /// A batch is sent when it reaches the size, or when no message has arrived for the idle timeout.
+    /// The timeout is checked when the batched sink is polled, see [flush_idle](./struct.BatchedSink.html#method.flush_idle).
+    ///
+    /// Requires the `time` feature
+    #[cfg(feature = "timer")]
+    fn batched<T>(self, size: usize, idle: std::time::Duration) -> BatchedSink<Self, T>
+    where
+        Self: Sink<Item = Vec<T>> + Sized,
+    {
+        BatchedSink::new(self, size, idle)
+    }
+
+    /// Wraps the sink with a circuit breaker, which opens after consecutive failed sends.
+    ///
+    /// While the circuit is open, messages are rejected immediately.  After the cool-down, the circuit half-opens,
+    /// and the next message is sent as a trial.  State changes can be observed with [events](./struct.CircuitBreakerSink.html#method.events).
+    ///
+    /// Requires the `time` feature
+    #[cfg(feature = "timer")]
+    fn circuit_breaker(self, config: CircuitBreaker) -> CircuitBreakerSink<Self>
+    where
+        Self: Sized,
+    {
+        CircuitBreakerSink::new(self, config)
+    }
+
+    /// Retries messages which are rejected, or time out, with exponential backoff.
+    ///
+    /// The sink makes up to the maximum number of attempts allowed by the policy, and then rejects the message,
+    /// or forwards it to the [dead-letter sink](./struct.RetrySink.html#method.dead_letter).
+    /// The sink tracks the attempts of the message which is being sent.  If a send is abandoned during the backoff,
+    /// the next message waits for the backoff, and inherits the failed attempts.
+    ///
+    /// Requires the `time` feature
+    #[cfg(feature = "timer")]
+    fn retry(self, policy: RetryPolicy) -> RetrySink<Self>
+    where
+        Self: Sized,
+    {
+        RetrySink::new(self, policy)
+    }
+
+    /// Sheds low-priority messages when the sink is congested.
+    ///
+    /// Messages are classified with the function.  If the sink can't accept a message, the [policy](./struct.ShedPolicy.html)
+    /// determines whether the message is dropped, or waits for capacity.  Dropped messages complete the send, and are counted by the sink.
+    fn shed<F>(self, policy: ShedPolicy, classify: F) -> ShedSink<Self, F>
+    where
+        F: FnMut(&Self::Item) -> usize,
+        Self: Sized,
+    {
+        ShedSink::new(self, policy, classify)
+    }
+
+    /// Wraps the sink with a middleware [Layer](./trait.Layer.html).
+    fn wrap<L>(self, layer: L) -> L::Sink
+    where
+        L: Layer<Self>,
+        Self: Sized,
+    {
+        layer.layer(self)
+    }
+
     /// Logs messages that are accepted by the sink using the Debug trait, at the provided log level.
     ///
     /// Requires the `logging` feature
@@ -253,6 +360,11 @@ pub enum PollSend<T> {
 
 /// A future returned by `Sink::send`, which wraps an item.
 /// The item is sent to the sink, or returned if the sink is closed.
+///
+/// The future is `Unpin`, so it can be polled by reference in a `select!` loop,
+/// and the item recovered with `into_inner` if another branch completes first.
+/// When the send is combined with a timeout, use [Sink::send_abortable](./trait.Sink.html#method.send_abortable),
+/// which also disarms the waker held by the sink.
 #[pin_project]
 #[must_use = "futures do nothing unless polled"]
 pub struct SendFuture<'s, S>
@@ -262,21 +374,38 @@ where
     #[pin]
     send: &'s mut S,
     value: Option<S::Item>,
-    #[pin]
-    _pin: PhantomPinned,
+    // set when the value is taken by take_value, after which the future must not be polled
+    taken: bool,
 }
 
 impl<'s, S> SendFuture<'s, S>
 where
     S: Sink + ?Sized,
 {
-    pub fn new(send: &'s mut S, value: S::Item) -> SendFuture<S> {
+    pub fn new(send: &'s mut S, value: S::Item) -> SendFuture<'s, S> {
         Self {
             send,
             value: Some(value),
-            _pin: PhantomPinned,
+            taken: false,
         }
     }
+
+    /// Consumes the future, returning the value if it has not been accepted by the sink.
+    ///
+    /// If the future was polled and returned `Pending`, the value was never enqueued.
+    /// This allows a send that lost a `select!` race to be retried without losing the message.
+    pub fn into_inner(self) -> Option<S::Item> {
+        self.value
+    }
+
+    /// Takes the value out of a pinned future, if it has not been accepted by the sink.
+    ///
+    /// Panics if the future is polled again.
+    pub fn take_value(self: Pin<&mut Self>) -> Option<S::Item> {
+        let this = self.project();
+        *this.taken = true;
+        this.value.take()
+    }
 }
 
 impl<'s, S> Future for SendFuture<'s, S>
@@ -286,14 +415,114 @@ where
     type Output = Result<(), SendError<S::Item>>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
-        if self.value.is_none() {
-            return Poll::Ready(Ok(()));
+        assert!(!self.taken, "SendFuture polled after take_value");
+
+        let this = self.project();
+        let value = match this.value.take() {
+            Some(value) => value,
+            None => return Poll::Ready(Ok(())),
+        };
+
+        let mut cx: Context<'_> = cx.into();
+        match this.send.poll_send(&mut cx, value) {
+            PollSend::Ready => Poll::Ready(Ok(())),
+            PollSend::Pending(value) => {
+                *this.value = Some(value);
+                Poll::Pending
+            }
+            PollSend::Rejected(value) => Poll::Ready(Err(SendError(value))),
         }
+    }
+}
 
+/// A future returned by `Sink::send_abortable`, which wraps an item.
+/// The item is sent to the sink, returned if the sink is closed, or reclaimed with `abort_pending`.
+#[pin_project]
+#[must_use = "futures do nothing unless polled"]
+pub struct AbortableSendFuture<'s, S>
+where
+    S: Sink + ?Sized,
+{
+    #[pin]
+    send: &'s mut S,
+    value: Option<S::Item>,
+    // allocated by the first poll, and handed to the sink in place of the task waker
+    abort: Option<(Arc<AbortWaker>, Waker)>,
+    // set when the value is taken by abort_pending, after which the future must not be polled
+    taken: bool,
+}
+
+impl<'s, S> AbortableSendFuture<'s, S>
+where
+    S: Sink + ?Sized,
+{
+    pub fn new(send: &'s mut S, value: S::Item) -> AbortableSendFuture<'s, S> {
+        Self {
+            send,
+            value: Some(value),
+            abort: None,
+            taken: false,
+        }
+    }
+
+    /// Cancels a pending send, returning the value if it has not been accepted by the sink.
+    ///
+    /// The waker held by the sink is disarmed, so the task is not woken for this send.
+    /// A value returned by `abort_pending` was never enqueued, and a value that was enqueued is never returned,
+    /// so a send that races with a timeout is delivered exactly once, or handed back.
+    ///
+    /// Panics if the future is polled again.
+    ///
+    /// ```rust
+    /// use std::{future::Future, pin::pin, task::Poll};
+    /// use futures_test::task::noop_context;
+    /// use postage::{mpsc, sink::Sink};
+    ///
+    /// let (mut tx, _rx) = mpsc::channel(1);
+    /// tx.try_send(1usize).unwrap();
+    ///
+    /// let mut send = pin!(tx.send_abortable(2));
+    /// assert!(send.as_mut().poll(&mut noop_context()).is_pending());
+    ///
+    /// // the timeout elapsed
+    /// assert_eq!(Some(2), send.as_mut().abort_pending());
+    /// ```
+    pub fn abort_pending(self: Pin<&mut Self>) -> Option<S::Item> {
         let this = self.project();
+        *this.taken = true;
+
+        if let Some((abort, _waker)) = this.abort.take() {
+            abort.abort();
+        }
+
+        this.value.take()
+    }
+}
 
-        let mut cx: crate::Context<'_> = cx.into();
-        match this.send.poll_send(&mut cx, this.value.take().unwrap()) {
+impl<'s, S> Future for AbortableSendFuture<'s, S>
+where
+    S: Sink + Unpin + ?Sized,
+{
+    type Output = Result<(), SendError<S::Item>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        assert!(
+            !self.taken,
+            "AbortableSendFuture polled after abort_pending"
+        );
+
+        let this = self.project();
+        let value = match this.value.take() {
+            Some(value) => value,
+            None => return Poll::Ready(Ok(())),
+        };
+
+        let (abort, waker) = this
+            .abort
+            .get_or_insert_with(|| AbortWaker::new(cx.waker()));
+        abort.register(cx.waker());
+
+        match this.send.poll_send(&mut Context::from_waker(waker), value) {
             PollSend::Ready => Poll::Ready(Ok(())),
             PollSend::Pending(value) => {
                 *this.value = Some(value);
@@ -306,6 +535,132 @@ where
 
 #[cfg(test)]
 mod tests {
+    use std::{
+        future::Future,
+        pin::{pin, Pin},
+        task::Poll,
+    };
+
+    use futures_test::task::{new_count_waker, noop_context};
+
+    use super::Sink;
+    use crate::{
+        mpsc,
+        sink::PollSend,
+        stream::{Stream, TryRecvError},
+        test::sink::{pending, ready, test_sink},
+    };
+
+    #[test]
+    fn send_future_into_inner() {
+        let mut sink = pending();
+        let mut future = sink.send(1usize);
+
+        let mut cx = noop_context();
+        assert!(Pin::new(&mut future).poll(&mut cx).is_pending());
+        assert_eq!(Some(1usize), future.into_inner());
+    }
+
+    #[test]
+    fn send_future_take_value() {
+        let mut sink = pending();
+        let mut future = pin!(sink.send(1usize));
+
+        let mut cx = noop_context();
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(Some(1usize), future.as_mut().take_value());
+        assert_eq!(None, future.as_mut().take_value());
+    }
+
+    #[test]
+    #[should_panic]
+    fn send_future_poll_after_take_value() {
+        let mut sink = pending();
+        let mut future = pin!(sink.send(1usize));
+
+        let mut cx = noop_context();
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+        future.as_mut().take_value();
+        let _ = future.as_mut().poll(&mut cx);
+    }
+
+    #[test]
+    fn send_future_accepted() {
+        let mut sink = ready();
+        let mut future = pin!(sink.send(1usize));
+
+        let mut cx = noop_context();
+        assert_eq!(Poll::Ready(Ok(())), future.as_mut().poll(&mut cx));
+        assert_eq!(None, future.as_mut().take_value());
+    }
+
+    #[test]
+    fn send_future_wakes_task() {
+        let (waker, count) = new_count_waker();
+        let mut cx = std::task::Context::from_waker(&waker);
+        let (mut tx, mut rx) = mpsc::channel(1);
+        tx.try_send(1usize).unwrap();
+
+        let mut future = pin!(tx.send(2));
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(0, count.get());
+
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(1, count.get());
+        assert_eq!(Poll::Ready(Ok(())), future.as_mut().poll(&mut cx));
+        assert_eq!(Ok(2), rx.try_recv());
+    }
+
+    #[test]
+    fn send_future_polls_once() {
+        let mut sink = test_sink([PollSend::Pending(1usize), PollSend::Ready]);
+        let mut cx = noop_context();
+
+        // each poll of the future is a single attempt on the sink
+        let mut future = pin!(sink.send(1usize));
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(Poll::Ready(Ok(())), future.as_mut().poll(&mut cx));
+        assert_eq!(&[1], sink.values());
+    }
+
+    #[test]
+    fn send_future_abort_pending() {
+        let (waker, count) = new_count_waker();
+        let mut cx = std::task::Context::from_waker(&waker);
+        let (mut tx, mut rx) = mpsc::channel(1);
+        tx.try_send(1usize).unwrap();
+
+        let mut future = pin!(tx.send_abortable(2));
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(Some(2), future.as_mut().abort_pending());
+
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(0, count.get());
+        assert_eq!(Err(TryRecvError::Pending), rx.try_recv());
+    }
+
+    #[test]
+    fn send_future_abort_accepted() {
+        let mut sink = ready();
+        let mut future = pin!(sink.send_abortable(1usize));
+
+        let mut cx = noop_context();
+        assert_eq!(Poll::Ready(Ok(())), future.as_mut().poll(&mut cx));
+        assert_eq!(None, future.as_mut().abort_pending());
+    }
+
+    #[test]
+    #[should_panic]
+    fn send_future_poll_after_abort() {
+        let mut sink = pending();
+        let mut future = pin!(sink.send_abortable(1usize));
+
+        let mut cx = noop_context();
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+        future.as_mut().abort_pending();
+        let _ = future.as_mut().poll(&mut cx);
+    }
+
     #[cfg(feature = "blocking")]
     #[test]
     fn test_blocking() {
@@ -0,0 +1,54 @@
+use std::{
+    sync::Arc,
+    task::{Wake, Waker},
+};
+
+use parking_lot::Mutex;
+
+/// A waker which forwards to the task waker, until the send is aborted.
+///
+/// Sinks hold on to the wakers they are given until they are notified.
+/// Once the send is aborted, the wakers held by the sink are disarmed, and no longer wake the task.
+#[derive(Debug)]
+pub(super) struct AbortWaker {
+    task: Mutex<Option<Waker>>,
+}
+
+impl AbortWaker {
+    pub fn new(task: &Waker) -> (Arc<Self>, Waker) {
+        let this = Arc::new(Self {
+            task: Mutex::new(Some(task.clone())),
+        });
+
+        let waker = Waker::from(this.clone());
+        (this, waker)
+    }
+
+    /// Updates the task waker, if the future has moved to another task
+    pub fn register(&self, task: &Waker) {
+        let mut lock = self.task.lock();
+        match *lock {
+            Some(ref waker) if waker.will_wake(task) => {}
+            _ => *lock = Some(task.clone()),
+        }
+    }
+
+    /// Disarms every copy of the waker
+    pub fn abort(&self) {
+        self.task.lock().take();
+    }
+}
+
+impl Wake for AbortWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        // clone the waker, so the task is not woken while the lock is held
+        let task = self.task.lock().clone();
+        if let Some(task) = task {
+            task.wake();
+        }
+    }
+}
@@ -0,0 +1,333 @@
+use std::{
+    fmt,
+    future::poll_fn,
+    pin::Pin,
+    task::Poll,
+    time::{Duration, Instant},
+};
+
+use pin_project::pin_project;
+
+use super::retry::poll_deadline;
+use crate::{
+    sink::{BufferedSink, PollReady, PollSend, SendErrorKind, Sink},
+    time::timer::Timer,
+    Context,
+};
+
+/// The sink returned by [Sink::batched](./trait.Sink.html#method.batched)
+///
+/// Buffered items are lost if the sink is dropped.  A partial batch can be sent with
+/// [flush](./trait.BufferedSink.html#method.flush) or [close](./trait.BufferedSink.html#method.close).
+#[pin_project]
+pub struct BatchedSink<S, T> {
+    #[pin]
+    sink: S,
+    size: usize,
+    idle: Duration,
+    buffer: Vec<T>,
+    batch: Option<Vec<T>>,
+    idle_deadline: Option<Instant>,
+    timer: Option<Timer>,
+    closed: bool,
+}
+
+impl<S, T> BatchedSink<S, T> {
+    pub(crate) fn new(sink: S, size: usize, idle: Duration) -> Self {
+        let size = size.max(1);
+
+        Self {
+            sink,
+            size,
+            idle,
+            buffer: Vec::with_capacity(size),
+            batch: None,
+            idle_deadline: None,
+            timer: None,
+            closed: false,
+        }
+    }
+
+    /// The number of accepted items which have not been sent downstream
+    pub fn buffered(&self) -> usize {
+        self.buffer.len() + self.batch.as_ref().map(Vec::len).unwrap_or(0)
+    }
+
+    /// Returns a reference to the wrapped sink
+    pub fn get_ref(&self) -> &S {
+        &self.sink
+    }
+
+    /// Returns a mutable reference to the wrapped sink
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    /// Returns the wrapped sink.  Buffered items are dropped.
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+}
+
+impl<S, T> BatchedSink<S, T>
+where
+    S: Sink<Item = Vec<T>>,
+{
+    /// Waits until the buffered items have been idle for the timeout, and sends them downstream.
+    ///
+    /// The sink only checks the timeout when it is polled, so a task which waits for items should also wait for this future,
+    /// e.g. in a `select!`.  If no items are buffered, the future waits indefinitely.
+    /// Returns an error if the wrapped sink is closed.
+    pub async fn flush_idle(&mut self) -> Result<(), SendErrorKind>
+    where
+        S: Unpin,
+    {
+        poll_fn(|cx| {
+            let mut this = Pin::new(&mut *self);
+            let buffered = this.buffered() > 0;
+
+            match this.as_mut().poll_drain(&mut cx.into()) {
+                PollReady::Ready if buffered && this.buffered() == 0 => Poll::Ready(Ok(())),
+                PollReady::Ready | PollReady::Pending => Poll::Pending,
+                PollReady::Closed => Poll::Ready(Err(SendErrorKind::Closed)),
+            }
+        })
+        .await
+    }
+
+    /// Moves the buffered items into the batch which is sent downstream
+    fn stage(self: Pin<&mut Self>) {
+        let this = self.project();
+        *this.batch = Some(std::mem::take(this.buffer));
+        *this.idle_deadline = None;
+    }
+
+    /// Sends batches downstream, until the buffer has room.  A partial batch is sent if it has been idle for the timeout.
+    fn poll_drain(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        loop {
+            let this = self.as_mut().project();
+            if let Some(batch) = this.batch.take() {
+                match this.sink.poll_send(cx, batch) {
+                    PollSend::Ready => {}
+                    PollSend::Pending(batch) => {
+                        *this.batch = Some(batch);
+                        return PollReady::Pending;
+                    }
+                    PollSend::Rejected(_batch) => {
+                        *this.closed = true;
+                        return PollReady::Closed;
+                    }
+                }
+            }
+
+            let idle = match *this.idle_deadline {
+                Some(deadline) => poll_deadline(this.timer, cx, deadline),
+                None => false,
+            };
+
+            if this.buffer.len() < *this.size && !idle {
+                return PollReady::Ready;
+            }
+
+            self.as_mut().stage();
+        }
+    }
+}
+
+impl<S, T> Sink for BatchedSink<S, T>
+where
+    S: Sink<Item = Vec<T>>,
+{
+    type Item = T;
+
+    fn poll_send(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        value: Self::Item,
+    ) -> PollSend<Self::Item> {
+        if self.closed {
+            return PollSend::Rejected(value);
+        }
+
+        if let PollReady::Closed = self.as_mut().poll_drain(cx) {
+            return PollSend::Rejected(value);
+        }
+
+        let this = self.as_mut().project();
+        if this.buffer.len() >= *this.size {
+            // the previous batch is waiting for the wrapped sink
+            return PollSend::Pending(value);
+        }
+
+        this.buffer.push(value);
+        if this.buffer.len() < *this.size {
+            let deadline = Instant::now() + *this.idle;
+            *this.idle_deadline = Some(deadline);
+
+            // registers the timer, so the task is woken when the batch becomes idle
+            poll_deadline(this.timer, cx, deadline);
+        } else if this.batch.is_none() {
+            // the value has been accepted.  if the wrapped sink closed, the next send is rejected.
+            self.as_mut().stage();
+            self.poll_drain(cx);
+        }
+
+        PollSend::Ready
+    }
+}
+
+impl<S, T> BufferedSink for BatchedSink<S, T>
+where
+    S: Sink<Item = Vec<T>>,
+{
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        if self.closed {
+            return PollReady::Closed;
+        }
+
+        match self.as_mut().poll_drain(cx) {
+            PollReady::Closed => PollReady::Closed,
+            _ if self.buffer.len() < self.size => PollReady::Ready,
+            _ => PollReady::Pending,
+        }
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        match self.as_mut().poll_drain(cx) {
+            PollReady::Ready => {}
+            poll => return poll,
+        }
+
+        if self.buffer.is_empty() {
+            return PollReady::Ready;
+        }
+
+        self.as_mut().stage();
+        self.poll_drain(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        let flush = self.as_mut().poll_flush(cx);
+        if flush != PollReady::Pending {
+            *self.project().closed = true;
+        }
+
+        flush
+    }
+}
+
+impl<S, T> fmt::Debug for BatchedSink<S, T>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BatchedSink")
+            .field("sink", &self.sink)
+            .field("size", &self.size)
+            .field("idle", &self.idle)
+            .field("buffered", &self.buffered())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        mpsc,
+        sink::{BufferedSink, SendErrorKind, Sink, TrySendError},
+        stream::{Stream, TryRecvError},
+    };
+
+    #[test]
+    fn sends_full_batch() {
+        let (tx, mut rx) = mpsc::channel(4);
+        let mut tx = tx.batched(2, Duration::from_secs(60));
+
+        tx.try_send(1usize).unwrap();
+        assert_eq!(Err(TryRecvError::Pending), rx.try_recv());
+        assert_eq!(1, tx.buffered());
+
+        tx.try_send(2).unwrap();
+        assert_eq!(Ok(vec![1, 2]), rx.try_recv());
+        assert_eq!(0, tx.buffered());
+    }
+
+    #[test]
+    fn waits_for_wrapped_sink() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let mut tx = tx.batched(1, Duration::from_secs(60));
+
+        tx.try_send(1usize).unwrap();
+        tx.try_send(2).unwrap();
+        tx.try_send(3).unwrap();
+        assert_eq!(Err(TrySendError::Pending(4)), tx.try_send(4));
+
+        assert_eq!(Ok(vec![1]), rx.try_recv());
+        tx.try_send(4).unwrap();
+        assert_eq!(Ok(vec![2]), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Pending), rx.try_recv());
+        assert_eq!(2, tx.buffered());
+    }
+
+    #[test]
+    fn rejects_after_wrapped_sink_closes() {
+        let (tx, rx) = mpsc::channel(4);
+        let mut tx = tx.batched(1, Duration::from_secs(60));
+        drop(rx);
+
+        tx.try_send(1usize).unwrap();
+        assert_eq!(Err(TrySendError::Rejected(2)), tx.try_send(2));
+    }
+
+    #[tokio::test]
+    async fn flush_sends_partial_batch() {
+        let (tx, mut rx) = mpsc::channel(4);
+        let mut tx = tx.batched(4, Duration::from_secs(60));
+
+        tx.send(1usize).await.unwrap();
+        tx.send(2).await.unwrap();
+        assert_eq!(Ok(()), tx.flush().await);
+        assert_eq!(Ok(vec![1, 2]), rx.try_recv());
+
+        tx.send(3).await.unwrap();
+        assert_eq!(Ok(()), tx.close().await);
+        assert_eq!(Ok(vec![3]), rx.try_recv());
+        assert!(tx.send(4).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn flush_idle() {
+        let (tx, mut rx) = mpsc::channel(4);
+        let mut tx = tx.batched(4, Duration::from_millis(10));
+
+        tx.send(1usize).await.unwrap();
+        tx.send(2).await.unwrap();
+        assert_eq!(Ok(()), tx.flush_idle().await);
+        assert_eq!(Ok(vec![1, 2]), rx.try_recv());
+    }
+
+    #[tokio::test]
+    async fn idle_batch_sent_before_next_item() {
+        let (tx, mut rx) = mpsc::channel(4);
+        let mut tx = tx.batched(4, Duration::from_millis(10));
+
+        tx.send(1usize).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        tx.send(2).await.unwrap();
+
+        assert_eq!(Ok(vec![1]), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Pending), rx.try_recv());
+    }
+
+    #[tokio::test]
+    async fn flush_idle_closed() {
+        let (tx, rx) = mpsc::channel(4);
+        let mut tx = tx.batched(4, Duration::from_millis(1));
+
+        tx.send(1usize).await.unwrap();
+        drop(rx);
+        assert_eq!(Err(SendErrorKind::Closed), tx.flush_idle().await);
+    }
+}
@@ -0,0 +1,221 @@
+use std::{future::Future, ops::DerefMut, pin::Pin, task::Poll};
+
+use crate::{
+    sink::{SendErrorKind, Sink},
+    Context,
+};
+
+/// The result of a `BufferedSink` readiness, flush, or close poll.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PollReady {
+    /// The operation completed
+    Ready,
+    /// The operation could not complete.  The sink will call the waker in the context when it can make progress.
+    Pending,
+    /// The sink is closed
+    Closed,
+}
+
+/// A sink which may buffer accepted items before they are delivered, such as an adapter over a socket or file.
+///
+/// `poll_send` returning `Ready` means the item was accepted into the buffer.  `poll_flush` completes
+/// when all accepted items have been delivered, and `poll_close` flushes the sink and then closes it.
+///
+/// Postage channel senders deliver items as they are accepted, so their flush and close operations complete immediately.
+/// The exception is the mpsc sender, whose flush waits until the receiver has taken the buffered messages.
+///
+/// The sink adapters, such as `filter`, `retry`, and `ChainSink`, implement `BufferedSink` when the wrapped sinks do,
+/// and forward readiness, flush, and close to them.
+pub trait BufferedSink: Sink {
+    /// Polls for capacity.  If this returns `Ready`, the next `poll_send` is expected to accept an item.
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady;
+
+    /// Polls to deliver all accepted items.
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady;
+
+    /// Polls to flush the buffer, and then close the sink.  Items should not be sent after the close completes.
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady;
+
+    /// Delivers all accepted items.  Returns an error if the sink closed before the buffer was flushed.
+    fn flush(&mut self) -> FlushFuture<'_, Self>
+    where
+        Self: Unpin,
+    {
+        FlushFuture {
+            sink: self,
+            close: false,
+        }
+    }
+
+    /// Flushes the sink, and then closes it.
+    fn close(&mut self) -> FlushFuture<'_, Self>
+    where
+        Self: Unpin,
+    {
+        FlushFuture {
+            sink: self,
+            close: true,
+        }
+    }
+}
+
+impl<S> BufferedSink for &mut S
+where
+    S: BufferedSink + Unpin + ?Sized,
+{
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        S::poll_ready(Pin::new(&mut **self), cx)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        S::poll_flush(Pin::new(&mut **self), cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        S::poll_close(Pin::new(&mut **self), cx)
+    }
+}
+
+impl<P, S> BufferedSink for Pin<P>
+where
+    P: DerefMut<Target = S> + Unpin,
+    S: BufferedSink + Unpin + ?Sized,
+{
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        Pin::get_mut(self).as_mut().poll_ready(cx)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        Pin::get_mut(self).as_mut().poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        Pin::get_mut(self).as_mut().poll_close(cx)
+    }
+}
+
+/// A future returned by `BufferedSink::flush` and `BufferedSink::close`.
+#[must_use = "futures do nothing unless polled"]
+pub struct FlushFuture<'s, S: ?Sized> {
+    sink: &'s mut S,
+    close: bool,
+}
+
+impl<'s, S> Future for FlushFuture<'s, S>
+where
+    S: BufferedSink + Unpin + ?Sized,
+{
+    type Output = Result<(), SendErrorKind>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut cx: crate::Context<'_> = cx.into();
+
+        let sink = Pin::new(&mut *this.sink);
+        let poll = if this.close {
+            sink.poll_close(&mut cx)
+        } else {
+            sink.poll_flush(&mut cx)
+        };
+
+        match poll {
+            PollReady::Ready => Poll::Ready(Ok(())),
+            PollReady::Pending => Poll::Pending,
+            PollReady::Closed => Poll::Ready(Err(SendErrorKind::Closed)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+
+    use super::{BufferedSink, PollReady};
+    use crate::{
+        sink::{PollSend, SendErrorKind, Sink},
+        Context,
+    };
+
+    /// Accepts items into a buffer, which is delivered on flush
+    struct WriteBuffer {
+        buffer: Vec<usize>,
+        written: Vec<usize>,
+        closed: bool,
+    }
+
+    impl Sink for WriteBuffer {
+        type Item = usize;
+
+        fn poll_send(self: Pin<&mut Self>, _cx: &mut Context<'_>, value: usize) -> PollSend<usize> {
+            if self.closed {
+                return PollSend::Rejected(value);
+            }
+
+            self.get_mut().buffer.push(value);
+            PollSend::Ready
+        }
+    }
+
+    impl BufferedSink for WriteBuffer {
+        fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> PollReady {
+            if self.closed {
+                PollReady::Closed
+            } else {
+                PollReady::Ready
+            }
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> PollReady {
+            let this = self.get_mut();
+            if this.closed && !this.buffer.is_empty() {
+                return PollReady::Closed;
+            }
+
+            this.written.append(&mut this.buffer);
+            PollReady::Ready
+        }
+
+        fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+            let flush = self.as_mut().poll_flush(cx);
+            self.get_mut().closed = true;
+            flush
+        }
+    }
+
+    #[tokio::test]
+    async fn flush_and_close() {
+        let mut sink = WriteBuffer {
+            buffer: Vec::new(),
+            written: Vec::new(),
+            closed: false,
+        };
+
+        sink.send(1).await.unwrap();
+        assert!(sink.written.is_empty());
+
+        assert_eq!(Ok(()), sink.flush().await);
+        assert_eq!(vec![1], sink.written);
+
+        sink.send(2).await.unwrap();
+        assert_eq!(Ok(()), sink.close().await);
+        assert_eq!(vec![1, 2], sink.written);
+
+        assert!(sink.send(3).await.is_err());
+        assert_eq!(
+            PollReady::Closed,
+            Pin::new(&mut sink).poll_ready(&mut Context::empty())
+        );
+        assert_eq!(Ok(()), sink.flush().await);
+    }
+
+    #[tokio::test]
+    async fn flush_closed_error() {
+        let mut sink = WriteBuffer {
+            buffer: vec![1],
+            written: Vec::new(),
+            closed: true,
+        };
+
+        assert_eq!(Err(SendErrorKind::Closed), sink.flush().await);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::sink::{PollSend, Sink};
+use crate::sink::{BufferedSink, PollReady, PollSend, Sink};
 use crate::Context;
 use atomic::{Atomic, Ordering};
 use pin_project::pin_project;
@@ -34,6 +34,23 @@ where
     }
 }
 
+impl<Left, Right> ChainSink<Left, Right> {
+    /// Returns references to the first and second sinks
+    pub fn get_ref(&self) -> (&Left, &Right) {
+        (&self.left, &self.right)
+    }
+
+    /// Returns mutable references to the first and second sinks
+    pub fn get_mut(&mut self) -> (&mut Left, &mut Right) {
+        (&mut self.left, &mut self.right)
+    }
+
+    /// Returns the first and second sinks
+    pub fn into_inner(self) -> (Left, Right) {
+        (self.left, self.right)
+    }
+}
+
 impl<Left, Right> Sink for ChainSink<Left, Right>
 where
     Left: Sink,
@@ -82,13 +99,67 @@ where
     }
 }
 
+impl<Left, Right> BufferedSink for ChainSink<Left, Right>
+where
+    Left: BufferedSink,
+    Right: BufferedSink<Item = Left::Item>,
+{
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        let this = self.project();
+
+        match this.state.load(Ordering::Acquire) {
+            State::WritingLeft => match this.left.poll_ready(cx) {
+                // the next send is rejected by the left sink, and written to the right sink
+                PollReady::Closed => this.right.poll_ready(cx),
+                poll => poll,
+            },
+            State::WritingRight => this.right.poll_ready(cx),
+            State::Closed => PollReady::Closed,
+        }
+    }
+
+    /// Flushes the sink which is being written.  Once the chain moves to the right sink,
+    /// messages which the left sink did not deliver before it closed cannot be flushed.
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        let this = self.project();
+
+        match this.state.load(Ordering::Acquire) {
+            State::WritingLeft => this.left.poll_flush(cx),
+            State::WritingRight => this.right.poll_flush(cx),
+            State::Closed => PollReady::Closed,
+        }
+    }
+
+    /// Closes both sinks.  Returns the result of the sink which was being written.
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        let this = self.project();
+        let state = this.state.load(Ordering::Acquire);
+        if let State::Closed = state {
+            return PollReady::Closed;
+        }
+
+        let left = this.left.poll_close(cx);
+        let right = this.right.poll_close(cx);
+        if left == PollReady::Pending || right == PollReady::Pending {
+            return PollReady::Pending;
+        }
+
+        this.state.store(State::Closed, Ordering::Release);
+        match state {
+            State::WritingLeft => left,
+            _ => right,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::pin::Pin;
 
     use crate::test::sink::*;
     use crate::{
-        sink::{PollSend, Sink},
+        sink::{BufferedSink, PollReady, PollSend, Sink},
+        stream::Stream,
         Context,
     };
 
@@ -112,8 +183,6 @@ mod tests {
             Pin::new(&mut chain).poll_send(&mut cx, 3)
         );
 
-        drop(chain);
-
         assert_eq!(&[1], left.values());
         assert_eq!(&[2], right.values());
     }
@@ -136,8 +205,6 @@ mod tests {
             Pin::new(&mut chain).poll_send(&mut cx, 3)
         );
 
-        drop(chain);
-
         assert_eq!(Vec::<usize>::new(), left.values());
         assert_eq!(&[2], right.values());
     }
@@ -167,9 +234,30 @@ mod tests {
             Pin::new(&mut chain).poll_send(&mut cx, 4)
         );
 
-        drop(chain);
-
         assert_eq!(Vec::<usize>::new(), left.values());
         assert_eq!(Vec::<usize>::new(), right.values());
     }
+
+    #[test]
+    fn flush_right() {
+        let mut cx = Context::empty();
+        let (left, left_rx) = crate::mpsc::channel(4);
+        let (right, mut right_rx) = crate::mpsc::channel(4);
+        let mut chain = ChainSink::new(left, right);
+
+        drop(left_rx);
+        assert_eq!(PollReady::Ready, Pin::new(&mut chain).poll_ready(&mut cx));
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut chain).poll_send(&mut cx, 1usize)
+        );
+
+        // the left sink is closed, and the flush waits for the right sink
+        assert_eq!(PollReady::Pending, Pin::new(&mut chain).poll_flush(&mut cx));
+        assert_eq!(Ok(1), right_rx.try_recv());
+        assert_eq!(PollReady::Ready, Pin::new(&mut chain).poll_flush(&mut cx));
+
+        assert_eq!(PollReady::Ready, Pin::new(&mut chain).poll_close(&mut cx));
+        assert_eq!(PollReady::Closed, Pin::new(&mut chain).poll_ready(&mut cx));
+    }
 }
@@ -0,0 +1,373 @@
+use std::{
+    fmt,
+    pin::Pin,
+    time::{Duration, Instant},
+};
+
+use pin_project::pin_project;
+
+use crate::{
+    sink::{BufferedSink, PollReady, PollSend, Sink},
+    time::timer::Timer,
+    Context,
+};
+
+/// The state of a [CircuitBreakerSink](./struct.CircuitBreakerSink.html)
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CircuitState {
+    /// Messages are sent to the wrapped sink
+    Closed,
+    /// Messages are rejected without being sent, until the cool-down expires
+    Open,
+    /// The next message is sent as a trial.  If it is accepted, the circuit closes, and otherwise it opens again.
+    HalfOpen,
+}
+
+/// Configures [Sink::circuit_breaker](./trait.Sink.html#method.circuit_breaker).
+///
+/// ```rust
+/// use std::time::Duration;
+/// use postage::sink::CircuitBreaker;
+///
+/// let config = CircuitBreaker::new(5, Duration::from_secs(30))
+///     .timeout(Duration::from_millis(500));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircuitBreaker {
+    failure_threshold: usize,
+    cool_down: Duration,
+    timeout: Option<Duration>,
+}
+
+impl CircuitBreaker {
+    /// Opens the circuit after `failure_threshold` consecutive failures,
+    /// and half-opens it after the `cool_down`.
+    pub fn new(failure_threshold: usize, cool_down: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cool_down,
+            timeout: None,
+        }
+    }
+
+    /// Counts a send as failed, and rejects the message, if the sink does not accept it within the timeout.
+    /// By default, only rejections are failures, and the sink can wait indefinitely.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+/// The sink returned by [Sink::circuit_breaker](./trait.Sink.html#method.circuit_breaker)
+#[pin_project]
+pub struct CircuitBreakerSink<S> {
+    #[pin]
+    sink: S,
+    breaker: Breaker,
+}
+
+struct Breaker {
+    config: CircuitBreaker,
+    state: CircuitState,
+    failures: usize,
+    opened_at: Option<Instant>,
+    attempt_started: Option<Instant>,
+    timer: Option<Timer>,
+    events: Option<Box<dyn FnMut(CircuitState) + Send>>,
+}
+
+impl Breaker {
+    fn transition(&mut self, to: CircuitState) {
+        self.state = to;
+
+        if let Some(events) = &mut self.events {
+            events(to);
+        }
+    }
+
+    /// Returns true if a message can be sent.  Half-opens the circuit if the cool-down has expired.
+    fn allow(&mut self) -> bool {
+        if self.state != CircuitState::Open {
+            return true;
+        }
+
+        let cool_down = self.config.cool_down;
+        let cooled = self
+            .opened_at
+            .is_some_and(|opened_at| opened_at.elapsed() >= cool_down);
+
+        if !cooled {
+            return false;
+        }
+
+        self.transition(CircuitState::HalfOpen);
+        true
+    }
+
+    fn succeeded(&mut self) {
+        self.failures = 0;
+        self.attempt_started = None;
+
+        if self.state == CircuitState::HalfOpen {
+            self.transition(CircuitState::Closed);
+        }
+    }
+
+    /// Returns true if the send has been pending for longer than the timeout
+    fn timed_out(&mut self, cx: &mut Context<'_>) -> bool {
+        let timeout = match self.config.timeout {
+            Some(timeout) => timeout,
+            None => return false,
+        };
+
+        let deadline = *self.attempt_started.get_or_insert_with(Instant::now) + timeout;
+        let timer = match &mut self.timer {
+            Some(timer) => timer,
+            None if cx.waker().is_some() => self.timer.insert(Timer::new(deadline)),
+            None => return Instant::now() >= deadline,
+        };
+
+        if timer.deadline() != deadline {
+            timer.reset(deadline);
+        }
+
+        timer.poll_elapsed(cx).is_ready()
+    }
+
+    fn failed(&mut self) {
+        self.attempt_started = None;
+        self.failures += 1;
+
+        if self.state == CircuitState::HalfOpen || self.failures >= self.config.failure_threshold {
+            self.opened_at = Some(Instant::now());
+            self.failures = 0;
+            self.transition(CircuitState::Open);
+        }
+    }
+}
+
+impl<S> CircuitBreakerSink<S> {
+    pub(crate) fn new(sink: S, config: CircuitBreaker) -> Self {
+        Self {
+            sink,
+            breaker: Breaker {
+                config,
+                state: CircuitState::Closed,
+                failures: 0,
+                opened_at: None,
+                attempt_started: None,
+                timer: None,
+                events: None,
+            },
+        }
+    }
+
+    /// Sends the new state to the sink each time the circuit changes state.
+    ///
+    /// Events are sent without blocking, and are discarded if the sink is full or closed.
+    pub fn events<E>(mut self, sink: E) -> Self
+    where
+        E: Sink<Item = CircuitState> + Send + 'static,
+    {
+        let mut sink = Box::pin(sink);
+        self.breaker.events = Some(Box::new(move |state| {
+            sink.as_mut().poll_send(&mut Context::empty(), state);
+        }));
+        self
+    }
+
+    /// The current state of the circuit
+    pub fn state(&self) -> CircuitState {
+        self.breaker.state
+    }
+
+    /// Returns a reference to the wrapped sink
+    pub fn get_ref(&self) -> &S {
+        &self.sink
+    }
+
+    /// Returns a mutable reference to the wrapped sink
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    /// Returns the wrapped sink
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+}
+
+impl<S: Sink> Sink for CircuitBreakerSink<S> {
+    type Item = S::Item;
+
+    fn poll_send(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        value: Self::Item,
+    ) -> PollSend<Self::Item> {
+        let this = self.project();
+        let breaker = this.breaker;
+
+        if !breaker.allow() {
+            return PollSend::Rejected(value);
+        }
+
+        match this.sink.poll_send(cx, value) {
+            PollSend::Ready => {
+                breaker.succeeded();
+                PollSend::Ready
+            }
+            PollSend::Pending(value) => {
+                if !breaker.timed_out(cx) {
+                    return PollSend::Pending(value);
+                }
+
+                breaker.failed();
+                PollSend::Rejected(value)
+            }
+            PollSend::Rejected(value) => {
+                breaker.failed();
+                PollSend::Rejected(value)
+            }
+        }
+    }
+}
+
+/// The circuit is not consulted.  While it is open, a send is rejected without reaching the wrapped sink.
+impl<S: BufferedSink> BufferedSink for CircuitBreakerSink<S> {
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        self.project().sink.poll_ready(cx)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        self.project().sink.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        self.project().sink.poll_close(cx)
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for CircuitBreakerSink<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CircuitBreakerSink")
+            .field("sink", &self.sink)
+            .field("config", &self.breaker.config)
+            .field("state", &self.breaker.state)
+            .field("failures", &self.breaker.failures)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{pin::Pin, thread, time::Duration};
+
+    use super::{CircuitBreaker, CircuitState};
+    use crate::{
+        mpsc,
+        sink::{PollSend, Sink},
+        stream::Stream,
+        test::sink::{pending, test_sink},
+        Context,
+    };
+
+    #[test]
+    fn opens_after_threshold() {
+        let (events_tx, mut events) = mpsc::channel(8);
+        let mut sink = test_sink(vec![
+            PollSend::Rejected(1usize),
+            PollSend::Rejected(2),
+            PollSend::Ready,
+        ])
+        .circuit_breaker(CircuitBreaker::new(2, Duration::from_secs(60)))
+        .events(events_tx);
+
+        assert!(sink.try_send(1).is_err());
+        assert_eq!(CircuitState::Closed, sink.state());
+        assert!(sink.try_send(2).is_err());
+        assert_eq!(CircuitState::Open, sink.state());
+        assert_eq!(Ok(CircuitState::Open), events.try_recv());
+
+        // fails fast, without sending to the wrapped sink
+        assert!(sink.try_send(3).is_err());
+        assert!(sink.get_ref().values().is_empty());
+    }
+
+    #[test]
+    fn success_resets_failures() {
+        let mut sink = test_sink(vec![
+            PollSend::Rejected(1usize),
+            PollSend::Ready,
+            PollSend::Rejected(3),
+        ])
+        .circuit_breaker(CircuitBreaker::new(2, Duration::from_secs(60)));
+
+        assert!(sink.try_send(1).is_err());
+        assert!(sink.try_send(2).is_ok());
+        assert!(sink.try_send(3).is_err());
+        assert_eq!(CircuitState::Closed, sink.state());
+    }
+
+    #[test]
+    fn half_open() {
+        let (events_tx, mut events) = mpsc::channel(8);
+        let mut sink = test_sink(vec![
+            PollSend::Rejected(1usize),
+            PollSend::Rejected(2),
+            PollSend::Ready,
+        ])
+        .circuit_breaker(CircuitBreaker::new(1, Duration::from_millis(5)))
+        .events(events_tx);
+
+        assert!(sink.try_send(1).is_err());
+        thread::sleep(Duration::from_millis(10));
+
+        // the trial fails, and the circuit opens again
+        assert!(sink.try_send(2).is_err());
+        assert_eq!(CircuitState::Open, sink.state());
+        thread::sleep(Duration::from_millis(10));
+
+        assert!(sink.try_send(3).is_ok());
+        assert_eq!(CircuitState::Closed, sink.state());
+
+        let mut states = Vec::new();
+        while let Ok(state) = events.try_recv() {
+            states.push(state);
+        }
+
+        assert_eq!(
+            vec![
+                CircuitState::Open,
+                CircuitState::HalfOpen,
+                CircuitState::Open,
+                CircuitState::HalfOpen,
+                CircuitState::Closed
+            ],
+            states
+        );
+    }
+
+    #[test]
+    fn pending_without_timeout() {
+        let mut sink =
+            pending::<usize>().circuit_breaker(CircuitBreaker::new(1, Duration::from_secs(60)));
+
+        let mut cx = Context::empty();
+        assert_eq!(
+            PollSend::Pending(1),
+            Pin::new(&mut sink).poll_send(&mut cx, 1)
+        );
+        assert_eq!(CircuitState::Closed, sink.state());
+    }
+
+    #[tokio::test]
+    async fn timeout() {
+        let mut sink = pending::<usize>().circuit_breaker(
+            CircuitBreaker::new(1, Duration::from_secs(60)).timeout(Duration::from_millis(10)),
+        );
+
+        assert!(sink.send(1).await.is_err());
+        assert_eq!(CircuitState::Open, sink.state());
+    }
+}
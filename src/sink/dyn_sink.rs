@@ -0,0 +1,81 @@
+use std::pin::Pin;
+
+use crate::sink::{PollSend, Sink};
+use crate::Context;
+
+/// An object-safe version of [Sink](./trait.Sink.html), which allows sinks to be stored as trait objects.
+///
+/// `DynSink` is implemented for all `Unpin` sinks, and boxed trait objects implement `Sink`.
+/// Sinks which are not `Unpin` can be pinned with `Box::pin` first.
+///
+/// ```rust
+/// use postage::{prelude::*, sink::DynSink};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let (tx, mut rx) = mpsc::channel(4);
+///     let (tx2, mut rx2) = broadcast::channel(4);
+///
+///     let mut routes: Vec<Box<dyn DynSink<usize> + Send>> = vec![Box::new(tx), Box::new(tx2)];
+///     for route in routes.iter_mut() {
+///         route.send(1).await.ok();
+///     }
+///
+///     assert_eq!(Some(1), rx.recv().await);
+///     assert_eq!(Some(1), rx2.recv().await);
+/// }
+/// ```
+pub trait DynSink<T> {
+    /// Attempts to accept the message.  See [Sink::poll_send](./trait.Sink.html#tymethod.poll_send).
+    fn poll_send_dyn(&mut self, cx: &mut Context<'_>, value: T) -> PollSend<T>;
+}
+
+impl<S> DynSink<S::Item> for S
+where
+    S: Sink + Unpin,
+{
+    fn poll_send_dyn(&mut self, cx: &mut Context<'_>, value: S::Item) -> PollSend<S::Item> {
+        Pin::new(self).poll_send(cx, value)
+    }
+}
+
+macro_rules! impl_boxed_sink {
+    ($($bounds:tt)*) => {
+        impl<'a, T> Sink for Box<dyn DynSink<T> $($bounds)* + 'a> {
+            type Item = T;
+
+            fn poll_send(self: Pin<&mut Self>, cx: &mut Context<'_>, value: T) -> PollSend<T> {
+                // deref to the trait object, as the box itself also implements DynSink
+                (**self.get_mut()).poll_send_dyn(cx, value)
+            }
+        }
+    };
+}
+
+impl_boxed_sink!();
+impl_boxed_sink!(+ Send);
+impl_boxed_sink!(+ Send + Sync);
+
+#[cfg(test)]
+mod tests {
+    use super::DynSink;
+    use crate::sink::{Sink, TrySendError};
+    use crate::test::sink::*;
+
+    #[test]
+    fn boxed_sinks() {
+        let mut sinks: Vec<Box<dyn DynSink<usize>>> = vec![Box::new(ready()), Box::new(rejected())];
+
+        assert_eq!(Ok(()), sinks[0].try_send(1));
+        assert_eq!(Err(TrySendError::Rejected(2)), sinks[1].try_send(2));
+    }
+
+    #[test]
+    fn boxed_send_sync() {
+        let (tx, mut rx) = crate::mpsc::channel(1);
+        let mut sink: Box<dyn DynSink<usize> + Send + Sync> = Box::new(tx);
+
+        assert_eq!(Ok(()), sink.try_send(1));
+        assert_eq!(Ok(1), crate::stream::Stream::try_recv(&mut rx));
+    }
+}
@@ -1,3 +1,21 @@
+/// The reason a sink did not accept an item.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SendErrorKind {
+    /// The sink is full, and could accept the item at a later time
+    Full,
+    /// The sink is closed, and will never accept the item
+    Closed,
+}
+
+impl std::fmt::Display for SendErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SendErrorKind::Full => f.write_str("the sink is full"),
+            SendErrorKind::Closed => f.write_str("the sink is closed"),
+        }
+    }
+}
+
 /// An error type returned by `Sink::try_send`, when the sink is full, or is closed.
 #[derive(Debug, PartialEq, Eq)]
 pub enum TrySendError<T> {
@@ -7,32 +25,121 @@ pub enum TrySendError<T> {
     Rejected(T),
 }
 
+impl<T> TrySendError<T> {
+    /// The reason the item was not accepted
+    pub fn kind(&self) -> SendErrorKind {
+        match self {
+            TrySendError::Pending(_) => SendErrorKind::Full,
+            TrySendError::Rejected(_) => SendErrorKind::Closed,
+        }
+    }
+
+    /// Returns true if the sink was full
+    pub fn is_full(&self) -> bool {
+        self.kind() == SendErrorKind::Full
+    }
+
+    /// Returns true if the sink was closed
+    pub fn is_closed(&self) -> bool {
+        self.kind() == SendErrorKind::Closed
+    }
+
+    /// Returns a reference to the item which was not accepted
+    pub fn value(&self) -> &T {
+        match self {
+            TrySendError::Pending(value) | TrySendError::Rejected(value) => value,
+        }
+    }
+
+    /// Consumes the error, returning the item which was not accepted
+    pub fn into_inner(self) -> T {
+        match self {
+            TrySendError::Pending(value) | TrySendError::Rejected(value) => value,
+        }
+    }
+}
+
 impl<T> std::fmt::Display for TrySendError<T>
 where
     T: std::fmt::Debug,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_fmt(format_args!("{:?}", &self))?;
-
-        Ok(())
+        write!(f, "failed to send {:?}: {}", self.value(), self.kind())
     }
 }
 
 impl<T> std::error::Error for TrySendError<T> where T: std::fmt::Debug {}
 
+impl<T> From<SendError<T>> for TrySendError<T> {
+    fn from(error: SendError<T>) -> Self {
+        TrySendError::Rejected(error.0)
+    }
+}
+
 /// An error type returned by `Sink::send`, if the sink is closed while a send is in progress.
 #[derive(Debug, PartialEq, Eq)]
 pub struct SendError<T>(pub T);
 
+impl<T> SendError<T> {
+    /// The reason the item was not accepted.  `send` waits while the sink is full, so this is always `Closed`.
+    pub fn kind(&self) -> SendErrorKind {
+        SendErrorKind::Closed
+    }
+
+    /// Returns a reference to the item which was not accepted
+    pub fn value(&self) -> &T {
+        &self.0
+    }
+
+    /// Consumes the error, returning the item which was not accepted
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
 impl<T> std::fmt::Display for SendError<T>
 where
     T: std::fmt::Debug,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_fmt(format_args!("{:?}", &self))?;
-
-        Ok(())
+        write!(f, "failed to send {:?}: {}", self.0, self.kind())
     }
 }
 
 impl<T> std::error::Error for SendError<T> where T: std::fmt::Debug {}
+
+#[cfg(test)]
+mod tests {
+    use super::{SendError, SendErrorKind, TrySendError};
+
+    #[test]
+    fn try_send_error_kind() {
+        let full = TrySendError::Pending(1usize);
+        assert_eq!(SendErrorKind::Full, full.kind());
+        assert!(full.is_full());
+        assert_eq!("failed to send 1: the sink is full", full.to_string());
+
+        let closed = TrySendError::Rejected(2usize);
+        assert!(closed.is_closed());
+        assert_eq!(&2, closed.value());
+        assert_eq!(2, closed.into_inner());
+    }
+
+    #[test]
+    fn send_error_kind() {
+        let error = SendError(1usize);
+        assert_eq!(SendErrorKind::Closed, error.kind());
+        assert_eq!("failed to send 1: the sink is closed", error.to_string());
+        assert_eq!(TrySendError::Rejected(1), TrySendError::from(error));
+    }
+
+    #[test]
+    fn into_boxed_error() {
+        fn send() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            Err(SendError(1usize))?;
+            Ok(())
+        }
+
+        assert!(send().is_err());
+    }
+}
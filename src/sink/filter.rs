@@ -2,7 +2,7 @@ use std::pin::Pin;
 
 use crate::Context;
 
-use crate::sink::{PollSend, Sink};
+use crate::sink::{BufferedSink, PollReady, PollSend, Sink};
 use pin_project::pin_project;
 
 #[pin_project]
@@ -22,6 +22,23 @@ where
     }
 }
 
+impl<Filter, Into> FilterSink<Filter, Into> {
+    /// Returns a reference to the wrapped sink
+    pub fn get_ref(&self) -> &Into {
+        &self.into
+    }
+
+    /// Returns a mutable reference to the wrapped sink
+    pub fn get_mut(&mut self) -> &mut Into {
+        &mut self.into
+    }
+
+    /// Returns the wrapped sink
+    pub fn into_inner(self) -> Into {
+        self.into
+    }
+}
+
 impl<Filter, Into> Sink for FilterSink<Filter, Into>
 where
     Into: Sink,
@@ -43,15 +60,35 @@ where
     }
 }
 
+impl<Filter, Into> BufferedSink for FilterSink<Filter, Into>
+where
+    Into: BufferedSink,
+    Filter: FnMut(&Into::Item) -> bool,
+{
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        self.project().into.poll_ready(cx)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        self.project().into.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        self.project().into.poll_close(cx)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::pin::Pin;
 
     use crate::test::sink::*;
     use crate::{
-        sink::{PollSend, Sink},
+        sink::{BufferedSink, PollReady, PollSend, Sink},
+        stream::Stream,
         Context,
     };
+    use futures_test::task::new_count_waker;
 
     use super::FilterSink;
 
@@ -79,8 +116,6 @@ mod tests {
             Pin::new(&mut filter).poll_send(&mut cx, 4usize)
         );
 
-        drop(filter);
-
         assert_eq!(&[2, 4], test_sink.values());
     }
 
@@ -119,4 +154,23 @@ mod tests {
 
         assert_eq!(PollSend::Ready, Pin::new(&mut find).poll_send(&mut cx, 1));
     }
+
+    #[test]
+    fn flush_through_filter() {
+        let (waker, count) = new_count_waker();
+        let mut cx = std::task::Context::from_waker(&waker).into();
+
+        let (tx, mut rx) = crate::mpsc::channel(4);
+        let mut tx = tx.filter(|i: &usize| *i > 1);
+        assert_eq!(Ok(()), tx.try_send(1));
+        assert_eq!(Ok(()), tx.try_send(2));
+
+        assert_eq!(PollReady::Pending, Pin::new(&mut tx).poll_flush(&mut cx));
+        assert_eq!(Ok(2), rx.try_recv());
+        assert!(count.get() > 0);
+        assert_eq!(PollReady::Ready, Pin::new(&mut tx).poll_flush(&mut cx));
+
+        drop(rx);
+        assert_eq!(PollReady::Closed, Pin::new(&mut tx).poll_close(&mut cx));
+    }
 }
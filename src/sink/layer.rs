@@ -0,0 +1,204 @@
+use std::fmt;
+
+/// Wraps a sink with middleware, producing a new sink.  Layers allow cross-cutting concerns such as logging,
+/// metrics, rate limiting, or transformation to be written once, and applied to any sink with `Sink::wrap`.
+///
+/// ```rust
+/// use postage::{mpsc, prelude::*, sink::layer_fn};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let (tx, mut rx) = mpsc::channel(4);
+///     let (tx2, mut rx2) = mpsc::channel(4);
+///
+///     let even = layer_fn(|sink: mpsc::Sender<usize>| sink.filter(|i| i % 2 == 0));
+///     let mut tx = tx.wrap(&even);
+///     let mut tx2 = tx2.wrap(&even);
+///
+///     tx.send(1).await.ok();
+///     tx.send(2).await.ok();
+///     tx2.send(4).await.ok();
+///
+///     assert_eq!(Some(2), rx.recv().await);
+///     assert_eq!(Some(4), rx2.recv().await);
+/// }
+/// ```
+pub trait Layer<S> {
+    /// The wrapped sink
+    type Sink;
+
+    /// Wraps the sink
+    fn layer(&self, inner: S) -> Self::Sink;
+}
+
+impl<L, S> Layer<S> for &L
+where
+    L: Layer<S> + ?Sized,
+{
+    type Sink = L::Sink;
+
+    fn layer(&self, inner: S) -> Self::Sink {
+        (**self).layer(inner)
+    }
+}
+
+/// Returns a layer which wraps sinks with the provided function
+pub fn layer_fn<F>(f: F) -> LayerFn<F> {
+    LayerFn { f }
+}
+
+/// A layer returned by `layer_fn`.
+#[derive(Clone, Copy)]
+pub struct LayerFn<F> {
+    f: F,
+}
+
+impl<F, S, Out> Layer<S> for LayerFn<F>
+where
+    F: Fn(S) -> Out,
+{
+    type Sink = Out;
+
+    fn layer(&self, inner: S) -> Self::Sink {
+        (self.f)(inner)
+    }
+}
+
+impl<F> fmt::Debug for LayerFn<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LayerFn").finish()
+    }
+}
+
+/// A layer which returns the sink unchanged.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Identity;
+
+impl<S> Layer<S> for Identity {
+    type Sink = S;
+
+    fn layer(&self, inner: S) -> Self::Sink {
+        inner
+    }
+}
+
+/// Two layers, applied in order.  The inner layer wraps the sink first, and the outer layer wraps the result.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Stack<Inner, Outer> {
+    inner: Inner,
+    outer: Outer,
+}
+
+impl<Inner, Outer> Stack<Inner, Outer> {
+    /// Stacks the layers.  Messages pass through the outer layer before the inner layer.
+    pub fn new(inner: Inner, outer: Outer) -> Self {
+        Self { inner, outer }
+    }
+}
+
+impl<S, Inner, Outer> Layer<S> for Stack<Inner, Outer>
+where
+    Inner: Layer<S>,
+    Outer: Layer<Inner::Sink>,
+{
+    type Sink = Outer::Sink;
+
+    fn layer(&self, inner: S) -> Self::Sink {
+        self.outer.layer(self.inner.layer(inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{pin::Pin, sync::Arc};
+
+    use parking_lot::Mutex;
+
+    use super::{layer_fn, Identity, Layer, Stack};
+    use crate::{
+        sink::{PollSend, Sink},
+        test::{noop_context, sink::test_sink},
+        Context,
+    };
+
+    /// Records its name when a message passes through it
+    struct Tag {
+        name: &'static str,
+        order: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    struct TagSink<S> {
+        name: &'static str,
+        order: Arc<Mutex<Vec<&'static str>>>,
+        inner: S,
+    }
+
+    impl<S> Layer<S> for Tag {
+        type Sink = TagSink<S>;
+
+        fn layer(&self, inner: S) -> Self::Sink {
+            TagSink {
+                name: self.name,
+                order: self.order.clone(),
+                inner,
+            }
+        }
+    }
+
+    impl<S: Sink + Unpin> Sink for TagSink<S> {
+        type Item = S::Item;
+
+        fn poll_send(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            value: Self::Item,
+        ) -> PollSend<Self::Item> {
+            let this = self.get_mut();
+            this.order.lock().push(this.name);
+            Pin::new(&mut this.inner).poll_send(cx, value)
+        }
+    }
+
+    #[test]
+    fn identity() {
+        let mut sink = test_sink(vec![PollSend::Ready]).wrap(Identity);
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut sink).poll_send(&mut noop_context(), 1usize)
+        );
+    }
+
+    #[test]
+    fn stack_order() {
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let tag = |name| Tag {
+            name,
+            order: order.clone(),
+        };
+
+        let stack = Stack::new(tag("inner"), tag("outer"));
+        let mut sink = stack.layer(test_sink(vec![PollSend::Ready]));
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut sink).poll_send(&mut noop_context(), 1usize)
+        );
+        assert_eq!(vec!["outer", "inner"], *order.lock());
+        assert_eq!(&[1], sink.inner.inner.values());
+    }
+
+    #[test]
+    fn layer_fn_filter() {
+        let above_one =
+            layer_fn(|sink: crate::test::sink::TestSink<_, usize>| sink.filter(|i: &usize| *i > 1));
+        let mut sink = test_sink(vec![PollSend::Ready]).wrap(&above_one);
+
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut sink).poll_send(&mut noop_context(), 1)
+        );
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut sink).poll_send(&mut noop_context(), 2)
+        );
+    }
+}
@@ -0,0 +1,365 @@
+use std::{
+    fmt,
+    pin::Pin,
+    time::{Duration, Instant},
+};
+
+use pin_project::pin_project;
+
+use crate::{
+    dead_letter::{DeadLetter, DeadLetterReason, DeadLetterSink},
+    sink::{BufferedSink, PollReady, PollSend, Sink},
+    time::timer::Timer,
+    Context,
+};
+
+/// Configures the attempts and exponential backoff used by [Sink::retry](./trait.Sink.html#method.retry).
+///
+/// ```rust
+/// use std::time::Duration;
+/// use postage::sink::RetryPolicy;
+///
+/// let policy = RetryPolicy::new(5)
+///     .backoff(Duration::from_millis(50), Duration::from_secs(5))
+///     .timeout(Duration::from_secs(1));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    timeout: Option<Duration>,
+}
+
+impl RetryPolicy {
+    /// Creates a policy which makes up to `max_attempts` attempts to send each message.
+    /// The backoff starts at 100ms, and doubles after each failure up to 10s.
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2.0,
+            timeout: None,
+        }
+    }
+
+    /// Sets the delay after the first failure, and the limit for later delays
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// Sets the factor the delay grows by after each failure.  Defaults to 2.
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Counts an attempt as failed if the sink does not accept the message within the timeout.
+    /// By default, the sink can wait indefinitely.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// The delay after the given number of failed attempts
+    fn delay(&self, failures: u32) -> Duration {
+        let factor = self.multiplier.powi(failures.saturating_sub(1) as i32);
+        self.initial_backoff
+            .mul_f64(factor.min(u32::MAX as f64))
+            .min(self.max_backoff)
+    }
+}
+
+/// The sink returned by [Sink::retry](./trait.Sink.html#method.retry)
+#[pin_project]
+pub struct RetrySink<S: Sink> {
+    #[pin]
+    sink: S,
+    policy: RetryPolicy,
+    dead_letter: Option<DeadLetterSink<S::Item>>,
+    failures: u32,
+    attempt_started: Option<Instant>,
+    backoff_until: Option<Instant>,
+    timer: Option<Timer>,
+}
+
+impl<S: Sink> RetrySink<S> {
+    pub(crate) fn new(sink: S, policy: RetryPolicy) -> Self {
+        Self {
+            sink,
+            policy,
+            dead_letter: None,
+            failures: 0,
+            attempt_started: None,
+            backoff_until: None,
+            timer: None,
+        }
+    }
+
+    /// Forwards messages which exhaust their attempts to the dead-letter sink, tagged with `DeadLetterReason::Exhausted`.
+    /// The send completes when the dead-letter sink accepts the message.
+    ///
+    /// Messages are forwarded without blocking.  If the dead-letter sink is full or closed, the message is rejected.
+    pub fn dead_letter<D>(mut self, sink: D) -> Self
+    where
+        D: Sink<Item = DeadLetter<S::Item>> + Send + 'static,
+    {
+        self.dead_letter = Some(DeadLetterSink::new(sink));
+        self
+    }
+
+    /// Returns a reference to the wrapped sink
+    pub fn get_ref(&self) -> &S {
+        &self.sink
+    }
+
+    /// Returns a mutable reference to the wrapped sink
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    /// Returns the wrapped sink
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+}
+
+/// Waits for the deadline, creating the timer if the task can be woken
+pub(super) fn poll_deadline(
+    timer: &mut Option<Timer>,
+    cx: &mut Context<'_>,
+    deadline: Instant,
+) -> bool {
+    let timer = match timer {
+        Some(timer) => timer,
+        None if cx.waker().is_some() => timer.insert(Timer::new(deadline)),
+        None => return Instant::now() >= deadline,
+    };
+
+    if timer.deadline() != deadline {
+        timer.reset(deadline);
+    }
+
+    timer.poll_elapsed(cx).is_ready()
+}
+
+impl<S: Sink> Sink for RetrySink<S> {
+    type Item = S::Item;
+
+    fn poll_send(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        value: Self::Item,
+    ) -> PollSend<Self::Item> {
+        let this = self.project();
+
+        if let Some(deadline) = *this.backoff_until {
+            if !poll_deadline(this.timer, cx, deadline) {
+                return PollSend::Pending(value);
+            }
+
+            *this.backoff_until = None;
+        }
+
+        let value = match this.sink.poll_send(cx, value) {
+            PollSend::Ready => {
+                *this.failures = 0;
+                *this.attempt_started = None;
+                return PollSend::Ready;
+            }
+            PollSend::Pending(value) => {
+                let timeout = match this.policy.timeout {
+                    Some(timeout) => timeout,
+                    None => return PollSend::Pending(value),
+                };
+
+                let started = *this.attempt_started.get_or_insert_with(Instant::now);
+                if !poll_deadline(this.timer, cx, started + timeout) {
+                    return PollSend::Pending(value);
+                }
+
+                value
+            }
+            PollSend::Rejected(value) => value,
+        };
+
+        *this.failures += 1;
+        *this.attempt_started = None;
+
+        if *this.failures < this.policy.max_attempts {
+            let deadline = Instant::now() + this.policy.delay(*this.failures);
+            *this.backoff_until = Some(deadline);
+
+            // registers the timer, so the task is woken when the backoff expires
+            poll_deadline(this.timer, cx, deadline);
+            return PollSend::Pending(value);
+        }
+
+        *this.failures = 0;
+        let value = match this.dead_letter {
+            Some(dead_letter) => {
+                let letter = DeadLetter {
+                    value,
+                    reason: DeadLetterReason::Exhausted,
+                };
+
+                match (dead_letter.forward)(letter) {
+                    Ok(()) => return PollSend::Ready,
+                    Err(value) => value,
+                }
+            }
+            None => value,
+        };
+
+        PollSend::Rejected(value)
+    }
+}
+
+impl<S: BufferedSink> BufferedSink for RetrySink<S> {
+    /// Waits for the backoff after a failed attempt, and then for the wrapped sink
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        let this = self.project();
+
+        if let Some(deadline) = *this.backoff_until {
+            if !poll_deadline(this.timer, cx, deadline) {
+                return PollReady::Pending;
+            }
+
+            *this.backoff_until = None;
+        }
+
+        this.sink.poll_ready(cx)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        self.project().sink.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        self.project().sink.poll_close(cx)
+    }
+}
+
+impl<S> fmt::Debug for RetrySink<S>
+where
+    S: Sink + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetrySink")
+            .field("sink", &self.sink)
+            .field("policy", &self.policy)
+            .field("failures", &self.failures)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        pin::Pin,
+        time::{Duration, Instant},
+    };
+
+    use super::RetryPolicy;
+    use crate::{
+        dead_letter::DeadLetterReason,
+        mpsc,
+        sink::{BufferedSink, PollReady, PollSend, SendErrorKind, Sink},
+        stream::Stream,
+        test::sink::{pending, rejected, test_sink},
+        Context,
+    };
+
+    fn fast_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy::new(max_attempts).backoff(Duration::from_millis(1), Duration::from_millis(4))
+    }
+
+    #[test]
+    fn delay() {
+        let policy =
+            RetryPolicy::new(10).backoff(Duration::from_millis(10), Duration::from_millis(50));
+        assert_eq!(Duration::from_millis(10), policy.delay(1));
+        assert_eq!(Duration::from_millis(20), policy.delay(2));
+        assert_eq!(Duration::from_millis(40), policy.delay(3));
+        assert_eq!(Duration::from_millis(50), policy.delay(4));
+        assert_eq!(Duration::from_millis(50), policy.delay(40));
+    }
+
+    #[tokio::test]
+    async fn retries_rejected() {
+        let mut sink = test_sink(vec![
+            PollSend::Rejected(1usize),
+            PollSend::Rejected(1),
+            PollSend::Ready,
+        ])
+        .retry(fast_policy(3));
+
+        assert_eq!(Ok(()), sink.send(1).await);
+        assert_eq!(&[1], sink.get_ref().values());
+    }
+
+    #[tokio::test]
+    async fn exhausted() {
+        let mut sink = rejected::<usize>().retry(fast_policy(3));
+        assert!(sink.send(1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn dead_letter() {
+        let (dead_tx, mut dead_rx) = mpsc::channel(4);
+        let mut sink = rejected::<usize>()
+            .retry(fast_policy(2))
+            .dead_letter(dead_tx);
+
+        assert_eq!(Ok(()), sink.send(1).await);
+
+        let letter = dead_rx.try_recv().unwrap();
+        assert_eq!(1, letter.value);
+        assert_eq!(DeadLetterReason::Exhausted, letter.reason);
+    }
+
+    #[tokio::test]
+    async fn timeout() {
+        let mut sink = pending::<usize>().retry(fast_policy(2).timeout(Duration::from_millis(10)));
+
+        let start = Instant::now();
+        assert!(sink.send(1).await.is_err());
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn backoff_is_pending() {
+        let mut sink = test_sink(vec![PollSend::Rejected(1usize), PollSend::Ready])
+            .retry(RetryPolicy::new(2).backoff(Duration::from_secs(60), Duration::from_secs(60)));
+
+        let mut cx = Context::empty();
+        assert_eq!(
+            PollSend::Pending(1),
+            Pin::new(&mut sink).poll_send(&mut cx, 1)
+        );
+        assert_eq!(
+            PollSend::Pending(1),
+            Pin::new(&mut sink).poll_send(&mut cx, 1)
+        );
+        assert!(sink.get_ref().values().is_empty());
+    }
+
+    #[tokio::test]
+    async fn flush() {
+        let (tx, mut rx) = mpsc::channel(4);
+        let mut sink = tx.retry(fast_policy(2));
+        assert_eq!(Ok(()), sink.send(1usize).await);
+
+        let mut cx = Context::empty();
+        assert_eq!(PollReady::Pending, Pin::new(&mut sink).poll_flush(&mut cx));
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(Ok(()), sink.flush().await);
+
+        drop(rx);
+        assert_eq!(Err(SendErrorKind::Closed), sink.close().await);
+    }
+}
@@ -0,0 +1,174 @@
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{self, Poll},
+};
+
+use crate::sink::{PollSend, SendError, Sink};
+
+/// Sends the message to the first sink which accepts it.
+///
+/// Resolves with the index of the sink which accepted the message.  If several sinks are ready, the earliest in the list wins.
+/// Sinks which are closed are skipped, and if every sink is closed, the message is returned in the error.
+///
+/// ```rust
+/// use postage::{mpsc, prelude::*, sink};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let (mut busy, _busy_rx) = mpsc::channel(1);
+///     let (mut idle, mut idle_rx) = mpsc::channel(1);
+///     busy.send("queued").await.ok();
+///
+///     let winner = sink::send_to_first_ready("job", [&mut busy, &mut idle]).await;
+///     assert_eq!(Ok(1), winner);
+///     assert_eq!(Some("job"), idle_rx.recv().await);
+/// }
+/// ```
+pub fn send_to_first_ready<S, I>(value: S::Item, sinks: I) -> SendToFirstReady<S>
+where
+    S: Sink + Unpin,
+    I: IntoIterator<Item = S>,
+{
+    SendToFirstReady {
+        sinks: sinks.into_iter().map(Some).collect(),
+        value: Some(value),
+    }
+}
+
+/// A future which sends a message to the first ready sink, created by [send_to_first_ready](./fn.send_to_first_ready.html)
+#[must_use = "futures do nothing unless polled"]
+pub struct SendToFirstReady<S: Sink> {
+    // closed sinks are set to None
+    sinks: Vec<Option<S>>,
+    value: Option<S::Item>,
+}
+
+impl<S: Sink> SendToFirstReady<S> {
+    /// Returns the message, if it has not been accepted by a sink
+    pub fn into_inner(self) -> Option<S::Item> {
+        self.value
+    }
+}
+
+// the sinks are Unpin, and the value is never pinned
+impl<S: Sink + Unpin> Unpin for SendToFirstReady<S> {}
+
+impl<S> Future for SendToFirstReady<S>
+where
+    S: Sink + Unpin,
+{
+    type Output = Result<usize, SendError<S::Item>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut cx = cx.into();
+        let mut value = this.value.take().expect("polled after completion");
+
+        for (index, slot) in this.sinks.iter_mut().enumerate() {
+            let sink = match slot {
+                Some(sink) => sink,
+                None => continue,
+            };
+
+            value = match Pin::new(sink).poll_send(&mut cx, value) {
+                PollSend::Ready => return Poll::Ready(Ok(index)),
+                PollSend::Pending(value) => value,
+                PollSend::Rejected(value) => {
+                    *slot = None;
+                    value
+                }
+            };
+        }
+
+        if this.sinks.iter().all(Option::is_none) {
+            return Poll::Ready(Err(SendError(value)));
+        }
+
+        this.value = Some(value);
+        Poll::Pending
+    }
+}
+
+impl<S: Sink> fmt::Debug for SendToFirstReady<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SendToFirstReady")
+            .field("sinks", &self.sinks.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::Future, pin::Pin, task::Poll};
+
+    use futures_test::task::{new_count_waker, noop_context};
+
+    use super::send_to_first_ready;
+    use crate::{
+        mpsc,
+        sink::{SendError, Sink},
+        stream::{Stream, TryRecvError},
+    };
+
+    #[test]
+    fn first_ready_wins() {
+        let mut cx = noop_context();
+        let (mut a, mut a_rx) = mpsc::channel(1);
+        let (mut b, mut b_rx) = mpsc::channel(1);
+
+        let mut send = send_to_first_ready(1usize, [&mut a, &mut b]);
+        assert_eq!(Poll::Ready(Ok(0)), Pin::new(&mut send).poll(&mut cx));
+
+        let mut send = send_to_first_ready(2, [&mut a, &mut b]);
+        assert_eq!(Poll::Ready(Ok(1)), Pin::new(&mut send).poll(&mut cx));
+
+        assert_eq!(Ok(1), a_rx.try_recv());
+        assert_eq!(Ok(2), b_rx.try_recv());
+    }
+
+    #[test]
+    fn waits_for_capacity() {
+        let (waker, count) = new_count_waker();
+        let mut cx = std::task::Context::from_waker(&waker);
+        let (mut a, _a_rx) = mpsc::channel(1);
+        let (mut b, mut b_rx) = mpsc::channel(1);
+        a.try_send(0usize).unwrap();
+        b.try_send(0).unwrap();
+
+        let mut send = send_to_first_ready(1, [&mut a, &mut b]);
+        assert_eq!(Poll::Pending, Pin::new(&mut send).poll(&mut cx));
+
+        assert_eq!(Ok(0), b_rx.try_recv());
+        assert_eq!(1, count.get());
+        assert_eq!(Poll::Ready(Ok(1)), Pin::new(&mut send).poll(&mut cx));
+        assert_eq!(Ok(1), b_rx.try_recv());
+    }
+
+    #[test]
+    fn skips_closed() {
+        let mut cx = noop_context();
+        let (mut a, a_rx) = mpsc::channel(1);
+        let (mut b, mut b_rx) = mpsc::channel(1);
+        drop(a_rx);
+
+        let mut send = send_to_first_ready(1usize, [&mut a, &mut b]);
+        assert_eq!(Poll::Ready(Ok(1)), Pin::new(&mut send).poll(&mut cx));
+        assert_eq!(Ok(1), b_rx.try_recv());
+        assert_eq!(Err(TryRecvError::Pending), b_rx.try_recv());
+    }
+
+    #[tokio::test]
+    async fn all_closed() {
+        let (a, a_rx) = mpsc::channel(1);
+        let (b, b_rx) = mpsc::channel(1);
+        drop(a_rx);
+        drop(b_rx);
+
+        assert_eq!(
+            Err(SendError(1usize)),
+            send_to_first_ready(1, vec![a, b]).await
+        );
+    }
+}
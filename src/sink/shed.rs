@@ -0,0 +1,284 @@
+use std::{collections::BTreeMap, fmt, pin::Pin};
+
+use pin_project::pin_project;
+
+use crate::{
+    sink::{BufferedSink, PollReady, PollSend, Sink},
+    Context,
+};
+
+/// Configures which priority classes [Sink::shed](./trait.Sink.html#method.shed) drops when the sink is congested.
+///
+/// Each class has a shed ratio, between 0.0 and 1.0.  When the sink can't accept a message, that fraction of the class's messages are dropped,
+/// and the rest wait for capacity.  Classes without a ratio are never shed.
+///
+/// ```rust
+/// use postage::sink::ShedPolicy;
+///
+/// // drop every class 2 message, and half of the class 1 messages, when the sink is full
+/// let policy = ShedPolicy::new().ratio(2, 1.0).ratio(1, 0.5);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ShedPolicy {
+    ratios: BTreeMap<usize, f64>,
+}
+
+impl ShedPolicy {
+    /// Creates a policy which sheds no messages
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the fraction of messages in the class which are dropped when the sink is congested
+    pub fn ratio(mut self, class: usize, ratio: f64) -> Self {
+        self.ratios.insert(class, ratio.clamp(0.0, 1.0));
+        self
+    }
+}
+
+#[derive(Debug, Default)]
+struct ClassState {
+    ratio: f64,
+    credit: f64,
+    shed: u64,
+}
+
+impl ClassState {
+    /// Spreads the drops evenly, so a ratio of 0.5 drops every other congested message
+    fn should_shed(&mut self) -> bool {
+        self.credit += self.ratio;
+        if self.credit < 1.0 {
+            return false;
+        }
+
+        self.credit -= 1.0;
+        self.shed += 1;
+        true
+    }
+}
+
+/// The sink returned by [Sink::shed](./trait.Sink.html#method.shed)
+#[pin_project]
+pub struct ShedSink<S, F> {
+    #[pin]
+    sink: S,
+    classify: F,
+    classes: BTreeMap<usize, ClassState>,
+    waiting: bool,
+}
+
+impl<S, F> ShedSink<S, F> {
+    pub(crate) fn new(sink: S, policy: ShedPolicy, classify: F) -> Self {
+        let classes = policy
+            .ratios
+            .into_iter()
+            .map(|(class, ratio)| {
+                let state = ClassState {
+                    ratio,
+                    ..ClassState::default()
+                };
+
+                (class, state)
+            })
+            .collect();
+
+        Self {
+            sink,
+            classify,
+            classes,
+            waiting: false,
+        }
+    }
+
+    /// The number of messages in the class which have been dropped
+    pub fn shed_count(&self, class: usize) -> u64 {
+        self.classes
+            .get(&class)
+            .map(|state| state.shed)
+            .unwrap_or(0)
+    }
+
+    /// The number of messages which have been dropped, in all classes
+    pub fn total_shed(&self) -> u64 {
+        self.classes.values().map(|state| state.shed).sum()
+    }
+
+    /// Returns a reference to the wrapped sink
+    pub fn get_ref(&self) -> &S {
+        &self.sink
+    }
+
+    /// Returns a mutable reference to the wrapped sink
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    /// Returns the wrapped sink
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+}
+
+impl<S, F> Sink for ShedSink<S, F>
+where
+    S: Sink,
+    F: FnMut(&S::Item) -> usize,
+{
+    type Item = S::Item;
+
+    fn poll_send(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        value: Self::Item,
+    ) -> PollSend<Self::Item> {
+        let this = self.project();
+
+        match this.sink.poll_send(cx, value) {
+            PollSend::Ready => {
+                *this.waiting = false;
+                PollSend::Ready
+            }
+            PollSend::Pending(value) => {
+                // a message which was kept is retried without shedding, so the ratio applies once per message
+                if !*this.waiting {
+                    let class = (this.classify)(&value);
+                    let shed = this
+                        .classes
+                        .get_mut(&class)
+                        .is_some_and(ClassState::should_shed);
+
+                    if shed {
+                        return PollSend::Ready;
+                    }
+                }
+
+                // try_send polls without a waker, and abandons the message if it is pending
+                *this.waiting = cx.waker().is_some();
+                PollSend::Pending(value)
+            }
+            PollSend::Rejected(value) => {
+                *this.waiting = false;
+                PollSend::Rejected(value)
+            }
+        }
+    }
+}
+
+impl<S, F> BufferedSink for ShedSink<S, F>
+where
+    S: BufferedSink,
+    F: FnMut(&S::Item) -> usize,
+{
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        self.project().sink.poll_ready(cx)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        self.project().sink.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        self.project().sink.poll_close(cx)
+    }
+}
+
+impl<S: fmt::Debug, F> fmt::Debug for ShedSink<S, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShedSink")
+            .field("sink", &self.sink)
+            .field("classes", &self.classes)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+
+    use futures_test::task::noop_context;
+
+    use super::ShedPolicy;
+    use crate::{
+        mpsc,
+        sink::{PollSend, Sink},
+        stream::Stream,
+        Context,
+    };
+
+    #[derive(Debug, PartialEq)]
+    struct Message {
+        class: usize,
+        id: usize,
+    }
+
+    fn message(class: usize, id: usize) -> Message {
+        Message { class, id }
+    }
+
+    #[test]
+    fn sends_when_not_congested() {
+        let (tx, mut rx) = mpsc::channel(4);
+        let mut tx = tx.shed(ShedPolicy::new().ratio(1, 1.0), |m: &Message| m.class);
+
+        tx.try_send(message(1, 0)).unwrap();
+        assert_eq!(Ok(message(1, 0)), rx.try_recv());
+        assert_eq!(0, tx.total_shed());
+    }
+
+    #[test]
+    fn sheds_low_priority() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let mut tx = tx.shed(ShedPolicy::new().ratio(1, 1.0), |m: &Message| m.class);
+
+        tx.try_send(message(0, 0)).unwrap();
+        tx.try_send(message(1, 1)).unwrap();
+        assert!(tx.try_send(message(0, 2)).is_err());
+
+        assert_eq!(1, tx.shed_count(1));
+        assert_eq!(0, tx.shed_count(0));
+        assert_eq!(Ok(message(0, 0)), rx.try_recv());
+    }
+
+    #[test]
+    fn ratio() {
+        let (tx, _rx) = mpsc::channel(1);
+        let mut tx = tx.shed(ShedPolicy::new().ratio(1, 0.5), |m: &Message| m.class);
+        tx.try_send(message(0, 0)).unwrap();
+
+        let mut kept = 0;
+        for id in 0..10 {
+            if tx.try_send(message(1, id)).is_err() {
+                kept += 1;
+            }
+        }
+
+        assert_eq!(5, kept);
+        assert_eq!(5, tx.shed_count(1));
+    }
+
+    #[test]
+    fn kept_message_is_not_shed_on_retry() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let mut tx = tx.shed(ShedPolicy::new().ratio(1, 0.5), |m: &Message| m.class);
+        tx.try_send(message(0, 0)).unwrap();
+
+        let mut cx = noop_context();
+        let mut cx = Context::from(&mut cx);
+        assert_eq!(
+            PollSend::Pending(message(1, 1)),
+            Pin::new(&mut tx).poll_send(&mut cx, message(1, 1))
+        );
+        assert_eq!(
+            PollSend::Pending(message(1, 1)),
+            Pin::new(&mut tx).poll_send(&mut cx, message(1, 1))
+        );
+
+        assert_eq!(Ok(message(0, 0)), rx.try_recv());
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut cx, message(1, 1))
+        );
+        assert_eq!(Ok(message(1, 1)), rx.try_recv());
+        assert_eq!(0, tx.total_shed());
+    }
+}
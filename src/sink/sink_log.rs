@@ -1,4 +1,4 @@
-use crate::sink::{PollSend, Sink};
+use crate::sink::{BufferedSink, PollReady, PollSend, Sink};
 use log::log_enabled;
 use pin_project::pin_project;
 use std::{fmt::Debug, pin::Pin};
@@ -26,6 +26,23 @@ where
     }
 }
 
+impl<S> SinkLog<S> {
+    /// Returns a reference to the wrapped sink
+    pub fn get_ref(&self) -> &S {
+        &self.sink
+    }
+
+    /// Returns a mutable reference to the wrapped sink
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    /// Returns the wrapped sink
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+}
+
 impl<S> Sink for SinkLog<S>
 where
     S: Sink,
@@ -60,6 +77,24 @@ where
     }
 }
 
+impl<S> BufferedSink for SinkLog<S>
+where
+    S: BufferedSink,
+    S::Item: Debug,
+{
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        self.project().sink.poll_ready(cx)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        self.project().sink.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollReady {
+        self.project().sink.poll_close(cx)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::pin::Pin;
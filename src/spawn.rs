@@ -0,0 +1,158 @@
+//! Spawns the background tasks used by postage utilities, without depending on a specific runtime.
+//!
+//! Any `Fn(BoxFuture)` closure implements [Spawn](./trait.Spawn.html), and spawners for tokio, async-std, and smol
+//! are provided with the `tokio`, `async-std`, and `smol` features.
+//!
+//! ```rust
+//! use postage::spawn::{BoxFuture, Spawn};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let spawner = |task: BoxFuture| {
+//!         tokio::spawn(task);
+//!     };
+//!
+//!     spawner.spawn(Box::pin(async { println!("in the background") }));
+//! }
+//! ```
+
+use std::{future::Future, pin::Pin};
+
+/// A boxed future, which can be run on any executor
+pub type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// Runs a future in the background, on an executor.
+pub trait Spawn {
+    /// Spawns the future.  The future runs to completion, even if the task handle is not kept.
+    fn spawn(&self, future: BoxFuture);
+}
+
+impl<F> Spawn for F
+where
+    F: Fn(BoxFuture),
+{
+    fn spawn(&self, future: BoxFuture) {
+        self(future)
+    }
+}
+
+/// Spawns tasks with `tokio::spawn`.  Must be used within a tokio runtime.
+///
+/// Requires the `tokio` feature.
+#[cfg(feature = "tokio")]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct TokioSpawner;
+
+#[cfg(feature = "tokio")]
+impl Spawn for TokioSpawner {
+    fn spawn(&self, future: BoxFuture) {
+        tokio::spawn(future);
+    }
+}
+
+/// Spawns tasks on the runtime, which can be used outside the runtime's threads.
+///
+/// Requires the `tokio` feature.
+#[cfg(feature = "tokio")]
+impl Spawn for tokio::runtime::Handle {
+    fn spawn(&self, future: BoxFuture) {
+        tokio::runtime::Handle::spawn(self, future);
+    }
+}
+
+/// Spawns tasks with `async_std::task::spawn`.
+///
+/// Requires the `async-std` feature.
+#[cfg(feature = "async-std")]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct AsyncStdSpawner;
+
+#[cfg(feature = "async-std")]
+impl Spawn for AsyncStdSpawner {
+    fn spawn(&self, future: BoxFuture) {
+        async_std::task::spawn(future);
+    }
+}
+
+/// Spawns tasks on the global smol executor, with `smol::spawn`.
+///
+/// Requires the `smol` feature.
+#[cfg(feature = "smol")]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SmolSpawner;
+
+#[cfg(feature = "smol")]
+impl Spawn for SmolSpawner {
+    fn spawn(&self, future: BoxFuture) {
+        smol::spawn(future).detach();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use futures_test::task::noop_context;
+    use parking_lot::Mutex;
+
+    use super::{BoxFuture, Spawn};
+
+    #[test]
+    fn closure() {
+        let spawned = Arc::new(Mutex::new(Vec::new()));
+        let tasks = spawned.clone();
+        let spawner = move |task: BoxFuture| tasks.lock().push(task);
+
+        let ran = Arc::new(AtomicUsize::new(0));
+        let task_ran = ran.clone();
+        spawner.spawn(Box::pin(async move {
+            task_ran.fetch_add(1, Ordering::Relaxed);
+        }));
+
+        let mut task = spawned.lock().pop().unwrap();
+        assert!(task.as_mut().poll(&mut noop_context()).is_ready());
+        assert_eq!(1, ran.load(Ordering::Relaxed));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn tokio() {
+        use crate::{oneshot, sink::Sink, stream::Stream};
+
+        let (mut tx, mut rx) = oneshot::channel();
+        super::TokioSpawner.spawn(Box::pin(async move {
+            tx.send(1usize).await.ok();
+        }));
+
+        assert_eq!(Some(1), rx.recv().await);
+    }
+
+    #[cfg(feature = "async-std")]
+    #[async_std::test]
+    async fn async_std() {
+        use crate::{oneshot, sink::Sink, stream::Stream};
+
+        let (mut tx, mut rx) = oneshot::channel();
+        super::AsyncStdSpawner.spawn(Box::pin(async move {
+            tx.send(1usize).await.ok();
+        }));
+
+        assert_eq!(Some(1), rx.recv().await);
+    }
+
+    #[cfg(feature = "smol")]
+    #[test]
+    fn smol() {
+        use crate::{oneshot, sink::Sink, stream::Stream};
+
+        let (mut tx, mut rx) = oneshot::channel();
+        super::SmolSpawner.spawn(Box::pin(async move {
+            tx.send(1usize).await.ok();
+        }));
+
+        assert_eq!(Some(1), smol::block_on(rx.recv()));
+    }
+}
@@ -0,0 +1,357 @@
+//! Graceful shutdown for channels.
+//!
+//! A [StopSource](./struct.StopSource.html) produces cloneable [StopTokens](./struct.StopToken.html), which can be attached
+//! to a channel with its `Builder`.  When the source is stopped (or dropped), senders reject new messages,
+//! and receivers drain the messages that were already sent, then observe the channel as closed.
+//!
+//! Tokens are accepted by:
+//!   - the `mpsc`, `dispatch`, `broadcast`, `watch`, `credit`, and `spill` builders.
+//!   - `broadcast::Builder::build_arc`, for messages which are not `Clone`.
+//!   - `mpsc::Builder::build_with_control`, which closes both lanes.
+//!   - `mpsc::linked_with` and `mpsc::sharded_with`, which take a builder for each channel or shard.
+//!
+//! The `oneshot` and `barrier` channels carry a single transmission, and close when their sender is dropped.
+//!
+//! ```rust
+//! use postage::{mpsc, prelude::*, stop::StopSource};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let stop = StopSource::new();
+//!     let (mut tx, mut rx) = mpsc::Builder::new(4).stop_token(stop.token()).build();
+//!
+//!     tx.send(1usize).await.ok();
+//!     stop.stop();
+//!
+//!     assert!(tx.send(2usize).await.is_err());
+//!     assert_eq!(Some(1), rx.recv().await);
+//!     assert_eq!(None, rx.recv().await);
+//! }
+//! ```
+
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Weak,
+    },
+};
+
+use parking_lot::Mutex;
+
+use crate::sync::notifier::Notifier;
+
+/// Triggers shutdown of the channels which hold its tokens.  Dropping the source also triggers shutdown.
+pub struct StopSource {
+    shared: Arc<StopShared>,
+}
+
+impl StopSource {
+    /// Creates a new source, which has not been stopped
+    pub fn new() -> Self {
+        Self {
+            shared: Arc::new(StopShared {
+                stopped: AtomicBool::new(false),
+                listeners: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Creates a token, which observes this source
+    pub fn token(&self) -> StopToken {
+        StopToken {
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Stops the channels which hold a token from this source, and wakes their blocked tasks.
+    pub fn stop(&self) {
+        if self.shared.stopped.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let listeners = std::mem::take(&mut *self.shared.listeners.lock());
+        for listener in listeners {
+            listener.notify();
+        }
+    }
+
+    /// Returns true if the source has been stopped
+    pub fn is_stopped(&self) -> bool {
+        self.shared.is_stopped()
+    }
+}
+
+impl Default for StopSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for StopSource {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl fmt::Debug for StopSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StopSource")
+            .field("stopped", &self.is_stopped())
+            .finish()
+    }
+}
+
+/// Observes a [StopSource](./struct.StopSource.html).  Can be cloned, and attached to any number of channels.
+#[derive(Clone)]
+pub struct StopToken {
+    shared: Arc<StopShared>,
+}
+
+impl StopToken {
+    /// Returns true if the source has been stopped, or dropped
+    pub fn is_stopped(&self) -> bool {
+        self.shared.is_stopped()
+    }
+
+    /// Registers the notifiers of a channel, so blocked tasks are woken when the source stops
+    pub(crate) fn register(&self, senders: &Arc<Notifier>, receivers: &Arc<Notifier>) {
+        let mut listeners = self.shared.listeners.lock();
+        if self.shared.is_stopped() {
+            return;
+        }
+
+        listeners.retain(StopListener::is_live);
+        listeners.push(StopListener {
+            senders: Arc::downgrade(senders),
+            receivers: Arc::downgrade(receivers),
+        });
+    }
+}
+
+impl fmt::Debug for StopToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StopToken")
+            .field("stopped", &self.is_stopped())
+            .finish()
+    }
+}
+
+struct StopShared {
+    stopped: AtomicBool,
+    listeners: Mutex<Vec<StopListener>>,
+}
+
+impl StopShared {
+    fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Acquire)
+    }
+}
+
+struct StopListener {
+    senders: Weak<Notifier>,
+    receivers: Weak<Notifier>,
+}
+
+impl StopListener {
+    fn is_live(&self) -> bool {
+        self.senders.strong_count() > 0
+    }
+
+    fn notify(&self) {
+        if let Some(senders) = self.senders.upgrade() {
+            senders.notify();
+        }
+
+        if let Some(receivers) = self.receivers.upgrade() {
+            receivers.notify();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+
+    use futures_test::task::new_count_waker;
+
+    use super::StopSource;
+    use crate::{
+        broadcast, dispatch, mpsc,
+        sink::{PollSend, Sink, TrySendError},
+        stream::{PollRecv, Stream, TryRecvError},
+        test::noop_context,
+        watch, Context,
+    };
+
+    #[test]
+    fn stop_drains_then_closes() {
+        let stop = StopSource::new();
+        let (mut tx, mut rx) = mpsc::Builder::new(4).stop_token(stop.token()).build();
+
+        assert_eq!(Ok(()), tx.try_send(1usize));
+        stop.stop();
+
+        assert!(tx.try_send(2).is_err());
+        assert_eq!(
+            PollRecv::Ready(1),
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+        assert_eq!(
+            PollRecv::Closed,
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+    }
+
+    #[test]
+    fn stop_wakes_receiver() {
+        let stop = StopSource::new();
+        let (_tx, mut rx) = dispatch::Builder::<usize>::new(4)
+            .stop_token(stop.token())
+            .build();
+
+        let (waker, count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker);
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx).poll_recv(&mut cx));
+
+        drop(stop);
+        assert_eq!(1, count.get());
+        assert_eq!(PollRecv::Closed, Pin::new(&mut rx).poll_recv(&mut cx));
+    }
+
+    #[test]
+    fn stop_wakes_sender() {
+        let stop = StopSource::new();
+        let (mut tx, _rx) = mpsc::Builder::new(1).stop_token(stop.token()).build();
+        assert_eq!(Ok(()), tx.try_send(1usize));
+
+        let (waker, count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker);
+        assert_eq!(
+            PollSend::Pending(2),
+            Pin::new(&mut tx).poll_send(&mut cx, 2)
+        );
+
+        stop.stop();
+        assert_eq!(1, count.get());
+        assert_eq!(
+            PollSend::Rejected(2),
+            Pin::new(&mut tx).poll_send(&mut cx, 2)
+        );
+    }
+
+    #[test]
+    fn stop_watch() {
+        let stop = StopSource::new();
+        let (mut tx, mut rx) = watch::Builder::new()
+            .stop_token(stop.token())
+            .build_with(1usize);
+
+        stop.stop();
+        assert!(tx.try_send(2).is_err());
+        assert_eq!(
+            PollRecv::Ready(1),
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+        assert_eq!(
+            PollRecv::Closed,
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+    }
+
+    #[test]
+    fn stop_broadcast_drains() {
+        let stop = StopSource::new();
+        let (mut tx, mut rx) = broadcast::Builder::new(4).stop_token(stop.token()).build();
+        let mut rx2 = tx.subscribe();
+
+        tx.try_send(1usize).unwrap();
+        tx.try_send(2).unwrap();
+        stop.stop();
+
+        assert_eq!(Err(TrySendError::Rejected(3)), tx.try_send(3));
+        for rx in [&mut rx, &mut rx2] {
+            assert_eq!(Ok(1), rx.try_recv());
+            assert_eq!(Ok(2), rx.try_recv());
+            assert_eq!(Err(TryRecvError::Closed), rx.try_recv());
+        }
+    }
+
+    #[test]
+    fn stop_watch_drains() {
+        let stop = StopSource::new();
+        let (mut tx, mut rx) = watch::Builder::new()
+            .stop_token(stop.token())
+            .build_with(1usize);
+
+        assert_eq!(Ok(1), rx.try_recv());
+        tx.try_send(2).unwrap();
+        stop.stop();
+
+        assert_eq!(Err(TrySendError::Rejected(3)), tx.try_send(3));
+        assert_eq!(Ok(2), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Closed), rx.try_recv());
+    }
+
+    #[test]
+    fn stop_linked() {
+        let stop = StopSource::new();
+        let ((mut input, mut stage_rx), (mut stage_tx, mut output)) = mpsc::linked_with(
+            mpsc::Builder::new(2).stop_token(stop.token()),
+            mpsc::Builder::new(2).stop_token(stop.token()),
+        );
+
+        input.try_send(1usize).unwrap();
+        stage_tx.try_send(10usize).unwrap();
+        stop.stop();
+
+        assert!(input.try_send(2).is_err());
+        assert!(stage_tx.try_send(20).is_err());
+        assert_eq!(Ok(1), stage_rx.try_recv());
+        assert_eq!(Ok(10), output.try_recv());
+        assert_eq!(Err(TryRecvError::Closed), stage_rx.try_recv());
+        assert_eq!(Err(TryRecvError::Closed), output.try_recv());
+    }
+
+    #[test]
+    fn stop_sharded() {
+        let stop = StopSource::new();
+        let (mut tx, mut rx) =
+            mpsc::sharded_with(2, || mpsc::Builder::new(2).stop_token(stop.token()));
+
+        tx.try_send(1usize).unwrap();
+        stop.stop();
+
+        assert!(tx.try_send(2).is_err());
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Closed), rx.try_recv());
+    }
+
+    #[test]
+    fn stop_control_lane() {
+        let stop = StopSource::new();
+        let (mut tx, mut rx) = mpsc::Builder::new(2)
+            .stop_token(stop.token())
+            .build_with_control();
+        let mut control = tx.control();
+
+        tx.try_send(1usize).unwrap();
+        control.try_send(2).unwrap();
+        stop.stop();
+
+        assert!(control.try_send(3).is_err());
+        assert_eq!(Ok(2), rx.try_recv());
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Closed), rx.try_recv());
+    }
+
+    #[test]
+    fn token_after_stop() {
+        let stop = StopSource::new();
+        stop.stop();
+
+        let (mut tx, _rx) = mpsc::Builder::new(1).stop_token(stop.token()).build();
+        assert!(stop.token().is_stopped());
+        assert!(tx.try_send(1usize).is_err());
+    }
+}
@@ -35,30 +35,53 @@
 //!     }
 //! }
 //! ```
-use std::{future::Future, marker::PhantomPinned, ops::DerefMut, pin::Pin};
+use std::{future::Future, ops::DerefMut, pin::Pin};
 
 use crate::Context;
 use pin_project::pin_project;
 use std::task::Poll;
 
 use self::{
-    chain::ChainStream, filter::FilterStream, find::FindStream, map::MapStream, merge::MergeStream,
-    once::OnceStream, repeat::RepeatStream,
+    backfill::BackfillStream, chain::ChainStream, fair::FairStream, filter::FilterStream,
+    find::FindStream, map::MapStream, map_or_divert::MapOrDivertStream, merge::MergeStream,
+    once::OnceStream, reconfigure::ReconfigureStream, repeat::RepeatStream,
+    with_latest_from::WithLatestFromStream,
 };
 
+#[cfg(feature = "nightly")]
+mod async_iter;
+mod backfill;
+#[cfg(feature = "blocking")]
+mod blocking_iter;
 mod chain;
+mod dyn_stream;
 mod errors;
-mod filter;
+mod fair;
+pub(crate) mod filter;
 mod find;
-mod map;
+mod iter;
+pub(crate) mod map;
+mod map_or_divert;
 mod merge;
 mod once;
+mod prioritized;
+mod reconfigure;
 mod repeat;
+mod try_stream;
+mod with_latest_from;
 
 #[cfg(feature = "logging")]
 mod stream_log;
 
+#[cfg(feature = "blocking")]
+pub use blocking_iter::BlockingIter;
+pub use dyn_stream::DynStream;
 pub use errors::*;
+pub use prioritized::PrioritizedStream;
+pub use reconfigure::Upstream;
+pub use try_stream::{
+    MapErrStream, TryCollectFuture, TryFilterStream, TryFoldFuture, TryForEachFuture, TryStream,
+};
 
 /// An asynchronous stream, which produces a series of messages until closed.
 ///
@@ -128,6 +151,14 @@ pub trait Stream {
     /// - `PollRecv::Closed` if the stream is closed, and no messages are expected.
     fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item>;
 
+    /// Returns the bounds on the number of items remaining in the stream, with the same meaning as `Iterator::size_hint`.
+    ///
+    /// The default implementation returns `(0, None)`.  Channel receivers return the number of buffered messages as the lower bound,
+    /// and provide an upper bound once all senders have been dropped.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+
     /// Retrieves a message from the stream.
     ///
     /// Returns:
@@ -172,6 +203,71 @@ pub trait Stream {
         pollster::block_on(self.recv())
     }
 
+    /// Converts the stream into an iterator, which blocks the current thread until each message is available.
+    /// The iterator ends when the stream is closed.
+    #[cfg(feature = "blocking")]
+    fn blocking_iter(self) -> BlockingIter<Self>
+    where
+        Self: Sized + Unpin,
+    {
+        BlockingIter::new(self)
+    }
+
+    /// Converts the stream into a rayon `ParallelIterator`, which fans the messages out across the rayon pool.
+    ///
+    /// Rayon worker threads block while they wait for messages, so CPU-bound consumers should be given a dedicated pool.
+    /// Messages are processed in no particular order.
+    ///
+    /// Requires the `rayon` feature.
+    ///
+    /// ```rust
+    /// use postage::{mpsc, prelude::*};
+    /// use rayon::iter::ParallelIterator;
+    ///
+    /// let (mut tx, rx) = mpsc::channel(4);
+    /// std::thread::spawn(move || {
+    ///     for i in 1..=10usize {
+    ///         tx.blocking_send(i).ok();
+    ///     }
+    /// });
+    ///
+    /// let sum: usize = rx.par_bridge().map(|i| i * i).sum();
+    /// assert_eq!(385, sum);
+    /// ```
+    #[cfg(feature = "rayon")]
+    fn par_bridge(self) -> rayon::iter::IterBridge<BlockingIter<Self>>
+    where
+        Self: Sized + Send + Unpin,
+        Self::Item: Send,
+    {
+        rayon::iter::ParallelBridge::par_bridge(self.blocking_iter())
+    }
+
+    /// Converts the stream into a future, which resolves to the next item and the stream.
+    ///
+    /// Unlike `recv`, the future owns the stream, so it can be held across await points, or moved into a select.
+    /// ```rust
+    /// use postage::{mpsc, prelude::*};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let (mut tx, rx) = mpsc::channel(4);
+    ///     tx.send(1usize).await.ok();
+    ///
+    ///     let (value, rx) = rx.into_future().await;
+    ///     assert_eq!(Some(1), value);
+    ///
+    ///     drop(tx);
+    ///     assert_eq!(None, rx.into_future().await.0);
+    /// }
+    /// ```
+    fn into_future(self) -> StreamFuture<Self>
+    where
+        Self: Sized + Unpin,
+    {
+        StreamFuture { stream: Some(self) }
+    }
+
     /// Transforms the stream with a map function.
     fn map<Map, Into>(self, map: Map) -> MapStream<Self, Map, Into>
     where
@@ -181,6 +277,39 @@ pub trait Stream {
         MapStream::new(self, map)
     }
 
+    /// Transforms the stream with a fallible map function.  Values are received from the stream,
+    /// and errors are sent to `divert`, so the stream itself remains infallible.
+    ///
+    /// The stream waits while `divert` is full.  If `divert` is closed, errors are dropped.
+    ///
+    /// ```rust
+    /// use postage::{mpsc, prelude::*};
+    ///
+    /// let (mut tx, rx) = mpsc::channel(4);
+    /// let (errors_tx, mut errors) = mpsc::channel(4);
+    /// let mut rx = rx.map_or_divert(|line: &str| line.parse::<usize>().map_err(|_| line), errors_tx);
+    ///
+    /// tx.try_send("1").ok();
+    /// tx.try_send("x").ok();
+    /// tx.try_send("2").ok();
+    ///
+    /// assert_eq!(Ok(1), rx.try_recv());
+    /// assert_eq!(Ok(2), rx.try_recv());
+    /// assert_eq!(Ok("x"), errors.try_recv());
+    /// ```
+    fn map_or_divert<Map, Into, Divert>(
+        self,
+        map: Map,
+        divert: Divert,
+    ) -> MapOrDivertStream<Self, Map, Into, Divert>
+    where
+        Map: Fn(Self::Item) -> Result<Into, Divert::Item>,
+        Divert: crate::sink::Sink,
+        Self: Sized,
+    {
+        MapOrDivertStream::new(self, map, divert)
+    }
+
     /// Filters messages returned by the stream, ignoring messages where `filter` returns false.
     fn filter<Filter>(self, filter: Filter) -> FilterStream<Self, Filter>
     where
@@ -199,6 +328,21 @@ pub trait Stream {
         MergeStream::new(self, other)
     }
 
+    /// Merges two streams, polling them in the order chosen by the [Fairness](../select/enum.Fairness.html) strategy.
+    ///
+    /// `Fairness::Prioritized` always checks `self` first, for the lowest latency on `self`.
+    fn merge_with<Other>(
+        self,
+        other: Other,
+        fairness: crate::select::Fairness,
+    ) -> MergeStream<Self, Other>
+    where
+        Other: Stream<Item = Self::Item>,
+        Self: Sized,
+    {
+        MergeStream::with_fairness(self, other, fairness)
+    }
+
     /// Chains two streams, returning values from `self` until it is closed, and then returning values from `other`.
     fn chain<Other>(self, other: Other) -> ChainStream<Self, Other>
     where
@@ -218,6 +362,94 @@ pub trait Stream {
         FindStream::new(self, condition)
     }
 
+    /// Pairs each message with a clone of the latest value in the watch channel, at the time the message is received.
+    ///
+    /// If the watch channel is empty, the message waits for the first value.  The stream closes when `self` is closed,
+    /// or if the watch channel closes before it has a value.
+    fn with_latest_from<W>(self, watch: crate::watch::Receiver<W>) -> WithLatestFromStream<Self, W>
+    where
+        W: Clone,
+        Self: Sized,
+    {
+        WithLatestFromStream::new(self, watch)
+    }
+
+    /// Rebuilds an adapter whenever the watch channel changes, so a pipeline can be tuned without restarting it.
+    ///
+    /// `build` is called with the latest configuration, and an [Upstream](./struct.Upstream.html) handle to this stream,
+    /// and returns the adapter which is received from.  When the configuration changes, the adapter is dropped and rebuilt,
+    /// and the upstream stream continues where it left off.  Messages buffered inside the dropped adapter are lost.
+    ///
+    /// The adapter is built when the watch channel has its first value.  If the watch channel closes, the current adapter is kept.
+    ///
+    /// ```rust
+    /// use postage::{mpsc, prelude::*, watch};
+    ///
+    /// let (mut tx, rx) = mpsc::channel(8);
+    /// let (mut threshold_tx, threshold_rx) = watch::channel_with(10usize);
+    /// let mut rx = rx.reconfigure(threshold_rx, |threshold, upstream| {
+    ///     let threshold = *threshold;
+    ///     upstream.filter(move |value: &usize| *value >= threshold)
+    /// });
+    ///
+    /// tx.try_send(5).ok();
+    /// tx.try_send(20).ok();
+    /// assert_eq!(Ok(20), rx.try_recv());
+    ///
+    /// threshold_tx.try_send(1).ok();
+    /// tx.try_send(5).ok();
+    /// assert_eq!(Ok(5), rx.try_recv());
+    /// ```
+    fn reconfigure<C, F, A>(
+        self,
+        config: crate::watch::Receiver<C>,
+        build: F,
+    ) -> ReconfigureStream<Self, C, F, A>
+    where
+        C: Clone,
+        F: FnMut(&C, Upstream<Self>) -> A,
+        A: Stream,
+        Self: Sized,
+    {
+        ReconfigureStream::new(self, config, build)
+    }
+
+    /// Interleaves messages fairly across keys, so one busy key cannot starve the others.
+    ///
+    /// Up to `lookahead` messages are read ahead, and queued by the key returned by `key`.
+    /// The queues are served with deficit round robin: each key with queued messages receives up to `quantum` messages per round.
+    /// Messages with the same key are received in order.
+    ///
+    /// Panics if `quantum` or `lookahead` is zero.
+    ///
+    /// ```rust
+    /// use postage::{mpsc, prelude::*};
+    ///
+    /// let (mut tx, rx) = mpsc::channel(8);
+    /// let mut rx = rx.fair_by_key(|(tenant, _): &(&str, usize)| *tenant, 1, 8);
+    ///
+    /// tx.try_send(("a", 1)).ok();
+    /// tx.try_send(("a", 2)).ok();
+    /// tx.try_send(("b", 1)).ok();
+    ///
+    /// assert_eq!(Ok(("a", 1)), rx.try_recv());
+    /// assert_eq!(Ok(("b", 1)), rx.try_recv());
+    /// assert_eq!(Ok(("a", 2)), rx.try_recv());
+    /// ```
+    fn fair_by_key<Key, K>(
+        self,
+        key: Key,
+        quantum: usize,
+        lookahead: usize,
+    ) -> FairStream<Self, Key, K>
+    where
+        Key: FnMut(&Self::Item) -> K,
+        K: std::hash::Hash + Eq + Clone,
+        Self: Sized,
+    {
+        FairStream::new(self, key, quantum, lookahead)
+    }
+
     /// Logs messages that are produced by the stream using the Debug trait, at the provided log level.
     ///
     /// Requires the `logging` feature
@@ -240,6 +472,10 @@ where
     fn poll_recv(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item> {
         S::poll_recv(Pin::new(&mut **self), cx)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        S::size_hint(&**self)
+    }
 }
 
 impl<P, S> Stream for Pin<P>
@@ -252,6 +488,97 @@ where
     fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item> {
         Pin::get_mut(self).as_mut().poll_recv(cx)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        S::size_hint(&**self)
+    }
+}
+
+/// Adds the size hints of two streams which are both consumed
+pub(crate) fn add_hints(
+    left: (usize, Option<usize>),
+    right: (usize, Option<usize>),
+) -> (usize, Option<usize>) {
+    let lower = left.0.saturating_add(right.0);
+    let upper = match (left.1, right.1) {
+        (Some(left), Some(right)) => left.checked_add(right),
+        _ => None,
+    };
+
+    (lower, upper)
+}
+
+/// Merges streams by priority.  The first stream has the highest priority.
+///
+/// Higher priority streams are always drained first, and a lower priority stream is only polled when every higher priority stream is pending.
+/// [PrioritizedStream::max_burst](./struct.PrioritizedStream.html#method.max_burst) prevents a busy stream from starving the streams below it.
+/// Streams of different types can be merged as `Box<dyn DynStream<T>>` trait objects.
+///
+/// ```rust
+/// use postage::{mpsc, prelude::*, stream::merge_prioritized};
+///
+/// let (mut control_tx, control) = mpsc::channel(4);
+/// let (mut data_tx, data) = mpsc::channel(4);
+/// let mut rx = merge_prioritized([control, data]).max_burst(8);
+///
+/// data_tx.try_send("data").ok();
+/// control_tx.try_send("flush").ok();
+///
+/// assert_eq!(Ok("flush"), rx.try_recv());
+/// assert_eq!(Ok("data"), rx.try_recv());
+/// ```
+pub fn merge_prioritized<S, I>(streams: I) -> PrioritizedStream<S>
+where
+    I: IntoIterator<Item = S>,
+    S: Stream + Unpin,
+{
+    PrioritizedStream::new(streams.into_iter().collect())
+}
+
+/// Returns a stream which produces the items of the iterator, and then is closed.
+pub fn iter<I>(iter: I) -> iter::IterStream<I::IntoIter>
+where
+    I: IntoIterator,
+{
+    iter::IterStream::new(iter.into_iter())
+}
+
+/// Produces the historical items, and then tails the live stream, skipping live items which the history already produced.
+///
+/// This is the 'catch up, then tail' pattern of event-sourced consumers.  Subscribe to the live channel before
+/// reading the history, so no items are missed between the two, and the overlap is removed by key.
+///
+/// The keys must increase, such as a sequence number or offset.  Live items with a key less than or equal to
+/// the key of the last historical item are skipped.  Iterators can be used as the history with [iter](./fn.iter.html).
+///
+/// ```rust
+/// use postage::{mpsc, prelude::*, stream};
+///
+/// let (mut tx, rx) = mpsc::channel(8);
+///
+/// // the live channel receives events 3 and 4, while the history is loaded
+/// tx.try_send(3usize).ok();
+/// tx.try_send(4).ok();
+///
+/// let history = vec![1, 2, 3];
+/// let mut events = stream::backfill(stream::iter(history), rx, |seq| *seq);
+///
+/// for expected in 1..=4 {
+///     assert_eq!(Ok(expected), events.try_recv());
+/// }
+/// ```
+pub fn backfill<History, Live, Key, K>(
+    history: History,
+    live: Live,
+    key: Key,
+) -> BackfillStream<History, Live, Key, K>
+where
+    History: Stream,
+    Live: Stream<Item = History::Item>,
+    Key: FnMut(&History::Item) -> K,
+    K: Ord,
+{
+    BackfillStream::new(history, live, key)
 }
 
 /// Returns a stream which produces a single value, and then is closed.
@@ -286,8 +613,6 @@ where
     S: Stream + ?Sized,
 {
     recv: &'s mut S,
-    #[pin]
-    _pin: PhantomPinned,
 }
 
 impl<'s, S: Stream> RecvFuture<'s, S>
@@ -295,10 +620,7 @@ where
     S: ?Sized,
 {
     pub fn new(recv: &'s mut S) -> RecvFuture<'s, S> {
-        Self {
-            recv,
-            _pin: PhantomPinned,
-        }
+        Self { recv }
     }
 }
 
@@ -320,6 +642,43 @@ where
     }
 }
 
+/// A future returned by `Stream::into_future`, which owns the stream.
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct StreamFuture<S> {
+    stream: Option<S>,
+}
+
+impl<S> StreamFuture<S> {
+    /// Consumes the future, returning the stream if it has not produced an item.
+    pub fn into_inner(self) -> Option<S> {
+        self.stream
+    }
+}
+
+impl<S> Future for StreamFuture<S>
+where
+    S: Stream + Unpin,
+{
+    type Output = (Option<S::Item>, S);
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        let stream = self
+            .stream
+            .as_mut()
+            .expect("StreamFuture polled after completion");
+
+        let mut cx: crate::Context<'_> = cx.into();
+        let item = match Pin::new(stream).poll_recv(&mut cx) {
+            PollRecv::Ready(v) => Some(v),
+            PollRecv::Pending => return Poll::Pending,
+            PollRecv::Closed => None,
+        };
+
+        Poll::Ready((item, self.stream.take().unwrap()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -332,4 +691,30 @@ mod tests {
         let mut stream = ready(1usize);
         assert_eq!(Some(1usize), stream.blocking_recv());
     }
+
+    #[test]
+    fn into_future() {
+        use super::Stream;
+        use crate::stream::PollRecv;
+        use crate::test::stream::{from_poll_iter, pending};
+        use futures_test::task::noop_context;
+        use std::{future::Future, pin::Pin, task::Poll};
+
+        let stream = from_poll_iter(vec![PollRecv::Ready(1usize), PollRecv::Closed]);
+        let mut future = stream.into_future();
+        let stream = match Pin::new(&mut future).poll(&mut noop_context()) {
+            Poll::Ready((Some(1), stream)) => stream,
+            _ => panic!("expected the first item"),
+        };
+
+        let mut future = stream.into_future();
+        assert!(matches!(
+            Pin::new(&mut future).poll(&mut noop_context()),
+            Poll::Ready((None, _))
+        ));
+
+        let mut future = pending::<usize>().into_future();
+        assert!(Pin::new(&mut future).poll(&mut noop_context()).is_pending());
+        assert!(future.into_inner().is_some());
+    }
 }
@@ -0,0 +1,118 @@
+//! Implementations of the nightly `AsyncIterator` trait, for the postage receivers and stream combinators.
+//!
+//! Requires the `nightly` feature, and a nightly compiler.
+
+use std::{
+    async_iter::AsyncIterator,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use super::{PollRecv, Stream};
+
+macro_rules! async_iterator {
+    ($( $(#[$meta:meta])* <$($param:ident $(: $bound:path)?),*> $ty:ty; )*) => {
+        $(
+            $(#[$meta])*
+            impl<$($param $(: $bound)?),*> AsyncIterator for $ty
+            where
+                Self: Stream,
+            {
+                type Item = <Self as Stream>::Item;
+
+                fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+                    match Stream::poll_recv(self, &mut cx.into()) {
+                        PollRecv::Ready(value) => Poll::Ready(Some(value)),
+                        PollRecv::Pending => Poll::Pending,
+                        PollRecv::Closed => Poll::Ready(None),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+async_iterator! {
+    // channels
+    <T> crate::ack::Receiver<T>;
+    <> crate::barrier::Receiver;
+    <T> crate::broadcast::Receiver<T>;
+    <T> crate::credit::Receiver<T>;
+    <T> crate::dispatch::Receiver<T>;
+    <T> crate::group::Receiver<T>;
+    <T> crate::mpsc::Receiver<T>;
+    <T> crate::mpsc::ShardedReceiver<T>;
+    <T> crate::mpsc::ControlledReceiver<T>;
+    <T> crate::oneshot::Receiver<T>;
+    <T> crate::watch::Receiver<T>;
+    <A: Stream, B: Stream> crate::watch::CombineLatest<A, B>;
+    <M> crate::actor::Mailbox<M>;
+    #[cfg(feature = "durable")]
+    <T, C> crate::durable::Receiver<T, C>;
+    #[cfg(feature = "spill")]
+    <T> crate::spill::Receiver<T>;
+
+    // combinators
+    <History, Live, Key, K> super::backfill::BackfillStream<History, Live, Key, K>;
+    <Left, Right> super::chain::ChainStream<Left, Right>;
+    <I> super::iter::IterStream<I>;
+    <S: Stream, Key, K> super::fair::FairStream<S, Key, K>;
+    <From, Filter> super::filter::FilterStream<From, Filter>;
+    <From, Condition> super::find::FindStream<From, Condition>;
+    <From, Map, Into> super::map::MapStream<From, Map, Into>;
+    <Left, Right> super::merge::MergeStream<Left, Right>;
+    <T> super::once::OnceStream<T>;
+    <S> super::PrioritizedStream<S>;
+    <S, C, F, A> super::reconfigure::ReconfigureStream<S, C, F, A>;
+    <S> super::Upstream<S>;
+    <T> super::repeat::RepeatStream<T>;
+    <From, Filter> super::TryFilterStream<From, Filter>;
+    <From, Map> super::MapErrStream<From, Map>;
+    <S: Stream, W> super::with_latest_from::WithLatestFromStream<S, W>;
+    #[cfg(feature = "logging")]
+    <S> super::stream_log::StreamLog<S>;
+    <S> crate::instrument::Measured<S>;
+    <S: Stream> crate::replay::RecordStream<S>;
+    <T> crate::replay::ReplayStream<T>;
+    #[cfg(feature = "codec")]
+    <S, D> crate::codec::DecodeStream<S, D>;
+    #[cfg(feature = "timer")]
+    <> crate::time::Interval;
+    #[cfg(feature = "timer")]
+    <S> crate::time::DeadlineReceiver<S>;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{async_iter::AsyncIterator, pin::Pin, task::Poll};
+
+    use futures_test::task::noop_context;
+
+    use crate::{mpsc, sink::Sink, stream::Stream};
+
+    #[test]
+    fn receiver() {
+        let mut cx = noop_context();
+        let (mut tx, mut rx) = mpsc::channel(2);
+
+        assert_eq!(Poll::Pending, Pin::new(&mut rx).poll_next(&mut cx));
+
+        tx.try_send(1usize).unwrap();
+        drop(tx);
+        assert_eq!(Poll::Ready(Some(1)), Pin::new(&mut rx).poll_next(&mut cx));
+        assert_eq!(Poll::Ready(None), Pin::new(&mut rx).poll_next(&mut cx));
+    }
+
+    #[test]
+    fn combinator() {
+        let mut cx = noop_context();
+        let (mut tx, rx) = mpsc::channel(2);
+        let mut doubled = rx.map(|value: usize| value * 2);
+
+        tx.try_send(2usize).unwrap();
+        assert_eq!(
+            Poll::Ready(Some(4)),
+            Pin::new(&mut doubled).poll_next(&mut cx)
+        );
+    }
+}
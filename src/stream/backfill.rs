@@ -0,0 +1,164 @@
+use std::pin::Pin;
+
+use pin_project::pin_project;
+
+use crate::{
+    stream::{add_hints, PollRecv, Stream},
+    Context,
+};
+
+/// Produces the historical items, and then the live items which are newer than the last historical item.
+#[pin_project]
+pub struct BackfillStream<History, Live, Key, K> {
+    #[pin]
+    history: History,
+    #[pin]
+    live: Live,
+    key: Key,
+    // the key of the last historical item, or None if the history has not closed
+    last: Option<K>,
+    live_started: bool,
+}
+
+impl<History, Live, Key, K> BackfillStream<History, Live, Key, K>
+where
+    History: Stream,
+    Live: Stream<Item = History::Item>,
+    Key: FnMut(&History::Item) -> K,
+    K: Ord,
+{
+    pub fn new(history: History, live: Live, key: Key) -> Self {
+        Self {
+            history,
+            live,
+            key,
+            last: None,
+            live_started: false,
+        }
+    }
+}
+
+impl<History, Live, Key, K> BackfillStream<History, Live, Key, K> {
+    /// Returns references to the history and live streams
+    pub fn get_ref(&self) -> (&History, &Live) {
+        (&self.history, &self.live)
+    }
+
+    /// Returns mutable references to the history and live streams
+    pub fn get_mut(&mut self) -> (&mut History, &mut Live) {
+        (&mut self.history, &mut self.live)
+    }
+
+    /// Returns the history and live streams
+    pub fn into_inner(self) -> (History, Live) {
+        (self.history, self.live)
+    }
+}
+
+impl<History, Live, Key, K> Stream for BackfillStream<History, Live, Key, K>
+where
+    History: Stream,
+    Live: Stream<Item = History::Item>,
+    Key: FnMut(&History::Item) -> K,
+    K: Ord,
+{
+    type Item = History::Item;
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item> {
+        let mut this = self.project();
+
+        if !*this.live_started {
+            match this.history.poll_recv(cx) {
+                PollRecv::Ready(value) => {
+                    *this.last = Some((this.key)(&value));
+                    return PollRecv::Ready(value);
+                }
+                PollRecv::Pending => return PollRecv::Pending,
+                PollRecv::Closed => *this.live_started = true,
+            }
+        }
+
+        loop {
+            let value = match this.live.as_mut().poll_recv(cx) {
+                PollRecv::Ready(value) => value,
+                PollRecv::Pending => return PollRecv::Pending,
+                PollRecv::Closed => return PollRecv::Closed,
+            };
+
+            let key = (this.key)(&value);
+            match this.last {
+                // the live item overlaps the history
+                Some(last) if key <= *last => continue,
+                _ => {
+                    // keys are increasing, so the overlap has ended
+                    *this.last = None;
+                    return PollRecv::Ready(value);
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let live = self.live.size_hint();
+        let live = (0, live.1);
+
+        if self.live_started {
+            live
+        } else {
+            add_hints(self.history.size_hint(), live)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        mpsc,
+        sink::Sink,
+        stream::{backfill, iter, Stream, TryRecvError},
+    };
+
+    #[test]
+    fn history_then_live() {
+        let (mut tx, rx) = mpsc::channel(8);
+        let mut stream = backfill(iter(vec![1usize, 2, 3]), rx, |value| *value);
+
+        // the live channel overlaps the history
+        for i in 2..=5 {
+            tx.try_send(i).unwrap();
+        }
+
+        for expected in 1..=5 {
+            assert_eq!(Ok(expected), stream.try_recv());
+        }
+
+        assert_eq!(Err(TryRecvError::Pending), stream.try_recv());
+        drop(tx);
+        assert_eq!(Err(TryRecvError::Closed), stream.try_recv());
+    }
+
+    #[test]
+    fn empty_history() {
+        let (mut tx, rx) = mpsc::channel(8);
+        let mut stream = backfill(iter(Vec::<(usize, &str)>::new()), rx, |(seq, _)| *seq);
+
+        tx.try_send((7, "a")).unwrap();
+        assert_eq!(Ok((7, "a")), stream.try_recv());
+    }
+
+    #[test]
+    fn history_from_stream() {
+        let (mut history_tx, history_rx) = mpsc::channel(8);
+        let (mut live_tx, live_rx) = mpsc::channel(8);
+        let mut stream = backfill(history_rx, live_rx, |value| *value);
+
+        history_tx.try_send(1usize).unwrap();
+        live_tx.try_send(2).unwrap();
+
+        assert_eq!(Ok(1), stream.try_recv());
+        assert_eq!(Err(TryRecvError::Pending), stream.try_recv());
+
+        drop(history_tx);
+        assert_eq!(Ok(2), stream.try_recv());
+    }
+}
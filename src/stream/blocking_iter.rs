@@ -0,0 +1,87 @@
+use crate::stream::Stream;
+
+/// An iterator which blocks the current thread on each item of a stream, created by [Stream::blocking_iter](./trait.Stream.html#method.blocking_iter).
+///
+/// The iterator ends when the stream is closed.
+#[derive(Debug)]
+pub struct BlockingIter<S> {
+    stream: S,
+}
+
+impl<S> BlockingIter<S> {
+    pub(crate) fn new(stream: S) -> Self {
+        Self { stream }
+    }
+
+    /// Returns a reference to the stream
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Returns a mutable reference to the stream
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Consumes the iterator, returning the stream
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S> Iterator for BlockingIter<S>
+where
+    S: Stream + Unpin,
+{
+    type Item = S::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.stream.blocking_recv()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use crate::{mpsc, sink::Sink, stream::Stream};
+
+    #[test]
+    fn blocking_iter() {
+        let (mut tx, rx) = mpsc::channel(2);
+
+        let sender = thread::spawn(move || {
+            for i in 0..8usize {
+                tx.blocking_send(i).unwrap();
+            }
+        });
+
+        assert_eq!(
+            (0..8).collect::<Vec<_>>(),
+            rx.blocking_iter().collect::<Vec<_>>()
+        );
+        sender.join().unwrap();
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_bridge() {
+        use rayon::iter::ParallelIterator;
+
+        let (mut tx, rx) = mpsc::channel(4);
+
+        let sender = thread::spawn(move || {
+            for i in 1..=100usize {
+                tx.blocking_send(i).unwrap();
+            }
+        });
+
+        let sum: usize = rx.par_bridge().map(|i| i * 2).sum();
+        assert_eq!(10100, sum);
+        sender.join().unwrap();
+    }
+}
@@ -2,7 +2,7 @@ use std::pin::Pin;
 
 use atomic::{Atomic, Ordering};
 
-use crate::stream::{PollRecv, Stream};
+use crate::stream::{add_hints, PollRecv, Stream};
 use crate::Context;
 use pin_project::pin_project;
 
@@ -36,6 +36,23 @@ where
     }
 }
 
+impl<Left, Right> ChainStream<Left, Right> {
+    /// Returns references to the first and second streams
+    pub fn get_ref(&self) -> (&Left, &Right) {
+        (&self.left, &self.right)
+    }
+
+    /// Returns mutable references to the first and second streams
+    pub fn get_mut(&mut self) -> (&mut Left, &mut Right) {
+        (&mut self.left, &mut self.right)
+    }
+
+    /// Returns the first and second streams
+    pub fn into_inner(self) -> (Left, Right) {
+        (self.left, self.right)
+    }
+}
+
 impl<Left, Right> Stream for ChainStream<Left, Right>
 where
     Left: Stream,
@@ -75,6 +92,14 @@ where
 
         unreachable!();
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.state.load(Ordering::Acquire) {
+            State::Left => add_hints(self.left.size_hint(), self.right.size_hint()),
+            State::Right => self.right.size_hint(),
+            State::Closed => (0, Some(0)),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -88,6 +113,7 @@ mod tests {
     };
 
     use super::ChainStream;
+    use crate::stream::once::OnceStream;
 
     #[test]
     fn chain() {
@@ -103,6 +129,19 @@ mod tests {
         assert_eq!(PollRecv::Closed, Pin::new(&mut find).poll_recv(&mut cx));
     }
 
+    #[test]
+    fn size_hint() {
+        let mut chain = ChainStream::new(OnceStream::new(1), OnceStream::new(2));
+        assert_eq!((2, Some(2)), chain.size_hint());
+
+        let mut cx = Context::empty();
+        assert_eq!(PollRecv::Ready(1), Pin::new(&mut chain).poll_recv(&mut cx));
+        assert_eq!(PollRecv::Ready(2), Pin::new(&mut chain).poll_recv(&mut cx));
+        assert_eq!((0, Some(0)), chain.size_hint());
+        assert_eq!(PollRecv::Closed, Pin::new(&mut chain).poll_recv(&mut cx));
+        assert_eq!((0, Some(0)), chain.size_hint());
+    }
+
     #[test]
     fn waits_for_right() {
         let left = from_poll_iter(vec![PollRecv::Pending]);
@@ -0,0 +1,106 @@
+use std::pin::Pin;
+
+use crate::stream::{PollRecv, Stream};
+use crate::Context;
+
+/// An object-safe version of [Stream](./trait.Stream.html), which allows streams to be stored as trait objects.
+///
+/// `DynStream` is implemented for all `Unpin` streams, and boxed trait objects implement `Stream`.
+/// Streams which are not `Unpin` can be pinned with `Box::pin` first.
+///
+/// ```rust
+/// use postage::{prelude::*, stream::DynStream};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let (mut tx, rx) = mpsc::channel(4);
+///     let (mut tx2, rx2) = broadcast::channel(4);
+///
+///     let mut streams: Vec<Box<dyn DynStream<usize> + Send>> = vec![Box::new(rx), Box::new(rx2)];
+///
+///     tx.send(1).await.ok();
+///     tx2.send(2).await.ok();
+///     assert_eq!(Some(1), streams[0].recv().await);
+///     assert_eq!(Some(2), streams[1].recv().await);
+/// }
+/// ```
+pub trait DynStream<T> {
+    /// Attempts to retrieve an item from the stream.  See [Stream::poll_recv](./trait.Stream.html#tymethod.poll_recv).
+    fn poll_recv_dyn(&mut self, cx: &mut Context<'_>) -> PollRecv<T>;
+
+    /// Returns the bounds on the number of items remaining.  See [Stream::size_hint](./trait.Stream.html#method.size_hint).
+    fn size_hint_dyn(&self) -> (usize, Option<usize>);
+}
+
+impl<S> DynStream<S::Item> for S
+where
+    S: Stream + Unpin,
+{
+    fn poll_recv_dyn(&mut self, cx: &mut Context<'_>) -> PollRecv<S::Item> {
+        Pin::new(self).poll_recv(cx)
+    }
+
+    fn size_hint_dyn(&self) -> (usize, Option<usize>) {
+        self.size_hint()
+    }
+}
+
+macro_rules! impl_boxed_stream {
+    ($($bounds:tt)*) => {
+        impl<'a, T> Stream for Box<dyn DynStream<T> $($bounds)* + 'a> {
+            type Item = T;
+
+            fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<T> {
+                // deref to the trait object, as the box itself also implements DynStream
+                (**self.get_mut()).poll_recv_dyn(cx)
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (**self).size_hint_dyn()
+            }
+        }
+    };
+}
+
+impl_boxed_stream!();
+impl_boxed_stream!(+ Send);
+impl_boxed_stream!(+ Send + Sync);
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+
+    use super::DynStream;
+    use crate::test::stream::*;
+    use crate::{
+        sink::Sink,
+        stream::{PollRecv, Stream},
+        Context,
+    };
+
+    #[test]
+    fn boxed_streams() {
+        let mut streams: Vec<Box<dyn DynStream<usize>>> =
+            vec![Box::new(ready(1usize)), Box::new(closed())];
+
+        let mut cx = Context::empty();
+        assert_eq!(
+            PollRecv::Ready(1),
+            Pin::new(&mut streams[0]).poll_recv(&mut cx)
+        );
+        assert_eq!(
+            PollRecv::Closed,
+            Pin::new(&mut streams[1]).poll_recv(&mut cx)
+        );
+    }
+
+    #[test]
+    fn boxed_send_sync() {
+        let (mut tx, rx) = crate::mpsc::channel(1);
+        let mut stream: Box<dyn DynStream<usize> + Send + Sync> = Box::new(rx);
+
+        tx.try_send(1).unwrap();
+        assert_eq!((1, None), stream.size_hint());
+        assert_eq!(Ok(1), stream.try_recv());
+    }
+}
@@ -4,9 +4,60 @@ use thiserror::Error;
 #[derive(Debug, Error, PartialEq, Eq)]
 pub enum TryRecvError {
     /// The stream may produce an item at a later time
-    #[error("TryRecvError::Pending")]
+    #[error("the stream is empty")]
     Pending,
     /// The stream is closed, and will never produce an item
-    #[error("TryRecvError::Closed")]
+    #[error("the stream is closed")]
     Closed,
 }
+
+impl TryRecvError {
+    /// Returns true if the stream was empty, and may produce an item at a later time
+    pub fn is_empty(&self) -> bool {
+        matches!(self, TryRecvError::Pending)
+    }
+
+    /// Returns true if the stream was closed
+    pub fn is_closed(&self) -> bool {
+        matches!(self, TryRecvError::Closed)
+    }
+}
+
+/// An error which indicates the stream is closed, and will never produce another item.
+///
+/// `Stream::recv` returns `None` when the stream closes.  `RecvError` allows the closure to be propagated with `?`:
+/// ```rust
+/// use postage::{mpsc, prelude::*, stream::RecvError};
+///
+/// async fn first(rx: &mut mpsc::Receiver<usize>) -> Result<usize, RecvError> {
+///     let value = rx.recv().await.ok_or(RecvError)?;
+///     Ok(value)
+/// }
+/// ```
+#[derive(Copy, Clone, Debug, Error, PartialEq, Eq, Hash)]
+#[error("the stream is closed")]
+pub struct RecvError;
+
+impl From<RecvError> for TryRecvError {
+    fn from(_: RecvError) -> Self {
+        TryRecvError::Closed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RecvError, TryRecvError};
+
+    #[test]
+    fn try_recv_error() {
+        assert!(TryRecvError::Pending.is_empty());
+        assert!(TryRecvError::Closed.is_closed());
+        assert_eq!("the stream is empty", TryRecvError::Pending.to_string());
+    }
+
+    #[test]
+    fn recv_error() {
+        assert_eq!("the stream is closed", RecvError.to_string());
+        assert_eq!(TryRecvError::Closed, TryRecvError::from(RecvError));
+    }
+}
@@ -0,0 +1,205 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    pin::Pin,
+};
+
+use pin_project::pin_project;
+
+use crate::{
+    stream::{PollRecv, Stream},
+    Context,
+};
+
+/// Interleaves messages fairly across keys, with deficit round robin over per-key queues.
+///
+/// Up to `lookahead` messages are read ahead from the stream, and queued by key.
+/// Each key with queued messages receives `quantum` deliveries per round.
+#[pin_project]
+pub struct FairStream<S: Stream, Key, K> {
+    #[pin]
+    stream: S,
+    key: Key,
+    quantum: usize,
+    lookahead: usize,
+    queues: HashMap<K, VecDeque<S::Item>>,
+    // the keys with queued messages, in round order.  the front key is being served
+    active: VecDeque<K>,
+    deficit: usize,
+    queued: usize,
+    closed: bool,
+}
+
+impl<S, Key, K> FairStream<S, Key, K>
+where
+    S: Stream,
+    Key: FnMut(&S::Item) -> K,
+    K: Hash + Eq + Clone,
+{
+    pub fn new(stream: S, key: Key, quantum: usize, lookahead: usize) -> Self {
+        assert!(quantum > 0, "the fair stream quantum must be non-zero");
+        assert!(lookahead > 0, "the fair stream lookahead must be non-zero");
+
+        Self {
+            stream,
+            key,
+            quantum,
+            lookahead,
+            queues: HashMap::new(),
+            active: VecDeque::new(),
+            deficit: 0,
+            queued: 0,
+            closed: false,
+        }
+    }
+}
+
+impl<S: Stream, Key, K> FairStream<S, Key, K> {
+    /// Returns a reference to the wrapped stream
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Returns a mutable reference to the wrapped stream
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Returns the wrapped stream
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S, Key, K> Stream for FairStream<S, Key, K>
+where
+    S: Stream,
+    Key: FnMut(&S::Item) -> K,
+    K: Hash + Eq + Clone,
+{
+    type Item = S::Item;
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item> {
+        let mut this = self.project();
+
+        while !*this.closed && *this.queued < *this.lookahead {
+            match this.stream.as_mut().poll_recv(cx) {
+                PollRecv::Ready(value) => {
+                    let key = (this.key)(&value);
+                    let queue = this.queues.entry(key.clone()).or_default();
+                    if queue.is_empty() {
+                        this.active.push_back(key);
+                    }
+
+                    queue.push_back(value);
+                    *this.queued += 1;
+                }
+                PollRecv::Pending => break,
+                PollRecv::Closed => *this.closed = true,
+            }
+        }
+
+        let key = match this.active.front() {
+            Some(key) => key,
+            None if *this.closed => return PollRecv::Closed,
+            None => return PollRecv::Pending,
+        };
+
+        if *this.deficit == 0 {
+            *this.deficit = *this.quantum;
+        }
+
+        let queue = this.queues.get_mut(key).unwrap();
+        let value = queue.pop_front().unwrap();
+        *this.queued -= 1;
+        *this.deficit -= 1;
+
+        if queue.is_empty() {
+            let key = this.active.pop_front().unwrap();
+            this.queues.remove(&key);
+            *this.deficit = 0;
+        } else if *this.deficit == 0 {
+            this.active.rotate_left(1);
+        }
+
+        PollRecv::Ready(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (low, high) = self.stream.size_hint();
+        (
+            low + self.queued,
+            high.and_then(|high| high.checked_add(self.queued)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        mpsc,
+        sink::Sink,
+        stream::{Stream, TryRecvError},
+    };
+
+    #[test]
+    fn interleaves_keys() {
+        let (mut tx, rx) = mpsc::channel(8);
+        let mut rx = rx.fair_by_key(|(tenant, _): &(&str, usize)| *tenant, 1, 8);
+
+        for i in 0..4 {
+            tx.try_send(("hot", i)).unwrap();
+        }
+        tx.try_send(("cold", 0)).unwrap();
+        tx.try_send(("cold", 1)).unwrap();
+
+        assert_eq!(Ok(("hot", 0)), rx.try_recv());
+        assert_eq!(Ok(("cold", 0)), rx.try_recv());
+        assert_eq!(Ok(("hot", 1)), rx.try_recv());
+        assert_eq!(Ok(("cold", 1)), rx.try_recv());
+        assert_eq!(Ok(("hot", 2)), rx.try_recv());
+        assert_eq!(Ok(("hot", 3)), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Pending), rx.try_recv());
+    }
+
+    #[test]
+    fn quantum() {
+        let (mut tx, rx) = mpsc::channel(8);
+        let mut rx = rx.fair_by_key(|value: &usize| *value % 2, 2, 8);
+
+        for i in [0, 2, 4, 1, 3, 5] {
+            tx.try_send(i).unwrap();
+        }
+
+        for expected in [0, 2, 1, 3, 4, 5] {
+            assert_eq!(Ok(expected), rx.try_recv());
+        }
+    }
+
+    #[test]
+    fn lookahead_limits_buffering() {
+        let (mut tx, rx) = mpsc::channel(8);
+        let mut rx = rx.fair_by_key(|value: &usize| *value, 1, 2);
+
+        for i in 0..4 {
+            tx.try_send(i).unwrap();
+        }
+
+        assert_eq!(Ok(0), rx.try_recv());
+        assert_eq!(3, rx.size_hint().0);
+    }
+
+    #[test]
+    fn drains_after_close() {
+        let (mut tx, rx) = mpsc::channel(8);
+        let mut rx = rx.fair_by_key(|value: &usize| *value, 1, 8);
+
+        tx.try_send(1).unwrap();
+        tx.try_send(2).unwrap();
+        drop(tx);
+
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(Ok(2), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Closed), rx.try_recv());
+    }
+}
@@ -18,6 +18,23 @@ where
     }
 }
 
+impl<From, Filter> FilterStream<From, Filter> {
+    /// Returns a reference to the wrapped stream
+    pub fn get_ref(&self) -> &From {
+        &self.from
+    }
+
+    /// Returns a mutable reference to the wrapped stream
+    pub fn get_mut(&mut self) -> &mut From {
+        &mut self.from
+    }
+
+    /// Returns the wrapped stream
+    pub fn into_inner(self) -> From {
+        self.from
+    }
+}
+
 impl<From, Filter> Stream for FilterStream<From, Filter>
 where
     From: Stream + Unpin,
@@ -40,6 +57,10 @@ where
             }
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.from.size_hint().1)
+    }
 }
 
 #[cfg(test)]
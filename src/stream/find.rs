@@ -31,6 +31,23 @@ where
     }
 }
 
+impl<From, Condition> FindStream<From, Condition> {
+    /// Returns a reference to the wrapped stream
+    pub fn get_ref(&self) -> &From {
+        &self.from
+    }
+
+    /// Returns a mutable reference to the wrapped stream
+    pub fn get_mut(&mut self) -> &mut From {
+        &mut self.from
+    }
+
+    /// Returns the wrapped stream
+    pub fn into_inner(self) -> From {
+        self.from
+    }
+}
+
 impl<From, Condition> Stream for FindStream<From, Condition>
 where
     From: Stream + Unpin,
@@ -60,6 +77,20 @@ where
             }
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.state.load(Ordering::Acquire) {
+            State::Reading => (
+                0,
+                self.from
+                    .size_hint()
+                    .1
+                    .map(|upper| upper.min(1))
+                    .or(Some(1)),
+            ),
+            State::Closed => (0, Some(0)),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,52 @@
+use std::pin::Pin;
+
+use crate::{
+    stream::{PollRecv, Stream},
+    Context,
+};
+
+pub struct IterStream<I> {
+    iter: I,
+}
+
+impl<I> IterStream<I>
+where
+    I: Iterator,
+{
+    pub fn new(iter: I) -> Self {
+        Self { iter }
+    }
+}
+
+impl<I> Stream for IterStream<I>
+where
+    I: Iterator + Unpin,
+{
+    type Item = I::Item;
+
+    fn poll_recv(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> PollRecv<Self::Item> {
+        match self.get_mut().iter.next() {
+            Some(value) => PollRecv::Ready(value),
+            None => PollRecv::Closed,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stream::{iter, Stream, TryRecvError};
+
+    #[test]
+    fn produces_items() {
+        let mut stream = iter(vec![1, 2]);
+
+        assert_eq!((2, Some(2)), stream.size_hint());
+        assert_eq!(Ok(1), stream.try_recv());
+        assert_eq!(Ok(2), stream.try_recv());
+        assert_eq!(Err(TryRecvError::Closed), stream.try_recv());
+    }
+}
@@ -27,6 +27,23 @@ where
     }
 }
 
+impl<From, Map, Into> MapStream<From, Map, Into> {
+    /// Returns a reference to the wrapped stream
+    pub fn get_ref(&self) -> &From {
+        &self.from
+    }
+
+    /// Returns a mutable reference to the wrapped stream
+    pub fn get_mut(&mut self) -> &mut From {
+        &mut self.from
+    }
+
+    /// Returns the wrapped stream
+    pub fn into_inner(self) -> From {
+        self.from
+    }
+}
+
 impl<From, Map, Into> Stream for MapStream<From, Map, Into>
 where
     From: Stream,
@@ -43,6 +60,10 @@ where
             PollRecv::Closed => PollRecv::Closed,
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.from.size_hint()
+    }
 }
 
 #[cfg(test)]
@@ -90,4 +111,17 @@ mod tests {
 
         assert_eq!(PollRecv::Closed, Pin::new(&mut find).poll_recv(&mut cx));
     }
+
+    #[test]
+    fn accessors() {
+        let (tx, rx) = crate::mpsc::channel::<usize>(4);
+        let mut stream = rx.map(|i| i + 1).filter(|i| *i > 1);
+        assert!(!stream.get_ref().get_ref().is_closed());
+
+        drop(tx);
+        assert!(stream.get_mut().get_mut().is_closed());
+
+        let rx = stream.into_inner().into_inner();
+        assert!(rx.is_closed());
+    }
 }
@@ -0,0 +1,159 @@
+use std::{marker::PhantomData, pin::Pin};
+
+use crate::{
+    sink::{PollSend, Sink},
+    stream::{PollRecv, Stream},
+    Context,
+};
+use pin_project::pin_project;
+
+#[pin_project]
+pub struct MapOrDivertStream<From, Map, Into, Divert>
+where
+    Divert: Sink,
+{
+    #[pin]
+    from: From,
+    #[pin]
+    divert: Divert,
+
+    map: Map,
+    // an error which is waiting for the divert sink
+    diverted: Option<Divert::Item>,
+    into: PhantomData<Into>,
+}
+
+impl<From, Map, Into, Divert> MapOrDivertStream<From, Map, Into, Divert>
+where
+    From: Stream,
+    Map: Fn(From::Item) -> Result<Into, Divert::Item>,
+    Divert: Sink,
+{
+    pub fn new(from: From, map: Map, divert: Divert) -> Self {
+        Self {
+            from,
+            divert,
+            map,
+            diverted: None,
+            into: PhantomData,
+        }
+    }
+}
+
+impl<From, Map, Into, Divert> MapOrDivertStream<From, Map, Into, Divert>
+where
+    Divert: Sink,
+{
+    /// Returns a reference to the wrapped stream
+    pub fn get_ref(&self) -> &From {
+        &self.from
+    }
+
+    /// Returns a mutable reference to the wrapped stream
+    pub fn get_mut(&mut self) -> &mut From {
+        &mut self.from
+    }
+
+    /// Returns the wrapped stream and the divert sink
+    pub fn into_inner(self) -> (From, Divert) {
+        (self.from, self.divert)
+    }
+}
+
+impl<From, Map, Into, Divert> Stream for MapOrDivertStream<From, Map, Into, Divert>
+where
+    From: Stream,
+    Map: Fn(From::Item) -> Result<Into, Divert::Item>,
+    Divert: Sink,
+{
+    type Item = Into;
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item> {
+        let mut this = self.project();
+
+        loop {
+            // the stream waits while the divert sink is full
+            if let Some(error) = this.diverted.take() {
+                match this.divert.as_mut().poll_send(cx, error) {
+                    PollSend::Ready | PollSend::Rejected(_) => {}
+                    PollSend::Pending(error) => {
+                        *this.diverted = Some(error);
+                        return PollRecv::Pending;
+                    }
+                }
+            }
+
+            match this.from.as_mut().poll_recv(cx) {
+                PollRecv::Ready(value) => match (this.map)(value) {
+                    Ok(value) => return PollRecv::Ready(value),
+                    Err(error) => *this.diverted = Some(error),
+                },
+                PollRecv::Pending => return PollRecv::Pending,
+                PollRecv::Closed => return PollRecv::Closed,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        mpsc,
+        sink::Sink,
+        stream::{Stream, TryRecvError},
+    };
+
+    fn parse(value: &str) -> Result<usize, String> {
+        value.parse().map_err(|_| value.to_string())
+    }
+
+    #[test]
+    fn diverts_errors() {
+        let (mut tx, rx) = mpsc::channel(4);
+        let (err_tx, mut err_rx) = mpsc::channel(4);
+        let mut rx = rx.map_or_divert(parse, err_tx);
+
+        tx.try_send("1").unwrap();
+        tx.try_send("a").unwrap();
+        tx.try_send("2").unwrap();
+
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(Ok(2), rx.try_recv());
+        assert_eq!(Ok("a".to_string()), err_rx.try_recv());
+
+        drop(tx);
+        assert_eq!(Err(TryRecvError::Closed), rx.try_recv());
+    }
+
+    #[test]
+    fn waits_for_divert_sink() {
+        let (mut tx, rx) = mpsc::channel(4);
+        let (err_tx, mut err_rx) = mpsc::channel(1);
+        let mut rx = rx.map_or_divert(parse, err_tx);
+
+        tx.try_send("a").unwrap();
+        tx.try_send("b").unwrap();
+        tx.try_send("1").unwrap();
+        drop(tx);
+
+        // the second error waits for the first to be received
+        assert_eq!(Err(TryRecvError::Pending), rx.try_recv());
+        assert_eq!(Ok("a".to_string()), err_rx.try_recv());
+
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(Ok("b".to_string()), err_rx.try_recv());
+        assert_eq!(Err(TryRecvError::Closed), rx.try_recv());
+    }
+
+    #[test]
+    fn closed_divert_sink() {
+        let (mut tx, rx) = mpsc::channel(4);
+        let (err_tx, err_rx) = mpsc::channel(4);
+        let mut rx = rx.map_or_divert(parse, err_tx);
+        drop(err_rx);
+
+        tx.try_send("a").unwrap();
+        tx.try_send("1").unwrap();
+        assert_eq!(Ok(1), rx.try_recv());
+    }
+}
@@ -2,7 +2,10 @@ use crate::stream::{PollRecv, Stream};
 use pin_project::pin_project;
 use std::pin::Pin;
 
-use crate::Context;
+use crate::{
+    select::{Fairness, Scheduler},
+    Context,
+};
 #[derive(Copy, Clone)]
 enum State {
     Left,
@@ -21,6 +24,7 @@ impl State {
 #[pin_project]
 pub struct MergeStream<Left, Right> {
     state: State,
+    scheduler: Scheduler,
     #[pin]
     left: Left,
     #[pin]
@@ -33,14 +37,36 @@ where
     Right: Stream<Item = Left::Item>,
 {
     pub fn new(left: Left, right: Right) -> Self {
+        Self::with_fairness(left, right, Fairness::Alternate)
+    }
+
+    pub fn with_fairness(left: Left, right: Right, fairness: Fairness) -> Self {
         Self {
             state: State::Left,
+            scheduler: Scheduler::new(fairness),
             left,
             right,
         }
     }
 }
 
+impl<Left, Right> MergeStream<Left, Right> {
+    /// Returns references to the merged streams
+    pub fn get_ref(&self) -> (&Left, &Right) {
+        (&self.left, &self.right)
+    }
+
+    /// Returns mutable references to the merged streams
+    pub fn get_mut(&mut self) -> (&mut Left, &mut Right) {
+        (&mut self.left, &mut self.right)
+    }
+
+    /// Returns the merged streams
+    pub fn into_inner(self) -> (Left, Right) {
+        (self.left, self.right)
+    }
+}
+
 impl<Left, Right> Stream for MergeStream<Left, Right>
 where
     Left: Stream,
@@ -51,6 +77,14 @@ where
     fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item> {
         let this = self.project();
 
+        // the alternating merge swaps sides after each poll, unless the second side has closed
+        if *this.scheduler.fairness() != Fairness::Alternate {
+            *this.state = match this.scheduler.start(2) {
+                0 => State::Left,
+                _ => State::Right,
+            };
+        }
+
         let poll = match this.state {
             State::Left => poll(this.left, this.right, cx),
             State::Right => poll(this.right, this.left, cx),
@@ -62,6 +96,10 @@ where
 
         poll.into_recv()
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        crate::stream::add_hints(self.left.size_hint(), self.right.size_hint())
+    }
 }
 
 enum MergePoll<T> {
@@ -105,6 +143,7 @@ mod tests {
 
     use crate::test::stream::*;
     use crate::{
+        select::Fairness,
         stream::{PollRecv, Stream},
         Context,
     };
@@ -179,6 +218,35 @@ mod tests {
         assert_eq!(PollRecv::Closed, Pin::new(&mut find).poll_recv(&mut cx));
     }
 
+    #[test]
+    fn prioritized() {
+        let left = from_poll_iter(vec![PollRecv::Ready(1), PollRecv::Ready(2)]);
+        let right = from_poll_iter(vec![PollRecv::Ready(3)]);
+        let mut merge = MergeStream::with_fairness(left, right, Fairness::Prioritized);
+
+        let mut cx = Context::empty();
+
+        assert_eq!(PollRecv::Ready(1), Pin::new(&mut merge).poll_recv(&mut cx));
+        assert_eq!(PollRecv::Ready(2), Pin::new(&mut merge).poll_recv(&mut cx));
+        assert_eq!(PollRecv::Ready(3), Pin::new(&mut merge).poll_recv(&mut cx));
+        assert_eq!(PollRecv::Closed, Pin::new(&mut merge).poll_recv(&mut cx));
+    }
+
+    #[test]
+    fn weighted() {
+        let left = from_poll_iter(vec![PollRecv::Ready(1), PollRecv::Ready(2)]);
+        let right = from_poll_iter(vec![PollRecv::Ready(3), PollRecv::Ready(4)]);
+        let fairness = Fairness::WeightedRoundRobin(vec![2, 1]);
+        let mut merge = MergeStream::with_fairness(left, right, fairness);
+
+        let mut cx = Context::empty();
+
+        assert_eq!(PollRecv::Ready(1), Pin::new(&mut merge).poll_recv(&mut cx));
+        assert_eq!(PollRecv::Ready(2), Pin::new(&mut merge).poll_recv(&mut cx));
+        assert_eq!(PollRecv::Ready(3), Pin::new(&mut merge).poll_recv(&mut cx));
+        assert_eq!(PollRecv::Ready(4), Pin::new(&mut merge).poll_recv(&mut cx));
+    }
+
     #[test]
     fn pending_uses_left() {
         let left = from_poll_iter(vec![PollRecv::Ready(1)]);
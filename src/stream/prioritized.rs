@@ -0,0 +1,208 @@
+use std::{fmt, ops::Range, pin::Pin};
+
+use crate::{
+    stream::{PollRecv, Stream},
+    Context,
+};
+
+/// A stream which merges several streams by priority, created by [merge_prioritized](./fn.merge_prioritized.html).
+///
+/// Streams are polled in priority order, and a lower priority stream is only polled when every higher priority stream is pending.
+/// The stream closes when every stream has closed.
+pub struct PrioritizedStream<S> {
+    streams: Vec<S>,
+    closed: Vec<bool>,
+    max_burst: Option<usize>,
+    // the priority of the last delivered message, and the number of consecutive messages it has delivered
+    served: usize,
+    burst: usize,
+}
+
+impl<S> PrioritizedStream<S>
+where
+    S: Stream + Unpin,
+{
+    pub(crate) fn new(streams: Vec<S>) -> Self {
+        let closed = vec![false; streams.len()];
+
+        Self {
+            streams,
+            closed,
+            max_burst: None,
+            served: 0,
+            burst: 0,
+        }
+    }
+
+    /// Limits the number of consecutive messages delivered from one stream, while lower priority streams may be waiting.
+    ///
+    /// After `max_burst` consecutive messages, the next receive checks the lower priority streams first.
+    /// If they are all pending, the higher priority stream continues.  By default, higher priority streams can starve lower priority streams.
+    ///
+    /// Panics if `max_burst` is zero.
+    pub fn max_burst(mut self, max_burst: usize) -> Self {
+        assert!(max_burst > 0, "the max burst must be non-zero");
+        self.max_burst = Some(max_burst);
+        self
+    }
+
+    /// The number of streams which have not closed
+    pub fn open(&self) -> usize {
+        self.closed.iter().filter(|closed| !**closed).count()
+    }
+
+    fn poll_range(
+        &mut self,
+        range: Range<usize>,
+        cx: &mut Context<'_>,
+    ) -> Option<(usize, S::Item)> {
+        for index in range {
+            if self.closed[index] {
+                continue;
+            }
+
+            match Pin::new(&mut self.streams[index]).poll_recv(cx) {
+                PollRecv::Ready(value) => return Some((index, value)),
+                PollRecv::Pending => {}
+                PollRecv::Closed => self.closed[index] = true,
+            }
+        }
+
+        None
+    }
+}
+
+impl<S> PrioritizedStream<S> {
+    /// Returns references to the wrapped streams, in priority order
+    pub fn get_ref(&self) -> &[S] {
+        &self.streams
+    }
+
+    /// Returns mutable references to the wrapped streams, in priority order
+    pub fn get_mut(&mut self) -> &mut [S] {
+        &mut self.streams
+    }
+
+    /// Returns the wrapped streams, in priority order
+    pub fn into_inner(self) -> Vec<S> {
+        self.streams
+    }
+}
+
+impl<S> Stream for PrioritizedStream<S>
+where
+    S: Stream + Unpin,
+{
+    type Item = S::Item;
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item> {
+        let this = self.get_mut();
+
+        let len = this.streams.len();
+        let starving = this.max_burst.is_some_and(|max| this.burst >= max);
+
+        // a starving poll checks the lower priority streams first, and then the streams which were skipped
+        let ready = if starving {
+            let lower = (this.served + 1).min(len);
+            this.poll_range(lower..len, cx)
+                .or_else(|| this.poll_range(0..lower, cx))
+        } else {
+            this.poll_range(0..len, cx)
+        };
+
+        match ready {
+            Some((index, value)) => {
+                if index == this.served {
+                    this.burst += 1;
+                } else {
+                    this.served = index;
+                    this.burst = 1;
+                }
+
+                PollRecv::Ready(value)
+            }
+            None if this.open() == 0 => PollRecv::Closed,
+            None => PollRecv::Pending,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.streams
+            .iter()
+            .map(Stream::size_hint)
+            .fold((0, Some(0)), crate::stream::add_hints)
+    }
+}
+
+impl<S> fmt::Debug for PrioritizedStream<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrioritizedStream")
+            .field("streams", &self.streams.len())
+            .field("closed", &self.closed)
+            .field("max_burst", &self.max_burst)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        mpsc,
+        sink::Sink,
+        stream::{merge_prioritized, Stream, TryRecvError},
+    };
+
+    #[test]
+    fn drains_higher_priority_first() {
+        let (mut high_tx, high) = mpsc::channel(4);
+        let (mut low_tx, low) = mpsc::channel(4);
+        let mut rx = merge_prioritized([high, low]);
+
+        low_tx.try_send(10usize).unwrap();
+        high_tx.try_send(1).unwrap();
+        high_tx.try_send(2).unwrap();
+
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(Ok(2), rx.try_recv());
+        assert_eq!(Ok(10), rx.try_recv());
+
+        low_tx.try_send(11).unwrap();
+        high_tx.try_send(3).unwrap();
+        assert_eq!(Ok(3), rx.try_recv());
+        assert_eq!(Ok(11), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Pending), rx.try_recv());
+    }
+
+    #[test]
+    fn max_burst() {
+        let (mut high_tx, high) = mpsc::channel(8);
+        let (mut medium_tx, medium) = mpsc::channel(8);
+        let (mut low_tx, low) = mpsc::channel(8);
+        let mut rx = merge_prioritized([high, medium, low]).max_burst(2);
+
+        for i in 0..5usize {
+            high_tx.try_send(i).unwrap();
+        }
+        medium_tx.try_send(10).unwrap();
+        low_tx.try_send(20).unwrap();
+
+        for expected in [0, 1, 10, 2, 3, 20, 4] {
+            assert_eq!(Ok(expected), rx.try_recv());
+        }
+    }
+
+    #[test]
+    fn closes_when_all_close() {
+        let (high_tx, high) = mpsc::channel::<usize>(4);
+        let (mut low_tx, low) = mpsc::channel(4);
+        let mut rx = merge_prioritized([high, low]);
+
+        drop(high_tx);
+        low_tx.try_send(1).unwrap();
+        assert_eq!(Ok(1), rx.try_recv());
+        assert_eq!(1, rx.open());
+
+        drop(low_tx);
+        assert_eq!(Err(TryRecvError::Closed), rx.try_recv());
+    }
+}
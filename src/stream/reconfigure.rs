@@ -0,0 +1,175 @@
+use std::{fmt, pin::Pin, sync::Arc};
+
+use parking_lot::Mutex;
+use pin_project::pin_project;
+
+use crate::{
+    stream::{PollRecv, Stream},
+    watch, Context,
+};
+
+/// A handle to the stream upstream of a [reconfigure](./trait.Stream.html#method.reconfigure) adapter.
+///
+/// The handle shares the upstream stream, so the adapter can be dropped and rebuilt without closing the stream.
+pub struct Upstream<S> {
+    stream: Arc<Mutex<Pin<Box<S>>>>,
+}
+
+impl<S> Stream for Upstream<S>
+where
+    S: Stream,
+{
+    type Item = S::Item;
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item> {
+        self.stream.lock().as_mut().poll_recv(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.lock().size_hint()
+    }
+}
+
+impl<S> fmt::Debug for Upstream<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Upstream").finish()
+    }
+}
+
+#[pin_project]
+pub struct ReconfigureStream<S, C, F, A> {
+    upstream: Arc<Mutex<Pin<Box<S>>>>,
+    config: watch::Receiver<C>,
+    config_closed: bool,
+    build: F,
+    adapter: Option<Pin<Box<A>>>,
+}
+
+impl<S, C, F, A> ReconfigureStream<S, C, F, A>
+where
+    S: Stream,
+    C: Clone,
+    F: FnMut(&C, Upstream<S>) -> A,
+    A: Stream,
+{
+    pub fn new(stream: S, config: watch::Receiver<C>, build: F) -> Self {
+        Self {
+            upstream: Arc::new(Mutex::new(Box::pin(stream))),
+            config,
+            config_closed: false,
+            build,
+            adapter: None,
+        }
+    }
+}
+
+impl<S, C, F, A> Stream for ReconfigureStream<S, C, F, A>
+where
+    S: Stream,
+    C: Clone,
+    F: FnMut(&C, Upstream<S>) -> A,
+    A: Stream,
+{
+    type Item = A::Item;
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item> {
+        let this = self.project();
+
+        // only the latest configuration is applied, if it changed several times between polls
+        let mut latest = None;
+        while !*this.config_closed {
+            match Pin::new(&mut *this.config).poll_recv(cx) {
+                PollRecv::Ready(config) => latest = Some(config),
+                PollRecv::Pending => break,
+                PollRecv::Closed => *this.config_closed = true,
+            }
+        }
+
+        if let Some(config) = latest {
+            let upstream = Upstream {
+                stream: this.upstream.clone(),
+            };
+
+            *this.adapter = Some(Box::pin((this.build)(&config, upstream)));
+        }
+
+        match this.adapter {
+            Some(adapter) => adapter.as_mut().poll_recv(cx),
+            // the watch channel closed before it had a value, so the adapter can never be built
+            None if *this.config_closed => PollRecv::Closed,
+            None => PollRecv::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        mpsc,
+        sink::Sink,
+        stream::{Stream, TryRecvError},
+        watch,
+    };
+
+    #[test]
+    fn rebuilds_on_change() {
+        let (mut tx, rx) = mpsc::channel(8);
+        let (mut config_tx, config_rx) = watch::channel_with(2usize);
+        let mut rx = rx.reconfigure(config_rx, |factor, upstream| {
+            let factor = *factor;
+            upstream.map(move |value: usize| value * factor)
+        });
+
+        tx.try_send(1).unwrap();
+        tx.try_send(2).unwrap();
+        assert_eq!(Ok(2), rx.try_recv());
+
+        config_tx.try_send(10).unwrap();
+        assert_eq!(Ok(20), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Pending), rx.try_recv());
+    }
+
+    #[test]
+    fn keeps_adapter_after_config_closes() {
+        let (mut tx, rx) = mpsc::channel(8);
+        let (config_tx, config_rx) = watch::channel_with(3usize);
+        let mut rx = rx.reconfigure(config_rx, |threshold, upstream| {
+            let threshold = *threshold;
+            upstream.filter(move |value: &usize| *value >= threshold)
+        });
+
+        drop(config_tx);
+        tx.try_send(1).unwrap();
+        tx.try_send(5).unwrap();
+        assert_eq!(Ok(5), rx.try_recv());
+
+        drop(tx);
+        assert_eq!(Err(TryRecvError::Closed), rx.try_recv());
+    }
+
+    #[test]
+    fn waits_for_first_config() {
+        let (mut tx, rx) = mpsc::channel(8);
+        let (mut config_tx, config_rx) = watch::channel_empty();
+        let mut rx = rx.reconfigure(config_rx, |limit: &usize, upstream| {
+            let limit = *limit;
+            upstream.filter(move |value: &usize| *value < limit)
+        });
+
+        tx.try_send(1).unwrap();
+        assert_eq!(Err(TryRecvError::Pending), rx.try_recv());
+
+        config_tx.try_send(2).unwrap();
+        assert_eq!(Ok(1), rx.try_recv());
+    }
+
+    #[test]
+    fn closed_before_first_config() {
+        let (_tx, rx) = mpsc::channel::<usize>(8);
+        let (config_tx, config_rx) = watch::channel_empty::<usize>();
+        let mut rx = rx.reconfigure(config_rx, |_, upstream| upstream);
+
+        drop(config_tx);
+        assert_eq!(Err(TryRecvError::Closed), rx.try_recv());
+    }
+}
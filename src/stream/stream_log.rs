@@ -27,6 +27,23 @@ where
     }
 }
 
+impl<S> StreamLog<S> {
+    /// Returns a reference to the wrapped stream
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Returns a mutable reference to the wrapped stream
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Returns the wrapped stream
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
 impl<S> Stream for StreamLog<S>
 where
     S: Stream,
@@ -45,6 +62,10 @@ where
             PollRecv::Closed => PollRecv::Closed,
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
 }
 
 #[cfg(test)]
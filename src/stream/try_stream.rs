@@ -0,0 +1,416 @@
+use std::{future::Future, pin::Pin, task::Poll};
+
+use crate::stream::{PollRecv, Stream};
+use crate::Context;
+use pin_project::pin_project;
+
+/// Combinators for streams of `Result<T, E>` items.
+///
+/// Items are passed to the `Ok` value, and errors are forwarded unchanged.
+/// The consuming combinators (`try_for_each`, `try_fold`, and `try_collect`) stop at the first error.
+///
+/// ```rust
+/// use postage::{mpsc, prelude::*, stream::TryStream};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let (mut tx, rx) = mpsc::channel(4);
+///     tx.send(Ok(1usize)).await.ok();
+///     tx.send(Err("bad message")).await.ok();
+///     tx.send(Ok(2usize)).await.ok();
+///     drop(tx);
+///
+///     let mut rx = rx.map_err(|e| e.to_string());
+///     let collected: Result<Vec<usize>, String> = rx.try_collect().await;
+///     assert_eq!(Err("bad message".to_string()), collected);
+/// }
+/// ```
+pub trait TryStream<T, E>: Stream<Item = Result<T, E>> {
+    /// Filters the `Ok` values, forwarding them if the filter returns true.  Errors are always forwarded.
+    fn try_filter<Filter>(self, filter: Filter) -> TryFilterStream<Self, Filter>
+    where
+        Filter: FnMut(&T) -> bool,
+        Self: Sized,
+    {
+        TryFilterStream { from: self, filter }
+    }
+
+    /// Transforms the errors produced by the stream.
+    fn map_err<Map, To>(self, map: Map) -> MapErrStream<Self, Map>
+    where
+        Map: FnMut(E) -> To,
+        Self: Sized,
+    {
+        MapErrStream { from: self, map }
+    }
+
+    /// Converts the errors produced by the stream with `Into`.
+    fn err_into<To>(self) -> MapErrStream<Self, fn(E) -> To>
+    where
+        E: Into<To>,
+        Self: Sized,
+    {
+        MapErrStream {
+            from: self,
+            map: E::into,
+        }
+    }
+
+    /// Calls the function with each `Ok` value, until the stream closes or produces an error.
+    fn try_for_each<F>(&mut self, f: F) -> TryForEachFuture<'_, Self, F>
+    where
+        F: FnMut(T) -> Result<(), E>,
+        Self: Unpin,
+    {
+        TryForEachFuture { stream: self, f }
+    }
+
+    /// Folds the `Ok` values into an accumulator, until the stream closes or produces an error.
+    fn try_fold<Acc, F>(&mut self, init: Acc, f: F) -> TryFoldFuture<'_, Self, Acc, F>
+    where
+        F: FnMut(Acc, T) -> Result<Acc, E>,
+        Self: Unpin,
+    {
+        TryFoldFuture {
+            stream: self,
+            acc: Some(init),
+            f,
+        }
+    }
+
+    /// Collects the `Ok` values, until the stream closes or produces an error.
+    fn try_collect<C>(&mut self) -> TryCollectFuture<'_, Self, C>
+    where
+        C: Default + Extend<T>,
+        Self: Unpin,
+    {
+        TryCollectFuture {
+            stream: self,
+            collection: Some(C::default()),
+        }
+    }
+}
+
+impl<S, T, E> TryStream<T, E> for S where S: Stream<Item = Result<T, E>> + ?Sized {}
+
+/// A stream returned by `TryStream::try_filter`.
+#[pin_project]
+pub struct TryFilterStream<From, Filter> {
+    #[pin]
+    from: From,
+    filter: Filter,
+}
+
+impl<From, Filter> TryFilterStream<From, Filter> {
+    /// Returns a reference to the wrapped stream
+    pub fn get_ref(&self) -> &From {
+        &self.from
+    }
+
+    /// Returns a mutable reference to the wrapped stream
+    pub fn get_mut(&mut self) -> &mut From {
+        &mut self.from
+    }
+
+    /// Returns the wrapped stream
+    pub fn into_inner(self) -> From {
+        self.from
+    }
+}
+
+impl<From, Filter, T, E> Stream for TryFilterStream<From, Filter>
+where
+    From: Stream<Item = Result<T, E>>,
+    Filter: FnMut(&T) -> bool,
+{
+    type Item = Result<T, E>;
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item> {
+        let mut this = self.project();
+
+        loop {
+            match this.from.as_mut().poll_recv(cx) {
+                PollRecv::Ready(Ok(value)) => {
+                    if (this.filter)(&value) {
+                        return PollRecv::Ready(Ok(value));
+                    }
+                }
+                PollRecv::Ready(Err(e)) => return PollRecv::Ready(Err(e)),
+                PollRecv::Pending => return PollRecv::Pending,
+                PollRecv::Closed => return PollRecv::Closed,
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.from.size_hint().1)
+    }
+}
+
+/// A stream returned by `TryStream::map_err` and `TryStream::err_into`.
+#[pin_project]
+pub struct MapErrStream<From, Map> {
+    #[pin]
+    from: From,
+    map: Map,
+}
+
+impl<From, Map> MapErrStream<From, Map> {
+    /// Returns a reference to the wrapped stream
+    pub fn get_ref(&self) -> &From {
+        &self.from
+    }
+
+    /// Returns a mutable reference to the wrapped stream
+    pub fn get_mut(&mut self) -> &mut From {
+        &mut self.from
+    }
+
+    /// Returns the wrapped stream
+    pub fn into_inner(self) -> From {
+        self.from
+    }
+}
+
+impl<From, Map, T, E, To> Stream for MapErrStream<From, Map>
+where
+    From: Stream<Item = Result<T, E>>,
+    Map: FnMut(E) -> To,
+{
+    type Item = Result<T, To>;
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item> {
+        let this = self.project();
+
+        match this.from.poll_recv(cx) {
+            PollRecv::Ready(result) => PollRecv::Ready(result.map_err(this.map)),
+            PollRecv::Pending => PollRecv::Pending,
+            PollRecv::Closed => PollRecv::Closed,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.from.size_hint()
+    }
+}
+
+/// A future returned by `TryStream::try_for_each`.
+#[must_use = "futures do nothing unless polled"]
+pub struct TryForEachFuture<'s, S: ?Sized, F> {
+    stream: &'s mut S,
+    f: F,
+}
+
+impl<'s, S, F, T, E> Future for TryForEachFuture<'s, S, F>
+where
+    S: Stream<Item = Result<T, E>> + Unpin + ?Sized,
+    F: FnMut(T) -> Result<(), E> + Unpin,
+{
+    type Output = Result<(), E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut cx: crate::Context<'_> = cx.into();
+
+        loop {
+            match Pin::new(&mut *this.stream).poll_recv(&mut cx) {
+                PollRecv::Ready(Ok(value)) => {
+                    if let Err(e) = (this.f)(value) {
+                        return Poll::Ready(Err(e));
+                    }
+                }
+                PollRecv::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                PollRecv::Pending => return Poll::Pending,
+                PollRecv::Closed => return Poll::Ready(Ok(())),
+            }
+        }
+    }
+}
+
+/// A future returned by `TryStream::try_fold`.
+#[must_use = "futures do nothing unless polled"]
+pub struct TryFoldFuture<'s, S: ?Sized, Acc, F> {
+    stream: &'s mut S,
+    acc: Option<Acc>,
+    f: F,
+}
+
+impl<'s, S, Acc, F, T, E> Future for TryFoldFuture<'s, S, Acc, F>
+where
+    S: Stream<Item = Result<T, E>> + Unpin + ?Sized,
+    F: FnMut(Acc, T) -> Result<Acc, E> + Unpin,
+    Acc: Unpin,
+{
+    type Output = Result<Acc, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut cx: crate::Context<'_> = cx.into();
+
+        loop {
+            match Pin::new(&mut *this.stream).poll_recv(&mut cx) {
+                PollRecv::Ready(Ok(value)) => {
+                    let acc = this
+                        .acc
+                        .take()
+                        .expect("TryFoldFuture polled after completion");
+                    this.acc = Some((this.f)(acc, value)?);
+                }
+                PollRecv::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                PollRecv::Pending => return Poll::Pending,
+                PollRecv::Closed => {
+                    let acc = this
+                        .acc
+                        .take()
+                        .expect("TryFoldFuture polled after completion");
+                    return Poll::Ready(Ok(acc));
+                }
+            }
+        }
+    }
+}
+
+/// A future returned by `TryStream::try_collect`.
+#[must_use = "futures do nothing unless polled"]
+pub struct TryCollectFuture<'s, S: ?Sized, C> {
+    stream: &'s mut S,
+    collection: Option<C>,
+}
+
+impl<'s, S, C, T, E> Future for TryCollectFuture<'s, S, C>
+where
+    S: Stream<Item = Result<T, E>> + Unpin + ?Sized,
+    C: Extend<T> + Unpin,
+{
+    type Output = Result<C, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut cx: crate::Context<'_> = cx.into();
+
+        loop {
+            match Pin::new(&mut *this.stream).poll_recv(&mut cx) {
+                PollRecv::Ready(Ok(value)) => {
+                    let collection = this
+                        .collection
+                        .as_mut()
+                        .expect("TryCollectFuture polled after completion");
+                    collection.extend(Some(value));
+                }
+                PollRecv::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                PollRecv::Pending => return Poll::Pending,
+                PollRecv::Closed => {
+                    let collection = this
+                        .collection
+                        .take()
+                        .expect("TryCollectFuture polled after completion");
+                    return Poll::Ready(Ok(collection));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+
+    use super::TryStream;
+    use crate::test::stream::*;
+    use crate::{
+        stream::{PollRecv, Stream},
+        Context,
+    };
+
+    #[test]
+    fn try_filter() {
+        let source = from_iter(vec![Ok(1usize), Err("e"), Ok(2usize), Ok(3usize)]);
+        let mut filter = source.try_filter(|value| value % 2 == 1);
+
+        let mut cx = Context::empty();
+        assert_eq!(
+            PollRecv::Ready(Ok(1)),
+            Pin::new(&mut filter).poll_recv(&mut cx)
+        );
+        assert_eq!(
+            PollRecv::Ready(Err("e")),
+            Pin::new(&mut filter).poll_recv(&mut cx)
+        );
+        assert_eq!(
+            PollRecv::Ready(Ok(3)),
+            Pin::new(&mut filter).poll_recv(&mut cx)
+        );
+        assert_eq!(PollRecv::Closed, Pin::new(&mut filter).poll_recv(&mut cx));
+    }
+
+    #[test]
+    fn map_err() {
+        let source = from_iter(vec![Ok(1usize), Err(2usize)]);
+        let mut map = source.map_err(|e| e * 10);
+
+        let mut cx = Context::empty();
+        assert_eq!(
+            PollRecv::Ready(Ok(1)),
+            Pin::new(&mut map).poll_recv(&mut cx)
+        );
+        assert_eq!(
+            PollRecv::Ready(Err(20)),
+            Pin::new(&mut map).poll_recv(&mut cx)
+        );
+    }
+
+    #[test]
+    fn err_into() {
+        let source = from_iter(vec![Err::<usize, u8>(1)]);
+        let mut map = source.err_into::<u64>();
+
+        let mut cx = Context::empty();
+        assert_eq!(
+            PollRecv::Ready(Err(1u64)),
+            Pin::new(&mut map).poll_recv(&mut cx)
+        );
+    }
+
+    #[tokio::test]
+    async fn try_for_each() {
+        let mut seen = Vec::new();
+        let mut source = from_iter(vec![Ok(1usize), Ok(2usize)]);
+        let result: Result<(), ()> = source
+            .try_for_each(|value| {
+                seen.push(value);
+                Ok(())
+            })
+            .await;
+        assert_eq!(Ok(()), result);
+        assert_eq!(vec![1, 2], seen);
+
+        let mut source = from_iter(vec![Ok(1usize), Ok(2usize), Ok(3usize)]);
+        let result = source
+            .try_for_each(|value| if value == 2 { Err(value) } else { Ok(()) })
+            .await;
+        assert_eq!(Err(2), result);
+        assert_eq!(Ok(Ok(3)), source.try_recv());
+    }
+
+    #[tokio::test]
+    async fn try_fold() {
+        let mut source = from_iter(vec![Ok(1usize), Ok(2usize), Ok(3usize)]);
+        let sum: Result<usize, ()> = source.try_fold(0, |acc, value| Ok(acc + value)).await;
+        assert_eq!(Ok(6), sum);
+
+        let mut source = from_iter(vec![Ok(1usize), Err("e"), Ok(3usize)]);
+        let sum = source.try_fold(0, |acc, value| Ok(acc + value)).await;
+        assert_eq!(Err("e"), sum);
+    }
+
+    #[tokio::test]
+    async fn try_collect() {
+        let mut source = from_iter(vec![Ok::<_, ()>(1usize), Ok(2usize)]);
+        let collected: Result<Vec<usize>, ()> = source.try_collect().await;
+        assert_eq!(Ok(vec![1, 2]), collected);
+
+        let mut source = from_iter(vec![Ok(1usize), Err("e")]);
+        let collected: Result<Vec<usize>, &str> = source.try_collect().await;
+        assert_eq!(Err("e"), collected);
+    }
+}
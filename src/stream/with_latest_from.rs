@@ -0,0 +1,130 @@
+use std::pin::Pin;
+
+use pin_project::pin_project;
+
+use crate::{
+    stream::{PollRecv, Stream},
+    watch, Context,
+};
+
+#[pin_project]
+pub struct WithLatestFromStream<S: Stream, W> {
+    #[pin]
+    stream: S,
+    watch: watch::Receiver<W>,
+    held: Option<S::Item>,
+}
+
+impl<S, W> WithLatestFromStream<S, W>
+where
+    S: Stream,
+    W: Clone,
+{
+    pub fn new(stream: S, watch: watch::Receiver<W>) -> Self {
+        Self {
+            stream,
+            watch,
+            held: None,
+        }
+    }
+}
+
+impl<S: Stream, W> WithLatestFromStream<S, W> {
+    /// Returns a reference to the wrapped stream
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Returns a mutable reference to the wrapped stream
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Returns the wrapped stream
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S, W> Stream for WithLatestFromStream<S, W>
+where
+    S: Stream,
+    W: Clone,
+{
+    type Item = (S::Item, W);
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item> {
+        let this = self.project();
+
+        if this.held.is_none() {
+            match this.stream.poll_recv(cx) {
+                PollRecv::Ready(value) => *this.held = Some(value),
+                PollRecv::Pending => return PollRecv::Pending,
+                PollRecv::Closed => return PollRecv::Closed,
+            }
+        }
+
+        if let Some(latest) = this.watch.try_borrow() {
+            let latest = latest.clone();
+            return PollRecv::Ready((this.held.take().unwrap(), latest));
+        }
+
+        // the watch channel was created empty, so the item waits for the first value
+        match Pin::new(this.watch).poll_recv(cx) {
+            PollRecv::Ready(latest) => PollRecv::Ready((this.held.take().unwrap(), latest)),
+            PollRecv::Pending => PollRecv::Pending,
+            PollRecv::Closed => PollRecv::Closed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        mpsc,
+        sink::Sink,
+        stream::{Stream, TryRecvError},
+        watch,
+    };
+
+    #[test]
+    fn pairs_latest() {
+        let (mut tx, rx) = mpsc::channel(4);
+        let (mut config_tx, config_rx) = watch::channel_with(1usize);
+        let mut rx = rx.with_latest_from(config_rx);
+
+        tx.try_send("a").unwrap();
+        assert_eq!(Ok(("a", 1)), rx.try_recv());
+
+        config_tx.try_send(2).unwrap();
+        config_tx.try_send(3).unwrap();
+        tx.try_send("b").unwrap();
+        tx.try_send("c").unwrap();
+        assert_eq!(Ok(("b", 3)), rx.try_recv());
+        assert_eq!(Ok(("c", 3)), rx.try_recv());
+        assert_eq!(Err(TryRecvError::Pending), rx.try_recv());
+    }
+
+    #[test]
+    fn waits_for_first_value() {
+        let (mut tx, rx) = mpsc::channel(4);
+        let (mut config_tx, config_rx) = watch::channel_empty::<usize>();
+        let mut rx = rx.with_latest_from(config_rx);
+
+        tx.try_send("a").unwrap();
+        assert_eq!(Err(TryRecvError::Pending), rx.try_recv());
+
+        config_tx.try_send(1).unwrap();
+        assert_eq!(Ok(("a", 1)), rx.try_recv());
+    }
+
+    #[test]
+    fn closes_with_stream() {
+        let (tx, rx) = mpsc::channel::<usize>(4);
+        let (_config_tx, config_rx) = watch::channel_with(1usize);
+        let mut rx = rx.with_latest_from(config_rx);
+
+        drop(tx);
+        assert_eq!(Err(TryRecvError::Closed), rx.try_recv());
+    }
+}
@@ -1,52 +1,91 @@
-use std::sync::Arc;
-
 use notifier::Notifier;
+use primitives::Arc;
 use ref_count::RefCount;
 use std::fmt::Debug;
 
-use crate::Context;
+use crate::{
+    metrics::MetricsHook, registry::Registration, stop::StopToken, trace::Tracer, Context,
+};
 
 use self::{notifier::NotificationGuard, ref_count::TryDecrement};
 
+pub(crate) mod envelope;
 pub mod mpmc_circular_buffer;
 pub mod notifier;
 mod oneshot_cell;
+pub(crate) mod primitives;
 mod ref_count;
 // mod rr_lock;
 mod state_cell;
 pub(crate) mod transfer;
 
-pub(crate) fn shared<E>(extension: E) -> (SenderShared<E>, ReceiverShared<E>) {
-    let inner = Arc::new(Shared::new(extension));
+pub(crate) fn shared<E>(
+    extension: E,
+    tracer: Tracer,
+    metrics: Option<MetricsHook>,
+    registration: Registration,
+    stop: Option<StopToken>,
+) -> (SenderShared<E>, ReceiverShared<E>) {
+    let inner = Arc::new(Shared::new(extension, tracer, metrics, registration, stop));
 
     let sender = SenderShared {
         inner: inner.clone(),
     };
 
-    let receiver = ReceiverShared { inner };
+    let receiver = ReceiverShared {
+        inner,
+        released: false,
+    };
 
     (sender, receiver)
 }
 
 #[derive(Debug)]
 pub struct Shared<E> {
-    sender_notify: Notifier,
+    // the notifiers are shared with the stop token, which wakes blocked tasks when it is stopped
+    sender_notify: std::sync::Arc<Notifier>,
     sender_count: RefCount,
-    receiver_notify: Notifier,
+    receiver_notify: std::sync::Arc<Notifier>,
     receiver_count: RefCount,
+    tracer: Tracer,
+    metrics: Option<MetricsHook>,
+    registration: Registration,
+    stop: Option<StopToken>,
     pub(crate) extension: E,
 }
 
 impl<E> Shared<E> {
-    pub fn new(extension: E) -> Self {
+    pub fn new(
+        extension: E,
+        tracer: Tracer,
+        metrics: Option<MetricsHook>,
+        registration: Registration,
+        stop: Option<StopToken>,
+    ) -> Self {
+        let sender_notify = std::sync::Arc::new(Notifier::new());
+        let receiver_notify = std::sync::Arc::new(Notifier::new());
+        if let Some(ref stop) = stop {
+            stop.register(&sender_notify, &receiver_notify);
+        }
+        registration.attach(&sender_notify, &receiver_notify);
+
         Self {
-            sender_notify: Notifier::new(),
+            sender_notify,
             sender_count: RefCount::new(1),
-            receiver_notify: Notifier::new(),
+            receiver_notify,
             receiver_count: RefCount::new(1),
+            tracer,
+            metrics,
+            registration,
+            stop,
             extension,
         }
     }
+
+    /// Returns true if the channel's stop token has been triggered
+    fn is_stopped(&self) -> bool {
+        self.stop.as_ref().is_some_and(StopToken::is_stopped)
+    }
 }
 
 pub struct SenderShared<E> {
@@ -58,6 +97,23 @@ impl<E> SenderShared<E> {
         &self.inner.extension
     }
 
+    pub fn tracer(&self) -> &Tracer {
+        &self.inner.tracer
+    }
+
+    pub fn metrics(&self) -> Option<&MetricsHook> {
+        self.inner.metrics.as_ref()
+    }
+
+    pub fn registration(&self) -> &Registration {
+        &self.inner.registration
+    }
+
+    /// The number of sender tasks waiting for capacity
+    pub fn blocked_senders(&self) -> usize {
+        self.inner.sender_notify.waiting()
+    }
+
     pub fn notify_receivers(&self) {
         self.inner.receiver_notify.notify();
     }
@@ -66,11 +122,16 @@ impl<E> SenderShared<E> {
         self.inner.sender_notify.notify();
     }
 
+    /// The notifier which wakes blocked senders, so another channel can wake them without holding a sender
+    pub fn notifier(&self) -> std::sync::Arc<Notifier> {
+        self.inner.sender_notify.clone()
+    }
+
     pub fn subscribe_recv(&self, cx: &Context<'_>) {
         self.inner.sender_notify.subscribe(cx);
     }
 
-    pub fn recv_guard(&self) -> NotificationGuard {
+    pub fn recv_guard(&self) -> NotificationGuard<'_> {
         self.inner.sender_notify.guard()
     }
 
@@ -78,16 +139,31 @@ impl<E> SenderShared<E> {
         self.inner.receiver_count.is_alive()
     }
 
+    /// The number of live receivers
+    pub fn receiver_count(&self) -> usize {
+        self.inner.receiver_count.count()
+    }
+
     pub fn clone_receiver(&self) -> ReceiverShared<E> {
         self.inner.receiver_count.increment();
+        self.inner.registration.receiver_added();
+
+        ReceiverShared {
+            inner: self.inner.clone(),
+            released: false,
+        }
+    }
 
+    /// Creates a receiver which takes over the count of a receiver which has been retired, and will be disowned
+    pub fn adopt_receiver(&self) -> ReceiverShared<E> {
         ReceiverShared {
             inner: self.inner.clone(),
+            released: false,
         }
     }
 
     pub fn is_closed(&self) -> bool {
-        !self.is_alive()
+        !self.is_alive() || self.inner.is_stopped()
     }
 }
 
@@ -104,6 +180,7 @@ impl<E> Clone for SenderShared<E> {
     fn clone(&self) -> Self {
         let inner = self.inner.clone();
         inner.sender_count.increment();
+        inner.registration.sender_added();
 
         Self { inner }
     }
@@ -111,9 +188,11 @@ impl<E> Clone for SenderShared<E> {
 
 impl<E> Drop for SenderShared<E> {
     fn drop(&mut self) {
+        self.inner.registration.sender_dropped();
         match self.inner.sender_count.decrement() {
             TryDecrement::Alive(_) => {}
             TryDecrement::Dead => {
+                self.inner.tracer.senders_closed();
                 self.notify_receivers();
             }
         }
@@ -122,6 +201,8 @@ impl<E> Drop for SenderShared<E> {
 
 pub struct ReceiverShared<E> {
     pub(crate) inner: Arc<Shared<E>>,
+    // set once the receiver's count has been released, or handed over to another receiver
+    released: bool,
 }
 
 impl<E> ReceiverShared<E> {
@@ -129,15 +210,41 @@ impl<E> ReceiverShared<E> {
         &self.inner.extension
     }
 
+    pub fn tracer(&self) -> &Tracer {
+        &self.inner.tracer
+    }
+
+    pub fn metrics(&self) -> Option<&MetricsHook> {
+        self.inner.metrics.as_ref()
+    }
+
+    pub fn registration(&self) -> &Registration {
+        &self.inner.registration
+    }
+
+    /// The number of sender tasks waiting for capacity
+    pub fn blocked_senders(&self) -> usize {
+        self.inner.sender_notify.waiting()
+    }
+
     pub fn notify_senders(&self) {
         self.inner.sender_notify.notify();
     }
 
+    pub fn notify_receivers(&self) {
+        self.inner.receiver_notify.notify();
+    }
+
+    /// The number of receiver tasks waiting for messages
+    pub fn waiting_receivers(&self) -> usize {
+        self.inner.receiver_notify.waiting()
+    }
+
     pub fn subscribe_send(&self, cx: &Context<'_>) {
         self.inner.receiver_notify.subscribe(cx);
     }
 
-    pub fn send_guard(&self) -> NotificationGuard {
+    pub fn send_guard(&self) -> NotificationGuard<'_> {
         self.inner.receiver_notify.guard()
     }
 
@@ -145,8 +252,34 @@ impl<E> ReceiverShared<E> {
         self.inner.sender_count.is_alive()
     }
 
+    /// The number of live receivers, including this one
+    pub fn receiver_count(&self) -> usize {
+        self.inner.receiver_count.count()
+    }
+
     pub fn is_closed(&self) -> bool {
-        !self.is_alive()
+        !self.is_alive() || self.inner.is_stopped()
+    }
+
+    /// Releases the receiver's count now, rather than when it is dropped.  Closes the channel if it was the last receiver.
+    pub fn release(&mut self) {
+        if std::mem::replace(&mut self.released, true) {
+            return;
+        }
+
+        self.inner.registration.receiver_dropped();
+        match self.inner.receiver_count.decrement() {
+            TryDecrement::Alive(_) => {}
+            TryDecrement::Dead => {
+                self.inner.tracer.receivers_closed();
+                self.notify_senders();
+            }
+        }
+    }
+
+    /// Hands the receiver's count over to the receiver which was adopted in its place, so it is not released on drop
+    pub fn disown(&mut self) {
+        self.released = true;
     }
 }
 
@@ -154,18 +287,17 @@ impl<E> Clone for ReceiverShared<E> {
     fn clone(&self) -> Self {
         let inner = self.inner.clone();
         inner.receiver_count.increment();
+        inner.registration.receiver_added();
 
-        Self { inner }
+        Self {
+            inner,
+            released: false,
+        }
     }
 }
 
 impl<E> Drop for ReceiverShared<E> {
     fn drop(&mut self) {
-        match self.inner.receiver_count.decrement() {
-            TryDecrement::Alive(_) => {}
-            TryDecrement::Dead => {
-                self.notify_senders();
-            }
-        }
+        self.release();
     }
 }
@@ -0,0 +1,32 @@
+use std::time::{Duration, Instant};
+
+/// A queued value, which is optionally stamped with the time it entered the queue.
+pub struct Envelope<T> {
+    value: T,
+    enqueued_at: Option<Instant>,
+}
+
+impl<T> Envelope<T> {
+    pub fn new(value: T, timestamp: bool) -> Self {
+        let enqueued_at = if timestamp {
+            Some(Instant::now())
+        } else {
+            None
+        };
+
+        Self { value, enqueued_at }
+    }
+
+    /// The time since the value was enqueued, if it was timestamped
+    pub fn age(&self) -> Option<Duration> {
+        self.enqueued_at.map(|instant| instant.elapsed())
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.value
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
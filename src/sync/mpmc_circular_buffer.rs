@@ -1,21 +1,27 @@
-use std::{cmp::max, sync::atomic::AtomicUsize};
+use std::cmp::max;
 
 use crate::Context;
-use atomic::Ordering;
-use parking_lot::{Mutex, RwLock};
 
-use super::notifier::Notifier;
+use super::{
+    notifier::Notifier,
+    primitives::{AtomicUsize, Mutex, Ordering, RwLock},
+};
 use std::fmt::Debug;
 
 // A lock-free multi-producer, multi-consumer circular buffer
 // Each reader will see each value created exactly once.
 // Cloned readers inherit the read location of the reader that was cloned.
+// If overwrite is enabled, writers overwrite unread values instead of waiting, and lagging readers observe TryRead::Overwritten.
+// If release is enabled, a slot drops its value once every reader has read it, unless lossy readers exist.
 
 pub struct MpmcCircularBuffer<T> {
     buffer: Box<[Slot<T>]>,
     head: AtomicUsize,
     maintenance: Mutex<()>,
     readers: AtomicUsize,
+    lossy_readers: AtomicUsize,
+    overwrite: bool,
+    release: bool,
 }
 
 impl<T> Debug for MpmcCircularBuffer<T> {
@@ -32,7 +38,7 @@ impl<T> MpmcCircularBuffer<T>
 where
     T: Clone,
 {
-    pub fn new(capacity: usize) -> (Self, BufferReader) {
+    pub fn new(capacity: usize, overwrite: bool) -> (Self, BufferReader) {
         // we require two readers, so that unique slots can be acquired and released
         let capacity = max(2, capacity);
         let mut vec = Vec::with_capacity(capacity);
@@ -45,13 +51,26 @@ where
             buffer: vec.into_boxed_slice(),
             head: AtomicUsize::new(1),
             readers: AtomicUsize::new(1),
+            lossy_readers: AtomicUsize::new(0),
             maintenance: Mutex::new(()),
+            overwrite,
+            release: false,
         };
 
-        let reader = BufferReader { index: 1 };
+        let reader = BufferReader {
+            index: 1,
+            lossy: None,
+        };
 
         (this, reader)
     }
+
+    /// Drops each value once every reader has read it, rather than when the slot is overwritten.
+    /// This allows readers to hold the only copy of the value, if no other reader has kept one.
+    pub fn release_on_read(mut self) -> Self {
+        self.release = true;
+        self
+    }
 }
 
 pub enum TryWrite<T> {
@@ -88,22 +107,23 @@ impl<T> MpmcCircularBuffer<T> {
             // try to write a value
             // if the write is accepted, release the head lock in the closure
             // this minimizes the time head is locked, and allows the move of value to occur after the lock is released
-            let try_write = head_slot.try_write(head_id, value, &self.readers, cx, || {
-                if let Err(_e) = self.head.compare_exchange(
-                    head_id,
-                    head_id + 1,
-                    Ordering::SeqCst,
-                    Ordering::Relaxed,
-                ) {
-                    #[cfg(feature = "debug")]
-                    log::warn!(
-                        "[{}] Expected {} head value, found {}",
+            let try_write =
+                head_slot.try_write(head_id, value, &self.readers, self.overwrite, cx, || {
+                    if let Err(_e) = self.head.compare_exchange(
                         head_id,
                         head_id + 1,
-                        _e
-                    );
-                }
-            });
+                        Ordering::SeqCst,
+                        Ordering::Relaxed,
+                    ) {
+                        #[cfg(feature = "debug")]
+                        log::warn!(
+                            "[{}] Expected {} head value, found {}",
+                            head_id,
+                            head_id + 1,
+                            _e
+                        );
+                    }
+                });
 
             match try_write {
                 SlotTryWrite::Pending(v) => {
@@ -132,6 +152,23 @@ impl<T> MpmcCircularBuffer<T> {
         }
     }
 
+    /// Returns true if a write would be accepted.  If not, subscribes to the release of the head slot.
+    ///
+    /// Another writer may take the slot before the caller writes, so the write can still be pending.
+    #[cfg(feature = "futures-traits")]
+    pub fn poll_writable(&self, cx: &Context<'_>) -> bool {
+        loop {
+            let head_id = self.head.load(Ordering::Acquire);
+            let head_slot = self.get_slot(head_id);
+
+            match head_slot.poll_writable(head_id, &self.readers, self.overwrite, cx) {
+                Some(writable) => return writable,
+                // the head was written by another writer
+                None => continue,
+            }
+        }
+    }
+
     pub fn new_reader(&self) -> BufferReader {
         let _maint = self.maintenance.lock();
         let index = self.head.load(Ordering::Acquire);
@@ -142,7 +179,77 @@ impl<T> MpmcCircularBuffer<T> {
         #[cfg(feature = "debug")]
         log::info!("[{}] New reader", index);
 
-        BufferReader { index }
+        BufferReader { index, lossy: None }
+    }
+
+    /// Creates a reader at the index, if the values from the index to the head are still in the buffer.
+    /// The index may be the head, in which case the reader observes the next value written.
+    pub fn new_reader_at(&self, index: usize) -> Option<BufferReader> {
+        let maint = self.maintenance.lock();
+        if !self.is_retained(index) {
+            return None;
+        }
+
+        self.readers.fetch_add(1, Ordering::AcqRel);
+        self.mark_read_in_range(0, index);
+        let mut reader = BufferReader { index, lossy: None };
+
+        // a writer may have overwritten the value before the reader was counted
+        if !self.is_retained(index) {
+            drop(maint);
+            reader.drop_with(self);
+            return None;
+        }
+
+        #[cfg(feature = "debug")]
+        log::info!("[{}] New reader at index", index);
+
+        Some(reader)
+    }
+
+    /// Whether the value at the index, and every later value, can still be read
+    fn is_retained(&self, index: usize) -> bool {
+        let head = self.head.load(Ordering::Acquire);
+        if index == 0 || index > head {
+            return false;
+        }
+
+        if index == head {
+            return true;
+        }
+
+        // released values may be missing from any slot, not just the oldest
+        if self.release {
+            return (index..head).all(|id| self.get_slot(id).holds(id));
+        }
+
+        self.get_slot(index).index.load(Ordering::Acquire) == index
+    }
+
+    /// Creates a reader which is not counted by writers, so it never holds a slot.
+    /// The reader keeps up to `capacity` of the most recent values, and skips older values.
+    pub fn new_lossy_reader(&self, capacity: usize) -> BufferReader {
+        let index = self.head.load(Ordering::Acquire);
+        let capacity = capacity.clamp(1, self.len());
+        self.lossy_readers.fetch_add(1, Ordering::AcqRel);
+
+        BufferReader {
+            index,
+            lossy: Some(capacity),
+        }
+    }
+
+    /// The number of written values which the slowest reader has not yet read
+    pub fn slowest_lag(&self) -> usize {
+        let head = self.head.load(Ordering::Acquire);
+        let readers = self.readers.load(Ordering::Acquire);
+
+        self.buffer
+            .iter()
+            .filter_map(|slot| slot.unread_index(readers))
+            .min()
+            .map(|index| head.saturating_sub(index))
+            .unwrap_or(0)
     }
 
     fn mark_read_in_range(&self, min: usize, max: usize) {
@@ -161,6 +268,8 @@ impl<T> MpmcCircularBuffer<T> {
 #[derive(Debug)]
 pub struct BufferReader {
     index: usize,
+    // the capacity of a reader which is not counted by writers
+    lossy: Option<usize>,
 }
 
 pub enum TryRead<T> {
@@ -168,6 +277,8 @@ pub enum TryRead<T> {
     Ready(T),
     /// A value is pending in this slot
     Pending,
+    /// The value at the reader position was overwritten before it was read
+    Overwritten,
 }
 
 impl BufferReader {
@@ -178,12 +289,20 @@ impl BufferReader {
         let index = self.index;
         let slot = buffer.get_slot(index);
 
-        let try_read = slot.try_read(index, &buffer.readers, cx);
+        let try_read = match self.lossy {
+            Some(_) => slot.try_read_uncounted(index, cx),
+            None => slot.try_read(index, &buffer.readers, cx),
+        };
 
         match &try_read {
             TryRead::Ready(_) => {
                 self.index += 1;
 
+                // lossy readers may still read the value, after every counted reader has read it
+                if buffer.release && buffer.lossy_readers.load(Ordering::Acquire) == 0 {
+                    slot.release(index, &buffer.readers);
+                }
+
                 #[cfg(feature = "debug")]
                 log::debug!(
                     "[{}] Read complete in slot {} with {:?} reads of {:?} required",
@@ -197,13 +316,74 @@ impl BufferReader {
                 #[cfg(feature = "debug")]
                 log::debug!("[{}] Read pending, slot: {:?}", index, slot);
             }
+            TryRead::Overwritten => {
+                #[cfg(feature = "debug")]
+                log::debug!("[{}] Read overwritten, slot: {:?}", index, slot);
+            }
         }
 
         try_read
     }
 
+    /// Moves a reader whose value was overwritten to the oldest value in the buffer.  Returns the number of values skipped.
+    pub fn skip_overwritten<T>(&mut self, buffer: &MpmcCircularBuffer<T>) -> usize {
+        let head = buffer.head.load(Ordering::Acquire);
+        let oldest = head.saturating_sub(buffer.len()).max(self.index + 1);
+        let skipped = oldest - self.index;
+        self.index = oldest;
+
+        skipped
+    }
+
+    /// Moves a lossy reader which has fallen more than its capacity behind to the most recent values.  Returns the number of values skipped.
+    pub fn skip_lagged<T>(&mut self, buffer: &MpmcCircularBuffer<T>) -> usize {
+        let capacity = match self.lossy {
+            Some(capacity) => capacity,
+            None => return 0,
+        };
+
+        let head = buffer.head.load(Ordering::Acquire);
+        let oldest = head.saturating_sub(capacity);
+        if oldest <= self.index {
+            return 0;
+        }
+
+        let skipped = oldest - self.index;
+        self.index = oldest;
+        skipped
+    }
+
+    /// The number of written values which this reader has not yet read
+    pub fn queued<T>(&self, buffer: &MpmcCircularBuffer<T>) -> usize {
+        let head = buffer.head.load(Ordering::Acquire);
+        let queued = head.saturating_sub(self.index);
+
+        match self.lossy {
+            Some(capacity) => queued.min(capacity),
+            None => queued,
+        }
+    }
+
+    /// The index of the next value the reader will read
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The capacity of a reader which is not counted by writers
+    pub fn lossy_capacity(&self) -> Option<usize> {
+        self.lossy
+    }
+
     // To avoid the need for shared Arc references, clone and drop are written as methods instead of using std traits
     pub fn clone_with<T>(&self, buffer: &MpmcCircularBuffer<T>) -> Self {
+        if self.lossy.is_some() {
+            buffer.lossy_readers.fetch_add(1, Ordering::AcqRel);
+            return BufferReader {
+                index: self.index,
+                lossy: self.lossy,
+            };
+        }
+
         let _maint = buffer.maintenance.lock();
         buffer.readers.fetch_add(1, Ordering::AcqRel);
 
@@ -213,10 +393,15 @@ impl BufferReader {
         #[cfg(feature = "debug")]
         log::error!("[{}] Cloned reader", index);
 
-        BufferReader { index }
+        BufferReader { index, lossy: None }
     }
 
     pub fn drop_with<T>(&mut self, buffer: &MpmcCircularBuffer<T>) {
+        if self.lossy.is_some() {
+            buffer.lossy_readers.fetch_sub(1, Ordering::AcqRel);
+            return;
+        }
+
         let _maint = buffer.maintenance.lock();
 
         // first, cancel all reads that this reader has committed
@@ -229,6 +414,7 @@ impl BufferReader {
         buffer.readers.fetch_sub(1, Ordering::AcqRel);
 
         // then go through the buffer, and release any slots that should be released
+        #[allow(clippy::unused_enumerate_index)]
         for (_id, slot) in buffer.buffer.iter().enumerate() {
             #[cfg(feature = "debug")]
             log::debug!(
@@ -275,6 +461,7 @@ impl<T> Slot<T> {
         index: usize,
         value: T,
         readers: &AtomicUsize,
+        overwrite: bool,
         cx: &Context<'_>,
         on_write: OnWrite,
     ) -> SlotTryWrite<T>
@@ -286,7 +473,8 @@ impl<T> Slot<T> {
 
             if prev_index >= index {
                 return SlotTryWrite::Written(value);
-            } else if prev_index != 0
+            } else if !overwrite
+                && prev_index != 0
                 && self.reads.load(Ordering::Acquire) < readers.load(Ordering::Acquire)
             {
                 self.on_release.subscribe(cx);
@@ -314,7 +502,8 @@ impl<T> Slot<T> {
 
             // lock the data, then update the index
             let mut data = self.data.write();
-            if prev_index != 0
+            if !overwrite
+                && prev_index != 0
                 && self.reads.load(Ordering::Acquire) < readers.load(Ordering::Acquire)
             {
                 #[cfg(feature = "debug")]
@@ -342,6 +531,42 @@ impl<T> Slot<T> {
         }
     }
 
+    /// Returns whether a value can be written at the index, subscribing to the release of the slot if it cannot.
+    /// Returns None if a value has already been written at the index.
+    #[cfg(feature = "futures-traits")]
+    pub fn poll_writable(
+        &self,
+        index: usize,
+        readers: &AtomicUsize,
+        overwrite: bool,
+        cx: &Context<'_>,
+    ) -> Option<bool> {
+        loop {
+            let prev_index = self.index.load(Ordering::Acquire);
+
+            if prev_index >= index {
+                return None;
+            }
+
+            if overwrite
+                || prev_index == 0
+                || self.reads.load(Ordering::Acquire) >= readers.load(Ordering::Acquire)
+            {
+                return Some(true);
+            }
+
+            self.on_release.subscribe(cx);
+
+            if prev_index < self.index.load(Ordering::Acquire)
+                || self.reads.load(Ordering::Acquire) >= readers.load(Ordering::Acquire)
+            {
+                continue;
+            }
+
+            return Some(false);
+        }
+    }
+
     fn mark_read_in_range(&self, min: usize, max: usize, readers: usize) {
         // prevent the index from changing while maintenance is performed
         let _read = self.data.read();
@@ -392,6 +617,38 @@ impl<T> Slot<T> {
         }
     }
 
+    /// Returns the index of the value in the slot, if it has been written and some reader has not read it
+    fn unread_index(&self, readers: usize) -> Option<usize> {
+        let index = self.index.load(Ordering::Acquire);
+        if index != 0 && self.reads.load(Ordering::Acquire) < readers {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the slot holds the value written at the index
+    fn holds(&self, index: usize) -> bool {
+        let data = self.data.read();
+        self.index.load(Ordering::Acquire) == index && data.is_some()
+    }
+
+    /// Drops the value written at the index, if every reader has read it
+    fn release(&self, index: usize, readers: &AtomicUsize) {
+        if self.reads.load(Ordering::Acquire) < readers.load(Ordering::Acquire) {
+            return;
+        }
+
+        let mut data = self.data.write();
+
+        // a reader may have been created at the index, or a writer may have replaced the value
+        if self.index.load(Ordering::Acquire) == index
+            && self.reads.load(Ordering::Acquire) >= readers.load(Ordering::Acquire)
+        {
+            data.take();
+        }
+    }
+
     fn notify_readers_decreased(&self, readers: &AtomicUsize) {
         if self.reads.load(Ordering::Acquire) >= readers.load(Ordering::Acquire) {
             self.on_release.notify();
@@ -423,11 +680,16 @@ where
                     slot_index,
                     index
                 );
-                return TryRead::Pending;
+                return TryRead::Overwritten;
             }
 
             let data_lock = self.data.read();
 
+            // the index only changes while the data is locked for writing
+            if self.index.load(Ordering::Acquire) != index {
+                continue;
+            }
+
             let reads = 1 + self.reads.fetch_add(1, Ordering::AcqRel);
             #[cfg(feature = "debug")]
             log::debug!(
@@ -449,6 +711,33 @@ where
             break TryRead::Ready(data_cloned);
         }
     }
+
+    /// Reads the value without counting the read, for readers which writers do not wait for
+    pub fn try_read_uncounted(&self, index: usize, cx: &Context<'_>) -> TryRead<T> {
+        loop {
+            let slot_index = self.index.load(Ordering::Acquire);
+            if slot_index < index {
+                self.on_write.subscribe(cx);
+
+                if self.index.load(Ordering::Acquire) >= index {
+                    continue;
+                }
+
+                return TryRead::Pending;
+            } else if slot_index > index {
+                return TryRead::Overwritten;
+            }
+
+            let data_lock = self.data.read();
+
+            // the index only changes while the data is locked for writing
+            if self.index.load(Ordering::Acquire) != index {
+                continue;
+            }
+
+            break TryRead::Ready(data_lock.as_ref().unwrap().clone());
+        }
+    }
 }
 
 impl<T> Debug for Slot<T> {
@@ -1,6 +1,5 @@
-use atomic::Ordering;
-use crossbeam_queue::SegQueue;
-use std::{sync::atomic::AtomicUsize, task::Waker};
+use super::primitives::{AtomicUsize, Ordering, SegQueue};
+use std::task::Waker;
 
 #[derive(Debug)]
 pub struct Notifier {
@@ -16,8 +15,10 @@ impl Notifier {
         }
     }
 
-    pub fn guard(&self) -> NotificationGuard {
-        let generation = self.generation.load(Ordering::Relaxed);
+    pub fn guard(&self) -> NotificationGuard<'_> {
+        // acquire, so that if the guard observes a notification,
+        // it also observes the state change (such as a closed channel) that preceded it
+        let generation = self.generation.load(Ordering::Acquire);
 
         NotificationGuard {
             generation,
@@ -46,6 +47,11 @@ impl Notifier {
         }
     }
 
+    /// The number of wakers which are waiting for a notification
+    pub fn waiting(&self) -> usize {
+        self.wakers.len()
+    }
+
     pub fn subscribe(&self, cx: &crate::Context<'_>) {
         if let Some(waker) = cx.waker() {
             self.wakers.push(waker.clone());
@@ -60,6 +66,6 @@ pub struct NotificationGuard<'a> {
 
 impl<'a> NotificationGuard<'a> {
     pub fn is_expired(&self) -> bool {
-        self.stored_generation.load(Ordering::Relaxed) != self.generation
+        self.stored_generation.load(Ordering::Acquire) != self.generation
     }
 }
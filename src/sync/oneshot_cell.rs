@@ -1,4 +1,4 @@
-use atomic::Ordering;
+use super::primitives::Ordering;
 
 use super::state_cell::StateCell;
 
@@ -43,6 +43,15 @@ impl<T> OneshotCell<T> {
         Ok(())
     }
 
+    /// The number of values which can still be received: the bounds are exact once the value is stored or taken
+    pub fn remaining(&self) -> (usize, Option<usize>) {
+        match self.state.load(Ordering::Acquire) {
+            State::None | State::Writing => (0, Some(1)),
+            State::Ready => (1, Some(1)),
+            State::Taken => (0, Some(0)),
+        }
+    }
+
     pub fn try_recv(&self) -> Result<T, TryRecvError> {
         unsafe {
             match self.state.compare_take(
@@ -0,0 +1,211 @@
+//! The atomics, locks, and queues used by the sync layer.
+//!
+//! When compiled with `RUSTFLAGS="--cfg postage_loom"`, these are swapped for [loom](https://docs.rs/loom) types,
+//! so the sender/receiver counts, slot claims, waker registration, and close protocol can be model-checked.
+//! Queues and generic atomics are emulated with loom locks.
+
+#[cfg(not(postage_loom))]
+pub(crate) use std_primitives::*;
+
+#[cfg(postage_loom)]
+pub(crate) use loom_primitives::*;
+
+#[cfg(not(postage_loom))]
+mod std_primitives {
+    pub(crate) use atomic::{Atomic, Ordering};
+    pub(crate) use crossbeam_queue::{ArrayQueue, SegQueue};
+    pub(crate) use parking_lot::{Mutex, RwLock};
+    pub(crate) use std::{
+        hint::spin_loop,
+        sync::{
+            atomic::{AtomicBool, AtomicU64, AtomicUsize},
+            Arc,
+        },
+        thread::yield_now,
+    };
+}
+
+#[cfg(postage_loom)]
+mod loom_primitives {
+    use std::{collections::VecDeque, fmt, mem::size_of, slice};
+
+    pub(crate) use loom::{
+        hint::spin_loop,
+        sync::{
+            atomic::{AtomicBool, AtomicU64, AtomicUsize},
+            Arc,
+        },
+        thread::yield_now,
+    };
+    pub(crate) use std::sync::atomic::Ordering;
+
+    pub(crate) struct Mutex<T>(loom::sync::Mutex<T>);
+
+    impl<T> Mutex<T> {
+        pub fn new(value: T) -> Self {
+            Self(loom::sync::Mutex::new(value))
+        }
+
+        pub fn lock(&self) -> loom::sync::MutexGuard<'_, T> {
+            self.0.lock().unwrap()
+        }
+    }
+
+    impl<T> fmt::Debug for Mutex<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("Mutex").finish()
+        }
+    }
+
+    pub(crate) struct RwLock<T>(loom::sync::RwLock<T>);
+
+    impl<T> RwLock<T> {
+        pub fn new(value: T) -> Self {
+            Self(loom::sync::RwLock::new(value))
+        }
+
+        pub fn read(&self) -> loom::sync::RwLockReadGuard<'_, T> {
+            self.0.read().unwrap()
+        }
+
+        pub fn write(&self) -> loom::sync::RwLockWriteGuard<'_, T> {
+            self.0.write().unwrap()
+        }
+    }
+
+    impl<T> fmt::Debug for RwLock<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("RwLock").finish()
+        }
+    }
+
+    /// An emulation of `atomic::Atomic`, which compares values bytewise.
+    pub(crate) struct Atomic<T>(loom::sync::Mutex<T>);
+
+    impl<T: Copy> Atomic<T> {
+        pub fn new(value: T) -> Self {
+            Self(loom::sync::Mutex::new(value))
+        }
+
+        pub fn is_lock_free() -> bool {
+            true
+        }
+
+        pub fn load(&self, _order: Ordering) -> T {
+            *self.0.lock().unwrap()
+        }
+
+        pub fn store(&self, value: T, _order: Ordering) {
+            *self.0.lock().unwrap() = value;
+        }
+
+        pub fn compare_exchange(
+            &self,
+            current: T,
+            new: T,
+            _success: Ordering,
+            _failure: Ordering,
+        ) -> Result<T, T> {
+            let mut value = self.0.lock().unwrap();
+
+            if bytes(&*value) == bytes(&current) {
+                Ok(std::mem::replace(&mut *value, new))
+            } else {
+                Err(*value)
+            }
+        }
+    }
+
+    fn bytes<T: Copy>(value: &T) -> &[u8] {
+        unsafe { slice::from_raw_parts(value as *const T as *const u8, size_of::<T>()) }
+    }
+
+    impl<T> fmt::Debug for Atomic<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("Atomic").finish()
+        }
+    }
+
+    /// An emulation of `crossbeam_queue::ArrayQueue`
+    pub(crate) struct ArrayQueue<T> {
+        capacity: usize,
+        items: loom::sync::Mutex<VecDeque<T>>,
+    }
+
+    impl<T> ArrayQueue<T> {
+        pub fn new(capacity: usize) -> Self {
+            assert!(capacity > 0, "capacity must be non-zero");
+
+            Self {
+                capacity,
+                items: loom::sync::Mutex::new(VecDeque::with_capacity(capacity)),
+            }
+        }
+
+        pub fn push(&self, value: T) -> Result<(), T> {
+            let mut items = self.items.lock().unwrap();
+
+            if items.len() >= self.capacity {
+                return Err(value);
+            }
+
+            items.push_back(value);
+            Ok(())
+        }
+
+        pub fn pop(&self) -> Option<T> {
+            self.items.lock().unwrap().pop_front()
+        }
+
+        pub fn len(&self) -> usize {
+            self.items.lock().unwrap().len()
+        }
+
+        pub fn is_full(&self) -> bool {
+            self.len() >= self.capacity
+        }
+    }
+
+    impl<T> fmt::Debug for ArrayQueue<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("ArrayQueue")
+                .field("capacity", &self.capacity)
+                .finish()
+        }
+    }
+
+    /// An emulation of `crossbeam_queue::SegQueue`
+    pub(crate) struct SegQueue<T> {
+        items: loom::sync::Mutex<VecDeque<T>>,
+    }
+
+    impl<T> SegQueue<T> {
+        pub fn new() -> Self {
+            Self {
+                items: loom::sync::Mutex::new(VecDeque::new()),
+            }
+        }
+
+        pub fn push(&self, value: T) {
+            self.items.lock().unwrap().push_back(value);
+        }
+
+        pub fn pop(&self) -> Option<T> {
+            self.items.lock().unwrap().pop_front()
+        }
+
+        pub fn len(&self) -> usize {
+            self.items.lock().unwrap().len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.items.lock().unwrap().is_empty()
+        }
+    }
+
+    impl<T> fmt::Debug for SegQueue<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("SegQueue").finish()
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use std::sync::atomic::{AtomicUsize, Ordering};
+use super::primitives::{AtomicUsize, Ordering};
 
 #[derive(Debug)]
 pub struct RefCount {
@@ -6,6 +6,7 @@ pub struct RefCount {
 }
 
 pub enum TryDecrement {
+    #[allow(dead_code)]
     Alive(usize),
     Dead,
 }
@@ -31,6 +32,10 @@ impl RefCount {
         self.count.load(Ordering::Acquire) > 0
     }
 
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::Acquire)
+    }
+
     pub fn increment(&self) {
         self.count.fetch_add(1, Ordering::AcqRel);
     }
@@ -1,6 +1,6 @@
 use std::cell::UnsafeCell;
 
-use atomic::{Atomic, Ordering};
+use super::primitives::{Atomic, Ordering};
 
 pub struct StateCell<S, T>
 where
@@ -23,6 +23,10 @@ where
         }
     }
 
+    pub fn load(&self, ordering: Ordering) -> S {
+        self.state.load(ordering)
+    }
+
     pub unsafe fn compare_store(
         &self,
         current: S,
@@ -1,10 +1,9 @@
-use atomic::{Atomic, Ordering};
-
-use crate::{stream::PollRecv, Context};
+use crate::{stream::PollRecv, trace::Tracer, Context};
 
 use super::{
     notifier::Notifier,
     oneshot_cell::{OneshotCell, TryRecvError},
+    primitives::{Atomic, Ordering},
 };
 
 #[derive(Copy, Clone)]
@@ -18,24 +17,32 @@ pub struct Transfer<T: Sized> {
     receiver: Atomic<State>,
     value: OneshotCell<T>,
     notify_rx: Notifier,
+    tracer: Tracer,
 }
 
 impl<T> Transfer<T> {
-    pub fn new() -> Self {
+    pub fn new(tracer: Tracer) -> Self {
         Self {
             sender: Atomic::new(State::Alive),
             receiver: Atomic::new(State::Alive),
             value: OneshotCell::new(),
             notify_rx: Notifier::new(),
+            tracer,
         }
     }
 
     pub fn send(&self, value: T) -> Result<(), T> {
         if let State::Dead = self.receiver.load(Ordering::Acquire) {
+            self.tracer.reject();
+            return Err(value);
+        }
+
+        if let Err(value) = self.value.send(value) {
+            self.tracer.reject();
             return Err(value);
         }
 
-        self.value.send(value)?;
+        self.tracer.send();
         self.notify_rx.notify();
 
         Ok(())
@@ -45,7 +52,10 @@ impl<T> Transfer<T> {
         loop {
             let guard = self.notify_rx.guard();
             match self.value.try_recv() {
-                Ok(value) => return PollRecv::Ready(value),
+                Ok(value) => {
+                    self.tracer.recv();
+                    return PollRecv::Ready(value);
+                }
                 Err(TryRecvError::Pending) => {
                     if let State::Dead = self.sender.load(Ordering::Acquire) {
                         return match self.value.try_recv() {
@@ -68,12 +78,33 @@ impl<T> Transfer<T> {
         }
     }
 
+    pub fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.value.remaining() {
+            // the sender was dropped without sending a value
+            (0, Some(1)) if self.is_sender_closed() => match self.value.remaining() {
+                (0, Some(1)) => (0, Some(0)),
+                remaining => remaining,
+            },
+            remaining => remaining,
+        }
+    }
+
+    pub fn is_sender_closed(&self) -> bool {
+        matches!(self.sender.load(Ordering::Acquire), State::Dead)
+    }
+
+    pub fn is_receiver_closed(&self) -> bool {
+        matches!(self.receiver.load(Ordering::Acquire), State::Dead)
+    }
+
     pub fn sender_disconnect(&self) {
         self.sender.store(State::Dead, Ordering::Release);
+        self.tracer.senders_closed();
         self.notify_rx.notify();
     }
 
     pub fn receiver_disconnect(&self) {
         self.receiver.store(State::Dead, Ordering::Release);
+        self.tracer.receivers_closed();
     }
 }
@@ -1,20 +1,44 @@
+//! Utilities for testing code which uses channels.
+//!
+//! Requires the `test-util` feature.
+//! - [pump](./pump/index.html), a channel which delivers messages only when explicitly pumped,
+//!   and records every poll transition.
+//! - [MockStream](./struct.MockStream.html) and [MockSink](./struct.MockSink.html), test doubles with scripted responses.
+
+mod mock;
+pub mod pump;
+
+pub use mock::{MockSend, MockSink, MockStream};
+
+#[cfg(test)]
 pub mod sink;
+#[cfg(test)]
 pub mod stream;
+#[cfg(test)]
 mod test_messages;
+#[cfg(test)]
 pub use test_messages::*;
 
+#[cfg(test)]
 use crate::Context;
+#[cfg(test)]
 use std::time::Duration;
 
+#[cfg(test)]
 pub const CHANNEL_TEST_ITERATIONS: usize = 2000;
+#[cfg(test)]
 pub const CHANNEL_TEST_SENDERS: usize = 10;
+#[cfg(test)]
 pub const CHANNEL_TEST_RECEIVERS: usize = 5;
+#[cfg(test)]
 pub const TEST_TIMEOUT: Duration = Duration::from_secs(100);
 
+#[cfg(test)]
 pub fn noop_context() -> crate::Context<'static> {
     Context::empty()
 }
 
+#[cfg(test)]
 pub fn panic_context() -> crate::Context<'static> {
     futures_test::task::panic_context().into()
 }
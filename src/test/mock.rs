@@ -0,0 +1,284 @@
+//! Scripted Sink and Stream test doubles.
+
+use std::{collections::VecDeque, fmt, pin::Pin};
+
+use crate::{
+    sink::{PollSend, Sink},
+    stream::{PollRecv, Stream},
+    Context,
+};
+
+/// A stream which returns a scripted sequence of responses.
+///
+/// Each poll returns the next response in the script.  Once the script is exhausted, the stream is closed.
+///
+/// ```rust
+/// use postage::{prelude::*, stream::PollRecv, test::MockStream};
+///
+/// let mut stream = MockStream::new(vec![PollRecv::Pending, PollRecv::Ready(1usize)]);
+///
+/// assert!(stream.try_recv().is_err());
+/// assert_eq!(Ok(1), stream.try_recv());
+/// assert!(stream.try_recv().is_err());
+/// assert_eq!(3, stream.polls());
+/// ```
+pub struct MockStream<T> {
+    script: VecDeque<PollRecv<T>>,
+    wake_on_pending: bool,
+    polls: usize,
+}
+
+impl<T> MockStream<T> {
+    /// Constructs a stream which returns the responses in order
+    pub fn new<I>(script: I) -> Self
+    where
+        I: IntoIterator<Item = PollRecv<T>>,
+    {
+        Self {
+            script: script.into_iter().collect(),
+            wake_on_pending: false,
+            polls: 0,
+        }
+    }
+
+    /// If enabled, the task is woken immediately each time the stream returns `Pending`.
+    /// This simulates spurious wakeups, and keeps an executor polling through the script.
+    pub fn wake_on_pending(mut self, wake: bool) -> Self {
+        self.wake_on_pending = wake;
+        self
+    }
+
+    /// The number of times the stream has been polled
+    pub fn polls(&self) -> usize {
+        self.polls
+    }
+
+    /// The number of responses which remain in the script
+    pub fn remaining(&self) -> usize {
+        self.script.len()
+    }
+}
+
+impl<T> Stream for MockStream<T> {
+    type Item = T;
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<T> {
+        let this = self.get_mut();
+        this.polls += 1;
+
+        match this.script.pop_front() {
+            Some(PollRecv::Pending) => {
+                if this.wake_on_pending {
+                    if let Some(waker) = cx.waker() {
+                        waker.wake_by_ref();
+                    }
+                }
+
+                PollRecv::Pending
+            }
+            Some(poll) => poll,
+            None => PollRecv::Closed,
+        }
+    }
+}
+
+impl<T> Unpin for MockStream<T> {}
+
+impl<T> fmt::Debug for MockStream<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockStream")
+            .field("remaining", &self.script.len())
+            .field("polls", &self.polls)
+            .finish()
+    }
+}
+
+/// A scripted response to a `MockSink` send.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MockSend {
+    /// The item is accepted, and recorded
+    Accept,
+    /// The sink is full, and the item is returned
+    Pending,
+    /// The sink is closed, and the item is returned
+    Reject,
+}
+
+/// A sink which responds with a scripted sequence of behaviors, and records the accepted items.
+///
+/// Once the script is exhausted, the sink rejects all items.
+///
+/// ```rust
+/// use postage::{prelude::*, test::{MockSend, MockSink}};
+///
+/// let mut sink = MockSink::new(vec![MockSend::Pending, MockSend::Accept]);
+///
+/// assert!(sink.try_send(1usize).is_err());
+/// assert!(sink.try_send(2usize).is_ok());
+/// assert!(sink.try_send(3usize).is_err());
+/// assert_eq!(&[2], sink.items());
+/// ```
+pub struct MockSink<T> {
+    script: VecDeque<MockSend>,
+    items: Vec<T>,
+    wake_on_pending: bool,
+    polls: usize,
+}
+
+impl<T> MockSink<T> {
+    /// Constructs a sink which responds with the behaviors in order
+    pub fn new<I>(script: I) -> Self
+    where
+        I: IntoIterator<Item = MockSend>,
+    {
+        Self {
+            script: script.into_iter().collect(),
+            items: Vec::new(),
+            wake_on_pending: false,
+            polls: 0,
+        }
+    }
+
+    /// If enabled, the task is woken immediately each time the sink returns `Pending`.
+    /// This simulates spurious wakeups, and keeps an executor polling through the script.
+    pub fn wake_on_pending(mut self, wake: bool) -> Self {
+        self.wake_on_pending = wake;
+        self
+    }
+
+    /// The items which were accepted, in order
+    pub fn items(&self) -> &[T] {
+        self.items.as_slice()
+    }
+
+    /// Consumes the sink, returning the accepted items
+    pub fn into_items(self) -> Vec<T> {
+        self.items
+    }
+
+    /// The number of times the sink has been polled
+    pub fn polls(&self) -> usize {
+        self.polls
+    }
+
+    /// The number of responses which remain in the script
+    pub fn remaining(&self) -> usize {
+        self.script.len()
+    }
+}
+
+impl<T> Sink for MockSink<T> {
+    type Item = T;
+
+    fn poll_send(self: Pin<&mut Self>, cx: &mut Context<'_>, value: T) -> PollSend<T> {
+        let this = self.get_mut();
+        this.polls += 1;
+
+        match this.script.pop_front() {
+            Some(MockSend::Accept) => {
+                this.items.push(value);
+                PollSend::Ready
+            }
+            Some(MockSend::Pending) => {
+                if this.wake_on_pending {
+                    if let Some(waker) = cx.waker() {
+                        waker.wake_by_ref();
+                    }
+                }
+
+                PollSend::Pending(value)
+            }
+            Some(MockSend::Reject) | None => PollSend::Rejected(value),
+        }
+    }
+}
+
+impl<T> Unpin for MockSink<T> {}
+
+impl<T> fmt::Debug for MockSink<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockSink")
+            .field("remaining", &self.script.len())
+            .field("accepted", &self.items.len())
+            .field("polls", &self.polls)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+
+    use futures_test::task::new_count_waker;
+
+    use super::{MockSend, MockSink, MockStream};
+    use crate::{
+        sink::{PollSend, Sink},
+        stream::{PollRecv, Stream},
+        Context,
+    };
+
+    #[test]
+    fn stream_script() {
+        let mut stream = MockStream::new(vec![
+            PollRecv::Ready(1usize),
+            PollRecv::Pending,
+            PollRecv::Ready(2usize),
+        ]);
+        let mut cx = Context::empty();
+
+        assert_eq!(PollRecv::Ready(1), Pin::new(&mut stream).poll_recv(&mut cx));
+        assert_eq!(PollRecv::Pending, Pin::new(&mut stream).poll_recv(&mut cx));
+        assert_eq!(PollRecv::Ready(2), Pin::new(&mut stream).poll_recv(&mut cx));
+        assert_eq!(PollRecv::Closed, Pin::new(&mut stream).poll_recv(&mut cx));
+        assert_eq!(4, stream.polls());
+        assert_eq!(0, stream.remaining());
+    }
+
+    #[test]
+    fn stream_wake_on_pending() {
+        let mut stream = MockStream::<usize>::new(vec![PollRecv::Pending]).wake_on_pending(true);
+        let (waker, count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        assert_eq!(PollRecv::Pending, Pin::new(&mut stream).poll_recv(&mut cx));
+        assert_eq!(1, count.get());
+    }
+
+    #[test]
+    fn sink_script() {
+        let mut sink = MockSink::new(vec![MockSend::Accept, MockSend::Pending, MockSend::Reject]);
+        let mut cx = Context::empty();
+
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut sink).poll_send(&mut cx, 1usize)
+        );
+        assert_eq!(
+            PollSend::Pending(2),
+            Pin::new(&mut sink).poll_send(&mut cx, 2usize)
+        );
+        assert_eq!(
+            PollSend::Rejected(3),
+            Pin::new(&mut sink).poll_send(&mut cx, 3usize)
+        );
+        assert_eq!(
+            PollSend::Rejected(4),
+            Pin::new(&mut sink).poll_send(&mut cx, 4usize)
+        );
+        assert_eq!(vec![1], sink.into_items());
+    }
+
+    #[test]
+    fn sink_wake_on_pending() {
+        let mut sink = MockSink::new(vec![MockSend::Pending]).wake_on_pending(true);
+        let (waker, count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        assert_eq!(
+            PollSend::Pending(1usize),
+            Pin::new(&mut sink).poll_send(&mut cx, 1usize)
+        );
+        assert_eq!(1, count.get());
+    }
+}
@@ -0,0 +1,422 @@
+//! A channel which delivers messages only when explicitly pumped.
+//!
+//! Sent messages are staged, and are not visible to the receiver until [Pump::step](./struct.Pump.html#method.step)
+//! or [Pump::pump](./struct.Pump.html#method.pump) is called.  Every poll of the sender or receiver is recorded as a
+//! [Transition](./enum.Transition.html), so tests can assert the exact interleaving without depending on executor scheduling.
+//!
+//! ```rust
+//! use postage::{prelude::*, test::pump::{self, Transition}};
+//!
+//! let (mut tx, mut rx, pump) = pump::channel(2);
+//!
+//! tx.try_send(1usize).ok();
+//! assert!(rx.try_recv().is_err());
+//!
+//! assert!(pump.step());
+//! assert_eq!(Ok(1), rx.try_recv());
+//!
+//! assert_eq!(
+//!     vec![
+//!         Transition::SendReady,
+//!         Transition::RecvPending,
+//!         Transition::Delivered,
+//!         Transition::RecvReady
+//!     ],
+//!     pump.take_transitions()
+//! );
+//! ```
+
+use std::{collections::VecDeque, fmt, pin::Pin, sync::Arc, task::Waker};
+
+use parking_lot::Mutex;
+
+use crate::{
+    sink::{PollSend, Sink},
+    stream::{PollRecv, Stream},
+    Context,
+};
+
+/// Constructs a pumped channel, which buffers up to `capacity` staged and delivered messages.
+pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>, Pump<T>) {
+    assert!(capacity > 0, "capacity must be non-zero");
+
+    let state = Arc::new(Mutex::new(State {
+        capacity,
+        staged: VecDeque::new(),
+        delivered: VecDeque::new(),
+        transitions: Vec::new(),
+        sender_waker: None,
+        receiver_waker: None,
+        sender_alive: true,
+        receiver_alive: true,
+    }));
+
+    let sender = Sender {
+        state: state.clone(),
+    };
+    let receiver = Receiver {
+        state: state.clone(),
+    };
+    let pump = Pump { state };
+
+    (sender, receiver, pump)
+}
+
+/// A state transition observed by the pumped channel.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Transition {
+    /// The sender polled, and the message was staged
+    SendReady,
+    /// The sender polled, and the channel was full
+    SendPending,
+    /// The sender polled, and the receiver had been dropped
+    SendRejected,
+    /// The receiver polled, and took a delivered message
+    RecvReady,
+    /// The receiver polled, and no message had been delivered
+    RecvPending,
+    /// The receiver polled, and the sender had been dropped with no staged messages
+    RecvClosed,
+    /// The pump delivered a staged message to the receiver
+    Delivered,
+    /// The sender was dropped
+    SenderDropped,
+    /// The receiver was dropped
+    ReceiverDropped,
+}
+
+struct State<T> {
+    capacity: usize,
+    staged: VecDeque<T>,
+    delivered: VecDeque<T>,
+    transitions: Vec<Transition>,
+    sender_waker: Option<Waker>,
+    receiver_waker: Option<Waker>,
+    sender_alive: bool,
+    receiver_alive: bool,
+}
+
+impl<T> State<T> {
+    fn len(&self) -> usize {
+        self.staged.len() + self.delivered.len()
+    }
+}
+
+fn wake(waker: &mut Option<Waker>) {
+    if let Some(waker) = waker.take() {
+        waker.wake();
+    }
+}
+
+fn register(slot: &mut Option<Waker>, cx: &Context<'_>) {
+    if let Some(waker) = cx.waker() {
+        *slot = Some(waker.clone());
+    }
+}
+
+/// Controls delivery of staged messages, and records the channel transitions.
+pub struct Pump<T> {
+    state: Arc<Mutex<State<T>>>,
+}
+
+impl<T> Pump<T> {
+    /// Delivers one staged message to the receiver.  Returns false if no messages were staged.
+    pub fn step(&self) -> bool {
+        let mut state = self.state.lock();
+
+        match state.staged.pop_front() {
+            Some(value) => {
+                state.delivered.push_back(value);
+                state.transitions.push(Transition::Delivered);
+                wake(&mut state.receiver_waker);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Delivers all staged messages to the receiver, returning the number of messages delivered.
+    pub fn pump(&self) -> usize {
+        let mut delivered = 0;
+
+        while self.step() {
+            delivered += 1;
+        }
+
+        delivered
+    }
+
+    /// The number of messages which have been sent, but not delivered
+    pub fn staged(&self) -> usize {
+        self.state.lock().staged.len()
+    }
+
+    /// The number of messages which have been delivered, but not received
+    pub fn delivered(&self) -> usize {
+        self.state.lock().delivered.len()
+    }
+
+    /// Returns the transitions recorded since the last call, in the order they occurred.
+    pub fn take_transitions(&self) -> Vec<Transition> {
+        std::mem::take(&mut self.state.lock().transitions)
+    }
+}
+
+impl<T> fmt::Debug for Pump<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.lock();
+        f.debug_struct("Pump")
+            .field("staged", &state.staged.len())
+            .field("delivered", &state.delivered.len())
+            .finish()
+    }
+}
+
+/// The sender half of a pumped channel.
+pub struct Sender<T> {
+    state: Arc<Mutex<State<T>>>,
+}
+
+impl<T> Sink for Sender<T> {
+    type Item = T;
+
+    fn poll_send(self: Pin<&mut Self>, cx: &mut Context<'_>, value: T) -> PollSend<T> {
+        let mut state = self.state.lock();
+
+        if !state.receiver_alive {
+            state.transitions.push(Transition::SendRejected);
+            return PollSend::Rejected(value);
+        }
+
+        if state.len() >= state.capacity {
+            state.transitions.push(Transition::SendPending);
+            register(&mut state.sender_waker, cx);
+            return PollSend::Pending(value);
+        }
+
+        state.staged.push_back(value);
+        state.transitions.push(Transition::SendReady);
+        PollSend::Ready
+    }
+}
+
+#[cfg(feature = "futures-traits")]
+mod impl_futures {
+    use std::task::Poll;
+
+    use super::register;
+    use crate::{
+        sink::{PollSend, SendError, Sink},
+        Context,
+    };
+
+    impl<T> futures_sink::Sink<T> for super::Sender<T> {
+        type Error = SendError<T>;
+
+        fn poll_ready(
+            self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            let mut state = self.state.lock();
+
+            // a closed channel is ready, and rejects the message in start_send
+            if !state.receiver_alive || state.len() < state.capacity {
+                return Poll::Ready(Ok(()));
+            }
+
+            register(&mut state.sender_waker, &cx.into());
+            Poll::Pending
+        }
+
+        fn start_send(self: std::pin::Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+            match self.poll_send(&mut Context::empty(), item) {
+                PollSend::Ready => Ok(()),
+                PollSend::Pending(item) | PollSend::Rejected(item) => Err(SendError(item)),
+            }
+        }
+
+        fn poll_flush(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.state.lock();
+        state.sender_alive = false;
+        state.transitions.push(Transition::SenderDropped);
+        wake(&mut state.receiver_waker);
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender").finish()
+    }
+}
+
+/// The receiver half of a pumped channel.
+pub struct Receiver<T> {
+    state: Arc<Mutex<State<T>>>,
+}
+
+impl<T> Stream for Receiver<T> {
+    type Item = T;
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<T> {
+        let mut state = self.state.lock();
+
+        if let Some(value) = state.delivered.pop_front() {
+            state.transitions.push(Transition::RecvReady);
+            wake(&mut state.sender_waker);
+            return PollRecv::Ready(value);
+        }
+
+        if !state.sender_alive && state.staged.is_empty() {
+            state.transitions.push(Transition::RecvClosed);
+            return PollRecv::Closed;
+        }
+
+        state.transitions.push(Transition::RecvPending);
+        register(&mut state.receiver_waker, cx);
+        PollRecv::Pending
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.state.lock();
+        state.receiver_alive = false;
+        state.transitions.push(Transition::ReceiverDropped);
+        wake(&mut state.sender_waker);
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+
+    use futures_test::task::new_count_waker;
+
+    use super::{channel, Transition};
+    use crate::{
+        sink::{PollSend, Sink},
+        stream::{PollRecv, Stream},
+        test::noop_context,
+        Context,
+    };
+
+    #[test]
+    fn step_delivers_one() {
+        let (mut tx, mut rx, pump) = channel(4);
+
+        assert!(tx.try_send(1usize).is_ok());
+        assert!(tx.try_send(2usize).is_ok());
+        assert_eq!(2, pump.staged());
+
+        assert!(pump.step());
+        assert_eq!(1, pump.staged());
+        assert_eq!(1, pump.delivered());
+
+        assert_eq!(Ok(1), rx.try_recv());
+        assert!(rx.try_recv().is_err());
+
+        assert_eq!(1, pump.pump());
+        assert_eq!(Ok(2), rx.try_recv());
+        assert!(!pump.step());
+    }
+
+    #[test]
+    fn capacity_includes_delivered() {
+        let (mut tx, mut rx, pump) = channel(1);
+
+        assert!(tx.try_send(1usize).is_ok());
+        pump.pump();
+        assert!(tx.try_send(2usize).is_err());
+
+        assert_eq!(Ok(1), rx.try_recv());
+        assert!(tx.try_send(2usize).is_ok());
+    }
+
+    #[test]
+    fn wakes_receiver_on_step() {
+        let (mut tx, mut rx, pump) = channel(1);
+        let (waker, count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        assert_eq!(PollRecv::Pending, Pin::new(&mut rx).poll_recv(&mut cx));
+        assert_eq!(
+            PollSend::Ready,
+            Pin::new(&mut tx).poll_send(&mut noop_context(), 1usize)
+        );
+        assert_eq!(0, count.get());
+
+        pump.step();
+        assert_eq!(1, count.get());
+        assert_eq!(PollRecv::Ready(1), Pin::new(&mut rx).poll_recv(&mut cx));
+    }
+
+    #[test]
+    fn closed_after_staged_drained() {
+        let (mut tx, mut rx, pump) = channel(1);
+
+        assert!(tx.try_send(1usize).is_ok());
+        drop(tx);
+
+        assert_eq!(
+            PollRecv::Pending,
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+        pump.pump();
+        assert_eq!(
+            PollRecv::Ready(1),
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+        assert_eq!(
+            PollRecv::Closed,
+            Pin::new(&mut rx).poll_recv(&mut noop_context())
+        );
+
+        assert_eq!(
+            vec![
+                Transition::SendReady,
+                Transition::SenderDropped,
+                Transition::RecvPending,
+                Transition::Delivered,
+                Transition::RecvReady,
+                Transition::RecvClosed
+            ],
+            pump.take_transitions()
+        );
+        assert!(pump.take_transitions().is_empty());
+    }
+
+    #[test]
+    fn rejects_after_receiver_drop() {
+        let (mut tx, rx, pump) = channel(1);
+        drop(rx);
+
+        assert!(tx.try_send(1usize).is_err());
+        assert_eq!(
+            vec![Transition::ReceiverDropped, Transition::SendRejected],
+            pump.take_transitions()
+        );
+    }
+}
@@ -24,21 +24,12 @@ where
     }
 }
 
-#[derive(PartialEq, Clone, Debug)]
+#[derive(PartialEq, Clone, Debug, Default)]
 pub struct Message {
     sender: usize,
     index: usize,
 }
 
-impl Default for Message {
-    fn default() -> Self {
-        Self {
-            sender: 0,
-            index: 0,
-        }
-    }
-}
-
 impl Message {
     pub fn new_iter(sender: usize) -> impl Iterator<Item = Message> {
         MessageIter {
@@ -0,0 +1,232 @@
+//! Timer-driven streams, which can be merged or selected alongside channel traffic, and receivers with deadlines.
+//!
+//! Requires the `time` feature, or the `time-async-io` feature.  With the `time` feature, timers are driven by tokio,
+//! so streams which wait for a deadline must be polled within a tokio runtime.  With the `time-async-io` feature,
+//! timers are driven by the `async-io` reactor thread, and can be polled by any executor, such as async-std or smol.
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use postage::{prelude::*, time};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut ticks = time::interval(Duration::from_millis(10));
+//!
+//!     let first = ticks.recv().await.unwrap();
+//!     let second = ticks.recv().await.unwrap();
+//!     assert!(second - first >= Duration::from_millis(10));
+//! }
+//! ```
+
+use std::{
+    convert::TryFrom,
+    fmt,
+    pin::Pin,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    stream::{PollRecv, Stream},
+    Context,
+};
+
+mod deadline;
+pub(crate) mod timer;
+
+pub use deadline::{DeadlineElapsed, DeadlineReceiver, RecvUntil};
+use timer::Timer;
+
+/// Creates a stream which produces a tick immediately, and then every `period`.
+///
+/// # Panics
+/// Panics if `period` is zero.
+pub fn interval(period: Duration) -> Interval {
+    interval_at(Instant::now(), period)
+}
+
+/// Creates a stream which produces a tick at `start`, and then every `period`.
+///
+/// # Panics
+/// Panics if `period` is zero.
+pub fn interval_at(start: Instant, period: Duration) -> Interval {
+    assert!(
+        period > Duration::ZERO,
+        "the interval period must be non-zero"
+    );
+
+    Interval {
+        period,
+        next: start,
+        missed_tick_behavior: MissedTickBehavior::default(),
+        timer: None,
+    }
+}
+
+/// Determines how an [Interval](./struct.Interval.html) schedules ticks, when the receiver falls behind by more than a period.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum MissedTickBehavior {
+    /// Produces the missed ticks as fast as possible, until the interval has caught up with the schedule
+    #[default]
+    Burst,
+    /// Produces one tick, and schedules the next tick one period later.  The schedule shifts by the delay.
+    Delay,
+    /// Produces one tick, and skips to the next tick on the original schedule
+    Skip,
+}
+
+/// A stream of ticks, created by [interval](./fn.interval.html).  Produces the `Instant` each tick was scheduled for.
+///
+/// The stream never closes.
+pub struct Interval {
+    period: Duration,
+    next: Instant,
+    missed_tick_behavior: MissedTickBehavior,
+    timer: Option<Timer>,
+}
+
+impl Interval {
+    /// Sets the behavior when the receiver falls behind the schedule.  Defaults to `MissedTickBehavior::Burst`.
+    pub fn missed_tick_behavior(mut self, behavior: MissedTickBehavior) -> Self {
+        self.missed_tick_behavior = behavior;
+        self
+    }
+
+    /// The period between ticks
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// The instant the next tick is scheduled for
+    pub fn next_tick(&self) -> Instant {
+        self.next
+    }
+
+    /// Reschedules the next tick for one period from now
+    pub fn reset(&mut self) {
+        self.next = Instant::now() + self.period;
+    }
+
+    fn schedule_after(&self, tick: Instant, now: Instant) -> Instant {
+        let next = tick + self.period;
+        if next > now {
+            return next;
+        }
+
+        match self.missed_tick_behavior {
+            MissedTickBehavior::Burst => next,
+            MissedTickBehavior::Delay => now + self.period,
+            MissedTickBehavior::Skip => {
+                let behind = (now - tick).as_nanos() / self.period.as_nanos();
+                let behind = u32::try_from(behind).unwrap_or(u32::MAX - 1);
+                tick + self.period * (behind + 1)
+            }
+        }
+    }
+}
+
+impl Stream for Interval {
+    type Item = Instant;
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item> {
+        let this = self.get_mut();
+        let next = this.next;
+
+        if Instant::now() < next {
+            // the timer is created on first use, so an interval can be created outside the runtime
+            let timer = match &mut this.timer {
+                Some(timer) => timer,
+                None if cx.waker().is_some() => this.timer.insert(Timer::new(next)),
+                None => return PollRecv::Pending,
+            };
+
+            if timer.deadline() != next {
+                timer.reset(next);
+            }
+
+            if timer.poll_elapsed(cx).is_pending() {
+                return PollRecv::Pending;
+            }
+        }
+
+        this.next = this.schedule_after(next, Instant::now());
+        PollRecv::Ready(next)
+    }
+}
+
+impl fmt::Debug for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Interval")
+            .field("period", &self.period)
+            .field("next", &self.next)
+            .field("missed_tick_behavior", &self.missed_tick_behavior)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{interval, interval_at, MissedTickBehavior};
+    use crate::stream::{Stream, TryRecvError};
+
+    const PERIOD: Duration = Duration::from_millis(20);
+
+    #[test]
+    fn first_tick_is_immediate() {
+        let mut ticks = interval(PERIOD);
+        let next = ticks.next_tick();
+
+        assert_eq!(Ok(next), ticks.try_recv());
+        assert_eq!(Err(TryRecvError::Pending), ticks.try_recv());
+        assert_eq!(next + PERIOD, ticks.next_tick());
+    }
+
+    #[test]
+    fn burst() {
+        let start = Instant::now() - PERIOD * 3;
+        let mut ticks = interval_at(start, PERIOD);
+
+        for i in 0..4 {
+            assert_eq!(Ok(start + PERIOD * i), ticks.try_recv());
+        }
+        assert_eq!(Err(TryRecvError::Pending), ticks.try_recv());
+    }
+
+    #[test]
+    fn delay() {
+        let start = Instant::now() - PERIOD * 3;
+        let mut ticks = interval_at(start, PERIOD).missed_tick_behavior(MissedTickBehavior::Delay);
+
+        assert_eq!(Ok(start), ticks.try_recv());
+        assert!(ticks.next_tick() > Instant::now());
+        assert_eq!(Err(TryRecvError::Pending), ticks.try_recv());
+    }
+
+    #[test]
+    fn skip() {
+        let start = Instant::now() - PERIOD * 3 - PERIOD / 2;
+        let mut ticks = interval_at(start, PERIOD).missed_tick_behavior(MissedTickBehavior::Skip);
+
+        assert_eq!(Ok(start), ticks.try_recv());
+        assert_eq!(start + PERIOD * 4, ticks.next_tick());
+        assert_eq!(Err(TryRecvError::Pending), ticks.try_recv());
+    }
+
+    #[test]
+    fn reset() {
+        let mut ticks = interval(PERIOD);
+        ticks.reset();
+        assert_eq!(Err(TryRecvError::Pending), ticks.try_recv());
+    }
+
+    #[tokio::test]
+    async fn waits_for_period() {
+        let mut ticks = interval(PERIOD);
+
+        let first = ticks.recv().await.unwrap();
+        let second = ticks.recv().await.unwrap();
+        assert_eq!(first + PERIOD, second);
+        assert!(Instant::now() >= second);
+    }
+}
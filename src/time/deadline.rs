@@ -0,0 +1,219 @@
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::Poll,
+    time::{Duration, Instant},
+};
+
+use thiserror::Error;
+
+use super::timer::Timer;
+use crate::{
+    stream::{PollRecv, Stream},
+    Context,
+};
+
+/// An error returned by `recv_until`, when the deadline passes before the stream produces an item.
+#[derive(Copy, Clone, Debug, Error, PartialEq, Eq, Hash)]
+#[error("the deadline elapsed before an item was received")]
+pub struct DeadlineElapsed;
+
+/// Wraps a stream with a timer, which is shared by each call to `recv_until`.
+///
+/// A request loop can call `recv_until` repeatedly, and each call resets the timer instead of registering a new one.
+///
+/// ```rust
+/// use std::time::{Duration, Instant};
+/// use postage::{mpsc, prelude::*, time::{DeadlineElapsed, DeadlineReceiver}};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let (mut tx, rx) = mpsc::channel(4);
+///     let mut rx = DeadlineReceiver::new(rx);
+///     let deadline = Instant::now() + Duration::from_millis(10);
+///
+///     tx.send(1usize).await.ok();
+///     assert_eq!(Ok(Some(1)), rx.recv_until(deadline).await);
+///     assert_eq!(Err(DeadlineElapsed), rx.recv_until(deadline).await);
+/// }
+/// ```
+pub struct DeadlineReceiver<S> {
+    stream: S,
+    timer: Option<Timer>,
+}
+
+impl<S> DeadlineReceiver<S> {
+    /// Wraps the stream
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            timer: None,
+        }
+    }
+
+    /// Returns a reference to the wrapped stream
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Returns a mutable reference to the wrapped stream
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Returns the wrapped stream
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S> DeadlineReceiver<S>
+where
+    S: Stream + Unpin,
+{
+    /// Receives an item, if one is produced before the deadline.
+    ///
+    /// Returns:
+    /// - `Ok(Some(value))` if an item was received
+    /// - `Ok(None)` if the stream closed
+    /// - `Err(DeadlineElapsed)` if the deadline passed first.  If an item is ready, it is returned even if the deadline has passed.
+    pub fn recv_until(&mut self, deadline: Instant) -> RecvUntil<'_, S> {
+        RecvUntil {
+            receiver: self,
+            deadline,
+        }
+    }
+
+    /// Receives an item, if one is produced within the timeout
+    pub fn recv_timeout(&mut self, timeout: Duration) -> RecvUntil<'_, S> {
+        self.recv_until(Instant::now() + timeout)
+    }
+
+    fn poll_recv_until(
+        &mut self,
+        cx: &mut Context<'_>,
+        deadline: Instant,
+    ) -> Poll<Result<Option<S::Item>, DeadlineElapsed>> {
+        match Pin::new(&mut self.stream).poll_recv(cx) {
+            PollRecv::Ready(value) => return Poll::Ready(Ok(Some(value))),
+            PollRecv::Closed => return Poll::Ready(Ok(None)),
+            PollRecv::Pending => {}
+        }
+
+        if Instant::now() >= deadline {
+            return Poll::Ready(Err(DeadlineElapsed));
+        }
+
+        let timer = match &mut self.timer {
+            Some(timer) => timer,
+            None if cx.waker().is_some() => self.timer.insert(Timer::new(deadline)),
+            None => return Poll::Pending,
+        };
+
+        if timer.deadline() != deadline {
+            timer.reset(deadline);
+        }
+
+        match timer.poll_elapsed(cx) {
+            Poll::Ready(()) => Poll::Ready(Err(DeadlineElapsed)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<S> Stream for DeadlineReceiver<S>
+where
+    S: Stream + Unpin,
+{
+    type Item = S::Item;
+
+    fn poll_recv(self: Pin<&mut Self>, cx: &mut Context<'_>) -> PollRecv<Self::Item> {
+        Pin::new(&mut self.get_mut().stream).poll_recv(cx)
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for DeadlineReceiver<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeadlineReceiver")
+            .field("stream", &self.stream)
+            .finish()
+    }
+}
+
+/// A future returned by `DeadlineReceiver::recv_until`
+#[must_use = "futures do nothing unless polled"]
+pub struct RecvUntil<'s, S> {
+    receiver: &'s mut DeadlineReceiver<S>,
+    deadline: Instant,
+}
+
+impl<S> Future for RecvUntil<'_, S>
+where
+    S: Stream + Unpin,
+{
+    type Output = Result<Option<S::Item>, DeadlineElapsed>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut cx = cx.into();
+        this.receiver.poll_recv_until(&mut cx, this.deadline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{DeadlineElapsed, DeadlineReceiver};
+    use crate::{mpsc, sink::Sink};
+
+    #[tokio::test]
+    async fn ready_before_deadline() {
+        let (mut tx, rx) = mpsc::channel(4);
+        let mut rx = DeadlineReceiver::new(rx);
+
+        tx.send(1usize).await.unwrap();
+        let deadline = Instant::now() - Duration::from_millis(1);
+        assert_eq!(Ok(Some(1)), rx.recv_until(deadline).await);
+    }
+
+    #[tokio::test]
+    async fn elapsed() {
+        let (_tx, rx) = mpsc::channel::<usize>(4);
+        let mut rx = DeadlineReceiver::new(rx);
+
+        let deadline = Instant::now() + Duration::from_millis(20);
+        assert_eq!(Err(DeadlineElapsed), rx.recv_until(deadline).await);
+        assert!(Instant::now() >= deadline);
+    }
+
+    #[tokio::test]
+    async fn closed() {
+        let (tx, rx) = mpsc::channel::<usize>(4);
+        let mut rx = DeadlineReceiver::new(rx);
+        drop(tx);
+
+        assert_eq!(Ok(None), rx.recv_timeout(Duration::from_secs(5)).await);
+    }
+
+    #[tokio::test]
+    async fn reuses_timer() {
+        let (mut tx, rx) = mpsc::channel(4);
+        let mut rx = DeadlineReceiver::new(rx);
+
+        let deadline = Instant::now() + Duration::from_millis(20);
+        assert_eq!(Err(DeadlineElapsed), rx.recv_until(deadline).await);
+        let timer = rx.timer.as_ref().unwrap() as *const _;
+
+        let send = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            tx.send(2usize).await.unwrap();
+            tx
+        });
+
+        assert_eq!(Ok(Some(2)), rx.recv_timeout(Duration::from_secs(5)).await);
+        assert_eq!(timer, rx.timer.as_ref().unwrap() as *const _);
+        drop(send.await.unwrap());
+    }
+}
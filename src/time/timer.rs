@@ -0,0 +1,99 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{self, Poll},
+    time::Instant,
+};
+
+use crate::Context;
+
+/// A resettable timer, which completes at a deadline.
+///
+/// Backed by the async-io timer with the `time-async-io` feature, which works with any executor.
+/// Otherwise, backed by the tokio timer, which requires a tokio runtime.
+pub(crate) struct Timer {
+    deadline: Instant,
+    sleep: Sleep,
+}
+
+#[cfg(feature = "time-async-io")]
+type Sleep = async_io::Timer;
+
+#[cfg(not(feature = "time-async-io"))]
+type Sleep = Pin<Box<tokio::time::Sleep>>;
+
+impl Timer {
+    pub fn new(deadline: Instant) -> Self {
+        Self {
+            deadline,
+            sleep: sleep_until(deadline),
+        }
+    }
+
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    pub fn reset(&mut self, deadline: Instant) {
+        self.deadline = deadline;
+        reset(&mut self.sleep, deadline);
+    }
+
+    /// Returns Ready if the deadline has passed.  Otherwise, the task is woken at the deadline.
+    pub fn poll_elapsed(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        if Instant::now() >= self.deadline {
+            return Poll::Ready(());
+        }
+
+        // try_recv polls without a waker, and shouldn't register with the runtime
+        if let Some(waker) = cx.waker() {
+            let mut cx = task::Context::from_waker(waker);
+            if Pin::new(&mut self.sleep).poll(&mut cx).is_ready() {
+                return Poll::Ready(());
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+#[cfg(feature = "time-async-io")]
+fn sleep_until(deadline: Instant) -> Sleep {
+    async_io::Timer::at(deadline)
+}
+
+#[cfg(feature = "time-async-io")]
+fn reset(sleep: &mut Sleep, deadline: Instant) {
+    sleep.set_at(deadline);
+}
+
+#[cfg(not(feature = "time-async-io"))]
+fn sleep_until(deadline: Instant) -> Sleep {
+    Box::pin(tokio::time::sleep_until(deadline.into()))
+}
+
+#[cfg(not(feature = "time-async-io"))]
+fn reset(sleep: &mut Sleep, deadline: Instant) {
+    sleep.as_mut().reset(deadline.into());
+}
+
+#[cfg(all(test, feature = "time-async-io", feature = "blocking"))]
+mod tests {
+    use std::{
+        future::poll_fn,
+        time::{Duration, Instant},
+    };
+
+    use super::Timer;
+
+    #[test]
+    fn async_io_without_runtime() {
+        let start = Instant::now();
+        let mut timer = Timer::new(start + Duration::from_millis(50));
+        timer.reset(start + Duration::from_millis(10));
+
+        pollster::block_on(poll_fn(|cx| timer.poll_elapsed(&mut cx.into())));
+        assert!(start.elapsed() >= Duration::from_millis(10));
+        assert!(timer.deadline() < start + Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,166 @@
+//! Channel lifecycle instrumentation.  With the `tracing` feature, each channel owns a span,
+//! and operations are recorded as events within that span.  Without the feature, the tracer is zero-sized and all calls are no-ops.
+
+#[derive(Clone)]
+pub(crate) struct Tracer {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+#[cfg(feature = "tracing")]
+impl Tracer {
+    pub fn new(kind: &'static str, name: Option<&str>, capacity: Option<usize>) -> Self {
+        let span = tracing::debug_span!(
+            "postage",
+            kind,
+            name = name.unwrap_or(""),
+            capacity = capacity.unwrap_or(0)
+        );
+
+        tracing::debug!(parent: &span, "channel created");
+
+        Self { span }
+    }
+
+    pub fn send(&self) {
+        tracing::trace!(parent: &self.span, "send");
+    }
+
+    pub fn recv(&self) {
+        tracing::trace!(parent: &self.span, "recv");
+    }
+
+    pub fn reject(&self) {
+        tracing::debug!(parent: &self.span, "send rejected");
+    }
+
+    pub fn full(&self) {
+        tracing::trace!(parent: &self.span, "channel full");
+    }
+
+    pub fn lag(&self) {
+        tracing::debug!(parent: &self.span, "send blocked by a lagging receiver");
+    }
+
+    pub fn skipped(&self, count: usize) {
+        tracing::debug!(parent: &self.span, count, "lagging receiver skipped messages");
+    }
+
+    pub fn disconnected(&self) {
+        tracing::debug!(parent: &self.span, "lagging receiver disconnected");
+    }
+
+    pub fn expired(&self) {
+        tracing::debug!(parent: &self.span, "message expired");
+    }
+
+    pub fn senders_closed(&self) {
+        tracing::debug!(parent: &self.span, "all senders closed");
+    }
+
+    pub fn receivers_closed(&self) {
+        tracing::debug!(parent: &self.span, "all receivers closed");
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+#[allow(dead_code)]
+impl Tracer {
+    #[inline]
+    pub fn new(_kind: &'static str, _name: Option<&str>, _capacity: Option<usize>) -> Self {
+        Self {}
+    }
+
+    #[inline]
+    pub fn send(&self) {}
+
+    #[inline]
+    pub fn recv(&self) {}
+
+    #[inline]
+    pub fn reject(&self) {}
+
+    #[inline]
+    pub fn full(&self) {}
+
+    #[inline]
+    pub fn lag(&self) {}
+
+    #[inline]
+    pub fn skipped(&self, _count: usize) {}
+
+    #[inline]
+    pub fn disconnected(&self) {}
+
+    #[inline]
+    pub fn expired(&self) {}
+
+    #[inline]
+    pub fn senders_closed(&self) {}
+
+    #[inline]
+    pub fn receivers_closed(&self) {}
+}
+
+impl std::fmt::Debug for Tracer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tracer").finish()
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use tracing::{span, Event, Metadata, Subscriber};
+
+    use crate::{mpsc, sink::Sink, stream::Stream};
+
+    #[derive(Clone, Default)]
+    struct CountingSubscriber {
+        spans: Arc<AtomicUsize>,
+        events: Arc<AtomicUsize>,
+    }
+
+    impl Subscriber for CountingSubscriber {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _span: &span::Attributes<'_>) -> span::Id {
+            let id = 1 + self.spans.fetch_add(1, Ordering::SeqCst);
+            span::Id::from_u64(id as u64)
+        }
+
+        fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+        fn event(&self, _event: &Event<'_>) {
+            self.events.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn enter(&self, _span: &span::Id) {}
+
+        fn exit(&self, _span: &span::Id) {}
+    }
+
+    #[test]
+    fn channel_events() {
+        let subscriber = CountingSubscriber::default();
+
+        tracing::subscriber::with_default(subscriber.clone(), || {
+            let (mut tx, mut rx) = mpsc::Builder::new(4).name("events").build();
+            tx.try_send(1usize).unwrap();
+            rx.try_recv().unwrap();
+            drop(tx);
+        });
+
+        assert_eq!(1, subscriber.spans.load(Ordering::SeqCst));
+        // created, send, recv, senders closed, receivers closed
+        assert_eq!(5, subscriber.events.load(Ordering::SeqCst));
+    }
+}
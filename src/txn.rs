@@ -0,0 +1,196 @@
+//! Sends messages to several channels as one transaction.
+//!
+//! [send_all](./fn.send_all.html) reserves a slot on every target channel before committing any of the sends,
+//! so a full channel never leaves the transaction partially sent.  Reservations are made with the mpsc
+//! [permit API](../mpsc/struct.Sender.html#method.reserve).
+//!
+//! ```rust
+//! use postage::{mpsc, prelude::*, txn};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let (mut orders, mut orders_rx) = mpsc::channel(4);
+//!     let (mut audit, mut audit_rx) = mpsc::channel(4);
+//!
+//!     txn::send_all([(&mut orders, "order 1"), (&mut audit, "placed order 1")])
+//!         .await
+//!         .unwrap();
+//!
+//!     assert_eq!(Some("order 1"), orders_rx.recv().await);
+//!     assert_eq!(Some("placed order 1"), audit_rx.recv().await);
+//! }
+//! ```
+
+use std::{future::poll_fn, task::Poll};
+
+use crate::{
+    mpsc::{SendPermit, Sender},
+    sink::{SendError, TrySendError},
+    Context,
+};
+
+/// Sends each message to its paired sender, once every sender has a free slot.
+///
+/// If any channel is full, the slots reserved on the other channels are released while the transaction waits,
+/// so concurrent transactions cannot deadlock on each other's reservations.
+/// If any channel is closed, no message is sent, and the messages are returned in the error, in the order they were provided.
+pub async fn send_all<'s, T, I>(targets: I) -> Result<(), SendError<Vec<T>>>
+where
+    T: 's,
+    I: IntoIterator<Item = (&'s mut Sender<T>, T)>,
+{
+    let (senders, values) = split(targets);
+    let mut values = Some(values);
+
+    poll_fn(|cx| {
+        let result = match reserve_all(&senders, &mut cx.into()) {
+            Poll::Ready(result) => result,
+            Poll::Pending => return Poll::Pending,
+        };
+
+        let values = values.take().expect("polled after completion");
+        Poll::Ready(match result {
+            Ok(permits) => {
+                commit(permits, values);
+                Ok(())
+            }
+            Err(_) => Err(SendError(values)),
+        })
+    })
+    .await
+}
+
+/// Sends each message to its paired sender, if every sender has a free slot.  Otherwise, no message is sent.
+pub fn try_send_all<'s, T, I>(targets: I) -> Result<(), TrySendError<Vec<T>>>
+where
+    T: 's,
+    I: IntoIterator<Item = (&'s mut Sender<T>, T)>,
+{
+    let (senders, values) = split(targets);
+
+    match reserve_all(&senders, &mut Context::empty()) {
+        Poll::Ready(Ok(permits)) => {
+            commit(permits, values);
+            Ok(())
+        }
+        Poll::Ready(Err(_)) => Err(TrySendError::Rejected(values)),
+        Poll::Pending => Err(TrySendError::Pending(values)),
+    }
+}
+
+fn split<'s, T, I>(targets: I) -> (Vec<&'s Sender<T>>, Vec<T>)
+where
+    T: 's,
+    I: IntoIterator<Item = (&'s mut Sender<T>, T)>,
+{
+    targets
+        .into_iter()
+        .map(|(sender, value)| (&*sender, value))
+        .unzip()
+}
+
+/// Reserves a slot on every sender.  If any reservation fails, the permits which were acquired are dropped.
+fn reserve_all<'s, T>(
+    senders: &[&'s Sender<T>],
+    cx: &mut Context<'_>,
+) -> Poll<Result<Vec<SendPermit<'s, T>>, SendError<()>>> {
+    let mut permits = Vec::with_capacity(senders.len());
+    for sender in senders {
+        match sender.poll_reserve(cx) {
+            Poll::Ready(Ok(permit)) => permits.push(permit),
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+            Poll::Pending => return Poll::Pending,
+        }
+    }
+
+    Poll::Ready(Ok(permits))
+}
+
+fn commit<T>(permits: Vec<SendPermit<'_, T>>, values: Vec<T>) {
+    for (permit, value) in permits.into_iter().zip(values) {
+        permit.send(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::Future, task::Context};
+
+    use futures_test::task::new_count_waker;
+
+    use super::{send_all, try_send_all};
+    use crate::{
+        mpsc,
+        sink::{SendError, Sink, TrySendError},
+        stream::{Stream, TryRecvError},
+    };
+
+    #[test]
+    fn sends_to_every_channel() {
+        let (mut a, mut a_rx) = mpsc::channel(1);
+        let (mut b, mut b_rx) = mpsc::channel(1);
+
+        try_send_all([(&mut a, 1usize), (&mut b, 2)]).unwrap();
+        assert_eq!(Ok(1), a_rx.try_recv());
+        assert_eq!(Ok(2), b_rx.try_recv());
+    }
+
+    #[test]
+    fn full_channel_sends_nothing() {
+        let (mut a, mut a_rx) = mpsc::channel(1);
+        let (mut b, mut b_rx) = mpsc::channel(1);
+        b.try_send(0usize).unwrap();
+
+        assert_eq!(
+            Err(TrySendError::Pending(vec![1, 2])),
+            try_send_all([(&mut a, 1), (&mut b, 2)])
+        );
+
+        // the reservation on the first channel was released
+        a.try_send(3).unwrap();
+        assert_eq!(Ok(3), a_rx.try_recv());
+        assert_eq!(Ok(0), b_rx.try_recv());
+        assert_eq!(Err(TryRecvError::Pending), a_rx.try_recv());
+    }
+
+    #[test]
+    fn closed_channel_sends_nothing() {
+        let (mut a, mut a_rx) = mpsc::channel(1);
+        let (mut b, b_rx) = mpsc::channel(1);
+        drop(b_rx);
+
+        assert_eq!(
+            Err(TrySendError::Rejected(vec![1usize, 2])),
+            try_send_all([(&mut a, 1), (&mut b, 2)])
+        );
+        assert_eq!(Err(TryRecvError::Pending), a_rx.try_recv());
+    }
+
+    #[test]
+    fn waits_for_capacity() {
+        let (mut a, mut a_rx) = mpsc::channel(1);
+        let (mut b, mut b_rx) = mpsc::channel(1);
+        b.try_send(0usize).unwrap();
+
+        let (waker, count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut send = Box::pin(send_all([(&mut a, 1), (&mut b, 2)]));
+        assert!(send.as_mut().poll(&mut cx).is_pending());
+
+        assert_eq!(Ok(0), b_rx.try_recv());
+        assert_eq!(1, count.get());
+        assert_eq!(std::task::Poll::Ready(Ok(())), send.as_mut().poll(&mut cx));
+
+        assert_eq!(Ok(1), a_rx.try_recv());
+        assert_eq!(Ok(2), b_rx.try_recv());
+    }
+
+    #[tokio::test]
+    async fn send_all_closed() {
+        let (mut a, a_rx) = mpsc::channel(1);
+        drop(a_rx);
+
+        let result = send_all(vec![(&mut a, 1usize)]).await;
+        assert_eq!(Err(SendError(vec![1])), result);
+    }
+}
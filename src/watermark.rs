@@ -0,0 +1,144 @@
+//! High and low watermarks for bounded channels.
+//!
+//! Watermarks can be configured on `mpsc` and `dispatch` channels with their `Builder`.
+//! The callback is invoked with [Watermark::High](./enum.Watermark.html#variant.High) when the buffer depth reaches the high threshold,
+//! and with [Watermark::Low](./enum.Watermark.html#variant.Low) when it drains back to the low threshold.
+//! Producers can use these signals to shed load before the channel applies backpressure.
+//!
+//! ```rust
+//! use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
+//! use postage::{mpsc, sink::Sink, stream::Stream, watermark::Watermark};
+//!
+//! let congested = Arc::new(AtomicBool::new(false));
+//! let flag = congested.clone();
+//!
+//! let (mut tx, mut rx) = mpsc::Builder::new(4)
+//!     .watermarks(3, 1, move |mark| flag.store(mark == Watermark::High, Ordering::Relaxed))
+//!     .build();
+//!
+//! for i in 0..3usize {
+//!     tx.try_send(i).ok();
+//! }
+//! assert!(congested.load(Ordering::Relaxed));
+//!
+//! rx.try_recv().ok();
+//! rx.try_recv().ok();
+//! assert!(!congested.load(Ordering::Relaxed));
+//! ```
+
+use std::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// A watermark which was crossed by the channel buffer depth.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Watermark {
+    /// The buffer depth rose to the high threshold
+    High,
+    /// The buffer depth fell to the low threshold, after reaching the high threshold
+    Low,
+}
+
+/// Tracks whether the channel is above the high watermark, and fires the callback on each transition.
+pub(crate) struct Watermarks {
+    high: usize,
+    low: usize,
+    raised: AtomicBool,
+    callback: Box<dyn Fn(Watermark) + Send + Sync>,
+}
+
+impl Watermarks {
+    pub fn new<F>(high: usize, low: usize, callback: F) -> Self
+    where
+        F: Fn(Watermark) + Send + Sync + 'static,
+    {
+        assert!(
+            low < high,
+            "the low watermark ({}) must be less than the high watermark ({})",
+            low,
+            high
+        );
+
+        Self {
+            high,
+            low,
+            raised: AtomicBool::new(false),
+            callback: Box::new(callback),
+        }
+    }
+
+    /// Called after a message is added to the buffer
+    pub fn on_send(&self, depth: usize) {
+        if depth >= self.high
+            && self
+                .raised
+                .compare_exchange(false, true, Ordering::AcqRel, Ordering::Relaxed)
+                .is_ok()
+        {
+            (self.callback)(Watermark::High);
+        }
+    }
+
+    /// Called after a message is removed from the buffer
+    pub fn on_recv(&self, depth: usize) {
+        if depth <= self.low
+            && self
+                .raised
+                .compare_exchange(true, false, Ordering::AcqRel, Ordering::Relaxed)
+                .is_ok()
+        {
+            (self.callback)(Watermark::Low);
+        }
+    }
+}
+
+impl fmt::Debug for Watermarks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Watermarks")
+            .field("high", &self.high)
+            .field("low", &self.low)
+            .field("raised", &self.raised.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use parking_lot::Mutex;
+
+    use super::{Watermark, Watermarks};
+
+    #[test]
+    fn hysteresis() {
+        let marks = Arc::new(Mutex::new(Vec::new()));
+        let log = marks.clone();
+        let watermarks = Watermarks::new(3, 1, move |mark| log.lock().push(mark));
+
+        watermarks.on_send(2);
+        watermarks.on_send(3);
+        watermarks.on_send(4);
+        watermarks.on_recv(3);
+        watermarks.on_send(3);
+        assert_eq!(vec![Watermark::High], *marks.lock());
+
+        watermarks.on_recv(2);
+        watermarks.on_recv(1);
+        watermarks.on_recv(0);
+        assert_eq!(vec![Watermark::High, Watermark::Low], *marks.lock());
+
+        watermarks.on_send(3);
+        assert_eq!(
+            vec![Watermark::High, Watermark::Low, Watermark::High],
+            *marks.lock()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn low_above_high() {
+        Watermarks::new(1, 2, |_| {});
+    }
+}
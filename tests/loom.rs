@@ -0,0 +1,144 @@
+//! Model-checked scenarios for the sync layer.
+//!
+//! Run with:
+//! ```sh
+//! RUSTFLAGS="--cfg postage_loom" cargo test --test loom --release
+//! ```
+#![cfg(postage_loom)]
+
+use loom::{future::block_on, thread};
+use postage::{broadcast, mpsc, oneshot, prelude::*};
+
+/// Explores interleavings with a bounded number of preemptions, which finds most ordering bugs
+/// while keeping the multi-message scenarios tractable.
+fn model<F>(f: F)
+where
+    F: Fn() + Sync + Send + 'static,
+{
+    let mut builder = loom::model::Builder::new();
+    builder.preemption_bound = Some(3);
+    builder.check(f);
+}
+
+#[test]
+fn mpsc_backpressure() {
+    model(|| {
+        let (mut tx, mut rx) = mpsc::channel(1);
+
+        let sender = thread::spawn(move || {
+            block_on(tx.send(1usize)).unwrap();
+            block_on(tx.send(2usize)).unwrap();
+        });
+
+        assert_eq!(Some(1), block_on(rx.recv()));
+        assert_eq!(Some(2), block_on(rx.recv()));
+        assert_eq!(None, block_on(rx.recv()));
+
+        sender.join().unwrap();
+    });
+}
+
+#[test]
+fn mpsc_concurrent_senders_close() {
+    model(|| {
+        let (tx, mut rx) = mpsc::channel(2);
+        let mut tx2 = tx.clone();
+
+        let sender = thread::spawn(move || {
+            block_on(tx2.send(1usize)).unwrap();
+        });
+        drop(tx);
+
+        assert_eq!(Some(1), block_on(rx.recv()));
+        assert_eq!(None, block_on(rx.recv()));
+
+        sender.join().unwrap();
+    });
+}
+
+#[test]
+fn mpsc_receiver_close() {
+    model(|| {
+        let (mut tx, rx) = mpsc::channel(1);
+
+        let receiver = thread::spawn(move || drop(rx));
+
+        // the send may be accepted before the receiver drops, but must not hang
+        let _ = block_on(tx.send(1usize));
+        let _ = block_on(tx.send(2usize));
+
+        receiver.join().unwrap();
+    });
+}
+
+#[test]
+fn mpsc_batch_and_single_sends() {
+    model(|| {
+        let (mut tx, mut rx) = mpsc::channel(3);
+        let mut tx2 = tx.clone();
+
+        let sender = thread::spawn(move || {
+            block_on(tx2.send_vectored(vec![1usize, 2])).unwrap();
+        });
+
+        block_on(tx.send(3usize)).unwrap();
+        sender.join().unwrap();
+        drop(tx);
+
+        let mut received = Vec::new();
+        while let Some(value) = block_on(rx.recv()) {
+            received.push(value);
+        }
+
+        // the single send is buffered before or after the batch, never between its messages
+        assert!(
+            received == [1, 2, 3] || received == [3, 1, 2],
+            "{:?}",
+            received
+        );
+    });
+}
+
+#[test]
+fn broadcast_lagging_receiver() {
+    model(|| {
+        let (mut tx, mut rx) = broadcast::channel(1);
+
+        let sender = thread::spawn(move || {
+            block_on(tx.send(1usize)).unwrap();
+            block_on(tx.send(2usize)).unwrap();
+        });
+
+        assert_eq!(Some(1), block_on(rx.recv()));
+        assert_eq!(Some(2), block_on(rx.recv()));
+        assert_eq!(None, block_on(rx.recv()));
+
+        sender.join().unwrap();
+    });
+}
+
+#[test]
+fn oneshot_send() {
+    model(|| {
+        let (mut tx, mut rx) = oneshot::channel();
+
+        let sender = thread::spawn(move || {
+            block_on(tx.send(1usize)).unwrap();
+        });
+
+        assert_eq!(Some(1), block_on(rx.recv()));
+        sender.join().unwrap();
+    });
+}
+
+#[test]
+fn oneshot_sender_drop() {
+    model(|| {
+        let (tx, mut rx) = oneshot::channel::<usize>();
+
+        let sender = thread::spawn(move || drop(tx));
+
+        assert_eq!(None, block_on(rx.recv()));
+        sender.join().unwrap();
+    });
+}